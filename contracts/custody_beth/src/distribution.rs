@@ -7,10 +7,16 @@ use cosmwasm_std::{
 use crate::contract::{CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION};
 use crate::error::ContractError;
 use crate::external::handle::{RewardContractExecuteMsg, RewardContractQueryMsg};
-use crate::state::{read_config, BETHAccruedRewardsResponse, Config};
+use crate::state::{
+    read_config, read_reward_swap_route, store_reward_swap_route, BETHAccruedRewardsResponse,
+    Config,
+};
 
+use moneymarket::custody::RewardSwapRouteResponse;
 use moneymarket::querier::{deduct_tax, query_all_balances, query_balance};
-use terra_cosmwasm::{create_swap_msg, TerraMsgWrapper};
+use moneymarket::swap::SwapRoute;
+use terra_cosmwasm::TerraMsgWrapper;
+use terraswap::asset::AssetInfo;
 
 // REWARD_THRESHOLD
 // This value is used as the minimum reward claim amount
@@ -98,14 +104,23 @@ pub fn swap_to_stable_denom(
     env: Env,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    let reward_swap_route: SwapRoute = read_reward_swap_route(deps.storage)?;
 
     let contract_addr = env.contract.address;
     let balances: Vec<Coin> = query_all_balances(deps.as_ref(), contract_addr)?;
     let mut messages: Vec<SubMsg<TerraMsgWrapper>> = balances
         .iter()
         .filter(|x| x.denom != config.stable_denom)
-        .map(|coin: &Coin| SubMsg::new(create_swap_msg(coin.clone(), config.stable_denom.clone())))
-        .collect();
+        .map(|coin: &Coin| {
+            Ok(SubMsg::new(reward_swap_route.swap_native_msg(
+                coin.clone(),
+                AssetInfo::NativeToken {
+                    denom: config.stable_denom.clone(),
+                },
+                None,
+            )?))
+        })
+        .collect::<StdResult<Vec<SubMsg<TerraMsgWrapper>>>>()?;
 
     if let Some(last) = messages.last_mut() {
         last.id = SWAP_TO_STABLE_OPERATION;
@@ -115,6 +130,34 @@ pub fn swap_to_stable_denom(
     Ok(Response::new().add_submessages(messages))
 }
 
+pub fn update_reward_swap_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_swap_route: SwapRoute,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let reward_swap_route = reward_swap_route.validate(deps.api)?;
+    store_reward_swap_route(deps.storage, &reward_swap_route)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_reward_swap_route"),
+        attr(
+            "reward_swap_router",
+            reward_swap_route.router().unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn query_reward_swap_route(deps: Deps) -> StdResult<RewardSwapRouteResponse> {
+    Ok(RewardSwapRouteResponse {
+        reward_swap_route: read_reward_swap_route(deps.storage)?,
+    })
+}
+
 pub(crate) fn get_accrued_rewards(
     deps: Deps,
     reward_contract_addr: Addr,
@@ -18,6 +18,9 @@ pub enum ContractError {
     #[error("Lock amount cannot excceed the user's spendable amount: {0}")]
     LockAmountExceedsSpendable(u128),
 
+    #[error("Migration amount cannot exceed locked amount: {0}")]
+    MigrationAmountExceedsLocked(u128),
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
@@ -3,6 +3,7 @@ pub mod contract;
 pub mod distribution;
 pub mod error;
 pub mod state;
+pub mod venue;
 
 mod external;
 
@@ -5,6 +5,8 @@ use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 use moneymarket::custody::{BAssetInfo, BorrowerResponse};
+use moneymarket::pagination::{calc_limit, calc_range_start};
+use moneymarket::swap::SwapRoute;
 
 //BETHAccruedRewardsResponse the struct that shows the result of accrued_rewards query
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
@@ -13,6 +15,8 @@ pub struct BETHAccruedRewardsResponse {
 }
 
 const KEY_CONFIG: &[u8] = b"config";
+const KEY_LIQUIDATION_ADAPTER: &[u8] = b"liquidation_adapter";
+const KEY_REWARD_SWAP_ROUTE: &[u8] = b"reward_swap_route";
 const PREFIX_BORROWER: &[u8] = b"borrower";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,6 +45,36 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
+/// Store the external venue adapter liquidations are routed to,
+/// or route them to the bid queue with `None`
+pub fn store_liquidation_adapter(
+    storage: &mut dyn Storage,
+    adapter: Option<CanonicalAddr>,
+) -> StdResult<()> {
+    match adapter {
+        Some(adapter) => Singleton::new(storage, KEY_LIQUIDATION_ADAPTER).save(&adapter),
+        None => {
+            Singleton::<CanonicalAddr>::new(storage, KEY_LIQUIDATION_ADAPTER).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_liquidation_adapter(storage: &dyn Storage) -> StdResult<Option<CanonicalAddr>> {
+    ReadonlySingleton::new(storage, KEY_LIQUIDATION_ADAPTER).may_load()
+}
+
+pub fn store_reward_swap_route(storage: &mut dyn Storage, route: &SwapRoute) -> StdResult<()> {
+    Singleton::new(storage, KEY_REWARD_SWAP_ROUTE).save(route)
+}
+
+/// Rewards are swapped on the market module until a route is stored
+pub fn read_reward_swap_route(storage: &dyn Storage) -> StdResult<SwapRoute> {
+    ReadonlySingleton::new(storage, KEY_REWARD_SWAP_ROUTE)
+        .may_load()
+        .map(Option::unwrap_or_default)
+}
+
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
@@ -69,9 +103,6 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
     }
 }
 
-// settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
 pub fn read_borrowers(
     deps: Deps,
     start_after: Option<CanonicalAddr>,
@@ -80,7 +111,7 @@ pub fn read_borrowers(
     let position_bucket: ReadonlyBucket<BorrowerInfo> =
         ReadonlyBucket::new(deps.storage, PREFIX_BORROWER);
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit(limit);
     let start = calc_range_start(start_after);
 
     position_bucket
@@ -97,12 +128,3 @@ pub fn read_borrowers(
         })
         .collect()
 }
-
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
-    start_after.map(|addr| {
-        let mut v = addr.as_slice().to_vec();
-        v.push(1);
-        v
-    })
-}
@@ -8,6 +8,7 @@ use cosmwasm_std::{
 };
 use cosmwasm_storage::to_length_prefixed;
 use cw20::TokenInfoResponse;
+use moneymarket::overseer::{CustodyMigrationResponse, QueryMsg as OverseerQueryMsg};
 use std::collections::HashMap;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -33,6 +34,7 @@ pub struct WasmMockQuerier {
     reward_balance: Uint128,
     other_balance: Uint128,
     tax_querier: TaxQuerier,
+    custody_migration: Option<CustodyMigrationResponse>,
 }
 
 #[derive(Clone, Default)]
@@ -200,12 +202,17 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: _,
                 msg,
-            }) => match from_binary(msg).unwrap() {
-                RewardContractQueryMsg::AccruedRewards { address: _ } => SystemResult::Ok(
-                    ContractResult::from(to_binary(&BETHAccruedRewardsResponse {
-                        rewards: self.accrued_rewards.rewards,
-                    })),
-                ),
+            }) => match from_binary(msg) {
+                Ok(OverseerQueryMsg::CustodyMigration {
+                    collateral_token: _,
+                }) => SystemResult::Ok(ContractResult::from(to_binary(&self.custody_migration))),
+                _ => match from_binary(msg).unwrap() {
+                    RewardContractQueryMsg::AccruedRewards { address: _ } => SystemResult::Ok(
+                        ContractResult::from(to_binary(&BETHAccruedRewardsResponse {
+                            rewards: self.accrued_rewards.rewards,
+                        })),
+                    ),
+                },
             },
             QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
                 if address == "reward" && denom == "uusd" {
@@ -240,6 +247,7 @@ impl WasmMockQuerier {
             accrued_rewards: BETHAccruedRewardsResponse::default(),
             reward_balance: Uint128::zero(),
             other_balance: Uint128::zero(),
+            custody_migration: None,
         }
     }
 
@@ -264,4 +272,8 @@ impl WasmMockQuerier {
     pub fn set_other_balances(&mut self, balance: Uint128) {
         self.other_balance = balance
     }
+
+    pub fn with_custody_migration(&mut self, custody_migration: Option<CustodyMigrationResponse>) {
+        self.custody_migration = custody_migration
+    }
 }
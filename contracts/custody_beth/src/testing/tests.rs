@@ -1,11 +1,12 @@
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Api, Attribute, BankMsg, Coin, ContractResult, CosmosMsg,
-    Decimal, Reply, Response, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    Decimal, Event, Reply, Response, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
 
 use crate::contract::{
-    execute, instantiate, query, reply, CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION,
+    execute, instantiate, query, reply, CLAIM_REWARDS_OPERATION, LIQUIDATION_OPERATION,
+    SWAP_TO_STABLE_OPERATION,
 };
 use crate::error::ContractError;
 use crate::external::handle::RewardContractExecuteMsg;
@@ -15,10 +16,19 @@ use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::custody::{
-    BAssetInfo, BorrowerResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BAssetInfo, BorrowerResponse, ConfigResponse, CustodyCapability, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, InterfaceVersionResponse, QueryMsg, RewardSwapRouteResponse,
+    CUSTODY_INTERFACE_VERSION,
 };
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::liquidation_queue::Cw20HookMsg as LiquidationQueueCw20HookMsg;
+use moneymarket::liquidation_venue::{
+    Cw20HookMsg as VenueCw20HookMsg, LiquidationResult, LiquidationVenue, LiquidationVenueResponse,
+};
+use moneymarket::overseer::CustodyMigrationResponse;
+use moneymarket::swap::{AstroportRouterMsg, AstroportSwapOperation, SwapRoute};
 use terra_cosmwasm::create_swap_msg;
+use terraswap::asset::AssetInfo;
 
 #[test]
 fn proper_initialization() {
@@ -53,6 +63,13 @@ fn proper_initialization() {
     assert_eq!("reward".to_string(), config_res.reward_contract);
     assert_eq!("liquidation".to_string(), config_res.liquidation_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::InterfaceVersion {}).unwrap();
+    let interface_res: InterfaceVersionResponse = from_binary(&query_res).unwrap();
+    assert_eq!(CUSTODY_INTERFACE_VERSION, interface_res.version);
+    assert!(interface_res
+        .capabilities
+        .contains(&CustodyCapability::LiquidateCollateral));
 }
 
 #[test]
@@ -783,6 +800,77 @@ fn swap_to_stable_denom() {
             ),
         ]
     );
+
+    let msg = ExecuteMsg::UpdateRewardSwapRoute {
+        reward_swap_route: SwapRoute::Astroport {
+            router: "router".to_string(),
+        },
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_reward_swap_route"),
+            attr("reward_swap_router", "router"),
+        ]
+    );
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::RewardSwapRoute {}).unwrap();
+    let route_res: RewardSwapRouteResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        route_res.reward_swap_route,
+        SwapRoute::Astroport {
+            router: "router".to_string(),
+        }
+    );
+
+    // the rewards are swapped on the router
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let router_swap = |denom: &str, amount: u128| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "router".to_string(),
+            msg: to_binary(&AstroportRouterMsg::ExecuteSwapOperations {
+                operations: vec![AstroportSwapOperation::AstroSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: denom.to_string(),
+                    },
+                    ask_asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                }],
+                minimum_receive: None,
+                to: None,
+            })
+            .unwrap(),
+            funds: vec![Coin {
+                denom: denom.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        })
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(router_swap("ukrw", 20000000000u128)),
+            SubMsg::reply_on_success(router_swap("usdr", 2000000u128), SWAP_TO_STABLE_OPERATION),
+        ]
+    );
 }
 
 #[test]
@@ -843,6 +931,7 @@ fn liquidate_collateral() {
         liquidator: "addr0001".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(100u64),
+        max_spread: None,
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -861,6 +950,7 @@ fn liquidate_collateral() {
         liquidator: "liquidator".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(10u64),
+        max_spread: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
@@ -873,6 +963,86 @@ fn liquidate_collateral() {
         ]
     );
 
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "beth".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "liquidation".to_string(),
+                    amount: Uint128::from(10u128),
+                    msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
+                        liquidator: "liquidator".to_string(),
+                        fee_address: Some("overseer".to_string()),
+                        repay_address: Some("market".to_string()),
+                        borrower: Some("addr0000".to_string()),
+                    })
+                    .unwrap()
+                })
+                .unwrap(),
+            }),
+            LIQUIDATION_OPERATION,
+        )]
+    );
+
+    // with a max spread the collateral is sold on the DEX
+    let msg = ExecuteMsg::LiquidateCollateral {
+        liquidator: "liquidator".to_string(),
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+        max_spread: Some(Decimal256::percent(5)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "beth".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "liquidation".to_string(),
+                    amount: Uint128::from(10u128),
+                    msg: to_binary(&LiquidationQueueCw20HookMsg::ExecuteBidWithSwap {
+                        liquidator: "liquidator".to_string(),
+                        fee_address: Some("overseer".to_string()),
+                        repay_address: Some("market".to_string()),
+                        max_spread: Decimal256::percent(5),
+                    })
+                    .unwrap()
+                })
+                .unwrap(),
+            }),
+            LIQUIDATION_OPERATION,
+        )]
+    );
+
+    // self-liquidations are sold to the zero premium bids
+    let msg = ExecuteMsg::SelfLiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(40u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg);
+    match res {
+        Err(ContractError::LiquidationAmountExceedsLocked(30)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SelfLiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
     assert_eq!(
         res.messages,
         vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -881,9 +1051,8 @@ fn liquidate_collateral() {
             msg: to_binary(&Cw20ExecuteMsg::Send {
                 contract: "liquidation".to_string(),
                 amount: Uint128::from(10u128),
-                msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
-                    liquidator: "liquidator".to_string(),
-                    fee_address: Some("overseer".to_string()),
+                msg: to_binary(&LiquidationQueueCw20HookMsg::ExecuteSelfLiquidation {
+                    borrower: "addr0000".to_string(),
                     repay_address: Some("market".to_string()),
                 })
                 .unwrap()
@@ -893,6 +1062,158 @@ fn liquidate_collateral() {
     );
 }
 
+#[test]
+fn liquidation_venue() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "beth".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("beth", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::LiquidationVenue {}).unwrap();
+    let venue_res: LiquidationVenueResponse = from_binary(&query_res).unwrap();
+    assert_eq!(venue_res.liquidation_venue, LiquidationVenue::BidQueue {});
+
+    let msg = ExecuteMsg::UpdateLiquidationVenue {
+        liquidation_venue: LiquidationVenue::Adapter {
+            contract_addr: "adapter".to_string(),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_liquidation_venue"),
+            attr("liquidation_venue", "adapter"),
+        ]
+    );
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::LiquidationVenue {}).unwrap();
+    let venue_res: LiquidationVenueResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        venue_res.liquidation_venue,
+        LiquidationVenue::Adapter {
+            contract_addr: "adapter".to_string(),
+        }
+    );
+
+    // liquidations are routed to the adapter
+    let msg = ExecuteMsg::LiquidateCollateral {
+        liquidator: "liquidator".to_string(),
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+        max_spread: Some(Decimal256::percent(5)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "beth".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "adapter".to_string(),
+                    amount: Uint128::from(10u128),
+                    msg: to_binary(&VenueCw20HookMsg::ExecuteLiquidation {
+                        liquidator: "liquidator".to_string(),
+                        borrower: "addr0000".to_string(),
+                        repay_address: "market".to_string(),
+                        fee_address: "overseer".to_string(),
+                        max_spread: Some(Decimal256::percent(5)),
+                    })
+                    .unwrap()
+                })
+                .unwrap(),
+            }),
+            LIQUIDATION_OPERATION
+        )]
+    );
+
+    // only the repay amounts reported by the adapter are counted
+    let reply_msg = Reply {
+        id: LIQUIDATION_OPERATION,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![
+                Event::new("wasm")
+                    .add_attribute("_contract_address", "adapter")
+                    .add_attribute("repay_amount", "7")
+                    .add_attribute("_contract_address", "other")
+                    .add_attribute("repay_amount", "100"),
+                Event::new("wasm")
+                    .add_attribute("_contract_address", "adapter")
+                    .add_attribute("repay_amount", "2"),
+            ],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidation_result"),
+            attr("repay_amount", "9"),
+        ]
+    );
+    let result: LiquidationResult = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(
+        result,
+        LiquidationResult {
+            venue: "adapter".to_string(),
+            repay_amount: Uint256::from(9u64),
+        }
+    );
+
+    // back to the bid queue
+    let msg = ExecuteMsg::UpdateLiquidationVenue {
+        liquidation_venue: LiquidationVenue::BidQueue {},
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_liquidation_venue"),
+            attr("liquidation_venue", "liquidation"),
+        ]
+    );
+}
+
 #[test]
 fn proper_distribute_rewards_with_no_rewards() {
     let mut deps = mock_dependencies(&[Coin {
@@ -966,3 +1287,175 @@ fn proper_distribute_rewards_with_no_rewards() {
         ),]
     );
 }
+
+#[test]
+fn migrate_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "beth".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+    });
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("beth", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::MigrateCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(60u64),
+        new_custody: "new_custody".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg);
+    match res {
+        Err(ContractError::MigrationAmountExceedsLocked(50)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::MigrateCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+        new_custody: "new_custody".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "beth".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "new_custody".to_string(),
+                amount: Uint128::from(50u128),
+                msg: to_binary(&Cw20HookMsg::ReceiveMigratedCollateral {
+                    borrower: "addr0000".to_string(),
+                })
+                .unwrap()
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0000".to_string(),
+            balance: Uint256::from(50u64),
+            spendable: Uint256::from(50u64),
+        }
+    );
+
+    // the replacing custody credits the migrated tokens as spendable
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "old_custody".to_string(),
+        amount: Uint128::from(50u128),
+        msg: to_binary(&Cw20HookMsg::ReceiveMigratedCollateral {
+            borrower: "addr0001".to_string(),
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_token", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // no custody migration in progress
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("beth", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_custody_migration(Some(CustodyMigrationResponse {
+            collateral_token: "beth".to_string(),
+            old_custody: "old_custody".to_string(),
+            new_custody: MOCK_CONTRACT_ADDR.to_string(),
+        }));
+
+    // any other holder of the token cannot credit migrated collateral
+    let other_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0002".to_string(),
+        amount: Uint128::from(50u128),
+        msg: to_binary(&Cw20HookMsg::ReceiveMigratedCollateral {
+            borrower: "addr0001".to_string(),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("beth", &[]), other_msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("beth", &[]), msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0001".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0001".to_string(),
+            balance: Uint256::from(50u64),
+            spendable: Uint256::from(50u64),
+        }
+    );
+}
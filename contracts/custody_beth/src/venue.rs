@@ -0,0 +1,201 @@
+use crate::contract::LIQUIDATION_OPERATION;
+use crate::error::ContractError;
+use crate::state::{read_config, read_liquidation_adapter, store_liquidation_adapter, Config};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, ContractResult, CosmosMsg, Deps, DepsMut, MessageInfo, Reply,
+    Response, StdResult, SubMsg, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::liquidation_queue::Cw20HookMsg as LiquidationQueueCw20HookMsg;
+use moneymarket::liquidation_venue::{
+    Cw20HookMsg as VenueCw20HookMsg, LiquidationResult, LiquidationVenue as LiquidationVenueMsg,
+    LiquidationVenueResponse, REPAY_AMOUNT_ATTRIBUTE,
+};
+use std::str::FromStr;
+use terra_cosmwasm::TerraMsgWrapper;
+
+/// Liquidation of the collateral of a borrower
+pub(crate) struct Liquidation {
+    pub liquidator: String,
+    pub borrower: String,
+    pub repay_address: String,
+    pub fee_address: String,
+    pub max_spread: Option<Decimal256>,
+}
+
+/// Contract the liquidated collateral is sent to, with the hook it expects
+pub(crate) trait LiquidationVenue {
+    fn contract(&self) -> &Addr;
+
+    fn hook_msg(&self, liquidation: Liquidation) -> StdResult<Binary>;
+
+    /// Sends the collateral to the venue, the reply reports the repay amount
+    fn liquidation_msg(
+        &self,
+        collateral_token: String,
+        amount: Uint256,
+        liquidation: Liquidation,
+    ) -> StdResult<SubMsg<TerraMsgWrapper>> {
+        Ok(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token,
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: self.contract().to_string(),
+                    amount: amount.into(),
+                    msg: self.hook_msg(liquidation)?,
+                })?,
+            }),
+            LIQUIDATION_OPERATION,
+        ))
+    }
+}
+
+/// Bids of the liquidation contract; with a max spread
+/// the collateral is sold on a DEX instead
+struct BidQueue {
+    contract: Addr,
+}
+
+impl LiquidationVenue for BidQueue {
+    fn contract(&self) -> &Addr {
+        &self.contract
+    }
+
+    fn hook_msg(&self, liquidation: Liquidation) -> StdResult<Binary> {
+        match liquidation.max_spread {
+            Some(max_spread) => to_binary(&LiquidationQueueCw20HookMsg::ExecuteBidWithSwap {
+                liquidator: liquidation.liquidator,
+                fee_address: Some(liquidation.fee_address),
+                repay_address: Some(liquidation.repay_address),
+                max_spread,
+            }),
+            None => to_binary(&LiquidationCw20HookMsg::ExecuteBid {
+                liquidator: liquidation.liquidator,
+                fee_address: Some(liquidation.fee_address),
+                repay_address: Some(liquidation.repay_address),
+                borrower: Some(liquidation.borrower),
+            }),
+        }
+    }
+}
+
+/// External AMM or orderbook adapter
+struct Adapter {
+    contract: Addr,
+}
+
+impl LiquidationVenue for Adapter {
+    fn contract(&self) -> &Addr {
+        &self.contract
+    }
+
+    fn hook_msg(&self, liquidation: Liquidation) -> StdResult<Binary> {
+        to_binary(&VenueCw20HookMsg::ExecuteLiquidation {
+            liquidator: liquidation.liquidator,
+            borrower: liquidation.borrower,
+            repay_address: liquidation.repay_address,
+            fee_address: liquidation.fee_address,
+            max_spread: liquidation.max_spread,
+        })
+    }
+}
+
+pub(crate) fn read_liquidation_venue(
+    deps: Deps,
+    config: &Config,
+) -> StdResult<Box<dyn LiquidationVenue>> {
+    Ok(match read_liquidation_adapter(deps.storage)? {
+        Some(adapter) => Box::new(Adapter {
+            contract: deps.api.addr_humanize(&adapter)?,
+        }),
+        None => Box::new(BidQueue {
+            contract: deps.api.addr_humanize(&config.liquidation_contract)?,
+        }),
+    })
+}
+
+pub fn update_liquidation_venue(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidation_venue: LiquidationVenueMsg,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let adapter = match &liquidation_venue {
+        LiquidationVenueMsg::BidQueue {} => None,
+        LiquidationVenueMsg::Adapter { contract_addr } => Some(
+            deps.api
+                .addr_canonicalize(deps.api.addr_validate(contract_addr)?.as_str())?,
+        ),
+    };
+    store_liquidation_adapter(deps.storage, adapter)?;
+
+    let venue = read_liquidation_venue(deps.as_ref(), &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_liquidation_venue"),
+        attr("liquidation_venue", venue.contract().to_string()),
+    ]))
+}
+
+/// Sums the repay amounts the venue reported in the liquidation events
+pub fn liquidation_reply(
+    deps: DepsMut,
+    msg: Reply,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let venue = read_liquidation_venue(deps.as_ref(), &config)?;
+
+    let events = match msg.result {
+        ContractResult::Ok(res) => res.events,
+        ContractResult::Err(_) => vec![],
+    };
+
+    // contract events either hold the attributes of a single contract,
+    // or of each contract after its contract address
+    let mut repay_amount = Uint256::zero();
+    for event in events
+        .iter()
+        .filter(|event| event.ty == "wasm" || event.ty == "from_contract")
+    {
+        let mut from_venue = false;
+        for attribute in event.attributes.iter() {
+            match attribute.key.as_str() {
+                "contract_address" | "_contract_address" => {
+                    from_venue = attribute.value == venue.contract().as_str()
+                }
+                REPAY_AMOUNT_ATTRIBUTE if from_venue => {
+                    repay_amount += Uint256::from_str(&attribute.value)?
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .set_data(to_binary(&LiquidationResult {
+            venue: venue.contract().to_string(),
+            repay_amount,
+        })?)
+        .add_attributes(vec![
+            attr("action", "liquidation_result"),
+            attr("repay_amount", repay_amount),
+        ]))
+}
+
+pub fn query_liquidation_venue(deps: Deps) -> StdResult<LiquidationVenueResponse> {
+    let liquidation_venue = match read_liquidation_adapter(deps.storage)? {
+        Some(adapter) => LiquidationVenueMsg::Adapter {
+            contract_addr: deps.api.addr_humanize(&adapter)?.to_string(),
+        },
+        None => LiquidationVenueMsg::BidQueue {},
+    };
+
+    Ok(LiquidationVenueResponse { liquidation_venue })
+}
@@ -3,15 +3,16 @@ use crate::state::{
     read_borrower_info, read_borrowers, read_config, remove_borrower_info, store_borrower_info,
     BorrowerInfo, Config,
 };
+use crate::venue::{read_liquidation_venue, Liquidation};
 
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response,
     StdResult, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
-use moneymarket::custody::{BorrowerResponse, BorrowersResponse};
-use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::custody::{BorrowerResponse, BorrowersResponse, Cw20HookMsg};
+use moneymarket::liquidation_queue::Cw20HookMsg as LiquidationQueueCw20HookMsg;
 use terra_cosmwasm::TerraMsgWrapper;
 
 /// Deposit new collateral
@@ -157,6 +158,61 @@ pub fn liquidate_collateral(
     liquidator: Addr,
     borrower: Addr,
     amount: Uint256,
+    max_spread: Option<Decimal256>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let borrowed_amt = borrower_info.balance - borrower_info.spendable;
+    if amount > borrowed_amt {
+        return Err(ContractError::LiquidationAmountExceedsLocked(
+            borrowed_amt.into(),
+        ));
+    }
+
+    borrower_info.balance = borrower_info.balance - amount;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    let venue = read_liquidation_venue(deps.as_ref(), &config)?;
+    let liquidation_msg = venue.liquidation_msg(
+        deps.api
+            .addr_humanize(&config.collateral_token)?
+            .to_string(),
+        amount,
+        Liquidation {
+            liquidator: liquidator.to_string(),
+            borrower: borrower.to_string(),
+            repay_address: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+            fee_address: deps
+                .api
+                .addr_humanize(&config.overseer_contract)?
+                .to_string(),
+            max_spread,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(liquidation_msg)
+        .add_attributes(vec![
+            attr("action", "liquidate_collateral"),
+            attr("liquidator", liquidator),
+            attr("borrower", borrower),
+            attr("amount", amount),
+        ]))
+}
+
+/// Sell locked collateral of the borrower to the zero premium bids, the
+/// proceeds are sent to the market to repay the loan
+/// Executor: overseer
+pub fn self_liquidate_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
@@ -188,13 +244,8 @@ pub fn liquidate_collateral(
                     .addr_humanize(&config.liquidation_contract)?
                     .to_string(),
                 amount: amount.into(),
-                msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
-                    liquidator: liquidator.to_string(),
-                    fee_address: Some(
-                        deps.api
-                            .addr_humanize(&config.overseer_contract)?
-                            .to_string(),
-                    ),
+                msg: to_binary(&LiquidationQueueCw20HookMsg::ExecuteSelfLiquidation {
+                    borrower: borrower.to_string(),
                     repay_address: Some(
                         deps.api.addr_humanize(&config.market_contract)?.to_string(),
                     ),
@@ -202,10 +253,63 @@ pub fn liquidate_collateral(
             })?,
         }))
         .add_attributes(vec![
-            attr("action", "liquidate_collateral"),
-            attr("liquidator", liquidator),
+            attr("action", "self_liquidate_collateral"),
+            attr("borrower", borrower),
+            attr("amount", amount),
+        ]))
+}
+
+/// Send locked collateral to the new custody contract
+/// and credit it to the borrower there
+/// Executor: overseer
+pub fn migrate_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+    new_custody: Addr,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let borrowed_amt = borrower_info.balance - borrower_info.spendable;
+    if amount > borrowed_amt {
+        return Err(ContractError::MigrationAmountExceedsLocked(
+            borrowed_amt.into(),
+        ));
+    }
+
+    borrower_info.balance = borrower_info.balance - amount;
+    if borrower_info.balance == Uint256::zero() {
+        remove_borrower_info(deps.storage, &borrower_raw);
+    } else {
+        store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.collateral_token)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: new_custody.to_string(),
+                amount: amount.into(),
+                msg: to_binary(&Cw20HookMsg::ReceiveMigratedCollateral {
+                    borrower: borrower.to_string(),
+                })?,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "migrate_collateral"),
             attr("borrower", borrower),
             attr("amount", amount),
+            attr("new_custody", new_custody),
         ]))
 }
 
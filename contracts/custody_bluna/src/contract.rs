@@ -6,20 +6,30 @@ use cosmwasm_std::{
 };
 
 use crate::collateral::{
-    deposit_collateral, liquidate_collateral, lock_collateral, query_borrower, query_borrowers,
-    unlock_collateral, withdraw_collateral,
+    deposit_collateral, liquidate_collateral, lock_collateral, migrate_collateral, query_borrower,
+    query_borrowers, self_liquidate_collateral, unlock_collateral, withdraw_collateral,
+};
+use crate::distribution::{
+    distribute_hook, distribute_rewards, query_reward_swap_route, swap_to_stable_denom,
+    update_reward_swap_route,
 };
-use crate::distribution::{distribute_hook, distribute_rewards, swap_to_stable_denom};
 use crate::error::ContractError;
 use crate::state::{read_config, store_config, Config};
+use crate::venue::{liquidation_reply, query_liquidation_venue, update_liquidation_venue};
 
 use cw20::Cw20ReceiveMsg;
 use moneymarket::common::optional_addr_validate;
-use moneymarket::custody::{ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use moneymarket::custody::{
+    ConfigResponse, CustodyCapability, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    InterfaceVersionResponse, QueryMsg, CUSTODY_INTERFACE_VERSION,
+};
+use moneymarket::overseer::OverseerQuerier;
+use moneymarket::querier::{store_fee_model, FeeModel};
 use terra_cosmwasm::TerraMsgWrapper;
 
 pub const CLAIM_REWARDS_OPERATION: u64 = 1u64;
 pub const SWAP_TO_STABLE_OPERATION: u64 = 2u64;
+pub const LIQUIDATION_OPERATION: u64 = 3u64;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -52,7 +62,7 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig {
             owner,
             liquidation_contract,
@@ -65,6 +75,13 @@ pub fn execute(
                 optional_addr_validate(api, liquidation_contract)?,
             )
         }
+        ExecuteMsg::UpdateFeeModel { fee_model } => update_fee_model(deps, info, fee_model),
+        ExecuteMsg::UpdateRewardSwapRoute { reward_swap_route } => {
+            update_reward_swap_route(deps, info, reward_swap_route)
+        }
+        ExecuteMsg::UpdateLiquidationVenue { liquidation_venue } => {
+            update_liquidation_venue(deps, info, liquidation_venue)
+        }
         ExecuteMsg::LockCollateral { borrower, amount } => {
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             lock_collateral(deps, info, borrower_addr, amount)
@@ -79,10 +96,31 @@ pub fn execute(
             liquidator,
             borrower,
             amount,
+            max_spread,
         } => {
             let liquidator_addr = deps.api.addr_validate(&liquidator)?;
             let borrower_addr = deps.api.addr_validate(&borrower)?;
-            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
+            liquidate_collateral(
+                deps,
+                info,
+                liquidator_addr,
+                borrower_addr,
+                amount,
+                max_spread,
+            )
+        }
+        ExecuteMsg::MigrateCollateral {
+            borrower,
+            amount,
+            new_custody,
+        } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            let new_custody_addr = deps.api.addr_validate(&new_custody)?;
+            migrate_collateral(deps, info, borrower_addr, amount, new_custody_addr)
+        }
+        ExecuteMsg::SelfLiquidateCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            self_liquidate_collateral(deps, info, borrower_addr, amount)
         }
     }
 }
@@ -98,12 +136,15 @@ pub fn reply(
         CLAIM_REWARDS_OPERATION => swap_to_stable_denom(deps, env),
         // Swap to stable callback
         SWAP_TO_STABLE_OPERATION => distribute_hook(deps, env),
+        // Liquidation callback
+        LIQUIDATION_OPERATION => liquidation_reply(deps, msg),
         _ => Err(ContractError::InvalidReplyId {}),
     }
 }
 
 pub fn receive_cw20(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
@@ -120,6 +161,29 @@ pub fn receive_cw20(
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
             deposit_collateral(deps, cw20_sender_addr, cw20_msg.amount.into())
         }
+        Ok(Cw20HookMsg::ReceiveMigratedCollateral { borrower }) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.collateral_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            // only the custody replaced by this one can send migrated collateral
+            let migration = OverseerQuerier::new(
+                deps.querier,
+                deps.api.addr_humanize(&config.overseer_contract)?,
+            )
+            .custody_migration(contract_addr.to_string())?;
+            match migration {
+                Some(migration)
+                    if migration.old_custody == cw20_msg.sender
+                        && migration.new_custody == env.contract.address.as_str() => {}
+                _ => return Err(ContractError::Unauthorized {}),
+            }
+
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            deposit_collateral(deps, borrower_addr, cw20_msg.amount.into())
+        }
         _ => Err(ContractError::MissingDepositCollateralHook {}),
     }
 }
@@ -148,6 +212,24 @@ pub fn update_config(
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn update_fee_model(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_model: FeeModel,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_fee_model(deps.storage, &fee_model)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fee_model"),
+        attr("fee_model", fee_model.to_string()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -161,6 +243,23 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             optional_addr_validate(deps.api, start_after)?,
             limit,
         )?),
+        QueryMsg::InterfaceVersion {} => to_binary(&query_interface_version()),
+        QueryMsg::LiquidationVenue {} => to_binary(&query_liquidation_venue(deps)?),
+        QueryMsg::RewardSwapRoute {} => to_binary(&query_reward_swap_route(deps)?),
+    }
+}
+
+pub fn query_interface_version() -> InterfaceVersionResponse {
+    InterfaceVersionResponse {
+        version: CUSTODY_INTERFACE_VERSION,
+        capabilities: vec![
+            CustodyCapability::LockCollateral,
+            CustodyCapability::UnlockCollateral,
+            CustodyCapability::LiquidateCollateral,
+            CustodyCapability::DistributeRewards,
+            CustodyCapability::MigrateCollateral,
+            CustodyCapability::SelfLiquidateCollateral,
+        ],
     }
 }
 
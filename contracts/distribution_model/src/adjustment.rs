@@ -0,0 +1,111 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_emission_adjustments, read_pending_emission, store_emission_adjustment,
+    store_pending_emission, Config, EmissionAdjustment, PendingEmission,
+};
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use moneymarket::distribution_model::{
+    EmissionAdjustmentsResponse, EmissionDelta, PendingEmissionResponse,
+};
+
+// maximum length of the reason of an adjustment
+const MAX_REASON_LENGTH: u32 = 256;
+
+pub fn adjust_emission(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market: Addr,
+    delta: EmissionDelta,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let adjuster = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if adjuster != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (credit, debit) = match &delta {
+        EmissionDelta::Credit { amount } => (*amount, Uint256::zero()),
+        EmissionDelta::Debit { amount } => (Uint256::zero(), *amount),
+    };
+    if (credit + debit).is_zero()
+        || reason.trim().is_empty()
+        || reason.chars().count() > MAX_REASON_LENGTH as usize
+    {
+        return Err(ContractError::InvalidEmissionAdjustment(MAX_REASON_LENGTH));
+    }
+
+    // the budget is kept net, a debit first cancelling the credit
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let pending_emission = read_pending_emission(deps.storage, &market_raw)?;
+    let credit = pending_emission.credit + credit;
+    let debit = pending_emission.debit + debit;
+    let pending_emission = if credit > debit {
+        PendingEmission {
+            credit: credit - debit,
+            debit: Uint256::zero(),
+        }
+    } else {
+        PendingEmission {
+            credit: Uint256::zero(),
+            debit: debit - credit,
+        }
+    };
+    store_pending_emission(deps.storage, &market_raw, &pending_emission)?;
+
+    let adjustment_id = store_emission_adjustment(
+        deps.storage,
+        &EmissionAdjustment {
+            market: market_raw,
+            delta: delta.clone(),
+            reason: reason.clone(),
+            adjuster,
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        },
+    )?;
+
+    let (direction, amount) = match delta {
+        EmissionDelta::Credit { amount } => ("credit", amount),
+        EmissionDelta::Debit { amount } => ("debit", amount),
+    };
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "adjust_emission"),
+        attr("adjustment_id", adjustment_id.to_string()),
+        attr("market", market.as_str()),
+        attr(direction, amount),
+        attr("reason", reason),
+        attr("pending_credit", pending_emission.credit),
+        attr("pending_debit", pending_emission.debit),
+    ]))
+}
+
+pub fn query_pending_emission(deps: Deps, market: Addr) -> StdResult<PendingEmissionResponse> {
+    let pending_emission =
+        read_pending_emission(deps.storage, &deps.api.addr_canonicalize(market.as_str())?)?;
+
+    Ok(PendingEmissionResponse {
+        market: market.to_string(),
+        credit: pending_emission.credit,
+        debit: pending_emission.debit,
+    })
+}
+
+pub fn query_emission_adjustments(
+    deps: Deps,
+    market: Option<Addr>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EmissionAdjustmentsResponse> {
+    let market = if let Some(market) = market {
+        Some(deps.api.addr_canonicalize(market.as_str())?)
+    } else {
+        None
+    };
+
+    Ok(EmissionAdjustmentsResponse {
+        adjustments: read_emission_adjustments(deps, market, start_after, limit)?,
+    })
+}
@@ -0,0 +1,202 @@
+use crate::budget::record_emission;
+use crate::contract::compute_emission_rate;
+use crate::error::ContractError;
+use crate::state::{
+    read_all_market_emissions, read_config, read_emission_state, read_emitted_amount,
+    read_market_emission, read_market_emissions, store_emission_state, store_market_emission,
+    Config, EmissionState, MarketEmissionInfo,
+};
+use crate::vesting::route_vesting_emissions;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, Event, MessageInfo, Response, StdResult, Storage,
+};
+use moneymarket::distribution_model::{
+    EmissionStateResponse, MarketEmissionsResponse, MarketWeight,
+};
+
+// maximum number of markets sharing the emission
+const MAX_MARKETS: u32 = 30;
+
+pub fn update_market_weight(
+    deps: DepsMut,
+    info: MessageInfo,
+    market: Addr,
+    market_weight: Option<MarketWeight>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let mut attributes = vec![
+        attr("action", "update_market_weight"),
+        attr("market", market.as_str()),
+    ];
+    match market_weight {
+        Some(market_weight) => {
+            let registered = read_market_emission(deps.storage, &market_raw)?.is_some();
+            if market_weight.weight.is_zero()
+                || (!registered
+                    && read_all_market_emissions(deps.storage)?.len() >= MAX_MARKETS as usize)
+            {
+                return Err(ContractError::InvalidMarketWeight(MAX_MARKETS));
+            }
+
+            attributes.push(attr("weight", market_weight.weight.to_string()));
+            store_market_emission(
+                deps.storage,
+                &market_raw,
+                Some(&MarketEmissionInfo {
+                    weight: market_weight.weight,
+                    emission_cap: market_weight.emission_cap,
+                    emission_rate: Decimal256::zero(),
+                }),
+            )?;
+        }
+        None => store_market_emission(deps.storage, &market_raw, None)?,
+    }
+
+    // the current global emission is shared again with the new weights
+    let global_emission_rate = read_emission_state(deps.storage)?
+        .map(|state| state.global_emission_rate)
+        .unwrap_or_default();
+    allocate_emission(deps.storage, global_emission_rate)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn update_emission_rate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    deposit_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the global emission starts from the floor
+    let state = read_emission_state(deps.storage)?;
+    let current_emission_rate = state
+        .as_ref()
+        .map(|state| state.global_emission_rate)
+        .unwrap_or(config.emission_floor);
+
+    // the vesting markets are paid the emission of the passed epoch first
+    let passed_blocks = state
+        .map(|state| env.block.height.saturating_sub(state.last_updated_height))
+        .unwrap_or(0);
+    let messages = route_vesting_emissions(deps.branch(), &env, passed_blocks)?;
+    let exhausted = record_emission(
+        deps.storage,
+        Uint256::from(passed_blocks) * current_emission_rate,
+    )?;
+
+    let global_emission_rate = compute_emission_rate(
+        deps.as_ref(),
+        &env,
+        deposit_rate,
+        target_deposit_rate,
+        threshold_deposit_rate,
+        current_emission_rate,
+    )?;
+    store_emission_state(
+        deps.storage,
+        &EmissionState {
+            global_emission_rate,
+            last_updated_height: env.block.height,
+        },
+    )?;
+    allocate_emission(deps.storage, global_emission_rate)?;
+
+    let mut response = Response::new().add_messages(messages);
+    if exhausted {
+        response = response.add_event(
+            Event::new("emission_budget_exhausted")
+                .add_attribute("emitted_amount", read_emitted_amount(deps.storage)?),
+        );
+    }
+
+    Ok(response.add_attributes(vec![
+        attr("action", "update_emission_rate"),
+        attr("global_emission_rate", global_emission_rate.to_string()),
+    ]))
+}
+
+/// Share the global emission across the markets by weight, each up to
+/// its emission cap
+pub(crate) fn allocate_emission(
+    storage: &mut dyn Storage,
+    global_emission_rate: Decimal256,
+) -> StdResult<()> {
+    for (market, market_emission) in allocated_emissions(storage, global_emission_rate)? {
+        store_market_emission(storage, &market, Some(&market_emission))?;
+    }
+
+    Ok(())
+}
+
+/// Markets with their share of the global emission, unchanged while
+/// none has weight
+pub(crate) fn allocated_emissions(
+    storage: &dyn Storage,
+    global_emission_rate: Decimal256,
+) -> StdResult<Vec<(CanonicalAddr, MarketEmissionInfo)>> {
+    let mut market_emissions = read_all_market_emissions(storage)?;
+    let total_weight = total_weight(&market_emissions);
+    if total_weight.is_zero() {
+        return Ok(market_emissions);
+    }
+
+    for (_, market_emission) in market_emissions.iter_mut() {
+        let emission_rate = global_emission_rate * market_emission.weight / total_weight;
+        market_emission.emission_rate = match market_emission.emission_cap {
+            Some(emission_cap) => std::cmp::min(emission_rate, emission_cap),
+            None => emission_rate,
+        };
+    }
+
+    Ok(market_emissions)
+}
+
+fn total_weight(market_emissions: &[(CanonicalAddr, MarketEmissionInfo)]) -> Decimal256 {
+    market_emissions
+        .iter()
+        .fold(Decimal256::zero(), |total_weight, (_, market_emission)| {
+            total_weight + market_emission.weight
+        })
+}
+
+pub fn query_emission_state(deps: Deps) -> StdResult<EmissionStateResponse> {
+    let state = read_emission_state(deps.storage)?;
+
+    Ok(EmissionStateResponse {
+        global_emission_rate: state
+            .as_ref()
+            .map(|state| state.global_emission_rate)
+            .unwrap_or_default(),
+        total_weight: total_weight(&read_all_market_emissions(deps.storage)?),
+        last_updated_height: state.map(|state| state.last_updated_height).unwrap_or(0),
+    })
+}
+
+pub fn query_market_emissions(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<MarketEmissionsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    Ok(MarketEmissionsResponse {
+        markets: read_market_emissions(deps, start_after, limit)?,
+    })
+}
@@ -0,0 +1,70 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_emission_budget, read_emitted_amount, store_emission_budget,
+    store_emitted_amount, Config,
+};
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage};
+use moneymarket::distribution_model::EmissionBudgetResponse;
+
+pub fn update_emission_budget(
+    deps: DepsMut,
+    info: MessageInfo,
+    emission_budget: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_emission_budget(deps.storage, emission_budget)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_emission_budget"),
+        attr(
+            "emission_budget",
+            emission_budget
+                .map(|emission_budget| emission_budget.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Whether the emitted amount has reached the budget
+pub(crate) fn budget_exhausted(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(match read_emission_budget(storage)? {
+        Some(emission_budget) => read_emitted_amount(storage)? >= emission_budget,
+        None => false,
+    })
+}
+
+/// Adds the amount emitted over an epoch, returning whether it
+/// exhausted the budget
+pub(crate) fn record_emission(storage: &mut dyn Storage, amount: Uint256) -> StdResult<bool> {
+    if amount.is_zero() {
+        return Ok(false);
+    }
+
+    let exhausted = budget_exhausted(storage)?;
+    let emitted_amount = read_emitted_amount(storage)? + amount;
+    store_emitted_amount(storage, emitted_amount)?;
+    Ok(!exhausted && budget_exhausted(storage)?)
+}
+
+pub fn query_emission_budget(deps: Deps) -> StdResult<EmissionBudgetResponse> {
+    let emission_budget = read_emission_budget(deps.storage)?;
+    let emitted_amount = read_emitted_amount(deps.storage)?;
+
+    Ok(EmissionBudgetResponse {
+        emission_budget,
+        emitted_amount,
+        remaining_amount: emission_budget.map(|emission_budget| {
+            if emission_budget > emitted_amount {
+                emission_budget - emitted_amount
+            } else {
+                Uint256::zero()
+            }
+        }),
+        exhausted: budget_exhausted(deps.storage)?,
+    })
+}
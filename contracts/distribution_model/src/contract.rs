@@ -2,13 +2,27 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 
+use crate::adjustment::{adjust_emission, query_emission_adjustments, query_pending_emission};
+use crate::allocation::{
+    allocated_emissions, query_emission_state, query_market_emissions, update_emission_rate,
+    update_market_weight,
+};
+use crate::budget::{budget_exhausted, query_emission_budget, update_emission_budget};
 use crate::error::ContractError;
-use crate::state::{read_config, store_config, Config};
+use crate::gauge::{query_gauge, sync_gauge_weights, update_gauge};
+use crate::reserve_guard::{
+    query_reserve_guard, reserve_scale, update_reserve_guard, update_reserve_health,
+};
+use crate::schedule::{emission_scale, query_emission_schedule, update_emission_schedule};
+use crate::split::{query_emission_split, split_emission_rate, update_emission_split};
+use crate::state::{read_config, read_emission_schedule, store_config, Config};
+use crate::vesting::{query_vesting, update_vesting};
 
 use cosmwasm_bignumber::Decimal256;
 use moneymarket::common::optional_addr_validate;
 use moneymarket::distribution_model::{
-    AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MarketEmissionResponse,
+    QueryMsg, SimulateEmissionUpdateResponse,
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -35,7 +49,7 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
@@ -58,6 +72,52 @@ pub fn execute(
                 decrement_multiplier,
             )
         }
+        ExecuteMsg::UpdateEmissionSchedule { emission_schedule } => {
+            update_emission_schedule(deps, info, emission_schedule)
+        }
+        ExecuteMsg::UpdateMarketWeight {
+            market,
+            market_weight,
+        } => {
+            let api = deps.api;
+            update_market_weight(deps, info, api.addr_validate(&market)?, market_weight)
+        }
+        ExecuteMsg::UpdateEmissionSplit { borrower_share } => {
+            update_emission_split(deps, info, borrower_share)
+        }
+        ExecuteMsg::UpdateReserveGuard { reserve_guard } => {
+            update_reserve_guard(deps, info, reserve_guard)
+        }
+        ExecuteMsg::UpdateReserveHealth {} => update_reserve_health(deps),
+        ExecuteMsg::UpdateGauge { gauge } => update_gauge(deps, info, gauge),
+        ExecuteMsg::SyncGaugeWeights {} => sync_gauge_weights(deps, env),
+        ExecuteMsg::AdjustEmission {
+            market,
+            delta,
+            reason,
+        } => {
+            let api = deps.api;
+            adjust_emission(deps, env, info, api.addr_validate(&market)?, delta, reason)
+        }
+        ExecuteMsg::UpdateVesting { market, vesting } => {
+            let api = deps.api;
+            update_vesting(deps, info, api.addr_validate(&market)?, vesting)
+        }
+        ExecuteMsg::UpdateEmissionBudget { emission_budget } => {
+            update_emission_budget(deps, info, emission_budget)
+        }
+        ExecuteMsg::UpdateEmissionRate {
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+        } => update_emission_rate(
+            deps,
+            env,
+            info,
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+        ),
     }
 }
 
@@ -100,7 +160,7 @@ pub fn update_config(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::AncEmissionRate {
@@ -110,11 +170,53 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             current_emission_rate,
         } => to_binary(&query_anc_emission_rate(
             deps,
+            env,
             deposit_rate,
             target_deposit_rate,
             threshold_deposit_rate,
             current_emission_rate,
         )?),
+        QueryMsg::SimulateEmissionUpdate {
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+            current_rate,
+        } => to_binary(&query_simulate_emission_update(
+            deps,
+            env,
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+            current_rate,
+        )?),
+        QueryMsg::EmissionSchedule {} => to_binary(&query_emission_schedule(deps, env)?),
+        QueryMsg::EmissionState {} => to_binary(&query_emission_state(deps)?),
+        QueryMsg::EmissionSplit {} => to_binary(&query_emission_split(deps)?),
+        QueryMsg::ReserveGuard {} => to_binary(&query_reserve_guard(deps)?),
+        QueryMsg::Gauge {} => to_binary(&query_gauge(deps)?),
+        QueryMsg::EmissionBudget {} => to_binary(&query_emission_budget(deps)?),
+        QueryMsg::Vesting { market } => {
+            to_binary(&query_vesting(deps, deps.api.addr_validate(&market)?)?)
+        }
+        QueryMsg::MarketEmissions { start_after, limit } => to_binary(&query_market_emissions(
+            deps,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
+        QueryMsg::PendingEmission { market } => to_binary(&query_pending_emission(
+            deps,
+            deps.api.addr_validate(&market)?,
+        )?),
+        QueryMsg::EmissionAdjustments {
+            market,
+            start_after,
+            limit,
+        } => to_binary(&query_emission_adjustments(
+            deps,
+            optional_addr_validate(deps.api, market)?,
+            start_after,
+            limit,
+        )?),
     }
 }
 
@@ -133,12 +235,88 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 
 fn query_anc_emission_rate(
     deps: Deps,
+    env: Env,
     deposit_rate: Decimal256,
     target_deposit_rate: Decimal256,
     threshold_deposit_rate: Decimal256,
     current_emission_rate: Decimal256,
 ) -> StdResult<AncEmissionRateResponse> {
+    let emission_rate = compute_emission_rate(
+        deps,
+        &env,
+        deposit_rate,
+        target_deposit_rate,
+        threshold_deposit_rate,
+        current_emission_rate,
+    )?;
+    let (borrower_emission_rate, depositor_emission_rate) =
+        split_emission_rate(deps.storage, emission_rate)?;
+
+    Ok(AncEmissionRateResponse {
+        emission_rate,
+        borrower_emission_rate,
+        depositor_emission_rate,
+    })
+}
+
+/// Emission the model would set for the epoch, with its split and its
+/// allocation across the markets, without updating it
+fn query_simulate_emission_update(
+    deps: Deps,
+    env: Env,
+    deposit_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+    current_rate: Decimal256,
+) -> StdResult<SimulateEmissionUpdateResponse> {
     let config: Config = read_config(deps.storage)?;
+    let emission_rate = compute_emission_rate(
+        deps,
+        &env,
+        deposit_rate,
+        target_deposit_rate,
+        threshold_deposit_rate,
+        current_rate,
+    )?;
+    let (borrower_emission_rate, depositor_emission_rate) =
+        split_emission_rate(deps.storage, emission_rate)?;
+    let (emission_cap, emission_floor) = emission_bounds(deps, &env, &config)?;
+
+    Ok(SimulateEmissionUpdateResponse {
+        emission_rate,
+        borrower_emission_rate,
+        depositor_emission_rate,
+        emission_cap,
+        emission_floor,
+        markets: allocated_emissions(deps.storage, emission_rate)?
+            .into_iter()
+            .map(|(market, market_emission)| {
+                Ok(MarketEmissionResponse {
+                    market: deps.api.addr_humanize(&market)?.to_string(),
+                    weight: market_emission.weight,
+                    emission_cap: market_emission.emission_cap,
+                    emission_rate: market_emission.emission_rate,
+                })
+            })
+            .collect::<StdResult<Vec<MarketEmissionResponse>>>()?,
+    })
+}
+
+/// Emission rate moved toward the target deposit rate by the multipliers,
+/// within the cap and floor scaled by the schedule. Zero once the budget
+/// is emitted
+pub(crate) fn compute_emission_rate(
+    deps: Deps,
+    env: &Env,
+    deposit_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+    current_emission_rate: Decimal256,
+) -> StdResult<Decimal256> {
+    let config: Config = read_config(deps.storage)?;
+    if budget_exhausted(deps.storage)? {
+        return Ok(Decimal256::zero());
+    }
 
     let half_dec = Decimal256::one() + Decimal256::one();
     let mid_rate = (threshold_deposit_rate + target_deposit_rate) / half_dec;
@@ -153,13 +331,24 @@ fn query_anc_emission_rate(
         current_emission_rate
     };
 
-    let emission_rate = if emission_rate > config.emission_cap {
-        config.emission_cap
-    } else if emission_rate < config.emission_floor {
-        config.emission_floor
+    let (emission_cap, emission_floor) = emission_bounds(deps, env, &config)?;
+    Ok(if emission_rate > emission_cap {
+        emission_cap
+    } else if emission_rate < emission_floor {
+        emission_floor
     } else {
         emission_rate
-    };
+    })
+}
+
+/// Emission cap and floor at the current block. The bounds decay with the
+/// schedule, and shrink with the runway of the interest buffer when it is
+/// short
+fn emission_bounds(deps: Deps, env: &Env, config: &Config) -> StdResult<(Decimal256, Decimal256)> {
+    let scale = emission_scale(
+        read_emission_schedule(deps.storage)?.as_ref(),
+        env.block.height,
+    ) * reserve_scale(deps.storage)?;
 
-    Ok(AncEmissionRateResponse { emission_rate })
+    Ok((config.emission_cap * scale, config.emission_floor * scale))
 }
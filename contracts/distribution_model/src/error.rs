@@ -11,4 +11,40 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Emission schedule must halve over a positive period or have 1 to {0} increasing phases scaled by at most 1")]
+    InvalidEmissionSchedule(u32),
+
+    #[error("Market weight must be positive, for at most {0} markets")]
+    InvalidMarketWeight(u32),
+
+    #[error("Borrower share of the emission must be at most 1")]
+    InvalidEmissionSplit {},
+
+    #[error("Reserve guard must release at a longer runway than it throttles at")]
+    InvalidReserveGuard {},
+
+    #[error("No reserve guard is set")]
+    NoReserveGuard {},
+
+    #[error(
+        "Gauge must have a positive epoch period and shares bounded by min_share <= max_share <= 1"
+    )]
+    InvalidGauge {},
+
+    #[error("No gauge is set")]
+    NoGauge {},
+
+    #[error("Gauge epoch has not passed since the last sync at {0}")]
+    GaugeEpochNotPassed(u64),
+
+    #[error(
+        "Emission adjustment must move a positive amount, for a reason of 1 to {0} characters"
+    )]
+    InvalidEmissionAdjustment(u32),
+
+    #[error(
+        "Vesting must be of a registered market, with a positive duration of at least the cliff"
+    )]
+    InvalidVesting {},
 }
@@ -0,0 +1,134 @@
+use crate::allocation::allocate_emission;
+use crate::error::ContractError;
+use crate::state::{
+    read_all_market_emissions, read_config, read_emission_state, read_gauge,
+    read_gauge_last_synced, store_gauge, store_gauge_last_synced, store_market_emission, Config,
+    GaugeInfo,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response, StdError, StdResult,
+    WasmQuery,
+};
+use moneymarket::distribution_model::{Gauge, GaugeResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Query of the gauge contract for the votes of a market
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GaugeQueryMsg {
+    GaugeWeight { gauge: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeWeightResponse {
+    pub weight: Uint256,
+}
+
+pub fn update_gauge(
+    deps: DepsMut,
+    info: MessageInfo,
+    gauge: Option<Gauge>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_gauge")];
+    let gauge = match gauge {
+        Some(gauge) => {
+            if gauge.epoch_period == 0
+                || gauge.min_share > gauge.max_share
+                || gauge.max_share > Decimal256::one()
+            {
+                return Err(ContractError::InvalidGauge {});
+            }
+
+            attributes.push(attr("gauge", gauge.contract_addr.as_str()));
+            Some(GaugeInfo {
+                contract_addr: deps
+                    .api
+                    .addr_canonicalize(deps.api.addr_validate(&gauge.contract_addr)?.as_str())?,
+                epoch_period: gauge.epoch_period,
+                min_share: gauge.min_share,
+                max_share: gauge.max_share,
+            })
+        }
+        None => None,
+    };
+    store_gauge(deps.storage, gauge)?;
+    store_gauge_last_synced(deps.storage, 0)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn sync_gauge_weights(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let gauge = read_gauge(deps.storage)?.ok_or(ContractError::NoGauge {})?;
+    let last_synced_time = read_gauge_last_synced(deps.storage)?;
+    if last_synced_time != 0 && env.block.time.seconds() < last_synced_time + gauge.epoch_period {
+        return Err(ContractError::GaugeEpochNotPassed(last_synced_time));
+    }
+
+    let gauge_addr = deps.api.addr_humanize(&gauge.contract_addr)?;
+    let mut market_votes = vec![];
+    for (market, market_emission) in read_all_market_emissions(deps.storage)?.into_iter() {
+        let gauge_weight: GaugeWeightResponse =
+            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gauge_addr.to_string(),
+                msg: to_binary(&GaugeQueryMsg::GaugeWeight {
+                    gauge: deps.api.addr_humanize(&market)?.to_string(),
+                })?,
+            }))?;
+        market_votes.push((market, market_emission, gauge_weight.weight));
+    }
+
+    // without votes the weights are kept
+    let total_votes = market_votes
+        .iter()
+        .fold(Uint256::zero(), |total_votes, (_, _, votes)| {
+            total_votes + *votes
+        });
+    if !total_votes.is_zero() {
+        for (market, mut market_emission, votes) in market_votes.into_iter() {
+            market_emission.weight = std::cmp::min(
+                std::cmp::max(
+                    Decimal256::from_uint256(votes) / Decimal256::from_uint256(total_votes),
+                    gauge.min_share,
+                ),
+                gauge.max_share,
+            );
+            store_market_emission(deps.storage, &market, Some(&market_emission))?;
+        }
+
+        let global_emission_rate = read_emission_state(deps.storage)?
+            .map(|state| state.global_emission_rate)
+            .unwrap_or_default();
+        allocate_emission(deps.storage, global_emission_rate)?;
+    }
+    store_gauge_last_synced(deps.storage, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "sync_gauge_weights"),
+        attr("total_votes", total_votes),
+    ]))
+}
+
+pub fn query_gauge(deps: Deps) -> StdResult<GaugeResponse> {
+    let gauge = read_gauge(deps.storage)?;
+
+    Ok(GaugeResponse {
+        gauge: gauge
+            .map(|gauge| {
+                Ok::<Gauge, StdError>(Gauge {
+                    contract_addr: deps.api.addr_humanize(&gauge.contract_addr)?.to_string(),
+                    epoch_period: gauge.epoch_period,
+                    min_share: gauge.min_share,
+                    max_share: gauge.max_share,
+                })
+            })
+            .transpose()?,
+        last_synced_time: read_gauge_last_synced(deps.storage)?,
+    })
+}
@@ -1,6 +1,16 @@
+pub mod adjustment;
+pub mod allocation;
+pub mod budget;
 pub mod contract;
 pub mod error;
+pub mod gauge;
+pub mod reserve_guard;
+pub mod schedule;
+pub mod split;
 pub mod state;
+pub mod vesting;
 
+#[cfg(test)]
+mod mock_querier;
 #[cfg(test)]
 mod tests;
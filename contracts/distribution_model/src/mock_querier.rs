@@ -0,0 +1,142 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, ContractResult, Empty, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+use moneymarket::overseer::ProjectedRunwayResponse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::gauge::GaugeWeightResponse;
+use crate::vesting::VestingAccountResponse;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Query interest buffer runway to overseer contract
+    ProjectedRunway {},
+    /// Query votes of a market to gauge contract
+    GaugeWeight { gauge: String },
+    /// Query vestings of a recipient to escrow contract
+    VestingAccount { address: String },
+}
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: WasmMockQuerier::new(MockQuerier::new(&[])),
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    // interest buffer runways of the overseer contracts, by contract
+    runways: HashMap<String, Option<Decimal256>>,
+    // votes of the markets in the gauge contracts, by contract and market
+    gauge_weights: HashMap<(String, String), Uint256>,
+    // vesting accounts in the escrow contracts, by contract and recipient
+    vesting_accounts: HashMap<(String, String), VestingAccountResponse>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    QueryMsg::ProjectedRunway {} => match self.runways.get(contract_addr) {
+                        Some(runway) => SystemResult::Ok(ContractResult::from(to_binary(
+                            &ProjectedRunwayResponse {
+                                interest_buffer: Uint256::zero(),
+                                distributed_interest: Uint256::zero(),
+                                runway: *runway,
+                                target_deposit_rate: Decimal256::zero(),
+                            },
+                        ))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No runway exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    QueryMsg::GaugeWeight { gauge } => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&GaugeWeightResponse {
+                            weight: self
+                                .gauge_weights
+                                .get(&(contract_addr.to_string(), gauge))
+                                .cloned()
+                                .unwrap_or_default(),
+                        })))
+                    }
+                    QueryMsg::VestingAccount { address } => {
+                        match self
+                            .vesting_accounts
+                            .get(&(contract_addr.to_string(), address))
+                        {
+                            Some(vesting_account) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(vesting_account)))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No vesting account exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            runways: HashMap::new(),
+            gauge_weights: HashMap::new(),
+            vesting_accounts: HashMap::new(),
+        }
+    }
+
+    pub fn with_runway(&mut self, overseer_contract: &str, runway: Option<Decimal256>) {
+        self.runways.insert(overseer_contract.to_string(), runway);
+    }
+
+    pub fn with_gauge_weights(&mut self, gauge_contract: &str, gauge_weights: &[(&str, u64)]) {
+        for (market, weight) in gauge_weights.iter() {
+            self.gauge_weights.insert(
+                (gauge_contract.to_string(), market.to_string()),
+                Uint256::from(*weight),
+            );
+        }
+    }
+
+    pub fn with_vesting_account(
+        &mut self,
+        escrow_contract: &str,
+        recipient: &str,
+        vesting_account: VestingAccountResponse,
+    ) {
+        self.vesting_accounts.insert(
+            (escrow_contract.to_string(), recipient.to_string()),
+            vesting_account,
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_reserve_guard, read_reserve_health, store_reserve_guard,
+    store_reserve_health, Config, ReserveGuardInfo, ReserveHealth,
+};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Storage};
+use moneymarket::distribution_model::{ReserveGuard, ReserveGuardResponse};
+use moneymarket::overseer::{OverseerQuerier, ProjectedRunwayResponse};
+
+pub fn update_reserve_guard(
+    deps: DepsMut,
+    info: MessageInfo,
+    reserve_guard: Option<ReserveGuard>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_reserve_guard")];
+    let reserve_guard = match reserve_guard {
+        Some(reserve_guard) => {
+            if reserve_guard.low_runway >= reserve_guard.high_runway {
+                return Err(ContractError::InvalidReserveGuard {});
+            }
+
+            attributes.push(attr("low_runway", reserve_guard.low_runway.to_string()));
+            attributes.push(attr("high_runway", reserve_guard.high_runway.to_string()));
+            Some(ReserveGuardInfo {
+                overseer_contract: deps.api.addr_canonicalize(
+                    deps.api
+                        .addr_validate(&reserve_guard.overseer_contract)?
+                        .as_str(),
+                )?,
+                low_runway: reserve_guard.low_runway,
+                high_runway: reserve_guard.high_runway,
+            })
+        }
+        None => None,
+    };
+
+    // the emission is released until the next health update
+    store_reserve_health(deps.storage, None)?;
+    store_reserve_guard(deps.storage, reserve_guard)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn update_reserve_health(deps: DepsMut) -> Result<Response, ContractError> {
+    let reserve_guard =
+        read_reserve_guard(deps.storage)?.ok_or(ContractError::NoReserveGuard {})?;
+    let projected_runway: ProjectedRunwayResponse = OverseerQuerier::new(
+        deps.querier,
+        deps.api.addr_humanize(&reserve_guard.overseer_contract)?,
+    )
+    .projected_runway()?;
+
+    let throttled = read_reserve_health(deps.storage)?
+        .map(|reserve_health| reserve_health.throttled)
+        .unwrap_or(false);
+    let reserve_health = reserve_health(&reserve_guard, throttled, projected_runway.runway);
+    let mut attributes = vec![
+        attr("action", "update_reserve_health"),
+        attr("throttled", reserve_health.throttled.to_string()),
+        attr("scale", reserve_health.scale.to_string()),
+    ];
+    if let Some(runway) = reserve_health.runway {
+        attributes.push(attr("runway", runway.to_string()));
+    }
+    store_reserve_health(deps.storage, Some(reserve_health))?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Health of the reserve at the runway. A throttled emission stays
+/// throttled until the runway recovers to the high runway, and an
+/// unspent buffer has an unbounded runway
+fn reserve_health(
+    reserve_guard: &ReserveGuardInfo,
+    throttled: bool,
+    runway: Option<Decimal256>,
+) -> ReserveHealth {
+    let throttled = match runway {
+        Some(runway) if throttled => runway < reserve_guard.high_runway,
+        Some(runway) => runway < reserve_guard.low_runway,
+        None => false,
+    };
+    let scale = match runway {
+        Some(runway) if throttled => runway / reserve_guard.high_runway,
+        _ => Decimal256::one(),
+    };
+
+    ReserveHealth {
+        throttled,
+        runway,
+        scale,
+    }
+}
+
+/// Scale of the emission at the last health update, one without a
+/// reserve guard
+pub(crate) fn reserve_scale(storage: &dyn Storage) -> StdResult<Decimal256> {
+    Ok(read_reserve_health(storage)?
+        .map(|reserve_health| reserve_health.scale)
+        .unwrap_or_else(Decimal256::one))
+}
+
+pub fn query_reserve_guard(deps: Deps) -> StdResult<ReserveGuardResponse> {
+    let reserve_guard = read_reserve_guard(deps.storage)?;
+    let reserve_health = read_reserve_health(deps.storage)?;
+
+    Ok(ReserveGuardResponse {
+        reserve_guard: reserve_guard
+            .map(|reserve_guard| {
+                Ok::<ReserveGuard, StdError>(ReserveGuard {
+                    overseer_contract: deps
+                        .api
+                        .addr_humanize(&reserve_guard.overseer_contract)?
+                        .to_string(),
+                    low_runway: reserve_guard.low_runway,
+                    high_runway: reserve_guard.high_runway,
+                })
+            })
+            .transpose()?,
+        throttled: reserve_health
+            .as_ref()
+            .map(|reserve_health| reserve_health.throttled)
+            .unwrap_or(false),
+        runway: reserve_health
+            .as_ref()
+            .and_then(|reserve_health| reserve_health.runway),
+        scale: reserve_health
+            .map(|reserve_health| reserve_health.scale)
+            .unwrap_or_else(Decimal256::one),
+    })
+}
@@ -0,0 +1,93 @@
+use crate::error::ContractError;
+use crate::state::{read_config, read_emission_schedule, store_emission_schedule, Config};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use moneymarket::distribution_model::{EmissionSchedule, EmissionScheduleResponse};
+
+// maximum number of phases of a piecewise schedule
+const MAX_EMISSION_PHASES: u32 = 20;
+
+pub fn update_emission_schedule(
+    deps: DepsMut,
+    info: MessageInfo,
+    emission_schedule: Option<EmissionSchedule>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_emission_schedule")];
+    match emission_schedule.as_ref() {
+        Some(EmissionSchedule::Halving {
+            start_height,
+            halving_period,
+        }) => {
+            if *halving_period == 0 {
+                return Err(ContractError::InvalidEmissionSchedule(MAX_EMISSION_PHASES));
+            }
+
+            attributes.push(attr("start_height", start_height.to_string()));
+            attributes.push(attr("halving_period", halving_period.to_string()));
+        }
+        Some(EmissionSchedule::Piecewise { phases }) => {
+            if phases.is_empty()
+                || phases.len() > MAX_EMISSION_PHASES as usize
+                || phases.iter().any(|phase| phase.scale > Decimal256::one())
+                || phases
+                    .windows(2)
+                    .any(|pair| pair[0].start_height >= pair[1].start_height)
+            {
+                return Err(ContractError::InvalidEmissionSchedule(MAX_EMISSION_PHASES));
+            }
+
+            attributes.push(attr("phases", phases.len().to_string()));
+        }
+        None => {}
+    }
+    store_emission_schedule(deps.storage, emission_schedule)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Scale of the emission at the block height, one before the schedule
+/// starts or without a schedule
+pub(crate) fn emission_scale(
+    emission_schedule: Option<&EmissionSchedule>,
+    block_height: u64,
+) -> Decimal256 {
+    match emission_schedule {
+        Some(EmissionSchedule::Halving {
+            start_height,
+            halving_period,
+        }) => {
+            let halvings = block_height.saturating_sub(*start_height) / halving_period;
+            // below the 18 decimals of Decimal256 past 60 halvings
+            if halvings >= 64 {
+                Decimal256::zero()
+            } else {
+                Decimal256::from_ratio(1u64, 1u64 << halvings)
+            }
+        }
+        Some(EmissionSchedule::Piecewise { phases }) => phases
+            .iter()
+            .rev()
+            .find(|phase| phase.start_height <= block_height)
+            .map(|phase| phase.scale)
+            .unwrap_or_else(Decimal256::one),
+        None => Decimal256::one(),
+    }
+}
+
+pub fn query_emission_schedule(deps: Deps, env: Env) -> StdResult<EmissionScheduleResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let emission_schedule = read_emission_schedule(deps.storage)?;
+    let scale = emission_scale(emission_schedule.as_ref(), env.block.height);
+
+    Ok(EmissionScheduleResponse {
+        emission_schedule,
+        scale,
+        emission_cap: config.emission_cap * scale,
+        emission_floor: config.emission_floor * scale,
+    })
+}
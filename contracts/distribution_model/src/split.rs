@@ -0,0 +1,47 @@
+use crate::error::ContractError;
+use crate::state::{read_borrower_share, read_config, store_borrower_share, Config};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage};
+use moneymarket::distribution_model::EmissionSplitResponse;
+
+pub fn update_emission_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower_share: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if borrower_share > Decimal256::one() {
+        return Err(ContractError::InvalidEmissionSplit {});
+    }
+    store_borrower_share(deps.storage, &borrower_share)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_emission_split"),
+        attr("borrower_share", borrower_share.to_string()),
+    ]))
+}
+
+/// Borrower and depositor emission rates, adding up to the emission rate
+pub(crate) fn split_emission_rate(
+    storage: &dyn Storage,
+    emission_rate: Decimal256,
+) -> StdResult<(Decimal256, Decimal256)> {
+    let borrower_emission_rate = emission_rate * read_borrower_share(storage)?;
+    Ok((
+        borrower_emission_rate,
+        emission_rate - borrower_emission_rate,
+    ))
+}
+
+pub fn query_emission_split(deps: Deps) -> StdResult<EmissionSplitResponse> {
+    let borrower_share = read_borrower_share(deps.storage)?;
+
+    Ok(EmissionSplitResponse {
+        borrower_share,
+        depositor_share: Decimal256::one() - borrower_share,
+    })
+}
@@ -1,11 +1,30 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::distribution_model::{
+    EmissionAdjustmentResponse, EmissionDelta, EmissionSchedule, MarketEmissionResponse,
+};
+use moneymarket::pagination::{calc_limit, calc_range_start};
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_EMISSION_SCHEDULE: &[u8] = b"emission_schedule";
+static KEY_EMISSION_STATE: &[u8] = b"emission_state";
+static KEY_BORROWER_SHARE: &[u8] = b"borrower_share";
+static KEY_RESERVE_GUARD: &[u8] = b"reserve_guard";
+static KEY_RESERVE_HEALTH: &[u8] = b"reserve_health";
+static KEY_GAUGE: &[u8] = b"gauge";
+static KEY_GAUGE_LAST_SYNCED: &[u8] = b"gauge_last_synced";
+static KEY_ADJUSTMENT_COUNT: &[u8] = b"adjustment_count";
+static KEY_EMISSION_BUDGET: &[u8] = b"emission_budget";
+static KEY_EMITTED_AMOUNT: &[u8] = b"emitted_amount";
+
+static PREFIX_MARKET_EMISSION: &[u8] = b"market_emission";
+static PREFIX_PENDING_EMISSION: &[u8] = b"pending_emission";
+static PREFIX_VESTING: &[u8] = b"vesting";
+static PREFIX_EMISSION_ADJUSTMENT: &[u8] = b"emission_adjustment";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -16,6 +35,65 @@ pub struct Config {
     pub decrement_multiplier: Decimal256,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionState {
+    pub global_emission_rate: Decimal256,
+    pub last_updated_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketEmissionInfo {
+    pub weight: Decimal256,
+    pub emission_cap: Option<Decimal256>,
+    pub emission_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveGuardInfo {
+    pub overseer_contract: CanonicalAddr,
+    pub low_runway: Decimal256,
+    pub high_runway: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveHealth {
+    pub throttled: bool,
+    pub runway: Option<Decimal256>,
+    pub scale: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeInfo {
+    pub contract_addr: CanonicalAddr,
+    pub epoch_period: u64,
+    pub min_share: Decimal256,
+    pub max_share: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PendingEmission {
+    pub credit: Uint256,
+    pub debit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionAdjustment {
+    pub market: CanonicalAddr,
+    pub delta: EmissionDelta,
+    pub reason: String,
+    pub adjuster: CanonicalAddr,
+    pub height: u64,
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingInfo {
+    pub escrow_contract: CanonicalAddr,
+    pub cliff: u64,
+    pub duration: u64,
+    pub routed_amount: Uint256,
+}
+
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
     singleton(storage, KEY_CONFIG).save(config)
 }
@@ -23,3 +101,292 @@ pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()>
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+pub fn store_emission_schedule(
+    storage: &mut dyn Storage,
+    emission_schedule: Option<EmissionSchedule>,
+) -> StdResult<()> {
+    match emission_schedule {
+        Some(emission_schedule) => {
+            singleton(storage, KEY_EMISSION_SCHEDULE).save(&emission_schedule)
+        }
+        None => {
+            singleton::<EmissionSchedule>(storage, KEY_EMISSION_SCHEDULE).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_emission_schedule(storage: &dyn Storage) -> StdResult<Option<EmissionSchedule>> {
+    singleton_read(storage, KEY_EMISSION_SCHEDULE).may_load()
+}
+
+pub fn store_emission_state(storage: &mut dyn Storage, state: &EmissionState) -> StdResult<()> {
+    singleton(storage, KEY_EMISSION_STATE).save(state)
+}
+
+pub fn read_emission_state(storage: &dyn Storage) -> StdResult<Option<EmissionState>> {
+    singleton_read(storage, KEY_EMISSION_STATE).may_load()
+}
+
+pub fn store_market_emission(
+    storage: &mut dyn Storage,
+    market: &CanonicalAddr,
+    market_emission: Option<&MarketEmissionInfo>,
+) -> StdResult<()> {
+    let mut market_emission_bucket: Bucket<MarketEmissionInfo> =
+        Bucket::new(storage, PREFIX_MARKET_EMISSION);
+    match market_emission {
+        Some(market_emission) => market_emission_bucket.save(market.as_slice(), market_emission),
+        None => {
+            market_emission_bucket.remove(market.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_market_emission(
+    storage: &dyn Storage,
+    market: &CanonicalAddr,
+) -> StdResult<Option<MarketEmissionInfo>> {
+    let market_emission_bucket: ReadonlyBucket<MarketEmissionInfo> =
+        ReadonlyBucket::new(storage, PREFIX_MARKET_EMISSION);
+    market_emission_bucket.may_load(market.as_slice())
+}
+
+pub fn read_all_market_emissions(
+    storage: &dyn Storage,
+) -> StdResult<Vec<(CanonicalAddr, MarketEmissionInfo)>> {
+    let market_emission_bucket: ReadonlyBucket<MarketEmissionInfo> =
+        ReadonlyBucket::new(storage, PREFIX_MARKET_EMISSION);
+    market_emission_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+pub fn read_market_emissions(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MarketEmissionResponse>> {
+    let market_emission_bucket: ReadonlyBucket<MarketEmissionInfo> =
+        ReadonlyBucket::new(deps.storage, PREFIX_MARKET_EMISSION);
+
+    let limit = calc_limit(limit);
+    let start = calc_range_start(start_after);
+
+    market_emission_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok(MarketEmissionResponse {
+                market: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                weight: v.weight,
+                emission_cap: v.emission_cap,
+                emission_rate: v.emission_rate,
+            })
+        })
+        .collect()
+}
+
+pub fn store_borrower_share(
+    storage: &mut dyn Storage,
+    borrower_share: &Decimal256,
+) -> StdResult<()> {
+    singleton(storage, KEY_BORROWER_SHARE).save(borrower_share)
+}
+
+/// Share of the emission incentivizing borrowers, all of it until split
+pub fn read_borrower_share(storage: &dyn Storage) -> StdResult<Decimal256> {
+    Ok(singleton_read(storage, KEY_BORROWER_SHARE)
+        .may_load()?
+        .unwrap_or_else(Decimal256::one))
+}
+
+pub fn store_reserve_guard(
+    storage: &mut dyn Storage,
+    reserve_guard: Option<ReserveGuardInfo>,
+) -> StdResult<()> {
+    match reserve_guard {
+        Some(reserve_guard) => singleton(storage, KEY_RESERVE_GUARD).save(&reserve_guard),
+        None => {
+            singleton::<ReserveGuardInfo>(storage, KEY_RESERVE_GUARD).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_reserve_guard(storage: &dyn Storage) -> StdResult<Option<ReserveGuardInfo>> {
+    singleton_read(storage, KEY_RESERVE_GUARD).may_load()
+}
+
+pub fn store_reserve_health(
+    storage: &mut dyn Storage,
+    reserve_health: Option<ReserveHealth>,
+) -> StdResult<()> {
+    match reserve_health {
+        Some(reserve_health) => singleton(storage, KEY_RESERVE_HEALTH).save(&reserve_health),
+        None => {
+            singleton::<ReserveHealth>(storage, KEY_RESERVE_HEALTH).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_reserve_health(storage: &dyn Storage) -> StdResult<Option<ReserveHealth>> {
+    singleton_read(storage, KEY_RESERVE_HEALTH).may_load()
+}
+
+pub fn store_gauge(storage: &mut dyn Storage, gauge: Option<GaugeInfo>) -> StdResult<()> {
+    match gauge {
+        Some(gauge) => singleton(storage, KEY_GAUGE).save(&gauge),
+        None => {
+            singleton::<GaugeInfo>(storage, KEY_GAUGE).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_gauge(storage: &dyn Storage) -> StdResult<Option<GaugeInfo>> {
+    singleton_read(storage, KEY_GAUGE).may_load()
+}
+
+pub fn store_gauge_last_synced(storage: &mut dyn Storage, last_synced_time: u64) -> StdResult<()> {
+    singleton(storage, KEY_GAUGE_LAST_SYNCED).save(&last_synced_time)
+}
+
+pub fn read_gauge_last_synced(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(singleton_read(storage, KEY_GAUGE_LAST_SYNCED)
+        .may_load()?
+        .unwrap_or(0))
+}
+
+pub fn store_pending_emission(
+    storage: &mut dyn Storage,
+    market: &CanonicalAddr,
+    pending_emission: &PendingEmission,
+) -> StdResult<()> {
+    let mut pending_emission_bucket: Bucket<PendingEmission> =
+        Bucket::new(storage, PREFIX_PENDING_EMISSION);
+    pending_emission_bucket.save(market.as_slice(), pending_emission)
+}
+
+pub fn read_pending_emission(
+    storage: &dyn Storage,
+    market: &CanonicalAddr,
+) -> StdResult<PendingEmission> {
+    let pending_emission_bucket: ReadonlyBucket<PendingEmission> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_EMISSION);
+    Ok(pending_emission_bucket
+        .may_load(market.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_emission_adjustment(
+    storage: &mut dyn Storage,
+    adjustment: &EmissionAdjustment,
+) -> StdResult<u64> {
+    let adjustment_id: u64 = singleton_read::<u64>(storage, KEY_ADJUSTMENT_COUNT)
+        .may_load()?
+        .unwrap_or_default()
+        + 1;
+    singleton(storage, KEY_ADJUSTMENT_COUNT).save(&adjustment_id)?;
+
+    let mut adjustment_bucket: Bucket<EmissionAdjustment> =
+        Bucket::new(storage, PREFIX_EMISSION_ADJUSTMENT);
+    adjustment_bucket.save(&adjustment_id.to_be_bytes(), adjustment)?;
+
+    Ok(adjustment_id)
+}
+
+pub fn read_emission_adjustments(
+    deps: Deps,
+    market: Option<CanonicalAddr>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EmissionAdjustmentResponse>> {
+    let adjustment_bucket: ReadonlyBucket<EmissionAdjustment> =
+        ReadonlyBucket::new(deps.storage, PREFIX_EMISSION_ADJUSTMENT);
+
+    let limit = calc_limit(limit);
+    let start = calc_range_start(start_after);
+
+    adjustment_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .filter(|elem| match (elem, market.as_ref()) {
+            (Ok((_, v)), Some(market)) => v.market == *market,
+            _ => true,
+        })
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut adjustment_id = [0u8; 8];
+            adjustment_id.copy_from_slice(&k);
+
+            Ok(EmissionAdjustmentResponse {
+                adjustment_id: u64::from_be_bytes(adjustment_id),
+                market: deps.api.addr_humanize(&v.market)?.to_string(),
+                delta: v.delta,
+                reason: v.reason,
+                adjuster: deps.api.addr_humanize(&v.adjuster)?.to_string(),
+                height: v.height,
+                time: v.time,
+            })
+        })
+        .collect()
+}
+
+pub fn store_vesting(
+    storage: &mut dyn Storage,
+    market: &CanonicalAddr,
+    vesting: Option<&VestingInfo>,
+) -> StdResult<()> {
+    let mut vesting_bucket: Bucket<VestingInfo> = Bucket::new(storage, PREFIX_VESTING);
+    match vesting {
+        Some(vesting) => vesting_bucket.save(market.as_slice(), vesting),
+        None => {
+            vesting_bucket.remove(market.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_vesting(
+    storage: &dyn Storage,
+    market: &CanonicalAddr,
+) -> StdResult<Option<VestingInfo>> {
+    let vesting_bucket: ReadonlyBucket<VestingInfo> = ReadonlyBucket::new(storage, PREFIX_VESTING);
+    vesting_bucket.may_load(market.as_slice())
+}
+
+pub fn store_emission_budget(
+    storage: &mut dyn Storage,
+    emission_budget: Option<Uint256>,
+) -> StdResult<()> {
+    match emission_budget {
+        Some(emission_budget) => singleton(storage, KEY_EMISSION_BUDGET).save(&emission_budget),
+        None => {
+            singleton::<Uint256>(storage, KEY_EMISSION_BUDGET).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_emission_budget(storage: &dyn Storage) -> StdResult<Option<Uint256>> {
+    singleton_read(storage, KEY_EMISSION_BUDGET).may_load()
+}
+
+pub fn store_emitted_amount(storage: &mut dyn Storage, emitted_amount: Uint256) -> StdResult<()> {
+    singleton(storage, KEY_EMITTED_AMOUNT).save(&emitted_amount)
+}
+
+pub fn read_emitted_amount(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(singleton_read(storage, KEY_EMITTED_AMOUNT)
+        .may_load()?
+        .unwrap_or_default())
+}
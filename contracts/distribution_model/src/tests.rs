@@ -1,11 +1,19 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
-use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::from_binary;
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use crate::mock_querier::{mock_dependencies as mock_wasm_dependencies, WasmMockQuerier};
+use crate::vesting::{EscrowExecuteMsg, VestingAccountResponse};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{from_binary, to_binary, CosmosMsg, Event, OwnedDeps, WasmMsg};
 use moneymarket::distribution_model::{
-    AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AncEmissionRateResponse, ConfigResponse, EmissionAdjustmentResponse,
+    EmissionAdjustmentsResponse, EmissionBudgetResponse, EmissionDelta, EmissionPhase,
+    EmissionSchedule, EmissionScheduleResponse, EmissionSplitResponse, EmissionStateResponse,
+    ExecuteMsg, Gauge, GaugeResponse, InstantiateMsg, MarketEmissionResponse,
+    MarketEmissionsResponse, MarketWeight, PendingEmissionResponse, QueryMsg, ReserveGuard,
+    ReserveGuardResponse, SimulateEmissionUpdateResponse, Vesting, VestingResponse,
 };
+use std::str::FromStr;
 
 #[test]
 fn proper_initialization() {
@@ -161,3 +169,1150 @@ fn proper_emission_rate() {
     let value: AncEmissionRateResponse = from_binary(&res).unwrap();
     assert_eq!("10", &value.emission_rate.to_string());
 }
+
+#[test]
+fn emission_schedule() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSchedule {
+        emission_schedule: Some(EmissionSchedule::Halving {
+            start_height: 10_000,
+            halving_period: 1_000,
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // halved twice at the mock block height 12_345
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSchedule {
+        emission_schedule: Some(EmissionSchedule::Halving {
+            start_height: 10_000,
+            halving_period: 1_000,
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionSchedule {}).unwrap();
+    let value: EmissionScheduleResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionScheduleResponse {
+            emission_schedule: Some(EmissionSchedule::Halving {
+                start_height: 10_000,
+                halving_period: 1_000,
+            }),
+            scale: Decimal256::percent(25),
+            emission_cap: Decimal256::from_uint256(25u64),
+            emission_floor: Decimal256::percent(250),
+        }
+    );
+
+    // capped by the decayed cap
+    let query_msg = QueryMsg::AncEmissionRate {
+        deposit_rate: Decimal256::percent(5),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+        current_emission_rate: Decimal256::from_uint256(80u128),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: AncEmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!("25", &value.emission_rate.to_string());
+
+    // phases out of order
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSchedule {
+        emission_schedule: Some(EmissionSchedule::Piecewise {
+            phases: vec![
+                EmissionPhase {
+                    start_height: 12_000,
+                    scale: Decimal256::percent(50),
+                },
+                EmissionPhase {
+                    start_height: 12_000,
+                    scale: Decimal256::percent(10),
+                },
+            ],
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidEmissionSchedule(20)) => (),
+        _ => panic!("Must return invalid emission schedule error"),
+    }
+
+    // scaled by the phase started at 12_000
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSchedule {
+        emission_schedule: Some(EmissionSchedule::Piecewise {
+            phases: vec![
+                EmissionPhase {
+                    start_height: 10_000,
+                    scale: Decimal256::percent(80),
+                },
+                EmissionPhase {
+                    start_height: 12_000,
+                    scale: Decimal256::percent(50),
+                },
+                EmissionPhase {
+                    start_height: 20_000,
+                    scale: Decimal256::percent(10),
+                },
+            ],
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // raised to the decayed floor
+    let query_msg = QueryMsg::AncEmissionRate {
+        deposit_rate: Decimal256::percent(9),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+        current_emission_rate: Decimal256::from_uint256(4u128),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: AncEmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!("5", &value.emission_rate.to_string());
+
+    // back to the reactive adjustment only
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSchedule {
+        emission_schedule: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionSchedule {}).unwrap();
+    let value: EmissionScheduleResponse = from_binary(&res).unwrap();
+    assert_eq!(value.emission_schedule, None);
+    assert_eq!(value.scale, Decimal256::one());
+    assert_eq!(value.emission_cap, Decimal256::from_uint256(100u64));
+}
+
+#[test]
+fn market_emissions() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateMarketWeight {
+        market: "market0000".to_string(),
+        market_weight: Some(MarketWeight {
+            weight: Decimal256::one(),
+            emission_cap: None,
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMarketWeight {
+        market: "market0000".to_string(),
+        market_weight: Some(MarketWeight {
+            weight: Decimal256::zero(),
+            emission_cap: None,
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidMarketWeight(30)) => (),
+        _ => panic!("Must return invalid market weight error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMarketWeight {
+        market: "market0000".to_string(),
+        market_weight: Some(MarketWeight {
+            weight: Decimal256::one(),
+            emission_cap: None,
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::UpdateMarketWeight {
+        market: "market0001".to_string(),
+        market_weight: Some(MarketWeight {
+            weight: Decimal256::from_uint256(3u64),
+            emission_cap: Some(Decimal256::from_uint256(5u64)),
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        deposit_rate: Decimal256::percent(5),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // incremented from the floor
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        deposit_rate: Decimal256::percent(5),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionState {}).unwrap();
+    let value: EmissionStateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionStateResponse {
+            global_emission_rate: Decimal256::from_uint256(11u64),
+            total_weight: Decimal256::from_uint256(4u64),
+            last_updated_height: mock_env().block.height,
+        }
+    );
+
+    // market0001 is capped
+    let query_msg = QueryMsg::MarketEmissions {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: MarketEmissionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.markets,
+        vec![
+            MarketEmissionResponse {
+                market: "market0000".to_string(),
+                weight: Decimal256::one(),
+                emission_cap: None,
+                emission_rate: Decimal256::from_str("2.75").unwrap(),
+            },
+            MarketEmissionResponse {
+                market: "market0001".to_string(),
+                weight: Decimal256::from_uint256(3u64),
+                emission_cap: Some(Decimal256::from_uint256(5u64)),
+                emission_rate: Decimal256::from_uint256(5u64),
+            },
+        ]
+    );
+
+    let query_msg = QueryMsg::MarketEmissions {
+        start_after: Some("market0000".to_string()),
+        limit: Some(1),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: MarketEmissionsResponse = from_binary(&res).unwrap();
+    assert_eq!(value.markets.len(), 1);
+    assert_eq!(value.markets[0].market, "market0001");
+
+    // the whole emission goes to market0000 once market0001 is deregistered
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMarketWeight {
+        market: "market0001".to_string(),
+        market_weight: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::MarketEmissions {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: MarketEmissionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.markets,
+        vec![MarketEmissionResponse {
+            market: "market0000".to_string(),
+            weight: Decimal256::one(),
+            emission_cap: None,
+            emission_rate: Decimal256::from_uint256(11u64),
+        }]
+    );
+}
+
+#[test]
+fn emission_split() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // all to the borrowers until split
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionSplit {}).unwrap();
+    let value: EmissionSplitResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionSplitResponse {
+            borrower_share: Decimal256::one(),
+            depositor_share: Decimal256::zero(),
+        }
+    );
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSplit {
+        borrower_share: Decimal256::percent(75),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSplit {
+        borrower_share: Decimal256::percent(101),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidEmissionSplit {}) => (),
+        _ => panic!("Must return invalid emission split error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateEmissionSplit {
+        borrower_share: Decimal256::percent(75),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::AncEmissionRate {
+        deposit_rate: Decimal256::percent(5),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+        current_emission_rate: Decimal256::from_uint256(80u128),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: AncEmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        AncEmissionRateResponse {
+            emission_rate: Decimal256::from_uint256(88u64),
+            borrower_emission_rate: Decimal256::from_uint256(66u64),
+            depositor_emission_rate: Decimal256::from_uint256(22u64),
+        }
+    );
+}
+
+#[test]
+fn reserve_guard() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdateReserveHealth {},
+    );
+    match res {
+        Err(ContractError::NoReserveGuard {}) => (),
+        _ => panic!("Must return no reserve guard error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateReserveGuard {
+        reserve_guard: Some(ReserveGuard {
+            overseer_contract: "overseer0000".to_string(),
+            low_runway: Decimal256::from_uint256(20u64),
+            high_runway: Decimal256::from_uint256(20u64),
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidReserveGuard {}) => (),
+        _ => panic!("Must return invalid reserve guard error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateReserveGuard {
+        reserve_guard: Some(ReserveGuard {
+            overseer_contract: "overseer0000".to_string(),
+            low_runway: Decimal256::from_uint256(20u64),
+            high_runway: Decimal256::from_uint256(40u64),
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let update_reserve_health = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                                 runway: Option<u64>| {
+        deps.querier
+            .with_runway("overseer0000", runway.map(Decimal256::from_uint256));
+        let info = mock_info("addr0000", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::UpdateReserveHealth {},
+        )
+        .unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ReserveGuard {}).unwrap();
+        let value: ReserveGuardResponse = from_binary(&res).unwrap();
+        (value.throttled, value.scale)
+    };
+
+    // healthy above the low runway
+    assert_eq!(
+        update_reserve_health(&mut deps, Some(30)),
+        (false, Decimal256::one())
+    );
+    // throttled below it
+    assert_eq!(
+        update_reserve_health(&mut deps, Some(10)),
+        (true, Decimal256::percent(25))
+    );
+
+    // the cap is scaled down to 25
+    let query_msg = QueryMsg::AncEmissionRate {
+        deposit_rate: Decimal256::percent(5),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+        current_emission_rate: Decimal256::from_uint256(80u128),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: AncEmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!("25", &value.emission_rate.to_string());
+
+    // still throttled until the runway recovers to the high runway
+    assert_eq!(
+        update_reserve_health(&mut deps, Some(30)),
+        (true, Decimal256::percent(75))
+    );
+    assert_eq!(
+        update_reserve_health(&mut deps, Some(40)),
+        (false, Decimal256::one())
+    );
+    // down to zero with the buffer
+    assert_eq!(
+        update_reserve_health(&mut deps, Some(0)),
+        (true, Decimal256::zero())
+    );
+    // released when the buffer is not spent
+    assert_eq!(
+        update_reserve_health(&mut deps, None),
+        (false, Decimal256::one())
+    );
+}
+
+#[test]
+fn gauge_weights() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SyncGaugeWeights {},
+    );
+    match res {
+        Err(ContractError::NoGauge {}) => (),
+        _ => panic!("Must return no gauge error"),
+    }
+
+    // Unauthorized err
+    let gauge = Gauge {
+        contract_addr: "gauge0000".to_string(),
+        epoch_period: 100,
+        min_share: Decimal256::percent(10),
+        max_share: Decimal256::percent(60),
+    };
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateGauge {
+        gauge: Some(gauge.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateGauge {
+        gauge: Some(Gauge {
+            min_share: Decimal256::percent(70),
+            ..gauge.clone()
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidGauge {}) => (),
+        _ => panic!("Must return invalid gauge error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateGauge {
+        gauge: Some(gauge.clone()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    for market in ["market0000", "market0001", "market0002"].iter() {
+        let msg = ExecuteMsg::UpdateMarketWeight {
+            market: market.to_string(),
+            market_weight: Some(MarketWeight {
+                weight: Decimal256::one(),
+                emission_cap: None,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        deposit_rate: Decimal256::percent(10),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // market0000 is bounded to 60% and market0002 to 10% of the votes,
+    // and the emission is allocated in proportion to the bounded weights
+    deps.querier.with_gauge_weights(
+        "gauge0000",
+        &[("market0000", 80), ("market0001", 20), ("market0002", 0)],
+    );
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::SyncGaugeWeights {},
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::MarketEmissions {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: MarketEmissionsResponse = from_binary(&res).unwrap();
+    let mut weights = value
+        .markets
+        .iter()
+        .map(|market| (market.market.clone(), market.weight))
+        .collect::<Vec<(String, Decimal256)>>();
+    weights.sort();
+    assert_eq!(
+        weights,
+        vec![
+            ("market0000".to_string(), Decimal256::percent(60)),
+            ("market0001".to_string(), Decimal256::percent(20)),
+            ("market0002".to_string(), Decimal256::percent(10)),
+        ]
+    );
+
+    // once an epoch
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::SyncGaugeWeights {},
+    );
+    match res {
+        Err(ContractError::GaugeEpochNotPassed(_)) => (),
+        _ => panic!("Must return gauge epoch not passed error"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_gauge_weights(
+        "gauge0000",
+        &[("market0000", 40), ("market0001", 40), ("market0002", 20)],
+    );
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::SyncGaugeWeights {},
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::MarketEmissions {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: MarketEmissionsResponse = from_binary(&res).unwrap();
+    let mut emission_rates = value
+        .markets
+        .iter()
+        .map(|market| (market.market.clone(), market.emission_rate))
+        .collect::<Vec<(String, Decimal256)>>();
+    emission_rates.sort();
+    assert_eq!(
+        emission_rates,
+        vec![
+            ("market0000".to_string(), Decimal256::from_uint256(4u64)),
+            ("market0001".to_string(), Decimal256::from_uint256(4u64)),
+            ("market0002".to_string(), Decimal256::from_uint256(2u64)),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Gauge {}).unwrap();
+    let value: GaugeResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        GaugeResponse {
+            gauge: Some(gauge),
+            last_synced_time: env.block.time.seconds(),
+        }
+    );
+}
+
+#[test]
+fn emission_adjustments() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::AdjustEmission {
+        market: "market0000".to_string(),
+        delta: EmissionDelta::Credit {
+            amount: Uint256::from(100u64),
+        },
+        reason: "missed epoch".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::AdjustEmission {
+        market: "market0000".to_string(),
+        delta: EmissionDelta::Credit {
+            amount: Uint256::from(100u64),
+        },
+        reason: " ".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidEmissionAdjustment(256)) => (),
+        _ => panic!("Must return invalid emission adjustment error"),
+    }
+
+    let msg = ExecuteMsg::AdjustEmission {
+        market: "market0000".to_string(),
+        delta: EmissionDelta::Debit {
+            amount: Uint256::zero(),
+        },
+        reason: "missed epoch".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidEmissionAdjustment(256)) => (),
+        _ => panic!("Must return invalid emission adjustment error"),
+    }
+
+    let adjustments = vec![
+        (
+            "market0000",
+            EmissionDelta::Credit {
+                amount: Uint256::from(100u64),
+            },
+            "missed epoch",
+        ),
+        (
+            "market0001",
+            EmissionDelta::Debit {
+                amount: Uint256::from(50u64),
+            },
+            "overpaid epoch",
+        ),
+        (
+            "market0000",
+            EmissionDelta::Debit {
+                amount: Uint256::from(130u64),
+            },
+            "double counted credit",
+        ),
+    ];
+    for (market, delta, reason) in adjustments.clone().into_iter() {
+        let msg = ExecuteMsg::AdjustEmission {
+            market: market.to_string(),
+            delta,
+            reason: reason.to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    // the debit cancels the credit first
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingEmission {
+            market: "market0000".to_string(),
+        },
+    )
+    .unwrap();
+    let value: PendingEmissionResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        PendingEmissionResponse {
+            market: "market0000".to_string(),
+            credit: Uint256::zero(),
+            debit: Uint256::from(30u64),
+        }
+    );
+
+    let env = mock_env();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::EmissionAdjustments {
+            market: None,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: EmissionAdjustmentsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.adjustments,
+        adjustments
+            .iter()
+            .enumerate()
+            .map(|(i, (market, delta, reason))| EmissionAdjustmentResponse {
+                adjustment_id: i as u64 + 1,
+                market: market.to_string(),
+                delta: delta.clone(),
+                reason: reason.to_string(),
+                adjuster: "owner0000".to_string(),
+                height: env.block.height,
+                time: env.block.time.seconds(),
+            })
+            .collect::<Vec<EmissionAdjustmentResponse>>()
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::EmissionAdjustments {
+            market: Some("market0000".to_string()),
+            start_after: Some(1),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: EmissionAdjustmentsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value
+            .adjustments
+            .iter()
+            .map(|adjustment| adjustment.adjustment_id)
+            .collect::<Vec<u64>>(),
+        vec![3]
+    );
+}
+
+#[test]
+fn simulate_emission_update() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    for (market, weight) in [("market0000", 1u64), ("market0001", 3u64)].iter() {
+        let msg = ExecuteMsg::UpdateMarketWeight {
+            market: market.to_string(),
+            market_weight: Some(MarketWeight {
+                weight: Decimal256::from_uint256(*weight),
+                emission_cap: None,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+    let msg = ExecuteMsg::UpdateEmissionSplit {
+        borrower_share: Decimal256::percent(75),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // deposit rate below the low trigger, the emission is incremented
+    let query_msg = QueryMsg::SimulateEmissionUpdate {
+        deposit_rate: Decimal256::percent(4),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+        current_rate: Decimal256::from_uint256(40u64),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: SimulateEmissionUpdateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        SimulateEmissionUpdateResponse {
+            emission_rate: Decimal256::from_uint256(44u64),
+            borrower_emission_rate: Decimal256::from_uint256(33u64),
+            depositor_emission_rate: Decimal256::from_uint256(11u64),
+            emission_cap: Decimal256::from_uint256(100u64),
+            emission_floor: Decimal256::from_uint256(10u64),
+            markets: value.markets.clone(),
+        }
+    );
+    let mut emission_rates = value
+        .markets
+        .iter()
+        .map(|market| (market.market.clone(), market.emission_rate))
+        .collect::<Vec<(String, Decimal256)>>();
+    emission_rates.sort();
+    assert_eq!(
+        emission_rates,
+        vec![
+            ("market0000".to_string(), Decimal256::from_uint256(11u64)),
+            ("market0001".to_string(), Decimal256::from_uint256(33u64)),
+        ]
+    );
+
+    // nothing is updated
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionState {}).unwrap();
+    let value: EmissionStateResponse = from_binary(&res).unwrap();
+    assert_eq!(value.global_emission_rate, Decimal256::zero());
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::MarketEmissions {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: MarketEmissionsResponse = from_binary(&res).unwrap();
+    assert!(value
+        .markets
+        .iter()
+        .all(|market| market.emission_rate.is_zero()));
+}
+
+#[test]
+fn vesting() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    for market in ["market0000", "market0001"].iter() {
+        let msg = ExecuteMsg::UpdateMarketWeight {
+            market: market.to_string(),
+            market_weight: Some(MarketWeight {
+                weight: Decimal256::one(),
+                emission_cap: None,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let vesting = Vesting {
+        escrow_contract: "escrow0000".to_string(),
+        cliff: 100,
+        duration: 1000,
+    };
+
+    // Unauthorized err
+    let msg = ExecuteMsg::UpdateVesting {
+        market: "market0000".to_string(),
+        vesting: Some(vesting.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // unregistered market
+    let msg = ExecuteMsg::UpdateVesting {
+        market: "market0002".to_string(),
+        vesting: Some(vesting.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidVesting {}) => (),
+        _ => panic!("Must return invalid vesting error"),
+    }
+
+    let msg = ExecuteMsg::UpdateVesting {
+        market: "market0000".to_string(),
+        vesting: Some(Vesting {
+            cliff: 2000,
+            ..vesting.clone()
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidVesting {}) => (),
+        _ => panic!("Must return invalid vesting error"),
+    }
+
+    let msg = ExecuteMsg::UpdateVesting {
+        market: "market0000".to_string(),
+        vesting: Some(vesting.clone()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // nothing is routed before the first epoch
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        deposit_rate: Decimal256::percent(10),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert!(res.messages.is_empty());
+
+    // the vesting market is paid 5 per block into the escrow
+    let mut env = mock_env();
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "escrow0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&EscrowExecuteMsg::CreateVesting {
+                recipient: "market0000".to_string(),
+                amount: Uint256::from(50u64),
+                cliff_time: env.block.time.seconds() + 100,
+                end_time: env.block.time.seconds() + 1000,
+            })
+            .unwrap(),
+        })]
+    );
+
+    deps.querier.with_vesting_account(
+        "escrow0000",
+        "market0000",
+        VestingAccountResponse {
+            total_amount: Uint256::from(50u64),
+            vested_amount: Uint256::from(20u64),
+            claimed_amount: Uint256::from(5u64),
+        },
+    );
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Vesting {
+            market: "market0000".to_string(),
+        },
+    )
+    .unwrap();
+    let value: VestingResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        VestingResponse {
+            market: "market0000".to_string(),
+            vesting: Some(vesting),
+            routed_amount: Uint256::from(50u64),
+            vested_amount: Uint256::from(20u64),
+            claimable_amount: Uint256::from(15u64),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Vesting {
+            market: "market0001".to_string(),
+        },
+    )
+    .unwrap();
+    let value: VestingResponse = from_binary(&res).unwrap();
+    assert_eq!(value.vesting, None);
+    assert_eq!(value.routed_amount, Uint256::zero());
+}
+
+#[test]
+fn emission_budget() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Unauthorized err
+    let msg = ExecuteMsg::UpdateEmissionBudget {
+        emission_budget: Some(Uint256::from(150u64)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // the emission is held at the floor, 10 per block
+    let update_msg = ExecuteMsg::UpdateEmissionRate {
+        deposit_rate: Decimal256::percent(10),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+    };
+    let mut env = mock_env();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), update_msg.clone()).unwrap();
+    assert!(res.events.is_empty());
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), update_msg.clone()).unwrap();
+    assert!(res.events.is_empty());
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EmissionBudget {}).unwrap();
+    let value: EmissionBudgetResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionBudgetResponse {
+            emission_budget: Some(Uint256::from(150u64)),
+            emitted_amount: Uint256::from(100u64),
+            remaining_amount: Some(Uint256::from(50u64)),
+            exhausted: false,
+        }
+    );
+
+    // the budget is exhausted over the next epoch
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), update_msg.clone()).unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("emission_budget_exhausted").add_attribute("emitted_amount", "200")]
+    );
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EmissionState {}).unwrap();
+    let value: EmissionStateResponse = from_binary(&res).unwrap();
+    assert_eq!(value.global_emission_rate, Decimal256::zero());
+
+    let query_msg = QueryMsg::AncEmissionRate {
+        deposit_rate: Decimal256::percent(10),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+        current_emission_rate: Decimal256::from_uint256(10u64),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap();
+    let value: AncEmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!(value.emission_rate, Decimal256::zero());
+
+    // nothing more is emitted, and the event is not repeated
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), update_msg).unwrap();
+    assert!(res.events.is_empty());
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EmissionBudget {}).unwrap();
+    let value: EmissionBudgetResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionBudgetResponse {
+            emission_budget: Some(Uint256::from(150u64)),
+            emitted_amount: Uint256::from(200u64),
+            remaining_amount: Some(Uint256::zero()),
+            exhausted: true,
+        }
+    );
+
+    // a raised budget resumes the emission
+    let msg = ExecuteMsg::UpdateEmissionBudget {
+        emission_budget: Some(Uint256::from(1000u64)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query(deps.as_ref(), env, query_msg).unwrap();
+    let value: AncEmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!(value.emission_rate, Decimal256::from_uint256(10u64));
+}
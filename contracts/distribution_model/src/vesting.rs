@@ -0,0 +1,180 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_all_market_emissions, read_config, read_market_emission, read_vesting, store_vesting,
+    Config, VestingInfo,
+};
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response,
+    StdError, StdResult, WasmMsg, WasmQuery,
+};
+use moneymarket::distribution_model::{Vesting, VestingResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Message to the vesting escrow, locking an amount for a recipient.
+/// The escrow draws the amount from the distributor
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowExecuteMsg {
+    CreateVesting {
+        recipient: String,
+        amount: Uint256,
+        cliff_time: u64,
+        end_time: u64,
+    },
+}
+
+/// Query of the vesting escrow for the vestings of a recipient
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowQueryMsg {
+    VestingAccount { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingAccountResponse {
+    pub total_amount: Uint256,
+    pub vested_amount: Uint256,
+    pub claimed_amount: Uint256,
+}
+
+pub fn update_vesting(
+    deps: DepsMut,
+    info: MessageInfo,
+    market: Addr,
+    vesting: Option<Vesting>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let mut attributes = vec![
+        attr("action", "update_vesting"),
+        attr("market", market.as_str()),
+    ];
+    let vesting = match vesting {
+        Some(vesting) => {
+            if vesting.duration == 0
+                || vesting.cliff > vesting.duration
+                || read_market_emission(deps.storage, &market_raw)?.is_none()
+            {
+                return Err(ContractError::InvalidVesting {});
+            }
+
+            attributes.push(attr("escrow_contract", vesting.escrow_contract.as_str()));
+            // the amount routed so far is kept across updates
+            let routed_amount = read_vesting(deps.storage, &market_raw)?
+                .map(|vesting| vesting.routed_amount)
+                .unwrap_or_default();
+            Some(VestingInfo {
+                escrow_contract: deps.api.addr_canonicalize(
+                    deps.api.addr_validate(&vesting.escrow_contract)?.as_str(),
+                )?,
+                cliff: vesting.cliff,
+                duration: vesting.duration,
+                routed_amount,
+            })
+        }
+        None => None,
+    };
+    store_vesting(deps.storage, &market_raw, vesting.as_ref())?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Routes the emission of the vesting markets over the passed blocks,
+/// at the rates allocated before them, into their escrows
+pub(crate) fn route_vesting_emissions(
+    deps: DepsMut,
+    env: &Env,
+    passed_blocks: u64,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
+    if passed_blocks == 0 {
+        return Ok(messages);
+    }
+
+    for (market, market_emission) in read_all_market_emissions(deps.storage)?.into_iter() {
+        let mut vesting = match read_vesting(deps.storage, &market)? {
+            Some(vesting) => vesting,
+            None => continue,
+        };
+
+        let amount = Uint256::from(passed_blocks) * market_emission.emission_rate;
+        if amount.is_zero() {
+            continue;
+        }
+
+        vesting.routed_amount += amount;
+        store_vesting(deps.storage, &market, Some(&vesting))?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&vesting.escrow_contract)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&EscrowExecuteMsg::CreateVesting {
+                recipient: deps.api.addr_humanize(&market)?.to_string(),
+                amount,
+                cliff_time: env.block.time.seconds() + vesting.cliff,
+                end_time: env.block.time.seconds() + vesting.duration,
+            })?,
+        }));
+    }
+
+    Ok(messages)
+}
+
+pub fn query_vesting(deps: Deps, market: Addr) -> StdResult<VestingResponse> {
+    let vesting = read_vesting(deps.storage, &deps.api.addr_canonicalize(market.as_str())?)?;
+    let routed_amount = vesting
+        .as_ref()
+        .map(|vesting| vesting.routed_amount)
+        .unwrap_or_default();
+
+    let (vested_amount, claimable_amount) = match vesting.as_ref() {
+        Some(vesting) if !routed_amount.is_zero() => {
+            let vesting_account: VestingAccountResponse =
+                deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: deps
+                        .api
+                        .addr_humanize(&vesting.escrow_contract)?
+                        .to_string(),
+                    msg: to_binary(&EscrowQueryMsg::VestingAccount {
+                        address: market.to_string(),
+                    })?,
+                }))?;
+            if vesting_account.claimed_amount > vesting_account.vested_amount {
+                return Err(StdError::generic_err("Escrow claimed more than it vested"));
+            }
+
+            (
+                vesting_account.vested_amount,
+                vesting_account.vested_amount - vesting_account.claimed_amount,
+            )
+        }
+        _ => (Uint256::zero(), Uint256::zero()),
+    };
+
+    Ok(VestingResponse {
+        market: market.to_string(),
+        vesting: vesting
+            .map(|vesting| {
+                Ok::<Vesting, StdError>(Vesting {
+                    escrow_contract: deps
+                        .api
+                        .addr_humanize(&vesting.escrow_contract)?
+                        .to_string(),
+                    cliff: vesting.cliff,
+                    duration: vesting.duration,
+                })
+            })
+            .transpose()?,
+        routed_amount,
+        vested_amount,
+        claimable_amount,
+    })
+}
@@ -1,29 +1,68 @@
+use crate::controller::{
+    adjust_rate, query_market_utilization, query_rate_controller, update_rate_controller,
+};
 use crate::error::ContractError;
-use crate::state::{read_config, store_config, Config};
+use crate::fixed_rate::{
+    query_fixed_rate_config, query_fixed_rate_quote, update_fixed_rate_config,
+};
+use crate::smoothing::{
+    query_utilization_average, record_utilization, smoothed_utilization,
+    update_utilization_smoothing,
+};
+use crate::state::{
+    read_config, read_curve, read_legacy_config, read_market_curves, store_config, Config, Curve,
+};
+use crate::timelock::{
+    assert_config_bounds, cancel_config_update, execute_config_update, propose_config_update,
+    query_pending_config,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
+use moneymarket::math::{
+    checked_add, checked_div, checked_from_uint256, checked_mul, checked_sub, saturating_sub,
+};
 
 use cosmwasm_bignumber::Decimal256;
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, OverflowError, OverflowOperation,
+    Response, StdError, StdResult,
+};
 use moneymarket::common::optional_addr_validate;
 use moneymarket::interest_model::{
-    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BacktestResponse, BorrowRateResponse, ConfigResponse, DepositRateResponse, ExecuteMsg,
+    InstantiateMsg, MarketsResponse, MigrateMsg, QueryMsg, RateCurvePoint, RateCurveResponse,
+    RateModelResponse,
 };
 
+// maximum number of points of a rate curve
+const MAX_RATE_CURVE_POINTS: u32 = 101;
+// maximum number of utilizations of a backtest path
+const MAX_BACKTEST_STEPS: u32 = 1000;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    assert_config_bounds(
+        Some(msg.base_rate),
+        Some(msg.optimal_utilization),
+        Some(msg.slope1),
+        Some(msg.slope2),
+        Some(msg.reserve_factor),
+    )?;
     store_config(
         deps.storage,
         &Config {
             owner: deps.api.addr_canonicalize(&msg.owner)?,
             base_rate: msg.base_rate,
-            interest_multiplier: msg.interest_multiplier,
+            optimal_utilization: msg.optimal_utilization,
+            slope1: msg.slope1,
+            slope2: msg.slope2,
+            reserve_factor: msg.reserve_factor,
         },
     )?;
 
@@ -33,70 +72,162 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::UpdateConfig {
+            market,
             owner,
             base_rate,
-            interest_multiplier,
+            optimal_utilization,
+            slope1,
+            slope2,
+            reserve_factor,
         } => {
             let api = deps.api;
-            update_config(
+            propose_config_update(
                 deps,
+                env,
                 info,
+                optional_addr_validate(api, market)?,
                 optional_addr_validate(api, owner)?,
                 base_rate,
-                interest_multiplier,
+                optimal_utilization,
+                slope1,
+                slope2,
+                reserve_factor,
             )
         }
+        ExecuteMsg::UpdateRateController { rate_controller } => {
+            update_rate_controller(deps, env, info, rate_controller)
+        }
+        ExecuteMsg::ExecuteConfigUpdate { market } => {
+            let api = deps.api;
+            execute_config_update(deps, env, info, optional_addr_validate(api, market)?)
+        }
+        ExecuteMsg::CancelConfigUpdate { market } => {
+            let api = deps.api;
+            cancel_config_update(deps, info, optional_addr_validate(api, market)?)
+        }
+        ExecuteMsg::AdjustRate {} => adjust_rate(deps, env),
+        ExecuteMsg::UpdateUtilizationSmoothing {
+            market,
+            utilization_smoothing,
+        } => {
+            let market = deps.api.addr_validate(&market)?;
+            update_utilization_smoothing(deps, env, info, market, utilization_smoothing)
+        }
+        ExecuteMsg::RecordUtilization {
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        } => record_utilization(
+            deps,
+            env,
+            info,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        ),
+        ExecuteMsg::UpdateFixedRateConfig { fixed_rate_config } => {
+            update_fixed_rate_config(deps, info, fixed_rate_config)
+        }
     }
 }
 
-pub fn update_config(
-    deps: DepsMut,
-    info: MessageInfo,
-    owner: Option<Addr>,
-    base_rate: Option<Decimal256>,
-    interest_multiplier: Option<Decimal256>,
-) -> Result<Response, ContractError> {
-    let mut config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
-    }
-
-    if let Some(base_rate) = base_rate {
-        config.base_rate = base_rate;
-    }
-
-    if let Some(interest_multiplier) = interest_multiplier {
-        config.interest_multiplier = interest_multiplier;
-    }
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // a 100% optimal utilization keeps the linear rate
+    let legacy_config = read_legacy_config(deps.storage)?;
+    let config = Config {
+        owner: legacy_config.owner,
+        base_rate: legacy_config.base_rate,
+        optimal_utilization: Decimal256::one(),
+        slope1: legacy_config.interest_multiplier,
+        slope2: Decimal256::zero(),
+        reserve_factor: Decimal256::zero(),
+    };
 
+    // a legacy config out of the bounds fails the migration
+    assert_config_bounds(
+        Some(config.base_rate),
+        Some(config.optimal_utilization),
+        Some(config.slope1),
+        Some(config.slope2),
+        Some(config.reserve_factor),
+    )?;
     store_config(deps.storage, &config)?;
+
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::BorrowRate {
             market_balance,
             total_liabilities,
             total_reserves,
+            market,
         } => to_binary(&query_borrow_rate(
             deps,
+            env,
             market_balance,
             total_liabilities,
             total_reserves,
+            optional_addr_validate(deps.api, market)?,
+        )?),
+        QueryMsg::RateModel { market } => to_binary(&query_rate_model(
+            deps,
+            optional_addr_validate(deps.api, market)?,
+        )?),
+        QueryMsg::DepositRate { market } => to_binary(&query_deposit_rate(
+            deps,
+            env,
+            deps.api.addr_validate(&market)?,
+        )?),
+        QueryMsg::QuoteFixedRate {
+            market,
+            amount,
+            duration,
+        } => to_binary(&query_fixed_rate_quote(
+            deps,
+            env,
+            deps.api.addr_validate(&market)?,
+            amount,
+            duration,
+        )?),
+        QueryMsg::Backtest {
+            utilization_path,
+            blocks_per_step,
+            market,
+        } => to_binary(&query_backtest(
+            deps,
+            utilization_path,
+            blocks_per_step.unwrap_or(1),
+            optional_addr_validate(deps.api, market)?,
+        )?),
+        QueryMsg::RateCurve { points, market } => to_binary(&query_rate_curve(
+            deps,
+            points,
+            optional_addr_validate(deps.api, market)?,
         )?),
+        QueryMsg::RateController {} => to_binary(&query_rate_controller(deps)?),
+        QueryMsg::FixedRateConfig {} => to_binary(&query_fixed_rate_config(deps)?),
+        QueryMsg::UtilizationAverage { market } => to_binary(&query_utilization_average(
+            deps,
+            deps.api.addr_validate(&market)?,
+        )?),
+        QueryMsg::PendingConfig { market } => to_binary(&query_pending_config(
+            deps,
+            optional_addr_validate(deps.api, market)?,
+        )?),
+        QueryMsg::Markets { start_after, limit } => {
+            to_binary(&query_markets(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -105,7 +236,10 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         base_rate: state.base_rate,
-        interest_multiplier: state.interest_multiplier,
+        optimal_utilization: state.optimal_utilization,
+        slope1: state.slope1,
+        slope2: state.slope2,
+        reserve_factor: state.reserve_factor,
     };
 
     Ok(resp)
@@ -113,23 +247,207 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 
 fn query_borrow_rate(
     deps: Deps,
+    env: Env,
     market_balance: Uint256,
     total_liabilities: Decimal256,
     total_reserves: Decimal256,
+    market: Option<Addr>,
 ) -> StdResult<BorrowRateResponse> {
-    let config: Config = read_config(deps.storage)?;
+    let market_raw = market
+        .map(|market| deps.api.addr_canonicalize(market.as_str()))
+        .transpose()?;
+    let curve = read_curve(deps.storage, market_raw.as_ref())?;
+
+    let mut utilization = utilization_ratio(market_balance, total_liabilities, total_reserves)?;
+    if let Some(market_raw) = market_raw.as_ref() {
+        utilization = smoothed_utilization(deps, &env, market_raw, utilization)?;
+    }
 
+    Ok(BorrowRateResponse {
+        rate: borrow_rate(&curve, utilization)?,
+    })
+}
+
+/// Utilization ratio of the market, saturating at 100% when bad debt
+/// leaves the liabilities above the value of the market net of reserves
+pub(crate) fn utilization_ratio(
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> StdResult<Decimal256> {
     // ignore decimal parts
-    let total_value_in_market =
-        Decimal256::from_uint256(market_balance) + total_liabilities - total_reserves;
+    let total_value_in_market = saturating_sub(
+        checked_add(checked_from_uint256(market_balance)?, total_liabilities)?,
+        total_reserves,
+    );
+
+    if total_liabilities.is_zero() {
+        Ok(Decimal256::zero())
+    } else if total_liabilities >= total_value_in_market {
+        Ok(Decimal256::one())
+    } else {
+        checked_div(total_liabilities, total_value_in_market)
+    }
+}
+
+/// Borrow rate at the utilization ratio, `slope1` applying past the
+/// optimal utilization when it is 100%
+pub(crate) fn borrow_rate(curve: &Curve, utilization_ratio: Decimal256) -> StdResult<Decimal256> {
+    if utilization_ratio <= curve.optimal_utilization
+        || curve.optimal_utilization == Decimal256::one()
+    {
+        checked_add(
+            curve.base_rate,
+            checked_mul(
+                checked_div(utilization_ratio, curve.optimal_utilization)?,
+                curve.slope1,
+            )?,
+        )
+    } else {
+        let excess_utilization = checked_div(
+            checked_sub(utilization_ratio, curve.optimal_utilization)?,
+            checked_sub(Decimal256::one(), curve.optimal_utilization)?,
+        )?;
+        checked_add(
+            checked_add(curve.base_rate, curve.slope1)?,
+            checked_mul(excess_utilization, curve.slope2)?,
+        )
+    }
+}
+
+/// Deposit rate at the utilization ratio. The deposits share the borrow
+/// interest of the liabilities, less the reserve factor
+pub(crate) fn deposit_rate(curve: &Curve, utilization_ratio: Decimal256) -> StdResult<Decimal256> {
+    checked_mul(
+        checked_mul(borrow_rate(curve, utilization_ratio)?, utilization_ratio)?,
+        checked_sub(Decimal256::one(), curve.reserve_factor)?,
+    )
+}
+
+fn read_market_or_default_curve(deps: Deps, market: Option<Addr>) -> StdResult<Curve> {
+    let market_raw = market
+        .map(|market| deps.api.addr_canonicalize(market.as_str()))
+        .transpose()?;
+    read_curve(deps.storage, market_raw.as_ref())
+}
+
+fn query_rate_model(deps: Deps, market: Option<Addr>) -> StdResult<RateModelResponse> {
+    let curve = read_market_or_default_curve(deps, market)?;
+
+    Ok(RateModelResponse {
+        base_rate: curve.base_rate,
+        optimal_utilization: curve.optimal_utilization,
+        optimal_rate: borrow_rate(&curve, curve.optimal_utilization)?,
+        max_rate: borrow_rate(&curve, Decimal256::one())?,
+    })
+}
+
+fn query_deposit_rate(deps: Deps, env: Env, market: Addr) -> StdResult<DepositRateResponse> {
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let curve = read_curve(deps.storage, Some(&market_raw))?;
+    let utilization = smoothed_utilization(
+        deps,
+        &env,
+        &market_raw,
+        query_market_utilization(deps, market.clone())?,
+    )?;
 
-    let utilization_ratio = if total_value_in_market.is_zero() {
-        Decimal256::zero()
+    Ok(DepositRateResponse {
+        market: market.to_string(),
+        utilization,
+        borrow_rate: borrow_rate(&curve, utilization)?,
+        deposit_rate: deposit_rate(&curve, utilization)?,
+    })
+}
+
+fn query_rate_curve(deps: Deps, points: u32, market: Option<Addr>) -> StdResult<RateCurveResponse> {
+    if !(2..=MAX_RATE_CURVE_POINTS).contains(&points) {
+        return Err(StdError::generic_err(
+            ContractError::InvalidRateCurvePoints(MAX_RATE_CURVE_POINTS).to_string(),
+        ));
+    }
+
+    let curve = read_market_or_default_curve(deps, market)?;
+    Ok(RateCurveResponse {
+        points: (0..points)
+            .map(|i| {
+                let utilization = Decimal256::from_ratio(i as u64, (points - 1) as u64);
+                Ok(RateCurvePoint {
+                    utilization,
+                    borrow_rate: borrow_rate(&curve, utilization)?,
+                    deposit_rate: deposit_rate(&curve, utilization)?,
+                })
+            })
+            .collect::<StdResult<Vec<RateCurvePoint>>>()?,
+    })
+}
+
+/// Compound the interest of each step, accrued at the rates of its
+/// utilization as the market accrues it between two updates
+fn query_backtest(
+    deps: Deps,
+    utilization_path: Vec<Decimal256>,
+    blocks_per_step: u64,
+    market: Option<Addr>,
+) -> StdResult<BacktestResponse> {
+    if utilization_path.is_empty()
+        || utilization_path.len() > MAX_BACKTEST_STEPS as usize
+        || utilization_path
+            .iter()
+            .any(|utilization| *utilization > Decimal256::one())
+    {
+        return Err(StdError::generic_err(
+            ContractError::InvalidUtilizationPath(MAX_BACKTEST_STEPS).to_string(),
+        ));
+    }
+
+    let curve = read_market_or_default_curve(deps, market)?;
+    let step_blocks = Decimal256::from_uint256(Uint256::from(blocks_per_step));
+    let mut interest_factor = Decimal256::one();
+    let mut deposit_factor = Decimal256::one();
+    for utilization in utilization_path.iter() {
+        interest_factor = checked_mul(
+            interest_factor,
+            checked_add(
+                Decimal256::one(),
+                checked_mul(borrow_rate(&curve, *utilization)?, step_blocks)?,
+            )?,
+        )?;
+        deposit_factor = checked_mul(
+            deposit_factor,
+            checked_add(
+                Decimal256::one(),
+                checked_mul(deposit_rate(&curve, *utilization)?, step_blocks)?,
+            )?,
+        )?;
+    }
+
+    let steps = utilization_path.len() as u64;
+    Ok(BacktestResponse {
+        interest_factor,
+        deposit_factor,
+        blocks: blocks_per_step.checked_mul(steps).ok_or_else(|| {
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Mul,
+                blocks_per_step,
+                steps,
+            ))
+        })?,
+    })
+}
+
+fn query_markets(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MarketsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(&start_after)?)
     } else {
-        total_liabilities / total_value_in_market
+        None
     };
 
-    Ok(BorrowRateResponse {
-        rate: utilization_ratio * config.interest_multiplier + config.base_rate,
+    Ok(MarketsResponse {
+        markets: read_market_curves(deps, start_after, limit)?,
     })
 }
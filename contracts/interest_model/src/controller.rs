@@ -0,0 +1,207 @@
+use crate::contract::utilization_ratio;
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_controller_state, read_market_curve, read_rate_controller, store_config,
+    store_controller_state, store_market_curve, store_rate_controller, Config, ControllerState,
+    RateControllerInfo,
+};
+use crate::timelock::{assert_controller_bounds, max_slope1};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+use moneymarket::interest_model::{RateController, RateControllerResponse};
+use moneymarket::market::{
+    ConfigResponse as MarketConfigResponse, MarketQuerier, StateResponse as MarketStateResponse,
+};
+use moneymarket::math::{checked_add, checked_mul, saturating_sub};
+use moneymarket::querier::query_balance;
+
+pub fn update_rate_controller(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rate_controller: Option<RateController>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_rate_controller")];
+    let rate_controller_info = match rate_controller {
+        Some(rate_controller) => {
+            if rate_controller.target_utilization.is_zero()
+                || rate_controller.target_utilization >= Decimal256::one()
+                || rate_controller.epoch_period == 0
+                || rate_controller.min_slope1 > rate_controller.max_slope1
+                || rate_controller.max_slope1 > max_slope1()
+            {
+                return Err(ContractError::InvalidRateController {});
+            }
+            assert_controller_bounds(rate_controller.epoch_period, rate_controller.max_step)?;
+
+            attributes.push(attr(
+                "target_utilization",
+                rate_controller.target_utilization.to_string(),
+            ));
+            Some(RateControllerInfo {
+                market_contract: deps
+                    .api
+                    .addr_canonicalize(&rate_controller.market_contract)?,
+                target_utilization: rate_controller.target_utilization,
+                epoch_period: rate_controller.epoch_period,
+                gain: rate_controller.gain,
+                max_step: rate_controller.max_step,
+                min_slope1: rate_controller.min_slope1,
+                max_slope1: rate_controller.max_slope1,
+            })
+        }
+        None => None,
+    };
+
+    // the first adjustment is an epoch after the controller is set
+    let controller_state = rate_controller_info.as_ref().map(|_| ControllerState {
+        last_adjusted_time: env.block.time.seconds(),
+        last_utilization: Decimal256::zero(),
+    });
+    store_controller_state(deps.storage, controller_state)?;
+    store_rate_controller(deps.storage, rate_controller_info)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn adjust_rate(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let rate_controller =
+        read_rate_controller(deps.storage)?.ok_or(ContractError::NoRateController {})?;
+    let mut controller_state =
+        read_controller_state(deps.storage)?.ok_or(ContractError::NoRateController {})?;
+    if env.block.time.seconds() < controller_state.last_adjusted_time + rate_controller.epoch_period
+    {
+        return Err(ContractError::EpochNotPassed(
+            controller_state.last_adjusted_time,
+        ));
+    }
+
+    // the curve of the market when it has one, the default curve otherwise
+    let utilization = query_market_utilization(
+        deps.as_ref(),
+        deps.api.addr_humanize(&rate_controller.market_contract)?,
+    )?;
+    let slope1 = match read_market_curve(deps.storage, &rate_controller.market_contract)? {
+        Some(mut curve) => {
+            curve.slope1 = adjusted_slope1(&rate_controller, curve.slope1, utilization)?;
+            store_market_curve(deps.storage, &rate_controller.market_contract, &curve)?;
+            curve.slope1
+        }
+        None => {
+            let mut config: Config = read_config(deps.storage)?;
+            config.slope1 = adjusted_slope1(&rate_controller, config.slope1, utilization)?;
+            store_config(deps.storage, &config)?;
+            config.slope1
+        }
+    };
+
+    controller_state.last_adjusted_time = env.block.time.seconds();
+    controller_state.last_utilization = utilization;
+    store_controller_state(deps.storage, Some(controller_state))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "adjust_rate"),
+        attr("utilization", utilization.to_string()),
+        attr("slope1", slope1.to_string()),
+    ]))
+}
+
+/// slope1 moved by the bounded step toward the target utilization;
+/// a slope1 out of the controller bounds is brought back by the same step
+pub(crate) fn adjusted_slope1(
+    rate_controller: &RateControllerInfo,
+    slope1: Decimal256,
+    utilization: Decimal256,
+) -> StdResult<Decimal256> {
+    let adjusted_slope1 = if utilization > rate_controller.target_utilization {
+        let step = std::cmp::min(
+            checked_mul(
+                rate_controller.gain,
+                utilization - rate_controller.target_utilization,
+            )?,
+            rate_controller.max_step,
+        );
+        checked_add(slope1, step)?
+    } else {
+        let step = std::cmp::min(
+            checked_mul(
+                rate_controller.gain,
+                rate_controller.target_utilization - utilization,
+            )?,
+            rate_controller.max_step,
+        );
+        saturating_sub(slope1, step)
+    };
+
+    let adjusted_slope1 = std::cmp::min(
+        std::cmp::max(adjusted_slope1, rate_controller.min_slope1),
+        rate_controller.max_slope1,
+    );
+
+    Ok(std::cmp::max(
+        std::cmp::min(
+            adjusted_slope1,
+            checked_add(slope1, rate_controller.max_step)?,
+        ),
+        saturating_sub(slope1, rate_controller.max_step),
+    ))
+}
+
+/// Utilization of the market, from its liabilities, reserves and balance
+pub(crate) fn query_market_utilization(deps: Deps, market_contract: Addr) -> StdResult<Decimal256> {
+    let (market_balance, total_liabilities, total_reserves) =
+        query_market_state(deps, market_contract)?;
+
+    utilization_ratio(market_balance, total_liabilities, total_reserves)
+}
+
+/// Balance, liabilities and reserves of the market
+pub(crate) fn query_market_state(
+    deps: Deps,
+    market_contract: Addr,
+) -> StdResult<(Uint256, Decimal256, Decimal256)> {
+    let market = MarketQuerier::new(deps.querier, market_contract.clone());
+    let market_config: MarketConfigResponse = market.config()?;
+    let market_state: MarketStateResponse = market.state(None)?;
+    let market_balance = query_balance(deps, market_contract, market_config.stable_denom)?;
+
+    Ok((
+        market_balance,
+        market_state.total_liabilities,
+        market_state.total_reserves,
+    ))
+}
+
+pub fn query_rate_controller(deps: Deps) -> StdResult<RateControllerResponse> {
+    let rate_controller = read_rate_controller(deps.storage)?;
+    let controller_state = read_controller_state(deps.storage)?;
+
+    Ok(RateControllerResponse {
+        rate_controller: rate_controller
+            .map(|rate_controller| {
+                Ok::<RateController, StdError>(RateController {
+                    market_contract: deps
+                        .api
+                        .addr_humanize(&rate_controller.market_contract)?
+                        .to_string(),
+                    target_utilization: rate_controller.target_utilization,
+                    epoch_period: rate_controller.epoch_period,
+                    gain: rate_controller.gain,
+                    max_step: rate_controller.max_step,
+                    min_slope1: rate_controller.min_slope1,
+                    max_slope1: rate_controller.max_slope1,
+                })
+            })
+            .transpose()?,
+        last_adjusted_time: controller_state
+            .as_ref()
+            .map(|controller_state| controller_state.last_adjusted_time),
+        last_utilization: controller_state
+            .map(|controller_state| controller_state.last_utilization),
+    })
+}
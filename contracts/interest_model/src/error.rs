@@ -11,4 +11,43 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("{0} is out of its bounds")]
+    ConfigOutOfBounds(String),
+
+    #[error("Rate curve must be sampled at 2 to {0} points")]
+    InvalidRateCurvePoints(u32),
+
+    #[error("Utilization smoothing period must be positive")]
+    InvalidUtilizationSmoothing {},
+
+    #[error("Duration premium must be at most 1 and max duration positive")]
+    InvalidFixedRateConfig {},
+
+    #[error("Fixed rates are not quoted")]
+    NoFixedRateConfig {},
+
+    #[error("Fixed rate duration must be positive and at most {0}")]
+    InvalidFixedRateDuration(u64),
+
+    #[error("Not enough liquidity in the market to borrow {0}")]
+    InsufficientLiquidity(String),
+
+    #[error("Utilization path must have 1 to {0} utilizations of at most 1")]
+    InvalidUtilizationPath(u32),
+
+    #[error("No config update is pending")]
+    NoPendingConfig {},
+
+    #[error("Config update is not executable before {0}")]
+    ConfigUpdateNotReady(u64),
+
+    #[error("Invalid rate controller")]
+    InvalidRateController {},
+
+    #[error("No rate controller is set")]
+    NoRateController {},
+
+    #[error("An epoch has not passed yet; last adjusted time: {0}")]
+    EpochNotPassed(u64),
 }
@@ -0,0 +1,127 @@
+use crate::contract::{borrow_rate, utilization_ratio};
+use crate::controller::query_market_state;
+use crate::error::ContractError;
+use crate::smoothing::smoothed_utilization;
+use crate::state::{
+    read_config, read_curve, read_fixed_rate_config, store_fixed_rate_config, Config,
+    FixedRateConfig,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+use moneymarket::interest_model::{
+    FixedRateConfig as FixedRateConfigMsg, FixedRateConfigResponse, FixedRateQuoteResponse,
+};
+use moneymarket::math::{checked_add, checked_from_uint256, checked_mul};
+
+const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+pub fn update_fixed_rate_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    fixed_rate_config: Option<FixedRateConfigMsg>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_fixed_rate_config")];
+    let fixed_rate_config = match fixed_rate_config {
+        Some(fixed_rate_config) => {
+            if fixed_rate_config.duration_premium > Decimal256::one()
+                || fixed_rate_config.max_duration == 0
+            {
+                return Err(ContractError::InvalidFixedRateConfig {});
+            }
+
+            attributes.push(attr(
+                "duration_premium",
+                fixed_rate_config.duration_premium.to_string(),
+            ));
+            attributes.push(attr(
+                "max_duration",
+                fixed_rate_config.max_duration.to_string(),
+            ));
+            Some(FixedRateConfig {
+                duration_premium: fixed_rate_config.duration_premium,
+                max_duration: fixed_rate_config.max_duration,
+            })
+        }
+        None => None,
+    };
+    store_fixed_rate_config(deps.storage, fixed_rate_config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Fixed rate of the loan, priced at the utilization of the market once
+/// the loan is taken. The utilization is not quoted below its average
+/// when the market is smoothed, so a same-block deposit does not lower
+/// the rate locked for the duration
+pub fn query_fixed_rate_quote(
+    deps: Deps,
+    env: Env,
+    market: Addr,
+    amount: Uint256,
+    duration: u64,
+) -> StdResult<FixedRateQuoteResponse> {
+    let fixed_rate_config = read_fixed_rate_config(deps.storage)?
+        .ok_or_else(|| StdError::generic_err(ContractError::NoFixedRateConfig {}.to_string()))?;
+    if duration == 0 || duration > fixed_rate_config.max_duration {
+        return Err(StdError::generic_err(
+            ContractError::InvalidFixedRateDuration(fixed_rate_config.max_duration).to_string(),
+        ));
+    }
+
+    let (market_balance, total_liabilities, total_reserves) =
+        query_market_state(deps, market.clone())?;
+    if amount > market_balance {
+        return Err(StdError::generic_err(
+            ContractError::InsufficientLiquidity(amount.to_string()).to_string(),
+        ));
+    }
+
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let utilization = utilization_ratio(
+        market_balance - amount,
+        checked_add(total_liabilities, checked_from_uint256(amount)?)?,
+        total_reserves,
+    )?;
+    let utilization = std::cmp::max(
+        utilization,
+        smoothed_utilization(deps, &env, &market_raw, utilization)?,
+    );
+
+    let curve = read_curve(deps.storage, Some(&market_raw))?;
+    let floating_rate = borrow_rate(&curve, utilization)?;
+    let fixed_rate = checked_mul(
+        floating_rate,
+        checked_add(
+            Decimal256::one(),
+            checked_mul(
+                fixed_rate_config.duration_premium,
+                Decimal256::from_ratio(duration, SECONDS_PER_YEAR),
+            )?,
+        )?,
+    )?;
+
+    Ok(FixedRateQuoteResponse {
+        market: market.to_string(),
+        amount,
+        duration,
+        utilization,
+        floating_rate,
+        fixed_rate,
+    })
+}
+
+pub fn query_fixed_rate_config(deps: Deps) -> StdResult<FixedRateConfigResponse> {
+    let fixed_rate_config = read_fixed_rate_config(deps.storage)?;
+
+    Ok(FixedRateConfigResponse {
+        fixed_rate_config: fixed_rate_config.map(|fixed_rate_config| FixedRateConfigMsg {
+            duration_premium: fixed_rate_config.duration_premium,
+            max_duration: fixed_rate_config.max_duration,
+        }),
+    })
+}
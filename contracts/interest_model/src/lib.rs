@@ -1,6 +1,12 @@
 pub mod contract;
+pub mod controller;
 pub mod error;
+pub mod fixed_rate;
+pub mod smoothing;
 pub mod state;
+pub mod timelock;
 
+#[cfg(test)]
+mod mock_querier;
 #[cfg(test)]
 mod tests;
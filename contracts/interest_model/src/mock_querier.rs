@@ -0,0 +1,114 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+use moneymarket::market::{
+    ConfigResponse as MarketConfigResponse, QueryMsg as MarketQueryMsg,
+    StateResponse as MarketStateResponse,
+};
+use std::collections::HashMap;
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[(&str, &[Coin])],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: WasmMockQuerier::new(MockQuerier::new(contract_balance)),
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    // total liabilities and reserves of the market contracts, by contract
+    market_states: HashMap<String, (Decimal256, Decimal256)>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    MarketQueryMsg::Config {} => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&MarketConfigResponse {
+                            owner_addr: "owner0000".to_string(),
+                            aterra_contract: "aterra".to_string(),
+                            interest_model: "interest".to_string(),
+                            distribution_model: "distribution".to_string(),
+                            overseer_contract: "overseer".to_string(),
+                            collector_contract: "collector".to_string(),
+                            distributor_contract: "distributor".to_string(),
+                            stable_denom: "uusd".to_string(),
+                            max_borrow_factor: Decimal256::one(),
+                        })))
+                    }
+                    MarketQueryMsg::State { .. } => match self.market_states.get(contract_addr) {
+                        Some((total_liabilities, total_reserves)) => SystemResult::Ok(
+                            ContractResult::from(to_binary(&MarketStateResponse {
+                                total_liabilities: *total_liabilities,
+                                total_reserves: *total_reserves,
+                                last_interest_updated: 0,
+                                last_reward_updated: 0,
+                                global_interest_index: Decimal256::one(),
+                                global_reward_index: Decimal256::zero(),
+                                anc_emission_rate: Decimal256::zero(),
+                                global_deposit_reward_index: Decimal256::zero(),
+                                anc_deposit_emission_rate: Decimal256::zero(),
+                                prev_aterra_supply: Uint256::zero(),
+                                prev_exchange_rate: Decimal256::one(),
+                            })),
+                        ),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No market state exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            market_states: HashMap::new(),
+        }
+    }
+
+    pub fn with_market_state(
+        &mut self,
+        market_contract: &str,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    ) {
+        self.market_states.insert(
+            market_contract.to_string(),
+            (total_liabilities, total_reserves),
+        );
+    }
+
+    pub fn update_balance(&mut self, addr: &str, balance: Vec<Coin>) {
+        self.base.update_balance(addr, balance);
+    }
+}
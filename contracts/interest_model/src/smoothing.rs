@@ -0,0 +1,146 @@
+use crate::contract::utilization_ratio;
+use crate::controller::query_market_utilization;
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_utilization_average, read_utilization_smoothing, store_utilization_average,
+    store_utilization_smoothing, Config, UtilizationAverage,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+use moneymarket::interest_model::{UtilizationAverageResponse, UtilizationSmoothing};
+
+/// Largest share of the average, in percent, a single record can replace
+const MAX_RECORD_WEIGHT: u64 = 50;
+
+pub fn update_utilization_smoothing(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market: Addr,
+    utilization_smoothing: Option<UtilizationSmoothing>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let period = match utilization_smoothing {
+        Some(utilization_smoothing) => {
+            if utilization_smoothing.period == 0 {
+                return Err(ContractError::InvalidUtilizationSmoothing {});
+            }
+
+            Some(utilization_smoothing.period)
+        }
+        None => None,
+    };
+
+    // the average starts at the current utilization
+    let utilization_average = match period {
+        Some(_) => Some(UtilizationAverage {
+            utilization: query_market_utilization(deps.as_ref(), market.clone())?,
+            last_recorded_time: env.block.time.seconds(),
+        }),
+        None => None,
+    };
+    store_utilization_smoothing(deps.storage, &market_raw, period)?;
+    store_utilization_average(deps.storage, &market_raw, utilization_average)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_utilization_smoothing"),
+        attr("market", market),
+        attr(
+            "period",
+            period.map(|period| period.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Fold the utilization of the sending market, before its transaction,
+/// into its average; markets without smoothing are left untouched
+pub fn record_utilization(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> Result<Response, ContractError> {
+    let market_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let utilization = utilization_ratio(market_balance, total_liabilities, total_reserves)?;
+    let average_utilization = smoothed_utilization(deps.as_ref(), &env, &market_raw, utilization)?;
+    if read_utilization_smoothing(deps.storage, &market_raw)?.is_some() {
+        store_utilization_average(
+            deps.storage,
+            &market_raw,
+            Some(UtilizationAverage {
+                utilization: average_utilization,
+                last_recorded_time: env.block.time.seconds(),
+            }),
+        )?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "record_utilization"),
+        attr("market", info.sender),
+        attr("utilization", utilization.to_string()),
+        attr("average_utilization", average_utilization.to_string()),
+    ]))
+}
+
+/// Utilization pricing the market: its average moved toward the
+/// utilization by the time passed since the last record, capped at
+/// `MAX_RECORD_WEIGHT`, or the utilization itself when the market
+/// is not smoothed
+pub(crate) fn smoothed_utilization(
+    deps: Deps,
+    env: &Env,
+    market: &CanonicalAddr,
+    utilization: Decimal256,
+) -> StdResult<Decimal256> {
+    let period = match read_utilization_smoothing(deps.storage, market)? {
+        Some(period) => period,
+        None => return Ok(utilization),
+    };
+    let utilization_average = match read_utilization_average(deps.storage, market)? {
+        Some(utilization_average) => utilization_average,
+        None => return Ok(utilization),
+    };
+
+    let elapsed = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(utilization_average.last_recorded_time);
+    let weight = std::cmp::min(
+        Decimal256::from_ratio(elapsed, period),
+        Decimal256::percent(MAX_RECORD_WEIGHT),
+    );
+    Ok(if utilization > utilization_average.utilization {
+        utilization_average.utilization + (utilization - utilization_average.utilization) * weight
+    } else {
+        utilization_average.utilization - (utilization_average.utilization - utilization) * weight
+    })
+}
+
+pub fn query_utilization_average(
+    deps: Deps,
+    market: Addr,
+) -> StdResult<UtilizationAverageResponse> {
+    let market_raw = deps.api.addr_canonicalize(market.as_str())?;
+    let period = read_utilization_smoothing(deps.storage, &market_raw)?;
+    let utilization_average = read_utilization_average(deps.storage, &market_raw)?;
+
+    Ok(UtilizationAverageResponse {
+        market: market.to_string(),
+        utilization_smoothing: period.map(|period| UtilizationSmoothing { period }),
+        average_utilization: utilization_average
+            .as_ref()
+            .map(|utilization_average| utilization_average.utilization),
+        last_recorded_time: utilization_average
+            .map(|utilization_average| utilization_average.last_recorded_time),
+    })
+}
@@ -2,18 +2,90 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read};
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::interest_model::MarketCurveResponse;
+use moneymarket::pagination::{calc_limit, calc_range_start};
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_RATE_CONTROLLER: &[u8] = b"rate_controller";
+static KEY_CONTROLLER_STATE: &[u8] = b"controller_state";
+static KEY_PENDING_CONFIG: &[u8] = b"pending_config";
+static KEY_FIXED_RATE_CONFIG: &[u8] = b"fixed_rate_config";
+
+static PREFIX_MARKET_CURVE: &[u8] = b"market_curve";
+static PREFIX_MARKET_PENDING_CONFIG: &[u8] = b"market_pending_config";
+static PREFIX_UTILIZATION_SMOOTHING: &[u8] = b"utilization_smoothing";
+static PREFIX_UTILIZATION_AVERAGE: &[u8] = b"utilization_average";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    pub owner: CanonicalAddr,
+    pub base_rate: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub slope1: Decimal256,
+    pub slope2: Decimal256,
+    pub reserve_factor: Decimal256,
+}
+
+/// Rate curve of a market
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Curve {
+    pub base_rate: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub slope1: Decimal256,
+    pub slope2: Decimal256,
+    pub reserve_factor: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UtilizationAverage {
+    pub utilization: Decimal256,
+    pub last_recorded_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedRateConfig {
+    pub duration_premium: Decimal256,
+    pub max_duration: u64,
+}
+
+/// Config of the linear model, before the two-slope model
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
     pub owner: CanonicalAddr,
     pub base_rate: Decimal256,
     pub interest_multiplier: Decimal256,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateControllerInfo {
+    pub market_contract: CanonicalAddr,
+    pub target_utilization: Decimal256,
+    pub epoch_period: u64,
+    pub gain: Decimal256,
+    pub max_step: Decimal256,
+    pub min_slope1: Decimal256,
+    pub max_slope1: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ControllerState {
+    pub last_adjusted_time: u64,
+    pub last_utilization: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfig {
+    pub owner: Option<CanonicalAddr>,
+    pub base_rate: Option<Decimal256>,
+    pub optimal_utilization: Option<Decimal256>,
+    pub slope1: Option<Decimal256>,
+    pub slope2: Option<Decimal256>,
+    pub reserve_factor: Option<Decimal256>,
+    pub executable_at: u64,
+}
+
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
     singleton(storage, KEY_CONFIG).save(config)
 }
@@ -21,3 +93,203 @@ pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()>
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+pub fn read_legacy_config(storage: &dyn Storage) -> StdResult<LegacyConfig> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_rate_controller(
+    storage: &mut dyn Storage,
+    rate_controller: Option<RateControllerInfo>,
+) -> StdResult<()> {
+    match rate_controller {
+        Some(rate_controller) => singleton(storage, KEY_RATE_CONTROLLER).save(&rate_controller),
+        None => {
+            singleton::<RateControllerInfo>(storage, KEY_RATE_CONTROLLER).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_rate_controller(storage: &dyn Storage) -> StdResult<Option<RateControllerInfo>> {
+    singleton_read(storage, KEY_RATE_CONTROLLER).may_load()
+}
+
+pub fn store_controller_state(
+    storage: &mut dyn Storage,
+    controller_state: Option<ControllerState>,
+) -> StdResult<()> {
+    match controller_state {
+        Some(controller_state) => singleton(storage, KEY_CONTROLLER_STATE).save(&controller_state),
+        None => {
+            singleton::<ControllerState>(storage, KEY_CONTROLLER_STATE).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_controller_state(storage: &dyn Storage) -> StdResult<Option<ControllerState>> {
+    singleton_read(storage, KEY_CONTROLLER_STATE).may_load()
+}
+
+/// Pending update of the curve of the market, or of the default config
+pub fn store_pending_config(
+    storage: &mut dyn Storage,
+    market: Option<&CanonicalAddr>,
+    pending_config: Option<PendingConfig>,
+) -> StdResult<()> {
+    match (market, pending_config) {
+        (Some(market), Some(pending_config)) => Bucket::new(storage, PREFIX_MARKET_PENDING_CONFIG)
+            .save(market.as_slice(), &pending_config),
+        (Some(market), None) => {
+            Bucket::<PendingConfig>::new(storage, PREFIX_MARKET_PENDING_CONFIG)
+                .remove(market.as_slice());
+            Ok(())
+        }
+        (None, Some(pending_config)) => {
+            singleton(storage, KEY_PENDING_CONFIG).save(&pending_config)
+        }
+        (None, None) => {
+            singleton::<PendingConfig>(storage, KEY_PENDING_CONFIG).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_pending_config(
+    storage: &dyn Storage,
+    market: Option<&CanonicalAddr>,
+) -> StdResult<Option<PendingConfig>> {
+    match market {
+        Some(market) => {
+            ReadonlyBucket::new(storage, PREFIX_MARKET_PENDING_CONFIG).may_load(market.as_slice())
+        }
+        None => singleton_read(storage, KEY_PENDING_CONFIG).may_load(),
+    }
+}
+
+pub fn store_market_curve(
+    storage: &mut dyn Storage,
+    market: &CanonicalAddr,
+    curve: &Curve,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_MARKET_CURVE).save(market.as_slice(), curve)
+}
+
+pub fn read_market_curve(
+    storage: &dyn Storage,
+    market: &CanonicalAddr,
+) -> StdResult<Option<Curve>> {
+    ReadonlyBucket::new(storage, PREFIX_MARKET_CURVE).may_load(market.as_slice())
+}
+
+/// Curve of the market, or the default curve of the config when the
+/// market has none
+pub fn read_curve(storage: &dyn Storage, market: Option<&CanonicalAddr>) -> StdResult<Curve> {
+    if let Some(market) = market {
+        if let Some(curve) = read_market_curve(storage, market)? {
+            return Ok(curve);
+        }
+    }
+
+    let config = read_config(storage)?;
+    Ok(Curve {
+        base_rate: config.base_rate,
+        optimal_utilization: config.optimal_utilization,
+        slope1: config.slope1,
+        slope2: config.slope2,
+        reserve_factor: config.reserve_factor,
+    })
+}
+
+pub fn store_fixed_rate_config(
+    storage: &mut dyn Storage,
+    fixed_rate_config: Option<FixedRateConfig>,
+) -> StdResult<()> {
+    match fixed_rate_config {
+        Some(fixed_rate_config) => {
+            singleton(storage, KEY_FIXED_RATE_CONFIG).save(&fixed_rate_config)
+        }
+        None => {
+            singleton::<FixedRateConfig>(storage, KEY_FIXED_RATE_CONFIG).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_fixed_rate_config(storage: &dyn Storage) -> StdResult<Option<FixedRateConfig>> {
+    singleton_read(storage, KEY_FIXED_RATE_CONFIG).may_load()
+}
+
+pub fn store_utilization_smoothing(
+    storage: &mut dyn Storage,
+    market: &CanonicalAddr,
+    period: Option<u64>,
+) -> StdResult<()> {
+    let mut smoothing_bucket: Bucket<u64> = Bucket::new(storage, PREFIX_UTILIZATION_SMOOTHING);
+    match period {
+        Some(period) => smoothing_bucket.save(market.as_slice(), &period),
+        None => {
+            smoothing_bucket.remove(market.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_utilization_smoothing(
+    storage: &dyn Storage,
+    market: &CanonicalAddr,
+) -> StdResult<Option<u64>> {
+    ReadonlyBucket::new(storage, PREFIX_UTILIZATION_SMOOTHING).may_load(market.as_slice())
+}
+
+pub fn store_utilization_average(
+    storage: &mut dyn Storage,
+    market: &CanonicalAddr,
+    utilization_average: Option<UtilizationAverage>,
+) -> StdResult<()> {
+    let mut average_bucket: Bucket<UtilizationAverage> =
+        Bucket::new(storage, PREFIX_UTILIZATION_AVERAGE);
+    match utilization_average {
+        Some(utilization_average) => average_bucket.save(market.as_slice(), &utilization_average),
+        None => {
+            average_bucket.remove(market.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_utilization_average(
+    storage: &dyn Storage,
+    market: &CanonicalAddr,
+) -> StdResult<Option<UtilizationAverage>> {
+    ReadonlyBucket::new(storage, PREFIX_UTILIZATION_AVERAGE).may_load(market.as_slice())
+}
+
+pub fn read_market_curves(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MarketCurveResponse>> {
+    let curve_bucket: ReadonlyBucket<Curve> =
+        ReadonlyBucket::new(deps.storage, PREFIX_MARKET_CURVE);
+
+    let limit = calc_limit(limit);
+    let start = calc_range_start(start_after);
+
+    curve_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok(MarketCurveResponse {
+                market: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                base_rate: v.base_rate,
+                optimal_utilization: v.optimal_utilization,
+                slope1: v.slope1,
+                slope2: v.slope2,
+                reserve_factor: v.reserve_factor,
+            })
+        })
+        .collect()
+}
@@ -1,11 +1,24 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
 use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies as mock_wasm_dependencies;
+use crate::state::LegacyConfig;
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::from_binary;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_binary, Api, Coin, OwnedDeps, StdError, Uint128};
+use cosmwasm_storage::singleton;
 use moneymarket::interest_model::{
-    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BacktestResponse, BorrowRateResponse, ConfigResponse, DepositRateResponse, ExecuteMsg,
+    FixedRateConfig, FixedRateQuoteResponse, InstantiateMsg, MarketCurveResponse, MarketsResponse,
+    MigrateMsg, PendingConfigResponse, QueryMsg, RateController, RateControllerResponse,
+    RateCurveResponse, RateModelResponse, UtilizationAverageResponse, UtilizationSmoothing,
+    CONFIG_UPDATE_DELAY,
 };
+use std::str::FromStr;
+
+// per block rate of x / 10^9
+fn rate(x: u64) -> Decimal256 {
+    Decimal256::from_ratio(x, 1_000_000_000u64)
+}
 
 #[test]
 fn proper_initialization() {
@@ -13,8 +26,11 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
-        base_rate: Decimal256::percent(10),
-        interest_multiplier: Decimal256::percent(10),
+        base_rate: rate(100),
+        optimal_utilization: Decimal256::one(),
+        slope1: rate(100),
+        slope2: Decimal256::zero(),
+        reserve_factor: Decimal256::zero(),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -27,28 +43,48 @@ fn proper_initialization() {
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let value: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0000", value.owner.as_str());
-    assert_eq!("0.1", &value.base_rate.to_string());
-    assert_eq!("0.1", &value.interest_multiplier.to_string());
+    assert_eq!("0.0000001", &value.base_rate.to_string());
+    assert_eq!("1", &value.optimal_utilization.to_string());
+    assert_eq!("0.0000001", &value.slope1.to_string());
+    assert_eq!("0", &value.slope2.to_string());
 
     let query_msg = QueryMsg::BorrowRate {
         market_balance: Uint256::from(1000000u128),
         total_liabilities: Decimal256::from_uint256(500000u128),
         total_reserves: Decimal256::from_uint256(100000u128),
+        market: None,
     };
     let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
     let value: BorrowRateResponse = from_binary(&res).unwrap();
     // utilization_ratio = 0.35714285714285714
-    // borrow_rate = 0.035714285 + 0.1
-    assert_eq!("0.135714285714285714", &value.rate.to_string());
+    // borrow_rate = 0.000000035714285 + 0.0000001
+    assert_eq!("0.000000135714285714", &value.rate.to_string());
 
     let query_msg = QueryMsg::BorrowRate {
         market_balance: Uint256::zero(),
         total_liabilities: Decimal256::zero(),
         total_reserves: Decimal256::zero(),
+        market: None,
     };
     let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
     let value: BorrowRateResponse = from_binary(&res).unwrap();
-    assert_eq!("0.1", &value.rate.to_string());
+    assert_eq!("0.0000001", &value.rate.to_string());
+
+    // rates out of the compiled bounds
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(10),
+        optimal_utilization: Decimal256::one(),
+        slope1: rate(100),
+        slope2: Decimal256::zero(),
+        reserve_factor: Decimal256::zero(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::ConfigOutOfBounds(param)) => assert_eq!(param, "base_rate"),
+        _ => panic!("Must return config out of bounds error"),
+    }
 }
 
 #[test]
@@ -57,42 +93,1182 @@ fn update_config() {
 
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
-        base_rate: Decimal256::percent(10),
-        interest_multiplier: Decimal256::percent(10),
+        base_rate: rate(100),
+        optimal_utilization: Decimal256::one(),
+        slope1: rate(100),
+        slope2: Decimal256::zero(),
+        reserve_factor: Decimal256::zero(),
     };
 
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // propose owner update
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
+        market: None,
         owner: Some("owner0001".to_string()),
         base_rate: None,
-        interest_multiplier: None,
+        optimal_utilization: None,
+        slope1: None,
+        slope2: None,
+        reserve_factor: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    let executable_at = mock_env().block.time.seconds() + CONFIG_UPDATE_DELAY;
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingConfig { market: None },
+    )
+    .unwrap();
+    let value: PendingConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        PendingConfigResponse {
+            owner: Some("owner0001".to_string()),
+            base_rate: None,
+            optimal_utilization: None,
+            slope1: None,
+            slope2: None,
+            reserve_factor: None,
+            executable_at: Some(executable_at),
+        }
+    );
+
+    // the delay has not passed
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ExecuteConfigUpdate { market: None },
+    );
+    match res {
+        Err(ContractError::ConfigUpdateNotReady(time)) => assert_eq!(time, executable_at),
+        _ => panic!("Must return config update not ready error"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(CONFIG_UPDATE_DELAY);
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecuteConfigUpdate { market: None },
+    )
+    .unwrap();
+
     // it worked, let's query the state
-    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
     let value: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0001", value.owner.as_str());
-    assert_eq!("0.1", &value.base_rate.to_string());
-    assert_eq!("0.1", &value.interest_multiplier.to_string());
+    assert_eq!("0.0000001", &value.base_rate.to_string());
+    assert_eq!("1", &value.optimal_utilization.to_string());
+    assert_eq!("0.0000001", &value.slope1.to_string());
+    assert_eq!("0", &value.slope2.to_string());
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PendingConfig { market: None },
+    )
+    .unwrap();
+    let value: PendingConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(value.executable_at, None);
 
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
+        market: None,
         owner: None,
-        base_rate: Some(Decimal256::percent(1)),
-        interest_multiplier: Some(Decimal256::percent(1)),
+        base_rate: Some(rate(10)),
+        optimal_utilization: None,
+        slope1: Some(rate(10)),
+        slope2: None,
+        reserve_factor: None,
     };
 
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // a 10,000% a year borrow rate is out of the bounds
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        market: None,
+        owner: None,
+        base_rate: None,
+        optimal_utilization: None,
+        slope1: None,
+        slope2: Some(rate(21474)),
+        reserve_factor: None,
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::ConfigOutOfBounds(param)) => assert_eq!(param, "slope2"),
+        _ => panic!("Must return config out of bounds error"),
+    }
+
+    // cancel a proposal
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        market: None,
+        owner: None,
+        base_rate: Some(rate(10)),
+        optimal_utilization: None,
+        slope1: None,
+        slope2: None,
+        reserve_factor: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("owner0001", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::CancelConfigUpdate { market: None },
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(CONFIG_UPDATE_DELAY);
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::ExecuteConfigUpdate { market: None },
+    );
+    match res {
+        Err(ContractError::NoPendingConfig {}) => (),
+        _ => panic!("Must return no pending config error"),
+    }
+}
+
+#[test]
+fn two_slope_borrow_rate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::zero(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // utilization_ratio = 0.4
+    // borrow_rate = 0.00000002 + 0.4 / 0.8 * 0.00000008
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(600000u128),
+        total_liabilities: Decimal256::from_uint256(400000u128),
+        total_reserves: Decimal256::zero(),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000006", &value.rate.to_string());
+
+    // utilization_ratio = 0.9
+    // borrow_rate = 0.00000002 + 0.00000008 + 0.1 / 0.2 * 0.000001
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(900000u128),
+        total_reserves: Decimal256::zero(),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.0000006", &value.rate.to_string());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RateModel { market: None },
+    )
+    .unwrap();
+    let value: RateModelResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        RateModelResponse {
+            base_rate: rate(20),
+            optimal_utilization: Decimal256::percent(80),
+            optimal_rate: rate(100),
+            max_rate: rate(1100),
+        }
+    );
+
+    // optimal utilization out of range
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        market: None,
+        owner: None,
+        base_rate: None,
+        optimal_utilization: Some(Decimal256::percent(40)),
+        slope1: None,
+        slope2: None,
+        reserve_factor: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::ConfigOutOfBounds(param)) => {
+            assert_eq!(param, "optimal_utilization")
+        }
+        _ => panic!("Must return config out of bounds error"),
+    }
+}
+
+#[test]
+fn degenerate_market_inputs() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::percent(20),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let borrow_rate = |deps: &OwnedDeps<_, _, _>,
+                       market_balance: u128,
+                       total_liabilities: u128,
+                       total_reserves: u128| {
+        let query_msg = QueryMsg::BorrowRate {
+            market_balance: Uint256::from(market_balance),
+            total_liabilities: Decimal256::from_uint256(total_liabilities),
+            total_reserves: Decimal256::from_uint256(total_reserves),
+            market: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        from_binary::<BorrowRateResponse>(&res).unwrap().rate
+    };
+
+    // empty market
+    assert_eq!(borrow_rate(&deps, 0, 0, 0), rate(20));
+    // reserves only
+    assert_eq!(borrow_rate(&deps, 0, 0, 1000), rate(20));
+    // bad debt left the liabilities above the market value net of reserves
+    assert_eq!(borrow_rate(&deps, 100, 1000, 500), rate(1100));
+    // bad debt left the reserves above the market value
+    assert_eq!(borrow_rate(&deps, 100, 1000, 2000), rate(1100));
+
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256(Decimal256::MAX.0),
+        total_liabilities: Decimal256::one(),
+        total_reserves: Decimal256::zero(),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::Overflow { .. }) => {}
+        _ => panic!("Must return overflow error"),
+    }
+
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::one(),
+        total_liabilities: Decimal256::MAX,
+        total_reserves: Decimal256::zero(),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::Overflow { .. }) => {}
+        _ => panic!("Must return overflow error"),
+    }
+
+    let query_msg = QueryMsg::Backtest {
+        utilization_path: vec![Decimal256::one(); 1000],
+        blocks_per_step: Some(u64::MAX),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::Overflow { .. }) => {}
+        _ => panic!("Must return overflow error"),
+    }
+}
+
+// xorshift generator, seeded so that a failing input reproduces
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // an amount of any magnitude up to u128::MAX
+    fn next_amount(&mut self) -> u128 {
+        let amount = ((self.next_u64() as u128) << 64) | self.next_u64() as u128;
+        amount >> (self.next_u64() % 128)
+    }
+
+    fn next_decimal(&mut self) -> Decimal256 {
+        Decimal256::from_uint256(self.next_amount()) / Decimal256::from_uint256(self.next_u64() | 1)
+    }
+}
+
+#[test]
+fn fuzz_rate_math() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::percent(20),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..5000 {
+        let query_msg = QueryMsg::BorrowRate {
+            market_balance: Uint256::from(rng.next_amount()),
+            total_liabilities: rng.next_decimal(),
+            total_reserves: rng.next_decimal(),
+            market: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg.clone())
+            .unwrap_or_else(|err| panic!("{:?} failed: {}", query_msg, err));
+        let value: BorrowRateResponse = from_binary(&res).unwrap();
+        assert!(
+            rate(20) <= value.rate && value.rate <= rate(1100),
+            "{:?} returned {}",
+            query_msg,
+            value.rate
+        );
+    }
+
+    for _ in 0..200 {
+        let steps = 1 + rng.next_u64() % 20;
+        let query_msg = QueryMsg::Backtest {
+            utilization_path: (0..steps)
+                .map(|_| Decimal256::from_ratio(rng.next_u64() % 1001, 1000u64))
+                .collect(),
+            blocks_per_step: Some(rng.next_u64() >> (rng.next_u64() % 64)),
+            market: None,
+        };
+        match query(deps.as_ref(), mock_env(), query_msg.clone()) {
+            Ok(res) => {
+                let value: BacktestResponse = from_binary(&res).unwrap();
+                assert!(
+                    Decimal256::one() <= value.deposit_factor
+                        && value.deposit_factor <= value.interest_factor,
+                    "{:?} returned {:?}",
+                    query_msg,
+                    value
+                );
+            }
+            Err(StdError::Overflow { .. }) => {}
+            Err(err) => panic!("{:?} failed: {}", query_msg, err),
+        }
+    }
+}
+
+#[test]
+fn migrate_linear_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    // the legacy multiplier is above the slope1 bound
+    singleton(&mut deps.storage, b"config")
+        .save(&LegacyConfig {
+            owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+            base_rate: rate(100),
+            interest_multiplier: rate(1000),
+        })
+        .unwrap();
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    match res {
+        Err(ContractError::ConfigOutOfBounds(param)) => assert_eq!(param, "slope1"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    singleton(&mut deps.storage, b"config")
+        .save(&LegacyConfig {
+            owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+            base_rate: rate(100),
+            interest_multiplier: rate(100),
+        })
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            base_rate: rate(100),
+            optimal_utilization: Decimal256::one(),
+            slope1: rate(100),
+            slope2: Decimal256::zero(),
+            reserve_factor: Decimal256::zero(),
+        }
+    );
+
+    // the linear rate is kept
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(1000000u128),
+        total_liabilities: Decimal256::from_uint256(500000u128),
+        total_reserves: Decimal256::from_uint256(100000u128),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.000000135714285714", &value.rate.to_string());
+}
+
+#[test]
+fn rate_controller() {
+    let mut deps = mock_wasm_dependencies(&[(
+        "market0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    )]);
+    deps.querier.with_market_state(
+        "market0000",
+        Decimal256::from_uint256(900000u128),
+        Decimal256::zero(),
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::zero(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let rate_controller = RateController {
+        market_contract: "market0000".to_string(),
+        target_utilization: Decimal256::percent(80),
+        epoch_period: 86400,
+        gain: rate(100),
+        max_step: rate(20),
+        min_slope1: rate(10),
+        max_slope1: rate(200),
+    };
+
+    // only the owner can set the controller
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(rate_controller.clone()),
+    };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
         Err(ContractError::Unauthorized {}) => (),
         _ => panic!("Must return unauthorized error"),
     }
+
+    // target utilization out of range
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(RateController {
+            target_utilization: Decimal256::one(),
+            ..rate_controller.clone()
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidRateController {}) => (),
+        _ => panic!("Must return invalid rate controller error"),
+    }
+
+    // epochs shorter than an hour
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(RateController {
+            epoch_period: 3599,
+            ..rate_controller.clone()
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::ConfigOutOfBounds(field)) => assert_eq!(field, "epoch_period"),
+        _ => panic!("Must return config out of bounds error"),
+    }
+
+    // the step would cross the slope1 bound faster than the config timelock
+    // max_step <= 0.000000429478 * 86400 / 172800
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(RateController {
+            max_step: Decimal256::from_ratio(214740u64, 1_000_000_000_000u64),
+            ..rate_controller.clone()
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::ConfigOutOfBounds(field)) => assert_eq!(field, "max_step"),
+        _ => panic!("Must return config out of bounds error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(RateController {
+            max_step: Decimal256::from_ratio(214739u64, 1_000_000_000_000u64),
+            ..rate_controller.clone()
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(rate_controller.clone()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // an epoch has not passed since the controller was set
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AdjustRate {});
+    match res {
+        Err(ContractError::EpochNotPassed(time)) => {
+            assert_eq!(time, mock_env().block.time.seconds())
+        }
+        _ => panic!("Must return epoch not passed error"),
+    }
+
+    // utilization_ratio = 0.9
+    // step = min(0.0000001 * 0.1, 0.00000002)
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86400);
+    let info = mock_info("addr0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AdjustRate {}).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000009", &value.slope1.to_string());
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::RateController {}).unwrap();
+    let value: RateControllerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        RateControllerResponse {
+            rate_controller: Some(rate_controller.clone()),
+            last_adjusted_time: Some(env.block.time.seconds()),
+            last_utilization: Some(Decimal256::percent(90)),
+        }
+    );
+
+    // utilization_ratio = 0.1
+    // step = min(0.0000001 * 0.7, 0.00000002)
+    deps.querier.update_balance(
+        "market0000",
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(900000u128),
+        }],
+    );
+    deps.querier.with_market_state(
+        "market0000",
+        Decimal256::from_uint256(100000u128),
+        Decimal256::zero(),
+    );
+    env.block.time = env.block.time.plus_seconds(86400);
+    let info = mock_info("addr0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AdjustRate {}).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000007", &value.slope1.to_string());
+
+    // raising min_slope1 brings slope1 back by a step per epoch
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateRateController {
+        rate_controller: Some(RateController {
+            min_slope1: rate(150),
+            ..rate_controller
+        }),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(86400);
+    let info = mock_info("addr0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AdjustRate {}).unwrap();
+
+    let res = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000009", &value.slope1.to_string());
+}
+
+#[test]
+fn market_curves() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(100),
+        optimal_utilization: Decimal256::one(),
+        slope1: rate(100),
+        slope2: Decimal256::zero(),
+        reserve_factor: Decimal256::zero(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // propose a curve of its own for market0000
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        market: Some("market0000".to_string()),
+        owner: None,
+        base_rate: None,
+        optimal_utilization: Some(Decimal256::percent(80)),
+        slope1: Some(rate(200)),
+        slope2: Some(rate(1000)),
+        reserve_factor: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the default config has no pending update
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingConfig { market: None },
+    )
+    .unwrap();
+    let value: PendingConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(value.executable_at, None);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(CONFIG_UPDATE_DELAY);
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ExecuteConfigUpdate {
+        market: Some("market0000".to_string()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Markets {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: MarketsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        MarketsResponse {
+            markets: vec![MarketCurveResponse {
+                market: "market0000".to_string(),
+                base_rate: rate(100),
+                optimal_utilization: Decimal256::percent(80),
+                slope1: rate(200),
+                slope2: rate(1000),
+                reserve_factor: Decimal256::zero(),
+            }],
+        }
+    );
+
+    // utilization_ratio = 0.4
+    // borrow_rate = 0.0000001 + 0.4 / 0.8 * 0.0000002
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(600000u128),
+        total_liabilities: Decimal256::from_uint256(400000u128),
+        total_reserves: Decimal256::zero(),
+        market: Some("market0000".to_string()),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.0000002", &value.rate.to_string());
+
+    // markets without a curve are priced by the default curve
+    // borrow_rate = 0.0000001 + 0.4 * 0.0000001
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(600000u128),
+        total_liabilities: Decimal256::from_uint256(400000u128),
+        total_reserves: Decimal256::zero(),
+        market: Some("market0001".to_string()),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000014", &value.rate.to_string());
+
+    let res = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("1", &value.optimal_utilization.to_string());
+    assert_eq!("0.0000001", &value.slope1.to_string());
+}
+
+#[test]
+fn rate_curve() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::percent(20),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::RateCurve {
+        points: 5,
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: RateCurveResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value
+            .points
+            .iter()
+            .map(|point| (
+                point.utilization.to_string(),
+                point.borrow_rate.to_string(),
+                point.deposit_rate.to_string()
+            ))
+            .collect::<Vec<(String, String, String)>>(),
+        vec![
+            ("0".to_string(), "0.00000002".to_string(), "0".to_string()),
+            (
+                "0.25".to_string(),
+                "0.000000045".to_string(),
+                "0.000000009".to_string()
+            ),
+            (
+                "0.5".to_string(),
+                "0.00000007".to_string(),
+                "0.000000028".to_string()
+            ),
+            (
+                "0.75".to_string(),
+                "0.000000095".to_string(),
+                "0.000000057".to_string()
+            ),
+            (
+                "1".to_string(),
+                "0.0000011".to_string(),
+                "0.00000088".to_string()
+            ),
+        ]
+    );
+
+    let query_msg = QueryMsg::RateCurve {
+        points: 1,
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Rate curve must be sampled at 2 to 101 points")
+        }
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn backtest() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::percent(20),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::Backtest {
+        utilization_path: vec![Decimal256::percent(50), Decimal256::one()],
+        blocks_per_step: Some(10),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BacktestResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        BacktestResponse {
+            interest_factor: Decimal256::from_str("1.0000117000077").unwrap(),
+            deposit_factor: Decimal256::from_str("1.000009080002464").unwrap(),
+            blocks: 20,
+        }
+    );
+
+    // a single block per utilization by default
+    let query_msg = QueryMsg::Backtest {
+        utilization_path: vec![Decimal256::zero(); 3],
+        blocks_per_step: None,
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BacktestResponse = from_binary(&res).unwrap();
+    assert_eq!(value.deposit_factor, Decimal256::one());
+    assert_eq!(value.blocks, 3);
+
+    let query_msg = QueryMsg::Backtest {
+        utilization_path: vec![],
+        blocks_per_step: None,
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Utilization path must have 1 to 1000 utilizations of at most 1"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let query_msg = QueryMsg::Backtest {
+        utilization_path: vec![Decimal256::percent(101)],
+        blocks_per_step: None,
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Utilization path must have 1 to 1000 utilizations of at most 1"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn deposit_rate() {
+    let mut deps = mock_wasm_dependencies(&[(
+        "market0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(600000u128),
+        }],
+    )]);
+    deps.querier.with_market_state(
+        "market0000",
+        Decimal256::from_uint256(400000u128),
+        Decimal256::zero(),
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::percent(20),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // utilization_ratio = 0.4
+    // borrow_rate = 0.00000002 + 0.4 / 0.8 * 0.00000008
+    // deposit_rate = 0.00000006 * 0.4 * (1 - 0.2)
+    let query_msg = QueryMsg::DepositRate {
+        market: "market0000".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: DepositRateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        DepositRateResponse {
+            market: "market0000".to_string(),
+            utilization: Decimal256::percent(40),
+            borrow_rate: rate(60),
+            deposit_rate: Decimal256::from_ratio(192u64, 10_000_000_000u64),
+        }
+    );
+
+    // the deposits earn the borrow interest less the reserves
+    assert_eq!(
+        value.deposit_rate * Decimal256::from_uint256(1000000u128),
+        value.borrow_rate
+            * Decimal256::from_uint256(400000u128)
+            * (Decimal256::one() - Decimal256::percent(20))
+    );
+
+    // a reserve factor above 50% is out of the bounds
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        market: None,
+        owner: None,
+        base_rate: None,
+        optimal_utilization: None,
+        slope1: None,
+        slope2: None,
+        reserve_factor: Some(Decimal256::percent(60)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::ConfigOutOfBounds(param)) => assert_eq!(param, "reserve_factor"),
+        _ => panic!("Must return config out of bounds error"),
+    }
+}
+
+#[test]
+fn utilization_smoothing() {
+    let mut deps = mock_wasm_dependencies(&[(
+        "market0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(600000u128),
+        }],
+    )]);
+    deps.querier.with_market_state(
+        "market0000",
+        Decimal256::from_uint256(400000u128),
+        Decimal256::zero(),
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::zero(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateUtilizationSmoothing {
+        market: "market0000".to_string(),
+        utilization_smoothing: Some(UtilizationSmoothing { period: 0 }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidUtilizationSmoothing {}) => (),
+        _ => panic!("Must return invalid utilization smoothing error"),
+    }
+
+    // the average starts at the utilization of 0.4
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateUtilizationSmoothing {
+        market: "market0000".to_string(),
+        utilization_smoothing: Some(UtilizationSmoothing { period: 3600 }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // a same-block deposit dropping the utilization to 0.04 does not move the rate
+    // borrow_rate = 0.00000002 + 0.4 / 0.8 * 0.00000008
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(9600000u128),
+        total_liabilities: Decimal256::from_uint256(400000u128),
+        total_reserves: Decimal256::zero(),
+        market: Some("market0000".to_string()),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000006", &value.rate.to_string());
+
+    // the unsmoothed rate follows the utilization
+    // borrow_rate = 0.00000002 + 0.04 / 0.8 * 0.00000008
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(9600000u128),
+        total_liabilities: Decimal256::from_uint256(400000u128),
+        total_reserves: Decimal256::zero(),
+        market: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.000000024", &value.rate.to_string());
+
+    // half a period later, the average is halfway to the utilization of 0.8
+    // borrow_rate = 0.00000002 + 0.6 / 0.8 * 0.00000008
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(1800);
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(400000u128),
+        total_reserves: Decimal256::zero(),
+        market: Some("market0000".to_string()),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.00000008", &value.rate.to_string());
+
+    // the market records its utilization of 0.9 before a transaction
+    let info = mock_info("market0000", &[]);
+    let msg = ExecuteMsg::RecordUtilization {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(900000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // records of other senders do not touch the average of the market
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::RecordUtilization {
+        market_balance: Uint256::from(900000u128),
+        total_liabilities: Decimal256::from_uint256(100000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::UtilizationAverage {
+        market: "market0000".to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+    let value: UtilizationAverageResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        UtilizationAverageResponse {
+            market: "market0000".to_string(),
+            utilization_smoothing: Some(UtilizationSmoothing { period: 3600 }),
+            average_utilization: Some(Decimal256::percent(65)),
+            last_recorded_time: Some(env.block.time.seconds()),
+        }
+    );
+
+    // after a full period the average still moves by at most half
+    // toward the utilization of 0.05
+    // borrow_rate = 0.00000002 + 0.35 / 0.8 * 0.00000008
+    env.block.time = env.block.time.plus_seconds(7200);
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(9500000u128),
+        total_liabilities: Decimal256::from_uint256(500000u128),
+        total_reserves: Decimal256::zero(),
+        market: Some("market0000".to_string()),
+    };
+    let res = query(deps.as_ref(), env, query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.000000055", &value.rate.to_string());
+}
+
+#[test]
+fn fixed_rate_quote() {
+    let mut deps = mock_wasm_dependencies(&[(
+        "market0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(600000u128),
+        }],
+    )]);
+    deps.querier.with_market_state(
+        "market0000",
+        Decimal256::from_uint256(400000u128),
+        Decimal256::zero(),
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: rate(20),
+        optimal_utilization: Decimal256::percent(80),
+        slope1: rate(80),
+        slope2: rate(1000),
+        reserve_factor: Decimal256::zero(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::QuoteFixedRate {
+        market: "market0000".to_string(),
+        amount: Uint256::from(200000u128),
+        duration: 15768000,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Fixed rates are not quoted"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateFixedRateConfig {
+        fixed_rate_config: Some(FixedRateConfig {
+            duration_premium: Decimal256::percent(200),
+            max_duration: 31536000,
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidFixedRateConfig {}) => (),
+        _ => panic!("Must return invalid fixed rate config error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateFixedRateConfig {
+        fixed_rate_config: Some(FixedRateConfig {
+            duration_premium: Decimal256::percent(10),
+            max_duration: 31536000,
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // utilization_ratio = 600000 / 1000000
+    // floating_rate = 0.00000002 + 0.6 / 0.8 * 0.00000008
+    // fixed_rate = 0.00000008 * (1 + 0.1 * 0.5)
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: FixedRateQuoteResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        FixedRateQuoteResponse {
+            market: "market0000".to_string(),
+            amount: Uint256::from(200000u128),
+            duration: 15768000,
+            utilization: Decimal256::percent(60),
+            floating_rate: rate(80),
+            fixed_rate: rate(84),
+        }
+    );
+
+    let query_msg = QueryMsg::QuoteFixedRate {
+        market: "market0000".to_string(),
+        amount: Uint256::from(200000u128),
+        duration: 31536001,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Fixed rate duration must be positive and at most 31536000"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let query_msg = QueryMsg::QuoteFixedRate {
+        market: "market0000".to_string(),
+        amount: Uint256::from(700000u128),
+        duration: 15768000,
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Not enough liquidity in the market to borrow 700000")
+        }
+        _ => panic!("Must return generic error"),
+    }
 }
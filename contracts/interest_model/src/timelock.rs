@@ -0,0 +1,268 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_curve, read_pending_config, store_config, store_market_curve,
+    store_pending_config, Config, PendingConfig,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+use moneymarket::interest_model::{PendingConfigResponse, CONFIG_UPDATE_DELAY};
+
+// Rates are per block; with 4,656,810 blocks a year the bounds are
+// 100%, 200% and 1,000% a year, in 10^-12 per block
+const MAX_BASE_RATE: u64 = 214_739;
+const MAX_SLOPE1: u64 = 429_478;
+const MAX_SLOPE2: u64 = 2_147_392;
+const RATE_BOUND_UNIT: u64 = 1_000_000_000_000;
+const MIN_OPTIMAL_UTILIZATION: u64 = 50;
+const MAX_RESERVE_FACTOR: u64 = 50;
+const MIN_CONTROLLER_EPOCH_PERIOD: u64 = 3600;
+
+pub(crate) fn max_slope1() -> Decimal256 {
+    Decimal256::from_ratio(MAX_SLOPE1, RATE_BOUND_UNIT)
+}
+
+/// Reject the rate controllers moving slope1 across its bound faster
+/// than a timelocked config update could
+pub(crate) fn assert_controller_bounds(
+    epoch_period: u64,
+    max_step: Decimal256,
+) -> Result<(), ContractError> {
+    if epoch_period < MIN_CONTROLLER_EPOCH_PERIOD {
+        return Err(ContractError::ConfigOutOfBounds("epoch_period".to_string()));
+    }
+
+    let max_controller_step = Decimal256::from_ratio(
+        (Uint256::from(MAX_SLOPE1) * Uint256::from(epoch_period)).0,
+        (Uint256::from(RATE_BOUND_UNIT) * Uint256::from(CONFIG_UPDATE_DELAY)).0,
+    );
+    if max_step > max_controller_step {
+        return Err(ContractError::ConfigOutOfBounds("max_step".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Reject the parameters out of the bounds compiled into the contract
+pub(crate) fn assert_config_bounds(
+    base_rate: Option<Decimal256>,
+    optimal_utilization: Option<Decimal256>,
+    slope1: Option<Decimal256>,
+    slope2: Option<Decimal256>,
+    reserve_factor: Option<Decimal256>,
+) -> Result<(), ContractError> {
+    if let Some(base_rate) = base_rate {
+        if base_rate > Decimal256::from_ratio(MAX_BASE_RATE, RATE_BOUND_UNIT) {
+            return Err(ContractError::ConfigOutOfBounds("base_rate".to_string()));
+        }
+    }
+
+    if let Some(optimal_utilization) = optimal_utilization {
+        if optimal_utilization < Decimal256::percent(MIN_OPTIMAL_UTILIZATION)
+            || optimal_utilization > Decimal256::one()
+        {
+            return Err(ContractError::ConfigOutOfBounds(
+                "optimal_utilization".to_string(),
+            ));
+        }
+    }
+
+    if let Some(slope1) = slope1 {
+        if slope1 > max_slope1() {
+            return Err(ContractError::ConfigOutOfBounds("slope1".to_string()));
+        }
+    }
+
+    if let Some(slope2) = slope2 {
+        if slope2 > Decimal256::from_ratio(MAX_SLOPE2, RATE_BOUND_UNIT) {
+            return Err(ContractError::ConfigOutOfBounds("slope2".to_string()));
+        }
+    }
+
+    if let Some(reserve_factor) = reserve_factor {
+        if reserve_factor > Decimal256::percent(MAX_RESERVE_FACTOR) {
+            return Err(ContractError::ConfigOutOfBounds(
+                "reserve_factor".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn propose_config_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market: Option<Addr>,
+    owner: Option<Addr>,
+    base_rate: Option<Decimal256>,
+    optimal_utilization: Option<Decimal256>,
+    slope1: Option<Decimal256>,
+    slope2: Option<Decimal256>,
+    reserve_factor: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    assert_config_bounds(
+        base_rate,
+        optimal_utilization,
+        slope1,
+        slope2,
+        reserve_factor,
+    )?;
+
+    let market_raw = market
+        .as_ref()
+        .map(|market| deps.api.addr_canonicalize(market.as_str()))
+        .transpose()?;
+    let executable_at = env.block.time.seconds() + CONFIG_UPDATE_DELAY;
+    let pending_config = PendingConfig {
+        owner: owner
+            .map(|owner| deps.api.addr_canonicalize(owner.as_str()))
+            .transpose()?,
+        base_rate,
+        optimal_utilization,
+        slope1,
+        slope2,
+        reserve_factor,
+        executable_at,
+    };
+    store_pending_config(deps.storage, market_raw.as_ref(), Some(pending_config))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_config_update"),
+        attr(
+            "market",
+            market.map(|market| market.to_string()).unwrap_or_default(),
+        ),
+        attr("executable_at", executable_at.to_string()),
+    ]))
+}
+
+pub fn execute_config_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_raw = market
+        .as_ref()
+        .map(|market| deps.api.addr_canonicalize(market.as_str()))
+        .transpose()?;
+    let pending_config = read_pending_config(deps.storage, market_raw.as_ref())?
+        .ok_or(ContractError::NoPendingConfig {})?;
+    if env.block.time.seconds() < pending_config.executable_at {
+        return Err(ContractError::ConfigUpdateNotReady(
+            pending_config.executable_at,
+        ));
+    }
+
+    if let Some(owner) = pending_config.owner {
+        config.owner = owner;
+    }
+
+    // a market without a curve starts from the default curve
+    let mut curve = read_curve(deps.storage, market_raw.as_ref())?;
+    if let Some(base_rate) = pending_config.base_rate {
+        curve.base_rate = base_rate;
+    }
+
+    if let Some(optimal_utilization) = pending_config.optimal_utilization {
+        curve.optimal_utilization = optimal_utilization;
+    }
+
+    if let Some(slope1) = pending_config.slope1 {
+        curve.slope1 = slope1;
+    }
+
+    if let Some(slope2) = pending_config.slope2 {
+        curve.slope2 = slope2;
+    }
+
+    if let Some(reserve_factor) = pending_config.reserve_factor {
+        curve.reserve_factor = reserve_factor;
+    }
+
+    match market_raw.as_ref() {
+        Some(market_raw) => store_market_curve(deps.storage, market_raw, &curve)?,
+        None => {
+            config.base_rate = curve.base_rate;
+            config.optimal_utilization = curve.optimal_utilization;
+            config.slope1 = curve.slope1;
+            config.slope2 = curve.slope2;
+            config.reserve_factor = curve.reserve_factor;
+        }
+    }
+
+    store_pending_config(deps.storage, market_raw.as_ref(), None)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "execute_config_update"),
+        attr(
+            "market",
+            market.map(|market| market.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn cancel_config_update(
+    deps: DepsMut,
+    info: MessageInfo,
+    market: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_raw = market
+        .map(|market| deps.api.addr_canonicalize(market.as_str()))
+        .transpose()?;
+    if read_pending_config(deps.storage, market_raw.as_ref())?.is_none() {
+        return Err(ContractError::NoPendingConfig {});
+    }
+    store_pending_config(deps.storage, market_raw.as_ref(), None)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "cancel_config_update")]))
+}
+
+pub fn query_pending_config(deps: Deps, market: Option<Addr>) -> StdResult<PendingConfigResponse> {
+    let market_raw = market
+        .map(|market| deps.api.addr_canonicalize(market.as_str()))
+        .transpose()?;
+    let pending_config = read_pending_config(deps.storage, market_raw.as_ref())?;
+
+    Ok(match pending_config {
+        Some(pending_config) => PendingConfigResponse {
+            owner: pending_config
+                .owner
+                .map(|owner| Ok::<String, StdError>(deps.api.addr_humanize(&owner)?.to_string()))
+                .transpose()?,
+            base_rate: pending_config.base_rate,
+            optimal_utilization: pending_config.optimal_utilization,
+            slope1: pending_config.slope1,
+            slope2: pending_config.slope2,
+            reserve_factor: pending_config.reserve_factor,
+            executable_at: Some(pending_config.executable_at),
+        },
+        None => PendingConfigResponse {
+            owner: None,
+            base_rate: None,
+            optimal_utilization: None,
+            slope1: None,
+            slope2: None,
+            reserve_factor: None,
+            executable_at: None,
+        },
+    })
+}
@@ -1,7 +1,8 @@
 use crate::error::ContractError;
+use crate::price_band::observe_price;
 use crate::state::{
-    read_bid, read_bids_by_collateral, read_bids_by_user, read_config, remove_bid, store_bid, Bid,
-    Config,
+    read_bid, read_bids_by_collateral, read_bids_by_user, read_config, read_config_for_collateral,
+    read_price_band, remove_bid, store_bid, Bid, Config,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -26,7 +27,7 @@ pub fn submit_bid(
         return Err(ContractError::AlreadyBidForCollateral(collateral_token));
     }
 
-    let config: Config = read_config(deps.storage)?;
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
     if config.max_premium_rate < premium_rate {
         return Err(ContractError::PremiumExceedsMaxPremium(
             config.max_premium_rate.to_string(),
@@ -116,8 +117,8 @@ pub fn execute_bid(
     collateral_token: Addr,
     amount: Uint256,
 ) -> Result<Response, ContractError> {
-    let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
     let bidder_raw = deps.api.addr_canonicalize(liquidator.as_str())?;
     let bid: Bid = read_bid(deps.storage, &bidder_raw, &collateral_token_raw)?;
 
@@ -136,6 +137,18 @@ pub fn execute_bid(
     let collateral_value = amount * price.rate;
     let required_stable = collateral_value
         * (Decimal256::one() - std::cmp::min(bid.premium_rate, config.max_premium_rate));
+    if let Some(price_band) = read_price_band(deps.storage)? {
+        if !observe_price(
+            deps.storage,
+            &env,
+            &price_band,
+            &collateral_token_raw,
+            price.rate,
+        )? {
+            return Err(ContractError::UnconfirmedPriceMove {});
+        }
+    }
+
     if required_stable > bid.amount {
         return Err(ContractError::InsufficientBidBalance(
             required_stable.into(),
@@ -5,18 +5,24 @@ use crate::bid::{
     execute_bid, query_bid, query_bids_by_collateral, query_bids_by_user, retract_bid, submit_bid,
 };
 use crate::error::ContractError;
-use crate::state::{read_config, store_config, Config};
+use crate::price_band::{query_price_band, query_price_reference, record_price, update_price_band};
+use crate::state::{
+    read_collateral_config, read_config, read_config_for_collateral, read_target_ltv,
+    store_collateral_config, store_config, store_target_ltv, CollateralConfig, Config,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    attr, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult,
 };
 use cw20::Cw20ReceiveMsg;
 use moneymarket::common::optional_addr_validate;
 use moneymarket::liquidation::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
+    CollateralConfig as CollateralConfigMsg, CollateralConfigResponse, ConfigResponse, Cw20HookMsg,
+    ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg, TargetLtvResponse,
 };
-use moneymarket::querier::query_tax_rate;
+use moneymarket::querier::{query_tax_rate, store_fee_model, FeeModel};
 use moneymarket::tokens::TokensHuman;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -76,6 +82,7 @@ pub fn execute(
                 price_timeframe,
             )
         }
+        ExecuteMsg::UpdateFeeModel { fee_model } => update_fee_model(deps, info, fee_model),
         ExecuteMsg::SubmitBid {
             collateral_token,
             premium_rate,
@@ -95,6 +102,24 @@ pub fn execute(
             let api = deps.api;
             retract_bid(deps, info, api.addr_validate(&collateral_token)?, amount)
         }
+        ExecuteMsg::UpdateTargetLtv { target_ltv } => update_target_ltv(deps, info, target_ltv),
+        ExecuteMsg::UpdateCollateralConfig {
+            collateral_token,
+            collateral_config,
+        } => {
+            let api = deps.api;
+            update_collateral_config(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                collateral_config,
+            )
+        }
+        ExecuteMsg::UpdatePriceBand { price_band } => update_price_band(deps, info, price_band),
+        ExecuteMsg::RecordPrice { collateral_token } => {
+            let api = deps.api;
+            record_price(deps, env, api.addr_validate(&collateral_token)?)
+        }
     }
 }
 
@@ -110,6 +135,7 @@ pub fn receive_cw20(
             liquidator,
             repay_address,
             fee_address,
+            ..
         }) => {
             let collateral_token = contract_addr.to_string();
             let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
@@ -185,6 +211,93 @@ pub fn update_config(
     Ok(Response::default())
 }
 
+pub fn update_fee_model(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_model: FeeModel,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_fee_model(deps.storage, &fee_model)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fee_model"),
+        attr("fee_model", fee_model.to_string()),
+    ]))
+}
+
+pub fn update_target_ltv(
+    deps: DepsMut,
+    info: MessageInfo,
+    target_ltv: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(target_ltv) = target_ltv {
+        if target_ltv.is_zero() || target_ltv >= Decimal256::one() {
+            return Err(ContractError::InvalidTargetLtv {});
+        }
+    }
+
+    store_target_ltv(deps.storage, target_ltv)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_target_ltv"),
+        attr(
+            "target_ltv",
+            target_ltv.map(|t| t.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn update_collateral_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    collateral_config: Option<CollateralConfigMsg>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_config = collateral_config.map(|c| CollateralConfig {
+        safe_ratio: c.safe_ratio,
+        bid_fee: c.bid_fee,
+        max_premium_rate: c.max_premium_rate,
+    });
+    if let Some(collateral_config) = &collateral_config {
+        if [
+            collateral_config.safe_ratio,
+            collateral_config.bid_fee,
+            collateral_config.max_premium_rate,
+        ]
+        .iter()
+        .flatten()
+        .any(|rate| *rate >= Decimal256::one())
+        {
+            return Err(ContractError::InvalidCollateralConfig {});
+        }
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    store_collateral_config(deps.storage, &collateral_token_raw, collateral_config)?;
+
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_collateral_config"),
+        attr("collateral_token", collateral_token),
+        attr("safe_ratio", config.safe_ratio.to_string()),
+        attr("bid_fee", config.bid_fee.to_string()),
+        attr("max_premium_rate", config.max_premium_rate.to_string()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -237,6 +350,18 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 limit,
             )?)
         }
+        QueryMsg::TargetLtv {} => to_binary(&TargetLtvResponse {
+            target_ltv: read_target_ltv(deps.storage)?,
+        }),
+        QueryMsg::CollateralConfig { collateral_token } => {
+            let collateral_addr = deps.api.addr_validate(&collateral_token)?;
+            to_binary(&query_collateral_config(deps, collateral_addr)?)
+        }
+        QueryMsg::PriceBand {} => to_binary(&query_price_band(deps)?),
+        QueryMsg::PriceReference { collateral_token } => {
+            let collateral_addr = deps.api.addr_validate(&collateral_token)?;
+            to_binary(&query_price_reference(deps, collateral_addr)?)
+        }
     }
 }
 
@@ -256,6 +381,23 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(resp)
 }
 
+fn query_collateral_config(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<CollateralConfigResponse> {
+    let config: Config = read_config_for_collateral(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(CollateralConfigResponse {
+        collateral_token: collateral_token.to_string(),
+        safe_ratio: config.safe_ratio,
+        bid_fee: config.bid_fee,
+        max_premium_rate: config.max_premium_rate,
+    })
+}
+
 fn query_liquidation_amount(
     deps: Deps,
     borrow_amount: Uint256,
@@ -273,17 +415,53 @@ fn query_liquidation_amount(
     }
 
     let mut collaterals_value = Uint256::zero();
+    let mut collateral_configs: Vec<(Uint256, Option<CollateralConfig>)> = vec![];
     for c in collaterals.iter().zip(collateral_prices.iter()) {
         let (collateral, price) = c;
         let collateral_value = collateral.1 * *price;
         collaterals_value += collateral_value;
+
+        let collateral_config =
+            read_collateral_config(deps.storage, &deps.api.addr_canonicalize(&collateral.0)?)?;
+        collateral_configs.push((collateral_value, collateral_config));
     }
 
     let tax_rate = query_tax_rate(deps)?;
 
-    let fee_deductor = (Decimal256::one() - config.max_premium_rate)
-        * (Decimal256::one() - config.bid_fee)
-        * (Decimal256::one() - tax_rate);
+    // With per-collateral overrides, the fee deductor and the safe ratio
+    // are averaged over the collaterals weighted by their value
+    let (fee_deductor, safe_ratio) = if collaterals_value.is_zero()
+        || collateral_configs.iter().all(|(_, c)| c.is_none())
+    {
+        (
+            (Decimal256::one() - config.max_premium_rate) * (Decimal256::one() - config.bid_fee),
+            config.safe_ratio,
+        )
+    } else {
+        collateral_configs.iter().fold(
+            (Decimal256::zero(), Decimal256::zero()),
+            |(fee_deductor, safe_ratio), (collateral_value, collateral_config)| {
+                let collateral_config = collateral_config.clone().unwrap_or(CollateralConfig {
+                    safe_ratio: None,
+                    bid_fee: None,
+                    max_premium_rate: None,
+                });
+                let max_premium_rate = collateral_config
+                    .max_premium_rate
+                    .unwrap_or(config.max_premium_rate);
+                let bid_fee = collateral_config.bid_fee.unwrap_or(config.bid_fee);
+                let weight = Decimal256::from_ratio(*collateral_value, collaterals_value);
+                (
+                    fee_deductor
+                        + weight
+                            * (Decimal256::one() - max_premium_rate)
+                            * (Decimal256::one() - bid_fee),
+                    safe_ratio + weight * collateral_config.safe_ratio.unwrap_or(config.safe_ratio),
+                )
+            },
+        )
+    };
+    let fee_deductor = fee_deductor * (Decimal256::one() - tax_rate);
 
     // expected_repay_amount must be bigger than borrow_amount
     // else force liquidate all collaterals
@@ -294,9 +472,18 @@ fn query_liquidation_amount(
 
     // When collaterals_value is smaller than liquidation_threshold,
     // liquidate all collaterals
-    let safe_borrow_amount = borrow_limit * config.safe_ratio;
+    let safe_borrow_amount = borrow_limit * safe_ratio;
+    let target_ltv = read_target_ltv(deps.storage)?;
     let liquidation_ratio = if collaterals_value < config.liquidation_threshold {
         Decimal256::from_uint256(borrow_amount) / Decimal256::from_uint256(expected_repay_amount)
+    } else if let Some(target_ltv) =
+        target_ltv.filter(|target_ltv| borrow_amount > collaterals_value * *target_ltv)
+    {
+        // sold_value * fee_deductor is repaid, so the smallest sold_value with
+        //   (borrow_amount - sold_value * fee_deductor) <= (collaterals_value - sold_value) * target_ltv
+        // is (borrow_amount - collaterals_value * target_ltv) / (fee_deductor - target_ltv)
+        Decimal256::from_uint256(borrow_amount - collaterals_value * target_ltv)
+            / (Decimal256::from_uint256(collaterals_value) * (fee_deductor - target_ltv))
     } else {
         Decimal256::from_uint256(borrow_amount - safe_borrow_amount)
             / Decimal256::from_uint256(expected_repay_amount - safe_borrow_amount)
@@ -32,4 +32,29 @@ pub enum ContractError {
 
     #[error("Retract amount cannot exceed bid balance: {0}")]
     RetractExceedsBid(u128),
+
+    #[error("Target LTV must be bigger than zero and smaller than one")]
+    InvalidTargetLtv {},
+
+    #[error("Collateral config rates must be smaller than one")]
+    InvalidCollateralConfig {},
+
+    #[error("Max price change and confirmation blocks must be bigger than zero")]
+    InvalidPriceBand {},
+
+    #[error("Price band is not enabled")]
+    PriceBandNotEnabled {},
+
+    #[error("Oracle price moved out of the price band and is not confirmed yet")]
+    UnconfirmedPriceMove {},
+}
+
+// Queries only return standard errors
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
 }
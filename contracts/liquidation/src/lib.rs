@@ -1,6 +1,7 @@
 mod bid;
 pub mod contract;
 pub mod error;
+mod price_band;
 pub mod state;
 
 #[cfg(test)]
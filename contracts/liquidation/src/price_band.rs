@@ -0,0 +1,195 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_price_band, read_price_reference, store_price_band, store_price_reference,
+    Config, PriceBand, PriceObservation, PriceReference,
+};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+};
+use moneymarket::liquidation::{
+    PriceBand as PriceBandMsg, PriceBandResponse, PriceObservation as PriceObservationMsg,
+    PriceReferenceResponse,
+};
+use moneymarket::oracle::PriceResponse;
+use moneymarket::querier::{query_price, TimeConstraints};
+
+pub fn update_price_band(
+    deps: DepsMut,
+    info: MessageInfo,
+    price_band: Option<PriceBandMsg>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_price_band")];
+    let price_band = match price_band {
+        Some(price_band) => {
+            if price_band.max_price_change.is_zero() || price_band.confirmation_blocks == 0 {
+                return Err(ContractError::InvalidPriceBand {});
+            }
+            attributes.push(attr(
+                "max_price_change",
+                price_band.max_price_change.to_string(),
+            ));
+            attributes.push(attr(
+                "confirmation_blocks",
+                price_band.confirmation_blocks.to_string(),
+            ));
+            Some(PriceBand {
+                max_price_change: price_band.max_price_change,
+                confirmation_blocks: price_band.confirmation_blocks,
+            })
+        }
+        None => None,
+    };
+
+    store_price_band(deps.storage, price_band)?;
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn record_price(
+    deps: DepsMut,
+    env: Env,
+    collateral_token: Addr,
+) -> Result<Response, ContractError> {
+    let price_band: PriceBand =
+        read_price_band(deps.storage)?.ok_or(ContractError::PriceBandNotEnabled {})?;
+
+    let config: Config = read_config(deps.storage)?;
+    let price: PriceResponse = query_price(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.to_string(),
+        config.stable_denom,
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let confirmed = observe_price(
+        deps.storage,
+        &env,
+        &price_band,
+        &collateral_token_raw,
+        price.rate,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "record_price"),
+        attr("collateral_token", collateral_token),
+        attr("price", price.rate.to_string()),
+        attr("confirmed", confirmed.to_string()),
+    ]))
+}
+
+/// Updates the price reference of the collateral with the oracle price and
+/// returns whether the price is confirmed. Prices within the band of the
+/// confirmed price are confirmed at once, while a price move out of the band
+/// is only confirmed once recorded again within the band of its first record,
+/// at least `confirmation_blocks` later
+pub(crate) fn observe_price(
+    storage: &mut dyn Storage,
+    env: &Env,
+    price_band: &PriceBand,
+    collateral_token: &CanonicalAddr,
+    price: Decimal256,
+) -> StdResult<bool> {
+    let observation = PriceObservation {
+        price,
+        height: env.block.height,
+    };
+    let (price_reference, confirmed) = match read_price_reference(storage, collateral_token)? {
+        Some(price_reference) if !in_band(price_band, price_reference.confirmed.price, price) => {
+            match price_reference.pending {
+                Some(pending) if in_band(price_band, pending.price, price) => {
+                    if env.block.height >= pending.height + price_band.confirmation_blocks {
+                        (
+                            PriceReference {
+                                confirmed: observation,
+                                pending: None,
+                            },
+                            true,
+                        )
+                    } else {
+                        (
+                            PriceReference {
+                                pending: Some(pending),
+                                ..price_reference
+                            },
+                            false,
+                        )
+                    }
+                }
+                _ => (
+                    PriceReference {
+                        pending: Some(observation),
+                        ..price_reference
+                    },
+                    false,
+                ),
+            }
+        }
+        _ => (
+            PriceReference {
+                confirmed: observation,
+                pending: None,
+            },
+            true,
+        ),
+    };
+
+    store_price_reference(storage, collateral_token, &price_reference)?;
+    Ok(confirmed)
+}
+
+fn in_band(price_band: &PriceBand, reference_price: Decimal256, price: Decimal256) -> bool {
+    let price_change = if price > reference_price {
+        price - reference_price
+    } else {
+        reference_price - price
+    };
+
+    price_change <= reference_price * price_band.max_price_change
+}
+
+pub fn query_price_band(deps: Deps) -> StdResult<PriceBandResponse> {
+    Ok(PriceBandResponse {
+        price_band: read_price_band(deps.storage)?.map(|price_band| PriceBandMsg {
+            max_price_change: price_band.max_price_change,
+            confirmation_blocks: price_band.confirmation_blocks,
+        }),
+    })
+}
+
+pub fn query_price_reference(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<PriceReferenceResponse> {
+    let price_reference = read_price_reference(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+    let observation = |observation: PriceObservation| PriceObservationMsg {
+        price: observation.price,
+        height: observation.height,
+    };
+
+    Ok(match price_reference {
+        Some(price_reference) => PriceReferenceResponse {
+            collateral_token: collateral_token.to_string(),
+            confirmed: Some(observation(price_reference.confirmed)),
+            pending: price_reference.pending.map(observation),
+        },
+        None => PriceReferenceResponse {
+            collateral_token: collateral_token.to_string(),
+            confirmed: None,
+            pending: None,
+        },
+    })
+}
@@ -2,11 +2,19 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 use moneymarket::liquidation::BidResponse;
+use moneymarket::pagination::{calc_limit, calc_range_start};
+
+use crate::error::ContractError;
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_TARGET_LTV: &[u8] = b"target_ltv";
+static KEY_PRICE_BAND: &[u8] = b"price_band";
+
+static PREFIX_COLLATERAL_CONFIG: &[u8] = b"collateral_config";
+static PREFIX_PRICE_REFERENCE: &[u8] = b"price_reference";
 
 static PREFIX_BID: &[u8] = b"bid";
 static PREFIX_BID_BY_USER: &[u8] = b"bid_by_user";
@@ -32,6 +40,130 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+pub fn store_target_ltv(
+    storage: &mut dyn Storage,
+    target_ltv: Option<Decimal256>,
+) -> StdResult<()> {
+    match target_ltv {
+        Some(target_ltv) => singleton(storage, KEY_TARGET_LTV).save(&target_ltv),
+        None => {
+            singleton::<Decimal256>(storage, KEY_TARGET_LTV).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_target_ltv(storage: &dyn Storage) -> StdResult<Option<Decimal256>> {
+    singleton_read(storage, KEY_TARGET_LTV).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceBand {
+    pub max_price_change: Decimal256,
+    pub confirmation_blocks: u64,
+}
+
+pub fn store_price_band(storage: &mut dyn Storage, price_band: Option<PriceBand>) -> StdResult<()> {
+    match price_band {
+        Some(price_band) => singleton(storage, KEY_PRICE_BAND).save(&price_band),
+        None => {
+            singleton::<PriceBand>(storage, KEY_PRICE_BAND).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_price_band(storage: &dyn Storage) -> StdResult<Option<PriceBand>> {
+    singleton_read(storage, KEY_PRICE_BAND).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceObservation {
+    pub price: Decimal256,
+    pub height: u64,
+}
+
+/// Last confirmed oracle price of a collateral, and the
+/// first record of a price move out of its band
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceReference {
+    pub confirmed: PriceObservation,
+    pub pending: Option<PriceObservation>,
+}
+
+pub fn store_price_reference(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    price_reference: &PriceReference,
+) -> StdResult<()> {
+    let mut price_reference_bucket: Bucket<PriceReference> =
+        Bucket::new(storage, PREFIX_PRICE_REFERENCE);
+    price_reference_bucket.save(collateral_token.as_slice(), price_reference)
+}
+
+pub fn read_price_reference(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<PriceReference>> {
+    let price_reference_bucket: ReadonlyBucket<PriceReference> =
+        ReadonlyBucket::new(storage, PREFIX_PRICE_REFERENCE);
+    price_reference_bucket.may_load(collateral_token.as_slice())
+}
+
+/// Per-collateral overrides of the flat config values
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralConfig {
+    pub safe_ratio: Option<Decimal256>,
+    pub bid_fee: Option<Decimal256>,
+    pub max_premium_rate: Option<Decimal256>,
+}
+
+pub fn store_collateral_config(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    collateral_config: Option<CollateralConfig>,
+) -> StdResult<()> {
+    let mut collateral_config_bucket: Bucket<CollateralConfig> =
+        Bucket::new(storage, PREFIX_COLLATERAL_CONFIG);
+    match collateral_config {
+        Some(collateral_config) => {
+            collateral_config_bucket.save(collateral_token.as_slice(), &collateral_config)
+        }
+        None => {
+            collateral_config_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_collateral_config(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<CollateralConfig>> {
+    let collateral_config_bucket: ReadonlyBucket<CollateralConfig> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_CONFIG);
+    collateral_config_bucket.may_load(collateral_token.as_slice())
+}
+
+/// Returns the config with the overrides of the given collateral applied
+pub fn read_config_for_collateral(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Config> {
+    let config: Config = read_config(storage)?;
+    Ok(match read_collateral_config(storage, collateral_token)? {
+        Some(collateral_config) => Config {
+            safe_ratio: collateral_config.safe_ratio.unwrap_or(config.safe_ratio),
+            bid_fee: collateral_config.bid_fee.unwrap_or(config.bid_fee),
+            max_premium_rate: collateral_config
+                .max_premium_rate
+                .unwrap_or(config.max_premium_rate),
+            ..config
+        },
+        None => config,
+    })
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Bid {
     pub amount: Uint256,
@@ -91,12 +223,9 @@ pub fn read_bid<'a>(
 
     bid_bucket
         .load(&[bidder.as_slice(), collateral_token.as_slice()].concat())
-        .map_err(|_| StdError::generic_err("No bids with the specified information exist"))
+        .map_err(|_| ContractError::NoBidExists {}.into())
 }
 
-// settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
 pub fn read_bids_by_collateral(
     deps: Deps,
     collateral_token: &CanonicalAddr,
@@ -108,7 +237,7 @@ pub fn read_bids_by_collateral(
         &[PREFIX_BID_BY_COLLATERAL, collateral_token.as_slice()],
     );
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit(limit);
     let start = calc_range_start(start_after);
 
     bid_bucket
@@ -143,7 +272,7 @@ pub fn read_bids_by_user(
     let bid_bucket: ReadonlyBucket<bool> =
         ReadonlyBucket::multilevel(deps.storage, &[PREFIX_BID_BY_USER, bidder.as_slice()]);
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit(limit);
     let start = calc_range_start(start_after);
 
     bid_bucket
@@ -168,12 +297,3 @@ pub fn read_bids_by_user(
         })
         .collect()
 }
-
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
-    start_after.map(|addr| {
-        let mut v = addr.as_slice().to_vec();
-        v.push(1);
-        v
-    })
-}
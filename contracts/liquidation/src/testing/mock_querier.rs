@@ -146,6 +146,8 @@ impl WasmMockQuerier {
                                 rate: v.0,
                                 last_updated_base: v.1,
                                 last_updated_quote: v.2,
+                                twap_rate: None,
+                                confidence: None,
                             })))
                         }
                         None => SystemResult::Err(SystemError::InvalidRequest {
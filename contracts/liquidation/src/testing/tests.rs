@@ -1,16 +1,18 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
-use crate::testing::mock_querier::mock_dependencies;
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
 use cosmwasm_std::{
-    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, OwnedDeps, SubMsg, Uint128,
+    WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::liquidation::{
-    BidResponse, BidsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    LiquidationAmountResponse, QueryMsg,
+    BidResponse, BidsResponse, CollateralConfig, CollateralConfigResponse, ConfigResponse,
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, PriceBand,
+    PriceBandResponse, PriceObservation, PriceReferenceResponse, QueryMsg, TargetLtvResponse,
 };
 
 #[test]
@@ -354,6 +356,7 @@ fn execute_bid() {
             liquidator: "addr0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -373,6 +376,7 @@ fn execute_bid() {
             liquidator: "addr0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -413,6 +417,7 @@ fn execute_bid() {
             liquidator: "addr0000".to_string(),
             fee_address: None,
             repay_address: None,
+            borrower: None,
         })
         .unwrap(),
     });
@@ -849,3 +854,538 @@ fn query_bids_by_collateral() {
         }
     );
 }
+
+#[test]
+fn query_liquidation_amount_target_ltv() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000u64),
+        price_timeframe: 60u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateTargetLtv {
+        target_ltv: Some(Decimal256::percent(60)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateTargetLtv {
+        target_ltv: Some(Decimal256::one()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg);
+    match res {
+        Err(ContractError::InvalidTargetLtv {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateTargetLtv {
+        target_ltv: Some(Decimal256::percent(60)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_target_ltv"),
+            attr("target_ltv", "0.6"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::TargetLtv {}).unwrap();
+    let res: TargetLtvResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        TargetLtvResponse {
+            target_ltv: Some(Decimal256::percent(60)),
+        }
+    );
+
+    // fee_deductor = 0.931095
+    // sold_value = (800,000 - 600,000) / (0.931095 - 0.6) = 604,056
+    // 800,000 - 604,056 * 0.931095 = 0.6 * (1,000,000 - 604,056)
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(800000u64),
+        borrow_limit: Uint256::from(700000u64),
+        collaterals: vec![
+            ("token0000".to_string(), Uint256::from(500000u64)),
+            ("token0001".to_string(), Uint256::from(1000000u64)),
+        ],
+        collateral_prices: vec![Decimal256::one(), Decimal256::percent(50)],
+    };
+    let res = query(deps.as_ref(), mock_env(), msg.clone()).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![
+                ("token0000".to_string(), Uint256::from(302028u64)),
+                ("token0001".to_string(), Uint256::from(604056u64)),
+            ],
+        }
+    );
+
+    // already within the target LTV, sized by the safe ratio
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::LiquidationAmount {
+            borrow_amount: Uint256::from(550000u64),
+            borrow_limit: Uint256::from(500000u64),
+            collaterals: vec![("token0000".to_string(), Uint256::from(1000000u64))],
+            collateral_prices: vec![Decimal256::one()],
+        },
+    )
+    .unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    // (550,000 - 50,000) / (931,095 - 50,000)
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(567475u64))],
+        }
+    );
+
+    let msg_update = ExecuteMsg::UpdateTargetLtv { target_ltv: None };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg_update,
+    )
+    .unwrap();
+
+    // (800,000 - 70,000) / (931,095 - 70,000)
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![
+                ("token0000".to_string(), Uint256::from(423878u64)),
+                ("token0001".to_string(), Uint256::from(847757u64)),
+            ],
+        }
+    );
+}
+
+#[test]
+fn collateral_config() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000u64),
+        price_timeframe: 60u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateCollateralConfig {
+        collateral_token: "token0000".to_string(),
+        collateral_config: Some(CollateralConfig {
+            safe_ratio: None,
+            bid_fee: Some(Decimal256::percent(2)),
+            max_premium_rate: Some(Decimal256::percent(20)),
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateCollateralConfig {
+            collateral_token: "token0000".to_string(),
+            collateral_config: Some(CollateralConfig {
+                safe_ratio: None,
+                bid_fee: Some(Decimal256::one()),
+                max_premium_rate: None,
+            }),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidCollateralConfig {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_collateral_config"),
+            attr("collateral_token", "token0000"),
+            attr("safe_ratio", "0.1"),
+            attr("bid_fee", "0.02"),
+            attr("max_premium_rate", "0.2"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralConfig {
+            collateral_token: "token0000".to_string(),
+        },
+    )
+    .unwrap();
+    let res: CollateralConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        CollateralConfigResponse {
+            collateral_token: "token0000".to_string(),
+            safe_ratio: Decimal256::percent(10),
+            bid_fee: Decimal256::percent(2),
+            max_premium_rate: Decimal256::percent(20),
+        }
+    );
+
+    // the override only applies to token0000
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::SubmitBid {
+            collateral_token: "token0000".to_string(),
+            premium_rate: Decimal256::percent(10),
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SubmitBid {
+            collateral_token: "token0001".to_string(),
+            premium_rate: Decimal256::percent(10),
+        },
+    );
+    match res {
+        Err(ContractError::PremiumExceedsMaxPremium(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // fee_deductor = 0.8 * 0.98 * 0.99 = 0.77616
+    // (550,000 - 50,000) / (776,160 - 50,000)
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(550000u64),
+        borrow_limit: Uint256::from(500000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(1000000u64))],
+        collateral_prices: vec![Decimal256::one()],
+    };
+    let res = query(deps.as_ref(), mock_env(), msg.clone()).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(688553u64))],
+        }
+    );
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateCollateralConfig {
+            collateral_token: "token0000".to_string(),
+            collateral_config: None,
+        },
+    )
+    .unwrap();
+
+    // back to the flat config
+    // (550,000 - 50,000) / (931,095 - 50,000)
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(567475u64))],
+        }
+    );
+}
+
+#[test]
+fn price_band() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    };
+
+    let env = mock_env();
+    let set_price = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                     price: Decimal256| {
+        deps.querier.with_oracle_price(&[(
+            &("asset0000".to_string(), "uusd".to_string()),
+            &(price, env.block.time.seconds(), env.block.time.seconds()),
+        )]);
+    };
+    set_price(&mut deps, Decimal256::percent(50));
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_rate: Decimal256::percent(1),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let record_msg = ExecuteMsg::RecordPrice {
+        collateral_token: "asset0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        record_msg.clone(),
+    );
+    match res {
+        Err(ContractError::PriceBandNotEnabled {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdatePriceBand {
+        price_band: Some(PriceBand {
+            max_price_change: Decimal256::percent(10),
+            confirmation_blocks: 5u64,
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let invalid_msg = ExecuteMsg::UpdatePriceBand {
+        price_band: Some(PriceBand {
+            max_price_change: Decimal256::percent(10),
+            confirmation_blocks: 0u64,
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        invalid_msg,
+    );
+    match res {
+        Err(ContractError::InvalidPriceBand {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_price_band"),
+            attr("max_price_change", "0.1"),
+            attr("confirmation_blocks", "5"),
+        ]
+    );
+
+    let res: PriceBandResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PriceBand {}).unwrap()).unwrap();
+    assert_eq!(
+        res.price_band,
+        Some(PriceBand {
+            max_price_change: Decimal256::percent(10),
+            confirmation_blocks: 5u64,
+        })
+    );
+
+    // the first record is confirmed at once
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        record_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "record_price"),
+            attr("collateral_token", "asset0000"),
+            attr("price", "0.5"),
+            attr("confirmed", "true"),
+        ]
+    );
+
+    let execute_bid_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "addr0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        execute_bid_msg.clone(),
+    )
+    .unwrap();
+
+    // a 20% price drop is out of the band
+    set_price(&mut deps, Decimal256::percent(40));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        execute_bid_msg.clone(),
+    );
+    match res {
+        Err(ContractError::UnconfirmedPriceMove {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        record_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("confirmed", "false"));
+
+    let res: PriceReferenceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PriceReference {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        PriceReferenceResponse {
+            collateral_token: "asset0000".to_string(),
+            confirmed: Some(PriceObservation {
+                price: Decimal256::percent(50),
+                height: env.block.height,
+            }),
+            pending: Some(PriceObservation {
+                price: Decimal256::percent(40),
+                height: env.block.height,
+            }),
+        }
+    );
+
+    // not confirmed before the confirmation blocks
+    let mut env = mock_env();
+    env.block.height += 4;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        execute_bid_msg.clone(),
+    );
+    match res {
+        Err(ContractError::UnconfirmedPriceMove {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height += 1;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        execute_bid_msg.clone(),
+    )
+    .unwrap();
+
+    let res: PriceReferenceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PriceReference {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.confirmed,
+        Some(PriceObservation {
+            price: Decimal256::percent(40),
+            height: env.block.height,
+        })
+    );
+    assert_eq!(res.pending, None);
+
+    // moves within the band of the confirmed price execute right away
+    set_price(&mut deps, Decimal256::percent(42));
+    execute(deps.as_mut(), env, info, execute_bid_msg).unwrap();
+}
@@ -1,6 +1,7 @@
+use crate::error::ContractError;
 use crate::state::Bid;
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Env, StdError, StdResult};
+use cosmwasm_std::Env;
 
 const MAX_SLOT_CAP: u8 = 30u8;
 
@@ -9,20 +10,17 @@ pub fn assert_activate_status(
     env: &Env,
     available_bids: Uint256,
     bid_threshold: Uint256,
-) -> StdResult<()> {
+) -> Result<(), ContractError> {
     match bid.wait_end {
         Some(wait_end) => {
             if available_bids < bid_threshold {
                 // skip waiting period
                 return Ok(());
             } else if wait_end > env.block.time.seconds() {
-                return Err(StdError::generic_err(format!(
-                    "Wait period expires at {}",
-                    wait_end
-                )));
+                return Err(ContractError::WaitPeriodNotExpired(wait_end));
             }
         }
-        None => return Err(StdError::generic_err("Bid is already active")),
+        None => return Err(ContractError::BidAlreadyActive {}),
     }
     Ok(())
 }
@@ -30,13 +28,10 @@ pub fn assert_activate_status(
 pub fn assert_withdraw_amount(
     withdraw_amount: Option<Uint256>,
     withdrawable_amount: Uint256,
-) -> StdResult<Uint256> {
+) -> Result<Uint256, ContractError> {
     let to_withdraw = if let Some(amount) = withdraw_amount {
         if amount > withdrawable_amount {
-            return Err(StdError::generic_err(format!(
-                "Requested amount is bigger than current withdrawable amount ({})",
-                withdrawable_amount
-            )));
+            return Err(ContractError::InvalidWithdrawAmount(withdrawable_amount));
         }
         amount
     } else {
@@ -46,27 +41,28 @@ pub fn assert_withdraw_amount(
     Ok(to_withdraw)
 }
 
-pub fn assert_max_slot(max_slot: u8) -> StdResult<()> {
+pub fn assert_max_slot(max_slot: u8) -> Result<(), ContractError> {
     if max_slot.gt(&MAX_SLOT_CAP) {
-        return Err(StdError::generic_err("Max slot exceeds limit"));
+        return Err(ContractError::MaxSlotExceedsLimit {});
     }
     Ok(())
 }
 
-pub fn assert_fees(fees: Decimal256) -> StdResult<()> {
+pub fn assert_fees(fees: Decimal256) -> Result<(), ContractError> {
     if fees > Decimal256::one() {
-        return Err(StdError::generic_err(
-            "The sum of bid_fee and liquidator_fee can not be greater than one",
-        ));
+        return Err(ContractError::InvalidFees {});
     }
     Ok(())
 }
 
-pub fn assert_max_slot_premium(max_slot: u8, premium_rate_per_slot: Decimal256) -> StdResult<()> {
+pub fn assert_max_slot_premium(
+    max_slot: u8,
+    premium_rate_per_slot: Decimal256,
+) -> Result<(), ContractError> {
     let max_slot_premium =
         premium_rate_per_slot * Decimal256::from_uint256(Uint256::from(max_slot as u128));
     if max_slot_premium >= Decimal256::one() {
-        return Err(StdError::generic_err("Max slot premium rate exceeds limit"));
+        return Err(ContractError::MaxSlotPremiumExceedsLimit {});
     }
     Ok(())
 }
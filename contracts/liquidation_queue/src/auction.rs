@@ -0,0 +1,537 @@
+use crate::bid::{assert_not_frozen, execute_pool_liquidation};
+use crate::error::ContractError;
+use crate::fee::{split_bid_fee, BidFeeSplit};
+use crate::incentives::accrue_pool_rewards;
+use crate::state::{
+    pop_auction_idx, pop_liquidation_log_idx, read_auction, read_auction_config,
+    read_auctions_by_borrower, read_auctions_by_collateral, read_bid_pool, read_collateral_info,
+    read_config, read_config_for_collateral, read_total_bids, remove_auction, store_auction,
+    store_auction_config, store_bid_pool, store_liquidation_log, store_total_bids, Auction,
+    AuctionConfig, BidPool, CollateralInfo, Config, LiquidationLog,
+};
+use crate::top_up::top_up_messages;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, SubMsg, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::liquidation_queue::{
+    AuctionConfig as AuctionConfigMsg, AuctionConfigResponse, AuctionResponse, AuctionsResponse,
+};
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::oracle::PriceResponse;
+use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub fn update_auction_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    auction_config: Option<AuctionConfigMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // fail if the collateral is not whitelisted
+    read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    if let Some(auction_config) = &auction_config {
+        if auction_config.initial_discount >= Decimal256::one() || auction_config.decay_period == 0
+        {
+            return Err(ContractError::InvalidAuctionConfig {});
+        }
+    }
+
+    store_auction_config(
+        deps.storage,
+        &collateral_token_raw,
+        auction_config.map(|auction_config| AuctionConfig {
+            initial_discount: auction_config.initial_discount,
+            decay_period: auction_config.decay_period,
+            expiry_period: auction_config.expiry_period,
+        }),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_auction_config"),
+        attr("collateral_token", collateral_token),
+    ]))
+}
+
+/// Collateral sent for liquidation is put up for sale instead of filling the bid
+/// pools. The loan of the borrower is repaid as bidders buy the collateral
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn start_auction(
+    deps: DepsMut,
+    env: Env,
+    auction_config: AuctionConfig,
+    liquidator: String,
+    repay_address: String,
+    fee_address: String,
+    borrower: Option<String>,
+    collateral_token: String,
+    amount: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let borrower = borrower.ok_or(ContractError::MissingAuctionBorrower {})?;
+
+    let auction_idx: Uint128 = pop_auction_idx(deps.storage)?;
+    store_auction(
+        deps.storage,
+        &Auction {
+            idx: auction_idx,
+            collateral_token: deps.api.addr_canonicalize(&collateral_token)?,
+            borrower: deps.api.addr_canonicalize(&borrower)?,
+            amount,
+            start_time: env.block.time.seconds(),
+            initial_discount: auction_config.initial_discount,
+            decay_period: auction_config.decay_period,
+            expiry_period: auction_config.expiry_period,
+            liquidator: deps.api.addr_canonicalize(&liquidator)?,
+            repay_address: deps.api.addr_canonicalize(&repay_address)?,
+            fee_address: deps.api.addr_canonicalize(&fee_address)?,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "start_auction"),
+        attr("auction_idx", auction_idx),
+        attr("borrower", borrower),
+        attr("collateral_token", collateral_token),
+        attr("collateral_amount", amount),
+    ]))
+}
+
+/// Stable asset is sent to buy the auctioned collateral at the oracle price
+/// minus the current discount. The unused amount is returned to the bidder
+pub fn auction_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_idx: Uint128,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut auction: Auction = read_auction(deps.storage, auction_idx)?;
+    let config: Config = read_config_for_collateral(deps.storage, &auction.collateral_token)?;
+    assert_not_frozen(deps.as_ref(), &config)?;
+
+    let collateral_token = deps
+        .api
+        .addr_humanize(&auction.collateral_token)?
+        .to_string();
+
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAssets(config.stable_denom));
+    }
+
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let price: PriceResponse = query_price(
+        deps.as_ref(),
+        oracle_contract,
+        collateral_token.clone(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let discount = auction_discount(&auction, env.block.time.seconds());
+    let auction_price = price.rate * (Decimal256::one() - discount);
+    if auction_price.is_zero() {
+        return Err(ContractError::ZeroAuctionPrice {});
+    }
+
+    let collateral_amount = auction.amount.min(amount / auction_price);
+    let bid_amount = collateral_amount * auction_price;
+
+    let bid_fee = bid_amount * config.bid_fee;
+    let liquidator_fee = bid_amount * config.liquidator_fee;
+    let repay_amount = bid_amount - bid_fee - liquidator_fee;
+    if collateral_amount.is_zero() || repay_amount.is_zero() {
+        return Err(ContractError::BidTooSmall {});
+    }
+
+    let log = LiquidationLog {
+        idx: pop_liquidation_log_idx(deps.storage)?,
+        height: env.block.height,
+        borrower: Some(auction.borrower.clone()),
+        collateral_token: auction.collateral_token.clone(),
+        collateral_amount,
+        premium_rate: discount,
+        repay_denom: config.stable_denom.clone(),
+        repay_amount,
+        liquidator: auction.liquidator.clone(),
+    };
+    store_liquidation_log(deps.storage, &log)?;
+
+    auction.amount = auction.amount - collateral_amount;
+    if auction.amount.is_zero() {
+        remove_auction(deps.storage, &auction);
+    } else {
+        store_auction(deps.storage, &auction)?;
+    }
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collateral_token.clone(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount: collateral_amount.into(),
+        })?,
+    })];
+
+    let bid_fee_split = split_bid_fee(deps.storage, deps.api, &config.stable_denom, bid_fee)?;
+    messages.extend(repay_messages(
+        deps.as_ref(),
+        &config,
+        &auction,
+        repay_amount,
+        liquidator_fee,
+        &bid_fee_split,
+    )?);
+    let refund_amount = amount - bid_amount;
+    if !refund_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom.clone(),
+                    amount: refund_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "auction_bid"),
+            attr("auction_idx", auction_idx),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", collateral_amount),
+            attr("repay_amount", repay_amount),
+            attr("bid_fee", bid_fee),
+            attr("liquidator_fee", liquidator_fee),
+        ])
+        .add_attributes(bid_fee_split.attributes()))
+}
+
+/// Once expired, the unsold collateral of the auction is liquidated against the
+/// stable bid pools at the oracle price, and the loan repaid with the proceeds
+pub fn expire_auction(
+    deps: DepsMut,
+    env: Env,
+    auction_idx: Uint128,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let auction: Auction = read_auction(deps.storage, auction_idx)?;
+    let config: Config = read_config_for_collateral(deps.storage, &auction.collateral_token)?;
+    assert_not_frozen(deps.as_ref(), &config)?;
+
+    let expires_at = auction_expiry(&auction);
+    if env.block.time.seconds() < expires_at {
+        return Err(ContractError::AuctionNotExpired(expires_at));
+    }
+
+    let collateral_token = deps
+        .api
+        .addr_humanize(&auction.collateral_token)?
+        .to_string();
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &auction.collateral_token)?;
+    let available_bids: Uint256 = read_total_bids(
+        deps.storage,
+        &auction.collateral_token,
+        &config.stable_denom,
+    )
+    .unwrap_or_default();
+
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let price: PriceResponse = query_price(
+        deps.as_ref(),
+        oracle_contract,
+        collateral_token.clone(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let mut remaining_collateral_to_liquidate = auction.amount;
+    let mut repay_amount = Uint256::zero();
+    let mut dust_bid = Uint256::zero();
+    let mut depleted_slots: Vec<u8> = vec![];
+    let mut premium_collateral = Decimal256::zero();
+    let mut filled: bool = false;
+    for slot in 0..collateral_info.max_slot + 1 {
+        let mut bid_pool: BidPool = match read_bid_pool(
+            deps.storage,
+            &auction.collateral_token,
+            &config.stable_denom,
+            slot,
+        ) {
+            Ok(bid_pool) => bid_pool,
+            Err(_) => continue,
+        };
+        if bid_pool.total_bid_amount.is_zero() {
+            continue;
+        };
+
+        accrue_pool_rewards(
+            deps.storage,
+            &env,
+            &bid_pool,
+            &auction.collateral_token,
+            &config.stable_denom,
+            slot,
+        )?;
+        let (pool_repay_amount, pool_liquidated_collateral, pool_dust_bid) =
+            execute_pool_liquidation(
+                deps.storage,
+                &mut bid_pool,
+                &auction.collateral_token,
+                &config.stable_denom,
+                slot,
+                remaining_collateral_to_liquidate,
+                price.rate,
+                &mut filled,
+            )?;
+
+        store_bid_pool(
+            deps.storage,
+            &auction.collateral_token,
+            &config.stable_denom,
+            slot,
+            &bid_pool,
+        )?;
+
+        repay_amount += pool_repay_amount;
+        dust_bid += pool_dust_bid;
+        premium_collateral +=
+            Decimal256::from_uint256(pool_liquidated_collateral) * bid_pool.premium_rate;
+        if bid_pool.total_bid_amount.is_zero() {
+            depleted_slots.push(slot);
+        }
+
+        if filled {
+            remaining_collateral_to_liquidate = Uint256::zero();
+            break;
+        } else {
+            remaining_collateral_to_liquidate =
+                remaining_collateral_to_liquidate - pool_liquidated_collateral;
+        }
+    }
+
+    if !remaining_collateral_to_liquidate.is_zero() {
+        return Err(ContractError::InsufficientBids {});
+    }
+
+    store_total_bids(
+        deps.storage,
+        &auction.collateral_token,
+        &config.stable_denom,
+        available_bids - repay_amount - dust_bid,
+    )?;
+    remove_auction(deps.storage, &auction);
+
+    let bid_fee = repay_amount * config.bid_fee;
+    let liquidator_fee = repay_amount * config.liquidator_fee;
+    let repay_amount = repay_amount - bid_fee - liquidator_fee;
+
+    let log = LiquidationLog {
+        idx: pop_liquidation_log_idx(deps.storage)?,
+        height: env.block.height,
+        borrower: Some(auction.borrower.clone()),
+        collateral_token: auction.collateral_token.clone(),
+        collateral_amount: auction.amount,
+        premium_rate: premium_collateral / Decimal256::from_uint256(auction.amount),
+        repay_denom: config.stable_denom.clone(),
+        repay_amount,
+        liquidator: auction.liquidator.clone(),
+    };
+    store_liquidation_log(deps.storage, &log)?;
+
+    let bid_fee_split = split_bid_fee(deps.storage, deps.api, &config.stable_denom, bid_fee)?;
+    let messages = repay_messages(
+        deps.as_ref(),
+        &config,
+        &auction,
+        repay_amount,
+        liquidator_fee,
+        &bid_fee_split,
+    )?;
+    let top_up_messages: Vec<SubMsg<TerraMsgWrapper>> = top_up_messages(
+        deps.storage,
+        &env,
+        &auction.collateral_token,
+        &depleted_slots,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(top_up_messages)
+        .add_attributes(vec![
+            attr("action", "expire_auction"),
+            attr("auction_idx", auction_idx),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", auction.amount),
+            attr("repay_amount", repay_amount),
+            attr("bid_fee", bid_fee),
+            attr("liquidator_fee", liquidator_fee),
+        ])
+        .add_attributes(bid_fee_split.attributes()))
+}
+
+/// Repays the loan of the auctioned borrower and pays out the fees
+fn repay_messages(
+    deps: Deps,
+    config: &Config,
+    auction: &Auction,
+    repay_amount: Uint256,
+    liquidator_fee: Uint256,
+    bid_fee_split: &BidFeeSplit,
+) -> StdResult<Vec<CosmosMsg<TerraMsgWrapper>>> {
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.addr_humanize(&auction.repay_address)?.to_string(),
+        funds: vec![deduct_tax(
+            deps,
+            Coin {
+                denom: config.stable_denom.clone(),
+                amount: repay_amount.into(),
+            },
+        )?],
+        msg: to_binary(&MarketExecuteMsg::RepayStableFor {
+            borrower: deps.api.addr_humanize(&auction.borrower)?.to_string(),
+        })?,
+    })];
+
+    let mut transfers: Vec<(String, Uint256)> = vec![
+        (
+            deps.api.addr_humanize(&auction.fee_address)?.to_string(),
+            bid_fee_split.yield_reserve,
+        ),
+        (
+            deps.api.addr_humanize(&auction.liquidator)?.to_string(),
+            liquidator_fee + bid_fee_split.caller,
+        ),
+    ];
+    if let Some(insurance_fund) = bid_fee_split.insurance_fund.clone() {
+        transfers.push(insurance_fund);
+    }
+    for (recipient, transfer_amount) in transfers.into_iter() {
+        if !transfer_amount.is_zero() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient,
+                amount: vec![deduct_tax(
+                    deps,
+                    Coin {
+                        denom: config.stable_denom.clone(),
+                        amount: transfer_amount.into(),
+                    },
+                )?],
+            }));
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Time after which the auction can be expired
+pub(crate) fn auction_expiry(auction: &Auction) -> u64 {
+    auction.start_time + auction.decay_period + auction.expiry_period
+}
+
+/// The discount decays linearly from the initial discount down to
+/// the oracle price once the decay period is over
+pub(crate) fn auction_discount(auction: &Auction, block_time: u64) -> Decimal256 {
+    let elapsed = block_time.saturating_sub(auction.start_time);
+    if elapsed >= auction.decay_period {
+        return Decimal256::zero();
+    }
+
+    auction.initial_discount
+        * Decimal256::from_ratio(
+            Uint256::from(auction.decay_period - elapsed),
+            Uint256::from(auction.decay_period),
+        )
+}
+
+pub fn query_auction_config(
+    deps: Deps,
+    collateral_token: String,
+) -> StdResult<AuctionConfigResponse> {
+    let auction_config = read_auction_config(
+        deps.storage,
+        &deps.api.addr_canonicalize(&collateral_token)?,
+    )?;
+
+    Ok(AuctionConfigResponse {
+        collateral_token,
+        auction_config: auction_config.map(|auction_config| AuctionConfigMsg {
+            initial_discount: auction_config.initial_discount,
+            decay_period: auction_config.decay_period,
+            expiry_period: auction_config.expiry_period,
+        }),
+    })
+}
+
+pub fn query_active_auctions(
+    deps: Deps,
+    env: Env,
+    collateral_token: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<AuctionsResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    let auctions: Vec<AuctionResponse> =
+        read_auctions_by_collateral(deps.storage, &collateral_token_raw, start_after, limit)?
+            .iter()
+            .map(|auction| auction_response(deps, &env, auction))
+            .collect::<StdResult<Vec<AuctionResponse>>>()?;
+
+    Ok(AuctionsResponse { auctions })
+}
+
+pub fn query_auctions_by_borrower(
+    deps: Deps,
+    env: Env,
+    borrower: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<AuctionsResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(&borrower)?;
+
+    let auctions: Vec<AuctionResponse> =
+        read_auctions_by_borrower(deps.storage, &borrower_raw, start_after, limit)?
+            .iter()
+            .map(|auction| auction_response(deps, &env, auction))
+            .collect::<StdResult<Vec<AuctionResponse>>>()?;
+
+    Ok(AuctionsResponse { auctions })
+}
+
+fn auction_response(deps: Deps, env: &Env, auction: &Auction) -> StdResult<AuctionResponse> {
+    Ok(AuctionResponse {
+        idx: auction.idx,
+        collateral_token: deps
+            .api
+            .addr_humanize(&auction.collateral_token)?
+            .to_string(),
+        borrower: deps.api.addr_humanize(&auction.borrower)?.to_string(),
+        amount: auction.amount,
+        start_time: auction.start_time,
+        discount: auction_discount(auction, env.block.time.seconds()),
+        expires_at: auction_expiry(auction),
+    })
+}
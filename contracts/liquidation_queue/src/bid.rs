@@ -1,19 +1,32 @@
 use crate::asserts::{assert_activate_status, assert_withdraw_amount};
-use crate::querier::query_collateral_whitelist_info;
+use crate::auction::start_auction;
+use crate::bidder_limits::assert_bidder_limits;
+use crate::error::ContractError;
+use crate::fee::split_bid_fee;
+use crate::incentives::{accrue_pool_rewards, settle_bid_rewards, snapshot_bid_rewards};
+use crate::querier::{query_collateral_whitelist_info, query_freeze_state};
 use crate::state::{
-    pop_bid_idx, read_bid, read_bid_pool, read_bids_by_user, read_collateral_info, read_config,
-    read_epoch_scale_sum, read_or_create_bid_pool, read_total_bids, remove_bid, store_bid,
-    store_bid_pool, store_epoch_scale_sum, store_total_bids, Bid, BidPool, CollateralInfo, Config,
+    pop_bid_idx, pop_liquidation_log_idx, read_auction_config, read_bid, read_bid_denoms,
+    read_bid_pool, read_bids_by_user, read_collateral_info, read_config,
+    read_config_for_collateral, read_epoch_sum, read_or_create_bid_pool, read_sniping_protection,
+    read_total_bids, remove_bid, store_bid, store_bid_pool, store_epoch_sum, store_liquidation_log,
+    store_total_bids, Bid, BidPool, CollateralInfo, Config, LiquidationLog,
 };
+use crate::top_up::top_up_messages;
 use bigint::U256;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, CanonicalAddr, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, Uint128, WasmMsg,
+    attr, to_binary, Attribute, BankMsg, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use moneymarket::math::{checked_mul, complement, div_dec, mul_dec, Rounding};
 use moneymarket::oracle::PriceResponse;
 use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+use terra_cosmwasm::{create_swap_send_msg, TerraMsgWrapper};
+
+/// Min stable value of a pool share (1e-9) before the pool closes its epoch
+const MIN_SHARE_VALUE: u64 = 1_000_000_000;
 
 /// Stable asset is submitted to create a bid record. If available bids for the collateral is under
 /// the threshold, the bid is activated. Bids are not used for liquidations until activated
@@ -23,38 +36,45 @@ pub fn submit_bid(
     info: MessageInfo,
     collateral_token: String,
     premium_slot: u8,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
     let bidder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
 
-    let amount: Uint256 = info
+    // bids are accepted in the stable denom and the other bid denoms
+    let mut accepted_denoms: Vec<String> = vec![config.stable_denom.clone()];
+    accepted_denoms.extend(read_bid_denoms(deps.storage)?);
+    let mut funds: Vec<(String, Uint256)> = info
         .funds
         .iter()
         .map(|item| {
-            if item.denom != config.stable_denom {
-                Err(StdError::generic_err(format!(
-                    "Invalid asset provided, only {} allowed",
-                    config.stable_denom
-                )))
+            if !accepted_denoms.contains(&item.denom) {
+                Err(ContractError::InvalidAsset(accepted_denoms.join(" or ")))
             } else {
-                Ok(item.amount)
+                Ok((item.denom.clone(), item.amount.into()))
             }
         })
-        .last()
-        .ok_or_else(|| {
-            StdError::generic_err(format!(
-                "No {} assets have been provided",
-                config.stable_denom
-            ))
-        })??
-        .into();
+        .collect::<Result<Vec<(String, Uint256)>, ContractError>>()?;
+    if funds.len() > 1 {
+        return Err(ContractError::MultipleBidDenoms {});
+    }
+    let (bid_denom, amount): (String, Uint256) = funds
+        .pop()
+        .ok_or_else(|| ContractError::ZeroAssets(accepted_denoms.join(" or ")))?;
+
+    assert_bidder_limits(
+        deps.as_ref(),
+        &collateral_token_raw,
+        &bidder_raw,
+        &bid_denom,
+        amount,
+    )?;
 
     // read or create bid_pool, make sure slot is valid
     let mut bid_pool: BidPool =
-        read_or_create_bid_pool(deps.storage, &collateral_info, premium_slot)?;
+        read_or_create_bid_pool(deps.storage, &collateral_info, &bid_denom, premium_slot)?;
 
     // create bid object
     let bid_idx: Uint128 = pop_bid_idx(deps.storage)?;
@@ -62,28 +82,51 @@ pub fn submit_bid(
         idx: bid_idx,
         bidder: bidder_raw,
         collateral_token: collateral_token_raw.clone(),
-        product_snapshot: Decimal256::one(),
+        bid_denom: bid_denom.clone(),
         amount,
+        share: Decimal256::zero(),
         premium_slot,
         sum_snapshot: Decimal256::zero(),
         pending_liquidated_collateral: Uint256::zero(),
         wait_end: None,
         epoch_snapshot: Uint128::zero(),
-        scale_snapshot: Uint128::zero(),
+        updated_at: env.block.time.seconds(),
     };
 
-    // if available bids is lower than bid_threshold, directly activate bid
+    // if available bids is lower than bid_threshold, directly activate bid,
+    // unless the collateral price has just been updated in this block
     let available_bids: Uint256 =
-        read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
-    if available_bids < collateral_info.bid_threshold {
+        read_total_bids(deps.storage, &collateral_token_raw, &bid_denom).unwrap_or_default();
+    if available_bids < collateral_info.bid_threshold
+        && !price_updated_in_block(deps.as_ref(), &env, &config, collateral_token.clone())?
+    {
         // update bid and bid pool, add new share and pool indexes to bid
+        accrue_pool_rewards(
+            deps.storage,
+            &env,
+            &bid_pool,
+            &collateral_token_raw,
+            &bid_denom,
+            premium_slot,
+        )?;
         process_bid_activation(&mut bid, &mut bid_pool, amount);
 
         // store bid_pool
-        store_bid_pool(deps.storage, &collateral_token_raw, premium_slot, &bid_pool)?;
+        store_bid_pool(
+            deps.storage,
+            &collateral_token_raw,
+            &bid_denom,
+            premium_slot,
+            &bid_pool,
+        )?;
 
         // increase total bid amount
-        store_total_bids(deps.storage, &collateral_token_raw, available_bids + amount)?;
+        store_total_bids(
+            deps.storage,
+            &collateral_token_raw,
+            &bid_denom,
+            available_bids + amount,
+        )?;
     } else {
         // calculate wait_end from current time
         bid.wait_end = Some(env.block.time.plus_seconds(config.waiting_period).seconds());
@@ -91,10 +134,12 @@ pub fn submit_bid(
 
     // save to storage
     store_bid(deps.storage, bid_idx, &bid)?;
+    snapshot_bid_rewards(deps.storage, &bid)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "submit_bid"),
         attr("bid_idx", bid_idx),
+        attr("bid_denom", bid_denom),
         attr("amount", amount),
     ]))
 }
@@ -107,13 +152,14 @@ pub fn activate_bids(
     info: MessageInfo,
     collateral_token: String,
     bids_idx: Option<Vec<Uint128>>,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
     let sender_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
     let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
-    let mut available_bids: Uint256 =
-        read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
+    let price_updated: bool =
+        price_updated_in_block(deps.as_ref(), &env, &config, collateral_token.clone())?;
 
     let bids: Vec<Bid> = if let Some(bids_idx) = &bids_idx {
         bids_idx
@@ -130,20 +176,31 @@ pub fn activate_bids(
     let mut total_activated_amount = Uint256::zero();
     for mut bid in bids.into_iter() {
         if bid.bidder != sender_raw {
-            return Err(StdError::generic_err("unauthorized"));
+            return Err(ContractError::Unauthorized {});
         }
         if bid.collateral_token != collateral_token_raw {
-            return Err(StdError::generic_err("Bid collateral token doesn't match"));
+            return Err(ContractError::BidCollateralMismatch {});
         }
-        let mut bid_pool: BidPool =
-            read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
+        let mut bid_pool: BidPool = read_bid_pool(
+            deps.storage,
+            &bid.collateral_token,
+            &bid.bid_denom,
+            bid.premium_slot,
+        )?;
+        let available_bids: Uint256 =
+            read_total_bids(deps.storage, &collateral_token_raw, &bid.bid_denom)
+                .unwrap_or_default();
 
         let amount_to_activate = bid.amount;
 
-        // assert that the bid is inactive and wait period has expired
-        if let Err(err) =
+        // assert that the bid is inactive and wait period has expired,
+        // and that no price update happened in this block
+        let activate_status = if price_updated {
+            Err(ContractError::PriceUpdatedInBlock {})
+        } else {
             assert_activate_status(&bid, &env, available_bids, collateral_info.bid_threshold)
-        {
+        };
+        if let Err(err) = activate_status {
             if bids_idx.is_some() {
                 // if the user provided the idx to activate, we should return error to notify the user
                 return Err(err);
@@ -154,124 +211,93 @@ pub fn activate_bids(
         }
 
         // update bid and bid pool, add new share and pool indexes to bid
+        settle_bid_rewards(deps.storage, &env, &bid, &bid_pool)?;
         process_bid_activation(&mut bid, &mut bid_pool, amount_to_activate);
+        bid.updated_at = env.block.time.seconds();
 
         // save to storage
         store_bid(deps.storage, bid.idx, &bid)?;
+        snapshot_bid_rewards(deps.storage, &bid)?;
         store_bid_pool(
             deps.storage,
             &bid.collateral_token,
+            &bid.bid_denom,
             bid.premium_slot,
             &bid_pool,
         )?;
+        store_total_bids(
+            deps.storage,
+            &collateral_token_raw,
+            &bid.bid_denom,
+            available_bids + amount_to_activate,
+        )?;
 
         total_activated_amount += amount_to_activate;
-        available_bids += amount_to_activate;
     }
 
-    store_total_bids(deps.storage, &collateral_token_raw, available_bids)?;
-
     Ok(Response::new().add_attributes(vec![
         attr("action", "activate_bids"),
         attr("amount", total_activated_amount),
     ]))
 }
 
+/// With sniping protection enabled, bids can not enter the pools in the same block
+/// the collateral price was updated, so a bidder can not front-run a liquidation
+/// triggered by the new price. Prices that can not be queried never block activations
+fn price_updated_in_block(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    collateral_token: String,
+) -> StdResult<bool> {
+    if !read_sniping_protection(deps.storage)? {
+        return Ok(false);
+    }
+
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    Ok(
+        match query_price(
+            deps,
+            oracle_contract,
+            collateral_token,
+            config.stable_denom.clone(),
+            None,
+        ) {
+            Ok(price) => {
+                std::cmp::max(price.last_updated_base, price.last_updated_quote)
+                    >= env.block.time.seconds()
+            }
+            Err(_) => false,
+        },
+    )
+}
+
 /// Bid owners can withdraw the ramaning bid amount at any time
 pub fn retract_bid(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     bid_idx: Uint128,
     amount: Option<Uint256>,
-) -> StdResult<Response> {
-    let config: Config = read_config(deps.storage)?;
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let sender_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
-    let mut bid: Bid = read_bid(deps.storage, bid_idx)?;
-    let collateral_token_raw: CanonicalAddr = bid.collateral_token.clone();
+    let bid: Bid = read_bid(deps.storage, bid_idx)?;
+    let bid_denom: String = bid.bid_denom.clone();
 
     if bid.bidder != sender_raw {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
-    // check if bid is active or waiting
-    let withdraw_amount: Uint256 = if bid.wait_end.is_some() {
-        // waiting bid amount can be withdrawn without restriction
-        let waiting_withdraw_amount = assert_withdraw_amount(amount, bid.amount)?;
-        if waiting_withdraw_amount == bid.amount {
-            remove_bid(deps.storage, bid.idx)?;
-        } else {
-            bid.amount = bid.amount - waiting_withdraw_amount;
-            store_bid(deps.storage, bid.idx, &bid)?;
-        }
-
-        waiting_withdraw_amount
-    } else {
-        let available_bids: Uint256 = read_total_bids(deps.storage, &collateral_token_raw)?;
-        let mut bid_pool: BidPool =
-            read_bid_pool(deps.storage, &collateral_token_raw, bid.premium_slot)?;
-
-        // calculate spent and reward until this moment
-        let (withdrawable_amount, residue_bid) = calculate_remaining_bid(&bid, &bid_pool)?;
-        let (liquidated_collateral, residue_collateral) =
-            calculate_liquidated_collateral(deps.storage, &bid)?;
-
-        // accumulate pending reward to be claimed later
-        bid.pending_liquidated_collateral += liquidated_collateral;
-
-        // stack residues, will give it to next claimer if it becomes bigger than 1.0
-        bid_pool.residue_collateral += residue_collateral;
-        bid_pool.residue_bid += residue_bid;
-
-        // check requested amount
-        let withdraw_amount: Uint256 = assert_withdraw_amount(amount, withdrawable_amount)?;
-
-        // remove or update bid
-        if withdraw_amount == withdrawable_amount && bid.pending_liquidated_collateral.is_zero() {
-            remove_bid(deps.storage, bid_idx)?;
-        } else {
-            store_bid(
-                deps.storage,
-                bid.idx,
-                &Bid {
-                    amount: withdrawable_amount - withdraw_amount,
-                    product_snapshot: bid_pool.product_snapshot,
-                    sum_snapshot: bid_pool.sum_snapshot,
-                    scale_snapshot: bid_pool.current_scale,
-                    ..bid
-                },
-            )?;
-        }
-
-        // update available bid amount
-        bid_pool.total_bid_amount = bid_pool.total_bid_amount - withdraw_amount;
+    let withdraw_amount: Uint256 = withdraw_from_bid(deps.storage, &env, bid, amount)?;
 
-        // claim residue bids if it is bigger than 1.0
-        let refund_amount = withdraw_amount + claim_bid_residue(&mut bid_pool);
-
-        store_bid_pool(
-            deps.storage,
-            &collateral_token_raw,
-            bid.premium_slot,
-            &bid_pool,
-        )?;
-        store_total_bids(
-            deps.storage,
-            &collateral_token_raw,
-            available_bids - withdraw_amount,
-        )?;
-
-        refund_amount
-    };
-
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
     if !withdraw_amount.is_zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
-                    denom: config.stable_denom,
+                    denom: bid_denom,
                     amount: withdraw_amount.into(),
                 },
             )?],
@@ -287,7 +313,9 @@ pub fn retract_bid(
 
 /// Overseer executes the liquidation providing a whitelisted collateral.
 /// This operation returns a repay_amount based on the available bids on each
-/// premium slot, consuming bids from lowest to higher premium slots
+/// premium slot, consuming bids from lowest to higher premium slots.
+/// The bids in the repay denom are consumed first, bids in other denoms
+/// are swapped to it. Collaterals in auction mode start an auction instead
 #[allow(clippy::too_many_arguments)]
 pub fn execute_liquidation(
     deps: DepsMut,
@@ -296,148 +324,402 @@ pub fn execute_liquidation(
     liquidator: String,
     repay_address: String,
     fee_address: String,
+    borrower: Option<String>,
+    repay_denom: Option<String>,
     collateral_token: String,
     amount: Uint256,
-) -> StdResult<Response> {
-    let config: Config = read_config(deps.storage)?;
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
-    let available_bids: Uint256 = read_total_bids(deps.storage, &collateral_token_raw)?;
 
-    // only collateral token custody can execute liquidations
-    let overseer = deps.api.addr_humanize(&config.overseer)?;
-    let custody_contract = query_collateral_whitelist_info(
-        &deps.querier,
-        overseer.to_string(),
-        collateral_token.to_string(),
-    )?
-    .custody_contract;
-    if sender != custody_contract {
-        return Err(StdError::generic_err(
-            "Unauthorized: only custody contract can execute liquidations",
-        ));
+    assert_custody_contract(deps.as_ref(), &config, &sender, &collateral_token)?;
+    assert_not_frozen(deps.as_ref(), &config)?;
+
+    if let Some(auction_config) = read_auction_config(deps.storage, &collateral_token_raw)? {
+        if let Some(repay_denom) = repay_denom {
+            if repay_denom != config.stable_denom {
+                return Err(ContractError::InvalidAuctionRepayDenom(config.stable_denom));
+            }
+        }
+        return start_auction(
+            deps,
+            env,
+            auction_config,
+            liquidator,
+            repay_address,
+            fee_address,
+            borrower,
+            collateral_token,
+            amount,
+        );
     }
 
-    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
-    let price: PriceResponse = query_price(
-        deps.as_ref(),
-        oracle_contract,
-        collateral_token.to_string(),
-        config.stable_denom.clone(),
-        Some(TimeConstraints {
-            block_time: env.block.time.seconds(),
-            valid_timeframe: config.price_timeframe,
-        }),
-    )?;
+    let repay_denom = repay_denom.unwrap_or_else(|| config.stable_denom.clone());
+    let mut bid_denoms: Vec<String> = vec![config.stable_denom.clone()];
+    bid_denoms.extend(read_bid_denoms(deps.storage)?);
+    if !bid_denoms.contains(&repay_denom) {
+        return Err(ContractError::InvalidRepayDenom(bid_denoms.join(" or ")));
+    }
 
+    // the pools in the repay denom are consumed first
+    bid_denoms.retain(|bid_denom| *bid_denom != repay_denom);
+    bid_denoms.insert(0, repay_denom.clone());
+
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
     let mut remaining_collateral_to_liquidate = amount;
-    let mut repay_amount = Uint256::zero();
-    let mut filled: bool = false;
-    for slot in 0..collateral_info.max_slot + 1 {
-        let mut bid_pool: BidPool = match read_bid_pool(deps.storage, &collateral_token_raw, slot) {
-            Ok(bid_pool) => bid_pool,
-            Err(_) => continue,
-        };
-        if bid_pool.total_bid_amount.is_zero() {
+    let mut denom_repay_amounts: Vec<(String, Uint256)> = vec![];
+    let mut depleted_slots: Vec<u8> = vec![];
+    let mut premium_collateral = Decimal256::zero();
+    for bid_denom in bid_denoms.into_iter() {
+        let available_bids: Uint256 =
+            read_total_bids(deps.storage, &collateral_token_raw, &bid_denom).unwrap_or_default();
+        if available_bids.is_zero() {
             continue;
-        };
+        }
 
-        let (pool_repay_amount, pool_liquidated_collateral) = execute_pool_liquidation(
-            deps.storage,
-            &mut bid_pool,
-            &collateral_token_raw,
-            slot,
-            remaining_collateral_to_liquidate,
-            price.rate,
-            &mut filled,
+        let price: PriceResponse = query_price(
+            deps.as_ref(),
+            oracle_contract.clone(),
+            collateral_token.to_string(),
+            bid_denom.clone(),
+            Some(TimeConstraints {
+                block_time: env.block.time.seconds(),
+                valid_timeframe: config.price_timeframe,
+            }),
         )?;
 
-        store_bid_pool(deps.storage, &collateral_token_raw, slot, &bid_pool)?;
+        let mut repay_amount = Uint256::zero();
+        let mut dust_bid = Uint256::zero();
+        let mut filled: bool = false;
+        for slot in 0..collateral_info.max_slot + 1 {
+            let mut bid_pool: BidPool =
+                match read_bid_pool(deps.storage, &collateral_token_raw, &bid_denom, slot) {
+                    Ok(bid_pool) => bid_pool,
+                    Err(_) => continue,
+                };
+            if bid_pool.total_bid_amount.is_zero() {
+                continue;
+            };
 
-        repay_amount += pool_repay_amount;
+            // the emissions are accrued before the pool can close its epoch
+            accrue_pool_rewards(
+                deps.storage,
+                &env,
+                &bid_pool,
+                &collateral_token_raw,
+                &bid_denom,
+                slot,
+            )?;
+            let (pool_repay_amount, pool_liquidated_collateral, pool_dust_bid) =
+                execute_pool_liquidation(
+                    deps.storage,
+                    &mut bid_pool,
+                    &collateral_token_raw,
+                    &bid_denom,
+                    slot,
+                    remaining_collateral_to_liquidate,
+                    price.rate,
+                    &mut filled,
+                )?;
+
+            store_bid_pool(
+                deps.storage,
+                &collateral_token_raw,
+                &bid_denom,
+                slot,
+                &bid_pool,
+            )?;
+
+            repay_amount += pool_repay_amount;
+            dust_bid += pool_dust_bid;
+            premium_collateral +=
+                Decimal256::from_uint256(pool_liquidated_collateral) * bid_pool.premium_rate;
+            // only the stable denom bids can be topped up
+            if bid_pool.total_bid_amount.is_zero() && bid_denom == config.stable_denom {
+                depleted_slots.push(slot);
+            }
 
-        if filled {
-            remaining_collateral_to_liquidate = Uint256::zero();
+            if filled {
+                remaining_collateral_to_liquidate = Uint256::zero();
+                break;
+            } else {
+                remaining_collateral_to_liquidate =
+                    remaining_collateral_to_liquidate - pool_liquidated_collateral;
+            }
+        }
+
+        store_total_bids(
+            deps.storage,
+            &collateral_token_raw,
+            &bid_denom,
+            available_bids - repay_amount - dust_bid,
+        )?;
+        denom_repay_amounts.push((bid_denom, repay_amount));
+
+        if remaining_collateral_to_liquidate.is_zero() {
             break;
-        } else {
-            remaining_collateral_to_liquidate =
-                remaining_collateral_to_liquidate - pool_liquidated_collateral;
         }
     }
 
     if !remaining_collateral_to_liquidate.is_zero() {
-        return Err(StdError::generic_err(
-            "Not enough bids to execute this liquidation",
+        return Err(ContractError::InsufficientBids {});
+    }
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    let mut transfers: Vec<(String, Coin)> = vec![];
+    let mut repay_attributes: Vec<Attribute> = vec![
+        attr("repay_amount", Uint256::zero()),
+        attr("bid_fee", Uint256::zero()),
+        attr("liquidator_fee", Uint256::zero()),
+    ];
+    let mut fee_split_attributes: Vec<Attribute> = vec![];
+    let mut swap_attributes: Vec<Attribute> = vec![];
+    let mut log_repay_amount = Uint256::zero();
+    for (bid_denom, repay_amount) in denom_repay_amounts.into_iter() {
+        let bid_fee = repay_amount * config.bid_fee;
+        let liquidator_fee = repay_amount * config.liquidator_fee;
+        let repay_amount = repay_amount - bid_fee - liquidator_fee;
+
+        // bids in other denoms are swapped to the repay denom, fees are paid in the bid denom
+        if bid_denom == repay_denom {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: repay_address.clone(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: bid_denom.clone(),
+                        amount: repay_amount.into(),
+                    },
+                )?],
+            }));
+        } else if !repay_amount.is_zero() {
+            messages.push(create_swap_send_msg(
+                repay_address.clone(),
+                Coin {
+                    denom: bid_denom.clone(),
+                    amount: repay_amount.into(),
+                },
+                repay_denom.clone(),
+            ));
+        }
+
+        let bid_fee_split = split_bid_fee(deps.storage, deps.api, &bid_denom, bid_fee)?;
+        let coin = |amount: Uint256| Coin {
+            denom: bid_denom.clone(),
+            amount: amount.into(),
+        };
+        transfers.push((fee_address.clone(), coin(bid_fee_split.yield_reserve)));
+        transfers.push((
+            liquidator.clone(),
+            coin(liquidator_fee + bid_fee_split.caller),
         ));
+        if let Some((insurance_fund, insurance_fee)) = bid_fee_split.insurance_fund.clone() {
+            transfers.push((insurance_fund, coin(insurance_fee)));
+        }
+
+        if bid_denom == repay_denom {
+            log_repay_amount = repay_amount;
+            repay_attributes = vec![
+                attr("repay_amount", repay_amount),
+                attr("bid_fee", bid_fee),
+                attr("liquidator_fee", liquidator_fee),
+            ];
+            fee_split_attributes = bid_fee_split.attributes();
+        } else {
+            swap_attributes.extend(vec![
+                attr("swap_amount", coin(repay_amount).to_string()),
+                attr("swap_bid_fee", coin(bid_fee).to_string()),
+                attr("swap_liquidator_fee", coin(liquidator_fee).to_string()),
+            ]);
+        }
     }
 
-    store_total_bids(
-        deps.storage,
-        &collateral_token_raw,
-        available_bids - repay_amount,
-    )?;
+    for (recipient, transfer) in transfers.into_iter() {
+        if !transfer.amount.is_zero() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient,
+                amount: vec![deduct_tax(deps.as_ref(), transfer)?],
+            }));
+        }
+    }
 
-    let bid_fee = repay_amount * config.bid_fee;
-    let liquidator_fee = repay_amount * config.liquidator_fee;
-    let repay_amount = repay_amount - bid_fee - liquidator_fee;
+    let top_up_messages: Vec<SubMsg<TerraMsgWrapper>> =
+        top_up_messages(deps.storage, &env, &collateral_token_raw, &depleted_slots)?;
 
-    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Bank(BankMsg::Send {
-        to_address: repay_address,
-        amount: vec![deduct_tax(
-            deps.as_ref(),
-            Coin {
-                denom: config.stable_denom.clone(),
-                amount: repay_amount.into(),
-            },
-        )?],
-    })];
+    // swapped bids are not part of the logged repay amount, as it depends on the swap rate
+    let borrower_raw = match &borrower {
+        Some(borrower) => Some(deps.api.addr_canonicalize(borrower)?),
+        None => None,
+    };
+    let log = LiquidationLog {
+        idx: pop_liquidation_log_idx(deps.storage)?,
+        height: env.block.height,
+        borrower: borrower_raw,
+        collateral_token: collateral_token_raw,
+        collateral_amount: amount,
+        premium_rate: if amount.is_zero() {
+            Decimal256::zero()
+        } else {
+            premium_collateral / Decimal256::from_uint256(amount)
+        },
+        repay_denom: repay_denom.clone(),
+        repay_amount: log_repay_amount,
+        liquidator: deps.api.addr_canonicalize(&liquidator)?,
+    };
+    store_liquidation_log(deps.storage, &log)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(top_up_messages)
+        .add_attributes(vec![
+            attr("action", "execute_bid"),
+            attr("stable_denom", repay_denom),
+        ])
+        .add_attributes(repay_attributes)
+        .add_attributes(vec![
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", amount),
+        ])
+        .add_attributes(fee_split_attributes)
+        .add_attributes(swap_attributes))
+}
 
-    if !bid_fee.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: fee_address,
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom.clone(),
-                    amount: bid_fee.into(),
-                },
-            )?],
-        }));
+/// Only collateral token custody can execute liquidations
+pub(crate) fn assert_custody_contract(
+    deps: Deps,
+    config: &Config,
+    sender: &str,
+    collateral_token: &str,
+) -> Result<(), ContractError> {
+    let overseer = deps.api.addr_humanize(&config.overseer)?;
+    let custody_contract =
+        query_collateral_whitelist_info(&deps.querier, overseer, collateral_token.to_string())?
+            .custody_contract;
+    if sender != custody_contract {
+        return Err(ContractError::NotCustodyContract {});
     }
-    if !liquidator_fee.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: liquidator,
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom.clone(),
-                    amount: liquidator_fee.into(),
-                },
-            )?],
-        }));
+
+    Ok(())
+}
+
+/// Liquidations are halted while the overseer has the protocol frozen
+pub(crate) fn assert_not_frozen(deps: Deps, config: &Config) -> Result<(), ContractError> {
+    let overseer = deps.api.addr_humanize(&config.overseer)?;
+    if query_freeze_state(&deps.querier, overseer)?.frozen {
+        return Err(ContractError::ProtocolFrozen {});
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_bid"),
-        attr("stable_denom", config.stable_denom),
-        attr("repay_amount", repay_amount),
-        attr("bid_fee", bid_fee),
-        attr("liquidator_fee", liquidator_fee),
-        attr("collateral_token", collateral_token),
-        attr("collateral_amount", amount),
-    ]))
+    Ok(())
+}
+
+/// Withdraws the amount from the bid, the whole withdrawable amount by default,
+/// and returns the amount to refund. Active bids account the liquidated
+/// collateral first, which stays pending to be claimed
+pub(crate) fn withdraw_from_bid(
+    storage: &mut dyn Storage,
+    env: &Env,
+    mut bid: Bid,
+    amount: Option<Uint256>,
+) -> Result<Uint256, ContractError> {
+    let collateral_token_raw: CanonicalAddr = bid.collateral_token.clone();
+    let bid_denom: String = bid.bid_denom.clone();
+    bid.updated_at = env.block.time.seconds();
+
+    // check if bid is active or waiting
+    if bid.wait_end.is_some() {
+        // waiting bid amount can be withdrawn without restriction
+        let waiting_withdraw_amount = assert_withdraw_amount(amount, bid.amount)?;
+        if waiting_withdraw_amount == bid.amount {
+            remove_bid(storage, bid.idx)?;
+        } else {
+            bid.amount = bid.amount - waiting_withdraw_amount;
+            store_bid(storage, bid.idx, &bid)?;
+        }
+
+        return Ok(waiting_withdraw_amount);
+    }
+
+    let available_bids: Uint256 = read_total_bids(storage, &collateral_token_raw, &bid_denom)?;
+    let mut bid_pool: BidPool =
+        read_bid_pool(storage, &collateral_token_raw, &bid_denom, bid.premium_slot)?;
+
+    // calculate spent and reward until this moment
+    let withdrawable_amount = calculate_remaining_bid(&bid, &bid_pool);
+    let (liquidated_collateral, residue_collateral) =
+        calculate_liquidated_collateral(storage, &bid)?;
+
+    // accumulate pending reward to be claimed later
+    bid.pending_liquidated_collateral += liquidated_collateral;
+
+    // stack residues, will give it to next claimer if it becomes bigger than 1.0
+    bid_pool.residue_collateral += residue_collateral;
+
+    // check requested amount
+    let withdraw_amount: Uint256 = assert_withdraw_amount(amount, withdrawable_amount)?;
+
+    // burn the shares of the withdrawn amount, rounding up in favor of the pool
+    settle_bid_rewards(storage, env, &bid, &bid_pool)?;
+    checkpoint_bid(&mut bid, &bid_pool);
+    snapshot_bid_rewards(storage, &bid)?;
+    let share_to_burn = if withdraw_amount == withdrawable_amount {
+        bid.share
+    } else {
+        std::cmp::min(
+            bid.share,
+            calculate_share_to_burn(&bid_pool, withdraw_amount),
+        )
+    };
+    bid.share = bid.share - share_to_burn;
+    bid_pool.total_share = bid_pool.total_share - share_to_burn;
+
+    // remove or update bid
+    if withdraw_amount == withdrawable_amount && bid.pending_liquidated_collateral.is_zero() {
+        remove_bid(storage, bid.idx)?;
+    } else {
+        store_bid(
+            storage,
+            bid.idx,
+            &Bid {
+                amount: withdrawable_amount - withdraw_amount,
+                ..bid
+            },
+        )?;
+    }
+
+    // update available bid amount
+    bid_pool.total_bid_amount = bid_pool.total_bid_amount - withdraw_amount;
+
+    // claim residue bids if it is bigger than 1.0
+    let refund_amount = withdraw_amount + claim_bid_residue(&mut bid_pool);
+
+    store_bid_pool(
+        storage,
+        &collateral_token_raw,
+        &bid_denom,
+        bid.premium_slot,
+        &bid_pool,
+    )?;
+    store_total_bids(
+        storage,
+        &collateral_token_raw,
+        &bid_denom,
+        available_bids - withdraw_amount,
+    )?;
+
+    Ok(refund_amount)
 }
 
 /// Bid owner can claim their share of the liquidated collateral until the
 /// bid is consumed
 pub fn claim_liquidations(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     collateral_token: String,
     bids_idx: Option<Vec<Uint128>>,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
 
@@ -453,21 +735,25 @@ pub fn claim_liquidations(
     let mut claim_amount = Uint256::zero();
     for bid in bids.into_iter() {
         if bid.bidder != sender_raw {
-            return Err(StdError::generic_err("unauthorized"));
+            return Err(ContractError::Unauthorized {});
         }
         if bid.collateral_token != collateral_token_raw {
-            return Err(StdError::generic_err("Bid collateral token doesn't match"));
+            return Err(ContractError::BidCollateralMismatch {});
         }
         if bid.wait_end.is_some() {
             // bid not activated
             continue;
         }
 
-        let mut bid_pool: BidPool =
-            read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
+        let mut bid_pool: BidPool = read_bid_pool(
+            deps.storage,
+            &bid.collateral_token,
+            &bid.bid_denom,
+            bid.premium_slot,
+        )?;
 
         // calculate remaining bid amount
-        let (remaining_bid, residue_bid) = calculate_remaining_bid(&bid, &bid_pool)?;
+        let remaining_bid = calculate_remaining_bid(&bid, &bid_pool);
 
         // calculate liquidated collateral
         let (liquidated_collateral, residue_collateral) =
@@ -475,41 +761,40 @@ pub fn claim_liquidations(
 
         // keep residues
         bid_pool.residue_collateral += residue_collateral;
-        bid_pool.residue_bid += residue_bid;
 
         // get claimable amount
         claim_amount += bid.pending_liquidated_collateral
             + liquidated_collateral
             + claim_col_residue(&mut bid_pool);
 
-        // store bid_pool to update residue
-        store_bid_pool(
-            deps.storage,
-            &collateral_token_raw,
-            bid.premium_slot,
-            &bid_pool,
-        )?;
+        let mut bid = bid;
+        settle_bid_rewards(deps.storage, &env, &bid, &bid_pool)?;
+        checkpoint_bid(&mut bid, &bid_pool);
+        snapshot_bid_rewards(deps.storage, &bid)?;
 
         // check if bid has been consumed, include 1 for rounding
         if remaining_bid <= Uint256::one() {
+            // the shares left are worth less than one, they go back to the pool
+            bid_pool.total_share = bid_pool.total_share - bid.share;
             remove_bid(deps.storage, bid.idx)?;
         } else {
-            store_bid(
-                deps.storage,
-                bid.idx,
-                &Bid {
-                    amount: remaining_bid,
-                    product_snapshot: bid_pool.product_snapshot,
-                    sum_snapshot: bid_pool.sum_snapshot,
-                    scale_snapshot: bid_pool.current_scale,
-                    pending_liquidated_collateral: Uint256::zero(),
-                    ..bid
-                },
-            )?;
+            bid.amount = remaining_bid;
+            bid.pending_liquidated_collateral = Uint256::zero();
+            bid.updated_at = env.block.time.seconds();
+            store_bid(deps.storage, bid.idx, &bid)?;
         }
+
+        // store bid_pool to update residue and shares
+        store_bid_pool(
+            deps.storage,
+            &collateral_token_raw,
+            &bid.bid_denom,
+            bid.premium_slot,
+            &bid_pool,
+        )?;
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
     if !claim_amount.is_zero() {
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: collateral_token.clone(),
@@ -529,153 +814,192 @@ pub fn claim_liquidations(
 }
 
 fn process_bid_activation(bid: &mut Bid, bid_pool: &mut BidPool, amount: Uint256) {
-    bid.product_snapshot = bid_pool.product_snapshot;
+    // shares are minted at the current value of the pool share
+    let share = if bid_pool.total_share.is_zero() || bid_pool.total_bid_amount.is_zero() {
+        Decimal256::from_uint256(amount)
+    } else {
+        Decimal256::from_uint256(amount) * bid_pool.total_share
+            / Decimal256::from_uint256(bid_pool.total_bid_amount)
+    };
+
+    bid.share += share;
     bid.sum_snapshot = bid_pool.sum_snapshot;
     bid.wait_end = None;
-    bid.scale_snapshot = bid_pool.current_scale;
     bid.epoch_snapshot = bid_pool.current_epoch;
 
+    bid_pool.total_share += share;
     bid_pool.total_bid_amount += amount;
 }
 
-/// On each collateral execution the product_snapshot and sum_snapshot are updated
-/// to track the expense and reward distribution for biders in the pool
-/// More details:
-/// https://github.com/liquity/liquity/blob/master/papers/Scalable_Reward_Distribution_with_Compounding_Stakes.pdf
+/// Adds the stable to the bid. Active bids account the liquidated collateral
+/// first and mint shares at the current share value, joining the pool directly
+pub(crate) fn deposit_to_bid(
+    storage: &mut dyn Storage,
+    env: &Env,
+    mut bid: Bid,
+    amount: Uint256,
+) -> StdResult<()> {
+    if bid.wait_end.is_some() {
+        bid.amount += amount;
+        return store_bid(storage, bid.idx, &bid);
+    }
+
+    let mut bid_pool: BidPool = read_bid_pool(
+        storage,
+        &bid.collateral_token,
+        &bid.bid_denom,
+        bid.premium_slot,
+    )?;
+    let remaining_bid = calculate_remaining_bid(&bid, &bid_pool);
+    let (liquidated_collateral, residue_collateral) =
+        calculate_liquidated_collateral(storage, &bid)?;
+    bid.pending_liquidated_collateral += liquidated_collateral;
+    bid_pool.residue_collateral += residue_collateral;
+
+    settle_bid_rewards(storage, env, &bid, &bid_pool)?;
+    checkpoint_bid(&mut bid, &bid_pool);
+    process_bid_activation(&mut bid, &mut bid_pool, amount);
+    bid.amount = remaining_bid + amount;
+
+    store_bid(storage, bid.idx, &bid)?;
+    snapshot_bid_rewards(storage, &bid)?;
+    store_bid_pool(
+        storage,
+        &bid.collateral_token,
+        &bid.bid_denom,
+        bid.premium_slot,
+        &bid_pool,
+    )?;
+
+    let available_bids: Uint256 = read_total_bids(storage, &bid.collateral_token, &bid.bid_denom)?;
+    store_total_bids(
+        storage,
+        &bid.collateral_token,
+        &bid.bid_denom,
+        available_bids + amount,
+    )
+}
+
+/// Moves the bid snapshots to the current state of the pool, once the liquidated
+/// collateral has been accounted. Bids from a closed epoch have no shares left
+fn checkpoint_bid(bid: &mut Bid, bid_pool: &BidPool) {
+    if bid.epoch_snapshot != bid_pool.current_epoch {
+        bid.share = Decimal256::zero();
+        bid.epoch_snapshot = bid_pool.current_epoch;
+    }
+    bid.sum_snapshot = bid_pool.sum_snapshot;
+}
+
+/// On each collateral execution the stable spent is deducted from the pool, which lowers
+/// the value of every share, and the liquidated collateral per share is added to sum_snapshot.
+/// Collateral that can not be represented per share is kept as residue
 #[allow(clippy::too_many_arguments)]
-fn execute_pool_liquidation(
+pub(crate) fn execute_pool_liquidation(
     storage: &mut dyn Storage,
     bid_pool: &mut BidPool,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     premium_slot: u8,
     collateral_to_liquidate: Uint256,
     price: Decimal256,
     filled: &mut bool,
-) -> StdResult<(Uint256, Uint256)> {
-    let premium_price = price * (Decimal256::one() - bid_pool.premium_rate);
+) -> StdResult<(Uint256, Uint256, Uint256)> {
+    let premium_price = checked_mul(price, complement(bid_pool.premium_rate)?)?;
     let mut pool_collateral_to_liquidate = collateral_to_liquidate;
-    let mut pool_required_stable = pool_collateral_to_liquidate * premium_price;
+    let mut pool_required_stable =
+        mul_dec(pool_collateral_to_liquidate, premium_price, Rounding::Down)?;
 
     if pool_required_stable > bid_pool.total_bid_amount {
         pool_required_stable = bid_pool.total_bid_amount;
-        pool_collateral_to_liquidate = pool_required_stable / premium_price;
+        pool_collateral_to_liquidate =
+            div_dec(pool_required_stable, premium_price, Rounding::Down)?;
     } else {
         *filled = true;
     }
 
-    // E / D
-    let col_per_bid: Decimal256 = Decimal256::from_uint256(pool_collateral_to_liquidate)
-        / Decimal256::from_uint256(bid_pool.total_bid_amount);
-
-    // Q / D
-    let expense_per_bid: Decimal256 = Decimal256::from_uint256(pool_required_stable)
-        / Decimal256::from_uint256(bid_pool.total_bid_amount);
-
     ///////// Update sum /////////
-    // E / D * P
-    let sum = bid_pool.product_snapshot * col_per_bid;
-
-    // S + E / D * P
-    bid_pool.sum_snapshot += sum;
+    let liquidated_collateral = Decimal256::from_uint256(pool_collateral_to_liquidate);
+    if bid_pool.total_share.is_zero() {
+        // no share holders, the whole amount goes to the residue
+        bid_pool.residue_collateral += liquidated_collateral;
+    } else {
+        // E / shares
+        let col_per_share: Decimal256 = liquidated_collateral / bid_pool.total_share;
+        bid_pool.sum_snapshot += col_per_share;
+        bid_pool.residue_collateral += liquidated_collateral - col_per_share * bid_pool.total_share;
+    }
     bid_pool.total_bid_amount = bid_pool.total_bid_amount - pool_required_stable;
 
-    // save reward sum for current epoch and scale
-    store_epoch_scale_sum(
+    // save reward sum for current epoch
+    store_epoch_sum(
         storage,
         collateral_token,
+        bid_denom,
         premium_slot,
         bid_pool.current_epoch,
-        bid_pool.current_scale,
         bid_pool.sum_snapshot,
     )?;
 
-    ///////// Update product /////////
-    // Check if the pool is emptied, if it is, reset (P = 1, S = 0)
-    if expense_per_bid == Decimal256::one() {
-        bid_pool.sum_snapshot = Decimal256::zero();
-        bid_pool.product_snapshot = Decimal256::one();
-        bid_pool.current_scale = Uint128::zero();
+    // the pool is closed when emptied, or when a share is worth less than
+    // the min share value, so the share supply can not grow unbounded
+    let mut dust_bid = Uint256::zero();
+    if !bid_pool.total_bid_amount.is_zero()
+        && Decimal256::from_uint256(bid_pool.total_bid_amount)
+            < bid_pool.total_share * Decimal256(U256::from(MIN_SHARE_VALUE))
+    {
+        dust_bid = bid_pool.total_bid_amount;
+        bid_pool.residue_bid += Decimal256::from_uint256(dust_bid);
+        bid_pool.total_bid_amount = Uint256::zero();
+    }
 
+    if bid_pool.total_bid_amount.is_zero() {
+        bid_pool.sum_snapshot = Decimal256::zero();
+        bid_pool.total_share = Decimal256::zero();
         bid_pool.current_epoch += Uint128::from(1u128);
-    } else {
-        // 1 - Q / D
-        let product = Decimal256::one() - expense_per_bid;
-
-        // check if scale needs to be increased (in case product truncates to zero)
-        let new_product = bid_pool.product_snapshot * product;
-        bid_pool.product_snapshot = if new_product < Decimal256(U256::from(1_000_000_000u64)) {
-            bid_pool.current_scale += Uint128::from(1u128);
-
-            Decimal256(bid_pool.product_snapshot.0 * U256::from(1_000_000_000u64)) * product
-        } else {
-            new_product
-        };
     }
-    println!("product: {}", bid_pool.product_snapshot);
-    Ok((pool_required_stable, pool_collateral_to_liquidate))
-}
 
-pub(crate) fn calculate_remaining_bid(
-    bid: &Bid,
-    bid_pool: &BidPool,
-) -> StdResult<(Uint256, Decimal256)> {
-    let scale_diff: Uint128 = bid_pool.current_scale.checked_sub(bid.scale_snapshot)?;
-    let epoch_diff: Uint128 = bid_pool.current_epoch.checked_sub(bid.epoch_snapshot)?;
+    Ok((pool_required_stable, pool_collateral_to_liquidate, dust_bid))
+}
 
-    let remaining_bid_dec: Decimal256 = if !epoch_diff.is_zero() {
+/// Value of the bid shares in the current epoch
+pub(crate) fn calculate_remaining_bid(bid: &Bid, bid_pool: &BidPool) -> Uint256 {
+    if bid.epoch_snapshot != bid_pool.current_epoch || bid_pool.total_share.is_zero() {
         // pool was emptied, return 0
-        Decimal256::zero()
-    } else if scale_diff.is_zero() {
-        Decimal256::from_uint256(bid.amount) * bid_pool.product_snapshot / bid.product_snapshot
-    } else if scale_diff == Uint128::from(1u128) {
-        // product has been scaled
-        let scaled_remaining_bid =
-            Decimal256::from_uint256(bid.amount) * bid_pool.product_snapshot / bid.product_snapshot;
-
-        Decimal256(scaled_remaining_bid.0 / U256::from(1_000_000_000u64))
-    } else {
-        Decimal256::zero()
-    };
+        return Uint256::zero();
+    }
 
-    let remaining_bid = remaining_bid_dec * Uint256::one();
-    // stacks the residue when converting to integer
-    let bid_residue = remaining_bid_dec - Decimal256::from_uint256(remaining_bid);
+    // the fraction lost when converting to integer stays in the pool
+    Decimal256::from_uint256(bid_pool.total_bid_amount)
+        * (bid.share / bid_pool.total_share)
+        * Uint256::one()
+}
 
-    Ok((remaining_bid, bid_residue))
+/// Shares worth the given amount, rounded up
+fn calculate_share_to_burn(bid_pool: &BidPool, amount: Uint256) -> Decimal256 {
+    let numerator: U256 = bid_pool.total_share.0 * amount.0;
+    let denominator: U256 = bid_pool.total_bid_amount.0;
+    let share_to_burn = numerator / denominator;
+    if share_to_burn * denominator == numerator {
+        Decimal256(share_to_burn)
+    } else {
+        Decimal256(share_to_burn + U256::one())
+    }
 }
 
 pub(crate) fn calculate_liquidated_collateral(
     storage: &dyn Storage,
     bid: &Bid,
 ) -> StdResult<(Uint256, Decimal256)> {
-    let reference_sum_snapshot = read_epoch_scale_sum(
+    let reference_sum_snapshot = read_epoch_sum(
         storage,
         &bid.collateral_token,
+        &bid.bid_denom,
         bid.premium_slot,
         bid.epoch_snapshot,
-        bid.scale_snapshot,
     )
     .unwrap_or_default();
 
-    // reward = reward from first scale + reward from second scale (if any)
-    let first_portion = reference_sum_snapshot - bid.sum_snapshot;
-    let second_portion = if let Ok(second_scale_sum_snapshot) = read_epoch_scale_sum(
-        storage,
-        &bid.collateral_token,
-        bid.premium_slot,
-        bid.epoch_snapshot,
-        bid.scale_snapshot + Uint128::from(1u128),
-    ) {
-        Decimal256(
-            (second_scale_sum_snapshot.0 - reference_sum_snapshot.0) / U256::from(1_000_000_000u64),
-        )
-    } else {
-        Decimal256::zero()
-    };
-
-    let liquidated_collateral_dec = Decimal256::from_uint256(bid.amount)
-        * (first_portion + second_portion)
-        / bid.product_snapshot;
+    let liquidated_collateral_dec = bid.share * (reference_sum_snapshot - bid.sum_snapshot);
     let liquidated_collateral = liquidated_collateral_dec * Uint256::one();
     // stacks the residue when converting to integer
     let residue_collateral =
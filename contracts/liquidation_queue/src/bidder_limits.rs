@@ -0,0 +1,133 @@
+use crate::bid::calculate_remaining_bid;
+use crate::error::ContractError;
+use crate::state::{
+    read_bid_pool, read_bidder_limits, read_bids_by_user, read_collateral_info, read_config,
+    store_bidder_limits, Bid, BidderLimits, Config,
+};
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{attr, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdResult};
+use moneymarket::liquidation_queue::{BidderLimits as BidderLimitsMsg, BidderLimitsResponse};
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub fn update_bidder_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    bidder_limits: Option<BidderLimitsMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    let mut attributes = vec![
+        attr("action", "update_bidder_limits"),
+        attr("collateral_token", collateral_token),
+    ];
+    let bidder_limits = match bidder_limits {
+        Some(bidder_limits) => {
+            if bidder_limits.max_bids == 0 || bidder_limits.max_bid_amount.is_zero() {
+                return Err(ContractError::InvalidBidderLimits {});
+            }
+
+            attributes.push(attr("max_bids", bidder_limits.max_bids.to_string()));
+            attributes.push(attr("max_bid_amount", bidder_limits.max_bid_amount));
+            Some(BidderLimits {
+                max_bids: bidder_limits.max_bids,
+                max_bid_amount: bidder_limits.max_bid_amount,
+            })
+        }
+        None => None,
+    };
+    store_bidder_limits(deps.storage, &collateral_token_raw, bidder_limits)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Consumed bids do not count toward the limits, even with collateral
+/// left to claim. The remaining amount is summed over the bids in the
+/// denom of the new bid
+pub(crate) fn assert_bidder_limits(
+    deps: Deps,
+    collateral_token: &CanonicalAddr,
+    bidder: &CanonicalAddr,
+    bid_denom: &str,
+    amount: Uint256,
+) -> Result<(), ContractError> {
+    let bidder_limits = match read_bidder_limits(deps.storage, collateral_token)? {
+        Some(bidder_limits) => bidder_limits,
+        None => return Ok(()),
+    };
+
+    let mut bids_count: u32 = 1;
+    let mut bid_amount = amount;
+    let mut start_after = None;
+    loop {
+        let bids: Vec<Bid> =
+            read_bids_by_user(deps.storage, collateral_token, bidder, start_after, None)?;
+        let last_idx = match bids.last() {
+            Some(bid) => bid.idx,
+            None => break,
+        };
+
+        for bid in bids.into_iter() {
+            let remaining_amount = if bid.wait_end.is_some() {
+                bid.amount
+            } else {
+                match read_bid_pool(
+                    deps.storage,
+                    collateral_token,
+                    &bid.bid_denom,
+                    bid.premium_slot,
+                ) {
+                    Ok(bid_pool) => calculate_remaining_bid(&bid, &bid_pool),
+                    Err(_) => Uint256::zero(),
+                }
+            };
+            if remaining_amount.is_zero() {
+                continue;
+            }
+
+            bids_count += 1;
+            if bid.bid_denom == bid_denom {
+                bid_amount += remaining_amount;
+            }
+        }
+
+        start_after = Some(last_idx);
+    }
+
+    if bids_count > bidder_limits.max_bids {
+        return Err(ContractError::MaxBidsExceeded(bidder_limits.max_bids));
+    }
+    if bid_amount > bidder_limits.max_bid_amount {
+        return Err(ContractError::MaxBidAmountExceeded(
+            bidder_limits.max_bid_amount,
+            bid_denom.to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn query_bidder_limits(
+    deps: Deps,
+    collateral_token: String,
+) -> StdResult<BidderLimitsResponse> {
+    let bidder_limits = read_bidder_limits(
+        deps.storage,
+        &deps.api.addr_canonicalize(&collateral_token)?,
+    )?
+    .map(|bidder_limits| BidderLimitsMsg {
+        max_bids: bidder_limits.max_bids,
+        max_bid_amount: bidder_limits.max_bid_amount,
+    });
+
+    Ok(BidderLimitsResponse {
+        collateral_token,
+        bidder_limits,
+    })
+}
@@ -2,22 +2,49 @@
 use cosmwasm_std::entry_point;
 
 use crate::asserts::{assert_fees, assert_max_slot, assert_max_slot_premium};
+use crate::auction::{
+    auction_bid, expire_auction, query_active_auctions, query_auction_config,
+    query_auctions_by_borrower, update_auction_config,
+};
 use crate::bid::{activate_bids, claim_liquidations, execute_liquidation, retract_bid, submit_bid};
+use crate::bidder_limits::{query_bidder_limits, update_bidder_limits};
+use crate::error::ContractError;
+use crate::expiry::{expire_bids, query_bid_expiry, query_expired_bids, update_bid_expiry};
+use crate::fee::{
+    query_collateral_fees, query_fee_split, query_fee_stats, update_collateral_fees,
+    update_fee_split,
+};
+use crate::incentives::{
+    accrue_collateral_rewards, claim_bid_rewards, query_bid_incentives, query_bidder_rewards,
+    update_bid_incentives,
+};
 use crate::querier::query_collateral_whitelist_info;
 use crate::query::{
-    query_bid, query_bid_pool, query_bid_pools, query_bids_by_user, query_collateral_info,
-    query_config, query_liquidation_amount,
+    query_bid, query_bid_denoms, query_bid_pool, query_bid_pools, query_bids_by_user,
+    query_collateral_info, query_config, query_liquidation_amount, query_liquidation_logs,
+    query_liquidation_logs_by_borrower, query_simulate_bid_execution, query_sniping_protection,
 };
+use crate::self_liquidation::execute_self_liquidation;
 use crate::state::{
-    read_collateral_info, read_config, store_collateral_info, store_config, CollateralInfo, Config,
+    read_collateral_info, read_config, store_bid_denoms, store_collateral_info, store_config,
+    store_sniping_protection, CollateralInfo, Config,
+};
+use crate::swap::{
+    execute_liquidation_with_swap, query_swap_router, repay_from_swap, update_swap_router,
+};
+use crate::top_up::{
+    query_bid_top_up, query_top_up_config, refill_bid, top_up_bid, update_bid_top_up,
+    update_top_up_config, TOP_UP_REPLY_ID,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
 };
 use cw20::Cw20ReceiveMsg;
 use moneymarket::liquidation_queue::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use moneymarket::querier::{store_fee_model, FeeModel};
+use terra_cosmwasm::TerraMsgWrapper;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -25,7 +52,7 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     assert_fees(msg.liquidator_fee + msg.bid_fee)?;
 
     store_config(
@@ -48,7 +75,12 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig {
@@ -74,6 +106,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             waiting_period,
             overseer,
         ),
+        ExecuteMsg::UpdateFeeModel { fee_model } => update_fee_model(deps, info, fee_model),
         ExecuteMsg::WhitelistCollateral {
             collateral_token,
             bid_threshold,
@@ -91,7 +124,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             collateral_token,
             bid_threshold,
             max_slot,
-        } => update_collateral_info(deps, info, collateral_token, bid_threshold, max_slot),
+        } => update_collateral_info(deps, env, info, collateral_token, bid_threshold, max_slot),
         ExecuteMsg::SubmitBid {
             collateral_token,
             premium_slot,
@@ -105,6 +138,73 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             collateral_token,
             bids_idx,
         } => claim_liquidations(deps, env, info, collateral_token, bids_idx),
+        ExecuteMsg::UpdateSnipingProtection { enabled } => {
+            update_sniping_protection(deps, info, enabled)
+        }
+        ExecuteMsg::UpdateAuctionConfig {
+            collateral_token,
+            auction_config,
+        } => update_auction_config(deps, info, collateral_token, auction_config),
+        ExecuteMsg::AuctionBid { auction_idx } => auction_bid(deps, env, info, auction_idx),
+        ExecuteMsg::ExpireAuction { auction_idx } => expire_auction(deps, env, auction_idx),
+        ExecuteMsg::UpdateCollateralFees {
+            collateral_token,
+            collateral_fees,
+        } => update_collateral_fees(deps, info, collateral_token, collateral_fees),
+        ExecuteMsg::UpdateFeeSplit { fee_split } => update_fee_split(deps, info, fee_split),
+        ExecuteMsg::UpdateTopUpConfig { top_up_config } => {
+            update_top_up_config(deps, info, top_up_config)
+        }
+        ExecuteMsg::UpdateBidTopUp { bid_idx, top_up } => {
+            update_bid_top_up(deps, info, bid_idx, top_up)
+        }
+        ExecuteMsg::UpdateBidDenoms { bid_denoms } => update_bid_denoms(deps, info, bid_denoms),
+        ExecuteMsg::UpdateBidExpiry { bid_expiry } => update_bid_expiry(deps, info, bid_expiry),
+        ExecuteMsg::ExpireBids { bids_idx } => expire_bids(deps, env, info, bids_idx),
+        ExecuteMsg::UpdateSwapRouter { swap_router } => update_swap_router(deps, info, swap_router),
+        ExecuteMsg::UpdateBidIncentives {
+            collateral_token,
+            bid_incentives,
+        } => update_bid_incentives(deps, env, info, collateral_token, bid_incentives),
+        ExecuteMsg::ClaimBidRewards { collateral_token } => {
+            claim_bid_rewards(deps, env, info, collateral_token)
+        }
+        ExecuteMsg::UpdateBidderLimits {
+            collateral_token,
+            bidder_limits,
+        } => update_bidder_limits(deps, info, collateral_token, bidder_limits),
+        ExecuteMsg::TopUpBid { bid_idx } => top_up_bid(deps, env, info, bid_idx),
+        ExecuteMsg::RefillBid {
+            bid_idx,
+            prev_balance,
+        } => refill_bid(deps, env, info, bid_idx, prev_balance),
+        ExecuteMsg::RepayFromSwap {
+            liquidator,
+            repay_address,
+            fee_address,
+            repay_amount,
+            bid_fee,
+            prev_balance,
+        } => repay_from_swap(
+            deps,
+            env,
+            info,
+            liquidator,
+            repay_address,
+            fee_address,
+            repay_amount,
+            bid_fee,
+            prev_balance,
+        ),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // a failing bid top-up must not revert the liquidation
+        TOP_UP_REPLY_ID => Ok(Response::new().add_attribute("action", "top_up_failed")),
+        _ => Err(ContractError::InvalidReplyId {}),
     }
 }
 
@@ -113,13 +213,15 @@ pub fn receive_cw20(
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let contract_addr = info.sender;
     match from_binary(&cw20_msg.msg)? {
         Cw20HookMsg::ExecuteBid {
             liquidator,
             repay_address,
             fee_address,
+            borrower,
+            repay_denom,
         } => {
             let collateral_token = contract_addr.to_string();
             let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
@@ -132,6 +234,47 @@ pub fn receive_cw20(
                 liquidator,
                 repay_address,
                 fee_address,
+                borrower,
+                repay_denom,
+                collateral_token,
+                cw20_msg.amount.into(),
+            )
+        }
+        Cw20HookMsg::ExecuteBidWithSwap {
+            liquidator,
+            repay_address,
+            fee_address,
+            max_spread,
+        } => {
+            let collateral_token = contract_addr.to_string();
+            let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
+            let fee_address = fee_address.unwrap_or_else(|| cw20_msg.sender.clone());
+
+            execute_liquidation_with_swap(
+                deps,
+                env,
+                cw20_msg.sender,
+                liquidator,
+                repay_address,
+                fee_address,
+                max_spread,
+                collateral_token,
+                cw20_msg.amount.into(),
+            )
+        }
+        Cw20HookMsg::ExecuteSelfLiquidation {
+            borrower,
+            repay_address,
+        } => {
+            let collateral_token = contract_addr.to_string();
+            let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
+
+            execute_self_liquidation(
+                deps,
+                env,
+                cw20_msg.sender,
+                borrower,
+                repay_address,
                 collateral_token,
                 cw20_msg.amount.into(),
             )
@@ -152,10 +295,10 @@ pub fn update_config(
     price_timeframe: Option<u64>,
     waiting_period: Option<u64>,
     overseer: Option<String>,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     if let Some(owner) = owner {
@@ -200,6 +343,24 @@ pub fn update_config(
     Ok(Response::new())
 }
 
+pub fn update_fee_model(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_model: FeeModel,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_fee_model(deps.storage, &fee_model)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_fee_model"),
+        ("fee_model", &fee_model.to_string()),
+    ]))
+}
+
 pub fn whitelist_collateral(
     deps: DepsMut,
     info: MessageInfo,
@@ -207,24 +368,22 @@ pub fn whitelist_collateral(
     bid_threshold: Uint256,
     max_slot: u8,
     premium_rate_per_slot: Decimal256,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     // fail if the collateral is already whitelisted
     if read_collateral_info(deps.storage, &collateral_token_raw).is_ok() {
-        return Err(StdError::generic_err("Collateral is already whitelisted"));
+        return Err(ContractError::CollateralAlreadyWhitelisted {});
     }
 
     // check if the colalteral is whitelisted in overseer
     let overseer = deps.api.addr_humanize(&config.overseer)?;
-    query_collateral_whitelist_info(&deps.querier, overseer.to_string(), collateral_token)
-        .map_err(|_| {
-            StdError::generic_err("This collateral is not whitelisted in Anchor overseer")
-        })?;
+    query_collateral_whitelist_info(&deps.querier, overseer, collateral_token)
+        .map_err(|_| ContractError::CollateralNotWhitelistedInOverseer {})?;
 
     // assert max slot does not exceed cap and max premium rate does not exceed 1
     assert_max_slot(max_slot)?;
@@ -247,15 +406,16 @@ pub fn whitelist_collateral(
 
 pub fn update_collateral_info(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     collateral_token: String,
     bid_threshold: Option<Uint256>,
     max_slot: Option<u8>,
-) -> StdResult<Response> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     // update collateral info
@@ -270,6 +430,9 @@ pub fn update_collateral_info(
         // assert max slot does not exceed cap and max premium rate does not exceed 1
         assert_max_slot(max_slot)?;
         assert_max_slot_premium(max_slot, collateral_info.premium_rate_per_slot)?;
+
+        // the slot weights of the bid incentives change with the max slot
+        accrue_collateral_rewards(deps.storage, &env, &collateral_info)?;
         collateral_info.max_slot = max_slot;
     }
 
@@ -279,8 +442,54 @@ pub fn update_collateral_info(
     Ok(Response::new().add_attribute("action", "update_collateral_info"))
 }
 
+pub fn update_sniping_protection(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_sniping_protection(deps.storage, enabled)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_sniping_protection"),
+        ("enabled", &enabled.to_string()),
+    ]))
+}
+
+pub fn update_bid_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    bid_denoms: Vec<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut denoms: Vec<String> = vec![];
+    for bid_denom in bid_denoms.into_iter() {
+        if bid_denom == config.stable_denom {
+            return Err(ContractError::StableBidDenom {});
+        }
+        if !denoms.contains(&bid_denom) {
+            denoms.push(bid_denom);
+        }
+    }
+
+    store_bid_denoms(deps.storage, &denoms)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_bid_denoms"),
+        ("bid_denoms", &denoms.join(",")),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::LiquidationAmount {
@@ -294,6 +503,20 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             borrow_limit,
             collaterals,
             collateral_prices,
+            false,
+        )?),
+        QueryMsg::SwapLiquidationAmount {
+            borrow_amount,
+            borrow_limit,
+            collaterals,
+            collateral_prices,
+        } => to_binary(&query_liquidation_amount(
+            deps,
+            borrow_amount,
+            borrow_limit,
+            collaterals,
+            collateral_prices,
+            true,
         )?),
         QueryMsg::CollateralInfo { collateral_token } => {
             to_binary(&query_collateral_info(deps, collateral_token)?)
@@ -322,6 +545,91 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         } => to_binary(&query_bid_pools(
             deps,
             collateral_token,
+            None,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::BidPoolsByDenom {
+            collateral_token,
+            bid_denom,
+            start_after,
+            limit,
+        } => to_binary(&query_bid_pools(
+            deps,
+            collateral_token,
+            Some(bid_denom),
+            start_after,
+            limit,
+        )?),
+        QueryMsg::BidDenoms {} => to_binary(&query_bid_denoms(deps)?),
+        QueryMsg::SnipingProtection {} => to_binary(&query_sniping_protection(deps)?),
+        QueryMsg::AuctionConfig { collateral_token } => {
+            to_binary(&query_auction_config(deps, collateral_token)?)
+        }
+        QueryMsg::CollateralFees { collateral_token } => {
+            to_binary(&query_collateral_fees(deps, collateral_token)?)
+        }
+        QueryMsg::FeeSplit {} => to_binary(&query_fee_split(deps)?),
+        QueryMsg::FeeStats { bid_denom } => to_binary(&query_fee_stats(deps, bid_denom)?),
+        QueryMsg::TopUpConfig {} => to_binary(&query_top_up_config(deps)?),
+        QueryMsg::BidTopUp { bid_idx } => to_binary(&query_bid_top_up(deps, bid_idx)?),
+        QueryMsg::BidExpiry {} => to_binary(&query_bid_expiry(deps)?),
+        QueryMsg::SwapRouter {} => to_binary(&query_swap_router(deps)?),
+        QueryMsg::LiquidationLogs { start_after, limit } => {
+            to_binary(&query_liquidation_logs(deps, start_after, limit)?)
+        }
+        QueryMsg::LiquidationLogsByBorrower {
+            borrower,
+            start_after,
+            limit,
+        } => to_binary(&query_liquidation_logs_by_borrower(
+            deps,
+            borrower,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ExpiredBids { start_after, limit } => {
+            to_binary(&query_expired_bids(deps, env, start_after, limit)?)
+        }
+        QueryMsg::SimulateBidExecution {
+            collateral_token,
+            amount,
+            price,
+        } => to_binary(&query_simulate_bid_execution(
+            deps,
+            collateral_token,
+            amount,
+            price,
+        )?),
+        QueryMsg::BidIncentives { collateral_token } => {
+            to_binary(&query_bid_incentives(deps, collateral_token)?)
+        }
+        QueryMsg::BidderRewards {
+            collateral_token,
+            bidder,
+        } => to_binary(&query_bidder_rewards(deps, env, collateral_token, bidder)?),
+        QueryMsg::BidderLimits { collateral_token } => {
+            to_binary(&query_bidder_limits(deps, collateral_token)?)
+        }
+        QueryMsg::ActiveAuctions {
+            collateral_token,
+            start_after,
+            limit,
+        } => to_binary(&query_active_auctions(
+            deps,
+            env,
+            collateral_token,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AuctionsByBorrower {
+            borrower,
+            start_after,
+            limit,
+        } => to_binary(&query_auctions_by_borrower(
+            deps,
+            env,
+            borrower,
             start_after,
             limit,
         )?),
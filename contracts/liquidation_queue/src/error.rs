@@ -0,0 +1,175 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("unauthorized")]
+    Unauthorized {},
+
+    #[error("Unauthorized: only custody contract can execute liquidations")]
+    NotCustodyContract {},
+
+    #[error("Protocol is frozen")]
+    ProtocolFrozen {},
+
+    #[error("Invalid reply id")]
+    InvalidReplyId {},
+
+    #[error("Wait period expires at {0}")]
+    WaitPeriodNotExpired(u64),
+
+    #[error("Bid is already active")]
+    BidAlreadyActive {},
+
+    #[error("Requested amount is bigger than current withdrawable amount ({0})")]
+    InvalidWithdrawAmount(Uint256),
+
+    #[error("Max slot exceeds limit")]
+    MaxSlotExceedsLimit {},
+
+    #[error("Max slot premium rate exceeds limit")]
+    MaxSlotPremiumExceedsLimit {},
+
+    #[error("The sum of bid_fee and liquidator_fee can not be greater than one")]
+    InvalidFees {},
+
+    #[error("The sum of insurance_share and caller_share can not be greater than one")]
+    InvalidFeeSplit {},
+
+    #[error("Collateral is already whitelisted")]
+    CollateralAlreadyWhitelisted {},
+
+    #[error("Collateral is not whitelisted")]
+    CollateralNotWhitelisted {},
+
+    #[error("This collateral is not whitelisted in Anchor overseer")]
+    CollateralNotWhitelistedInOverseer {},
+
+    #[error("The stable denom is always accepted for bids")]
+    StableBidDenom {},
+
+    #[error("Invalid asset provided, only {0} allowed")]
+    InvalidAsset(String),
+
+    #[error("Bids can only be submitted in a single denom")]
+    MultipleBidDenoms {},
+
+    #[error("No {0} assets have been provided")]
+    ZeroAssets(String),
+
+    #[error("Bid collateral token doesn't match")]
+    BidCollateralMismatch {},
+
+    #[error("Bids can not be activated in the block of a price update")]
+    PriceUpdatedInBlock {},
+
+    #[error("No bids with the specified information exist")]
+    NoBidExists {},
+
+    #[error("Bid pool not found")]
+    NoBidPoolExists {},
+
+    #[error("Invalid premium slot")]
+    InvalidPremiumSlot {},
+
+    #[error("Invalid repay denom, only {0} allowed")]
+    InvalidRepayDenom(String),
+
+    #[error("Not enough bids to execute this liquidation")]
+    InsufficientBids {},
+
+    #[error("Not enough zero premium bids to self-liquidate")]
+    InsufficientZeroPremiumBids {},
+
+    #[error("Bidder limits must allow at least one bid")]
+    InvalidBidderLimits {},
+
+    #[error("Bidders can hold at most {0} bids on the collateral")]
+    MaxBidsExceeded(u32),
+
+    #[error("Bidders can hold at most {0}{1} of bids on the collateral")]
+    MaxBidAmountExceeded(Uint256, String),
+
+    #[error("The reward token of the collateral can not be changed")]
+    RewardTokenChange {},
+
+    #[error("The collateral has no bid incentives")]
+    NoBidIncentives {},
+
+    #[error("Bid top-ups are not enabled")]
+    TopUpNotEnabled {},
+
+    #[error("Only {0} bids can be topped up")]
+    InvalidTopUpDenom(String),
+
+    #[error("Refill amount must be positive and can not be greater than the cap")]
+    InvalidRefillAmount {},
+
+    #[error("Bid has no top-up")]
+    NoBidTopUp {},
+
+    #[error("Expiry period must be positive")]
+    InvalidExpiryPeriod {},
+
+    #[error("Keeper reward can not be greater than {0}%")]
+    InvalidKeeperReward(u64),
+
+    #[error("Bid expiry is not enabled")]
+    BidExpiryNotEnabled {},
+
+    #[error("Bid {0} has not expired")]
+    BidNotExpired(Uint128),
+
+    #[error("Liquidations with swap are not enabled")]
+    SwapNotEnabled {},
+
+    #[error("Liquidations with swap require a DEX router")]
+    InvalidSwapRoute {},
+
+    #[error("Max spread must be lower than one")]
+    InvalidMaxSpread {},
+
+    #[error("Swap amount does not cover the repay amount")]
+    InsufficientSwapAmount {},
+
+    #[error("Initial discount must be lower than one and decay period can not be zero")]
+    InvalidAuctionConfig {},
+
+    #[error("Auction liquidations require the borrower")]
+    MissingAuctionBorrower {},
+
+    #[error("Auctions can only repay in {0}")]
+    InvalidAuctionRepayDenom(String),
+
+    #[error("No active auction with the given idx")]
+    NoAuctionExists {},
+
+    #[error("Bid amount is too small to buy any collateral")]
+    BidTooSmall {},
+
+    #[error("Auctioned collateral has no price")]
+    ZeroAuctionPrice {},
+
+    #[error("Auction can not be expired until {0}")]
+    AuctionNotExpired(u64),
+
+    #[error("Corrupted data found. 16 byte expected.")]
+    CorruptedData {},
+}
+
+// Queries only return standard errors
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::bid::{calculate_liquidated_collateral, withdraw_from_bid};
+use crate::error::ContractError;
+use crate::state::{
+    read_bid, read_bid_expiry, read_bids, read_config, store_bid_expiry, Bid, BidExpiry, Config,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+    Uint128,
+};
+use moneymarket::liquidation_queue::{
+    BidExpiry as BidExpiryMsg, BidExpiryResponse, ExpiredBidsResponse,
+};
+use moneymarket::querier::deduct_tax;
+use terra_cosmwasm::TerraMsgWrapper;
+
+/// Max rate of the retracted amount paid to keepers (5%)
+const MAX_KEEPER_REWARD_PERCENT: u64 = 5;
+
+pub fn update_bid_expiry(
+    deps: DepsMut,
+    info: MessageInfo,
+    bid_expiry: Option<BidExpiryMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_bid_expiry")];
+    let bid_expiry = match bid_expiry {
+        Some(bid_expiry) => {
+            if bid_expiry.expiry_period == 0 {
+                return Err(ContractError::InvalidExpiryPeriod {});
+            }
+            if bid_expiry.keeper_reward > Decimal256::percent(MAX_KEEPER_REWARD_PERCENT) {
+                return Err(ContractError::InvalidKeeperReward(
+                    MAX_KEEPER_REWARD_PERCENT,
+                ));
+            }
+            attributes.push(attr("expiry_period", bid_expiry.expiry_period.to_string()));
+            attributes.push(attr("keeper_reward", bid_expiry.keeper_reward.to_string()));
+            Some(BidExpiry {
+                expiry_period: bid_expiry.expiry_period,
+                keeper_reward: bid_expiry.keeper_reward,
+            })
+        }
+        None => None,
+    };
+
+    store_bid_expiry(deps.storage, bid_expiry)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Bids expire when they have not been updated for the expiry period and
+/// no collateral has been liquidated against them, so they are only stable
+fn is_expired(
+    storage: &dyn Storage,
+    env: &Env,
+    bid_expiry: &BidExpiry,
+    bid: &Bid,
+) -> StdResult<bool> {
+    if env.block.time.seconds() < bid.updated_at + bid_expiry.expiry_period
+        || !bid.pending_liquidated_collateral.is_zero()
+    {
+        return Ok(false);
+    }
+    if bid.wait_end.is_some() {
+        return Ok(true);
+    }
+
+    let (liquidated_collateral, residue_collateral) =
+        calculate_liquidated_collateral(storage, bid)?;
+    Ok(liquidated_collateral.is_zero() && residue_collateral.is_zero())
+}
+
+/// Anyone can retract expired bids to keep the pools small. The bid amount
+/// is returned to the bid owner, minus the keeper reward paid to the sender
+pub fn expire_bids(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bids_idx: Vec<Uint128>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let bid_expiry: BidExpiry =
+        read_bid_expiry(deps.storage)?.ok_or(ContractError::BidExpiryNotEnabled {})?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    let mut keeper_rewards: Vec<Coin> = vec![];
+    for bid_idx in bids_idx.iter() {
+        let bid: Bid = read_bid(deps.storage, *bid_idx)?;
+        if !is_expired(deps.storage, &env, &bid_expiry, &bid)? {
+            return Err(ContractError::BidNotExpired(*bid_idx));
+        }
+
+        let bidder = deps.api.addr_humanize(&bid.bidder)?;
+        let bid_denom: String = bid.bid_denom.clone();
+        let refund_amount: Uint256 = withdraw_from_bid(deps.storage, &env, bid, None)?;
+        let keeper_reward: Uint256 = refund_amount * bid_expiry.keeper_reward;
+
+        let bidder_amount = refund_amount - keeper_reward;
+        if !bidder_amount.is_zero() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: bidder.to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: bid_denom.clone(),
+                        amount: bidder_amount.into(),
+                    },
+                )?],
+            }));
+        }
+
+        match keeper_rewards
+            .iter_mut()
+            .find(|coin| coin.denom == bid_denom)
+        {
+            Some(coin) => coin.amount += Uint128::from(keeper_reward),
+            None => keeper_rewards.push(Coin {
+                denom: bid_denom,
+                amount: keeper_reward.into(),
+            }),
+        }
+    }
+
+    let mut reward_attributes = vec![];
+    for coin in keeper_rewards.into_iter() {
+        if coin.amount.is_zero() {
+            continue;
+        }
+        reward_attributes.push(attr("keeper_reward", coin.to_string()));
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deduct_tax(deps.as_ref(), coin)?],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "expire_bids"),
+            attr("expired_bids", bids_idx.len().to_string()),
+        ])
+        .add_attributes(reward_attributes))
+}
+
+pub fn query_bid_expiry(deps: Deps) -> StdResult<BidExpiryResponse> {
+    Ok(BidExpiryResponse {
+        bid_expiry: read_bid_expiry(deps.storage)?.map(|bid_expiry| BidExpiryMsg {
+            expiry_period: bid_expiry.expiry_period,
+            keeper_reward: bid_expiry.keeper_reward,
+        }),
+    })
+}
+
+pub fn query_expired_bids(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<ExpiredBidsResponse> {
+    let bids: Vec<Bid> = read_bids(deps.storage, start_after, limit)?;
+    let last_bid_idx: Option<Uint128> = bids.last().map(|bid| bid.idx);
+
+    let mut bids_idx: Vec<Uint128> = vec![];
+    if let Some(bid_expiry) = read_bid_expiry(deps.storage)? {
+        for bid in bids.iter() {
+            if is_expired(deps.storage, &env, &bid_expiry, bid)? {
+                bids_idx.push(bid.idx);
+            }
+        }
+    }
+
+    Ok(ExpiredBidsResponse {
+        bids_idx,
+        last_bid_idx,
+    })
+}
@@ -0,0 +1,201 @@
+use crate::asserts::assert_fees;
+use crate::error::ContractError;
+use crate::state::{
+    read_collateral_info, read_config, read_config_for_collateral, read_fee_split, read_fee_stats,
+    store_collateral_fees, store_fee_split, store_fee_stats, CollateralFees, Config, FeeSplit,
+    FeeStats,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Api, Attribute, Deps, DepsMut, MessageInfo, Response, StdResult, Storage,
+};
+use moneymarket::liquidation_queue::{
+    CollateralFees as CollateralFeesMsg, CollateralFeesResponse, FeeSplit as FeeSplitMsg,
+    FeeSplitResponse, FeeStatsResponse,
+};
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub fn update_fee_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_split: Option<FeeSplitMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let fee_split = match fee_split {
+        Some(fee_split) => {
+            if fee_split.insurance_share + fee_split.caller_share > Decimal256::one() {
+                return Err(ContractError::InvalidFeeSplit {});
+            }
+
+            Some(FeeSplit {
+                insurance_fund: deps.api.addr_canonicalize(&fee_split.insurance_fund)?,
+                insurance_share: fee_split.insurance_share,
+                caller_share: fee_split.caller_share,
+            })
+        }
+        None => None,
+    };
+
+    let mut attributes = vec![attr("action", "update_fee_split")];
+    if let Some(fee_split) = &fee_split {
+        attributes.push(attr(
+            "insurance_fund",
+            deps.api.addr_humanize(&fee_split.insurance_fund)?,
+        ));
+        attributes.push(attr(
+            "insurance_share",
+            fee_split.insurance_share.to_string(),
+        ));
+        attributes.push(attr("caller_share", fee_split.caller_share.to_string()));
+    }
+
+    store_fee_split(deps.storage, fee_split)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn update_collateral_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    collateral_fees: Option<CollateralFeesMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // fail if the collateral is not whitelisted
+    read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    if let Some(collateral_fees) = &collateral_fees {
+        assert_fees(collateral_fees.bid_fee + collateral_fees.liquidator_fee)?;
+    }
+
+    store_collateral_fees(
+        deps.storage,
+        &collateral_token_raw,
+        collateral_fees.map(|collateral_fees| CollateralFees {
+            bid_fee: collateral_fees.bid_fee,
+            liquidator_fee: collateral_fees.liquidator_fee,
+        }),
+    )?;
+
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_collateral_fees"),
+        attr("collateral_token", collateral_token),
+        attr("bid_fee", config.bid_fee.to_string()),
+        attr("liquidator_fee", config.liquidator_fee.to_string()),
+    ]))
+}
+
+pub fn query_collateral_fees(
+    deps: Deps,
+    collateral_token: String,
+) -> StdResult<CollateralFeesResponse> {
+    let config: Config = read_config_for_collateral(
+        deps.storage,
+        &deps.api.addr_canonicalize(&collateral_token)?,
+    )?;
+
+    Ok(CollateralFeesResponse {
+        collateral_token,
+        bid_fee: config.bid_fee,
+        liquidator_fee: config.liquidator_fee,
+    })
+}
+
+/// Bid fee portions sent to the yield reserve, the insurance fund and the caller
+pub(crate) struct BidFeeSplit {
+    pub yield_reserve: Uint256,
+    pub insurance_fund: Option<(String, Uint256)>,
+    pub caller: Uint256,
+}
+
+impl BidFeeSplit {
+    /// Only emitted when the bid fee is split
+    pub fn attributes(&self) -> Vec<Attribute> {
+        match &self.insurance_fund {
+            Some((_, insurance_fee)) => vec![
+                attr("yield_reserve_fee", self.yield_reserve),
+                attr("insurance_fee", *insurance_fee),
+                attr("caller_fee", self.caller),
+            ],
+            None => vec![],
+        }
+    }
+}
+
+/// Splits the bid fee following the fee split config and
+/// adds the portions to the cumulative fee stats of the bid denom
+pub(crate) fn split_bid_fee(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    bid_denom: &str,
+    bid_fee: Uint256,
+) -> StdResult<BidFeeSplit> {
+    let fee_split = match read_fee_split(storage)? {
+        Some(fee_split) => {
+            let insurance_fee = bid_fee * fee_split.insurance_share;
+            let caller_fee = bid_fee * fee_split.caller_share;
+            BidFeeSplit {
+                yield_reserve: bid_fee - insurance_fee - caller_fee,
+                insurance_fund: Some((
+                    api.addr_humanize(&fee_split.insurance_fund)?.to_string(),
+                    insurance_fee,
+                )),
+                caller: caller_fee,
+            }
+        }
+        None => BidFeeSplit {
+            yield_reserve: bid_fee,
+            insurance_fund: None,
+            caller: Uint256::zero(),
+        },
+    };
+
+    let mut fee_stats: FeeStats = read_fee_stats(storage, bid_denom)?;
+    fee_stats.yield_reserve += fee_split.yield_reserve;
+    fee_stats.caller += fee_split.caller;
+    if let Some((_, insurance_fee)) = &fee_split.insurance_fund {
+        fee_stats.insurance_fund += *insurance_fee;
+    }
+    store_fee_stats(storage, bid_denom, &fee_stats)?;
+
+    Ok(fee_split)
+}
+
+pub fn query_fee_split(deps: Deps) -> StdResult<FeeSplitResponse> {
+    let fee_split = match read_fee_split(deps.storage)? {
+        Some(fee_split) => Some(FeeSplitMsg {
+            insurance_fund: deps
+                .api
+                .addr_humanize(&fee_split.insurance_fund)?
+                .to_string(),
+            insurance_share: fee_split.insurance_share,
+            caller_share: fee_split.caller_share,
+        }),
+        None => None,
+    };
+
+    Ok(FeeSplitResponse { fee_split })
+}
+
+pub fn query_fee_stats(deps: Deps, bid_denom: Option<String>) -> StdResult<FeeStatsResponse> {
+    let bid_denom = match bid_denom {
+        Some(bid_denom) => bid_denom,
+        None => read_config(deps.storage)?.stable_denom,
+    };
+    let fee_stats: FeeStats = read_fee_stats(deps.storage, &bid_denom)?;
+    Ok(FeeStatsResponse {
+        yield_reserve: fee_stats.yield_reserve,
+        insurance_fund: fee_stats.insurance_fund,
+        caller: fee_stats.caller,
+    })
+}
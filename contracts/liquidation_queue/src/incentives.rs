@@ -0,0 +1,381 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_bid_incentives, read_bid_pool, read_bid_reward_index, read_bid_reward_token,
+    read_bidder_rewards, read_bids_by_user, read_collateral_info, read_config,
+    read_epoch_reward_index, read_pool_reward_time, store_bid_incentives, store_bid_reward_index,
+    store_bidder_rewards, store_epoch_reward_index, store_pool_reward_time, Bid, BidIncentives,
+    BidPool, CollateralInfo, Config,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::liquidation_queue::{
+    BidIncentives as BidIncentivesMsg, BidIncentivesResponse, BidderRewardsResponse,
+};
+use moneymarket::reward_index::{accrue_index, pending_reward};
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub fn update_bid_incentives(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: String,
+    bid_incentives: Option<BidIncentivesMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    // the emissions until now are accrued at the previous rate
+    accrue_collateral_rewards(deps.storage, &env, &collateral_info)?;
+
+    let mut attributes = vec![
+        attr("action", "update_bid_incentives"),
+        attr("collateral_token", collateral_token),
+    ];
+    let bid_incentives = match bid_incentives {
+        Some(bid_incentives) => {
+            let reward_token = deps.api.addr_canonicalize(&bid_incentives.reward_token)?;
+            if let Some(prev_reward_token) =
+                read_bid_reward_token(deps.storage, &collateral_token_raw)?
+            {
+                if prev_reward_token != reward_token {
+                    return Err(ContractError::RewardTokenChange {});
+                }
+            }
+
+            attributes.push(attr("reward_token", bid_incentives.reward_token));
+            attributes.push(attr("emission_rate", bid_incentives.emission_rate));
+            Some(BidIncentives {
+                reward_token,
+                emission_rate: bid_incentives.emission_rate,
+            })
+        }
+        None => None,
+    };
+    store_bid_incentives(deps.storage, &collateral_token_raw, bid_incentives)?;
+
+    // pools start accruing the new rate from now
+    for premium_slot in 0..collateral_info.max_slot + 1 {
+        store_pool_reward_time(
+            deps.storage,
+            &collateral_token_raw,
+            premium_slot,
+            env.block.time.seconds(),
+        )?;
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Accrues the emissions of every premium slot of the collateral,
+/// before a change of the emission split
+pub(crate) fn accrue_collateral_rewards(
+    storage: &mut dyn Storage,
+    env: &Env,
+    collateral_info: &CollateralInfo,
+) -> StdResult<()> {
+    let config: Config = read_config(storage)?;
+    for premium_slot in 0..collateral_info.max_slot + 1 {
+        if let Ok(bid_pool) = read_bid_pool(
+            storage,
+            &collateral_info.collateral_token,
+            &config.stable_denom,
+            premium_slot,
+        ) {
+            accrue_pool_rewards(
+                storage,
+                env,
+                &bid_pool,
+                &collateral_info.collateral_token,
+                &config.stable_denom,
+                premium_slot,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Emissions of the premium slot over the elapsed time. Of n slots, slot i
+/// weighs n - i, so the lowest premiums receive the largest share
+fn slot_emission(
+    collateral_info: &CollateralInfo,
+    bid_incentives: &BidIncentives,
+    premium_slot: u8,
+    elapsed: u64,
+) -> Uint256 {
+    let slots = collateral_info.max_slot as u64 + 1;
+    let weight = slots.saturating_sub(premium_slot as u64);
+    (bid_incentives.emission_rate * Uint256::from(elapsed))
+        .multiply_ratio(weight, slots * (slots + 1) / 2)
+}
+
+/// Rewards per share of the current epoch of the pool,
+/// including the emissions since the last accrual
+fn pool_reward_index(
+    storage: &dyn Storage,
+    env: &Env,
+    bid_pool: &BidPool,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<Decimal256> {
+    let reward_index = read_epoch_reward_index(
+        storage,
+        collateral_token,
+        premium_slot,
+        bid_pool.current_epoch,
+    )?;
+    let bid_incentives = match read_bid_incentives(storage, collateral_token)? {
+        Some(bid_incentives) => bid_incentives,
+        None => return Ok(reward_index),
+    };
+
+    let last_time = read_pool_reward_time(storage, collateral_token, premium_slot)?
+        .unwrap_or_else(|| env.block.time.seconds());
+    if bid_pool.total_share.is_zero() || env.block.time.seconds() <= last_time {
+        // emissions to a pool without shares are not distributed
+        return Ok(reward_index);
+    }
+
+    let collateral_info: CollateralInfo = read_collateral_info(storage, collateral_token)?;
+    let emission = slot_emission(
+        &collateral_info,
+        &bid_incentives,
+        premium_slot,
+        env.block.time.seconds() - last_time,
+    );
+
+    Ok(accrue_index(
+        reward_index,
+        Decimal256::from_uint256(emission),
+        bid_pool.total_share,
+    ))
+}
+
+/// Accrues the emissions of the pool, before its shares change.
+/// Only the stable denom pools receive emissions
+pub(crate) fn accrue_pool_rewards(
+    storage: &mut dyn Storage,
+    env: &Env,
+    bid_pool: &BidPool,
+    collateral_token: &CanonicalAddr,
+    bid_denom: &str,
+    premium_slot: u8,
+) -> StdResult<()> {
+    let config: Config = read_config(storage)?;
+    if bid_denom != config.stable_denom || read_bid_incentives(storage, collateral_token)?.is_none()
+    {
+        return Ok(());
+    }
+
+    let reward_index = pool_reward_index(storage, env, bid_pool, collateral_token, premium_slot)?;
+    store_epoch_reward_index(
+        storage,
+        collateral_token,
+        premium_slot,
+        bid_pool.current_epoch,
+        reward_index,
+    )?;
+    store_pool_reward_time(
+        storage,
+        collateral_token,
+        premium_slot,
+        env.block.time.seconds(),
+    )
+}
+
+/// Credits the bidder with the rewards of the bid shares since the last
+/// settlement, accruing the pool first. Must be called before the shares
+/// or the epoch of the bid change
+pub(crate) fn settle_bid_rewards(
+    storage: &mut dyn Storage,
+    env: &Env,
+    bid: &Bid,
+    bid_pool: &BidPool,
+) -> StdResult<()> {
+    accrue_pool_rewards(
+        storage,
+        env,
+        bid_pool,
+        &bid.collateral_token,
+        &bid.bid_denom,
+        bid.premium_slot,
+    )?;
+
+    let reward_index = read_epoch_reward_index(
+        storage,
+        &bid.collateral_token,
+        bid.premium_slot,
+        bid.epoch_snapshot,
+    )?;
+    let rewards = bid_rewards(storage, bid, reward_index)?;
+    if !rewards.is_zero() {
+        let pending_rewards =
+            read_bidder_rewards(storage, &bid.collateral_token, &bid.bidder)? + rewards;
+        store_bidder_rewards(storage, &bid.collateral_token, &bid.bidder, pending_rewards)?;
+    }
+
+    store_bid_reward_index(storage, bid.idx, reward_index)
+}
+
+/// Moves the reward snapshot of the bid to its epoch,
+/// once its shares have been minted or checkpointed
+pub(crate) fn snapshot_bid_rewards(storage: &mut dyn Storage, bid: &Bid) -> StdResult<()> {
+    let reward_index = read_epoch_reward_index(
+        storage,
+        &bid.collateral_token,
+        bid.premium_slot,
+        bid.epoch_snapshot,
+    )?;
+    store_bid_reward_index(storage, bid.idx, reward_index)
+}
+
+fn bid_rewards(storage: &dyn Storage, bid: &Bid, reward_index: Decimal256) -> StdResult<Uint256> {
+    if bid.wait_end.is_some() {
+        return Ok(Uint256::zero());
+    }
+
+    let bid_reward_index = read_bid_reward_index(storage, bid.idx)?;
+    Ok(pending_reward(reward_index, bid_reward_index, bid.share) * Uint256::one())
+}
+
+/// Bidders claim the rewards of their bids on the collateral,
+/// including the emissions of their active bids until now
+pub fn claim_bid_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: String,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let reward_token = read_bid_reward_token(deps.storage, &collateral_token_raw)?
+        .ok_or(ContractError::NoBidIncentives {})?;
+
+    let bids: Vec<Bid> =
+        read_bids_by_user(deps.storage, &collateral_token_raw, &sender_raw, None, None)?;
+    for bid in bids.iter() {
+        let bid_pool: BidPool = read_bid_pool(
+            deps.storage,
+            &bid.collateral_token,
+            &bid.bid_denom,
+            bid.premium_slot,
+        )?;
+        settle_bid_rewards(deps.storage, &env, bid, &bid_pool)?;
+    }
+
+    let claim_amount = read_bidder_rewards(deps.storage, &collateral_token_raw, &sender_raw)?;
+    store_bidder_rewards(
+        deps.storage,
+        &collateral_token_raw,
+        &sender_raw,
+        Uint256::zero(),
+    )?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    if !claim_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&reward_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: claim_amount.into(),
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_bid_rewards"),
+        attr("collateral_token", collateral_token),
+        attr("reward_amount", claim_amount),
+    ]))
+}
+
+pub fn query_bid_incentives(
+    deps: Deps,
+    collateral_token: String,
+) -> StdResult<BidIncentivesResponse> {
+    let bid_incentives = match read_bid_incentives(
+        deps.storage,
+        &deps.api.addr_canonicalize(&collateral_token)?,
+    )? {
+        Some(bid_incentives) => Some(BidIncentivesMsg {
+            reward_token: deps
+                .api
+                .addr_humanize(&bid_incentives.reward_token)?
+                .to_string(),
+            emission_rate: bid_incentives.emission_rate,
+        }),
+        None => None,
+    };
+
+    Ok(BidIncentivesResponse {
+        collateral_token,
+        bid_incentives,
+    })
+}
+
+pub fn query_bidder_rewards(
+    deps: Deps,
+    env: Env,
+    collateral_token: String,
+    bidder: String,
+) -> StdResult<BidderRewardsResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let bidder_raw = deps.api.addr_canonicalize(&bidder)?;
+
+    let mut pending_rewards =
+        read_bidder_rewards(deps.storage, &collateral_token_raw, &bidder_raw)?;
+    let bids: Vec<Bid> =
+        read_bids_by_user(deps.storage, &collateral_token_raw, &bidder_raw, None, None)?;
+    for bid in bids
+        .iter()
+        .filter(|bid| bid.bid_denom == config.stable_denom)
+    {
+        let bid_pool: BidPool = read_bid_pool(
+            deps.storage,
+            &bid.collateral_token,
+            &bid.bid_denom,
+            bid.premium_slot,
+        )?;
+        let reward_index = if bid.epoch_snapshot == bid_pool.current_epoch {
+            pool_reward_index(
+                deps.storage,
+                &env,
+                &bid_pool,
+                &collateral_token_raw,
+                bid.premium_slot,
+            )?
+        } else {
+            read_epoch_reward_index(
+                deps.storage,
+                &collateral_token_raw,
+                bid.premium_slot,
+                bid.epoch_snapshot,
+            )?
+        };
+        pending_rewards += bid_rewards(deps.storage, bid, reward_index)?;
+    }
+
+    let reward_token = match read_bid_reward_token(deps.storage, &collateral_token_raw)? {
+        Some(reward_token) => Some(deps.api.addr_humanize(&reward_token)?.to_string()),
+        None => None,
+    };
+
+    Ok(BidderRewardsResponse {
+        collateral_token,
+        bidder,
+        reward_token,
+        pending_rewards,
+    })
+}
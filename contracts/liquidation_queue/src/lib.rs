@@ -1,9 +1,18 @@
 mod asserts;
+mod auction;
 mod bid;
+mod bidder_limits;
 pub mod contract;
+pub mod error;
+mod expiry;
+mod fee;
+mod incentives;
 mod querier;
 mod query;
+mod self_liquidation;
 mod state;
+mod swap;
+mod top_up;
 
 #[cfg(test)]
 mod testing;
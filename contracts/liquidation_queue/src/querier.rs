@@ -1,22 +1,20 @@
-use cosmwasm_std::{to_binary, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
-use moneymarket::overseer::{
-    QueryMsg as OverseerQueryMsg, WhitelistResponse, WhitelistResponseElem,
-};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
+use moneymarket::overseer::{FreezeStateResponse, OverseerQuerier, WhitelistResponseElem};
 
 pub fn query_collateral_whitelist_info(
     querier: &QuerierWrapper,
-    overseer: String,
+    overseer: Addr,
     collateral_token: String,
 ) -> StdResult<WhitelistResponseElem> {
-    let whitelist_res: WhitelistResponse =
-        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: overseer,
-            msg: to_binary(&OverseerQueryMsg::Whitelist {
-                collateral_token: Some(collateral_token),
-                start_after: None,
-                limit: None,
-            })?,
-        }))?;
+    let whitelist_res =
+        OverseerQuerier::new(*querier, overseer).whitelist(Some(collateral_token), None, None)?;
 
     Ok(whitelist_res.elems[0].clone())
 }
+
+pub fn query_freeze_state(
+    querier: &QuerierWrapper,
+    overseer: Addr,
+) -> StdResult<FreezeStateResponse> {
+    OverseerQuerier::new(*querier, overseer).freeze_state()
+}
@@ -1,15 +1,20 @@
 use crate::bid::{calculate_liquidated_collateral, calculate_remaining_bid};
 use crate::querier::query_collateral_whitelist_info;
 use crate::state::{
-    read_bid, read_bid_pool, read_bid_pools, read_bids_by_user, read_collateral_info, read_config,
-    read_total_bids, Bid, BidPool, CollateralInfo, Config,
+    read_auction_config, read_bid, read_bid_denoms, read_bid_pool, read_bid_pools,
+    read_bids_by_bidder, read_bids_by_user, read_collateral_info, read_config,
+    read_config_for_collateral, read_liquidation_logs, read_liquidation_logs_by_borrower,
+    read_sniping_protection, read_total_bids, Bid, BidPool, CollateralInfo, Config, LiquidationLog,
 };
+use crate::swap::swap_discount;
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, StdResult, Uint128};
+use cosmwasm_std::{Addr, CanonicalAddr, Deps, StdResult, Uint128};
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ConfigResponse, LiquidationAmountResponse,
+    BidDenomsResponse, BidFill, BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse,
+    CollateralInfoResponse, ConfigResponse, LiquidationAmountResponse, LiquidationLogResponse,
+    LiquidationLogsResponse, SimulateBidExecutionResponse, SnipingProtectionResponse,
 };
+use moneymarket::math::{checked_mul, complement, div_dec, mul_dec, Rounding};
 use moneymarket::querier::query_tax_rate_and_cap;
 use moneymarket::tokens::TokensHuman;
 
@@ -31,21 +36,36 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(resp)
 }
 
+pub fn query_bid_denoms(deps: Deps) -> StdResult<BidDenomsResponse> {
+    Ok(BidDenomsResponse {
+        stable_denom: read_config(deps.storage)?.stable_denom,
+        bid_denoms: read_bid_denoms(deps.storage)?,
+    })
+}
+
+pub fn query_sniping_protection(deps: Deps) -> StdResult<SnipingProtectionResponse> {
+    Ok(SnipingProtectionResponse {
+        enabled: read_sniping_protection(deps.storage)?,
+    })
+}
+
 /// The amount of collateral to be liquidated depends on the status of the bid pools
 /// for each collateral. To find out how much collateral should be liquidated
 /// we find the intersaction between f(x) and g(x); where x = liquidated collateral,
 /// f(x) determines liquidation amount at which the safe ratio is satisfied, and g(x) gives
 /// the repay amount based on the collateral being liquidated, which takes into account the
-/// available bids at different premium rates
+/// available bids at different premium rates. Collaterals sold on the swap router, with `swap`,
+/// are sized as auctions
 pub fn query_liquidation_amount(
     deps: Deps,
     borrow_amount: Uint256,
     borrow_limit: Uint256,
     collaterals: TokensHuman,
     collateral_prices: Vec<Decimal256>,
+    swap: bool,
 ) -> StdResult<LiquidationAmountResponse> {
     let config: Config = read_config(deps.storage)?;
-    let overseer: String = deps.api.addr_humanize(&config.overseer)?.to_string();
+    let overseer: Addr = deps.api.addr_humanize(&config.overseer)?;
 
     // Safely collateralized check
     if borrow_amount <= borrow_limit {
@@ -56,7 +76,14 @@ pub fn query_liquidation_amount(
 
     // calculate value of all collaterals and weights
     let (collaterals_value, total_weight, collateral_weights, max_ltvs) =
-        compute_collateral_weights(deps, overseer, &collaterals, &collateral_prices)?;
+        compute_collateral_weights(
+            deps,
+            overseer,
+            &config.stable_denom,
+            &collaterals,
+            &collateral_prices,
+            swap,
+        )?;
 
     // check partial liquidation condition
     let safe_ratio = if collaterals_value <= config.liquidation_threshold {
@@ -65,25 +92,26 @@ pub fn query_liquidation_amount(
         config.safe_ratio
     };
 
-    // check tax cap
-    let (mut tax_rate, tax_cap) = query_tax_rate_and_cap(deps, config.stable_denom)?;
+    // check tax cap, a fee model without tax rate charges the cap as is
+    let (mut tax_rate, tax_cap) = query_tax_rate_and_cap(deps, config.stable_denom.clone())?;
     let mut tax_cap_adj = tax_cap;
-    if borrow_amount * tax_rate > tax_cap_adj {
+    if borrow_amount * tax_rate >= tax_cap_adj {
         tax_rate = Decimal256::zero()
     } else {
         tax_cap_adj = Uint256::from(1u128)
     }
 
-    let base_fee_deductor = (Decimal256::one() - config.bid_fee)
-        * (Decimal256::one() - config.liquidator_fee)
-        * (Decimal256::one() - tax_rate);
-
     let mut result: Vec<(String, Uint256)> = vec![];
     for (i, collateral) in collaterals.iter().enumerate() {
         let (price, weight, max_ltv) = (collateral_prices[i], collateral_weights[i], max_ltvs[i]);
 
         let collateral_token_raw = deps.api.addr_canonicalize(&collateral.0)?;
         let collateral_info = read_collateral_info(deps.storage, &collateral_token_raw)?;
+        let collateral_config: Config =
+            read_config_for_collateral(deps.storage, &collateral_token_raw)?;
+        let base_fee_deductor = (Decimal256::one() - collateral_config.bid_fee)
+            * (Decimal256::one() - collateral_config.liquidator_fee)
+            * (Decimal256::one() - tax_rate);
 
         // calculate borrow amount and limit portion
         let position_portion =
@@ -91,16 +119,47 @@ pub fn query_liquidation_amount(
         let collateral_borrow_amount = borrow_amount * position_portion;
         let collateral_borrow_limit = borrow_limit * position_portion;
 
+        // auctions never sell the collateral below the initial discount, the amount
+        // is sized for the safe ratio to hold even when filled at the initial discount.
+        // Swaps repay at the premium of the highest slot
+        let discount: Option<Decimal256> = if swap {
+            Some(swap_discount(&collateral_info))
+        } else {
+            read_auction_config(deps.storage, &collateral_token_raw)?
+                .map(|auction_config| auction_config.initial_discount)
+        };
+        if let Some(discount) = discount {
+            let safe_borrow = safe_ratio * collateral_borrow_limit;
+            let fee_deductor = (Decimal256::one() - discount) * base_fee_deductor;
+            let liquidation_amount = if fee_deductor > safe_ratio * max_ltv {
+                ((collateral_borrow_amount - safe_borrow + tax_cap_adj)
+                    / (price * (fee_deductor - (safe_ratio * max_ltv))))
+                    + Uint256::one() // round up
+            } else {
+                collateral.1
+            };
+
+            result.push((
+                collateral.0.to_string(),
+                liquidation_amount.min(collateral.1),
+            ));
+            continue;
+        }
+
         // iterate bid pools until safe ratio condition is met (intersection f(x) and g(x))
         let mut x = Uint256::zero();
         let mut g_x = Uint256::zero();
         let mut intersected = false;
         for slot in 0..collateral_info.max_slot + 1 {
-            let (slot_available_bids, premium_rate) =
-                match read_bid_pool(deps.storage, &collateral_token_raw, slot) {
-                    Ok(bid_pool) => (bid_pool.total_bid_amount, bid_pool.premium_rate),
-                    Err(_) => continue,
-                };
+            let (slot_available_bids, premium_rate) = match read_bid_pool(
+                deps.storage,
+                &collateral_token_raw,
+                &config.stable_denom,
+                slot,
+            ) {
+                Ok(bid_pool) => (bid_pool.total_bid_amount, bid_pool.premium_rate),
+                Err(_) => continue,
+            };
             if slot_available_bids.is_zero() {
                 continue;
             };
@@ -149,14 +208,90 @@ pub fn query_liquidation_amount(
     })
 }
 
+/// Walks the bid pools as the liquidation would, without consuming the bids.
+/// Auctioned collaterals are filled at the initial discount
+pub fn query_simulate_bid_execution(
+    deps: Deps,
+    collateral_token: String,
+    amount: Uint256,
+    price: Decimal256,
+) -> StdResult<SimulateBidExecutionResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    let mut fills: Vec<BidFill> = vec![];
+    let mut remaining_collateral = amount;
+    if let Some(auction_config) = read_auction_config(deps.storage, &collateral_token_raw)? {
+        fills.push(BidFill {
+            premium_rate: auction_config.initial_discount,
+            collateral_amount: amount,
+            stable_amount: amount * price * (Decimal256::one() - auction_config.initial_discount),
+        });
+        remaining_collateral = Uint256::zero();
+    } else {
+        for slot in 0..collateral_info.max_slot + 1 {
+            let bid_pool: BidPool = match read_bid_pool(
+                deps.storage,
+                &collateral_token_raw,
+                &config.stable_denom,
+                slot,
+            ) {
+                Ok(bid_pool) => bid_pool,
+                Err(_) => continue,
+            };
+            if bid_pool.total_bid_amount.is_zero() {
+                continue;
+            };
+
+            let premium_price = checked_mul(price, complement(bid_pool.premium_rate)?)?;
+            let mut pool_collateral = remaining_collateral;
+            let mut pool_stable = mul_dec(pool_collateral, premium_price, Rounding::Down)?;
+            if pool_stable > bid_pool.total_bid_amount {
+                pool_stable = bid_pool.total_bid_amount;
+                pool_collateral = div_dec(pool_stable, premium_price, Rounding::Down)?;
+            }
+
+            fills.push(BidFill {
+                premium_rate: bid_pool.premium_rate,
+                collateral_amount: pool_collateral,
+                stable_amount: pool_stable,
+            });
+            remaining_collateral = remaining_collateral - pool_collateral;
+            if remaining_collateral.is_zero() {
+                break;
+            }
+        }
+    }
+
+    let stable_amount: Uint256 = fills
+        .iter()
+        .fold(Uint256::zero(), |sum, fill| sum + fill.stable_amount);
+    let bid_fee = stable_amount * config.bid_fee;
+    let liquidator_fee = stable_amount * config.liquidator_fee;
+
+    Ok(SimulateBidExecutionResponse {
+        collateral_token,
+        collateral_amount: amount - remaining_collateral,
+        repay_amount: stable_amount - bid_fee - liquidator_fee,
+        bid_fee,
+        liquidator_fee,
+        fills,
+    })
+}
+
 /// The portion of collateral that liquidated from the available set is calculated
-/// based on weight = min(collateral_value, available_bids) / max_ltv
+/// based on weight = min(collateral_value, available_bids) / max_ltv, or
+/// collateral_value / max_ltv for auctioned collaterals
 #[allow(clippy::ptr_arg)]
 fn compute_collateral_weights(
     deps: Deps,
-    overseer: String,
+    overseer: Addr,
+    stable_denom: &str,
     collaterals: &TokensHuman,
     collateral_prices: &Vec<Decimal256>,
+    swap: bool,
 ) -> StdResult<(Uint256, Uint256, Vec<Uint256>, Vec<Decimal256>)> {
     let mut collaterals_value = Uint256::zero();
     let mut total_weight = Uint256::zero();
@@ -164,18 +299,29 @@ fn compute_collateral_weights(
     let mut max_ltvs: Vec<Decimal256> = vec![];
 
     for (collateral, price) in collaterals.iter().zip(collateral_prices.iter()) {
-        let collateral_available_bids =
-            read_total_bids(deps.storage, &deps.api.addr_canonicalize(&collateral.0)?)
-                .unwrap_or_default();
+        let collateral_available_bids = read_total_bids(
+            deps.storage,
+            &deps.api.addr_canonicalize(&collateral.0)?,
+            stable_denom,
+        )
+        .unwrap_or_default();
         let max_ltv = query_collateral_whitelist_info(
             &deps.querier,
-            overseer.to_string(),
+            overseer.clone(),
             collateral.0.to_string(),
         )?
         .max_ltv;
 
         let collateral_value = collateral.1 * *price;
-        let weigth = collateral_value.min(collateral_available_bids) / max_ltv;
+        let weigth = if swap
+            || read_auction_config(deps.storage, &deps.api.addr_canonicalize(&collateral.0)?)?
+                .is_some()
+        {
+            // auctioned and swapped collaterals do not depend on the available bids
+            collateral_value / max_ltv
+        } else {
+            collateral_value.min(collateral_available_bids) / max_ltv
+        };
 
         total_weight += weigth;
         collaterals_value += collateral_value;
@@ -193,13 +339,18 @@ fn compute_collateral_weights(
 
 pub fn query_bid(deps: Deps, bid_idx: Uint128) -> StdResult<BidResponse> {
     let bid: Bid = read_bid(deps.storage, bid_idx)?;
-    let bid_pool: BidPool = read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
+    let bid_pool: BidPool = read_bid_pool(
+        deps.storage,
+        &bid.collateral_token,
+        &bid.bid_denom,
+        bid.premium_slot,
+    )?;
 
     let (bid_amount, bid_pending_liquidated_collateral) = if bid.wait_end.is_some() {
         (bid.amount, bid.pending_liquidated_collateral)
     } else {
         // calculate remaining bid amount
-        let (remaining_bid, _) = calculate_remaining_bid(&bid, &bid_pool)?;
+        let remaining_bid = calculate_remaining_bid(&bid, &bid_pool);
 
         // calculate liquidated collateral
         let (liquidated_collateral, _) = calculate_liquidated_collateral(deps.storage, &bid)?;
@@ -214,68 +365,78 @@ pub fn query_bid(deps: Deps, bid_idx: Uint128) -> StdResult<BidResponse> {
         idx: bid.idx,
         collateral_token: deps.api.addr_humanize(&bid.collateral_token)?.to_string(),
         bidder: deps.api.addr_humanize(&bid.bidder)?.to_string(),
+        bid_denom: bid.bid_denom,
         amount: bid_amount,
         premium_slot: bid.premium_slot,
         pending_liquidated_collateral: bid_pending_liquidated_collateral,
-        product_snapshot: bid.product_snapshot,
+        share: bid.share,
         sum_snapshot: bid.sum_snapshot,
         wait_end: bid.wait_end,
         epoch_snapshot: bid.epoch_snapshot,
-        scale_snapshot: bid.scale_snapshot,
     })
 }
 
 pub fn query_bids_by_user(
     deps: Deps,
-    collateral_token: String,
+    collateral_token: Option<String>,
     bidder: String,
     start_after: Option<Uint128>,
     limit: Option<u8>,
 ) -> StdResult<BidsResponse> {
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
     let bidder_raw = deps.api.addr_canonicalize(&bidder)?;
 
-    let bids: Vec<BidResponse> = read_bids_by_user(
-        deps.storage,
-        &collateral_token_raw,
-        &bidder_raw,
-        start_after,
-        limit,
-    )?
-    .iter()
-    .map(|bid| {
-        let bid_pool: BidPool =
-            read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
-        let (bid_amount, bid_pending_liquidated_collateral) = if bid.wait_end.is_some() {
-            (bid.amount, bid.pending_liquidated_collateral)
-        } else {
-            // calculate remaining bid amount
-            let (remaining_bid, _) = calculate_remaining_bid(bid, &bid_pool)?;
-
-            // calculate liquidated collateral
-            let (liquidated_collateral, _) = calculate_liquidated_collateral(deps.storage, bid)?;
+    // without collateral token, the bids of all collaterals are returned
+    let bids: Vec<Bid> = match collateral_token {
+        Some(collateral_token) => read_bids_by_user(
+            deps.storage,
+            &deps.api.addr_canonicalize(&collateral_token)?,
+            &bidder_raw,
+            start_after,
+            limit,
+        )?,
+        None => read_bids_by_bidder(deps.storage, &bidder_raw, start_after, limit)?,
+    };
 
-            (
-                remaining_bid,
-                bid.pending_liquidated_collateral + liquidated_collateral,
-            )
-        };
-        let res = BidResponse {
-            idx: bid.idx,
-            collateral_token: deps.api.addr_humanize(&bid.collateral_token)?.to_string(),
-            bidder: deps.api.addr_humanize(&bid.bidder)?.to_string(),
-            amount: bid_amount,
-            premium_slot: bid.premium_slot,
-            pending_liquidated_collateral: bid_pending_liquidated_collateral,
-            product_snapshot: bid.product_snapshot,
-            sum_snapshot: bid.sum_snapshot,
-            wait_end: bid.wait_end,
-            epoch_snapshot: bid.epoch_snapshot,
-            scale_snapshot: bid.scale_snapshot,
-        };
-        Ok(res)
-    })
-    .collect::<StdResult<Vec<BidResponse>>>()?;
+    let bids: Vec<BidResponse> = bids
+        .iter()
+        .map(|bid| {
+            let bid_pool: BidPool = read_bid_pool(
+                deps.storage,
+                &bid.collateral_token,
+                &bid.bid_denom,
+                bid.premium_slot,
+            )?;
+            let (bid_amount, bid_pending_liquidated_collateral) = if bid.wait_end.is_some() {
+                (bid.amount, bid.pending_liquidated_collateral)
+            } else {
+                // calculate remaining bid amount
+                let remaining_bid = calculate_remaining_bid(bid, &bid_pool);
+
+                // calculate liquidated collateral
+                let (liquidated_collateral, _) =
+                    calculate_liquidated_collateral(deps.storage, bid)?;
+
+                (
+                    remaining_bid,
+                    bid.pending_liquidated_collateral + liquidated_collateral,
+                )
+            };
+            let res = BidResponse {
+                idx: bid.idx,
+                collateral_token: deps.api.addr_humanize(&bid.collateral_token)?.to_string(),
+                bidder: deps.api.addr_humanize(&bid.bidder)?.to_string(),
+                bid_denom: bid.bid_denom.clone(),
+                amount: bid_amount,
+                premium_slot: bid.premium_slot,
+                pending_liquidated_collateral: bid_pending_liquidated_collateral,
+                share: bid.share,
+                sum_snapshot: bid.sum_snapshot,
+                wait_end: bid.wait_end,
+                epoch_snapshot: bid.epoch_snapshot,
+            };
+            Ok(res)
+        })
+        .collect::<StdResult<Vec<BidResponse>>>()?;
 
     Ok(BidsResponse { bids })
 }
@@ -285,39 +446,54 @@ pub fn query_bid_pool(
     collateral_token: String,
     bid_slot: u8,
 ) -> StdResult<BidPoolResponse> {
+    let config: Config = read_config(deps.storage)?;
     let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
-    let bid_pool: BidPool = read_bid_pool(deps.storage, &collateral_token_raw, bid_slot)?;
+    let bid_pool: BidPool = read_bid_pool(
+        deps.storage,
+        &collateral_token_raw,
+        &config.stable_denom,
+        bid_slot,
+    )?;
 
     Ok(BidPoolResponse {
         sum_snapshot: bid_pool.sum_snapshot,
-        product_snapshot: bid_pool.product_snapshot,
+        total_share: bid_pool.total_share,
         total_bid_amount: bid_pool.total_bid_amount,
         premium_rate: bid_pool.premium_rate,
         current_epoch: bid_pool.current_epoch,
-        current_scale: bid_pool.current_scale,
     })
 }
 
+/// Bid pools in the stable denom when no bid denom is given
 pub fn query_bid_pools(
     deps: Deps,
     collateral_token: String,
+    bid_denom: Option<String>,
     start_after: Option<u8>,
     limit: Option<u8>,
 ) -> StdResult<BidPoolsResponse> {
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let bid_denom = match bid_denom {
+        Some(bid_denom) => bid_denom,
+        None => read_config(deps.storage)?.stable_denom,
+    };
 
-    let bid_pools: Vec<BidPoolResponse> =
-        read_bid_pools(deps.storage, &collateral_token_raw, start_after, limit)?
-            .iter()
-            .map(|bid_pool| BidPoolResponse {
-                sum_snapshot: bid_pool.sum_snapshot,
-                product_snapshot: bid_pool.product_snapshot,
-                total_bid_amount: bid_pool.total_bid_amount,
-                premium_rate: bid_pool.premium_rate,
-                current_epoch: bid_pool.current_epoch,
-                current_scale: bid_pool.current_scale,
-            })
-            .collect();
+    let bid_pools: Vec<BidPoolResponse> = read_bid_pools(
+        deps.storage,
+        &collateral_token_raw,
+        &bid_denom,
+        start_after,
+        limit,
+    )?
+    .iter()
+    .map(|bid_pool| BidPoolResponse {
+        sum_snapshot: bid_pool.sum_snapshot,
+        total_share: bid_pool.total_share,
+        total_bid_amount: bid_pool.total_bid_amount,
+        premium_rate: bid_pool.premium_rate,
+        current_epoch: bid_pool.current_epoch,
+    })
+    .collect();
 
     Ok(BidPoolsResponse { bid_pools })
 }
@@ -337,3 +513,52 @@ pub fn query_collateral_info(
         premium_rate_per_slot: collateral_info.premium_rate_per_slot,
     })
 }
+
+pub fn query_liquidation_logs(
+    deps: Deps,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<LiquidationLogsResponse> {
+    let logs: Vec<LiquidationLog> = read_liquidation_logs(deps.storage, start_after, limit)?;
+    liquidation_logs_response(deps, logs)
+}
+
+pub fn query_liquidation_logs_by_borrower(
+    deps: Deps,
+    borrower: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<LiquidationLogsResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(&borrower)?;
+    let logs: Vec<LiquidationLog> =
+        read_liquidation_logs_by_borrower(deps.storage, &borrower_raw, start_after, limit)?;
+    liquidation_logs_response(deps, logs)
+}
+
+fn liquidation_logs_response(
+    deps: Deps,
+    logs: Vec<LiquidationLog>,
+) -> StdResult<LiquidationLogsResponse> {
+    let logs: Vec<LiquidationLogResponse> = logs
+        .into_iter()
+        .map(|log| {
+            let borrower = match log.borrower {
+                Some(borrower) => Some(deps.api.addr_humanize(&borrower)?.to_string()),
+                None => None,
+            };
+            Ok(LiquidationLogResponse {
+                idx: log.idx,
+                height: log.height,
+                borrower,
+                collateral_token: deps.api.addr_humanize(&log.collateral_token)?.to_string(),
+                collateral_amount: log.collateral_amount,
+                premium_rate: log.premium_rate,
+                repay_denom: log.repay_denom,
+                repay_amount: log.repay_amount,
+                liquidator: deps.api.addr_humanize(&log.liquidator)?.to_string(),
+            })
+        })
+        .collect::<StdResult<Vec<LiquidationLogResponse>>>()?;
+
+    Ok(LiquidationLogsResponse { logs })
+}
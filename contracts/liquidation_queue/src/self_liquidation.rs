@@ -0,0 +1,140 @@
+use crate::bid::{assert_custody_contract, assert_not_frozen, execute_pool_liquidation};
+use crate::error::ContractError;
+use crate::incentives::accrue_pool_rewards;
+use crate::state::{
+    pop_liquidation_log_idx, read_bid_pool, read_config, read_total_bids, store_bid_pool,
+    store_liquidation_log, store_total_bids, BidPool, Config, LiquidationLog,
+};
+use crate::top_up::top_up_messages;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, BankMsg, Coin, CosmosMsg, DepsMut, Env, Response, SubMsg};
+use moneymarket::oracle::PriceResponse;
+use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+use terra_cosmwasm::TerraMsgWrapper;
+
+/// Slot of the bids paying the oracle price
+const ZERO_PREMIUM_SLOT: u8 = 0;
+
+/// The borrower sells the collateral to the stable denom bids of the zero
+/// premium slot. No bid fee or liquidator fee is taken, the whole proceeds
+/// repay the loan. Fails when the slot cannot fill the amount
+pub fn execute_self_liquidation(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    borrower: String,
+    repay_address: String,
+    collateral_token: String,
+    amount: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    assert_custody_contract(deps.as_ref(), &config, &sender, &collateral_token)?;
+    assert_not_frozen(deps.as_ref(), &config)?;
+
+    let mut bid_pool: BidPool = read_bid_pool(
+        deps.storage,
+        &collateral_token_raw,
+        &config.stable_denom,
+        ZERO_PREMIUM_SLOT,
+    )
+    .map_err(|_| ContractError::InsufficientZeroPremiumBids {})?;
+
+    let price: PriceResponse = query_price(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.clone(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    accrue_pool_rewards(
+        deps.storage,
+        &env,
+        &bid_pool,
+        &collateral_token_raw,
+        &config.stable_denom,
+        ZERO_PREMIUM_SLOT,
+    )?;
+    let mut filled = false;
+    let (repay_amount, _, dust_bid) = execute_pool_liquidation(
+        deps.storage,
+        &mut bid_pool,
+        &collateral_token_raw,
+        &config.stable_denom,
+        ZERO_PREMIUM_SLOT,
+        amount,
+        price.rate,
+        &mut filled,
+    )?;
+    if !filled {
+        return Err(ContractError::InsufficientZeroPremiumBids {});
+    }
+
+    store_bid_pool(
+        deps.storage,
+        &collateral_token_raw,
+        &config.stable_denom,
+        ZERO_PREMIUM_SLOT,
+        &bid_pool,
+    )?;
+    let available_bids =
+        read_total_bids(deps.storage, &collateral_token_raw, &config.stable_denom)?;
+    store_total_bids(
+        deps.storage,
+        &collateral_token_raw,
+        &config.stable_denom,
+        available_bids - repay_amount - dust_bid,
+    )?;
+
+    let depleted_slots: Vec<u8> = if bid_pool.total_bid_amount.is_zero() {
+        vec![ZERO_PREMIUM_SLOT]
+    } else {
+        vec![]
+    };
+    let top_up_messages: Vec<SubMsg<TerraMsgWrapper>> =
+        top_up_messages(deps.storage, &env, &collateral_token_raw, &depleted_slots)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(&borrower)?;
+    let log = LiquidationLog {
+        idx: pop_liquidation_log_idx(deps.storage)?,
+        height: env.block.height,
+        borrower: Some(borrower_raw.clone()),
+        collateral_token: collateral_token_raw,
+        collateral_amount: amount,
+        premium_rate: Decimal256::zero(),
+        repay_denom: config.stable_denom.clone(),
+        repay_amount,
+        liquidator: borrower_raw,
+    };
+    store_liquidation_log(deps.storage, &log)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    if !repay_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: repay_address,
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: repay_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(top_up_messages)
+        .add_attributes(vec![
+            attr("action", "execute_self_liquidation"),
+            attr("borrower", borrower),
+            attr("repay_amount", repay_amount),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", amount),
+        ]))
+}
@@ -1,22 +1,57 @@
+use crate::error::ContractError;
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::pagination::{calc_limit_with_max, calc_range_start};
+use moneymarket::reward_index::{read_index, remove_index, store_index};
+use moneymarket::swap::SwapRoute;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_BID_IDX: &[u8] = b"bid_idx";
+static KEY_SNIPING_PROTECTION: &[u8] = b"sniping_protection";
+static KEY_AUCTION_IDX: &[u8] = b"auction_idx";
+static KEY_FEE_SPLIT: &[u8] = b"fee_split";
+static KEY_TOP_UP_CONFIG: &[u8] = b"top_up_config";
+static KEY_BID_DENOMS: &[u8] = b"bid_denoms";
+static KEY_BID_EXPIRY: &[u8] = b"bid_expiry";
+static KEY_SWAP_ROUTER: &[u8] = b"swap_router";
+static KEY_LIQUIDATION_LOG_IDX: &[u8] = b"liquidation_log_idx";
 
 static PREFIX_BID: &[u8] = b"bid";
 static PREFIX_BID_BY_USER: &[u8] = b"bid_by_user";
+static PREFIX_BID_BY_BIDDER: &[u8] = b"bid_by_bidder";
 static PREFIX_BID_POOL_BY_COLLATERAL: &[u8] = b"bid_pool_by_col";
 static PREFIX_TOTAL_BIDS_BY_COLLATERAL: &[u8] = b"total_bids_by_col";
 static PREFIX_COLLATERAL_INFO: &[u8] = b"col_info";
-static PREFIX_EPOCH_SCALE_SUM: &[u8] = b"epoch_scale_sum";
+static PREFIX_EPOCH_SUM: &[u8] = b"epoch_sum";
+static PREFIX_AUCTION_CONFIG: &[u8] = b"auction_config";
+static PREFIX_AUCTION: &[u8] = b"auction";
+static PREFIX_AUCTION_BY_COLLATERAL: &[u8] = b"auction_by_col";
+static PREFIX_AUCTION_BY_BORROWER: &[u8] = b"auction_by_borrower";
+static PREFIX_BID_TOP_UP: &[u8] = b"bid_top_up";
+static PREFIX_BID_TOP_UP_BY_POOL: &[u8] = b"bid_top_up_by_pool";
+static PREFIX_FEE_STATS: &[u8] = b"fee_stats";
+static PREFIX_LIQUIDATION_LOG: &[u8] = b"liquidation_log";
+static PREFIX_LIQUIDATION_LOG_BY_BORROWER: &[u8] = b"liquidation_log_by_borrower";
+static PREFIX_BID_INCENTIVES: &[u8] = b"bid_incentives";
+static PREFIX_BID_REWARD_TOKEN: &[u8] = b"bid_reward_token";
+static PREFIX_POOL_REWARD_TIME: &[u8] = b"pool_reward_time";
+static PREFIX_EPOCH_REWARD_INDEX: &[u8] = b"epoch_reward_index";
+static PREFIX_BID_REWARD_INDEX: &[u8] = b"bid_reward_index";
+static PREFIX_BIDDER_REWARDS: &[u8] = b"bidder_rewards";
+static PREFIX_BIDDER_LIMITS: &[u8] = b"bidder_limits";
+static PREFIX_COLLATERAL_FEES: &[u8] = b"collateral_fees";
 
-const MAX_LIMIT: u8 = 31;
-const DEFAULT_LIMIT: u8 = 10;
+// one page holds the bid pools of every premium slot
+const MAX_LIMIT: u32 = 31;
+
+/// Number of liquidations kept in the global log
+pub const MAX_LIQUIDATION_LOGS: u128 = 1000;
+/// Number of liquidations kept in the log of each borrower
+pub const MAX_BORROWER_LIQUIDATION_LOGS: usize = 100;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -40,6 +75,121 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+/// Returns the config with the fees of the given collateral applied
+pub fn read_config_for_collateral(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Config> {
+    let config: Config = read_config(storage)?;
+    Ok(match read_collateral_fees(storage, collateral_token)? {
+        Some(collateral_fees) => Config {
+            bid_fee: collateral_fees.bid_fee,
+            liquidator_fee: collateral_fees.liquidator_fee,
+            ..config
+        },
+        None => config,
+    })
+}
+
+/// Fees of the collateral replacing the flat config fees
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralFees {
+    pub bid_fee: Decimal256,
+    pub liquidator_fee: Decimal256,
+}
+
+pub fn store_collateral_fees(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    collateral_fees: Option<CollateralFees>,
+) -> StdResult<()> {
+    let mut collateral_fees_bucket: Bucket<CollateralFees> =
+        Bucket::new(storage, PREFIX_COLLATERAL_FEES);
+    match collateral_fees {
+        Some(collateral_fees) => {
+            collateral_fees_bucket.save(collateral_token.as_slice(), &collateral_fees)
+        }
+        None => {
+            collateral_fees_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_collateral_fees(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<CollateralFees>> {
+    let collateral_fees_bucket: ReadonlyBucket<CollateralFees> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_FEES);
+    collateral_fees_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_bid_denoms(storage: &mut dyn Storage, bid_denoms: &[String]) -> StdResult<()> {
+    singleton(storage, KEY_BID_DENOMS).save(&bid_denoms.to_vec())
+}
+
+/// Denoms accepted for bids besides the stable denom
+pub fn read_bid_denoms(storage: &dyn Storage) -> StdResult<Vec<String>> {
+    Ok(singleton_read(storage, KEY_BID_DENOMS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_sniping_protection(storage: &mut dyn Storage, enabled: bool) -> StdResult<()> {
+    singleton(storage, KEY_SNIPING_PROTECTION).save(&enabled)
+}
+
+pub fn read_sniping_protection(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(singleton_read(storage, KEY_SNIPING_PROTECTION)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeSplit {
+    pub insurance_fund: CanonicalAddr,
+    pub insurance_share: Decimal256,
+    pub caller_share: Decimal256,
+}
+
+pub fn store_fee_split(storage: &mut dyn Storage, fee_split: Option<FeeSplit>) -> StdResult<()> {
+    match fee_split {
+        Some(fee_split) => singleton(storage, KEY_FEE_SPLIT).save(&fee_split),
+        None => {
+            singleton::<FeeSplit>(storage, KEY_FEE_SPLIT).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_fee_split(storage: &dyn Storage) -> StdResult<Option<FeeSplit>> {
+    singleton_read(storage, KEY_FEE_SPLIT).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FeeStats {
+    pub yield_reserve: Uint256,
+    pub insurance_fund: Uint256,
+    pub caller: Uint256,
+}
+
+pub fn store_fee_stats(
+    storage: &mut dyn Storage,
+    bid_denom: &str,
+    fee_stats: &FeeStats,
+) -> StdResult<()> {
+    let mut fee_stats_bucket: Bucket<FeeStats> = Bucket::new(storage, PREFIX_FEE_STATS);
+    fee_stats_bucket.save(bid_denom.as_bytes(), fee_stats)
+}
+
+pub fn read_fee_stats(storage: &dyn Storage, bid_denom: &str) -> StdResult<FeeStats> {
+    let fee_stats_bucket: ReadonlyBucket<FeeStats> = ReadonlyBucket::new(storage, PREFIX_FEE_STATS);
+    Ok(fee_stats_bucket
+        .may_load(bid_denom.as_bytes())?
+        .unwrap_or_default())
+}
+
 pub fn pop_bid_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
     let mut idx_store = singleton(storage, KEY_BID_IDX);
     let last_idx: Uint128 = idx_store.load().unwrap_or_else(|_| Uint128::from(1u128));
@@ -50,11 +200,14 @@ pub fn pop_bid_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
 pub fn store_total_bids(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     total_bids: Uint256,
 ) -> StdResult<()> {
-    let mut total_bids_bucket: Bucket<Uint256> =
-        Bucket::new(storage, PREFIX_TOTAL_BIDS_BY_COLLATERAL);
-    total_bids_bucket.save(collateral_token.as_slice(), &total_bids)?;
+    let mut total_bids_bucket: Bucket<Uint256> = Bucket::multilevel(
+        storage,
+        &[PREFIX_TOTAL_BIDS_BY_COLLATERAL, collateral_token.as_slice()],
+    );
+    total_bids_bucket.save(bid_denom.as_bytes(), &total_bids)?;
 
     Ok(())
 }
@@ -62,49 +215,52 @@ pub fn store_total_bids(
 pub fn read_total_bids(
     storage: &dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
 ) -> StdResult<Uint256> {
-    let total_bids_bucket: ReadonlyBucket<Uint256> =
-        ReadonlyBucket::new(storage, PREFIX_TOTAL_BIDS_BY_COLLATERAL);
-    total_bids_bucket.load(collateral_token.as_slice())
+    let total_bids_bucket: ReadonlyBucket<Uint256> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_TOTAL_BIDS_BY_COLLATERAL, collateral_token.as_slice()],
+    );
+    total_bids_bucket.load(bid_denom.as_bytes())
 }
 
-pub fn store_epoch_scale_sum(
+pub fn store_epoch_sum(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     premium_slot: u8,
     epoch: Uint128,
-    scale: Uint128,
     sum: Decimal256,
 ) -> StdResult<()> {
-    let mut epoch_scale_sum: Bucket<Decimal256> = Bucket::multilevel(
+    let mut epoch_sum: Bucket<Decimal256> = Bucket::multilevel(
         storage,
         &[
-            PREFIX_EPOCH_SCALE_SUM,
+            PREFIX_EPOCH_SUM,
             collateral_token.as_slice(),
+            bid_denom.as_bytes(),
             &premium_slot.to_be_bytes(),
-            &epoch.u128().to_be_bytes(),
         ],
     );
-    epoch_scale_sum.save(&scale.u128().to_be_bytes(), &sum)
+    epoch_sum.save(&epoch.u128().to_be_bytes(), &sum)
 }
 
-pub fn read_epoch_scale_sum(
+pub fn read_epoch_sum(
     storage: &dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     premium_slot: u8,
     epoch: Uint128,
-    scale: Uint128,
 ) -> StdResult<Decimal256> {
-    let epoch_scale_sum: ReadonlyBucket<Decimal256> = ReadonlyBucket::multilevel(
+    let epoch_sum: ReadonlyBucket<Decimal256> = ReadonlyBucket::multilevel(
         storage,
         &[
-            PREFIX_EPOCH_SCALE_SUM,
+            PREFIX_EPOCH_SUM,
             collateral_token.as_slice(),
+            bid_denom.as_bytes(),
             &premium_slot.to_be_bytes(),
-            &epoch.u128().to_be_bytes(),
         ],
     );
-    epoch_scale_sum.load(&scale.u128().to_be_bytes())
+    epoch_sum.load(&epoch.u128().to_be_bytes())
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -135,17 +291,203 @@ pub fn read_collateral_info(
         ReadonlyBucket::new(storage, PREFIX_COLLATERAL_INFO);
     collateral_info_bucket
         .load(collateral_token.as_slice())
-        .map_err(|_| StdError::generic_err("Collateral is not whitelisted"))
+        .map_err(|_| ContractError::CollateralNotWhitelisted {}.into())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionConfig {
+    pub initial_discount: Decimal256,
+    pub decay_period: u64,
+    pub expiry_period: u64,
+}
+
+pub fn store_auction_config(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    auction_config: Option<AuctionConfig>,
+) -> StdResult<()> {
+    let mut auction_config_bucket: Bucket<AuctionConfig> =
+        Bucket::new(storage, PREFIX_AUCTION_CONFIG);
+    match auction_config {
+        Some(auction_config) => {
+            auction_config_bucket.save(collateral_token.as_slice(), &auction_config)
+        }
+        None => {
+            auction_config_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_auction_config(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<AuctionConfig>> {
+    let auction_config_bucket: ReadonlyBucket<AuctionConfig> =
+        ReadonlyBucket::new(storage, PREFIX_AUCTION_CONFIG);
+    auction_config_bucket.may_load(collateral_token.as_slice())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderLimits {
+    pub max_bids: u32,
+    pub max_bid_amount: Uint256,
+}
+
+pub fn store_bidder_limits(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    bidder_limits: Option<BidderLimits>,
+) -> StdResult<()> {
+    let mut bidder_limits_bucket: Bucket<BidderLimits> = Bucket::new(storage, PREFIX_BIDDER_LIMITS);
+    match bidder_limits {
+        Some(bidder_limits) => {
+            bidder_limits_bucket.save(collateral_token.as_slice(), &bidder_limits)
+        }
+        None => {
+            bidder_limits_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_bidder_limits(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<BidderLimits>> {
+    let bidder_limits_bucket: ReadonlyBucket<BidderLimits> =
+        ReadonlyBucket::new(storage, PREFIX_BIDDER_LIMITS);
+    bidder_limits_bucket.may_load(collateral_token.as_slice())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub idx: Uint128,
+    pub collateral_token: CanonicalAddr,
+    pub borrower: CanonicalAddr,
+    pub amount: Uint256,
+    pub start_time: u64,
+    pub initial_discount: Decimal256,
+    pub decay_period: u64,
+    pub expiry_period: u64,
+    pub liquidator: CanonicalAddr,
+    pub repay_address: CanonicalAddr,
+    pub fee_address: CanonicalAddr,
+}
+
+pub fn pop_auction_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
+    let mut idx_store = singleton(storage, KEY_AUCTION_IDX);
+    let last_idx: Uint128 = idx_store.load().unwrap_or_else(|_| Uint128::from(1u128));
+    idx_store.save(&(last_idx + Uint128::from(1u128)))?;
+    Ok(last_idx)
+}
+
+pub fn store_auction(storage: &mut dyn Storage, auction: &Auction) -> StdResult<()> {
+    let mut auction_bucket: Bucket<Auction> = Bucket::new(storage, PREFIX_AUCTION);
+    auction_bucket.save(&auction.idx.u128().to_be_bytes(), auction)?;
+
+    let mut auction_indexer_by_collateral: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUCTION_BY_COLLATERAL,
+            auction.collateral_token.as_slice(),
+        ],
+    );
+    auction_indexer_by_collateral.save(&auction.idx.u128().to_be_bytes(), &true)?;
+
+    let mut auction_indexer_by_borrower: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[PREFIX_AUCTION_BY_BORROWER, auction.borrower.as_slice()],
+    );
+    auction_indexer_by_borrower.save(&auction.idx.u128().to_be_bytes(), &true)?;
+
+    Ok(())
+}
+
+pub fn remove_auction(storage: &mut dyn Storage, auction: &Auction) {
+    let mut auction_bucket: Bucket<Auction> = Bucket::new(storage, PREFIX_AUCTION);
+    auction_bucket.remove(&auction.idx.u128().to_be_bytes());
+
+    // remove indexer
+    let mut auction_indexer_by_collateral: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUCTION_BY_COLLATERAL,
+            auction.collateral_token.as_slice(),
+        ],
+    );
+    auction_indexer_by_collateral.remove(&auction.idx.u128().to_be_bytes());
+
+    let mut auction_indexer_by_borrower: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[PREFIX_AUCTION_BY_BORROWER, auction.borrower.as_slice()],
+    );
+    auction_indexer_by_borrower.remove(&auction.idx.u128().to_be_bytes());
+}
+
+pub fn read_auction(storage: &dyn Storage, auction_idx: Uint128) -> StdResult<Auction> {
+    let auction_bucket: ReadonlyBucket<Auction> = ReadonlyBucket::new(storage, PREFIX_AUCTION);
+    auction_bucket
+        .load(&auction_idx.u128().to_be_bytes())
+        .map_err(|_| ContractError::NoAuctionExists {}.into())
+}
+
+pub fn read_auctions_by_collateral(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<Auction>> {
+    let auction_indexer_by_collateral: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_AUCTION_BY_COLLATERAL, collateral_token.as_slice()],
+    );
+
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
+
+    auction_indexer_by_collateral
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            read_auction(storage, Uint128::from(bytes_to_u128(&k)?))
+        })
+        .collect()
+}
+
+pub fn read_auctions_by_borrower(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<Auction>> {
+    let auction_indexer_by_borrower: ReadonlyBucket<bool> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_AUCTION_BY_BORROWER, borrower.as_slice()]);
+
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
+
+    auction_indexer_by_borrower
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            read_auction(storage, Uint128::from(bytes_to_u128(&k)?))
+        })
+        .collect()
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BidPool {
+    /// Liquidated collateral per share accumulated in the current epoch
     pub sum_snapshot: Decimal256,
-    pub product_snapshot: Decimal256,
+    /// Shares of the bids in the current epoch, each worth
+    /// total_bid_amount / total_share of stable
+    pub total_share: Decimal256,
     pub total_bid_amount: Uint256,
     pub premium_rate: Decimal256,
     pub current_epoch: Uint128,
-    pub current_scale: Uint128,
     pub residue_collateral: Decimal256,
     pub residue_bid: Decimal256,
 }
@@ -153,12 +495,17 @@ pub struct BidPool {
 pub fn store_bid_pool(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     premium_slot: u8,
     bid_pool: &BidPool,
 ) -> StdResult<()> {
     let mut bid_pool_bucket: Bucket<BidPool> = Bucket::multilevel(
         storage,
-        &[PREFIX_BID_POOL_BY_COLLATERAL, collateral_token.as_slice()],
+        &[
+            PREFIX_BID_POOL_BY_COLLATERAL,
+            collateral_token.as_slice(),
+            bid_denom.as_bytes(),
+        ],
     );
     bid_pool_bucket.save(&premium_slot.to_be_bytes(), bid_pool)
 }
@@ -166,20 +513,26 @@ pub fn store_bid_pool(
 pub fn read_bid_pool(
     storage: &dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     premium_slot: u8,
 ) -> StdResult<BidPool> {
     let bid_pool_bucket: ReadonlyBucket<BidPool> = ReadonlyBucket::multilevel(
         storage,
-        &[PREFIX_BID_POOL_BY_COLLATERAL, collateral_token.as_slice()],
+        &[
+            PREFIX_BID_POOL_BY_COLLATERAL,
+            collateral_token.as_slice(),
+            bid_denom.as_bytes(),
+        ],
     );
     bid_pool_bucket
         .load(&premium_slot.to_be_bytes())
-        .map_err(|_| StdError::generic_err("Bid pool not found"))
+        .map_err(|_| ContractError::NoBidPoolExists {}.into())
 }
 
 pub fn read_or_create_bid_pool(
     storage: &mut dyn Storage,
     collateral_info: &CollateralInfo,
+    bid_denom: &str,
     premium_slot: u8,
 ) -> StdResult<BidPool> {
     let bid_pool_bucket: ReadonlyBucket<BidPool> = ReadonlyBucket::multilevel(
@@ -187,6 +540,7 @@ pub fn read_or_create_bid_pool(
         &[
             PREFIX_BID_POOL_BY_COLLATERAL,
             collateral_info.collateral_token.as_slice(),
+            bid_denom.as_bytes(),
         ],
     );
     match bid_pool_bucket.load(&premium_slot.to_be_bytes()) {
@@ -194,25 +548,25 @@ pub fn read_or_create_bid_pool(
         Err(_) => {
             if (0..collateral_info.max_slot + 1).contains(&premium_slot) {
                 let bid_pool = BidPool {
-                    product_snapshot: Decimal256::one(),
                     sum_snapshot: Decimal256::zero(),
+                    total_share: Decimal256::zero(),
                     total_bid_amount: Uint256::zero(),
                     premium_rate: collateral_info.premium_rate_per_slot
                         * Decimal256::from_uint256(Uint256::from(premium_slot as u128)),
                     current_epoch: Uint128::zero(),
-                    current_scale: Uint128::zero(),
                     residue_collateral: Decimal256::zero(),
                     residue_bid: Decimal256::zero(),
                 };
                 store_bid_pool(
                     storage,
                     &collateral_info.collateral_token,
+                    bid_denom,
                     premium_slot,
                     &bid_pool,
                 )?;
                 Ok(bid_pool)
             } else {
-                Err(StdError::generic_err("Invalid premium slot"))
+                Err(ContractError::InvalidPremiumSlot {}.into())
             }
         }
     }
@@ -221,15 +575,20 @@ pub fn read_or_create_bid_pool(
 pub fn read_bid_pools(
     storage: &dyn Storage,
     collateral_token: &CanonicalAddr,
+    bid_denom: &str,
     start_after: Option<u8>,
     limit: Option<u8>,
 ) -> StdResult<Vec<BidPool>> {
     let bid_pool_bucket: ReadonlyBucket<BidPool> = ReadonlyBucket::multilevel(
         storage,
-        &[PREFIX_BID_POOL_BY_COLLATERAL, collateral_token.as_slice()],
+        &[
+            PREFIX_BID_POOL_BY_COLLATERAL,
+            collateral_token.as_slice(),
+            bid_denom.as_bytes(),
+        ],
     );
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
     let start = calc_range_start(start_after);
 
     bid_pool_bucket
@@ -248,13 +607,16 @@ pub struct Bid {
     pub collateral_token: CanonicalAddr,
     pub premium_slot: u8,
     pub bidder: CanonicalAddr,
+    pub bid_denom: String,
     pub amount: Uint256,
-    pub product_snapshot: Decimal256,
+    /// Share of the bid pool, zero until the bid is activated
+    pub share: Decimal256,
     pub sum_snapshot: Decimal256,
     pub pending_liquidated_collateral: Uint256,
     pub wait_end: Option<u64>,
     pub epoch_snapshot: Uint128,
-    pub scale_snapshot: Uint128,
+    /// Last time the bid was submitted, activated, retracted or claimed
+    pub updated_at: u64,
 }
 
 pub fn store_bid(storage: &mut dyn Storage, bid_idx: Uint128, bid: &Bid) -> StdResult<()> {
@@ -271,6 +633,10 @@ pub fn store_bid(storage: &mut dyn Storage, bid_idx: Uint128, bid: &Bid) -> StdR
     );
     bid_indexer_by_user.save(&bid_idx.u128().to_be_bytes(), &true)?;
 
+    let mut bid_indexer_by_bidder: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_BID_BY_BIDDER, bid.bidder.as_slice()]);
+    bid_indexer_by_bidder.save(&bid_idx.u128().to_be_bytes(), &true)?;
+
     Ok(())
 }
 
@@ -290,14 +656,401 @@ pub fn remove_bid(storage: &mut dyn Storage, bid_idx: Uint128) -> StdResult<()>
     );
     bid_indexer_by_user.remove(&bid_idx.u128().to_be_bytes());
 
+    let mut bid_indexer_by_bidder: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_BID_BY_BIDDER, bid.bidder.as_slice()]);
+    bid_indexer_by_bidder.remove(&bid_idx.u128().to_be_bytes());
+
+    remove_index(
+        storage,
+        &[PREFIX_BID_REWARD_INDEX],
+        &bid_idx.u128().to_be_bytes(),
+    );
+
+    store_bid_top_up(storage, &bid, None)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopUpConfig {
+    pub market_contract: CanonicalAddr,
+    pub aterra_contract: CanonicalAddr,
+}
+
+pub fn store_top_up_config(
+    storage: &mut dyn Storage,
+    top_up_config: Option<TopUpConfig>,
+) -> StdResult<()> {
+    match top_up_config {
+        Some(top_up_config) => singleton(storage, KEY_TOP_UP_CONFIG).save(&top_up_config),
+        None => {
+            singleton::<TopUpConfig>(storage, KEY_TOP_UP_CONFIG).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_top_up_config(storage: &dyn Storage) -> StdResult<Option<TopUpConfig>> {
+    singleton_read(storage, KEY_TOP_UP_CONFIG).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidExpiry {
+    pub expiry_period: u64,
+    pub keeper_reward: Decimal256,
+}
+
+pub fn store_bid_expiry(storage: &mut dyn Storage, bid_expiry: Option<BidExpiry>) -> StdResult<()> {
+    match bid_expiry {
+        Some(bid_expiry) => singleton(storage, KEY_BID_EXPIRY).save(&bid_expiry),
+        None => {
+            singleton::<BidExpiry>(storage, KEY_BID_EXPIRY).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_bid_expiry(storage: &dyn Storage) -> StdResult<Option<BidExpiry>> {
+    singleton_read(storage, KEY_BID_EXPIRY).may_load()
+}
+
+pub fn store_swap_router(
+    storage: &mut dyn Storage,
+    swap_router: Option<SwapRoute>,
+) -> StdResult<()> {
+    match swap_router {
+        Some(swap_router) => singleton(storage, KEY_SWAP_ROUTER).save(&swap_router),
+        None => {
+            singleton::<SwapRoute>(storage, KEY_SWAP_ROUTER).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_swap_router(storage: &dyn Storage) -> StdResult<Option<SwapRoute>> {
+    singleton_read(storage, KEY_SWAP_ROUTER).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationLog {
+    pub idx: Uint128,
+    pub height: u64,
+    pub borrower: Option<CanonicalAddr>,
+    pub collateral_token: CanonicalAddr,
+    pub collateral_amount: Uint256,
+    pub premium_rate: Decimal256,
+    pub repay_denom: String,
+    pub repay_amount: Uint256,
+    pub liquidator: CanonicalAddr,
+}
+
+pub fn pop_liquidation_log_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
+    let mut idx_store = singleton(storage, KEY_LIQUIDATION_LOG_IDX);
+    let last_idx: Uint128 = idx_store.load().unwrap_or_else(|_| Uint128::from(1u128));
+    idx_store.save(&(last_idx + Uint128::from(1u128)))?;
+    Ok(last_idx)
+}
+
+/// Stores the log and drops the oldest entries of the global
+/// and borrower logs above their max size
+pub fn store_liquidation_log(storage: &mut dyn Storage, log: &LiquidationLog) -> StdResult<()> {
+    let mut log_bucket: Bucket<LiquidationLog> = Bucket::new(storage, PREFIX_LIQUIDATION_LOG);
+    log_bucket.save(&log.idx.u128().to_be_bytes(), log)?;
+    if log.idx.u128() > MAX_LIQUIDATION_LOGS {
+        log_bucket.remove(&(log.idx.u128() - MAX_LIQUIDATION_LOGS).to_be_bytes());
+    }
+
+    if let Some(borrower) = &log.borrower {
+        let mut borrower_log_bucket: Bucket<LiquidationLog> = Bucket::multilevel(
+            storage,
+            &[PREFIX_LIQUIDATION_LOG_BY_BORROWER, borrower.as_slice()],
+        );
+        borrower_log_bucket.save(&log.idx.u128().to_be_bytes(), log)?;
+
+        let keys: Vec<Vec<u8>> = borrower_log_bucket
+            .range(None, None, Order::Descending)
+            .skip(MAX_BORROWER_LIQUIDATION_LOGS)
+            .map(|elem| Ok(elem?.0))
+            .collect::<StdResult<Vec<Vec<u8>>>>()?;
+        for key in keys.iter() {
+            borrower_log_bucket.remove(key);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_liquidation_logs(
+    storage: &dyn Storage,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<LiquidationLog>> {
+    let log_bucket: ReadonlyBucket<LiquidationLog> =
+        ReadonlyBucket::new(storage, PREFIX_LIQUIDATION_LOG);
+
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
+
+    log_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (_, log) = elem?;
+            Ok(log)
+        })
+        .collect()
+}
+
+pub fn read_liquidation_logs_by_borrower(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<LiquidationLog>> {
+    let borrower_log_bucket: ReadonlyBucket<LiquidationLog> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_LIQUIDATION_LOG_BY_BORROWER, borrower.as_slice()],
+    );
+
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
+
+    borrower_log_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (_, log) = elem?;
+            Ok(log)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidTopUp {
+    /// aTokens redeemed on each refill
+    pub refill_amount: Uint256,
+    /// aTokens left to redeem
+    pub remaining_cap: Uint256,
+}
+
+pub fn store_bid_top_up(
+    storage: &mut dyn Storage,
+    bid: &Bid,
+    top_up: Option<BidTopUp>,
+) -> StdResult<()> {
+    let mut top_up_bucket: Bucket<BidTopUp> = Bucket::new(storage, PREFIX_BID_TOP_UP);
+    match &top_up {
+        Some(top_up) => top_up_bucket.save(&bid.idx.u128().to_be_bytes(), top_up)?,
+        None => top_up_bucket.remove(&bid.idx.u128().to_be_bytes()),
+    }
+
+    let mut top_up_indexer_by_pool: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_BID_TOP_UP_BY_POOL,
+            bid.collateral_token.as_slice(),
+            &bid.premium_slot.to_be_bytes(),
+        ],
+    );
+    match top_up {
+        Some(_) => top_up_indexer_by_pool.save(&bid.idx.u128().to_be_bytes(), &true)?,
+        None => top_up_indexer_by_pool.remove(&bid.idx.u128().to_be_bytes()),
+    }
+
     Ok(())
 }
 
+pub fn read_bid_top_up(storage: &dyn Storage, bid_idx: Uint128) -> StdResult<Option<BidTopUp>> {
+    let top_up_bucket: ReadonlyBucket<BidTopUp> = ReadonlyBucket::new(storage, PREFIX_BID_TOP_UP);
+    top_up_bucket.may_load(&bid_idx.u128().to_be_bytes())
+}
+
+/// Index of the bids with a top-up in the stable denom pool, ordered by bid index
+pub fn read_bid_top_ups_by_pool(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    limit: u8,
+) -> StdResult<Vec<Uint128>> {
+    let top_up_indexer_by_pool: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_BID_TOP_UP_BY_POOL,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+    );
+
+    top_up_indexer_by_pool
+        .range(None, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|elem| {
+            let (k, _) = elem?;
+            Ok(Uint128::from(bytes_to_u128(&k)?))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidIncentives {
+    pub reward_token: CanonicalAddr,
+    pub emission_rate: Uint256,
+}
+
+/// The reward token of the collateral is kept once the incentives are
+/// removed, so the rewards earned until then can still be claimed
+pub fn store_bid_incentives(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    bid_incentives: Option<BidIncentives>,
+) -> StdResult<()> {
+    let mut bid_incentives_bucket: Bucket<BidIncentives> =
+        Bucket::new(storage, PREFIX_BID_INCENTIVES);
+    match bid_incentives {
+        Some(bid_incentives) => {
+            bid_incentives_bucket.save(collateral_token.as_slice(), &bid_incentives)?;
+
+            let mut reward_token_bucket: Bucket<CanonicalAddr> =
+                Bucket::new(storage, PREFIX_BID_REWARD_TOKEN);
+            reward_token_bucket.save(collateral_token.as_slice(), &bid_incentives.reward_token)
+        }
+        None => {
+            bid_incentives_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_bid_incentives(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<BidIncentives>> {
+    let bid_incentives_bucket: ReadonlyBucket<BidIncentives> =
+        ReadonlyBucket::new(storage, PREFIX_BID_INCENTIVES);
+    bid_incentives_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn read_bid_reward_token(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<CanonicalAddr>> {
+    let reward_token_bucket: ReadonlyBucket<CanonicalAddr> =
+        ReadonlyBucket::new(storage, PREFIX_BID_REWARD_TOKEN);
+    reward_token_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_pool_reward_time(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    time: u64,
+) -> StdResult<()> {
+    let mut pool_reward_time: Bucket<u64> = Bucket::multilevel(
+        storage,
+        &[PREFIX_POOL_REWARD_TIME, collateral_token.as_slice()],
+    );
+    pool_reward_time.save(&premium_slot.to_be_bytes(), &time)
+}
+
+pub fn read_pool_reward_time(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<Option<u64>> {
+    let pool_reward_time: ReadonlyBucket<u64> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_POOL_REWARD_TIME, collateral_token.as_slice()],
+    );
+    pool_reward_time.may_load(&premium_slot.to_be_bytes())
+}
+
+pub fn store_epoch_reward_index(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    epoch: Uint128,
+    reward_index: Decimal256,
+) -> StdResult<()> {
+    store_index(
+        storage,
+        &[
+            PREFIX_EPOCH_REWARD_INDEX,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+        &epoch.u128().to_be_bytes(),
+        reward_index,
+    )
+}
+
+pub fn read_epoch_reward_index(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    epoch: Uint128,
+) -> StdResult<Decimal256> {
+    read_index(
+        storage,
+        &[
+            PREFIX_EPOCH_REWARD_INDEX,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+        &epoch.u128().to_be_bytes(),
+    )
+}
+
+pub fn store_bid_reward_index(
+    storage: &mut dyn Storage,
+    bid_idx: Uint128,
+    reward_index: Decimal256,
+) -> StdResult<()> {
+    store_index(
+        storage,
+        &[PREFIX_BID_REWARD_INDEX],
+        &bid_idx.u128().to_be_bytes(),
+        reward_index,
+    )
+}
+
+pub fn read_bid_reward_index(storage: &dyn Storage, bid_idx: Uint128) -> StdResult<Decimal256> {
+    read_index(
+        storage,
+        &[PREFIX_BID_REWARD_INDEX],
+        &bid_idx.u128().to_be_bytes(),
+    )
+}
+
+pub fn store_bidder_rewards(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    bidder: &CanonicalAddr,
+    pending_rewards: Uint256,
+) -> StdResult<()> {
+    let mut bidder_rewards: Bucket<Uint256> = Bucket::multilevel(
+        storage,
+        &[PREFIX_BIDDER_REWARDS, collateral_token.as_slice()],
+    );
+    bidder_rewards.save(bidder.as_slice(), &pending_rewards)
+}
+
+pub fn read_bidder_rewards(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    bidder: &CanonicalAddr,
+) -> StdResult<Uint256> {
+    let bidder_rewards: ReadonlyBucket<Uint256> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_BIDDER_REWARDS, collateral_token.as_slice()],
+    );
+    Ok(bidder_rewards
+        .may_load(bidder.as_slice())?
+        .unwrap_or_default())
+}
+
 pub fn read_bid(storage: &dyn Storage, bid_idx: Uint128) -> StdResult<Bid> {
     let bid_bucket: ReadonlyBucket<Bid> = ReadonlyBucket::new(storage, PREFIX_BID);
     bid_bucket
         .load(&bid_idx.u128().to_be_bytes())
-        .map_err(|_| StdError::generic_err("No bids with the specified information exist"))
+        .map_err(|_| ContractError::NoBidExists {}.into())
 }
 
 pub fn read_bids_by_user(
@@ -316,8 +1069,8 @@ pub fn read_bids_by_user(
         ],
     );
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start_idx(start_after);
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
 
     bid_user_index
         .range(start.as_deref(), None, Order::Ascending)
@@ -329,29 +1082,53 @@ pub fn read_bids_by_user(
         .collect()
 }
 
-fn bytes_to_u128(data: &[u8]) -> StdResult<u128> {
-    match data[0..16].try_into() {
-        Ok(bytes) => Ok(u128::from_be_bytes(bytes)),
-        Err(_) => Err(StdError::generic_err(
-            "Corrupted data found. 16 byte expected.",
-        )),
-    }
+/// All bids, ordered by bid index
+pub fn read_bids(
+    storage: &dyn Storage,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<Bid>> {
+    let bid_bucket: ReadonlyBucket<Bid> = ReadonlyBucket::new(storage, PREFIX_BID);
+
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
+
+    bid_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (_, bid) = elem?;
+            Ok(bid)
+        })
+        .collect()
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start_idx(start_after: Option<Uint128>) -> Option<Vec<u8>> {
-    start_after.map(|idx| {
-        let mut v = idx.u128().to_be_bytes().to_vec();
-        v.push(1);
-        v
-    })
+/// Bids of the bidder across all collateral tokens, ordered by bid index
+pub fn read_bids_by_bidder(
+    storage: &dyn Storage,
+    bidder: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<Bid>> {
+    let bid_bidder_index: ReadonlyBucket<bool> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_BID_BY_BIDDER, bidder.as_slice()]);
+
+    let limit = calc_limit_with_max(limit.map(u32::from), MAX_LIMIT);
+    let start = calc_range_start(start_after);
+
+    bid_bidder_index
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            read_bid(storage, Uint128::from(bytes_to_u128(&k)?))
+        })
+        .collect()
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<u8>) -> Option<Vec<u8>> {
-    start_after.map(|id| {
-        let mut v = id.to_be_bytes().to_vec();
-        v.push(1);
-        v
-    })
+fn bytes_to_u128(data: &[u8]) -> StdResult<u128> {
+    match data[0..16].try_into() {
+        Ok(bytes) => Ok(u128::from_be_bytes(bytes)),
+        Err(_) => Err(ContractError::CorruptedData {}.into()),
+    }
 }
@@ -0,0 +1,218 @@
+use crate::bid::{assert_custody_contract, assert_not_frozen};
+use crate::error::ContractError;
+use crate::fee::split_bid_fee;
+use crate::state::{
+    read_collateral_info, read_config, read_config_for_collateral, read_swap_router,
+    store_swap_router, CollateralInfo, Config,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, WasmMsg,
+};
+use moneymarket::liquidation_queue::{ExecuteMsg, SwapRouterResponse};
+use moneymarket::oracle::PriceResponse;
+use moneymarket::querier::{deduct_tax, query_balance, query_price, TimeConstraints};
+use moneymarket::swap::SwapRoute;
+use terra_cosmwasm::TerraMsgWrapper;
+use terraswap::asset::AssetInfo;
+
+pub fn update_swap_router(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap_router: Option<SwapRoute>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let swap_router = match swap_router {
+        Some(swap_router) => {
+            // the collateral is a cw20 token, which the market module can not swap
+            if swap_router.router().is_none() {
+                return Err(ContractError::InvalidSwapRoute {});
+            }
+            Some(swap_router.validate(deps.api)?)
+        }
+        None => None,
+    };
+    store_swap_router(deps.storage, swap_router.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_swap_router"),
+        attr(
+            "swap_router",
+            swap_router
+                .as_ref()
+                .and_then(SwapRoute::router)
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Premium of the highest bid slot of the collateral
+pub(crate) fn swap_discount(collateral_info: &CollateralInfo) -> Decimal256 {
+    collateral_info.premium_rate_per_slot
+        * Decimal256::from_uint256(Uint256::from(collateral_info.max_slot as u128))
+}
+
+/// The collateral is sold on the swap router for the stable denom, which must
+/// cover the repayment and the fees of a bid in the highest premium slot, and
+/// the oracle price minus the max spread. The swap proceeds are split once the
+/// swap is executed
+#[allow(clippy::too_many_arguments)]
+pub fn execute_liquidation_with_swap(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    liquidator: String,
+    repay_address: String,
+    fee_address: String,
+    max_spread: Decimal256,
+    collateral_token: String,
+    amount: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let config: Config = read_config_for_collateral(deps.storage, &collateral_token_raw)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    assert_custody_contract(deps.as_ref(), &config, &sender, &collateral_token)?;
+    assert_not_frozen(deps.as_ref(), &config)?;
+
+    let swap_router = read_swap_router(deps.storage)?.ok_or(ContractError::SwapNotEnabled {})?;
+    if max_spread >= Decimal256::one() {
+        return Err(ContractError::InvalidMaxSpread {});
+    }
+
+    let price: PriceResponse = query_price(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.clone(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let collateral_value = amount * price.rate;
+    let bid_amount = collateral_value * (Decimal256::one() - swap_discount(&collateral_info));
+    let bid_fee = bid_amount * config.bid_fee;
+    let liquidator_fee = bid_amount * config.liquidator_fee;
+    let repay_amount = bid_amount - bid_fee - liquidator_fee;
+    let minimum_receive = std::cmp::max(
+        repay_amount + bid_fee,
+        collateral_value * (Decimal256::one() - max_spread),
+    );
+
+    let prev_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom.clone(),
+    )?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            swap_router.swap_cw20_msg(
+                collateral_token.clone(),
+                amount.into(),
+                AssetInfo::NativeToken {
+                    denom: config.stable_denom,
+                },
+                Some(minimum_receive.into()),
+            )?,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RepayFromSwap {
+                    liquidator,
+                    repay_address,
+                    fee_address,
+                    repay_amount,
+                    bid_fee,
+                    prev_balance,
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "execute_bid_with_swap"),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", amount),
+            attr("minimum_receive", minimum_receive),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn repay_from_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    liquidator: String,
+    repay_address: String,
+    fee_address: String,
+    repay_amount: Uint256,
+    bid_fee: Uint256,
+    prev_balance: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address,
+        config.stable_denom.clone(),
+    )?;
+    let swap_amount = balance - prev_balance;
+    if swap_amount < repay_amount + bid_fee {
+        return Err(ContractError::InsufficientSwapAmount {});
+    }
+
+    let bid_fee_split = split_bid_fee(deps.storage, deps.api, &config.stable_denom, bid_fee)?;
+    let liquidator_amount = swap_amount - repay_amount - bid_fee + bid_fee_split.caller;
+
+    let mut transfers: Vec<(String, Uint256)> = vec![
+        (repay_address, repay_amount),
+        (fee_address, bid_fee_split.yield_reserve),
+        (liquidator, liquidator_amount),
+    ];
+    if let Some(insurance_fund) = bid_fee_split.insurance_fund.clone() {
+        transfers.push(insurance_fund);
+    }
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    for (recipient, amount) in transfers.into_iter() {
+        if !amount.is_zero() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient,
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: config.stable_denom.clone(),
+                        amount: amount.into(),
+                    },
+                )?],
+            }));
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "repay_from_swap"),
+            attr("swap_amount", swap_amount),
+            attr("repay_amount", repay_amount),
+            attr("bid_fee", bid_fee),
+            attr("liquidator_amount", liquidator_amount),
+        ])
+        .add_attributes(bid_fee_split.attributes()))
+}
+
+pub fn query_swap_router(deps: Deps) -> StdResult<SwapRouterResponse> {
+    Ok(SwapRouterResponse {
+        swap_router: read_swap_router(deps.storage)?,
+    })
+}
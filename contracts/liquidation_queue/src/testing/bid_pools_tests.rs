@@ -1,4 +1,5 @@
 use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -87,6 +88,8 @@ fn one_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -202,6 +205,8 @@ fn two_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -250,6 +255,8 @@ fn two_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -279,7 +286,9 @@ fn two_bidder_distribution() {
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         res,
-        StdError::generic_err("No bids with the specified information exist")
+        ContractError::Std(StdError::generic_err(
+            "No bids with the specified information exist"
+        ))
     );
 
     // BOB:
@@ -306,7 +315,9 @@ fn two_bidder_distribution() {
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         res,
-        StdError::generic_err("No bids with the specified information exist")
+        ContractError::Std(StdError::generic_err(
+            "No bids with the specified information exist"
+        ))
     );
 }
 
@@ -388,6 +399,8 @@ fn two_bidder_distribution_big_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -436,6 +449,8 @@ fn two_bidder_distribution_big_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -465,7 +480,9 @@ fn two_bidder_distribution_big_numbers() {
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         res,
-        StdError::generic_err("No bids with the specified information exist")
+        ContractError::Std(StdError::generic_err(
+            "No bids with the specified information exist"
+        ))
     );
 
     // BOB:
@@ -492,7 +509,9 @@ fn two_bidder_distribution_big_numbers() {
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         res,
-        StdError::generic_err("No bids with the specified information exist")
+        ContractError::Std(StdError::generic_err(
+            "No bids with the specified information exist"
+        ))
     );
 }
 
@@ -588,6 +607,8 @@ fn one_user_two_bid_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -618,6 +639,8 @@ fn one_user_two_bid_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -635,7 +658,7 @@ fn one_user_two_bid_slots() {
         vec![
             attr("action", "claim_liquidations"),
             attr("collateral_token", "col0000"),
-            attr("collateral_amount", "9999999"), // rounding, favors the system
+            attr("collateral_amount", "10000000"),
         ]
     );
 
@@ -740,6 +763,8 @@ fn partial_withdraw_after_execution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -784,6 +809,8 @@ fn partial_withdraw_after_execution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -823,7 +850,9 @@ fn partial_withdraw_after_execution() {
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         res,
-        StdError::generic_err("No bids with the specified information exist")
+        ContractError::Std(StdError::generic_err(
+            "No bids with the specified information exist"
+        ))
     );
 
     // BOB:
@@ -859,7 +888,9 @@ fn partial_withdraw_after_execution() {
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         res,
-        StdError::generic_err("No bids with the specified information exist")
+        ContractError::Std(StdError::generic_err(
+            "No bids with the specified information exist"
+        ))
     );
 }
 
@@ -930,6 +961,8 @@ fn completely_empty_pool() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -960,7 +993,7 @@ fn completely_empty_pool() {
         .unwrap(),
     )
     .unwrap();
-    assert!(!bid_response.product_snapshot.is_zero(),);
+    assert!(!bid_response.share.is_zero(),);
     assert!(bid_response.epoch_snapshot == Uint128::from(1u128)); // epoch increased
 
     let bid_pool: BidPoolResponse = from_binary(
@@ -979,12 +1012,11 @@ fn completely_empty_pool() {
     assert_eq!(
         bid_pool,
         BidPoolResponse {
-            sum_snapshot: Decimal256::zero(),    // reseted
-            product_snapshot: Decimal256::one(), // reseted
+            sum_snapshot: Decimal256::zero(), // reseted
+            total_share: Decimal256::from_uint256(Uint256::from(2000u128)),
             premium_rate: Decimal256::zero(),
             total_bid_amount: Uint256::from(2000u128), // only bob's bid
             current_epoch: Uint128::from(1u128),       // increased epoch
-            current_scale: Uint128::zero(),
         }
     );
 
@@ -997,6 +1029,8 @@ fn completely_empty_pool() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1085,7 +1119,7 @@ fn product_truncated_to_zero() {
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // force product to become zero
+    // force the share value below the min share value
     for _ in 0..8 {
         // ALICE BIDS 1000000000 uUST
         let msg = ExecuteMsg::SubmitBid {
@@ -1110,6 +1144,8 @@ fn product_truncated_to_zero() {
                 liquidator: "liquidator00000".to_string(),
                 fee_address: Some("fee0000".to_string()),
                 repay_address: Some("repay0000".to_string()),
+                borrower: None,
+                repay_denom: None,
             })
             .unwrap(),
         });
@@ -1128,7 +1164,7 @@ fn product_truncated_to_zero() {
         vec![
             attr("action", "claim_liquidations"),
             attr("collateral_token", "col0000"),
-            attr("collateral_amount", "7999999959"), // 999999995 * 8 = 7,999,999,960 missing 1ucol due to rounding and product resolution
+            attr("collateral_amount", "7999999960"), // 999999995 * 8 = 7,999,999,960
         ]
     );
 
@@ -1144,10 +1180,27 @@ fn product_truncated_to_zero() {
         .unwrap(),
     )
     .unwrap();
-    assert_eq!(bid_pool.total_bid_amount, Uint256::from(40u128)); // 5 * 8 = 40
+    // every second bid mints shares worth less than the min share value,
+    // closing the epoch with 10 uusd moved to the residue
+    assert_eq!(bid_pool.total_bid_amount, Uint256::zero());
+    assert_eq!(bid_pool.current_epoch, Uint128::from(4u128));
+
+    // the residue goes to the next bidder retracting from the pool
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "col0000".to_string(),
+        premium_slot: 0u8,
+    };
+    let bid_info = mock_info(
+        "alice0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), bid_info, msg).unwrap();
 
     let msg = ExecuteMsg::RetractBid {
-        bid_idx: Uint128::from(8u128), // only last bid is active, others are consumed
+        bid_idx: Uint128::from(9u128),
         amount: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1155,8 +1208,8 @@ fn product_truncated_to_zero() {
         res.attributes,
         vec![
             attr("action", "retract_bid"),
-            attr("bid_idx", "8"),
-            attr("amount", "39"), // 5 * 8 = 40 missing 1ucol due to rounding
+            attr("bid_idx", "9"),
+            attr("amount", "140"), // 100 + 5 * 8 residue
         ]
     );
 }
@@ -1269,6 +1322,8 @@ fn two_bidder_reward_distribution_common_slot() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1334,7 +1389,7 @@ fn two_bidder_reward_distribution_common_slot() {
         vec![
             attr("action", "retract_bid"),
             attr("bid_idx", "2"),
-            attr("amount", "53"),
+            attr("amount", "52"),
         ]
     );
 }
@@ -1501,6 +1556,8 @@ fn two_bidder_distribution_multiple_common_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1516,6 +1573,8 @@ fn two_bidder_distribution_multiple_common_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1538,7 +1597,7 @@ fn two_bidder_distribution_multiple_common_slots() {
         vec![
             attr("action", "claim_liquidations"),
             attr("collateral_token", "col0000"),
-            attr("collateral_amount", "15"),
+            attr("collateral_amount", "16"),
         ]
     );
 
@@ -1761,6 +1820,8 @@ fn two_bidder_unequal_deposit_reward_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1820,7 +1881,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
         vec![
             attr("action", "claim_liquidations"),
             attr("collateral_token", "col0000"),
-            attr("collateral_amount", "29"),
+            attr("collateral_amount", "30"), // 29 + 1 from the residue, 21 + 30 = 51
         ]
     );
     let msg = ExecuteMsg::RetractBid {
@@ -1833,7 +1894,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
         vec![
             attr("action", "retract_bid"),
             attr("bid_idx", "2"),
-            attr("amount", "143"),
+            attr("amount", "142"),
         ]
     );
 }
@@ -1976,6 +2037,8 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2049,6 +2112,8 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2198,14 +2263,13 @@ fn not_enough_bid_for_collateral() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    assert_eq!(
-        res,
-        StdError::generic_err("Not enough bids to execute this liquidation")
-    )
+    assert_eq!(res, ContractError::InsufficientBids {})
 }
 
 #[test]
@@ -2314,6 +2378,8 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1,4 +1,4 @@
-use moneymarket::overseer::{WhitelistResponse, WhitelistResponseElem};
+use moneymarket::overseer::{FreezeStateResponse, WhitelistResponse, WhitelistResponseElem};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -17,12 +17,16 @@ use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrap
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     /// Query oracle price to oracle contract
-    Price { base: String, quote: String },
+    Price {
+        base: String,
+        quote: String,
+    },
     Whitelist {
         collateral_token: Option<String>,
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    FreezeState {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -46,6 +50,7 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     oracle_price_querier: OraclePriceQuerier,
     collateral_querier: CollateralQuerier,
+    frozen: bool,
 }
 
 #[derive(Clone, Default)]
@@ -177,6 +182,8 @@ impl WasmMockQuerier {
                                 rate: v.0,
                                 last_updated_base: v.1,
                                 last_updated_quote: v.2,
+                                twap_rate: None,
+                                confidence: None,
                             })))
                         }
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -212,6 +219,14 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::FreezeState {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&FreezeStateResponse {
+                        guardian: None,
+                        unfreeze_delay: 0u64,
+                        frozen: self.frozen,
+                        frozen_at: None,
+                    })))
+                }
             },
             _ => self.base.handle_query(request),
         }
@@ -225,6 +240,7 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
             collateral_querier: CollateralQuerier::default(),
+            frozen: false,
         }
     }
 
@@ -244,4 +260,12 @@ impl WasmMockQuerier {
     pub fn with_collateral_max_ltv(&mut self, collaterals: &[(&String, &Decimal256)]) {
         self.collateral_querier = CollateralQuerier::new(collaterals);
     }
+
+    pub fn with_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    pub fn update_balance(&mut self, addr: &str, balance: Vec<Coin>) {
+        self.base.update_balance(addr, balance);
+    }
 }
@@ -3,4 +3,5 @@ mod mock_querier;
 mod product_stress_tests;
 mod query_liq_amount_tests;
 mod query_tests;
+mod share_accounting_tests;
 mod tests;
@@ -17,7 +17,7 @@ use super::mock_querier::WasmMockQuerier;
 const TOLERANCE: &str = "0.00001"; // 0.001%
 const ITERATIONS: u32 = 100u32;
 
-//#[test]
+#[test]
 fn stress_tests() {
     // submit bids and execute liquidations repeatedly
     // we can alternate larger and smaller executions to decrease the bid_pool product at different rates
@@ -175,6 +175,8 @@ fn simulate_bids_with_2_liq_amounts(
                     liquidator: "liquidator00000".to_string(),
                     fee_address: Some("fee0000".to_string()),
                     repay_address: Some("repay0000".to_string()),
+                    borrower: None,
+                    repay_denom: None,
                 })
                 .unwrap(),
             });
@@ -191,6 +193,8 @@ fn simulate_bids_with_2_liq_amounts(
                     liquidator: "liquidator00000".to_string(),
                     fee_address: Some("fee0000".to_string()),
                     repay_address: Some("repay0000".to_string()),
+                    borrower: None,
+                    repay_denom: None,
                 })
                 .unwrap(),
             });
@@ -209,7 +213,7 @@ fn simulate_bids_with_2_liq_amounts(
                 deps.as_ref(),
                 mock_env(),
                 QueryMsg::BidsByUser {
-                    collateral_token: "col0000".to_string(),
+                    collateral_token: Some("col0000".to_string()),
                     bidder: "alice0000".to_string(),
                     limit: Some(30u8),
                     start_after: Some(Uint128::from(queried_bids)),
@@ -231,7 +235,7 @@ fn simulate_bids_with_2_liq_amounts(
     }
     println!("total claimed:    {}", total_claimed);
     println!("total liquidated: {}", total_liquidated);
-    assert!(total_claimed < total_liquidated);
+    assert!(total_claimed <= total_liquidated);
 
     let error: Decimal256 = Decimal256::one()
         - Decimal256::from_uint256(total_claimed) / Decimal256::from_uint256(total_liquidated);
@@ -5,7 +5,7 @@ use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128};
 use cw20::Cw20ReceiveMsg;
 use moneymarket::liquidation_queue::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
+    AuctionConfig, Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
 };
 
 #[test]
@@ -97,6 +97,8 @@ fn partial_one_collateral_one_slot_high_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -203,6 +205,8 @@ fn partial_one_collateral_one_slot() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -309,6 +313,8 @@ fn partial_one_collateral_one_slot_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -424,6 +430,8 @@ fn partial_one_collateral_one_slot_with_fees_all() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -553,6 +561,8 @@ fn partial_one_collateral_two_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -666,6 +676,8 @@ fn partial_one_collateral_two_slots_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -774,6 +786,8 @@ fn non_partial_liquidation() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -878,6 +892,8 @@ fn non_partial_liquidation_two_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -977,6 +993,8 @@ fn non_partial_liquidation_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1090,6 +1108,8 @@ fn non_partial_liquidation_two_slots_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1203,6 +1223,8 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1352,6 +1374,8 @@ fn partial_two_collaterals_ltv_diff() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1376,6 +1400,8 @@ fn partial_two_collaterals_ltv_diff() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1527,6 +1553,8 @@ fn partial_two_collaterals_multi_slots_per_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1551,6 +1579,8 @@ fn partial_two_collaterals_multi_slots_per_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1692,6 +1722,8 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1716,6 +1748,8 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1886,6 +1920,8 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1910,6 +1946,8 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -1934,6 +1972,8 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2110,6 +2150,8 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2134,6 +2176,8 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2158,6 +2202,8 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2334,6 +2380,8 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2358,6 +2406,8 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2382,6 +2432,8 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2530,6 +2582,8 @@ fn not_enough_bids_for_one_of_two_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2563,6 +2617,8 @@ fn not_enough_bids_for_one_of_two_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2697,6 +2753,8 @@ fn integration_test_simul() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -2714,3 +2772,73 @@ fn integration_test_simul() {
         }))]
     );
 }
+
+#[test]
+fn partial_one_collateral_auction() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(0),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(50))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(80),
+        bid_fee: Decimal256::percent(0),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::zero(),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAuctionConfig {
+        collateral_token: "token0000".to_string(),
+        auction_config: Some(AuctionConfig {
+            initial_discount: Decimal256::percent(10),
+            decay_period: 3600u64,
+            expiry_period: 3600u64,
+        }),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // no bids are needed, the amount is sized on the initial discount
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(10500u64),
+        borrow_limit: Uint256::from(10000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(20000u64))], // value 20000 (LTV 50%), limit = 10,000
+        collateral_prices: vec![Decimal256::percent(100)],
+    };
+
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(5003u64))],
+        }
+    );
+
+    // 5003 col liq, sold at 0.9 at worst = 4,502
+    // remaining = 20000 - 5003 = 14,997
+    // new limit = 14,997 * 1 * 0.5 = 7,498
+    // safe = 7,498 * 0.8 = 5,998 **
+
+    // new borrow amount = 10500 - 4502 = 5,998 **
+}
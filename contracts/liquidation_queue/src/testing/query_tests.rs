@@ -4,8 +4,8 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{from_binary, Coin, Decimal, Uint128};
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
+    BidFill, BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
+    ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg, SimulateBidExecutionResponse,
 };
 
 #[test]
@@ -223,14 +223,14 @@ fn query_bids() {
             idx: Uint128::from(1u128),
             collateral_token: "token0000".to_string(),
             bidder: "addr0000".to_string(),
+            bid_denom: "uusd".to_string(),
             amount: Uint256::from(1000u128),
             premium_slot: 5u8,
             pending_liquidated_collateral: Uint256::zero(),
             wait_end: None,
-            product_snapshot: Decimal256::one(),
+            share: Decimal256::from_uint256(Uint256::from(1000u128)),
             sum_snapshot: Decimal256::zero(),
             epoch_snapshot: Uint128::zero(),
-            scale_snapshot: Uint128::zero(),
         }
     );
 
@@ -239,7 +239,7 @@ fn query_bids() {
             deps.as_ref(),
             mock_env(),
             QueryMsg::BidsByUser {
-                collateral_token: "token0000".to_string(),
+                collateral_token: Some("token0000".to_string()),
                 bidder: "addr0000".to_string(),
                 start_after: None,
                 limit: None,
@@ -256,40 +256,40 @@ fn query_bids() {
                     idx: Uint128::from(1u128),
                     collateral_token: "token0000".to_string(),
                     bidder: "addr0000".to_string(),
+                    bid_denom: "uusd".to_string(),
                     amount: Uint256::from(1000u128),
                     premium_slot: 5u8,
                     pending_liquidated_collateral: Uint256::zero(),
                     wait_end: None,
-                    product_snapshot: Decimal256::one(),
+                    share: Decimal256::from_uint256(Uint256::from(1000u128)),
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
-                    scale_snapshot: Uint128::zero(),
                 },
                 BidResponse {
                     idx: Uint128::from(2u128),
                     collateral_token: "token0000".to_string(),
                     bidder: "addr0000".to_string(),
+                    bid_denom: "uusd".to_string(),
                     amount: Uint256::from(1000u128),
                     premium_slot: 5u8,
                     pending_liquidated_collateral: Uint256::zero(),
                     wait_end: None,
-                    product_snapshot: Decimal256::one(),
+                    share: Decimal256::from_uint256(Uint256::from(1000u128)),
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
-                    scale_snapshot: Uint128::zero(),
                 },
                 BidResponse {
                     idx: Uint128::from(3u128),
                     collateral_token: "token0000".to_string(),
                     bidder: "addr0000".to_string(),
+                    bid_denom: "uusd".to_string(),
                     amount: Uint256::from(1000u128),
                     premium_slot: 10u8,
                     pending_liquidated_collateral: Uint256::zero(),
                     wait_end: None,
-                    product_snapshot: Decimal256::one(),
+                    share: Decimal256::from_uint256(Uint256::from(1000u128)),
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
-                    scale_snapshot: Uint128::zero(),
                 }
             ]
         }
@@ -300,7 +300,7 @@ fn query_bids() {
             deps.as_ref(),
             mock_env(),
             QueryMsg::BidsByUser {
-                collateral_token: "token0000".to_string(),
+                collateral_token: Some("token0000".to_string()),
                 bidder: "addr0000".to_string(),
                 start_after: Some(Uint128::from(1u128)),
                 limit: Some(1u8),
@@ -316,19 +316,168 @@ fn query_bids() {
                 idx: Uint128::from(2u128),
                 collateral_token: "token0000".to_string(),
                 bidder: "addr0000".to_string(),
+                bid_denom: "uusd".to_string(),
                 amount: Uint256::from(1000u128),
                 premium_slot: 5u8,
                 pending_liquidated_collateral: Uint256::zero(),
                 wait_end: None,
-                product_snapshot: Decimal256::one(),
+                share: Decimal256::from_uint256(Uint256::from(1000u128)),
                 sum_snapshot: Decimal256::zero(),
                 epoch_snapshot: Uint128::zero(),
-                scale_snapshot: Uint128::zero(),
             }]
         }
     );
 }
 
+#[test]
+fn query_bids_across_collaterals() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_collateral_max_ltv(&[
+        (&"token0000".to_string(), &Decimal256::percent(90)),
+        (&"token0001".to_string(), &Decimal256::percent(90)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for collateral_token in ["token0000", "token0001"] {
+        let msg = ExecuteMsg::WhitelistCollateral {
+            collateral_token: collateral_token.to_string(),
+            max_slot: 30u8,
+            bid_threshold: Uint256::from(10000u128), // to get instant activation
+            premium_rate_per_slot: Decimal256::percent(1),
+        };
+        let info = mock_info("owner0000", &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // bid 1 and 3 from addr0000, bid 2 from addr0001
+    for (bidder, collateral_token) in [
+        ("addr0000", "token0000"),
+        ("addr0001", "token0000"),
+        ("addr0000", "token0001"),
+    ] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: collateral_token.to_string(),
+            premium_slot: 5u8,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let bids_response: BidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: None,
+                bidder: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bids_response,
+        BidsResponse {
+            bids: vec![
+                BidResponse {
+                    idx: Uint128::from(1u128),
+                    collateral_token: "token0000".to_string(),
+                    bidder: "addr0000".to_string(),
+                    bid_denom: "uusd".to_string(),
+                    amount: Uint256::from(1000u128),
+                    premium_slot: 5u8,
+                    pending_liquidated_collateral: Uint256::zero(),
+                    wait_end: None,
+                    share: Decimal256::from_uint256(Uint256::from(1000u128)),
+                    sum_snapshot: Decimal256::zero(),
+                    epoch_snapshot: Uint128::zero(),
+                },
+                BidResponse {
+                    idx: Uint128::from(3u128),
+                    collateral_token: "token0001".to_string(),
+                    bidder: "addr0000".to_string(),
+                    bid_denom: "uusd".to_string(),
+                    amount: Uint256::from(1000u128),
+                    premium_slot: 5u8,
+                    pending_liquidated_collateral: Uint256::zero(),
+                    wait_end: None,
+                    share: Decimal256::from_uint256(Uint256::from(1000u128)),
+                    sum_snapshot: Decimal256::zero(),
+                    epoch_snapshot: Uint128::zero(),
+                }
+            ]
+        }
+    );
+
+    let bids_response: BidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: None,
+                bidder: "addr0000".to_string(),
+                start_after: Some(Uint128::from(1u128)),
+                limit: Some(1u8),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bids_response.bids.len(), 1);
+    assert_eq!(bids_response.bids[0].idx, Uint128::from(3u128));
+
+    // retracted bids are removed from the index
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(3u128),
+        amount: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let bids_response: BidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: None,
+                bidder: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bids_response.bids.len(), 1);
+    assert_eq!(bids_response.bids[0].idx, Uint128::from(1u128));
+}
+
 #[test]
 fn query_bid_pools() {
     let mut deps = mock_dependencies(&[]);
@@ -405,9 +554,8 @@ fn query_bid_pools() {
             total_bid_amount: Uint256::from(1000u128),
             premium_rate: Decimal256::percent(5),
             sum_snapshot: Decimal256::zero(),
-            product_snapshot: Decimal256::one(),
+            total_share: Decimal256::from_uint256(Uint256::from(1000u128)),
             current_epoch: Uint128::zero(),
-            current_scale: Uint128::zero(),
         }
     );
 
@@ -432,25 +580,22 @@ fn query_bid_pools() {
                     total_bid_amount: Uint256::from(1000u128),
                     premium_rate: Decimal256::percent(5),
                     sum_snapshot: Decimal256::zero(),
-                    product_snapshot: Decimal256::one(),
+                    total_share: Decimal256::from_uint256(Uint256::from(1000u128)),
                     current_epoch: Uint128::zero(),
-                    current_scale: Uint128::zero(),
                 },
                 BidPoolResponse {
                     total_bid_amount: Uint256::from(1000u128),
                     premium_rate: Decimal256::percent(6),
                     sum_snapshot: Decimal256::zero(),
-                    product_snapshot: Decimal256::one(),
+                    total_share: Decimal256::from_uint256(Uint256::from(1000u128)),
                     current_epoch: Uint128::zero(),
-                    current_scale: Uint128::zero(),
                 },
                 BidPoolResponse {
                     total_bid_amount: Uint256::from(1000u128),
                     premium_rate: Decimal256::percent(10),
                     sum_snapshot: Decimal256::zero(),
-                    product_snapshot: Decimal256::one(),
+                    total_share: Decimal256::from_uint256(Uint256::from(1000u128)),
                     current_epoch: Uint128::zero(),
-                    current_scale: Uint128::zero(),
                 }
             ]
         }
@@ -476,9 +621,8 @@ fn query_bid_pools() {
                 total_bid_amount: Uint256::from(1000u128),
                 premium_rate: Decimal256::percent(5),
                 sum_snapshot: Decimal256::zero(),
-                product_snapshot: Decimal256::one(),
+                total_share: Decimal256::from_uint256(Uint256::from(1000u128)),
                 current_epoch: Uint128::zero(),
-                current_scale: Uint128::zero(),
             },]
         }
     );
@@ -540,3 +684,103 @@ fn query_collateral_info() {
         }
     );
 }
+
+#[test]
+fn query_simulate_bid_execution() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(50))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000u128), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (premium_slot, amount) in [(1u8, 1000u128), (2u8, 10000u128)] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "token0000".to_string(),
+            premium_slot,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // slot 1 fills 1,000 / 0.99 = 1,010 collateral
+    // slot 2 fills the remaining 3,990 collateral for 3,910 uusd
+    let res: SimulateBidExecutionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateBidExecution {
+                collateral_token: "token0000".to_string(),
+                amount: Uint256::from(5000u64),
+                price: Decimal256::one(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SimulateBidExecutionResponse {
+            collateral_token: "token0000".to_string(),
+            collateral_amount: Uint256::from(5000u64),
+            repay_amount: Uint256::from(4861u64),
+            bid_fee: Uint256::from(49u64),
+            liquidator_fee: Uint256::zero(),
+            fills: vec![
+                BidFill {
+                    premium_rate: Decimal256::percent(1),
+                    collateral_amount: Uint256::from(1010u64),
+                    stable_amount: Uint256::from(1000u64),
+                },
+                BidFill {
+                    premium_rate: Decimal256::percent(2),
+                    collateral_amount: Uint256::from(3990u64),
+                    stable_amount: Uint256::from(3910u64),
+                },
+            ],
+        }
+    );
+
+    // bids are not consumed
+    let res: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidPool {
+                collateral_token: "token0000".to_string(),
+                bid_slot: 1u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.total_bid_amount, Uint256::from(1000u64));
+}
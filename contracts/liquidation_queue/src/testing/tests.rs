@@ -1,17 +1,32 @@
 use crate::contract::{execute, instantiate, query};
-use crate::testing::mock_querier::mock_dependencies;
+use crate::error::ContractError;
+use crate::state::MAX_BORROWER_LIQUIDATION_LOGS;
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use crate::top_up::TOP_UP_REPLY_ID;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128,
+    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Env, OwnedDeps, StdError,
+    SubMsg, Uint128, WasmMsg,
 };
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::liquidation_queue::{
-    BidResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    QueryMsg,
+    AuctionConfig, AuctionConfigResponse, AuctionResponse, AuctionsResponse, BidDenomsResponse,
+    BidExpiry, BidExpiryResponse, BidIncentives, BidIncentivesResponse, BidPoolResponse,
+    BidPoolsResponse, BidResponse, BidTopUp, BidTopUpResponse, BidderLimits, BidderLimitsResponse,
+    BidderRewardsResponse, CollateralFees, CollateralFeesResponse, CollateralInfoResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, ExpiredBidsResponse, FeeSplit, FeeSplitResponse,
+    FeeStatsResponse, InstantiateMsg, LiquidationAmountResponse, LiquidationLogResponse,
+    LiquidationLogsResponse, QueryMsg, SnipingProtectionResponse, SwapRouterResponse, TopUpConfig,
 };
 
+use moneymarket::market::{Cw20HookMsg as MarketCw20HookMsg, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::querier::FeeModel;
+use moneymarket::swap::SwapRoute;
+use terra_cosmwasm::create_swap_send_msg;
+use terraswap::asset::AssetInfo;
+use terraswap::router::{Cw20HookMsg as RouterCw20HookMsg, SwapOperation};
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -162,7 +177,7 @@ fn update_config() {
     };
 
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    assert_eq!(err, StdError::generic_err("unauthorized"));
+    assert_eq!(err, ContractError::Unauthorized {});
 }
 
 #[test]
@@ -202,10 +217,7 @@ fn submit_bid() {
     };
     let info = mock_info("addr0000", &[]);
     let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("No uusd assets have been provided")
-    );
+    assert_eq!(err, ContractError::ZeroAssets("uusd".to_string()));
 
     let info = mock_info(
         "addr0000",
@@ -215,10 +227,7 @@ fn submit_bid() {
         }],
     );
     let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("Invalid asset provided, only uusd allowed")
-    );
+    assert_eq!(err, ContractError::InvalidAsset("uusd".to_string()));
 
     let info = mock_info(
         "addr0000",
@@ -234,10 +243,7 @@ fn submit_bid() {
         ],
     );
     let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("Invalid asset provided, only uusd allowed")
-    );
+    assert_eq!(err, ContractError::InvalidAsset("uusd".to_string()));
 
     let info = mock_info(
         "addr0000",
@@ -267,14 +273,14 @@ fn submit_bid() {
             idx: Uint128::from(1u128),
             collateral_token: "asset0000".to_string(),
             bidder: "addr0000".to_string(),
+            bid_denom: "uusd".to_string(),
             amount: Uint256::from(1000000u128),
             premium_slot: 1u8,
-            product_snapshot: Decimal256::one(),
+            share: Decimal256::zero(),
             sum_snapshot: Decimal256::zero(),
             pending_liquidated_collateral: Uint256::zero(),
             wait_end: Some(wait_end.seconds()),
             epoch_snapshot: Uint128::zero(),
-            scale_snapshot: Uint128::zero(),
         }
     );
 }
@@ -333,16 +339,13 @@ fn activate_bid() {
     let mut env = mock_env();
     env.block.time = wait_end;
     let err = execute(deps.as_mut(), env, info, msg.clone()).unwrap_err();
-    assert_eq!(err, StdError::generic_err("unauthorized"));
+    assert_eq!(err, ContractError::Unauthorized {});
 
     let info = mock_info("addr0000", &[]);
     let mut env = mock_env();
     env.block.time = wait_end.minus_seconds(2u64);
     let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err(format!("Wait period expires at {}", wait_end.seconds()))
-    );
+    assert_eq!(err, ContractError::WaitPeriodNotExpired(wait_end.seconds()));
 
     // graceful return when idx is not specified
     let msg2 = ExecuteMsg::ActivateBids {
@@ -382,14 +385,14 @@ fn activate_bid() {
             idx: Uint128::from(1u128),
             collateral_token: "asset0000".to_string(),
             bidder: "addr0000".to_string(),
+            bid_denom: "uusd".to_string(),
             amount: Uint256::from(1000000u128),
             premium_slot: 1u8,
-            product_snapshot: Decimal256::one(),
+            share: Decimal256::from_uint256(Uint256::from(1000000u128)),
             sum_snapshot: Decimal256::zero(),
             pending_liquidated_collateral: Uint256::zero(),
             wait_end: None,
             epoch_snapshot: Uint128::zero(),
-            scale_snapshot: Uint128::zero(),
         }
     );
 }
@@ -542,6 +545,95 @@ fn retract_unactive_bid() {
     );
 }
 
+#[test]
+fn fee_model() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1010000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let retract_msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: Some(Uint256::from(505000u128)),
+    };
+    let refund = |amount: u128| {
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        }))]
+    };
+
+    // the treasury tax applies by default
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, retract_msg.clone()).unwrap();
+    assert_eq!(res.messages, refund(500000u128));
+
+    let msg = ExecuteMsg::UpdateFeeModel {
+        fee_model: FeeModel::NoTax {},
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_fee_model"),
+            attr("fee_model", "no_tax"),
+        ]
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, retract_msg).unwrap();
+    assert_eq!(res.messages, refund(505000u128));
+}
+
 #[test]
 fn execute_bid() {
     let mut deps = mock_dependencies(&[]);
@@ -622,6 +714,8 @@ fn execute_bid() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -630,10 +724,7 @@ fn execute_bid() {
     let info = mock_info("asset0000", &[]);
     let env = mock_env();
     let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("Unauthorized: only custody contract can execute liquidations",)
-    );
+    assert_eq!(err, ContractError::NotCustodyContract {});
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "custody0000".to_string(), // only custody contract can execute
@@ -642,10 +733,18 @@ fn execute_bid() {
             liquidator: "liquidator0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
 
+    // liquidations are halted while the protocol is frozen
+    deps.querier.with_frozen(true);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::ProtocolFrozen {});
+    deps.querier.with_frozen(false);
+
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
         res.messages,
@@ -681,6 +780,8 @@ fn execute_bid() {
             liquidator: "liquidator0000".to_string(),
             fee_address: None,
             repay_address: None,
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -719,14 +820,13 @@ fn execute_bid() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    assert_eq!(
-        res,
-        StdError::generic_err("Not enough bids to execute this liquidation")
-    );
+    assert_eq!(res, ContractError::InsufficientBids {});
 }
 
 #[test]
@@ -807,6 +907,8 @@ fn claim_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
         })
         .unwrap(),
     });
@@ -868,7 +970,7 @@ fn update_collateral_info() {
     // unauthorized attempt
     let info = mock_info("addr0000", &[]);
     let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
-    assert_eq!(err, StdError::generic_err("unauthorized"));
+    assert_eq!(err, ContractError::Unauthorized {});
 
     // successfull attempt
     let info = mock_info("owner0000", &[]);
@@ -896,3 +998,2828 @@ fn update_collateral_info() {
         }
     );
 }
+
+#[test]
+fn sniping_protection() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // bids below the threshold are activated on submission
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(100),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let msg = ExecuteMsg::UpdateSnipingProtection { enabled: true };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_sniping_protection"),
+            attr("enabled", "true"),
+        ]
+    );
+
+    let res: SnipingProtectionResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SnipingProtection {}).unwrap())
+            .unwrap();
+    assert!(res.enabled);
+
+    // bid submitted in the block of the price update waits
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bid_response.wait_end,
+        Some(env.block.time.plus_seconds(60u64).seconds())
+    );
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u64)]),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::PriceUpdatedInBlock {});
+
+    // next block, the waiting period is skipped under the threshold
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(6u64);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "activate_bids"), attr("amount", "1000000"),]
+    );
+}
+
+#[test]
+fn auction_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAuctionConfig {
+        collateral_token: "asset0000".to_string(),
+        auction_config: Some(AuctionConfig {
+            initial_discount: Decimal256::percent(10),
+            decay_period: 100u64,
+            expiry_period: 600u64,
+        }),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let invalid_msg = ExecuteMsg::UpdateAuctionConfig {
+        collateral_token: "asset0000".to_string(),
+        auction_config: Some(AuctionConfig {
+            initial_discount: Decimal256::percent(100),
+            decay_period: 100u64,
+            expiry_period: 600u64,
+        }),
+    };
+    let info = mock_info("owner0000", &[]);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), invalid_msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidAuctionConfig {});
+
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: AuctionConfigResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AuctionConfig {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.auction_config,
+        Some(AuctionConfig {
+            initial_discount: Decimal256::percent(10),
+            decay_period: 100u64,
+            expiry_period: 600u64,
+        })
+    );
+
+    // auctions need the borrower to repay
+    let liquidation_msg = |borrower: Option<String>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(1000000u128),
+            msg: to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower,
+                repay_denom: None,
+            })
+            .unwrap(),
+        })
+    };
+    let info = mock_info("asset0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        liquidation_msg(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MissingAuctionBorrower {});
+
+    // no auction is started while the protocol is frozen
+    deps.querier.with_frozen(true);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        liquidation_msg(Some("borrower0000".to_string())),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProtocolFrozen {});
+    deps.querier.with_frozen(false);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        liquidation_msg(Some("borrower0000".to_string())),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "start_auction"),
+            attr("auction_idx", "1"),
+            attr("borrower", "borrower0000"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+        ]
+    );
+
+    // half way through the decay period the discount is 5%
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(50u64);
+    let res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ActiveAuctions {
+                collateral_token: "asset0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.auctions,
+        vec![AuctionResponse {
+            idx: Uint128::from(1u128),
+            collateral_token: "asset0000".to_string(),
+            borrower: "borrower0000".to_string(),
+            amount: Uint256::from(1000000u128),
+            start_time: mock_env().block.time.seconds(),
+            discount: Decimal256::percent(5),
+            expires_at: mock_env().block.time.seconds() + 700u64,
+        }]
+    );
+
+    // auction price 0.475
+    // collateral_amount 500,000
+    // bid_fee           2,375
+    // liquidator_fee    2,375
+    // repay_amount      232,750
+    let msg = ExecuteMsg::AuctionBid {
+        auction_idx: Uint128::from(1u128),
+    };
+    let info = mock_info(
+        "bidder0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(237500u128),
+        }],
+    );
+    // nor can the collateral be bought
+    deps.querier.with_frozen(true);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::ProtocolFrozen {});
+    deps.querier.with_frozen(false);
+
+    // the collateral can not be bought without a price
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::zero(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::ZeroAuctionPrice {});
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "bidder0000".to_string(),
+                    amount: Uint128::from(500000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "repay0000".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(232750u128),
+                }],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFor {
+                    borrower: "borrower0000".to_string(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2375u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2375u128),
+                }],
+            })),
+        ]
+    );
+
+    // the fees of the collateral apply to the auctions too
+    let fees_msg = |bid_fee: u64| ExecuteMsg::UpdateCollateralFees {
+        collateral_token: "asset0000".to_string(),
+        collateral_fees: Some(CollateralFees {
+            bid_fee: Decimal256::percent(bid_fee),
+            liquidator_fee: Decimal256::zero(),
+        }),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        fees_msg(2),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        fees_msg(101),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFees {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        fees_msg(2),
+    )
+    .unwrap();
+
+    let res: CollateralFeesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralFees {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        CollateralFeesResponse {
+            collateral_token: "asset0000".to_string(),
+            bid_fee: Decimal256::percent(2),
+            liquidator_fee: Decimal256::zero(),
+        }
+    );
+
+    // after the decay period the collateral sells at the oracle price,
+    // the unused stables go back to the bidder
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(200u64);
+    let info = mock_info(
+        "bidder0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(300000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "auction_bid"),
+            attr("auction_idx", "1"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "500000"),
+            attr("repay_amount", "245000"),
+            attr("bid_fee", "5000"),
+            attr("liquidator_fee", "0"),
+        ]
+    );
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "bidder0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(50000u128),
+            }],
+        }))
+    );
+
+    // the auction is closed once all the collateral is sold
+    let info = mock_info(
+        "bidder0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(300000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "No active auction with the given idx"
+        ))
+    );
+}
+
+#[test]
+fn auction_expiry() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAuctionConfig {
+        collateral_token: "asset0000".to_string(),
+        auction_config: Some(AuctionConfig {
+            initial_discount: Decimal256::percent(10),
+            decay_period: 100u64,
+            expiry_period: 600u64,
+        }),
+    };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: Some("borrower0000".to_string()),
+            repay_denom: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let submit_bid = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                      bidder: &str,
+                      bid_idx: u128,
+                      amount: u128,
+                      env: Env| {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 1u8,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ActivateBids {
+            collateral_token: "asset0000".to_string(),
+            bids_idx: Some(vec![Uint128::from(bid_idx)]),
+        };
+        let mut env = env;
+        env.block.time = env.block.time.plus_seconds(60u64);
+        execute(deps.as_mut(), env, mock_info(bidder, &[]), msg).unwrap();
+    };
+    // the auction can only be expired once the expiry period is over
+    let msg = ExecuteMsg::ExpireAuction {
+        auction_idx: Uint128::from(1u128),
+    };
+    let info = mock_info("addr0000", &[]);
+    let expires_at = env.block.time.seconds() + 700u64;
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(699u64);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::AuctionNotExpired(expires_at));
+
+    let res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AuctionsByBorrower {
+                borrower: "borrower0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.auctions,
+        vec![AuctionResponse {
+            idx: Uint128::from(1u128),
+            collateral_token: "asset0000".to_string(),
+            borrower: "borrower0000".to_string(),
+            amount: Uint256::from(1000000u128),
+            start_time: mock_env().block.time.seconds(),
+            discount: Decimal256::zero(),
+            expires_at,
+        }]
+    );
+
+    // the bid pools must fill the whole collateral
+    env.block.time = env.block.time.plus_seconds(1u64);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientBids {});
+
+    // premium price 0.495, required stable 495,000
+    submit_bid(&mut deps, "bidder0000", 1u128, 200000u128, env.clone());
+    submit_bid(&mut deps, "bidder0001", 2u128, 400000u128, env.clone());
+    env.block.time = env.block.time.plus_seconds(60u64);
+
+    deps.querier.with_frozen(true);
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::ProtocolFrozen {});
+    deps.querier.with_frozen(false);
+
+    // bid_fee        4,950
+    // liquidator_fee 4,950
+    // repay_amount   485,100
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "repay0000".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(485100u128),
+                }],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFor {
+                    borrower: "borrower0000".to_string(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(4950u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(4950u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes[..7],
+        vec![
+            attr("action", "expire_auction"),
+            attr("auction_idx", "1"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+            attr("repay_amount", "485100"),
+            attr("bid_fee", "4950"),
+            attr("liquidator_fee", "4950"),
+        ]
+    );
+
+    // the collateral went to the bidders
+    let res: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.amount, Uint256::from(34999u128));
+    assert_eq!(res.pending_liquidated_collateral, Uint256::from(333333u128));
+
+    let res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ActiveAuctions {
+                collateral_token: "asset0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.auctions.is_empty());
+    let res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AuctionsByBorrower {
+                borrower: "borrower0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.auctions.is_empty());
+
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "No active auction with the given idx"
+        ))
+    );
+}
+
+#[test]
+fn fee_split() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateFeeSplit {
+        fee_split: Some(FeeSplit {
+            insurance_fund: "insurance0000".to_string(),
+            insurance_share: Decimal256::percent(20),
+            caller_share: Decimal256::percent(30),
+        }),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateFeeSplit {
+            fee_split: Some(FeeSplit {
+                insurance_fund: "insurance0000".to_string(),
+                insurance_share: Decimal256::percent(60),
+                caller_share: Decimal256::percent(50),
+            }),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFeeSplit {});
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_fee_split"),
+            attr("insurance_fund", "insurance0000"),
+            attr("insurance_share", "0.2"),
+            attr("caller_share", "0.3"),
+        ]
+    );
+
+    let res: FeeSplitResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FeeSplit {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FeeSplitResponse {
+            fee_split: Some(FeeSplit {
+                insurance_fund: "insurance0000".to_string(),
+                insurance_share: Decimal256::percent(20),
+                caller_share: Decimal256::percent(30),
+            }),
+        }
+    );
+
+    // required_stable  495,000
+    // bid_fee            4,950 -> 2,475 yield reserve, 990 insurance, 1,485 caller
+    // liquidator_fee     4,950
+    // repay_amount     485,100
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(480297u128), // 485100 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2450u128), // 2475 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(6371u128), // 6435 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "insurance0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(980u128), // 990 / (1 + tax_rate)
+                }]
+            })),
+        ]
+    );
+    assert!(res.attributes.contains(&attr("insurance_fee", "990")));
+    assert!(res.attributes.contains(&attr("caller_fee", "1485")));
+
+    let res: FeeStatsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::FeeStats { bid_denom: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        FeeStatsResponse {
+            yield_reserve: Uint256::from(2475u64),
+            insurance_fund: Uint256::from(990u64),
+            caller: Uint256::from(1485u64),
+        }
+    );
+
+    // without a split the whole bid fee is accounted to the yield reserve
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateFeeSplit { fee_split: None },
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+
+    let res: FeeStatsResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::FeeStats { bid_denom: None }).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        FeeStatsResponse {
+            yield_reserve: Uint256::from(7425u64),
+            insurance_fund: Uint256::from(990u64),
+            caller: Uint256::from(1485u64),
+        }
+    );
+}
+
+#[test]
+fn bid_top_up() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 0u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let top_up_msg = ExecuteMsg::UpdateBidTopUp {
+        bid_idx: Uint128::from(1u128),
+        top_up: Some(BidTopUp {
+            refill_amount: Uint256::from(600u128),
+            cap: Uint256::from(1000u128),
+        }),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        top_up_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TopUpNotEnabled {});
+
+    let msg = ExecuteMsg::UpdateTopUpConfig {
+        top_up_config: Some(TopUpConfig {
+            market_contract: "market0000".to_string(),
+            aterra_contract: "aterra0000".to_string(),
+        }),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_top_up_config"),
+            attr("market_contract", "market0000"),
+            attr("aterra_contract", "aterra0000"),
+        ]
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        top_up_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateBidTopUp {
+            bid_idx: Uint128::from(1u128),
+            top_up: Some(BidTopUp {
+                refill_amount: Uint256::from(1001u128),
+                cap: Uint256::from(1000u128),
+            }),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRefillAmount {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        top_up_msg,
+    )
+    .unwrap();
+
+    // the liquidation depletes the pool
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::TopUpBid {
+                    bid_idx: Uint128::from(1u128),
+                })
+                .unwrap(),
+            }),
+            TOP_UP_REPLY_ID,
+        )
+    );
+
+    // only the contract can top up bids
+    let msg = ExecuteMsg::TopUpBid {
+        bid_idx: Uint128::from(1u128),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aterra0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(600u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aterra0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "market0000".to_string(),
+                    amount: Uint128::from(600u128),
+                    msg: to_binary(&MarketCw20HookMsg::RedeemStable {}).unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RefillBid {
+                    bid_idx: Uint128::from(1u128),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res: BidTopUpResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BidTopUp {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BidTopUpResponse {
+            bid_idx: Uint128::from(1u128),
+            top_up: Some(BidTopUp {
+                refill_amount: Uint256::from(600u128),
+                cap: Uint256::from(400u128),
+            }),
+        }
+    );
+
+    // the market sends the redeemed stable, minus tax
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(660u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::RefillBid {
+            bid_idx: Uint128::from(1u128),
+            prev_balance: Uint256::from(66u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "refill_bid"),
+            attr("bid_idx", "1"),
+            attr("amount", "594"),
+        ]
+    );
+
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.amount, Uint256::from(594u128));
+    assert_eq!(
+        bid_response.pending_liquidated_collateral,
+        Uint256::from(1000u128)
+    );
+
+    // the last refill is capped and removes the top-up
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "top_up_bid"),
+            attr("bid_idx", "1"),
+            attr("redeem_amount", "400"),
+        ]
+    );
+
+    let res: BidTopUpResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::BidTopUp {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.top_up, None);
+}
+
+#[test]
+fn multiple_bid_denoms() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[
+        (
+            &("asset0000".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::percent(50),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("asset0000".to_string(), "ukrw".to_string()),
+            &(
+                Decimal256::from_uint256(500u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(1000000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let submit_bid = |denom: &str, amount: u128| {
+        (
+            ExecuteMsg::SubmitBid {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 0u8,
+            },
+            vec![Coin {
+                denom: denom.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    // other denoms are rejected until the owner accepts them
+    let (msg, funds) = submit_bid("ukrw", 500000000u128);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &funds),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAsset("uusd".to_string()));
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateBidDenoms {
+            bid_denoms: vec!["ukrw".to_string()],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBidDenoms {
+            bid_denoms: vec!["uusd".to_string()],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::StableBidDenom {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBidDenoms {
+            bid_denoms: vec!["ukrw".to_string(), "ukrw".to_string()],
+        },
+    )
+    .unwrap();
+    let res: BidDenomsResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::BidDenoms {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        BidDenomsResponse {
+            stable_denom: "uusd".to_string(),
+            bid_denoms: vec!["ukrw".to_string()],
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &funds),
+        msg,
+    )
+    .unwrap();
+    let (msg, funds) = submit_bid("uusd", 100000u128);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &funds),
+        msg,
+    )
+    .unwrap();
+
+    let (msg, _) = submit_bid("uusd", 0u128);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::ZeroAssets("uusd or ukrw".to_string()));
+
+    let liquidate = |repay_denom: Option<String>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(1000000u128),
+            msg: to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower: None,
+                repay_denom,
+            })
+            .unwrap(),
+        })
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        liquidate(Some("ueur".to_string())),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRepayDenom("uusd or ukrw".to_string())
+    );
+
+    // 200,000 collateral are bought by the uusd bids, at 0.5 uusd
+    // the remaining 800,000 by the ukrw bids, at 500 ukrw
+    //   uusd: 100,000 spent -> 1,000 bid_fee, 1,000 liquidator_fee, 98,000 repay
+    //   ukrw: 400,000,000 spent -> 4,000,000 bid_fee, 4,000,000 liquidator_fee,
+    //         392,000,000 swapped to uusd
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        liquidate(None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(97029u128), // 98000 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(create_swap_send_msg(
+                "repay0000".to_string(),
+                Coin {
+                    denom: "ukrw".to_string(),
+                    amount: Uint128::from(392000000u128),
+                },
+                "uusd".to_string(),
+            )),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(990u128), // 1000 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(990u128), // 1000 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "ukrw".to_string(),
+                    amount: Uint128::from(4000000u128),
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator0000".to_string(),
+                amount: vec![Coin {
+                    denom: "ukrw".to_string(),
+                    amount: Uint128::from(4000000u128),
+                }]
+            })),
+        ]
+    );
+    assert!(res.attributes.contains(&attr("repay_amount", "98000")));
+    assert!(res
+        .attributes
+        .contains(&attr("swap_amount", "392000000ukrw")));
+
+    let res: FeeStatsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::FeeStats {
+                bid_denom: Some("ukrw".to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.yield_reserve, Uint256::from(4000000u64));
+
+    let res: BidPoolsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BidPoolsByDenom {
+                collateral_token: "asset0000".to_string(),
+                bid_denom: "ukrw".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.bid_pools[0].total_bid_amount,
+        Uint256::from(100000000u64)
+    );
+
+    // the ukrw bid is retracted in ukrw
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        ExecuteMsg::RetractBid {
+            bid_idx: Uint128::from(1u128),
+            amount: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(100000000u128),
+            }]
+        }))]
+    );
+}
+
+#[test]
+fn bid_expiry() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for (bidder, premium_slot) in [("addr0000", 0u8), ("addr0001", 1u8)].iter() {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: *premium_slot,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        );
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    let expire_msg = ExecuteMsg::ExpireBids {
+        bids_idx: vec![Uint128::from(2u128)],
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        expire_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BidExpiryNotEnabled {});
+
+    let msg = ExecuteMsg::UpdateBidExpiry {
+        bid_expiry: Some(BidExpiry {
+            expiry_period: 100u64,
+            keeper_reward: Decimal256::percent(1),
+        }),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBidExpiry {
+            bid_expiry: Some(BidExpiry {
+                expiry_period: 100u64,
+                keeper_reward: Decimal256::percent(6),
+            }),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidKeeperReward(5));
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_bid_expiry"),
+            attr("expiry_period", "100"),
+            attr("keeper_reward", "0.01"),
+        ]
+    );
+
+    let bid_expiry: BidExpiryResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::BidExpiry {}).unwrap()).unwrap();
+    assert_eq!(
+        bid_expiry,
+        BidExpiryResponse {
+            bid_expiry: Some(BidExpiry {
+                expiry_period: 100u64,
+                keeper_reward: Decimal256::percent(1),
+            }),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        expire_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BidNotExpired(Uint128::from(2u128)));
+
+    // the first bid is partially filled
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(100u64);
+    let expired_bids: ExpiredBidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ExpiredBids {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        expired_bids,
+        ExpiredBidsResponse {
+            bids_idx: vec![Uint128::from(2u128)],
+            last_bid_idx: Some(Uint128::from(2u128)),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::ExpireBids {
+            bids_idx: vec![Uint128::from(1u128)],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BidNotExpired(Uint128::from(1u128)));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        expire_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "expire_bids"),
+            attr("expired_bids", "1"),
+            attr("keeper_reward", "10000uusd"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(980198u128), // 990000 - tax
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "keeper0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(9900u128), // 10000 - tax
+                }],
+            })),
+        ]
+    );
+    query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Bid {
+            bid_idx: Uint128::from(2u128),
+        },
+    )
+    .unwrap_err();
+
+    // claiming keeps the first bid from expiring
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ClaimLiquidations {
+            collateral_token: "asset0000".to_string(),
+            bids_idx: None,
+        },
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(99u64);
+    let expired_bids: ExpiredBidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::ExpiredBids {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(expired_bids.bids_idx, Vec::<Uint128>::new());
+}
+
+#[test]
+fn liquidation_with_swap() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the highest slot premium is 10%
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 10u8,
+        bid_threshold: Uint256::from(100000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let swap_msg = |max_spread: Decimal256| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary(&Cw20HookMsg::ExecuteBidWithSwap {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                max_spread,
+            })
+            .unwrap(),
+        })
+    };
+    deps.querier.with_frozen(true);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        swap_msg(Decimal256::percent(5)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProtocolFrozen {});
+    deps.querier.with_frozen(false);
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        swap_msg(Decimal256::percent(5)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SwapNotEnabled {});
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSwapRouter {
+            swap_router: Some(SwapRoute::NativeSwap {}),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSwapRoute {});
+
+    let msg = ExecuteMsg::UpdateSwapRouter {
+        swap_router: Some(SwapRoute::Terraswap {
+            router: "router0000".to_string(),
+        }),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_swap_router"),
+            attr("swap_router", "router0000"),
+        ]
+    );
+
+    let swap_router: SwapRouterResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::SwapRouter {}).unwrap()).unwrap();
+    assert_eq!(
+        swap_router,
+        SwapRouterResponse {
+            swap_router: Some(SwapRoute::Terraswap {
+                router: "router0000".to_string(),
+            }),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        swap_msg(Decimal256::one()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMaxSpread {});
+
+    // bid amount = 1000 * (1 - 0.1) = 900, fees of 1% each
+    // minimum receive = max(882 + 9, 1000 * (1 - 0.05))
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        swap_msg(Decimal256::percent(5)),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_bid_with_swap"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000"),
+            attr("minimum_receive", "950"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "router0000".to_string(),
+                    amount: Uint128::from(1000u128),
+                    msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                        operations: vec![SwapOperation::TerraSwap {
+                            offer_asset_info: AssetInfo::Token {
+                                contract_addr: "asset0000".to_string(),
+                            },
+                            ask_asset_info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string(),
+                            },
+                        }],
+                        minimum_receive: Some(Uint128::from(950u128)),
+                        to: None,
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RepayFromSwap {
+                    liquidator: "liquidator0000".to_string(),
+                    repay_address: "repay0000".to_string(),
+                    fee_address: "fee0000".to_string(),
+                    repay_amount: Uint256::from(882u128),
+                    bid_fee: Uint256::from(9u128),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let repay_msg = ExecuteMsg::RepayFromSwap {
+        liquidator: "liquidator0000".to_string(),
+        repay_address: "repay0000".to_string(),
+        fee_address: "fee0000".to_string(),
+        repay_amount: Uint256::from(882u128),
+        bid_fee: Uint256::from(9u128),
+        prev_balance: Uint256::zero(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidator0000", &[]),
+        repay_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(890u128),
+        }],
+    );
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        repay_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientSwapAmount {});
+
+    // the liquidator gets the swap remainder, 960 - 882 - 9
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(960u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        repay_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_from_swap"),
+            attr("swap_amount", "960"),
+            attr("repay_amount", "882"),
+            attr("bid_fee", "9"),
+            attr("liquidator_amount", "69"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(873u128), // 882 - tax
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(8u128), // 9 - tax
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(68u128), // 69 - tax
+                }],
+            })),
+        ]
+    );
+
+    // without bids the collateral can only be liquidated with a swap
+    let msg = QueryMsg::SwapLiquidationAmount {
+        borrow_amount: Uint256::from(1000u64),
+        borrow_limit: Uint256::from(900u64),
+        collaterals: vec![("asset0000".to_string(), Uint256::from(2000u64))],
+        collateral_prices: vec![Decimal256::one()],
+    };
+    let res: LiquidationAmountResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    // (1000 + 1) / (0.9 * 0.99 * 0.99 * 0.99) + 1
+    assert_eq!(
+        res.collaterals,
+        vec![("asset0000".to_string(), Uint256::from(1147u64))]
+    );
+}
+
+#[test]
+fn self_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // only the zero premium bids buy self-liquidated collateral
+    for (premium_slot, amount) in [(0u8, 500000u128), (1u8, 1000000u128)].iter() {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: *premium_slot,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(*amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let self_liquidation = |sender: &str, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::ExecuteSelfLiquidation {
+                borrower: "borrower0000".to_string(),
+                repay_address: Some("repay0000".to_string()),
+            })
+            .unwrap(),
+        })
+    };
+
+    let info = mock_info("asset0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        self_liquidation("addr0001", 400000u128),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotCustodyContract {});
+
+    // 1000002 * 0.5 > 500000
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        self_liquidation("custody0000", 1000002u128),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientZeroPremiumBids {});
+
+    // the whole 400000 * 0.5 repays the loan, without fees
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        self_liquidation("custody0000", 400000u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "repay0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(200000u128),
+            }]
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_self_liquidation"),
+            attr("borrower", "borrower0000"),
+            attr("repay_amount", "200000"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "400000"),
+        ]
+    );
+
+    let bid_pool: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::BidPool {
+                collateral_token: "asset0000".to_string(),
+                bid_slot: 0u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_pool.total_bid_amount, Uint256::from(300000u64));
+}
+
+#[test]
+fn bidder_limits() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateBidderLimits {
+        collateral_token: "asset0000".to_string(),
+        bidder_limits: Some(BidderLimits {
+            max_bids: 2u32,
+            max_bid_amount: Uint256::from(1000000u64),
+        }),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBidderLimits {
+            collateral_token: "asset0000".to_string(),
+            bidder_limits: Some(BidderLimits {
+                max_bids: 0u32,
+                max_bid_amount: Uint256::from(1000000u64),
+            }),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBidderLimits {});
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_bidder_limits"),
+            attr("collateral_token", "asset0000"),
+            attr("max_bids", "2"),
+            attr("max_bid_amount", "1000000"),
+        ]
+    );
+
+    let bidder_limits_res: BidderLimitsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidderLimits {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bidder_limits_res,
+        BidderLimitsResponse {
+            collateral_token: "asset0000".to_string(),
+            bidder_limits: Some(BidderLimits {
+                max_bids: 2u32,
+                max_bid_amount: Uint256::from(1000000u64),
+            }),
+        }
+    );
+
+    let submit_bid = |bidder: &str, amount: u128| {
+        (
+            mock_info(
+                bidder,
+                &[Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(amount),
+                }],
+            ),
+            ExecuteMsg::SubmitBid {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 1u8,
+            },
+        )
+    };
+
+    // the new bid is counted against the total bid size
+    let (info, msg) = submit_bid("addr0000", 1000001u128);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MaxBidAmountExceeded(Uint256::from(1000000u128), "uusd".to_string())
+    );
+
+    for amount in [400000u128, 500000u128].iter() {
+        let (info, msg) = submit_bid("addr0000", *amount);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let (info, msg) = submit_bid("addr0000", 100000u128);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxBidsExceeded(2));
+
+    // limits apply to each bidder
+    let (info, msg) = submit_bid("addr0001", 1000000u128);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // retracted bids free their slot and size
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let (info, msg) = submit_bid("addr0000", 600000u128);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MaxBidAmountExceeded(Uint256::from(1000000u128), "uusd".to_string())
+    );
+    let (info, msg) = submit_bid("addr0000", 500000u128);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // removing the limits lifts the caps
+    let msg = ExecuteMsg::UpdateBidderLimits {
+        collateral_token: "asset0000".to_string(),
+        bidder_limits: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let (info, msg) = submit_bid("addr0000", 1000000u128);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn liquidation_logs() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 10u8,
+        bid_threshold: Uint256::from(u128::MAX),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 2u8,
+    };
+    let info = mock_info(
+        "bidder0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000000u128),
+        }],
+    );
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let liquidation_msg = |borrower: Option<&str>, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower: borrower.map(|borrower| borrower.to_string()),
+                repay_denom: None,
+            })
+            .unwrap(),
+        })
+    };
+    let info = mock_info("asset0000", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        liquidation_msg(Some("borrower0000"), 10000u128),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        liquidation_msg(None, 20000u128),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        liquidation_msg(Some("borrower0001"), 4000u128),
+    )
+    .unwrap();
+
+    let res: LiquidationLogsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::LiquidationLogs {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.logs,
+        vec![
+            LiquidationLogResponse {
+                idx: Uint128::from(1u128),
+                height: env.block.height,
+                borrower: Some("borrower0000".to_string()),
+                collateral_token: "asset0000".to_string(),
+                collateral_amount: Uint256::from(10000u128),
+                premium_rate: Decimal256::percent(2),
+                repay_denom: "uusd".to_string(),
+                repay_amount: Uint256::from(4802u128), // 4900 - 49 bid fee - 49 liquidator fee
+                liquidator: "liquidator0000".to_string(),
+            },
+            LiquidationLogResponse {
+                idx: Uint128::from(2u128),
+                height: env.block.height,
+                borrower: None,
+                collateral_token: "asset0000".to_string(),
+                collateral_amount: Uint256::from(20000u128),
+                premium_rate: Decimal256::percent(2),
+                repay_denom: "uusd".to_string(),
+                repay_amount: Uint256::from(9604u128),
+                liquidator: "liquidator0000".to_string(),
+            },
+            LiquidationLogResponse {
+                idx: Uint128::from(3u128),
+                height: env.block.height,
+                borrower: Some("borrower0001".to_string()),
+                collateral_token: "asset0000".to_string(),
+                collateral_amount: Uint256::from(4000u128),
+                premium_rate: Decimal256::percent(2),
+                repay_denom: "uusd".to_string(),
+                repay_amount: Uint256::from(1922u128),
+                liquidator: "liquidator0000".to_string(),
+            },
+        ]
+    );
+
+    let res: LiquidationLogsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::LiquidationLogs {
+                start_after: Some(Uint128::from(1u128)),
+                limit: Some(1u8),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.logs.iter().map(|log| log.idx).collect::<Vec<Uint128>>(),
+        vec![Uint128::from(2u128)]
+    );
+
+    let borrower_logs = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                         borrower: &str| {
+        let res: LiquidationLogsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::LiquidationLogsByBorrower {
+                    borrower: borrower.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.logs.iter().map(|log| log.idx).collect::<Vec<Uint128>>()
+    };
+    assert_eq!(
+        borrower_logs(&deps, "borrower0000"),
+        vec![Uint128::from(1u128)]
+    );
+    assert_eq!(
+        borrower_logs(&deps, "borrower0001"),
+        vec![Uint128::from(3u128)]
+    );
+
+    // only the latest liquidations of each borrower are kept
+    for _ in 0..MAX_BORROWER_LIQUIDATION_LOGS {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            liquidation_msg(Some("borrower0001"), 100u128),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        borrower_logs(&deps, "borrower0001")[0],
+        Uint128::from(4u128)
+    );
+    assert_eq!(
+        borrower_logs(&deps, "borrower0000"),
+        vec![Uint128::from(1u128)]
+    );
+}
+
+#[test]
+fn bid_incentives() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 100000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let mut env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 1u8,
+        bid_threshold: Uint256::from(u128::MAX),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    // slot 0 receives 2/3 of the emissions, slot 1 receives 1/3
+    let msg = ExecuteMsg::UpdateBidIncentives {
+        collateral_token: "asset0000".to_string(),
+        bid_incentives: Some(BidIncentives {
+            reward_token: "reward0000".to_string(),
+            emission_rate: Uint256::from(300u64),
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_bid_incentives"),
+            attr("collateral_token", "asset0000"),
+            attr("reward_token", "reward0000"),
+            attr("emission_rate", "300"),
+        ]
+    );
+
+    let res: BidIncentivesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BidIncentives {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.bid_incentives,
+        Some(BidIncentives {
+            reward_token: "reward0000".to_string(),
+            emission_rate: Uint256::from(300u64),
+        })
+    );
+
+    // the reward token can not be changed
+    let msg = ExecuteMsg::UpdateBidIncentives {
+        collateral_token: "asset0000".to_string(),
+        bid_incentives: Some(BidIncentives {
+            reward_token: "reward0001".to_string(),
+            emission_rate: Uint256::from(300u64),
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg);
+    match res {
+        Err(ContractError::RewardTokenChange {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    for (bidder, premium_slot, amount) in [
+        ("alice0000", 0u8, 1000u128),
+        ("bob0000", 0u8, 3000u128),
+        ("carol0000", 1u8, 1000u128),
+    ] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    let bidder_rewards =
+        |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, env: &Env, bidder: &str| {
+            let res: BidderRewardsResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::BidderRewards {
+                        collateral_token: "asset0000".to_string(),
+                        bidder: bidder.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.pending_rewards
+        };
+
+    // 2000 to slot 0 split 1:3, 1000 to slot 1
+    env.block.time = env.block.time.plus_seconds(10);
+    assert_eq!(
+        bidder_rewards(&deps, &env, "alice0000"),
+        Uint256::from(500u64)
+    );
+    assert_eq!(
+        bidder_rewards(&deps, &env, "bob0000"),
+        Uint256::from(1500u64)
+    );
+    assert_eq!(
+        bidder_rewards(&deps, &env, "carol0000"),
+        Uint256::from(1000u64)
+    );
+
+    // a retracted bid keeps its rewards and stops earning
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: None,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("alice0000", &[]), msg).unwrap();
+
+    // slot 0 is emptied by a liquidation, closing its epoch
+    env.block.time = env.block.time.plus_seconds(10);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(3000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+            repay_denom: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(10);
+    assert_eq!(
+        bidder_rewards(&deps, &env, "alice0000"),
+        Uint256::from(500u64)
+    );
+    // 1500 + 2000, rounded down
+    assert_eq!(
+        bidder_rewards(&deps, &env, "bob0000"),
+        Uint256::from(3499u64)
+    );
+    assert_eq!(
+        bidder_rewards(&deps, &env, "carol0000"),
+        Uint256::from(3000u64)
+    );
+
+    let msg = ExecuteMsg::ClaimBidRewards {
+        collateral_token: "asset0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "carol0000".to_string(),
+                amount: Uint128::from(3000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(bidder_rewards(&deps, &env, "carol0000"), Uint256::zero());
+
+    // rewards stay claimable once the emissions stop
+    let msg = ExecuteMsg::UpdateBidIncentives {
+        collateral_token: "asset0000".to_string(),
+        bid_incentives: None,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(10);
+    assert_eq!(bidder_rewards(&deps, &env, "carol0000"), Uint256::zero());
+    let msg = ExecuteMsg::ClaimBidRewards {
+        collateral_token: "asset0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("alice0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_bid_rewards"),
+            attr("collateral_token", "asset0000"),
+            attr("reward_amount", "500"),
+        ]
+    );
+}
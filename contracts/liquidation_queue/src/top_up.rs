@@ -0,0 +1,257 @@
+use crate::bid::deposit_to_bid;
+use crate::error::ContractError;
+use crate::state::{
+    read_bid, read_bid_top_up, read_bid_top_ups_by_pool, read_config, read_top_up_config,
+    store_bid_top_up, store_top_up_config, Bid, BidTopUp, Config, TopUpConfig,
+};
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::liquidation_queue::{
+    BidTopUp as BidTopUpMsg, BidTopUpResponse, ExecuteMsg, TopUpConfig as TopUpConfigMsg,
+    TopUpConfigResponse,
+};
+use moneymarket::market::Cw20HookMsg as MarketCw20HookMsg;
+use moneymarket::querier::query_balance;
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub const TOP_UP_REPLY_ID: u64 = 1;
+
+/// Max bids refilled per depleted pool on each liquidation
+const MAX_TOP_UPS_PER_POOL: u8 = 10;
+
+pub fn update_top_up_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    top_up_config: Option<TopUpConfigMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_top_up_config")];
+    let top_up_config = match top_up_config {
+        Some(top_up_config) => {
+            attributes.push(attr("market_contract", &top_up_config.market_contract));
+            attributes.push(attr("aterra_contract", &top_up_config.aterra_contract));
+            Some(TopUpConfig {
+                market_contract: deps.api.addr_canonicalize(&top_up_config.market_contract)?,
+                aterra_contract: deps.api.addr_canonicalize(&top_up_config.aterra_contract)?,
+            })
+        }
+        None => None,
+    };
+
+    store_top_up_config(deps.storage, top_up_config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn update_bid_top_up(
+    deps: DepsMut,
+    info: MessageInfo,
+    bid_idx: Uint128,
+    top_up: Option<BidTopUpMsg>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let bid: Bid = read_bid(deps.storage, bid_idx)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != bid.bidder {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(top_up) = &top_up {
+        if read_top_up_config(deps.storage)?.is_none() {
+            return Err(ContractError::TopUpNotEnabled {});
+        }
+        // aTerra is redeemed for the stable denom
+        let config: Config = read_config(deps.storage)?;
+        if bid.bid_denom != config.stable_denom {
+            return Err(ContractError::InvalidTopUpDenom(config.stable_denom));
+        }
+        if top_up.refill_amount.is_zero() || top_up.refill_amount > top_up.cap {
+            return Err(ContractError::InvalidRefillAmount {});
+        }
+    }
+
+    store_bid_top_up(
+        deps.storage,
+        &bid,
+        top_up.map(|top_up| BidTopUp {
+            refill_amount: top_up.refill_amount,
+            remaining_cap: top_up.cap,
+        }),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_bid_top_up"),
+        attr("bid_idx", bid_idx),
+    ]))
+}
+
+/// Refills of the bids with a top-up in the pools depleted by a liquidation.
+/// A failing refill, like a revoked allowance, is ignored in the reply,
+/// so a bidder cannot block the liquidation
+pub(crate) fn top_up_messages(
+    storage: &dyn Storage,
+    env: &Env,
+    collateral_token: &CanonicalAddr,
+    depleted_slots: &[u8],
+) -> StdResult<Vec<SubMsg<TerraMsgWrapper>>> {
+    if read_top_up_config(storage)?.is_none() {
+        return Ok(vec![]);
+    }
+
+    let mut messages: Vec<SubMsg<TerraMsgWrapper>> = vec![];
+    for slot in depleted_slots.iter() {
+        for bid_idx in
+            read_bid_top_ups_by_pool(storage, collateral_token, *slot, MAX_TOP_UPS_PER_POOL)?
+        {
+            messages.push(SubMsg::reply_on_error(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ExecuteMsg::TopUpBid { bid_idx })?,
+                }),
+                TOP_UP_REPLY_ID,
+            ));
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Pulls the aTerra of the bid owner and redeems it in the market. The stable
+/// balance is passed to the refill, to credit the bid with the redeemed amount
+pub fn top_up_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bid_idx: Uint128,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let top_up_config: TopUpConfig =
+        read_top_up_config(deps.storage)?.ok_or(ContractError::TopUpNotEnabled {})?;
+    let bid: Bid = read_bid(deps.storage, bid_idx)?;
+    let mut top_up: BidTopUp =
+        read_bid_top_up(deps.storage, bid_idx)?.ok_or(ContractError::NoBidTopUp {})?;
+
+    let redeem_amount = top_up.refill_amount.min(top_up.remaining_cap);
+    top_up.remaining_cap = top_up.remaining_cap - redeem_amount;
+    if top_up.remaining_cap.is_zero() {
+        store_bid_top_up(deps.storage, &bid, None)?;
+    } else {
+        store_bid_top_up(deps.storage, &bid, Some(top_up))?;
+    }
+
+    let prev_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom,
+    )?;
+
+    let aterra_contract = deps
+        .api
+        .addr_humanize(&top_up_config.aterra_contract)?
+        .to_string();
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: aterra_contract.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: deps.api.addr_humanize(&bid.bidder)?.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: redeem_amount.into(),
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: aterra_contract,
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: deps
+                        .api
+                        .addr_humanize(&top_up_config.market_contract)?
+                        .to_string(),
+                    amount: redeem_amount.into(),
+                    msg: to_binary(&MarketCw20HookMsg::RedeemStable {})?,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RefillBid {
+                    bid_idx,
+                    prev_balance,
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "top_up_bid"),
+            attr("bid_idx", bid_idx),
+            attr("redeem_amount", redeem_amount),
+        ]))
+}
+
+pub fn refill_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bid_idx: Uint128,
+    prev_balance: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom,
+    )?;
+    let refill_amount = balance - prev_balance;
+
+    let bid: Bid = read_bid(deps.storage, bid_idx)?;
+    deposit_to_bid(deps.storage, &env, bid, refill_amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refill_bid"),
+        attr("bid_idx", bid_idx),
+        attr("amount", refill_amount),
+    ]))
+}
+
+pub fn query_top_up_config(deps: Deps) -> StdResult<TopUpConfigResponse> {
+    let top_up_config = match read_top_up_config(deps.storage)? {
+        Some(top_up_config) => Some(TopUpConfigMsg {
+            market_contract: deps
+                .api
+                .addr_humanize(&top_up_config.market_contract)?
+                .to_string(),
+            aterra_contract: deps
+                .api
+                .addr_humanize(&top_up_config.aterra_contract)?
+                .to_string(),
+        }),
+        None => None,
+    };
+
+    Ok(TopUpConfigResponse { top_up_config })
+}
+
+pub fn query_bid_top_up(deps: Deps, bid_idx: Uint128) -> StdResult<BidTopUpResponse> {
+    Ok(BidTopUpResponse {
+        bid_idx,
+        top_up: read_bid_top_up(deps.storage, bid_idx)?.map(|top_up| BidTopUpMsg {
+            refill_amount: top_up.refill_amount,
+            cap: top_up.remaining_cap,
+        }),
+    })
+}
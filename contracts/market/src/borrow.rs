@@ -4,10 +4,12 @@ use cosmwasm_std::{
     attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
     StdResult, WasmMsg,
 };
-use moneymarket::interest_model::BorrowRateResponse;
+use moneymarket::interest_model::{BorrowRateResponse, ExecuteMsg as InterestModelExecuteMsg};
 use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
-use moneymarket::overseer::BorrowLimitResponse;
+use moneymarket::math::{checked_div, checked_sub};
+use moneymarket::overseer::{BorrowLimitResponse, ExecuteMsg as OverseerExecuteMsg};
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::reward_index::{accrue_index, pending_reward};
 
 use crate::deposit::compute_exchange_rate_raw;
 use crate::error::ContractError;
@@ -33,7 +35,8 @@ pub fn borrow_stable(
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    let record_message =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
@@ -43,7 +46,7 @@ pub fn borrow_stable(
     let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
     let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
         deps.as_ref(),
-        overseer,
+        overseer.clone(),
         borrower.clone(),
         Some(env.block.time.seconds()),
     )?;
@@ -69,6 +72,7 @@ pub fn borrow_stable(
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
 
     Ok(Response::new()
+        .add_messages(record_message)
         .add_message(CosmosMsg::Bank(BankMsg::Send {
             to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
             amount: vec![deduct_tax(
@@ -79,6 +83,14 @@ pub fn borrow_stable(
                 },
             )?],
         }))
+        // Fails the borrow when a collateral goes over its debt ceiling
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer.to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::SyncBorrowerDebt {
+                borrower: borrower.to_string(),
+            })?,
+        }))
         .add_attributes(vec![
             attr("action", "borrow_stable"),
             attr("borrower", borrower),
@@ -116,6 +128,21 @@ pub fn repay_stable_from_liquidation(
     repay_stable(deps, env, info)
 }
 
+/// Repay the loan of another borrower with the sent stables,
+/// any amount above the loan is paid back to the borrower
+pub fn repay_stable_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    // override env
+    let mut info = info;
+    info.sender = borrower;
+
+    repay_stable(deps, env, info)
+}
+
 pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
@@ -139,7 +166,7 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
-    compute_interest(
+    let record_message = compute_interest(
         deps.as_ref(),
         &config,
         &mut state,
@@ -179,11 +206,14 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
     store_state(deps.storage, &state)?;
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "repay_stable"),
-        attr("borrower", borrower),
-        attr("repay_amount", repay_amount),
-    ]))
+    Ok(Response::new()
+        .add_messages(record_message)
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "repay_stable"),
+            attr("borrower", borrower),
+            attr("repay_amount", repay_amount),
+        ]))
 }
 
 pub fn claim_rewards(
@@ -200,7 +230,8 @@ pub fn claim_rewards(
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    let record_message =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
@@ -233,23 +264,28 @@ pub fn claim_rewards(
         vec![]
     };
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "claim_rewards"),
-        attr("claim_amount", claim_amount),
-    ]))
+    Ok(Response::new()
+        .add_messages(record_message)
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "claim_rewards"),
+            attr("claim_amount", claim_amount),
+        ]))
 }
 
 /// Compute interest and update state
-/// total liabilities and total reserves
+/// total liabilities and total reserves.
+/// Returns the message recording the utilization before the accrual
+/// in the interest model, `None` when interest is already up to date
 pub fn compute_interest(
     deps: Deps,
     config: &Config,
     state: &mut State,
     block_height: u64,
     deposit_amount: Option<Uint256>,
-) -> StdResult<()> {
+) -> StdResult<Option<CosmosMsg>> {
     if state.last_interest_updated >= block_height {
-        return Ok(());
+        return Ok(None);
     }
 
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
@@ -262,6 +298,7 @@ pub fn compute_interest(
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
         deps,
         deps.api.addr_humanize(&config.interest_model)?,
+        deps.api.addr_humanize(&config.contract_addr)?,
         balance,
         state.total_liabilities,
         state.total_reserves,
@@ -270,6 +307,7 @@ pub fn compute_interest(
     let target_deposit_rate: Decimal256 =
         query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
 
+    let record_message = record_utilization_message(deps, config, state, balance)?;
     compute_interest_raw(
         state,
         block_height,
@@ -277,9 +315,28 @@ pub fn compute_interest(
         aterra_supply,
         borrow_rate_res.rate,
         target_deposit_rate,
-    );
+    )?;
 
-    Ok(())
+    Ok(Some(record_message))
+}
+
+/// Message folding the utilization of the market into the average
+/// the interest model smooths its borrow rate with
+pub(crate) fn record_utilization_message(
+    deps: Deps,
+    config: &Config,
+    state: &State,
+    balance: Uint256,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.addr_humanize(&config.interest_model)?.to_string(),
+        funds: vec![],
+        msg: to_binary(&InterestModelExecuteMsg::RecordUtilization {
+            market_balance: balance,
+            total_liabilities: state.total_liabilities,
+            total_reserves: state.total_reserves,
+        })?,
+    }))
 }
 
 // CONTRACT: to use this function as state update purpose,
@@ -294,9 +351,9 @@ pub fn compute_interest_raw(
     aterra_supply: Uint256,
     borrow_rate: Decimal256,
     target_deposit_rate: Decimal256,
-) {
+) -> StdResult<()> {
     if state.last_interest_updated >= block_height {
-        return;
+        return Ok(());
     }
 
     let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
@@ -308,9 +365,12 @@ pub fn compute_interest_raw(
         state.global_interest_index * (Decimal256::one() + interest_factor);
     state.total_liabilities += interest_accrued;
 
-    let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
-    let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
-    let deposit_rate = (effective_deposit_rate - Decimal256::one()) / passed_blocks;
+    let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance)?;
+    let effective_deposit_rate = checked_div(exchange_rate, state.prev_exchange_rate)?;
+    let deposit_rate = checked_div(
+        checked_sub(effective_deposit_rate, Decimal256::one())?,
+        passed_blocks,
+    )?;
 
     if deposit_rate > target_deposit_rate {
         // excess_deposit_rate(_per_block)
@@ -322,12 +382,14 @@ pub fn compute_interest_raw(
         let excess_yield = prev_deposits * passed_blocks * excess_deposit_rate;
 
         state.total_reserves += excess_yield;
-        exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
+        exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance)?;
     }
 
     state.prev_aterra_supply = aterra_supply;
     state.prev_exchange_rate = exchange_rate;
     state.last_interest_updated = block_height;
+
+    Ok(())
 }
 
 /// Compute new interest and apply to liability
@@ -337,7 +399,8 @@ pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerI
     liability.interest_index = state.global_interest_index;
 }
 
-/// Compute distributed reward and update global index
+/// Compute distributed reward and update global indexes
+/// of the borrowers and of the depositors
 pub fn compute_reward(state: &mut State, block_height: u64) {
     if state.last_reward_updated >= block_height {
         return;
@@ -347,18 +410,27 @@ pub fn compute_reward(state: &mut State, block_height: u64) {
     let reward_accrued = passed_blocks * state.anc_emission_rate;
     let borrow_amount = state.total_liabilities / state.global_interest_index;
 
-    if !reward_accrued.is_zero() && !borrow_amount.is_zero() {
-        state.global_reward_index += reward_accrued / borrow_amount;
-    }
+    state.global_reward_index =
+        accrue_index(state.global_reward_index, reward_accrued, borrow_amount);
+
+    // prev_aterra_supply is the supply over the passed blocks,
+    // compute_interest running first
+    state.global_deposit_reward_index = accrue_index(
+        state.global_deposit_reward_index,
+        passed_blocks * state.anc_deposit_emission_rate,
+        Decimal256::from_uint256(state.prev_aterra_supply),
+    );
 
     state.last_reward_updated = block_height;
 }
 
 /// Compute reward amount a borrower received
 pub(crate) fn compute_borrower_reward(state: &State, liability: &mut BorrowerInfo) {
-    liability.pending_rewards += Decimal256::from_uint256(liability.loan_amount)
-        / state.global_interest_index
-        * (state.global_reward_index - liability.reward_index);
+    liability.pending_rewards += pending_reward(
+        state.global_reward_index,
+        liability.reward_index,
+        Decimal256::from_uint256(liability.loan_amount) / state.global_interest_index,
+    );
     liability.reward_index = state.global_reward_index;
 }
 
@@ -3,18 +3,18 @@ use cosmwasm_std::entry_point;
 
 use crate::borrow::{
     borrow_stable, claim_rewards, compute_interest, compute_interest_raw, compute_reward,
-    query_borrower_info, query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    query_borrower_info, query_borrower_infos, record_utilization_message, repay_stable,
+    repay_stable_for, repay_stable_from_liquidation,
 };
 use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
 use crate::error::ContractError;
 use crate::querier::{query_anc_emission_rate, query_borrow_rate, query_target_deposit_rate};
-use crate::response::MsgInstantiateContractResponse;
 use crate::state::{read_config, read_state, store_config, store_state, Config, State};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps,
-    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    DepsMut, Env, MessageInfo, Reply, Response, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
 
@@ -24,8 +24,8 @@ use moneymarket::market::{
     ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
     StateResponse,
 };
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
-use protobuf::Message;
+use moneymarket::querier::{deduct_tax, query_balance, query_supply, store_fee_model, FeeModel};
+use moneymarket::reply::parse_instantiate_contract_address;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 1000000;
@@ -77,6 +77,8 @@ pub fn instantiate(
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: msg.anc_emission_rate,
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -154,6 +156,7 @@ pub fn execute(
                 max_borrow_factor,
             )
         }
+        ExecuteMsg::UpdateFeeModel { fee_model } => update_fee_model(deps, info, fee_model),
         ExecuteMsg::ExecuteEpochOperations {
             deposit_rate,
             target_deposit_rate,
@@ -180,6 +183,10 @@ pub fn execute(
             )
         }
         ExecuteMsg::RepayStable {} => repay_stable(deps, env, info),
+        ExecuteMsg::RepayStableFor { borrower } => {
+            let api = deps.api;
+            repay_stable_for(deps, env, info, api.addr_validate(&borrower)?)
+        }
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
@@ -205,16 +212,9 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
     match msg.id {
         1 => {
             // get new token's contract address
-            let res: MsgInstantiateContractResponse = Message::parse_from_bytes(
+            let token_addr = Addr::unchecked(parse_instantiate_contract_address(
                 msg.result.unwrap().data.unwrap().as_slice(),
-            )
-            .map_err(|_| {
-                ContractError::Std(StdError::parse_err(
-                    "MsgInstantiateContractResponse",
-                    "failed to parse data",
-                ))
-            })?;
-            let token_addr = Addr::unchecked(res.get_contract_address());
+            )?);
 
             register_aterra(deps, token_addr)
         }
@@ -304,9 +304,11 @@ pub fn update_config(
         config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
     }
 
+    let mut record_message: Option<CosmosMsg> = None;
     if interest_model.is_some() {
         let mut state: State = read_state(deps.storage)?;
-        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+        record_message =
+            compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
         store_state(deps.storage, &state)?;
 
         if let Some(interest_model) = interest_model {
@@ -323,7 +325,27 @@ pub fn update_config(
     }
 
     store_config(deps.storage, &config)?;
-    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+    Ok(Response::new()
+        .add_messages(record_message)
+        .add_attributes(vec![attr("action", "update_config")]))
+}
+
+pub fn update_fee_model(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_model: FeeModel,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_fee_model(deps.storage, &fee_model)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fee_model"),
+        attr("fee_model", fee_model.to_string()),
+    ]))
 }
 
 pub fn execute_epoch_operations(
@@ -356,11 +378,22 @@ pub fn execute_epoch_operations(
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
         deps.as_ref(),
         deps.api.addr_humanize(&config.interest_model)?,
+        deps.api.addr_humanize(&config.contract_addr)?,
         balance,
         state.total_liabilities,
         state.total_reserves,
     )?;
 
+    let record_message = if state.last_interest_updated < env.block.height {
+        Some(record_utilization_message(
+            deps.as_ref(),
+            &config,
+            &state,
+            balance,
+        )?)
+    } else {
+        None
+    };
     compute_interest_raw(
         &mut state,
         env.block.height,
@@ -368,11 +401,11 @@ pub fn execute_epoch_operations(
         aterra_supply,
         borrow_rate_res.rate,
         target_deposit_rate,
-    );
+    )?;
 
     // recompute prev_exchange_rate with distributed_interest
     state.prev_exchange_rate =
-        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
+        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest)?;
 
     compute_reward(&mut state, env.block.height);
 
@@ -400,24 +433,32 @@ pub fn execute_epoch_operations(
         vec![]
     };
 
-    // Query updated anc_emission_rate
-    state.anc_emission_rate = query_anc_emission_rate(
+    // Query updated anc_emission_rate, split between borrowers and depositors
+    let anc_emission_rate = query_anc_emission_rate(
         deps.as_ref(),
         deps.api.addr_humanize(&config.distribution_model)?,
         deposit_rate,
         target_deposit_rate,
         threshold_deposit_rate,
-        state.anc_emission_rate,
-    )?
-    .emission_rate;
+        state.anc_emission_rate + state.anc_deposit_emission_rate,
+    )?;
+    state.anc_emission_rate = anc_emission_rate.borrower_emission_rate;
+    state.anc_deposit_emission_rate = anc_emission_rate.depositor_emission_rate;
 
     store_state(deps.storage, &state)?;
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_epoch_operations"),
-        attr("total_reserves", total_reserves),
-        attr("anc_emission_rate", state.anc_emission_rate.to_string()),
-    ]))
+    Ok(Response::new()
+        .add_messages(record_message)
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "execute_epoch_operations"),
+            attr("total_reserves", total_reserves),
+            attr("anc_emission_rate", state.anc_emission_rate.to_string()),
+            attr(
+                "anc_deposit_emission_rate",
+                state.anc_deposit_emission_rate.to_string(),
+            ),
+        ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -487,15 +528,11 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
     };
 
     if block_height < state.last_interest_updated {
-        return Err(StdError::generic_err(
-            "block_height must bigger than last_interest_updated",
-        ));
+        return Err(ContractError::InterestUpdatedAfterBlock(state.last_interest_updated).into());
     }
 
     if block_height < state.last_reward_updated {
-        return Err(StdError::generic_err(
-            "block_height must bigger than last_reward_updated",
-        ));
+        return Err(ContractError::RewardUpdatedAfterBlock(state.last_reward_updated).into());
     }
 
     let config: Config = read_config(deps.storage)?;
@@ -514,6 +551,8 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         global_interest_index: state.global_interest_index,
         global_reward_index: state.global_reward_index,
         anc_emission_rate: state.anc_emission_rate,
+        global_deposit_reward_index: state.global_deposit_reward_index,
+        anc_deposit_emission_rate: state.anc_deposit_emission_rate,
         prev_aterra_supply: state.prev_aterra_supply,
         prev_exchange_rate: state.prev_exchange_rate,
     })
@@ -537,14 +576,15 @@ pub fn query_epoch_state(
 
     if let Some(block_height) = block_height {
         if block_height < state.last_interest_updated {
-            return Err(StdError::generic_err(
-                "block_height must bigger than last_interest_updated",
-            ));
+            return Err(
+                ContractError::InterestUpdatedAfterBlock(state.last_interest_updated).into(),
+            );
         }
 
         let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
             deps,
             deps.api.addr_humanize(&config.interest_model)?,
+            deps.api.addr_humanize(&config.contract_addr)?,
             balance,
             state.total_liabilities,
             state.total_reserves,
@@ -561,13 +601,13 @@ pub fn query_epoch_state(
             aterra_supply,
             borrow_rate_res.rate,
             target_deposit_rate,
-        );
+        )?;
     }
 
     // compute_interest_raw store current exchange rate
     // as prev_exchange_rate, so just return prev_exchange_rate
     let exchange_rate =
-        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
+        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest)?;
 
     Ok(EpochStateResponse {
         exchange_rate,
@@ -9,6 +9,9 @@ use crate::error::ContractError;
 use crate::state::{read_config, read_state, store_state, Config, State};
 
 use cw20::Cw20ExecuteMsg;
+use moneymarket::math::{
+    checked_add, checked_div, checked_from_uint256, checked_sub, div_dec, mul_dec, Rounding,
+};
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
 
 pub fn deposit_stable(
@@ -33,7 +36,7 @@ pub fn deposit_stable(
 
     // Update interest related state
     let mut state: State = read_state(deps.storage)?;
-    compute_interest(
+    let record_message = compute_interest(
         deps.as_ref(),
         &config,
         &mut state,
@@ -45,11 +48,12 @@ pub fn deposit_stable(
     // Load anchor token exchange rate with updated state
     let exchange_rate =
         compute_exchange_rate(deps.as_ref(), &config, &state, Some(deposit_amount))?;
-    let mint_amount = deposit_amount / exchange_rate;
+    let mint_amount = div_dec(deposit_amount, exchange_rate, Rounding::Down)?;
 
     state.prev_aterra_supply += mint_amount;
     store_state(deps.storage, &state)?;
     Ok(Response::new()
+        .add_messages(record_message)
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
             funds: vec![],
@@ -76,12 +80,13 @@ pub fn redeem_stable(
 
     // Update interest related state
     let mut state: State = read_state(deps.storage)?;
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    let record_message =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
     compute_reward(&mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
     let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
-    let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
+    let redeem_amount = mul_dec(Uint256::from(burn_amount), exchange_rate, Rounding::Down)?;
 
     let current_balance = query_balance(
         deps.as_ref(),
@@ -95,6 +100,7 @@ pub fn redeem_stable(
     state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
     store_state(deps.storage, &state)?;
     Ok(Response::new()
+        .add_messages(record_message)
         .add_messages(vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
@@ -151,20 +157,26 @@ pub(crate) fn compute_exchange_rate(
         config.stable_denom.to_string(),
     )? - deposit_amount.unwrap_or_else(Uint256::zero);
 
-    Ok(compute_exchange_rate_raw(state, aterra_supply, balance))
+    compute_exchange_rate_raw(state, aterra_supply, balance)
 }
 
 pub fn compute_exchange_rate_raw(
     state: &State,
     aterra_supply: Uint256,
     contract_balance: Uint256,
-) -> Decimal256 {
+) -> StdResult<Decimal256> {
     if aterra_supply.is_zero() {
-        return Decimal256::one();
+        return Ok(Decimal256::one());
     }
 
     // (aterra / stable_denom)
     // exchange_rate = (balance + total_liabilities - total_reserves) / aterra_supply
-    (Decimal256::from_uint256(contract_balance) + state.total_liabilities - state.total_reserves)
-        / Decimal256::from_uint256(aterra_supply)
+    let market_value = checked_sub(
+        checked_add(
+            checked_from_uint256(contract_balance)?,
+            state.total_liabilities,
+        )?,
+        state.total_reserves,
+    )?;
+    checked_div(market_value, checked_from_uint256(aterra_supply)?)
 }
@@ -35,4 +35,20 @@ pub enum ContractError {
 
     #[error("Repay amount must be greater than 0 {0}")]
     ZeroRepay(String),
+
+    #[error("block_height must bigger than last_interest_updated: {0}")]
+    InterestUpdatedAfterBlock(u64),
+
+    #[error("block_height must bigger than last_reward_updated: {0}")]
+    RewardUpdatedAfterBlock(u64),
+}
+
+// Queries only return standard errors
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
 }
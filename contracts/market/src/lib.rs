@@ -3,7 +3,6 @@ pub mod contract;
 pub mod deposit;
 pub mod error;
 pub mod querier;
-pub mod response;
 pub mod state;
 
 #[cfg(test)]
@@ -6,6 +6,7 @@ use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
 use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
 
 use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::pagination::{calc_limit, calc_range_start};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
@@ -35,6 +36,8 @@ pub struct State {
     pub global_interest_index: Decimal256,
     pub global_reward_index: Decimal256,
     pub anc_emission_rate: Decimal256,
+    pub global_deposit_reward_index: Decimal256,
+    pub anc_deposit_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
 }
@@ -83,9 +86,6 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
     }
 }
 
-// settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
 pub fn read_borrower_infos(
     deps: Deps,
     start_after: Option<CanonicalAddr>,
@@ -94,7 +94,7 @@ pub fn read_borrower_infos(
     let liability_bucket: ReadonlyBucket<BorrowerInfo> =
         bucket_read(deps.storage, PREFIX_LIABILITY);
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit(limit);
     let start = calc_range_start(start_after);
 
     liability_bucket
@@ -113,12 +113,3 @@ pub fn read_borrower_infos(
         })
         .collect()
 }
-
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
-    start_after.map(|addr| {
-        let mut v = addr.as_slice().to_vec();
-        v.push(1);
-        v
-    })
-}
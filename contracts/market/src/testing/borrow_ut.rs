@@ -1,4 +1,4 @@
-use crate::borrow::{compute_borrower_interest, compute_interest};
+use crate::borrow::{compute_borrower_interest, compute_interest, compute_reward};
 use crate::state::{store_state, BorrowerInfo, Config, State};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -16,6 +16,8 @@ fn proper_compute_borrower_interest() {
         global_interest_index: Decimal256::one(),
         global_reward_index: Decimal256::zero(),
         anc_emission_rate: Decimal256::one(),
+        global_deposit_reward_index: Decimal256::zero(),
+        anc_deposit_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
     };
@@ -42,6 +44,8 @@ fn proper_compute_borrower_interest() {
         global_interest_index: Decimal256::from_uint256(2u128),
         global_reward_index: Decimal256::zero(),
         anc_emission_rate: Decimal256::zero(),
+        global_deposit_reward_index: Decimal256::zero(),
+        anc_deposit_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
     };
@@ -99,6 +103,8 @@ fn proper_compute_interest() {
         global_interest_index: Decimal256::one(),
         global_reward_index: Decimal256::zero(),
         anc_emission_rate: Decimal256::one(),
+        global_deposit_reward_index: Decimal256::zero(),
+        anc_deposit_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
     };
@@ -124,6 +130,8 @@ fn proper_compute_interest() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -149,6 +157,8 @@ fn proper_compute_interest() {
             global_interest_index: Decimal256::from_uint256(2u128),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_ratio(19995, 10000),
         }
@@ -163,6 +173,8 @@ fn proper_compute_interest() {
         global_interest_index: Decimal256::one(),
         global_reward_index: Decimal256::zero(),
         anc_emission_rate: Decimal256::one(),
+        global_deposit_reward_index: Decimal256::zero(),
+        anc_deposit_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::from(2000000u128),
         prev_exchange_rate: Decimal256::one(),
     };
@@ -198,8 +210,41 @@ fn proper_compute_interest() {
             global_interest_index: Decimal256::from_uint256(2u128),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_uint256(2u64),
         }
     );
 }
+
+#[test]
+fn proper_compute_reward() {
+    let env = mock_env();
+    let mut state = State {
+        total_liabilities: Decimal256::from_uint256(2000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.height,
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::from_uint256(2u128),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::from_uint256(3u128),
+        global_deposit_reward_index: Decimal256::zero(),
+        anc_deposit_emission_rate: Decimal256::from_uint256(1u128),
+        prev_aterra_supply: Uint256::from(500u128),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    // 10 blocks of 3 ANC over 1000 borrowed
+    // and of 1 ANC over 500 aterra
+    compute_reward(&mut state, env.block.height + 10);
+    assert_eq!(state.global_reward_index, Decimal256::percent(3));
+    assert_eq!(state.global_deposit_reward_index, Decimal256::percent(2));
+    assert_eq!(state.last_reward_updated, env.block.height + 10);
+
+    // no aterra to accrue to
+    state.prev_aterra_supply = Uint256::zero();
+    compute_reward(&mut state, env.block.height + 20);
+    assert_eq!(state.global_reward_index, Decimal256::percent(6));
+    assert_eq!(state.global_deposit_reward_index, Decimal256::percent(2));
+}
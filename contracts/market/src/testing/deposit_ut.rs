@@ -38,6 +38,8 @@ fn proper_compute_exchange_rate() {
         global_interest_index: Decimal256::one(),
         global_reward_index: Decimal256::zero(),
         anc_emission_rate: Decimal256::one(),
+        global_deposit_reward_index: Decimal256::zero(),
+        anc_deposit_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
     };
@@ -251,6 +251,8 @@ impl WasmMockQuerier {
                     } => SystemResult::Ok(ContractResult::from(to_binary(
                         &AncEmissionRateResponse {
                             emission_rate: Decimal256::from_uint256(5u64),
+                            borrower_emission_rate: Decimal256::from_uint256(5u64),
+                            depositor_emission_rate: Decimal256::zero(),
                         },
                     ))),
                     QueryMsg::Config {} => {
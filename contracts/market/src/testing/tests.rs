@@ -1,8 +1,8 @@
 use crate::contract::{execute, instantiate, query, reply, INITIAL_DEPOSIT_AMOUNT};
 use crate::error::ContractError;
-use crate::response::MsgInstantiateContractResponse;
 use crate::state::{read_borrower_infos, read_state, store_state, State};
 use crate::testing::mock_querier::mock_dependencies;
+use moneymarket::response::MsgInstantiateContractResponse;
 
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -12,10 +12,12 @@ use cosmwasm_std::{
     SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use moneymarket::interest_model::ExecuteMsg as InterestModelExecuteMsg;
 use moneymarket::market::{
     BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
     StateResponse,
 };
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
 use moneymarket::querier::deduct_tax;
 use protobuf::Message;
 use std::str::FromStr;
@@ -519,6 +521,8 @@ fn deposit_stable() {
             last_interest_updated: mock_env().block.height,
             last_reward_updated: mock_env().block.height,
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::from(1000000u64),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -558,6 +562,8 @@ fn deposit_stable() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
         },
@@ -608,6 +614,8 @@ fn deposit_stable() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
         },
@@ -635,6 +643,8 @@ fn deposit_stable() {
             last_interest_updated: env.block.height,
             last_reward_updated: env.block.height,
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1818181),
             prev_exchange_rate: Decimal256::from_ratio(55u64, 100u64),
         }
@@ -772,6 +782,8 @@ fn redeem_stable() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -898,6 +910,8 @@ fn borrow_stable() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -932,17 +946,37 @@ fn borrow_stable() {
 
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(500000u128),
-                }
-            )
-            .unwrap()],
-        }))]
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "interest".to_string(),
+                funds: vec![],
+                msg: to_binary(&InterestModelExecuteMsg::RecordUtilization {
+                    market_balance: Uint256::from(1000000u64),
+                    total_liabilities: Decimal256::from_uint256(1000000u64),
+                    total_reserves: Decimal256::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(500000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "overseer".to_string(),
+                funds: vec![],
+                msg: to_binary(&OverseerExecuteMsg::SyncBorrowerDebt {
+                    borrower: "addr0000".to_string(),
+                })
+                .unwrap(),
+            }))
+        ]
     );
 
     assert_eq!(
@@ -963,6 +997,8 @@ fn borrow_stable() {
             global_interest_index: Decimal256::from_uint256(2u128),
             global_reward_index: Decimal256::from_str("0.0001").unwrap(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -989,6 +1025,8 @@ fn borrow_stable() {
             global_interest_index: Decimal256::from_str("2.02").unwrap(),
             global_reward_index: Decimal256::from_str("0.0001008").unwrap(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -1142,6 +1180,8 @@ fn assert_max_borrow_factor() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -1253,6 +1293,8 @@ fn repay_stable() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -1436,6 +1478,8 @@ fn repay_stable_from_liquidation() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -1607,6 +1651,8 @@ fn claim_rewards() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -1636,15 +1682,27 @@ fn claim_rewards() {
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "distributor".to_string(),
-            funds: vec![],
-            msg: to_binary(&FaucetExecuteMsg::Spend {
-                recipient: "addr0001".to_string(),
-                amount: Uint128::from(33u128),
-            })
-            .unwrap(),
-        }))]
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "interest".to_string(),
+                funds: vec![],
+                msg: to_binary(&InterestModelExecuteMsg::RecordUtilization {
+                    market_balance: Uint256::from(1000000u64),
+                    total_liabilities: Decimal256::from_uint256(1500000u64),
+                    total_reserves: Decimal256::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "distributor".to_string(),
+                funds: vec![],
+                msg: to_binary(&FaucetExecuteMsg::Spend {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(33u128),
+                })
+                .unwrap(),
+            }))
+        ]
     );
 
     let res: BorrowerInfoResponse = from_binary(
@@ -1738,6 +1796,8 @@ fn execute_epoch_operations() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -1765,13 +1825,25 @@ fn execute_epoch_operations() {
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "collector".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(2970u128), // 1% tax
-            }],
-        }))]
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "interest".to_string(),
+                funds: vec![],
+                msg: to_binary(&InterestModelExecuteMsg::RecordUtilization {
+                    market_balance: Uint256::from(1000000u64),
+                    total_liabilities: Decimal256::from_uint256(1000000u64),
+                    total_reserves: Decimal256::from_uint256(3000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2970u128), // 1% tax
+                }],
+            }))
+        ]
     );
 
     let state = read_state(deps.as_ref().storage).unwrap();
@@ -1785,6 +1857,8 @@ fn execute_epoch_operations() {
             global_interest_index: Decimal256::from_uint256(2u64),
             global_reward_index: Decimal256::from_str("0.0001").unwrap(),
             anc_emission_rate: Decimal256::from_uint256(5u64),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -1812,6 +1886,8 @@ fn execute_epoch_operations() {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         },
@@ -1828,8 +1904,21 @@ fn execute_epoch_operations() {
         distributed_interest: Uint256::zero(),
     };
 
+    // the reserves are not sent, only the utilization is recorded
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "interest".to_string(),
+            funds: vec![],
+            msg: to_binary(&InterestModelExecuteMsg::RecordUtilization {
+                market_balance: Uint256::from(2999u64),
+                total_liabilities: Decimal256::from_uint256(1000000u64),
+                total_reserves: Decimal256::from_uint256(3000u64),
+            })
+            .unwrap(),
+        }))]
+    );
 
     let state = read_state(deps.as_ref().storage).unwrap();
     assert_eq!(
@@ -1842,6 +1931,8 @@ fn execute_epoch_operations() {
             global_interest_index: Decimal256::from_uint256(2u64),
             global_reward_index: Decimal256::from_str("0.0001").unwrap(),
             anc_emission_rate: Decimal256::from_uint256(5u64),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -1938,3 +2029,141 @@ fn execute_epoch_operations() {
 //     // only overseer can execute this
 //     let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
 // }
+
+#[test]
+fn repay_stable_for() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            global_deposit_reward_index: Decimal256::zero(),
+            anc_deposit_emission_rate: Decimal256::zero(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RepayStableFor {
+        borrower: "addr0000".to_string(),
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u64),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+        ]
+    );
+
+    // the amount above the loan goes back to the borrower
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u64),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "400000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+}
@@ -0,0 +1,220 @@
+use crate::circuit_breaker::deviation;
+use crate::error::ContractError;
+use crate::state::{
+    read_amm_feed, read_amm_snapshots, read_config, store_amm_feed, store_amm_snapshots,
+    AmmFeedInfo, AmmSnapshot, AmmSnapshots, Config,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response, StdError, StdResult,
+    Uint128, WasmQuery,
+};
+use moneymarket::oracle::{AmmFeed, AmmFeedResponse, AmmTwapResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// scale of the pair cumulative prices
+const AMM_PRICE_PRECISION: u128 = 1_000_000;
+
+/// Query of the AMM pair for its cumulative prices
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairQueryMsg {
+    CumulativePrices {},
+}
+
+/// Fields of the pair cumulative prices the TWAP is computed from,
+/// accumulated up to the query block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CumulativePricesResponse {
+    pub price0_cumulative_last: Uint128,
+    pub price1_cumulative_last: Uint128,
+}
+
+pub fn update_amm_feed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+    amm_feed: Option<AmmFeed>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amm_feed_info = match amm_feed.as_ref() {
+        Some(amm_feed) => {
+            if amm_feed.asset_index > 1 || amm_feed.window == 0 {
+                return Err(ContractError::InvalidAmmFeed {});
+            }
+
+            Some(AmmFeedInfo {
+                pair_contract: deps.api.addr_canonicalize(&amm_feed.pair_contract)?,
+                asset_index: amm_feed.asset_index,
+                window: amm_feed.window,
+                max_deviation: amm_feed.max_deviation,
+            })
+        }
+        None => None,
+    };
+
+    // the TWAP of the new pair starts from now
+    let amm_snapshots = match amm_feed_info.as_ref() {
+        Some(amm_feed) => Some(AmmSnapshots {
+            current: AmmSnapshot {
+                time: env.block.time.seconds(),
+                cumulative_price: query_cumulative_price(deps.as_ref(), amm_feed)?,
+            },
+            previous: None,
+        }),
+        None => None,
+    };
+    store_amm_feed(deps.storage, &asset, amm_feed_info)?;
+    store_amm_snapshots(deps.storage, &asset, amm_snapshots)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_amm_feed"),
+        attr("asset", asset),
+        attr(
+            "pair_contract",
+            amm_feed
+                .map(|amm_feed| amm_feed.pair_contract)
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn snapshot_amm_price(
+    deps: DepsMut,
+    env: Env,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let amm_feed = read_amm_feed(deps.storage, &asset)?.ok_or(ContractError::NoAmmFeed {})?;
+    let amm_snapshots = read_amm_snapshots(deps.storage, &asset)?;
+
+    let time = env.block.time.seconds();
+    let recorded = match amm_snapshots {
+        Some(amm_snapshots) if amm_snapshots.current.time + amm_feed.window > time => false,
+        amm_snapshots => {
+            let cumulative_price = query_cumulative_price(deps.as_ref(), &amm_feed)?;
+            store_amm_snapshots(
+                deps.storage,
+                &asset,
+                Some(AmmSnapshots {
+                    current: AmmSnapshot {
+                        time,
+                        cumulative_price,
+                    },
+                    previous: amm_snapshots.map(|amm_snapshots| amm_snapshots.current),
+                }),
+            )?;
+            true
+        }
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "snapshot_amm_price"),
+        attr("asset", asset),
+        attr("recorded", recorded.to_string()),
+    ]))
+}
+
+fn query_cumulative_price(deps: Deps, amm_feed: &AmmFeedInfo) -> StdResult<Uint128> {
+    let cumulative_prices: CumulativePricesResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps.api.addr_humanize(&amm_feed.pair_contract)?.to_string(),
+            msg: to_binary(&PairQueryMsg::CumulativePrices {})?,
+        }))?;
+
+    Ok(if amm_feed.asset_index == 0 {
+        cumulative_prices.price0_cumulative_last
+    } else {
+        cumulative_prices.price1_cumulative_last
+    })
+}
+
+/// TWAP of the pair since the latest snapshot as old as the window, and
+/// the seconds it is computed over
+fn amm_twap(deps: Deps, env: &Env, asset: &str) -> StdResult<Option<(Decimal256, u64)>> {
+    let amm_feed = match read_amm_feed(deps.storage, asset)? {
+        Some(amm_feed) => amm_feed,
+        None => return Ok(None),
+    };
+    let amm_snapshots = match read_amm_snapshots(deps.storage, asset)? {
+        Some(amm_snapshots) => amm_snapshots,
+        None => return Ok(None),
+    };
+
+    let time = env.block.time.seconds();
+    let snapshot = match amm_snapshots.previous {
+        _ if amm_snapshots.current.time + amm_feed.window <= time => amm_snapshots.current,
+        Some(previous) if previous.time + amm_feed.window <= time => previous,
+        _ => return Ok(None),
+    };
+
+    // cumulative prices wrap around on overflow
+    let cumulative_price = query_cumulative_price(deps, &amm_feed)?;
+    let window = time - snapshot.time;
+    Ok(Some((
+        Decimal256::from_uint256(Uint256::from(
+            cumulative_price
+                .wrapping_sub(snapshot.cumulative_price)
+                .u128(),
+        )) / Decimal256::from_uint256(Uint256::from(AMM_PRICE_PRECISION * window as u128)),
+        window,
+    )))
+}
+
+/// Reject the price when it deviates from the AMM TWAP of the asset by
+/// more than the max deviation. Assets without a TWAP yet are not checked
+pub(crate) fn check_amm_price(
+    deps: Deps,
+    env: &Env,
+    asset: &str,
+    price: Decimal256,
+) -> StdResult<()> {
+    let amm_feed = match read_amm_feed(deps.storage, asset)? {
+        Some(amm_feed) => amm_feed,
+        None => return Ok(()),
+    };
+
+    if let Some((amm_rate, _)) = amm_twap(deps, env, asset)? {
+        if deviation(amm_rate, price) > amm_feed.max_deviation {
+            return Err(StdError::generic_err(
+                ContractError::AmmPriceDeviation(asset.to_string()).to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn query_amm_feed(deps: Deps, asset: String) -> StdResult<AmmFeedResponse> {
+    let amm_feed = read_amm_feed(deps.storage, &asset)?;
+
+    Ok(AmmFeedResponse {
+        asset,
+        amm_feed: amm_feed
+            .map(|amm_feed| {
+                Ok::<AmmFeed, StdError>(AmmFeed {
+                    pair_contract: deps.api.addr_humanize(&amm_feed.pair_contract)?.to_string(),
+                    asset_index: amm_feed.asset_index,
+                    window: amm_feed.window,
+                    max_deviation: amm_feed.max_deviation,
+                })
+            })
+            .transpose()?,
+    })
+}
+
+pub fn query_amm_twap(deps: Deps, env: Env, asset: String) -> StdResult<AmmTwapResponse> {
+    let (rate, window) = amm_twap(deps, &env, &asset)?
+        .ok_or_else(|| StdError::generic_err(ContractError::NoAmmTwap {}.to_string()))?;
+
+    Ok(AmmTwapResponse {
+        asset,
+        rate,
+        window,
+    })
+}
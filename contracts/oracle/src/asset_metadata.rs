@@ -0,0 +1,73 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_asset_metadata, read_config, store_asset_metadata, AssetMetadataInfo, Config,
+};
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult};
+use moneymarket::oracle::{AssetMetadata, AssetMetadataResponse, PriceSource};
+
+pub fn update_asset_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    asset_metadata: Option<AssetMetadata>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![
+        attr("action", "update_asset_metadata"),
+        attr("asset", asset.clone()),
+    ];
+    let asset_metadata_info = match asset_metadata {
+        Some(asset_metadata) => {
+            if asset_metadata.source == PriceSource::ExchangeRate
+                && asset_metadata.hub_contract.is_none()
+            {
+                return Err(ContractError::InvalidAssetMetadata {});
+            }
+
+            attributes.push(attr("decimals", asset_metadata.decimals.to_string()));
+            attributes.push(attr("source", asset_metadata.source.to_string()));
+            Some(AssetMetadataInfo {
+                decimals: asset_metadata.decimals,
+                source: asset_metadata.source,
+                hub_contract: asset_metadata
+                    .hub_contract
+                    .map(|hub_contract| deps.api.addr_canonicalize(&hub_contract))
+                    .transpose()?,
+                category: asset_metadata.category,
+            })
+        }
+        None => None,
+    };
+    store_asset_metadata(deps.storage, &asset, asset_metadata_info)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn query_asset_metadata(deps: Deps, asset: String) -> StdResult<AssetMetadataResponse> {
+    let asset_metadata = read_asset_metadata(deps.storage, &asset)?;
+
+    Ok(AssetMetadataResponse {
+        asset,
+        asset_metadata: asset_metadata
+            .map(|asset_metadata| {
+                Ok::<AssetMetadata, StdError>(AssetMetadata {
+                    decimals: asset_metadata.decimals,
+                    source: asset_metadata.source,
+                    hub_contract: asset_metadata
+                        .hub_contract
+                        .map(|hub_contract| {
+                            Ok::<String, StdError>(
+                                deps.api.addr_humanize(&hub_contract)?.to_string(),
+                            )
+                        })
+                        .transpose()?,
+                    category: asset_metadata.category,
+                })
+            })
+            .transpose()?,
+    })
+}
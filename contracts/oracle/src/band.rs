@@ -0,0 +1,120 @@
+use crate::error::ContractError;
+use crate::state::{read_band_feed, read_config, store_band_feed, BandFeedInfo, Config, PriceInfo};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response, StdError, StdResult,
+    WasmQuery,
+};
+use moneymarket::oracle::{BandFeed, BandFeedResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Query of the Band std-reference contract for a pair
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BandQueryMsg {
+    GetReferenceData {
+        base_symbol: String,
+        quote_symbol: String,
+    },
+}
+
+/// `rate` is scaled by 10^18
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferenceData {
+    pub rate: Uint256,
+    pub last_updated_base: u64,
+    pub last_updated_quote: u64,
+}
+
+pub fn update_band_feed(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    band_feed: Option<BandFeed>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let band_feed_info = match band_feed.as_ref() {
+        Some(band_feed) => Some(BandFeedInfo {
+            contract_addr: deps.api.addr_canonicalize(&band_feed.contract_addr)?,
+            base_symbol: band_feed.base_symbol.clone(),
+            quote_symbol: band_feed.quote_symbol.clone(),
+            max_price_age: band_feed.max_price_age,
+        }),
+        None => None,
+    };
+    store_band_feed(deps.storage, &asset, band_feed_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_band_feed"),
+        attr("asset", asset),
+        attr(
+            "pair",
+            band_feed
+                .map(|band_feed| format!("{}/{}", band_feed.base_symbol, band_feed.quote_symbol))
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Price of the Band pair, updated when both of its symbols were.
+/// The price is rejected when older than the max price age
+pub(crate) fn query_band_price(
+    deps: Deps,
+    env: &Env,
+    band_feed: &BandFeedInfo,
+) -> StdResult<PriceInfo> {
+    let reference_data: ReferenceData =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps
+                .api
+                .addr_humanize(&band_feed.contract_addr)?
+                .to_string(),
+            msg: to_binary(&BandQueryMsg::GetReferenceData {
+                base_symbol: band_feed.base_symbol.clone(),
+                quote_symbol: band_feed.quote_symbol.clone(),
+            })?,
+        }))?;
+
+    let last_updated_time = std::cmp::min(
+        reference_data.last_updated_base,
+        reference_data.last_updated_quote,
+    );
+    if last_updated_time + band_feed.max_price_age < env.block.time.seconds() {
+        return Err(StdError::generic_err("Band price is too old"));
+    }
+    if reference_data.rate.is_zero() {
+        return Err(StdError::generic_err("Band price must be positive"));
+    }
+
+    Ok(PriceInfo {
+        price: Decimal256::from_uint256(reference_data.rate)
+            / Decimal256::from_uint256(Uint256::from(1_000_000_000_000_000_000u128)),
+        last_updated_time,
+    })
+}
+
+pub fn query_band_feed(deps: Deps, asset: String) -> StdResult<BandFeedResponse> {
+    let band_feed = read_band_feed(deps.storage, &asset)?;
+
+    Ok(BandFeedResponse {
+        asset,
+        band_feed: band_feed
+            .map(|band_feed| {
+                Ok::<BandFeed, StdError>(BandFeed {
+                    contract_addr: deps
+                        .api
+                        .addr_humanize(&band_feed.contract_addr)?
+                        .to_string(),
+                    base_symbol: band_feed.base_symbol,
+                    quote_symbol: band_feed.quote_symbol,
+                    max_price_age: band_feed.max_price_age,
+                })
+            })
+            .transpose()?,
+    })
+}
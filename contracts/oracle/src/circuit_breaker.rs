@@ -0,0 +1,149 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_deviation_guard, read_pending_price, read_price, store_deviation_guard,
+    store_pending_price, store_price, store_price_checkpoint, Config, DeviationGuard, PriceInfo,
+};
+use crate::subscription::subscription_messages;
+use crate::twap::record_observation;
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use moneymarket::oracle::{
+    DeviationGuard as DeviationGuardMsg, DeviationGuardResponse, PendingPriceResponse,
+};
+
+pub fn update_deviation_guard(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    deviation_guard: Option<DeviationGuardMsg>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![
+        attr("action", "update_deviation_guard"),
+        attr("asset", asset.clone()),
+    ];
+    let deviation_guard = deviation_guard.map(|deviation_guard| {
+        attributes.push(attr(
+            "max_deviation",
+            deviation_guard.max_deviation.to_string(),
+        ));
+        attributes.push(attr("window", deviation_guard.window.to_string()));
+        DeviationGuard {
+            max_deviation: deviation_guard.max_deviation,
+            window: deviation_guard.window,
+        }
+    });
+
+    store_pending_price(deps.storage, &asset, None)?;
+    store_deviation_guard(deps.storage, &asset, deviation_guard)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// The fed price to apply, or `None` when it is held. A held price is
+/// confirmed by a later feed within the max deviation of it
+pub(crate) fn guard_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    price: PriceInfo,
+) -> StdResult<Option<PriceInfo>> {
+    let deviation_guard = match read_deviation_guard(storage, asset)? {
+        Some(deviation_guard) => deviation_guard,
+        None => return Ok(Some(price)),
+    };
+
+    let within_guard = match read_price(storage, asset) {
+        Ok(prev_price) => {
+            prev_price.last_updated_time + deviation_guard.window < price.last_updated_time
+                || deviation(prev_price.price, price.price) <= deviation_guard.max_deviation
+        }
+        Err(_) => true,
+    };
+    let confirmed = match read_pending_price(storage, asset)? {
+        Some(pending_price) => {
+            pending_price.last_updated_time < price.last_updated_time
+                && deviation(pending_price.price, price.price) <= deviation_guard.max_deviation
+        }
+        None => false,
+    };
+
+    if within_guard || confirmed {
+        store_pending_price(storage, asset, None)?;
+        return Ok(Some(price));
+    }
+
+    store_pending_price(storage, asset, Some(price))?;
+    Ok(None)
+}
+
+pub fn confirm_pending_price(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pending_price =
+        read_pending_price(deps.storage, &asset)?.ok_or(ContractError::NoPendingPrice {})?;
+    store_pending_price(deps.storage, &asset, None)?;
+    store_price(deps.storage, &asset, &pending_price)?;
+    store_price_checkpoint(deps.storage, &asset, &pending_price)?;
+    record_observation(
+        deps.storage,
+        &asset,
+        pending_price.price,
+        env.block.time.seconds(),
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(subscription_messages(deps.branch(), &asset)?)
+        .add_attributes(vec![
+            attr("action", "confirm_pending_price"),
+            attr("asset", asset),
+            attr("price", pending_price.price.to_string()),
+        ]))
+}
+
+pub(crate) fn deviation(reference: Decimal256, price: Decimal256) -> Decimal256 {
+    if reference.is_zero() {
+        return Decimal256::zero();
+    }
+
+    if price > reference {
+        (price - reference) / reference
+    } else {
+        (reference - price) / reference
+    }
+}
+
+pub fn query_deviation_guard(deps: Deps, asset: String) -> StdResult<DeviationGuardResponse> {
+    let deviation_guard = read_deviation_guard(deps.storage, &asset)?;
+
+    Ok(DeviationGuardResponse {
+        asset,
+        deviation_guard: deviation_guard.map(|deviation_guard| DeviationGuardMsg {
+            max_deviation: deviation_guard.max_deviation,
+            window: deviation_guard.window,
+        }),
+    })
+}
+
+pub fn query_pending_price(deps: Deps, asset: String) -> StdResult<PendingPriceResponse> {
+    let pending_price = read_pending_price(deps.storage, &asset)?;
+
+    Ok(PendingPriceResponse {
+        asset,
+        price: pending_price
+            .as_ref()
+            .map(|pending_price| pending_price.price),
+        last_updated_time: pending_price.map(|pending_price| pending_price.last_updated_time),
+    })
+}
@@ -1,15 +1,50 @@
+use crate::amm::{
+    check_amm_price, query_amm_feed, query_amm_twap, snapshot_amm_price, update_amm_feed,
+};
+use crate::asset_metadata::{query_asset_metadata, update_asset_metadata};
+use crate::band::{query_band_feed, query_band_price, update_band_feed};
+use crate::circuit_breaker::{
+    confirm_pending_price, guard_price, query_deviation_guard, query_pending_price,
+    update_deviation_guard,
+};
+use crate::emergency_price::{
+    active_emergency_price, clear_emergency_price, query_emergency_price, set_emergency_price,
+};
 use crate::error::ContractError;
+use crate::exchange_rate::{
+    query_exchange_rate, query_exchange_rate_feed, update_exchange_rate_feed,
+};
+use crate::fallback::{fallback_price, query_fallback_policy, update_fallback_policy};
+use crate::feeder_quorum::{query_feeder_quorum, submit_quorum_price, update_feeder_quorum};
+use crate::feeder_rotation::{accept_feeder, is_feeder, propose_feeder, query_feeder_rotation};
+use crate::heartbeat::{
+    is_paused, pause_price, query_feeder_stats, query_heartbeat, record_feeder_update,
+    resume_price, update_heartbeat,
+};
+use crate::history::query_price_history;
+use crate::price_quote::{query_price_quote, update_price_quote};
+use crate::pyth::{query_pyth_feed, query_pyth_price, update_pyth_feed};
+use crate::signed_prices::{feed_prices_signed, query_signer, register_signer};
 use crate::state::{
-    read_config, read_feeder, read_price, read_prices, store_config, store_feeder, store_price,
-    Config, PriceInfo,
+    read_band_feed, read_config, read_exchange_rate_feed, read_feeder, read_feeder_quorum,
+    read_feeder_stats, read_max_price_age, read_price, read_price_quote, read_prices,
+    read_pyth_feed, store_config, store_feeder, store_max_price_age, store_previous_feeder,
+    store_price, store_price_checkpoint, store_proposed_feeder, Config, PriceInfo,
 };
+use crate::subscription::{
+    query_subscriptions, subscribe, subscription_messages, unsubscribe, PRICE_CALLBACK_REPLY_ID,
+};
+use crate::twap::{compute_twap, query_twap_window, record_observation, update_twap_window};
 use cosmwasm_bignumber::Decimal256;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+    Storage, SubMsg,
+};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, MaxPriceAgeResponse, PriceKind,
+    PriceResponse, PricesResponse, PricesResponseElem, QueryMsg,
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -40,7 +75,69 @@ pub fn execute(
     match msg {
         ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
         ExecuteMsg::RegisterFeeder { asset, feeder } => register_feeder(deps, info, asset, feeder),
+        ExecuteMsg::RegisterSigner { asset, signer } => register_signer(deps, info, asset, signer),
+        ExecuteMsg::FeedPricesSigned { payload, signature } => {
+            feed_prices_signed(deps, env, payload, signature)
+        }
+        ExecuteMsg::ProposeFeeder {
+            asset,
+            feeder,
+            overlap,
+        } => propose_feeder(deps, info, asset, feeder, overlap),
+        ExecuteMsg::AcceptFeeder { asset } => accept_feeder(deps, env, info, asset),
         ExecuteMsg::FeedPrice { prices } => feed_prices(deps, env, info, prices),
+        ExecuteMsg::UpdatePriceQuote { asset, quote_asset } => {
+            update_price_quote(deps, info, asset, quote_asset)
+        }
+        ExecuteMsg::UpdateTwapWindow { twap_window } => update_twap_window(deps, info, twap_window),
+        ExecuteMsg::UpdateMaxPriceAge {
+            asset,
+            max_price_age,
+        } => update_max_price_age(deps, info, asset, max_price_age),
+        ExecuteMsg::UpdateFeederQuorum {
+            asset,
+            feeder_quorum,
+        } => update_feeder_quorum(deps, info, asset, feeder_quorum),
+        ExecuteMsg::UpdatePythFeed { asset, pyth_feed } => {
+            update_pyth_feed(deps, info, asset, pyth_feed)
+        }
+        ExecuteMsg::UpdateBandFeed { asset, band_feed } => {
+            update_band_feed(deps, info, asset, band_feed)
+        }
+        ExecuteMsg::UpdateExchangeRateFeed {
+            asset,
+            exchange_rate_feed,
+        } => update_exchange_rate_feed(deps, info, asset, exchange_rate_feed),
+        ExecuteMsg::UpdateHeartbeat { asset, heartbeat } => {
+            update_heartbeat(deps, info, asset, heartbeat)
+        }
+        ExecuteMsg::PausePrice { asset } => pause_price(deps, info, asset),
+        ExecuteMsg::ResumePrice { asset } => resume_price(deps, info, asset),
+        ExecuteMsg::UpdateFallbackPolicy {
+            asset,
+            fallback_policy,
+        } => update_fallback_policy(deps, info, asset, fallback_policy),
+        ExecuteMsg::UpdateDeviationGuard {
+            asset,
+            deviation_guard,
+        } => update_deviation_guard(deps, info, asset, deviation_guard),
+        ExecuteMsg::ConfirmPendingPrice { asset } => confirm_pending_price(deps, env, info, asset),
+        ExecuteMsg::UpdateAmmFeed { asset, amm_feed } => {
+            update_amm_feed(deps, env, info, asset, amm_feed)
+        }
+        ExecuteMsg::SnapshotAmmPrice { asset } => snapshot_amm_price(deps, env, asset),
+        ExecuteMsg::SetEmergencyPrice {
+            asset,
+            price,
+            expires_at,
+        } => set_emergency_price(deps, env, info, asset, price, expires_at),
+        ExecuteMsg::ClearEmergencyPrice { asset } => clear_emergency_price(deps, info, asset),
+        ExecuteMsg::Subscribe { asset, thresholds } => subscribe(deps, info, asset, thresholds),
+        ExecuteMsg::Unsubscribe { asset, subscriber } => unsubscribe(deps, info, asset, subscriber),
+        ExecuteMsg::UpdateAssetMetadata {
+            asset,
+            asset_metadata,
+        } => update_asset_metadata(deps, info, asset, asset_metadata),
     }
 }
 
@@ -74,6 +171,8 @@ pub fn register_feeder(
     }
 
     store_feeder(deps.storage, &asset, &deps.api.addr_canonicalize(&feeder)?)?;
+    store_proposed_feeder(deps.storage, &asset, None)?;
+    store_previous_feeder(deps.storage, &asset, None)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register_feeder"),
@@ -82,49 +181,179 @@ pub fn register_feeder(
     ]))
 }
 
-pub fn feed_prices(
+pub fn update_max_price_age(
     deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    max_price_age: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_max_price_age(deps.storage, &asset, max_price_age)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_max_price_age"),
+        attr("asset", asset),
+        attr(
+            "max_price_age",
+            max_price_age
+                .map(|max_price_age| max_price_age.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn feed_prices(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     prices: Vec<(String, Decimal256)>,
 ) -> Result<Response, ContractError> {
+    let mut messages: Vec<SubMsg> = vec![];
     let mut attributes = vec![attr("action", "feed_prices")];
     let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     for price in prices {
         let asset: String = price.0;
         let price: Decimal256 = price.1;
 
-        // Check feeder permission
-        let feeder = read_feeder(deps.storage, &asset)?;
-        if feeder != sender_raw {
-            return Err(ContractError::Unauthorized {});
-        }
+        assert_fed_asset(deps.storage, &asset)?;
+
+        let price_info = PriceInfo {
+            last_updated_time: env.block.time.seconds(),
+            price,
+        };
+
+        // Check feeder permission, assets with a feeder quorum
+        // are only updated once the quorum is reached
+        let price_info = match read_feeder_quorum(deps.storage, &asset)? {
+            Some(feeder_quorum) => {
+                if !feeder_quorum.feeders.contains(&sender_raw) {
+                    return Err(ContractError::Unauthorized {});
+                }
+
+                submit_quorum_price(
+                    deps.storage,
+                    &feeder_quorum,
+                    &asset,
+                    &sender_raw,
+                    &price_info,
+                )?
+            }
+            None => {
+                if !is_feeder(deps.storage, &asset, &sender_raw, env.block.time.seconds())? {
+                    return Err(ContractError::Unauthorized {});
+                }
 
+                Some(price_info)
+            }
+        };
+
+        if apply_fed_price(deps.storage, &asset, price_info, env.block.time.seconds())? {
+            attributes.push(attr("held_asset", asset.to_string()));
+        }
+        if active_emergency_price(deps.storage, &env, &asset)?.is_some() {
+            attributes.push(attr("overridden_asset", asset.to_string()));
+        }
+        messages.extend(subscription_messages(deps.branch(), &asset)?);
         attributes.push(attr("asset", asset.to_string()));
         attributes.push(attr("price", price.to_string()));
+    }
 
-        store_price(
-            deps.storage,
-            &asset,
-            &PriceInfo {
-                last_updated_time: env.block.time.seconds(),
-                price,
-            },
-        )?;
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(attributes))
+}
+
+/// Prices of Pyth, Band and derived assets are not fed
+pub(crate) fn assert_fed_asset(storage: &dyn Storage, asset: &str) -> Result<(), ContractError> {
+    if read_pyth_feed(storage, asset)?.is_some() {
+        return Err(ContractError::PythPricedAsset {});
+    }
+    if read_band_feed(storage, asset)?.is_some() {
+        return Err(ContractError::BandPricedAsset {});
+    }
+    if read_exchange_rate_feed(storage, asset)?.is_some() {
+        return Err(ContractError::DerivedPricedAsset {});
     }
 
-    Ok(Response::new().add_attributes(attributes))
+    Ok(())
+}
+
+/// Apply the fed price of the asset, `None` standing for a quorum not
+/// reached yet. Returns whether the price is held by the deviation guard
+pub(crate) fn apply_fed_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    price_info: Option<PriceInfo>,
+    time: u64,
+) -> StdResult<bool> {
+    record_feeder_update(storage, asset, time)?;
+
+    // Prices deviating beyond the guard are held until confirmed
+    let price_info = match price_info {
+        Some(price_info) => match guard_price(storage, asset, price_info)? {
+            Some(price_info) => price_info,
+            None => return Ok(true),
+        },
+        None => return Ok(false),
+    };
+
+    store_price(storage, asset, &price_info)?;
+    store_price_checkpoint(storage, asset, &price_info)?;
+    record_observation(storage, asset, price_info.price, time)?;
+
+    Ok(false)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // a failing subscriber callback must not revert the feed
+        PRICE_CALLBACK_REPLY_ID => {
+            Ok(Response::new().add_attribute("action", "price_callback_failed"))
+        }
+        _ => Err(ContractError::InvalidReplyId {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::Feeder { asset } => to_binary(&query_feeder(deps, asset)?),
-        QueryMsg::Price { base, quote } => to_binary(&query_price(deps, base, quote)?),
+        QueryMsg::Price {
+            base,
+            quote,
+            price_kind,
+        } => to_binary(&query_price(deps, env, base, quote, price_kind)?),
         QueryMsg::Prices { start_after, limit } => {
             to_binary(&query_prices(deps, start_after, limit)?)
         }
+        QueryMsg::PriceHistory { base, quote, limit } => {
+            to_binary(&query_price_history(deps, base, quote, limit)?)
+        }
+        QueryMsg::FeederRotation { asset } => to_binary(&query_feeder_rotation(deps, asset)?),
+        QueryMsg::Heartbeat { asset } => to_binary(&query_heartbeat(deps, asset)?),
+        QueryMsg::FallbackPolicy { asset } => to_binary(&query_fallback_policy(deps, asset)?),
+        QueryMsg::FeederStats { asset } => to_binary(&query_feeder_stats(deps, env, asset)?),
+        QueryMsg::Signer { asset } => to_binary(&query_signer(deps, asset)?),
+        QueryMsg::PriceQuote { asset } => to_binary(&query_price_quote(deps, asset)?),
+        QueryMsg::TwapWindow {} => to_binary(&query_twap_window(deps)?),
+        QueryMsg::MaxPriceAge { asset } => to_binary(&query_max_price_age(deps, asset)?),
+        QueryMsg::FeederQuorum { asset } => to_binary(&query_feeder_quorum(deps, asset)?),
+        QueryMsg::PythFeed { asset } => to_binary(&query_pyth_feed(deps, asset)?),
+        QueryMsg::BandFeed { asset } => to_binary(&query_band_feed(deps, asset)?),
+        QueryMsg::ExchangeRateFeed { asset } => to_binary(&query_exchange_rate_feed(deps, asset)?),
+        QueryMsg::DeviationGuard { asset } => to_binary(&query_deviation_guard(deps, asset)?),
+        QueryMsg::PendingPrice { asset } => to_binary(&query_pending_price(deps, asset)?),
+        QueryMsg::AssetMetadata { asset } => to_binary(&query_asset_metadata(deps, asset)?),
+        QueryMsg::AmmFeed { asset } => to_binary(&query_amm_feed(deps, asset)?),
+        QueryMsg::AmmTwap { asset } => to_binary(&query_amm_twap(deps, env, asset)?),
+        QueryMsg::Subscriptions { asset } => to_binary(&query_subscriptions(deps, asset)?),
+        QueryMsg::EmergencyPrice { asset } => to_binary(&query_emergency_price(deps, env, asset)?),
     }
 }
 
@@ -148,30 +377,186 @@ fn query_feeder(deps: Deps, asset: String) -> StdResult<FeederResponse> {
     Ok(resp)
 }
 
-fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceResponse> {
+fn query_price(
+    deps: Deps,
+    env: Env,
+    base: String,
+    quote: String,
+    price_kind: Option<PriceKind>,
+) -> StdResult<PriceResponse> {
     let config: Config = read_config(deps.storage)?;
-    let quote_price = if config.base_asset == quote {
-        PriceInfo {
-            price: Decimal256::one(),
-            last_updated_time: 9999999999,
+    // Prices come with their confidence interval, zero for sources
+    // without one
+    let source_price = |asset: &str| -> StdResult<(PriceInfo, Decimal256)> {
+        if config.base_asset == asset {
+            Ok((
+                PriceInfo {
+                    price: Decimal256::one(),
+                    last_updated_time: 9999999999,
+                },
+                Decimal256::zero(),
+            ))
+        } else if let Some(pyth_feed) = read_pyth_feed(deps.storage, asset)? {
+            query_pyth_price(deps, &env, &pyth_feed, false)
+        } else if let Some(band_feed) = read_band_feed(deps.storage, asset)? {
+            Ok((
+                query_band_price(deps, &env, &band_feed)?,
+                Decimal256::zero(),
+            ))
+        } else {
+            Ok((read_price(deps.storage, asset)?, Decimal256::zero()))
         }
-    } else {
-        read_price(deps.storage, &quote)?
     };
+    // Prices quoted in another asset are routed through its price
+    let quoted_price = |asset: &str| -> StdResult<(PriceInfo, Decimal256)> {
+        let (price, confidence) = source_price(asset)?;
+        match read_price_quote(deps.storage, asset)? {
+            Some(quote_asset) => {
+                let (quote_price, quote_confidence) = source_price(&quote_asset)?;
+                Ok((
+                    PriceInfo {
+                        price: price.price * quote_price.price,
+                        last_updated_time: std::cmp::min(
+                            price.last_updated_time,
+                            quote_price.last_updated_time,
+                        ),
+                    },
+                    confidence * quote_price.price + price.price * quote_confidence,
+                ))
+            }
+            None => Ok((price, confidence)),
+        }
+    };
+    // Derived prices are the underlying price times the hub exchange
+    // rate, as old as the older of both
+    let spot_price = |asset: &str| -> StdResult<(PriceInfo, Decimal256)> {
+        match read_exchange_rate_feed(deps.storage, asset)? {
+            Some(exchange_rate_feed) => {
+                let (underlying_price, confidence) =
+                    quoted_price(&exchange_rate_feed.underlying_asset)?;
+                let exchange_rate = query_exchange_rate(deps, &env, &exchange_rate_feed)?;
+                Ok((
+                    PriceInfo {
+                        price: underlying_price.price * exchange_rate.price,
+                        last_updated_time: std::cmp::min(
+                            underlying_price.last_updated_time,
+                            exchange_rate.last_updated_time,
+                        ),
+                    },
+                    confidence * exchange_rate.price,
+                ))
+            }
+            None => quoted_price(asset),
+        }
+    };
+    // Prices older than the max price age of their asset are rejected
+    let fresh_price = |asset: &str| -> StdResult<(PriceInfo, Decimal256)> {
+        let (price, confidence) = spot_price(asset)?;
+        let feeder_stats = read_feeder_stats(deps.storage, asset)?;
+        if is_paused(deps.storage, asset, &feeder_stats, env.block.time.seconds())? {
+            return Err(StdError::generic_err(
+                ContractError::PricePaused(asset.to_string()).to_string(),
+            ));
+        }
+        if let Some(max_price_age) = read_max_price_age(deps.storage, asset)? {
+            if price.last_updated_time + max_price_age < env.block.time.seconds() {
+                return Err(StdError::generic_err(
+                    ContractError::PriceTooOld(asset.to_string(), price.last_updated_time)
+                        .to_string(),
+                ));
+            }
+        }
+
+        Ok((price, confidence))
+    };
+    // Emergency prices override every source, and are always fresh.
+    // Paused, stale or failing prices fall back to the policy of their asset,
+    // and the price served, fallback or not, must not deviate from the AMM TWAP
+    let checked_price = |asset: &str| -> StdResult<(PriceInfo, Decimal256)> {
+        if let Some(price) = active_emergency_price(deps.storage, &env, asset)? {
+            return Ok((
+                PriceInfo {
+                    price,
+                    last_updated_time: env.block.time.seconds(),
+                },
+                Decimal256::zero(),
+            ));
+        }
 
-    let base_price = if config.base_asset == base {
-        PriceInfo {
-            price: Decimal256::one(),
-            last_updated_time: 9999999999,
+        let (price, confidence) = fresh_price(asset).or_else(|err| {
+            Ok::<_, StdError>((
+                fallback_price(deps.storage, &env, asset, err)?,
+                Decimal256::zero(),
+            ))
+        })?;
+        check_amm_price(deps, &env, asset, price.price)?;
+
+        Ok((price, confidence))
+    };
+    let (quote_price, quote_confidence) = checked_price(&quote)?;
+    let (base_price, base_confidence) = checked_price(&base)?;
+
+    let spot_rate = base_price.price / quote_price.price;
+    let twap_rate = match price_kind {
+        Some(PriceKind::Twap) | Some(PriceKind::Both) => {
+            // Pyth assets are averaged by their EMA price
+            let source_twap = |asset: &str| -> StdResult<Decimal256> {
+                if let Some(price) = active_emergency_price(deps.storage, &env, asset)? {
+                    Ok(price)
+                } else if config.base_asset == asset {
+                    Ok(Decimal256::one())
+                } else if let Some(pyth_feed) = read_pyth_feed(deps.storage, asset)? {
+                    Ok(query_pyth_price(deps, &env, &pyth_feed, true)?.0.price)
+                } else {
+                    compute_twap(deps.storage, asset, env.block.time.seconds())
+                }
+            };
+            let quoted_twap = |asset: &str| -> StdResult<Decimal256> {
+                match read_price_quote(deps.storage, asset)? {
+                    Some(quote_asset) => Ok(source_twap(asset)? * source_twap(&quote_asset)?),
+                    None => source_twap(asset),
+                }
+            };
+            // the underlying price is averaged, not the exchange rate
+            let twap = |asset: &str| -> StdResult<Decimal256> {
+                match read_exchange_rate_feed(deps.storage, asset)? {
+                    Some(exchange_rate_feed) => {
+                        Ok(quoted_twap(&exchange_rate_feed.underlying_asset)?
+                            * query_exchange_rate(deps, &env, &exchange_rate_feed)?.price)
+                    }
+                    None => quoted_twap(asset),
+                }
+            };
+            Some(twap(&base)? / twap(&quote)?)
         }
-    } else {
-        read_price(deps.storage, &base)?
+        _ => None,
     };
 
+    let rate = match price_kind {
+        Some(PriceKind::Twap) => twap_rate.unwrap_or(spot_rate),
+        _ => spot_rate,
+    };
+    // the relative confidence of the ratio is the sum of both
+    let confidence =
+        base_confidence / quote_price.price + spot_rate * quote_confidence / quote_price.price;
     Ok(PriceResponse {
-        rate: base_price.price / quote_price.price,
+        rate,
         last_updated_base: base_price.last_updated_time,
         last_updated_quote: quote_price.last_updated_time,
+        twap_rate,
+        confidence: if confidence.is_zero() {
+            None
+        } else {
+            Some(confidence)
+        },
+    })
+}
+
+fn query_max_price_age(deps: Deps, asset: String) -> StdResult<MaxPriceAgeResponse> {
+    let max_price_age = read_max_price_age(deps.storage, &asset)?;
+    Ok(MaxPriceAgeResponse {
+        asset,
+        max_price_age,
     })
 }
 
@@ -180,6 +565,14 @@ fn query_prices(
     start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<PricesResponse> {
-    let prices: Vec<PricesResponseElem> = read_prices(deps.storage, start_after, limit)?;
+    let prices: Vec<PricesResponseElem> = read_prices(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|price| {
+            Ok(PricesResponseElem {
+                metadata: query_asset_metadata(deps, price.asset.clone())?.asset_metadata,
+                ..price
+            })
+        })
+        .collect::<StdResult<Vec<PricesResponseElem>>>()?;
     Ok(PricesResponse { prices })
 }
@@ -0,0 +1,97 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_emergency_price, store_emergency_price, Config, EmergencyPrice,
+};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use moneymarket::oracle::{EmergencyPriceResponse, MAX_EMERGENCY_PRICE_DURATION};
+
+pub fn set_emergency_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+    price: Decimal256,
+    expires_at: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let time = env.block.time.seconds();
+    if price.is_zero() || expires_at <= time || expires_at > time + MAX_EMERGENCY_PRICE_DURATION {
+        return Err(ContractError::InvalidEmergencyPrice(
+            MAX_EMERGENCY_PRICE_DURATION,
+        ));
+    }
+
+    let prev_emergency_price = active_emergency_price(deps.storage, &env, &asset)?;
+    store_emergency_price(
+        deps.storage,
+        &asset,
+        Some(EmergencyPrice { price, expires_at }),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_emergency_price"),
+        attr("asset", asset),
+        attr("price", price.to_string()),
+        attr("expires_at", expires_at.to_string()),
+        attr(
+            "prev_price",
+            prev_emergency_price
+                .map(|prev_price| prev_price.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn clear_emergency_price(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let emergency_price =
+        read_emergency_price(deps.storage, &asset)?.ok_or(ContractError::NoEmergencyPrice {})?;
+    store_emergency_price(deps.storage, &asset, None)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "clear_emergency_price"),
+        attr("asset", asset),
+        attr("price", emergency_price.price.to_string()),
+    ]))
+}
+
+/// Emergency price of the asset, until it expires
+pub(crate) fn active_emergency_price(
+    storage: &dyn Storage,
+    env: &Env,
+    asset: &str,
+) -> StdResult<Option<Decimal256>> {
+    Ok(read_emergency_price(storage, asset)?
+        .filter(|emergency_price| emergency_price.expires_at > env.block.time.seconds())
+        .map(|emergency_price| emergency_price.price))
+}
+
+pub fn query_emergency_price(
+    deps: Deps,
+    env: Env,
+    asset: String,
+) -> StdResult<EmergencyPriceResponse> {
+    let emergency_price = read_emergency_price(deps.storage, &asset)?
+        .filter(|emergency_price| emergency_price.expires_at > env.block.time.seconds());
+
+    Ok(EmergencyPriceResponse {
+        asset,
+        price: emergency_price
+            .as_ref()
+            .map(|emergency_price| emergency_price.price),
+        expires_at: emergency_price.map(|emergency_price| emergency_price.expires_at),
+    })
+}
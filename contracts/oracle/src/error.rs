@@ -11,4 +11,88 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Feeder quorum must be between one and the # of distinct feeders, at most {0}")]
+    InvalidFeederQuorum(u32),
+
+    #[error("Pyth price id must be a 32 bytes hex string")]
+    InvalidPythFeed {},
+
+    #[error("Asset price is pulled from Pyth")]
+    PythPricedAsset {},
+
+    #[error("Asset price is pulled from Band")]
+    BandPricedAsset {},
+
+    #[error("Asset price is derived from an exchange rate")]
+    DerivedPricedAsset {},
+
+    #[error("Exchange rate feed must derive from another, not derived, asset")]
+    InvalidExchangeRateFeed {},
+
+    #[error("Price of {0} is too old; last updated: {1}")]
+    PriceTooOld(String, u64),
+
+    #[error("No feeder proposed for the asset")]
+    NoProposedFeeder {},
+
+    #[error("Price of {0} is paused")]
+    PricePaused(String),
+
+    #[error("Heartbeat interval and max missed heartbeats must be greater than zero")]
+    InvalidHeartbeat {},
+
+    #[error("Signer must be a 32 bytes ed25519 public key")]
+    InvalidSigner {},
+
+    #[error("Invalid price signature")]
+    InvalidSignature {},
+
+    #[error("Signed price of {0} must be newer than its price and not in the future")]
+    InvalidSignedPrice(String),
+
+    #[error("Signed prices are signed for another chain or oracle contract")]
+    InvalidSignedDomain {},
+
+    #[error("Assets must be quoted in another asset, neither quoted nor derived")]
+    InvalidPriceQuote {},
+
+    #[error("Fallback price must be positive")]
+    InvalidFallbackPolicy {},
+
+    #[error("No pending price for the asset")]
+    NoPendingPrice {},
+
+    #[error("Exchange rate priced assets must name their hub contract")]
+    InvalidAssetMetadata {},
+
+    #[error("AMM feed asset index must be 0 or 1 and its window longer than zero")]
+    InvalidAmmFeed {},
+
+    #[error("No AMM feed for the asset")]
+    NoAmmFeed {},
+
+    #[error("No AMM price snapshot as old as the window")]
+    NoAmmTwap {},
+
+    #[error("Price of {0} deviates from its AMM TWAP")]
+    AmmPriceDeviation(String),
+
+    #[error("Price thresholds must be ascending, positive and at most {0}")]
+    InvalidPriceThresholds(u32),
+
+    #[error("Asset has the maximum # of subscribers: {0}")]
+    TooManySubscribers(u32),
+
+    #[error("Invalid reply ID")]
+    InvalidReplyId {},
+
+    #[error("Emergency price must be positive and expire within {0} seconds")]
+    InvalidEmergencyPrice(u64),
+
+    #[error("No emergency price for the asset")]
+    NoEmergencyPrice {},
+
+    #[error("TWAP window must be longer than zero")]
+    InvalidTwapWindow {},
 }
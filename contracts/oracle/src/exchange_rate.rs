@@ -0,0 +1,123 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_exchange_rate_feed, store_exchange_rate_feed, Config, ExchangeRateFeedInfo,
+    PriceInfo,
+};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, to_binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response, StdError, StdResult,
+    WasmQuery,
+};
+use moneymarket::oracle::{ExchangeRateFeed, ExchangeRateFeedResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Query of the LST hub contract for its state
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HubQueryMsg {
+    State {},
+}
+
+/// Fields of the hub state the exchange rate is read from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HubStateResponse {
+    pub exchange_rate: Decimal256,
+    pub last_index_modification: u64,
+}
+
+pub fn update_exchange_rate_feed(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    exchange_rate_feed: Option<ExchangeRateFeed>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let exchange_rate_feed_info = match exchange_rate_feed.as_ref() {
+        Some(exchange_rate_feed) => {
+            // derived prices are composed of a single exchange rate
+            if exchange_rate_feed.underlying_asset == asset
+                || read_exchange_rate_feed(deps.storage, &exchange_rate_feed.underlying_asset)?
+                    .is_some()
+            {
+                return Err(ContractError::InvalidExchangeRateFeed {});
+            }
+
+            Some(ExchangeRateFeedInfo {
+                hub_contract: deps
+                    .api
+                    .addr_canonicalize(&exchange_rate_feed.hub_contract)?,
+                underlying_asset: exchange_rate_feed.underlying_asset.clone(),
+                max_rate_age: exchange_rate_feed.max_rate_age,
+            })
+        }
+        None => None,
+    };
+    store_exchange_rate_feed(deps.storage, &asset, exchange_rate_feed_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_exchange_rate_feed"),
+        attr("asset", asset),
+        attr(
+            "underlying_asset",
+            exchange_rate_feed
+                .map(|exchange_rate_feed| exchange_rate_feed.underlying_asset)
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Exchange rate of the hub, as of its last index update. The rate is
+/// rejected when older than the max rate age
+pub(crate) fn query_exchange_rate(
+    deps: Deps,
+    env: &Env,
+    exchange_rate_feed: &ExchangeRateFeedInfo,
+) -> StdResult<PriceInfo> {
+    let hub_state: HubStateResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps
+                .api
+                .addr_humanize(&exchange_rate_feed.hub_contract)?
+                .to_string(),
+            msg: to_binary(&HubQueryMsg::State {})?,
+        }))?;
+
+    if hub_state.last_index_modification + exchange_rate_feed.max_rate_age
+        < env.block.time.seconds()
+    {
+        return Err(StdError::generic_err("Hub exchange rate is too old"));
+    }
+    if hub_state.exchange_rate.is_zero() {
+        return Err(StdError::generic_err("Hub exchange rate must be positive"));
+    }
+
+    Ok(PriceInfo {
+        price: hub_state.exchange_rate,
+        last_updated_time: hub_state.last_index_modification,
+    })
+}
+
+pub fn query_exchange_rate_feed(deps: Deps, asset: String) -> StdResult<ExchangeRateFeedResponse> {
+    let exchange_rate_feed = read_exchange_rate_feed(deps.storage, &asset)?;
+
+    Ok(ExchangeRateFeedResponse {
+        asset,
+        exchange_rate_feed: exchange_rate_feed
+            .map(|exchange_rate_feed| {
+                Ok::<ExchangeRateFeed, StdError>(ExchangeRateFeed {
+                    hub_contract: deps
+                        .api
+                        .addr_humanize(&exchange_rate_feed.hub_contract)?
+                        .to_string(),
+                    underlying_asset: exchange_rate_feed.underlying_asset,
+                    max_rate_age: exchange_rate_feed.max_rate_age,
+                })
+            })
+            .transpose()?,
+    })
+}
@@ -0,0 +1,63 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_fallback_policy, read_price, store_fallback_policy, Config, PriceInfo,
+};
+use cosmwasm_std::{attr, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage};
+use moneymarket::oracle::{FallbackPolicy, FallbackPolicyResponse};
+
+pub fn update_fallback_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    fallback_policy: Option<FallbackPolicy>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(FallbackPolicy::FixedPrice { price }) = fallback_policy.as_ref() {
+        if price.is_zero() {
+            return Err(ContractError::InvalidFallbackPolicy {});
+        }
+    }
+    store_fallback_policy(deps.storage, &asset, fallback_policy)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fallback_policy"),
+        attr("asset", asset.clone()),
+        attr(
+            "fallback_policy",
+            read_fallback_policy(deps.storage, &asset)?.to_string(),
+        ),
+    ]))
+}
+
+/// Price of the asset under its fallback policy, when its price could
+/// not be read for `err`. Fixed prices are always fresh
+pub(crate) fn fallback_price(
+    storage: &dyn Storage,
+    env: &Env,
+    asset: &str,
+    err: StdError,
+) -> StdResult<PriceInfo> {
+    match read_fallback_policy(storage, asset)? {
+        FallbackPolicy::Fail => Err(err),
+        FallbackPolicy::LastGoodPrice { max_age } => match read_price(storage, asset) {
+            Ok(price) if price.last_updated_time + max_age >= env.block.time.seconds() => Ok(price),
+            _ => Err(err),
+        },
+        FallbackPolicy::FixedPrice { price } => Ok(PriceInfo {
+            price,
+            last_updated_time: env.block.time.seconds(),
+        }),
+    }
+}
+
+pub fn query_fallback_policy(deps: Deps, asset: String) -> StdResult<FallbackPolicyResponse> {
+    let fallback_policy = read_fallback_policy(deps.storage, &asset)?;
+    Ok(FallbackPolicyResponse {
+        asset,
+        fallback_policy,
+    })
+}
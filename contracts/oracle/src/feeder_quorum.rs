@@ -0,0 +1,131 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_feeder_prices, read_feeder_quorum, remove_feeder_prices, store_feeder_price,
+    store_feeder_quorum, Config, FeederQuorumInfo, PriceInfo,
+};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Storage,
+};
+use moneymarket::oracle::{FeederQuorum, FeederQuorumResponse};
+
+// maximum number of feeders pricing a single asset
+const MAX_QUORUM_FEEDERS: u32 = 10;
+
+pub fn update_feeder_quorum(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    feeder_quorum: Option<FeederQuorum>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let feeder_quorum_info = match feeder_quorum.as_ref() {
+        Some(feeder_quorum) => {
+            let mut feeders = vec![];
+            for feeder in feeder_quorum.feeders.iter() {
+                let feeder_raw = deps.api.addr_canonicalize(feeder)?;
+                if feeders.contains(&feeder_raw) {
+                    return Err(ContractError::InvalidFeederQuorum(MAX_QUORUM_FEEDERS));
+                }
+                feeders.push(feeder_raw);
+            }
+
+            if feeders.len() > MAX_QUORUM_FEEDERS as usize
+                || feeder_quorum.quorum == 0
+                || feeder_quorum.quorum as usize > feeders.len()
+            {
+                return Err(ContractError::InvalidFeederQuorum(MAX_QUORUM_FEEDERS));
+            }
+
+            Some(FeederQuorumInfo {
+                feeders,
+                quorum: feeder_quorum.quorum,
+                max_price_age: feeder_quorum.max_price_age,
+            })
+        }
+        None => None,
+    };
+
+    // submissions of the previous feeders are dropped
+    remove_feeder_prices(deps.storage, &asset)?;
+    store_feeder_quorum(deps.storage, &asset, feeder_quorum_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_feeder_quorum"),
+        attr("asset", asset),
+        attr(
+            "quorum",
+            feeder_quorum
+                .map(|q| format!("{}/{}", q.quorum, q.feeders.len()))
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Record the submission of the feeder, and return the median of the
+/// fresh submissions once the quorum is reached. The price is as old as
+/// the oldest submission it is computed from
+pub(crate) fn submit_quorum_price(
+    storage: &mut dyn Storage,
+    feeder_quorum: &FeederQuorumInfo,
+    asset: &str,
+    feeder: &CanonicalAddr,
+    price: &PriceInfo,
+) -> StdResult<Option<PriceInfo>> {
+    store_feeder_price(storage, asset, feeder, price)?;
+
+    let mut fresh_prices: Vec<PriceInfo> = read_feeder_prices(storage, asset)?
+        .into_iter()
+        .map(|(_, feeder_price)| feeder_price)
+        .filter(|feeder_price| {
+            feeder_price.last_updated_time + feeder_quorum.max_price_age >= price.last_updated_time
+        })
+        .collect();
+    if fresh_prices.len() < feeder_quorum.quorum as usize {
+        return Ok(None);
+    }
+
+    let last_updated_time = fresh_prices
+        .iter()
+        .map(|feeder_price| feeder_price.last_updated_time)
+        .min()
+        .ok_or_else(|| StdError::generic_err("No fresh price submission"))?;
+
+    fresh_prices.sort_by_key(|feeder_price| feeder_price.price);
+    let mid = fresh_prices.len() / 2;
+    let price = if fresh_prices.len().is_multiple_of(2) {
+        (fresh_prices[mid - 1].price + fresh_prices[mid].price) * Decimal256::percent(50)
+    } else {
+        fresh_prices[mid].price
+    };
+
+    Ok(Some(PriceInfo {
+        price,
+        last_updated_time,
+    }))
+}
+
+pub fn query_feeder_quorum(deps: Deps, asset: String) -> StdResult<FeederQuorumResponse> {
+    let feeder_quorum = read_feeder_quorum(deps.storage, &asset)?;
+
+    Ok(FeederQuorumResponse {
+        asset,
+        feeder_quorum: feeder_quorum
+            .map(|feeder_quorum| {
+                Ok::<FeederQuorum, StdError>(FeederQuorum {
+                    feeders: feeder_quorum
+                        .feeders
+                        .iter()
+                        .map(|feeder| Ok(deps.api.addr_humanize(feeder)?.to_string()))
+                        .collect::<StdResult<Vec<String>>>()?,
+                    quorum: feeder_quorum.quorum,
+                    max_price_age: feeder_quorum.max_price_age,
+                })
+            })
+            .transpose()?,
+    })
+}
@@ -0,0 +1,112 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_feeder, read_previous_feeder, read_proposed_feeder, store_feeder,
+    store_previous_feeder, store_proposed_feeder, Config, PreviousFeederInfo, ProposedFeederInfo,
+};
+use cosmwasm_std::{
+    attr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage,
+};
+use moneymarket::oracle::FeederRotationResponse;
+
+pub fn propose_feeder(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    feeder: String,
+    overlap: u64,
+) -> Result<Response, ContractError> {
+    // proposed by the owner or the current feeder
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner && sender_raw != read_feeder(deps.storage, &asset)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_proposed_feeder(
+        deps.storage,
+        &asset,
+        Some(ProposedFeederInfo {
+            feeder: deps.api.addr_canonicalize(&feeder)?,
+            overlap,
+        }),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_feeder"),
+        attr("asset", asset),
+        attr("feeder", feeder),
+        attr("overlap", overlap.to_string()),
+    ]))
+}
+
+pub fn accept_feeder(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let proposed_feeder =
+        read_proposed_feeder(deps.storage, &asset)?.ok_or(ContractError::NoProposedFeeder {})?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != proposed_feeder.feeder {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let previous_feeder = read_feeder(deps.storage, &asset)?;
+    store_previous_feeder(
+        deps.storage,
+        &asset,
+        Some(PreviousFeederInfo {
+            feeder: previous_feeder,
+            until: env.block.time.seconds() + proposed_feeder.overlap,
+        }),
+    )?;
+    store_feeder(deps.storage, &asset, &proposed_feeder.feeder)?;
+    store_proposed_feeder(deps.storage, &asset, None)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_feeder"),
+        attr("asset", asset),
+        attr("feeder", info.sender),
+    ]))
+}
+
+/// Whether the feeder may feed the asset at `time`, the previous
+/// feeder being accepted during the overlap of a rotation
+pub(crate) fn is_feeder(
+    storage: &dyn Storage,
+    asset: &str,
+    feeder: &CanonicalAddr,
+    time: u64,
+) -> StdResult<bool> {
+    if read_feeder(storage, asset)? == *feeder {
+        return Ok(true);
+    }
+
+    Ok(match read_previous_feeder(storage, asset)? {
+        Some(previous_feeder) => previous_feeder.feeder == *feeder && time <= previous_feeder.until,
+        None => false,
+    })
+}
+
+pub fn query_feeder_rotation(deps: Deps, asset: String) -> StdResult<FeederRotationResponse> {
+    let proposed_feeder = read_proposed_feeder(deps.storage, &asset)?;
+    let previous_feeder = read_previous_feeder(deps.storage, &asset)?;
+
+    Ok(FeederRotationResponse {
+        asset,
+        proposed_feeder: proposed_feeder
+            .as_ref()
+            .map(|proposed_feeder| {
+                Ok::<String, StdError>(deps.api.addr_humanize(&proposed_feeder.feeder)?.to_string())
+            })
+            .transpose()?,
+        overlap: proposed_feeder.map(|proposed_feeder| proposed_feeder.overlap),
+        previous_feeder: previous_feeder
+            .as_ref()
+            .map(|previous_feeder| {
+                Ok::<String, StdError>(deps.api.addr_humanize(&previous_feeder.feeder)?.to_string())
+            })
+            .transpose()?,
+        previous_feeder_until: previous_feeder.map(|previous_feeder| previous_feeder.until),
+    })
+}
@@ -0,0 +1,150 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_feeder_stats, read_heartbeat, store_feeder_stats, store_heartbeat, Config,
+    FeederStats, Heartbeat,
+};
+use cosmwasm_std::{attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use moneymarket::oracle::{FeederStatsResponse, Heartbeat as HeartbeatMsg, HeartbeatResponse};
+
+pub fn update_heartbeat(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    heartbeat: Option<HeartbeatMsg>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![
+        attr("action", "update_heartbeat"),
+        attr("asset", asset.clone()),
+    ];
+    let heartbeat = match heartbeat {
+        Some(heartbeat) => {
+            if heartbeat.interval == 0 || heartbeat.max_missed == 0 {
+                return Err(ContractError::InvalidHeartbeat {});
+            }
+
+            attributes.push(attr("interval", heartbeat.interval.to_string()));
+            attributes.push(attr("max_missed", heartbeat.max_missed.to_string()));
+            Some(Heartbeat {
+                interval: heartbeat.interval,
+                max_missed: heartbeat.max_missed,
+            })
+        }
+        None => None,
+    };
+    store_heartbeat(deps.storage, &asset, heartbeat)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn pause_price(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut feeder_stats = read_feeder_stats(deps.storage, &asset)?;
+    feeder_stats.paused = true;
+    store_feeder_stats(deps.storage, &asset, &feeder_stats)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "pause_price"), attr("asset", asset)]))
+}
+
+pub fn resume_price(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut feeder_stats = read_feeder_stats(deps.storage, &asset)?;
+    feeder_stats.paused = false;
+    store_feeder_stats(deps.storage, &asset, &feeder_stats)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "resume_price"), attr("asset", asset)]))
+}
+
+/// Count the update of the asset price, pausing the price when it comes
+/// after too many missed heartbeats. A paused price stays paused until
+/// resumed by the owner
+pub(crate) fn record_feeder_update(
+    storage: &mut dyn Storage,
+    asset: &str,
+    time: u64,
+) -> StdResult<()> {
+    let mut feeder_stats = read_feeder_stats(storage, asset)?;
+    if let Some(last_update_time) = feeder_stats.last_update_time {
+        feeder_stats.max_gap = std::cmp::max(feeder_stats.max_gap, time - last_update_time);
+    }
+    feeder_stats.paused = is_paused(storage, asset, &feeder_stats, time)?;
+    feeder_stats.updates += 1;
+    feeder_stats.last_update_time = Some(time);
+
+    store_feeder_stats(storage, asset, &feeder_stats)
+}
+
+/// Whether the asset price is paused at `time`, either explicitly or
+/// for heartbeats missed since the last update
+pub(crate) fn is_paused(
+    storage: &dyn Storage,
+    asset: &str,
+    feeder_stats: &FeederStats,
+    time: u64,
+) -> StdResult<bool> {
+    if feeder_stats.paused {
+        return Ok(true);
+    }
+
+    Ok(
+        match (
+            read_heartbeat(storage, asset)?,
+            feeder_stats.last_update_time,
+        ) {
+            (Some(heartbeat), Some(last_update_time)) => {
+                (time - last_update_time) / heartbeat.interval >= heartbeat.max_missed as u64
+            }
+            _ => false,
+        },
+    )
+}
+
+pub fn query_heartbeat(deps: Deps, asset: String) -> StdResult<HeartbeatResponse> {
+    let heartbeat = read_heartbeat(deps.storage, &asset)?;
+
+    Ok(HeartbeatResponse {
+        asset,
+        heartbeat: heartbeat.map(|heartbeat| HeartbeatMsg {
+            interval: heartbeat.interval,
+            max_missed: heartbeat.max_missed,
+        }),
+    })
+}
+
+pub fn query_feeder_stats(deps: Deps, env: Env, asset: String) -> StdResult<FeederStatsResponse> {
+    let feeder_stats = read_feeder_stats(deps.storage, &asset)?;
+    let paused = is_paused(
+        deps.storage,
+        &asset,
+        &feeder_stats,
+        env.block.time.seconds(),
+    )?;
+
+    Ok(FeederStatsResponse {
+        asset,
+        updates: feeder_stats.updates,
+        last_update_time: feeder_stats.last_update_time,
+        max_gap: feeder_stats.max_gap,
+        paused,
+    })
+}
@@ -0,0 +1,70 @@
+use crate::state::{read_config, read_price_checkpoints, Config, PriceInfo, PRICE_HISTORY_SIZE};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{Deps, StdResult};
+use moneymarket::oracle::{PriceCheckpoint, PriceHistoryResponse};
+use moneymarket::pagination::calc_limit_with_max;
+
+/// Rates of the pair at each checkpoint of either asset, priced with
+/// the latest checkpoint of the other. Only fed prices are checkpointed
+pub fn query_price_history(
+    deps: Deps,
+    base: String,
+    quote: String,
+    limit: Option<u32>,
+) -> StdResult<PriceHistoryResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let limit = calc_limit_with_max(limit, PRICE_HISTORY_SIZE);
+
+    // `None` stands for the base asset, priced one at all times
+    let checkpoints = |asset: &str| -> StdResult<Option<Vec<PriceInfo>>> {
+        if config.base_asset == asset {
+            Ok(None)
+        } else {
+            Ok(Some(read_price_checkpoints(deps.storage, asset)?))
+        }
+    };
+    let base_checkpoints = checkpoints(&base)?;
+    let quote_checkpoints = checkpoints(&quote)?;
+
+    let price_at = |checkpoints: &Option<Vec<PriceInfo>>, time: u64| -> Option<Decimal256> {
+        match checkpoints {
+            Some(checkpoints) => checkpoints
+                .iter()
+                .find(|checkpoint| checkpoint.last_updated_time <= time)
+                .map(|checkpoint| checkpoint.price),
+            None => Some(Decimal256::one()),
+        }
+    };
+
+    let mut times: Vec<u64> = base_checkpoints
+        .iter()
+        .chain(quote_checkpoints.iter())
+        .flatten()
+        .map(|checkpoint| checkpoint.last_updated_time)
+        .collect();
+    times.sort_unstable_by(|a, b| b.cmp(a));
+    times.dedup();
+
+    let mut history: Vec<PriceCheckpoint> = vec![];
+    for time in times {
+        if history.len() == limit {
+            break;
+        }
+
+        // older checkpoints miss a price as well
+        match (
+            price_at(&base_checkpoints, time),
+            price_at(&quote_checkpoints, time),
+        ) {
+            (Some(base_price), Some(quote_price)) => history.push(PriceCheckpoint {
+                time,
+                rate: base_price / quote_price,
+            }),
+            _ => break,
+        }
+    }
+
+    Ok(PriceHistoryResponse {
+        checkpoints: history,
+    })
+}
@@ -1,6 +1,24 @@
+pub mod amm;
+pub mod asset_metadata;
+pub mod band;
+pub mod circuit_breaker;
 pub mod contract;
+pub mod emergency_price;
 pub mod error;
+pub mod exchange_rate;
+pub mod fallback;
+pub mod feeder_quorum;
+pub mod feeder_rotation;
+pub mod heartbeat;
+pub mod history;
+pub mod price_quote;
+pub mod pyth;
+pub mod signed_prices;
 pub mod state;
+pub mod subscription;
+pub mod twap;
 
+#[cfg(test)]
+mod mock_querier;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,173 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, ContractResult, Empty, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::amm::CumulativePricesResponse;
+use crate::band::ReferenceData;
+use crate::exchange_rate::HubStateResponse;
+use crate::pyth::{PriceFeed, PriceFeedResponse};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Query price feed to pyth contract
+    PriceFeed { id: String },
+    /// Query reference data to band std-reference contract
+    GetReferenceData {
+        base_symbol: String,
+        quote_symbol: String,
+    },
+    /// Query state to LST hub contract
+    State {},
+    /// Query cumulative prices to AMM pair contract
+    CumulativePrices {},
+}
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: WasmMockQuerier::new(MockQuerier::new(&[])),
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    // price feeds of the pyth contracts, by contract and price id
+    pyth_price_feeds: HashMap<(String, String), PriceFeed>,
+    // reference data of the band contracts, by contract and pair
+    band_reference_data: HashMap<(String, String, String), ReferenceData>,
+    // states of the LST hub contracts, by contract
+    hub_states: HashMap<String, HubStateResponse>,
+    // cumulative prices of the AMM pair contracts, by contract
+    cumulative_prices: HashMap<String, CumulativePricesResponse>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    QueryMsg::PriceFeed { id } => {
+                        match self.pyth_price_feeds.get(&(contract_addr.to_string(), id)) {
+                            Some(price_feed) => SystemResult::Ok(ContractResult::from(to_binary(
+                                &PriceFeedResponse {
+                                    price_feed: price_feed.clone(),
+                                },
+                            ))),
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No price feed exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    QueryMsg::GetReferenceData {
+                        base_symbol,
+                        quote_symbol,
+                    } => match self.band_reference_data.get(&(
+                        contract_addr.to_string(),
+                        base_symbol,
+                        quote_symbol,
+                    )) {
+                        Some(reference_data) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(reference_data)))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No reference data exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    QueryMsg::State {} => match self.hub_states.get(contract_addr) {
+                        Some(hub_state) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(hub_state)))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No hub state exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    QueryMsg::CumulativePrices {} => {
+                        match self.cumulative_prices.get(contract_addr) {
+                            Some(cumulative_prices) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(cumulative_prices)))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No cumulative prices exist".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            pyth_price_feeds: HashMap::new(),
+            band_reference_data: HashMap::new(),
+            hub_states: HashMap::new(),
+            cumulative_prices: HashMap::new(),
+        }
+    }
+
+    pub fn with_pyth_price_feed(&mut self, pyth_contract: &str, price_feed: PriceFeed) {
+        self.pyth_price_feeds.insert(
+            (pyth_contract.to_string(), price_feed.id.clone()),
+            price_feed,
+        );
+    }
+
+    pub fn with_band_reference_data(
+        &mut self,
+        band_contract: &str,
+        pair: (&str, &str),
+        reference_data: ReferenceData,
+    ) {
+        self.band_reference_data.insert(
+            (
+                band_contract.to_string(),
+                pair.0.to_string(),
+                pair.1.to_string(),
+            ),
+            reference_data,
+        );
+    }
+
+    pub fn with_hub_state(&mut self, hub_contract: &str, hub_state: HubStateResponse) {
+        self.hub_states.insert(hub_contract.to_string(), hub_state);
+    }
+
+    pub fn with_cumulative_prices(
+        &mut self,
+        pair_contract: &str,
+        cumulative_prices: CumulativePricesResponse,
+    ) {
+        self.cumulative_prices
+            .insert(pair_contract.to_string(), cumulative_prices);
+    }
+}
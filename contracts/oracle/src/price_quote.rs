@@ -0,0 +1,45 @@
+use crate::contract::assert_fed_asset;
+use crate::error::ContractError;
+use crate::state::{
+    is_quote_asset, read_config, read_exchange_rate_feed, read_price_quote, store_price_quote,
+    Config,
+};
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdResult};
+use moneymarket::oracle::PriceQuoteResponse;
+
+pub fn update_price_quote(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    quote_asset: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // prices are routed through a single quote asset
+    if let Some(quote_asset) = quote_asset.as_ref() {
+        assert_fed_asset(deps.storage, &asset)?;
+        if *quote_asset == asset
+            || *quote_asset == config.base_asset
+            || read_price_quote(deps.storage, quote_asset)?.is_some()
+            || read_exchange_rate_feed(deps.storage, quote_asset)?.is_some()
+            || is_quote_asset(deps.storage, &asset)?
+        {
+            return Err(ContractError::InvalidPriceQuote {});
+        }
+    }
+    store_price_quote(deps.storage, &asset, quote_asset.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_price_quote"),
+        attr("asset", asset),
+        attr("quote_asset", quote_asset.unwrap_or(config.base_asset)),
+    ]))
+}
+
+pub fn query_price_quote(deps: Deps, asset: String) -> StdResult<PriceQuoteResponse> {
+    let quote_asset = read_price_quote(deps.storage, &asset)?;
+    Ok(PriceQuoteResponse { asset, quote_asset })
+}
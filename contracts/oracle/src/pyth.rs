@@ -0,0 +1,179 @@
+use crate::error::ContractError;
+use crate::state::{read_config, read_pyth_feed, store_pyth_feed, Config, PriceInfo, PythFeedInfo};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response, StdError, StdResult,
+    WasmQuery,
+};
+use moneymarket::oracle::{PythFeed, PythFeedResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Query of the Pyth contract for a price feed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PythQueryMsg {
+    PriceFeed { id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceFeedResponse {
+    pub price_feed: PriceFeed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceFeed {
+    pub id: String,
+    pub price: PythPrice,
+    pub ema_price: PythPrice,
+}
+
+/// `price` and `conf` are integers serialized as strings,
+/// scaled by 10^`expo`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PythPrice {
+    pub price: String,
+    pub conf: String,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+// widest exponent normalized without overflow
+const MAX_EXPO: i32 = 38;
+
+pub fn update_pyth_feed(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    pyth_feed: Option<PythFeed>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pyth_feed_info = match pyth_feed.as_ref() {
+        Some(pyth_feed) => {
+            let price_id = pyth_feed.price_id.to_lowercase();
+            if price_id.len() != 64 || !price_id.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ContractError::InvalidPythFeed {});
+            }
+
+            Some(PythFeedInfo {
+                contract_addr: deps.api.addr_canonicalize(&pyth_feed.contract_addr)?,
+                price_id,
+                max_confidence: pyth_feed.max_confidence,
+                max_price_age: pyth_feed.max_price_age,
+            })
+        }
+        None => None,
+    };
+    store_pyth_feed(deps.storage, &asset, pyth_feed_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_pyth_feed"),
+        attr("asset", asset),
+        attr(
+            "price_id",
+            pyth_feed
+                .map(|pyth_feed| pyth_feed.price_id)
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Price of the Pyth feed, or of its EMA price, with its confidence
+/// interval. The price is rejected when older than the max price age or
+/// less confident than the max confidence interval
+pub(crate) fn query_pyth_price(
+    deps: Deps,
+    env: &Env,
+    pyth_feed: &PythFeedInfo,
+    ema: bool,
+) -> StdResult<(PriceInfo, Decimal256)> {
+    let price_feed: PriceFeedResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps
+                .api
+                .addr_humanize(&pyth_feed.contract_addr)?
+                .to_string(),
+            msg: to_binary(&PythQueryMsg::PriceFeed {
+                id: pyth_feed.price_id.clone(),
+            })?,
+        }))?;
+    let pyth_price = if ema {
+        price_feed.price_feed.ema_price
+    } else {
+        price_feed.price_feed.price
+    };
+
+    let publish_time = if pyth_price.publish_time < 0 {
+        0
+    } else {
+        pyth_price.publish_time as u64
+    };
+    if publish_time + pyth_feed.max_price_age < env.block.time.seconds() {
+        return Err(StdError::generic_err("Pyth price is too old"));
+    }
+
+    let price: i64 = pyth_price
+        .price
+        .parse()
+        .map_err(|_| StdError::generic_err("Invalid Pyth price"))?;
+    let conf: u64 = pyth_price
+        .conf
+        .parse()
+        .map_err(|_| StdError::generic_err("Invalid Pyth price"))?;
+    if price <= 0 {
+        return Err(StdError::generic_err("Pyth price must be positive"));
+    }
+    if Decimal256::from_ratio(conf, price as u64) > pyth_feed.max_confidence {
+        return Err(StdError::generic_err(
+            "Pyth price confidence interval is too wide",
+        ));
+    }
+
+    Ok((
+        PriceInfo {
+            price: normalize_price(price as u64, pyth_price.expo)?,
+            last_updated_time: publish_time,
+        },
+        normalize_price(conf, pyth_price.expo)?,
+    ))
+}
+
+/// price * 10^expo, truncated to the 18 decimals of Decimal256
+fn normalize_price(price: u64, expo: i32) -> StdResult<Decimal256> {
+    if expo.abs() > MAX_EXPO {
+        return Err(StdError::generic_err("Invalid Pyth price exponent"));
+    }
+
+    let price = Uint256::from(price);
+    let scale = Uint256::from(10u128.pow(expo.unsigned_abs()));
+    Ok(if expo < 0 {
+        Decimal256::from_uint256(price) / Decimal256::from_uint256(scale)
+    } else {
+        Decimal256::from_uint256(price * scale)
+    })
+}
+
+pub fn query_pyth_feed(deps: Deps, asset: String) -> StdResult<PythFeedResponse> {
+    let pyth_feed = read_pyth_feed(deps.storage, &asset)?;
+
+    Ok(PythFeedResponse {
+        asset,
+        pyth_feed: pyth_feed
+            .map(|pyth_feed| {
+                Ok::<PythFeed, StdError>(PythFeed {
+                    contract_addr: deps
+                        .api
+                        .addr_humanize(&pyth_feed.contract_addr)?
+                        .to_string(),
+                    price_id: pyth_feed.price_id,
+                    max_confidence: pyth_feed.max_confidence,
+                    max_price_age: pyth_feed.max_price_age,
+                })
+            })
+            .transpose()?,
+    })
+}
@@ -0,0 +1,122 @@
+use crate::contract::{apply_fed_price, assert_fed_asset};
+use crate::emergency_price::active_emergency_price;
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_feeder_quorum, read_price, read_signer, store_signer, Config, PriceInfo,
+};
+use crate::subscription::subscription_messages;
+use cosmwasm_std::{
+    attr, from_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
+};
+use moneymarket::oracle::{SignedPrices, SignerResponse};
+
+// length of ed25519 public keys
+const ED25519_PUBKEY_LEN: usize = 32;
+
+pub fn register_signer(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    signer: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(signer) = signer.as_ref() {
+        if signer.len() != ED25519_PUBKEY_LEN {
+            return Err(ContractError::InvalidSigner {});
+        }
+    }
+    store_signer(deps.storage, &asset, signer.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_signer"),
+        attr("asset", asset),
+        attr(
+            "signer",
+            signer.map(|signer| signer.to_base64()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Feed prices signed off-chain, relayed by anyone. Each price must be
+/// signed by the signer of its asset and newer than the asset price,
+/// which prevents replays; the payload names the chain and the oracle
+/// contract so it is not replayed on another deployment
+pub fn feed_prices_signed(
+    mut deps: DepsMut,
+    env: Env,
+    payload: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let signed_prices: SignedPrices = from_binary(&payload)?;
+    if signed_prices.chain_id != env.block.chain_id
+        || signed_prices.oracle_contract != env.contract.address.as_str()
+    {
+        return Err(ContractError::InvalidSignedDomain {});
+    }
+
+    let mut verified_signers: Vec<Binary> = vec![];
+    let mut messages: Vec<SubMsg> = vec![];
+    let mut attributes = vec![attr("action", "feed_prices_signed")];
+    for signed_price in signed_prices.prices {
+        let asset = signed_price.asset;
+        assert_fed_asset(deps.storage, &asset)?;
+
+        // quorum prices are fed by the quorum feeders
+        let signer = match read_signer(deps.storage, &asset)? {
+            Some(signer) if read_feeder_quorum(deps.storage, &asset)?.is_none() => signer,
+            _ => return Err(ContractError::Unauthorized {}),
+        };
+        if !verified_signers.contains(&signer) {
+            // malformed signatures do not verify either
+            let verified = deps
+                .api
+                .ed25519_verify(payload.as_slice(), signature.as_slice(), signer.as_slice())
+                .unwrap_or(false);
+            if !verified {
+                return Err(ContractError::InvalidSignature {});
+            }
+            verified_signers.push(signer);
+        }
+
+        let last_updated_time = read_price(deps.storage, &asset)
+            .map(|price| price.last_updated_time)
+            .unwrap_or_default();
+        if signed_price.timestamp > env.block.time.seconds()
+            || signed_price.timestamp <= last_updated_time
+        {
+            return Err(ContractError::InvalidSignedPrice(asset));
+        }
+
+        let price_info = PriceInfo {
+            price: signed_price.price,
+            last_updated_time: signed_price.timestamp,
+        };
+        if apply_fed_price(
+            deps.storage,
+            &asset,
+            Some(price_info),
+            env.block.time.seconds(),
+        )? {
+            attributes.push(attr("held_asset", asset.to_string()));
+        }
+        if active_emergency_price(deps.storage, &env, &asset)?.is_some() {
+            attributes.push(attr("overridden_asset", asset.to_string()));
+        }
+        messages.extend(subscription_messages(deps.branch(), &asset)?);
+        attributes.push(attr("asset", asset));
+        attributes.push(attr("price", signed_price.price.to_string()));
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(attributes))
+}
+
+pub fn query_signer(deps: Deps, asset: String) -> StdResult<SignerResponse> {
+    let signer = read_signer(deps.storage, &asset)?;
+    Ok(SignerResponse { asset, signer })
+}
@@ -2,15 +2,46 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage};
+use cosmwasm_std::{Binary, CanonicalAddr, Order, StdError, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
-use moneymarket::oracle::PricesResponseElem;
+use moneymarket::oracle::{FallbackPolicy, PriceSource, PricesResponseElem};
+use moneymarket::pagination::{calc_limit, calc_range_start};
 
 static PREFIX_PRICE: &[u8] = b"price";
 static PREFIX_FEEDER: &[u8] = b"feeder";
+static PREFIX_OBSERVATION: &[u8] = b"observation";
+static PREFIX_FEEDER_QUORUM: &[u8] = b"feeder_quorum";
+static PREFIX_FEEDER_PRICE: &[u8] = b"feeder_price";
+static PREFIX_PYTH_FEED: &[u8] = b"pyth_feed";
+static PREFIX_BAND_FEED: &[u8] = b"band_feed";
+static PREFIX_PROPOSED_FEEDER: &[u8] = b"proposed_feeder";
+static PREFIX_PREVIOUS_FEEDER: &[u8] = b"previous_feeder";
+static PREFIX_PRICE_QUOTE: &[u8] = b"price_quote";
+static PREFIX_SIGNER: &[u8] = b"signer";
+static PREFIX_HEARTBEAT: &[u8] = b"heartbeat";
+static PREFIX_FALLBACK_POLICY: &[u8] = b"fallback_policy";
+static PREFIX_FEEDER_STATS: &[u8] = b"feeder_stats";
+static PREFIX_PRICE_CHECKPOINT: &[u8] = b"price_checkpoint";
+static PREFIX_NEXT_CHECKPOINT_SLOT: &[u8] = b"next_checkpoint_slot";
+static PREFIX_MAX_PRICE_AGE: &[u8] = b"max_price_age";
+static PREFIX_EXCHANGE_RATE_FEED: &[u8] = b"exchange_rate_feed";
+static PREFIX_DEVIATION_GUARD: &[u8] = b"deviation_guard";
+static PREFIX_PENDING_PRICE: &[u8] = b"pending_price";
+static PREFIX_ASSET_METADATA: &[u8] = b"asset_metadata";
+static PREFIX_AMM_FEED: &[u8] = b"amm_feed";
+static PREFIX_AMM_SNAPSHOTS: &[u8] = b"amm_snapshots";
+static PREFIX_SUBSCRIPTION: &[u8] = b"subscription";
+static PREFIX_EMERGENCY_PRICE: &[u8] = b"emergency_price";
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_TWAP_WINDOW: &[u8] = b"twap_window";
+
+/// TWAP window when none is set, 30 minutes
+pub const DEFAULT_TWAP_WINDOW: u64 = 1800;
+
+/// Checkpoints kept in the price history of each asset
+pub const PRICE_HISTORY_SIZE: u32 = 30;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -48,9 +79,6 @@ pub fn read_price(storage: &dyn Storage, asset: &str) -> StdResult<PriceInfo> {
     }
 }
 
-// settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
 pub fn read_prices(
     storage: &dyn Storage,
     start_after: Option<String>,
@@ -58,7 +86,7 @@ pub fn read_prices(
 ) -> StdResult<Vec<PricesResponseElem>> {
     let price_bucket: ReadonlyBucket<PriceInfo> = ReadonlyBucket::new(storage, PREFIX_PRICE);
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit(limit);
     let start = calc_range_start(start_after);
 
     price_bucket
@@ -72,6 +100,7 @@ pub fn read_prices(
                 asset,
                 price: v.price,
                 last_updated_time: v.last_updated_time,
+                metadata: None,
             })
         })
         .collect()
@@ -97,11 +126,718 @@ pub fn read_feeder(storage: &dyn Storage, asset: &str) -> StdResult<CanonicalAdd
     }
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<String>) -> Option<Vec<u8>> {
-    start_after.map(|idx| {
-        let mut v = idx.as_bytes().to_vec();
-        v.push(1);
-        v
-    })
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposedFeederInfo {
+    pub feeder: CanonicalAddr,
+    pub overlap: u64,
+}
+
+pub fn store_proposed_feeder(
+    storage: &mut dyn Storage,
+    asset: &str,
+    proposed_feeder: Option<ProposedFeederInfo>,
+) -> StdResult<()> {
+    let mut proposed_feeder_bucket: Bucket<ProposedFeederInfo> =
+        Bucket::new(storage, PREFIX_PROPOSED_FEEDER);
+    match proposed_feeder {
+        Some(proposed_feeder) => proposed_feeder_bucket.save(asset.as_bytes(), &proposed_feeder),
+        None => {
+            proposed_feeder_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_proposed_feeder(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<ProposedFeederInfo>> {
+    let proposed_feeder_bucket: ReadonlyBucket<ProposedFeederInfo> =
+        ReadonlyBucket::new(storage, PREFIX_PROPOSED_FEEDER);
+    proposed_feeder_bucket.may_load(asset.as_bytes())
+}
+
+/// Feeder replaced by a rotation, accepted until `until`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PreviousFeederInfo {
+    pub feeder: CanonicalAddr,
+    pub until: u64,
+}
+
+pub fn store_previous_feeder(
+    storage: &mut dyn Storage,
+    asset: &str,
+    previous_feeder: Option<PreviousFeederInfo>,
+) -> StdResult<()> {
+    let mut previous_feeder_bucket: Bucket<PreviousFeederInfo> =
+        Bucket::new(storage, PREFIX_PREVIOUS_FEEDER);
+    match previous_feeder {
+        Some(previous_feeder) => previous_feeder_bucket.save(asset.as_bytes(), &previous_feeder),
+        None => {
+            previous_feeder_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_previous_feeder(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<PreviousFeederInfo>> {
+    let previous_feeder_bucket: ReadonlyBucket<PreviousFeederInfo> =
+        ReadonlyBucket::new(storage, PREFIX_PREVIOUS_FEEDER);
+    previous_feeder_bucket.may_load(asset.as_bytes())
+}
+
+pub fn store_twap_window(storage: &mut dyn Storage, twap_window: Option<u64>) -> StdResult<()> {
+    match twap_window {
+        Some(twap_window) => singleton(storage, KEY_TWAP_WINDOW).save(&twap_window),
+        None => {
+            singleton::<u64>(storage, KEY_TWAP_WINDOW).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_twap_window(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(singleton_read(storage, KEY_TWAP_WINDOW)
+        .may_load()?
+        .unwrap_or(DEFAULT_TWAP_WINDOW))
+}
+
+/// Record the price in the history ring buffer of the asset,
+/// overwriting its oldest checkpoint once full. Prices of a same
+/// block share a checkpoint, the last one kept
+pub fn store_price_checkpoint(
+    storage: &mut dyn Storage,
+    asset: &str,
+    price: &PriceInfo,
+) -> StdResult<()> {
+    let next_slot_bucket: ReadonlyBucket<u32> =
+        ReadonlyBucket::new(storage, PREFIX_NEXT_CHECKPOINT_SLOT);
+    let next_slot = next_slot_bucket.may_load(asset.as_bytes())?.unwrap_or(0);
+    let last_slot = (next_slot + PRICE_HISTORY_SIZE - 1) % PRICE_HISTORY_SIZE;
+
+    let mut checkpoint_bucket: Bucket<PriceInfo> =
+        Bucket::multilevel(storage, &[PREFIX_PRICE_CHECKPOINT, asset.as_bytes()]);
+    let slot = match checkpoint_bucket.may_load(&last_slot.to_be_bytes())? {
+        Some(checkpoint) if checkpoint.last_updated_time == price.last_updated_time => last_slot,
+        _ => next_slot,
+    };
+    checkpoint_bucket.save(&slot.to_be_bytes(), price)?;
+
+    let mut next_slot_bucket: Bucket<u32> = Bucket::new(storage, PREFIX_NEXT_CHECKPOINT_SLOT);
+    next_slot_bucket.save(asset.as_bytes(), &((slot + 1) % PRICE_HISTORY_SIZE))
+}
+
+/// Price checkpoints of the asset, newest first
+pub fn read_price_checkpoints(storage: &dyn Storage, asset: &str) -> StdResult<Vec<PriceInfo>> {
+    let checkpoint_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_PRICE_CHECKPOINT, asset.as_bytes()]);
+    let mut checkpoints: Vec<PriceInfo> = checkpoint_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<PriceInfo>>>()?;
+    checkpoints.sort_by_key(|checkpoint| std::cmp::Reverse(checkpoint.last_updated_time));
+    Ok(checkpoints)
+}
+
+pub fn store_price_quote(
+    storage: &mut dyn Storage,
+    asset: &str,
+    quote_asset: Option<String>,
+) -> StdResult<()> {
+    let mut price_quote_bucket: Bucket<String> = Bucket::new(storage, PREFIX_PRICE_QUOTE);
+    match quote_asset {
+        Some(quote_asset) => price_quote_bucket.save(asset.as_bytes(), &quote_asset),
+        None => {
+            price_quote_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_price_quote(storage: &dyn Storage, asset: &str) -> StdResult<Option<String>> {
+    let price_quote_bucket: ReadonlyBucket<String> =
+        ReadonlyBucket::new(storage, PREFIX_PRICE_QUOTE);
+    price_quote_bucket.may_load(asset.as_bytes())
+}
+
+/// Whether any asset is quoted in `quote_asset`
+pub fn is_quote_asset(storage: &dyn Storage, quote_asset: &str) -> StdResult<bool> {
+    let price_quote_bucket: ReadonlyBucket<String> =
+        ReadonlyBucket::new(storage, PREFIX_PRICE_QUOTE);
+    for item in price_quote_bucket.range(None, None, Order::Ascending) {
+        if item?.1 == quote_asset {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+pub fn store_signer(
+    storage: &mut dyn Storage,
+    asset: &str,
+    signer: Option<Binary>,
+) -> StdResult<()> {
+    let mut signer_bucket: Bucket<Binary> = Bucket::new(storage, PREFIX_SIGNER);
+    match signer {
+        Some(signer) => signer_bucket.save(asset.as_bytes(), &signer),
+        None => {
+            signer_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_signer(storage: &dyn Storage, asset: &str) -> StdResult<Option<Binary>> {
+    let signer_bucket: ReadonlyBucket<Binary> = ReadonlyBucket::new(storage, PREFIX_SIGNER);
+    signer_bucket.may_load(asset.as_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Heartbeat {
+    pub interval: u64,
+    pub max_missed: u32,
+}
+
+pub fn store_heartbeat(
+    storage: &mut dyn Storage,
+    asset: &str,
+    heartbeat: Option<Heartbeat>,
+) -> StdResult<()> {
+    let mut heartbeat_bucket: Bucket<Heartbeat> = Bucket::new(storage, PREFIX_HEARTBEAT);
+    match heartbeat {
+        Some(heartbeat) => heartbeat_bucket.save(asset.as_bytes(), &heartbeat),
+        None => {
+            heartbeat_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_heartbeat(storage: &dyn Storage, asset: &str) -> StdResult<Option<Heartbeat>> {
+    let heartbeat_bucket: ReadonlyBucket<Heartbeat> =
+        ReadonlyBucket::new(storage, PREFIX_HEARTBEAT);
+    heartbeat_bucket.may_load(asset.as_bytes())
+}
+
+pub fn store_fallback_policy(
+    storage: &mut dyn Storage,
+    asset: &str,
+    fallback_policy: Option<FallbackPolicy>,
+) -> StdResult<()> {
+    let mut fallback_policy_bucket: Bucket<FallbackPolicy> =
+        Bucket::new(storage, PREFIX_FALLBACK_POLICY);
+    match fallback_policy {
+        Some(fallback_policy) => fallback_policy_bucket.save(asset.as_bytes(), &fallback_policy),
+        None => {
+            fallback_policy_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_fallback_policy(storage: &dyn Storage, asset: &str) -> StdResult<FallbackPolicy> {
+    let fallback_policy_bucket: ReadonlyBucket<FallbackPolicy> =
+        ReadonlyBucket::new(storage, PREFIX_FALLBACK_POLICY);
+    Ok(fallback_policy_bucket
+        .may_load(asset.as_bytes())?
+        .unwrap_or(FallbackPolicy::Fail))
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederStats {
+    pub updates: u64,
+    pub last_update_time: Option<u64>,
+    pub max_gap: u64,
+    pub paused: bool,
+}
+
+pub fn store_feeder_stats(
+    storage: &mut dyn Storage,
+    asset: &str,
+    feeder_stats: &FeederStats,
+) -> StdResult<()> {
+    let mut feeder_stats_bucket: Bucket<FeederStats> = Bucket::new(storage, PREFIX_FEEDER_STATS);
+    feeder_stats_bucket.save(asset.as_bytes(), feeder_stats)
+}
+
+pub fn read_feeder_stats(storage: &dyn Storage, asset: &str) -> StdResult<FeederStats> {
+    let feeder_stats_bucket: ReadonlyBucket<FeederStats> =
+        ReadonlyBucket::new(storage, PREFIX_FEEDER_STATS);
+    Ok(feeder_stats_bucket
+        .may_load(asset.as_bytes())?
+        .unwrap_or_default())
+}
+
+pub fn store_max_price_age(
+    storage: &mut dyn Storage,
+    asset: &str,
+    max_price_age: Option<u64>,
+) -> StdResult<()> {
+    let mut max_price_age_bucket: Bucket<u64> = Bucket::new(storage, PREFIX_MAX_PRICE_AGE);
+    match max_price_age {
+        Some(max_price_age) => max_price_age_bucket.save(asset.as_bytes(), &max_price_age),
+        None => {
+            max_price_age_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_max_price_age(storage: &dyn Storage, asset: &str) -> StdResult<Option<u64>> {
+    let max_price_age_bucket: ReadonlyBucket<u64> =
+        ReadonlyBucket::new(storage, PREFIX_MAX_PRICE_AGE);
+    max_price_age_bucket.may_load(asset.as_bytes())
+}
+
+/// Price fed at `time`, with the cumulative price up to that time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    pub time: u64,
+    pub price: Decimal256,
+    pub cumulative_price: Decimal256,
+}
+
+pub fn store_observation(
+    storage: &mut dyn Storage,
+    asset: &str,
+    observation: &Observation,
+) -> StdResult<()> {
+    let mut observation_bucket: Bucket<Observation> =
+        Bucket::multilevel(storage, &[PREFIX_OBSERVATION, asset.as_bytes()]);
+    observation_bucket.save(&observation.time.to_be_bytes(), observation)
+}
+
+pub fn remove_observation(storage: &mut dyn Storage, asset: &str, time: u64) {
+    let mut observation_bucket: Bucket<Observation> =
+        Bucket::multilevel(storage, &[PREFIX_OBSERVATION, asset.as_bytes()]);
+    observation_bucket.remove(&time.to_be_bytes())
+}
+
+/// Latest observation made at or before `time`
+pub fn read_observation_at(
+    storage: &dyn Storage,
+    asset: &str,
+    time: u64,
+) -> StdResult<Option<Observation>> {
+    let observation_bucket: ReadonlyBucket<Observation> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_OBSERVATION, asset.as_bytes()]);
+    let observation = observation_bucket
+        .range(None, Some(&(time + 1).to_be_bytes()), Order::Descending)
+        .next()
+        .map(|item| Ok(item?.1))
+        .transpose();
+    observation
+}
+
+pub fn read_oldest_observations(
+    storage: &dyn Storage,
+    asset: &str,
+    limit: usize,
+) -> StdResult<Vec<Observation>> {
+    let observation_bucket: ReadonlyBucket<Observation> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_OBSERVATION, asset.as_bytes()]);
+    observation_bucket
+        .range(None, None, Order::Ascending)
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederQuorumInfo {
+    pub feeders: Vec<CanonicalAddr>,
+    pub quorum: u32,
+    pub max_price_age: u64,
+}
+
+pub fn store_feeder_quorum(
+    storage: &mut dyn Storage,
+    asset: &str,
+    feeder_quorum: Option<FeederQuorumInfo>,
+) -> StdResult<()> {
+    let mut feeder_quorum_bucket: Bucket<FeederQuorumInfo> =
+        Bucket::new(storage, PREFIX_FEEDER_QUORUM);
+    match feeder_quorum {
+        Some(feeder_quorum) => feeder_quorum_bucket.save(asset.as_bytes(), &feeder_quorum),
+        None => {
+            feeder_quorum_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_feeder_quorum(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<FeederQuorumInfo>> {
+    let feeder_quorum_bucket: ReadonlyBucket<FeederQuorumInfo> =
+        ReadonlyBucket::new(storage, PREFIX_FEEDER_QUORUM);
+    feeder_quorum_bucket.may_load(asset.as_bytes())
+}
+
+pub fn store_feeder_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    feeder: &CanonicalAddr,
+    price: &PriceInfo,
+) -> StdResult<()> {
+    let mut feeder_price_bucket: Bucket<PriceInfo> =
+        Bucket::multilevel(storage, &[PREFIX_FEEDER_PRICE, asset.as_bytes()]);
+    feeder_price_bucket.save(feeder.as_slice(), price)
+}
+
+pub fn read_feeder_prices(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Vec<(CanonicalAddr, PriceInfo)>> {
+    let feeder_price_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_FEEDER_PRICE, asset.as_bytes()]);
+    let feeder_prices = feeder_price_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect();
+    feeder_prices
+}
+
+pub fn remove_feeder_prices(storage: &mut dyn Storage, asset: &str) -> StdResult<()> {
+    let feeders: Vec<CanonicalAddr> = read_feeder_prices(storage, asset)?
+        .into_iter()
+        .map(|(feeder, _)| feeder)
+        .collect();
+    let mut feeder_price_bucket: Bucket<PriceInfo> =
+        Bucket::multilevel(storage, &[PREFIX_FEEDER_PRICE, asset.as_bytes()]);
+    for feeder in feeders.iter() {
+        feeder_price_bucket.remove(feeder.as_slice());
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PythFeedInfo {
+    pub contract_addr: CanonicalAddr,
+    pub price_id: String,
+    pub max_confidence: Decimal256,
+    pub max_price_age: u64,
+}
+
+pub fn store_pyth_feed(
+    storage: &mut dyn Storage,
+    asset: &str,
+    pyth_feed: Option<PythFeedInfo>,
+) -> StdResult<()> {
+    let mut pyth_feed_bucket: Bucket<PythFeedInfo> = Bucket::new(storage, PREFIX_PYTH_FEED);
+    match pyth_feed {
+        Some(pyth_feed) => pyth_feed_bucket.save(asset.as_bytes(), &pyth_feed),
+        None => {
+            pyth_feed_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_pyth_feed(storage: &dyn Storage, asset: &str) -> StdResult<Option<PythFeedInfo>> {
+    let pyth_feed_bucket: ReadonlyBucket<PythFeedInfo> =
+        ReadonlyBucket::new(storage, PREFIX_PYTH_FEED);
+    pyth_feed_bucket.may_load(asset.as_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BandFeedInfo {
+    pub contract_addr: CanonicalAddr,
+    pub base_symbol: String,
+    pub quote_symbol: String,
+    pub max_price_age: u64,
+}
+
+pub fn store_band_feed(
+    storage: &mut dyn Storage,
+    asset: &str,
+    band_feed: Option<BandFeedInfo>,
+) -> StdResult<()> {
+    let mut band_feed_bucket: Bucket<BandFeedInfo> = Bucket::new(storage, PREFIX_BAND_FEED);
+    match band_feed {
+        Some(band_feed) => band_feed_bucket.save(asset.as_bytes(), &band_feed),
+        None => {
+            band_feed_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_band_feed(storage: &dyn Storage, asset: &str) -> StdResult<Option<BandFeedInfo>> {
+    let band_feed_bucket: ReadonlyBucket<BandFeedInfo> =
+        ReadonlyBucket::new(storage, PREFIX_BAND_FEED);
+    band_feed_bucket.may_load(asset.as_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateFeedInfo {
+    pub hub_contract: CanonicalAddr,
+    pub underlying_asset: String,
+    pub max_rate_age: u64,
+}
+
+pub fn store_exchange_rate_feed(
+    storage: &mut dyn Storage,
+    asset: &str,
+    exchange_rate_feed: Option<ExchangeRateFeedInfo>,
+) -> StdResult<()> {
+    let mut exchange_rate_feed_bucket: Bucket<ExchangeRateFeedInfo> =
+        Bucket::new(storage, PREFIX_EXCHANGE_RATE_FEED);
+    match exchange_rate_feed {
+        Some(exchange_rate_feed) => {
+            exchange_rate_feed_bucket.save(asset.as_bytes(), &exchange_rate_feed)
+        }
+        None => {
+            exchange_rate_feed_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_exchange_rate_feed(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<ExchangeRateFeedInfo>> {
+    let exchange_rate_feed_bucket: ReadonlyBucket<ExchangeRateFeedInfo> =
+        ReadonlyBucket::new(storage, PREFIX_EXCHANGE_RATE_FEED);
+    exchange_rate_feed_bucket.may_load(asset.as_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeviationGuard {
+    pub max_deviation: Decimal256,
+    pub window: u64,
+}
+
+pub fn store_deviation_guard(
+    storage: &mut dyn Storage,
+    asset: &str,
+    deviation_guard: Option<DeviationGuard>,
+) -> StdResult<()> {
+    let mut deviation_guard_bucket: Bucket<DeviationGuard> =
+        Bucket::new(storage, PREFIX_DEVIATION_GUARD);
+    match deviation_guard {
+        Some(deviation_guard) => deviation_guard_bucket.save(asset.as_bytes(), &deviation_guard),
+        None => {
+            deviation_guard_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_deviation_guard(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<DeviationGuard>> {
+    let deviation_guard_bucket: ReadonlyBucket<DeviationGuard> =
+        ReadonlyBucket::new(storage, PREFIX_DEVIATION_GUARD);
+    deviation_guard_bucket.may_load(asset.as_bytes())
+}
+
+pub fn store_pending_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    pending_price: Option<PriceInfo>,
+) -> StdResult<()> {
+    let mut pending_price_bucket: Bucket<PriceInfo> = Bucket::new(storage, PREFIX_PENDING_PRICE);
+    match pending_price {
+        Some(pending_price) => pending_price_bucket.save(asset.as_bytes(), &pending_price),
+        None => {
+            pending_price_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_pending_price(storage: &dyn Storage, asset: &str) -> StdResult<Option<PriceInfo>> {
+    let pending_price_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_PRICE);
+    pending_price_bucket.may_load(asset.as_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetMetadataInfo {
+    pub decimals: u8,
+    pub source: PriceSource,
+    pub hub_contract: Option<CanonicalAddr>,
+    pub category: Option<String>,
+}
+
+pub fn store_asset_metadata(
+    storage: &mut dyn Storage,
+    asset: &str,
+    asset_metadata: Option<AssetMetadataInfo>,
+) -> StdResult<()> {
+    let mut asset_metadata_bucket: Bucket<AssetMetadataInfo> =
+        Bucket::new(storage, PREFIX_ASSET_METADATA);
+    match asset_metadata {
+        Some(asset_metadata) => asset_metadata_bucket.save(asset.as_bytes(), &asset_metadata),
+        None => {
+            asset_metadata_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_asset_metadata(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<AssetMetadataInfo>> {
+    let asset_metadata_bucket: ReadonlyBucket<AssetMetadataInfo> =
+        ReadonlyBucket::new(storage, PREFIX_ASSET_METADATA);
+    asset_metadata_bucket.may_load(asset.as_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmmFeedInfo {
+    pub pair_contract: CanonicalAddr,
+    pub asset_index: u8,
+    pub window: u64,
+    pub max_deviation: Decimal256,
+}
+
+/// Cumulative price of the AMM pair at `time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmmSnapshot {
+    pub time: u64,
+    pub cumulative_price: Uint128,
+}
+
+/// The previous snapshot prices the TWAP until the current one is as old
+/// as the window
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmmSnapshots {
+    pub current: AmmSnapshot,
+    pub previous: Option<AmmSnapshot>,
+}
+
+pub fn store_amm_feed(
+    storage: &mut dyn Storage,
+    asset: &str,
+    amm_feed: Option<AmmFeedInfo>,
+) -> StdResult<()> {
+    let mut amm_feed_bucket: Bucket<AmmFeedInfo> = Bucket::new(storage, PREFIX_AMM_FEED);
+    match amm_feed {
+        Some(amm_feed) => amm_feed_bucket.save(asset.as_bytes(), &amm_feed),
+        None => {
+            amm_feed_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_amm_feed(storage: &dyn Storage, asset: &str) -> StdResult<Option<AmmFeedInfo>> {
+    let amm_feed_bucket: ReadonlyBucket<AmmFeedInfo> =
+        ReadonlyBucket::new(storage, PREFIX_AMM_FEED);
+    amm_feed_bucket.may_load(asset.as_bytes())
+}
+
+pub fn store_amm_snapshots(
+    storage: &mut dyn Storage,
+    asset: &str,
+    amm_snapshots: Option<AmmSnapshots>,
+) -> StdResult<()> {
+    let mut amm_snapshots_bucket: Bucket<AmmSnapshots> = Bucket::new(storage, PREFIX_AMM_SNAPSHOTS);
+    match amm_snapshots {
+        Some(amm_snapshots) => amm_snapshots_bucket.save(asset.as_bytes(), &amm_snapshots),
+        None => {
+            amm_snapshots_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_amm_snapshots(storage: &dyn Storage, asset: &str) -> StdResult<Option<AmmSnapshots>> {
+    let amm_snapshots_bucket: ReadonlyBucket<AmmSnapshots> =
+        ReadonlyBucket::new(storage, PREFIX_AMM_SNAPSHOTS);
+    amm_snapshots_bucket.may_load(asset.as_bytes())
+}
+
+/// `band` is the # of thresholds at or below the last notified price
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Subscription {
+    pub thresholds: Vec<Decimal256>,
+    pub band: u32,
+}
+
+pub fn store_subscription(
+    storage: &mut dyn Storage,
+    asset: &str,
+    subscriber: &CanonicalAddr,
+    subscription: Option<Subscription>,
+) -> StdResult<()> {
+    let mut subscription_bucket: Bucket<Subscription> =
+        Bucket::multilevel(storage, &[PREFIX_SUBSCRIPTION, asset.as_bytes()]);
+    match subscription {
+        Some(subscription) => subscription_bucket.save(subscriber.as_slice(), &subscription),
+        None => {
+            subscription_bucket.remove(subscriber.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_subscription(
+    storage: &dyn Storage,
+    asset: &str,
+    subscriber: &CanonicalAddr,
+) -> StdResult<Option<Subscription>> {
+    let subscription_bucket: ReadonlyBucket<Subscription> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_SUBSCRIPTION, asset.as_bytes()]);
+    subscription_bucket.may_load(subscriber.as_slice())
+}
+
+pub fn read_subscriptions(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Vec<(CanonicalAddr, Subscription)>> {
+    let subscription_bucket: ReadonlyBucket<Subscription> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_SUBSCRIPTION, asset.as_bytes()]);
+    subscription_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmergencyPrice {
+    pub price: Decimal256,
+    pub expires_at: u64,
+}
+
+pub fn store_emergency_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    emergency_price: Option<EmergencyPrice>,
+) -> StdResult<()> {
+    let mut emergency_price_bucket: Bucket<EmergencyPrice> =
+        Bucket::new(storage, PREFIX_EMERGENCY_PRICE);
+    match emergency_price {
+        Some(emergency_price) => emergency_price_bucket.save(asset.as_bytes(), &emergency_price),
+        None => {
+            emergency_price_bucket.remove(asset.as_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_emergency_price(
+    storage: &dyn Storage,
+    asset: &str,
+) -> StdResult<Option<EmergencyPrice>> {
+    let emergency_price_bucket: ReadonlyBucket<EmergencyPrice> =
+        ReadonlyBucket::new(storage, PREFIX_EMERGENCY_PRICE);
+    emergency_price_bucket.may_load(asset.as_bytes())
 }
@@ -0,0 +1,160 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_price, read_subscription, read_subscriptions, store_subscription, Config,
+    Subscription,
+};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, to_binary, CosmosMsg, Deps, DepsMut, MessageInfo, ReplyOn, Response, StdResult, SubMsg,
+    WasmMsg,
+};
+use moneymarket::oracle::{PriceSubscriberMsg, SubscriptionResponse, SubscriptionsResponse};
+
+pub const PRICE_CALLBACK_REPLY_ID: u64 = 1;
+
+// gas available to a subscriber callback,
+// so it cannot make the feed emitting it run out of gas
+const PRICE_CALLBACK_GAS_LIMIT: u64 = 300_000;
+
+// maximum number of thresholds of a single subscription
+const MAX_PRICE_THRESHOLDS: u32 = 10;
+
+// maximum number of subscribers of a single asset
+const MAX_SUBSCRIBERS: u32 = 10;
+
+pub fn subscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    thresholds: Vec<Decimal256>,
+) -> Result<Response, ContractError> {
+    if thresholds.is_empty()
+        || thresholds.len() > MAX_PRICE_THRESHOLDS as usize
+        || thresholds[0].is_zero()
+        || thresholds.windows(2).any(|t| t[0] >= t[1])
+    {
+        return Err(ContractError::InvalidPriceThresholds(MAX_PRICE_THRESHOLDS));
+    }
+
+    let subscriber_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_subscription(deps.storage, &asset, &subscriber_raw)?.is_none()
+        && read_subscriptions(deps.storage, &asset)?.len() >= MAX_SUBSCRIBERS as usize
+    {
+        return Err(ContractError::TooManySubscribers(MAX_SUBSCRIBERS));
+    }
+
+    // the subscriber is notified of moves from the current price
+    let band = match read_price(deps.storage, &asset) {
+        Ok(price) => price_band(&thresholds, price.price),
+        Err(_) => 0,
+    };
+    store_subscription(
+        deps.storage,
+        &asset,
+        &subscriber_raw,
+        Some(Subscription { thresholds, band }),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "subscribe"),
+        attr("asset", asset),
+        attr("subscriber", info.sender),
+    ]))
+}
+
+pub fn unsubscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    subscriber: Option<String>,
+) -> Result<Response, ContractError> {
+    let subscriber = match subscriber {
+        Some(subscriber) => {
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            deps.api.addr_validate(&subscriber)?
+        }
+        None => info.sender,
+    };
+
+    store_subscription(
+        deps.storage,
+        &asset,
+        &deps.api.addr_canonicalize(subscriber.as_str())?,
+        None,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unsubscribe"),
+        attr("asset", asset),
+        attr("subscriber", subscriber),
+    ]))
+}
+
+fn price_band(thresholds: &[Decimal256], price: Decimal256) -> u32 {
+    thresholds
+        .iter()
+        .filter(|threshold| **threshold <= price)
+        .count() as u32
+}
+
+/// Callbacks to the subscribers of the asset whose price moved into
+/// another band since the last notification. The callbacks are gas
+/// capped and their failure is ignored in the reply.
+pub(crate) fn subscription_messages(deps: DepsMut, asset: &str) -> StdResult<Vec<SubMsg>> {
+    let price = match read_price(deps.storage, asset) {
+        Ok(price) => price.price,
+        Err(_) => return Ok(vec![]),
+    };
+
+    let mut messages: Vec<SubMsg> = vec![];
+    for (subscriber, mut subscription) in read_subscriptions(deps.storage, asset)? {
+        let band = price_band(&subscription.thresholds, price);
+        if band == subscription.band {
+            continue;
+        }
+
+        let prev_band = subscription.band;
+        subscription.band = band;
+        store_subscription(deps.storage, asset, &subscriber, Some(subscription))?;
+
+        messages.push(SubMsg {
+            id: PRICE_CALLBACK_REPLY_ID,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&subscriber)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&PriceSubscriberMsg::PriceBandCrossed {
+                    asset: asset.to_string(),
+                    price,
+                    prev_band,
+                    band,
+                })?,
+            }),
+            gas_limit: Some(PRICE_CALLBACK_GAS_LIMIT),
+            reply_on: ReplyOn::Error,
+        });
+    }
+
+    Ok(messages)
+}
+
+pub fn query_subscriptions(deps: Deps, asset: String) -> StdResult<SubscriptionsResponse> {
+    let subscriptions = read_subscriptions(deps.storage, &asset)?
+        .into_iter()
+        .map(|(subscriber, subscription)| {
+            Ok(SubscriptionResponse {
+                subscriber: deps.api.addr_humanize(&subscriber)?.to_string(),
+                thresholds: subscription.thresholds,
+                band: subscription.band,
+            })
+        })
+        .collect::<StdResult<Vec<SubscriptionResponse>>>()?;
+
+    Ok(SubscriptionsResponse {
+        asset,
+        subscriptions,
+    })
+}
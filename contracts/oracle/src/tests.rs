@@ -1,11 +1,27 @@
-use crate::contract::{execute, instantiate, query};
+use crate::amm::CumulativePricesResponse;
+use crate::band::ReferenceData;
+use crate::contract::{execute, instantiate, query, reply};
 use crate::error::ContractError;
-use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::from_binary;
+use crate::exchange_rate::HubStateResponse;
+use crate::mock_querier::mock_dependencies as mock_wasm_dependencies;
+use crate::pyth::{PriceFeed, PythPrice};
+use crate::state::read_oldest_observations;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Binary, ContractResult, CosmosMsg, Deps, Env, Reply, ReplyOn,
+    StdError, SubMsg, Uint128, WasmMsg,
+};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    AmmFeed, AmmFeedResponse, AmmTwapResponse, AssetMetadata, AssetMetadataResponse, BandFeed,
+    BandFeedResponse, ConfigResponse, DeviationGuard, DeviationGuardResponse,
+    EmergencyPriceResponse, ExchangeRateFeed, ExchangeRateFeedResponse, ExecuteMsg, FallbackPolicy,
+    FallbackPolicyResponse, FeederQuorum, FeederQuorumResponse, FeederResponse,
+    FeederRotationResponse, FeederStatsResponse, Heartbeat, HeartbeatResponse, InstantiateMsg,
+    MaxPriceAgeResponse, PendingPriceResponse, PriceCheckpoint, PriceHistoryResponse, PriceKind,
+    PriceQuoteResponse, PriceResponse, PriceSource, PriceSubscriberMsg, PricesResponse,
+    PricesResponseElem, PythFeed, PythFeedResponse, QueryMsg, SignerResponse, SubscriptionResponse,
+    SubscriptionsResponse, TwapWindowResponse, MAX_EMERGENCY_PRICE_DURATION,
 };
 use std::str::FromStr;
 
@@ -115,6 +131,210 @@ fn register_feeder() {
     );
 }
 
+#[test]
+fn feeder_rotation() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::ProposeFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0001".to_string(),
+        overlap: 60u64,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the current feeder hands off to the next one
+    let res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_feeder"),
+            attr("asset", "mAAPL"),
+            attr("feeder", "feeder0001"),
+            attr("overlap", "60"),
+        ]
+    );
+
+    let accept_msg = ExecuteMsg::AcceptFeeder {
+        asset: "mAAPL".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        accept_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0001", &[]),
+        accept_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "accept_feeder"),
+            attr("asset", "mAAPL"),
+            attr("feeder", "feeder0001"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0001", &[]),
+        accept_msg,
+    );
+    match res {
+        Err(ContractError::NoProposedFeeder {}) => (),
+        _ => panic!("Must return no proposed feeder error"),
+    }
+
+    let value: FeederResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Feeder {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(value.feeder, "feeder0001");
+
+    let value: FeederRotationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::FeederRotation {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        FeederRotationResponse {
+            asset: "mAAPL".to_string(),
+            proposed_feeder: None,
+            overlap: None,
+            previous_feeder: Some("feeder0000".to_string()),
+            previous_feeder_until: Some(env.block.time.seconds() + 60),
+        }
+    );
+
+    // both feeders are accepted during the overlap
+    let feed_msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::one())],
+    };
+    let mut feed_env = env.clone();
+    feed_env.block.time = feed_env.block.time.plus_seconds(60);
+    for feeder in ["feeder0000", "feeder0001"].iter() {
+        execute(
+            deps.as_mut(),
+            feed_env.clone(),
+            mock_info(feeder, &[]),
+            feed_msg.clone(),
+        )
+        .unwrap();
+    }
+
+    feed_env.block.time = feed_env.block.time.plus_seconds(1);
+    let res = execute(
+        deps.as_mut(),
+        feed_env.clone(),
+        mock_info("feeder0000", &[]),
+        feed_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the owner replaces a feeder at once, dropping the rotation
+    let msg = ExecuteMsg::ProposeFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0002".to_string(),
+        overlap: 60u64,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0003".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let value: FeederRotationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::FeederRotation {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        FeederRotationResponse {
+            asset: "mAAPL".to_string(),
+            proposed_feeder: None,
+            overlap: None,
+            previous_feeder: None,
+            previous_feeder_until: None,
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        feed_env.clone(),
+        mock_info("feeder0001", &[]),
+        feed_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+    execute(
+        deps.as_mut(),
+        feed_env,
+        mock_info("feeder0003", &[]),
+        feed_msg,
+    )
+    .unwrap();
+}
+
 #[test]
 fn feed_price() {
     let mut deps = mock_dependencies(&[]);
@@ -159,6 +379,7 @@ fn feed_price() {
         QueryMsg::Price {
             base: "mAAPL".to_string(),
             quote: "base0000".to_string(),
+            price_kind: None,
         },
     )
     .unwrap();
@@ -170,6 +391,8 @@ fn feed_price() {
             rate: Decimal256::from_str("1.2").unwrap(),
             last_updated_base: env.block.time.seconds(),
             last_updated_quote: 9999999999,
+            twap_rate: None,
+            confidence: None,
         }
     );
 
@@ -179,6 +402,7 @@ fn feed_price() {
         QueryMsg::Price {
             base: "mGOGL".to_string(),
             quote: "mAAPL".to_string(),
+            price_kind: None,
         },
     )
     .unwrap();
@@ -190,6 +414,8 @@ fn feed_price() {
             rate: Decimal256::from_str("1.833333333333333333").unwrap(),
             last_updated_base: env.block.time.seconds(),
             last_updated_quote: env.block.time.seconds(),
+            twap_rate: None,
+            confidence: None,
         }
     );
 
@@ -212,11 +438,13 @@ fn feed_price() {
                     asset: "mAAPL".to_string(),
                     price: Decimal256::from_str("1.2").unwrap(),
                     last_updated_time: env.block.time.seconds(),
+                    metadata: None,
                 },
                 PricesResponseElem {
                     asset: "mGOGL".to_string(),
                     price: Decimal256::from_str("2.2").unwrap(),
                     last_updated_time: env.block.time.seconds(),
+                    metadata: None,
                 }
             ],
         }
@@ -234,3 +462,2710 @@ fn feed_price() {
         _ => panic!("Must return unauthorized error"),
     }
 }
+
+#[test]
+fn max_price_age() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateMaxPriceAge {
+        asset: "mAAPL".to_string(),
+        max_price_age: Some(60u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_max_price_age"),
+            attr("asset", "mAAPL"),
+            attr("max_price_age", "60"),
+        ]
+    );
+
+    let value: MaxPriceAgeResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::MaxPriceAge {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        MaxPriceAgeResponse {
+            asset: "mAAPL".to_string(),
+            max_price_age: Some(60u64),
+        }
+    );
+
+    let env = mock_env();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("1.2").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    let price_query = QueryMsg::Price {
+        base: "mAAPL".to_string(),
+        quote: "base0000".to_string(),
+        price_kind: None,
+    };
+
+    let mut query_env = env.clone();
+    query_env.block.time = query_env.block.time.plus_seconds(60);
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), query_env.clone(), price_query.clone()).unwrap())
+            .unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.2").unwrap());
+
+    query_env.block.time = query_env.block.time.plus_seconds(1);
+    let res = query(deps.as_ref(), query_env.clone(), price_query.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(format!(
+            "Price of mAAPL is too old; last updated: {}",
+            env.block.time.seconds()
+        ))
+    );
+
+    // stale prices are accepted again once the max price age is removed
+    let msg = ExecuteMsg::UpdateMaxPriceAge {
+        asset: "mAAPL".to_string(),
+        max_price_age: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), query_env, price_query).unwrap()).unwrap();
+    assert_eq!(value.last_updated_base, env.block.time.seconds());
+}
+
+#[test]
+fn heartbeat() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let heartbeat_msg = |interval: u64, max_missed: u32| ExecuteMsg::UpdateHeartbeat {
+        asset: "mAAPL".to_string(),
+        heartbeat: Some(Heartbeat {
+            interval,
+            max_missed,
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        heartbeat_msg(60, 3),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    for msg in [heartbeat_msg(0, 3), heartbeat_msg(60, 0)].iter() {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::InvalidHeartbeat {}) => (),
+            _ => panic!("Must return invalid heartbeat error"),
+        }
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        heartbeat_msg(60, 3),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_heartbeat"),
+            attr("asset", "mAAPL"),
+            attr("interval", "60"),
+            attr("max_missed", "3"),
+        ]
+    );
+
+    let value: HeartbeatResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Heartbeat {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        HeartbeatResponse {
+            asset: "mAAPL".to_string(),
+            heartbeat: Some(Heartbeat {
+                interval: 60,
+                max_missed: 3,
+            }),
+        }
+    );
+
+    let feed_msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::one())],
+    };
+    let price_query = QueryMsg::Price {
+        base: "mAAPL".to_string(),
+        quote: "base0000".to_string(),
+        price_kind: None,
+    };
+    let query_stats = |deps: Deps, env: Env| {
+        from_binary::<FeederStatsResponse>(
+            &query(
+                deps,
+                env,
+                QueryMsg::FeederStats {
+                    asset: "mAAPL".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let start_time = mock_env().block.time.seconds();
+    let mut env = mock_env();
+    let info = mock_info("feeder0000", &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_msg.clone()).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_msg.clone()).unwrap();
+    env.block.time = env.block.time.plus_seconds(30);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_msg.clone()).unwrap();
+    assert_eq!(
+        query_stats(deps.as_ref(), env.clone()),
+        FeederStatsResponse {
+            asset: "mAAPL".to_string(),
+            updates: 3,
+            last_update_time: Some(start_time + 130),
+            max_gap: 100,
+            paused: false,
+        }
+    );
+
+    // the price is paused once three heartbeats are missed
+    env.block.time = env.block.time.plus_seconds(179);
+    query(deps.as_ref(), env.clone(), price_query.clone()).unwrap();
+    env.block.time = env.block.time.plus_seconds(1);
+    assert!(query_stats(deps.as_ref(), env.clone()).paused);
+    let res = query(deps.as_ref(), env.clone(), price_query.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Price of mAAPL is paused")
+    );
+
+    // and stays paused after the next update, until resumed
+    execute(deps.as_mut(), env.clone(), info, feed_msg).unwrap();
+    let res = query(deps.as_ref(), env.clone(), price_query.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Price of mAAPL is paused")
+    );
+
+    let msg = ExecuteMsg::ResumePrice {
+        asset: "mAAPL".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    query(deps.as_ref(), env.clone(), price_query).unwrap();
+    assert_eq!(
+        query_stats(deps.as_ref(), env),
+        FeederStatsResponse {
+            asset: "mAAPL".to_string(),
+            updates: 4,
+            last_update_time: Some(start_time + 310),
+            max_gap: 180,
+            paused: false,
+        }
+    );
+}
+
+#[test]
+fn fallback_policy() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("1.2").unwrap())],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg).unwrap();
+
+    let pause_msg = ExecuteMsg::PausePrice {
+        asset: "mAAPL".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder0000", &[]),
+        pause_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        pause_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause_price"), attr("asset", "mAAPL")]
+    );
+
+    let price_query = QueryMsg::Price {
+        base: "mAAPL".to_string(),
+        quote: "base0000".to_string(),
+        price_kind: None,
+    };
+    let query_price = |deps: Deps, env: Env| {
+        query(deps, env, price_query.clone()).map(|res| from_binary::<PriceResponse>(&res).unwrap())
+    };
+
+    // paused prices fail by default
+    assert_eq!(
+        query_price(deps.as_ref(), mock_env()).unwrap_err(),
+        StdError::generic_err("Price of mAAPL is paused")
+    );
+    let value: FallbackPolicyResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::FallbackPolicy {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(value.fallback_policy, FallbackPolicy::Fail);
+
+    let fallback_policy_msg = |fallback_policy: FallbackPolicy| ExecuteMsg::UpdateFallbackPolicy {
+        asset: "mAAPL".to_string(),
+        fallback_policy: Some(fallback_policy),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        fallback_policy_msg(FallbackPolicy::Fail),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        fallback_policy_msg(FallbackPolicy::FixedPrice {
+            price: Decimal256::zero(),
+        }),
+    );
+    match res {
+        Err(ContractError::InvalidFallbackPolicy {}) => (),
+        _ => panic!("Must return invalid fallback policy error"),
+    }
+
+    // the last good price is used up to its max age
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        fallback_policy_msg(FallbackPolicy::LastGoodPrice { max_age: 100u64 }),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_fallback_policy"),
+            attr("asset", "mAAPL"),
+            attr("fallback_policy", "last_good_price"),
+        ]
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let value = query_price(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.2").unwrap());
+    assert_eq!(value.last_updated_base, mock_env().block.time.seconds());
+
+    env.block.time = env.block.time.plus_seconds(1);
+    assert!(query_price(deps.as_ref(), env.clone()).is_err());
+
+    // fixed prices are set by the owner
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        fallback_policy_msg(FallbackPolicy::FixedPrice {
+            price: Decimal256::one(),
+        }),
+    )
+    .unwrap();
+    let value = query_price(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(value.rate, Decimal256::one());
+    assert_eq!(value.last_updated_base, env.block.time.seconds());
+
+    let msg = ExecuteMsg::ResumePrice {
+        asset: "mAAPL".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let value = query_price(deps.as_ref(), env).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.2").unwrap());
+}
+
+#[test]
+fn price_history() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for asset in ["mAAPL", "mGOGL"].iter() {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: asset.to_string(),
+            feeder: "feeder0000".to_string(),
+        };
+        let info = mock_info("owner0000", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let feed_price = |asset: &str, price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![(asset.to_string(), Decimal256::from_str(price).unwrap())],
+    };
+    let query_history = |deps: Deps, base: &str, quote: &str, limit: Option<u32>| {
+        from_binary::<PriceHistoryResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::PriceHistory {
+                    base: base.to_string(),
+                    quote: quote.to_string(),
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .checkpoints
+    };
+
+    let start_time = mock_env().block.time.seconds();
+    let mut env = mock_env();
+    let info = mock_info("feeder0000", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        feed_price("mAAPL", "2"),
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        feed_price("mGOGL", "3"),
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        feed_price("mAAPL", "3"),
+    )
+    .unwrap();
+    // prices of a same block share a checkpoint
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        feed_price("mAAPL", "4"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_history(deps.as_ref(), "mAAPL", "base0000", None),
+        vec![
+            PriceCheckpoint {
+                time: start_time + 20,
+                rate: Decimal256::from_str("4").unwrap(),
+            },
+            PriceCheckpoint {
+                time: start_time,
+                rate: Decimal256::from_str("2").unwrap(),
+            },
+        ]
+    );
+
+    // pairs are checkpointed at the checkpoints of either asset,
+    // as long as both are priced
+    assert_eq!(
+        query_history(deps.as_ref(), "mGOGL", "mAAPL", None),
+        vec![
+            PriceCheckpoint {
+                time: start_time + 20,
+                rate: Decimal256::from_str("0.75").unwrap(),
+            },
+            PriceCheckpoint {
+                time: start_time + 10,
+                rate: Decimal256::from_str("1.5").unwrap(),
+            },
+        ]
+    );
+    assert_eq!(
+        query_history(deps.as_ref(), "mGOGL", "mAAPL", Some(1)),
+        vec![PriceCheckpoint {
+            time: start_time + 20,
+            rate: Decimal256::from_str("0.75").unwrap(),
+        }]
+    );
+
+    // the oldest checkpoints are overwritten once the history is full
+    for _ in 0..30 {
+        env.block.time = env.block.time.plus_seconds(10);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            feed_price("mAAPL", "5"),
+        )
+        .unwrap();
+    }
+    let history = query_history(deps.as_ref(), "mAAPL", "base0000", Some(100));
+    assert_eq!(history.len(), 30);
+    assert_eq!(history[0].time, env.block.time.seconds());
+    assert_eq!(history[29].time, start_time + 30);
+}
+
+#[test]
+fn feed_prices_signed() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // ed25519 key of the seed [1u8; 32], prices signed off-chain
+    let signer = Binary::from_base64("iojj3XQJ8ZX9UtstPLpdcspnCb8dlBIb83SIAbQPb1w=").unwrap();
+    let payload = Binary::from(
+        br#"{"chain_id":"cosmos-testnet-14002","oracle_contract":"cosmos2contract","prices":[{"asset":"mAAPL","price":"1.2","timestamp":1571797419}]}"#.to_vec(),
+    );
+    let signature = Binary::from_base64(
+        "cefFx+eMs5+o/eUaDkxrA9s/rmcB3S7HlaSfKXHvO5z5rGy6JMQv6T8jbMnsORsjkesmEMqLX6h81mKYv8RyDA==",
+    )
+    .unwrap();
+    // same payload, signed by the key of the seed [2u8; 32]
+    let other_signature = Binary::from_base64(
+        "x2fwl4urcP3qcUGaF2VUgvNKe/PsRV8RI9n3a5OSr4JxNI8TLJUxJ6FfOJn80FBOp8mfpfY6VdySEFVVOxnjAg==",
+    )
+    .unwrap();
+    let next_payload = Binary::from(
+        br#"{"chain_id":"cosmos-testnet-14002","oracle_contract":"cosmos2contract","prices":[{"asset":"mAAPL","price":"1.3","timestamp":1571797500}]}"#.to_vec(),
+    );
+    let next_signature = Binary::from_base64(
+        "3jiFJZKsxTE2vJPQ/V8DVo2v40ExjwtL5nBpRJftle31kchX7qU4IQ+9KlqQ0oqgSRPCND917Th+bpWaAXA6Dw==",
+    )
+    .unwrap();
+    // same prices, signed by the same signer for another chain and another oracle
+    let other_chain_payload = Binary::from(
+        br#"{"chain_id":"columbus-5","oracle_contract":"cosmos2contract","prices":[{"asset":"mAAPL","price":"1.2","timestamp":1571797419}]}"#.to_vec(),
+    );
+    let other_chain_signature = Binary::from_base64(
+        "3IBzCnmJFfw8DcuLiqHD233SkmkRMLMiRH8F7vSnvXu/IFMr6hcyP967r4f8xaOzDB5cJoJQJYcAYAg9NRkqCg==",
+    )
+    .unwrap();
+    let other_oracle_payload = Binary::from(
+        br#"{"chain_id":"cosmos-testnet-14002","oracle_contract":"oracle0001","prices":[{"asset":"mAAPL","price":"1.2","timestamp":1571797419}]}"#.to_vec(),
+    );
+    let other_oracle_signature = Binary::from_base64(
+        "+mTPVQ1EfjcpXmnqpK7NL7ZxmZIqiDGwSxpIi4h5PkSC8XO8Jw2LOR2OKjXIgvsUTdtEKwoWuKsWTtWxftUxBg==",
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::RegisterSigner {
+        asset: "mAAPL".to_string(),
+        signer: Some(signer.clone()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RegisterSigner {
+            asset: "mAAPL".to_string(),
+            signer: Some(Binary::from(vec![1u8; 33])),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidSigner {}) => (),
+        _ => panic!("Must return invalid signer error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_signer"),
+            attr("asset", "mAAPL"),
+            attr("signer", signer.to_base64()),
+        ]
+    );
+
+    let value: SignerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Signer {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        SignerResponse {
+            asset: "mAAPL".to_string(),
+            signer: Some(signer),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer0000", &[]),
+        ExecuteMsg::FeedPricesSigned {
+            payload: payload.clone(),
+            signature: other_signature,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidSignature {}) => (),
+        _ => panic!("Must return invalid signature error"),
+    }
+
+    // prices signed for another deployment are not replayed here
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer0000", &[]),
+        ExecuteMsg::FeedPricesSigned {
+            payload: other_chain_payload,
+            signature: other_chain_signature,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidSignedDomain {}) => (),
+        _ => panic!("Must return invalid signed domain error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer0000", &[]),
+        ExecuteMsg::FeedPricesSigned {
+            payload: other_oracle_payload,
+            signature: other_oracle_signature,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidSignedDomain {}) => (),
+        _ => panic!("Must return invalid signed domain error"),
+    }
+
+    // anyone relays signed prices
+    let feed_msg = ExecuteMsg::FeedPricesSigned { payload, signature };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer0000", &[]),
+        feed_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "feed_prices_signed"),
+            attr("asset", "mAAPL"),
+            attr("price", "1.2"),
+        ]
+    );
+
+    let price_query = QueryMsg::Price {
+        base: "mAAPL".to_string(),
+        quote: "base0000".to_string(),
+        price_kind: None,
+    };
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1.2").unwrap(),
+            last_updated_base: 1571797419,
+            last_updated_quote: 9999999999,
+            twap_rate: None,
+            confidence: None,
+        }
+    );
+
+    // signed prices are not replayed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer0001", &[]),
+        feed_msg,
+    );
+    match res {
+        Err(ContractError::InvalidSignedPrice(asset)) => assert_eq!(asset, "mAAPL"),
+        _ => panic!("Must return invalid signed price error"),
+    }
+
+    // nor fed ahead of their time
+    let next_feed_msg = ExecuteMsg::FeedPricesSigned {
+        payload: next_payload,
+        signature: next_signature,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer0000", &[]),
+        next_feed_msg.clone(),
+    );
+    match res {
+        Err(ContractError::InvalidSignedPrice(asset)) => assert_eq!(asset, "mAAPL"),
+        _ => panic!("Must return invalid signed price error"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("relayer0000", &[]),
+        next_feed_msg,
+    )
+    .unwrap();
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env, price_query).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.3").unwrap());
+    assert_eq!(value.last_updated_base, 1571797500);
+}
+
+#[test]
+fn price_quote() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for asset in ["uluna", "mAAPL"].iter() {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: asset.to_string(),
+            feeder: "feeder0000".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    }
+
+    let price_quote_msg = |asset: &str, quote_asset: &str| ExecuteMsg::UpdatePriceQuote {
+        asset: asset.to_string(),
+        quote_asset: Some(quote_asset.to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        price_quote_msg("mAAPL", "uluna"),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        price_quote_msg("mAAPL", "uluna"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_price_quote"),
+            attr("asset", "mAAPL"),
+            attr("quote_asset", "uluna"),
+        ]
+    );
+
+    let value: PriceQuoteResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PriceQuote {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        PriceQuoteResponse {
+            asset: "mAAPL".to_string(),
+            quote_asset: Some("uluna".to_string()),
+        }
+    );
+
+    // prices are routed through a single quote asset
+    for msg in [
+        price_quote_msg("mGOGL", "mGOGL"),
+        price_quote_msg("mGOGL", "mAAPL"),
+        price_quote_msg("uluna", "ukrw"),
+    ]
+    .iter()
+    {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::InvalidPriceQuote {}) => (),
+            _ => panic!("Must return invalid price quote error"),
+        }
+    }
+
+    let env = mock_env();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![
+            ("uluna".to_string(), Decimal256::from_str("80").unwrap()),
+            ("mAAPL".to_string(), Decimal256::from_str("0.5").unwrap()),
+        ],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    let query_price = |base: &str, quote: &str| {
+        from_binary::<PriceResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Price {
+                    base: base.to_string(),
+                    quote: quote.to_string(),
+                    price_kind: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_price("mAAPL", "uusd"),
+        PriceResponse {
+            rate: Decimal256::from_str("40").unwrap(),
+            last_updated_base: env.block.time.seconds(),
+            last_updated_quote: 9999999999,
+            twap_rate: None,
+            confidence: None,
+        }
+    );
+    assert_eq!(
+        query_price("mAAPL", "uluna").rate,
+        Decimal256::from_str("0.5").unwrap()
+    );
+    assert_eq!(
+        query_price("uluna", "mAAPL").rate,
+        Decimal256::from_str("2").unwrap()
+    );
+}
+
+#[test]
+fn asset_metadata() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for asset in ["uluna", "bluna"].iter() {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: asset.to_string(),
+            feeder: "feeder0000".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    }
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![
+            ("bluna".to_string(), Decimal256::from_str("0.9").unwrap()),
+            ("uluna".to_string(), Decimal256::one()),
+        ],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    let bluna_metadata = AssetMetadata {
+        decimals: 6,
+        source: PriceSource::ExchangeRate,
+        hub_contract: Some("hub0000".to_string()),
+        category: Some("lst".to_string()),
+    };
+    let msg = ExecuteMsg::UpdateAssetMetadata {
+        asset: "bluna".to_string(),
+        asset_metadata: Some(bluna_metadata.clone()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // derived assets name their hub
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateAssetMetadata {
+            asset: "bluna".to_string(),
+            asset_metadata: Some(AssetMetadata {
+                hub_contract: None,
+                ..bluna_metadata.clone()
+            }),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidAssetMetadata {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_asset_metadata"),
+            attr("asset", "bluna"),
+            attr("decimals", "6"),
+            attr("source", "exchange_rate"),
+        ]
+    );
+
+    let value: AssetMetadataResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AssetMetadata {
+                asset: "bluna".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        AssetMetadataResponse {
+            asset: "bluna".to_string(),
+            asset_metadata: Some(bluna_metadata.clone()),
+        }
+    );
+
+    // prices are listed with the metadata of their asset
+    let value: PricesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Prices {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        PricesResponse {
+            prices: vec![
+                PricesResponseElem {
+                    asset: "bluna".to_string(),
+                    price: Decimal256::from_str("0.9").unwrap(),
+                    last_updated_time: env.block.time.seconds(),
+                    metadata: Some(bluna_metadata),
+                },
+                PricesResponseElem {
+                    asset: "uluna".to_string(),
+                    price: Decimal256::one(),
+                    last_updated_time: env.block.time.seconds(),
+                    metadata: None,
+                },
+            ],
+        }
+    );
+
+    let msg = ExecuteMsg::UpdateAssetMetadata {
+        asset: "bluna".to_string(),
+        asset_metadata: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let value: AssetMetadataResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::AssetMetadata {
+                asset: "bluna".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(value.asset_metadata, None);
+}
+
+#[test]
+fn twap_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let feed_price = |price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+    };
+    let price_query = |price_kind: PriceKind| QueryMsg::Price {
+        base: "mAAPL".to_string(),
+        quote: "base0000".to_string(),
+        price_kind: Some(price_kind),
+    };
+
+    let start_env = mock_env();
+    let info = mock_info("feeder0000", &[]);
+    execute(
+        deps.as_mut(),
+        start_env.clone(),
+        info.clone(),
+        feed_price("1"),
+    )
+    .unwrap();
+
+    // without elapsed time the TWAP is the spot price
+    let value: PriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            start_env.clone(),
+            price_query(PriceKind::Twap),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(value.rate, Decimal256::one());
+    assert_eq!(value.twap_rate, Some(Decimal256::one()));
+
+    let mut env = start_env.clone();
+    env.block.time = env.block.time.plus_seconds(600);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("2")).unwrap();
+
+    // the history is shorter than the default window
+    env.block.time = env.block.time.plus_seconds(600);
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query(PriceKind::Both)).unwrap())
+            .unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("2").unwrap(),
+            last_updated_base: start_env.block.time.seconds() + 600,
+            last_updated_quote: 9999999999,
+            twap_rate: Some(Decimal256::from_str("1.5").unwrap()),
+            confidence: None,
+        }
+    );
+
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query(PriceKind::Spot)).unwrap())
+            .unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("2").unwrap());
+    assert_eq!(value.twap_rate, None);
+
+    let msg = ExecuteMsg::UpdateTwapWindow {
+        twap_window: Some(900),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateTwapWindow {
+            twap_window: Some(0),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidTwapWindow {}) => (),
+        _ => panic!("Must return invalid twap window error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_twap_window"),
+            attr("twap_window", "900")
+        ]
+    );
+    let value: TwapWindowResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TwapWindow {}).unwrap()).unwrap();
+    assert_eq!(value, TwapWindowResponse { twap_window: 900 });
+
+    // 300 seconds at 1 and 600 seconds at 2
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query(PriceKind::Twap)).unwrap())
+            .unwrap();
+    assert_eq!(
+        value.rate,
+        Decimal256::from_str("1.666666666666666666").unwrap()
+    );
+
+    // observations before the window start are pruned, but the last one
+    env.block.time = env.block.time.plus_seconds(800);
+    execute(deps.as_mut(), env.clone(), info, feed_price("4")).unwrap();
+    assert_eq!(
+        read_oldest_observations(deps.as_ref().storage, "mAAPL", 10)
+            .unwrap()
+            .iter()
+            .map(|observation| observation.time)
+            .collect::<Vec<u64>>(),
+        vec![
+            start_env.block.time.seconds() + 600,
+            start_env.block.time.seconds() + 2000,
+        ]
+    );
+
+    // 900 seconds at 2 before the new price
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env, price_query(PriceKind::Twap)).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("2").unwrap());
+}
+
+#[test]
+fn feeder_quorum() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let feeder_quorum = |feeders: &[&str], quorum: u32| ExecuteMsg::UpdateFeederQuorum {
+        asset: "mAAPL".to_string(),
+        feeder_quorum: Some(FeederQuorum {
+            feeders: feeders.iter().map(|feeder| feeder.to_string()).collect(),
+            quorum,
+            max_price_age: 60u64,
+        }),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        feeder_quorum(&["feeder0001", "feeder0002", "feeder0003"], 2),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    for msg in [
+        feeder_quorum(&["feeder0001", "feeder0002"], 0),
+        feeder_quorum(&["feeder0001", "feeder0002"], 3),
+        feeder_quorum(&["feeder0001", "feeder0001"], 2),
+    ]
+    .iter()
+    {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::InvalidFeederQuorum(10)) => (),
+            _ => panic!("Must return invalid feeder quorum error"),
+        }
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        feeder_quorum(&["feeder0001", "feeder0002", "feeder0003"], 2),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_feeder_quorum"),
+            attr("asset", "mAAPL"),
+            attr("quorum", "2/3"),
+        ]
+    );
+
+    let value: FeederQuorumResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::FeederQuorum {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        FeederQuorumResponse {
+            asset: "mAAPL".to_string(),
+            feeder_quorum: Some(FeederQuorum {
+                feeders: vec![
+                    "feeder0001".to_string(),
+                    "feeder0002".to_string(),
+                    "feeder0003".to_string(),
+                ],
+                quorum: 2,
+                max_price_age: 60u64,
+            }),
+        }
+    );
+
+    let feed_price = |price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+    };
+    let query_price = |deps: Deps| {
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::Price {
+                base: "mAAPL".to_string(),
+                quote: "base0000".to_string(),
+                price_kind: None,
+            },
+        )
+        .map(|res| from_binary::<PriceResponse>(&res).unwrap())
+    };
+
+    // the registered feeder is not part of the quorum
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder0000", &[]),
+        feed_price("1"),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // no price until the quorum is reached
+    let start_env = mock_env();
+    let info = mock_info("feeder0001", &[]);
+    execute(deps.as_mut(), start_env.clone(), info, feed_price("1")).unwrap();
+    assert!(query_price(deps.as_ref()).is_err());
+
+    let mut env = start_env.clone();
+    env.block.time = env.block.time.plus_seconds(10);
+    let info = mock_info("feeder0002", &[]);
+    execute(deps.as_mut(), env.clone(), info, feed_price("1.2")).unwrap();
+    let value = query_price(deps.as_ref()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.1").unwrap());
+    assert_eq!(value.last_updated_base, start_env.block.time.seconds());
+
+    // an outlier does not move the median far
+    env.block.time = env.block.time.plus_seconds(10);
+    let info = mock_info("feeder0003", &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("5")).unwrap();
+    let value = query_price(deps.as_ref()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.2").unwrap());
+    assert_eq!(value.last_updated_base, start_env.block.time.seconds());
+
+    // stale submissions do not count toward the quorum
+    env.block.time = env.block.time.plus_seconds(80);
+    execute(deps.as_mut(), env, info, feed_price("5")).unwrap();
+    let value = query_price(deps.as_ref()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.2").unwrap());
+    assert_eq!(value.last_updated_base, start_env.block.time.seconds());
+}
+
+#[test]
+fn deviation_guard() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateDeviationGuard {
+        asset: "mAAPL".to_string(),
+        deviation_guard: Some(DeviationGuard {
+            max_deviation: Decimal256::percent(10),
+            window: 60u64,
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_deviation_guard"),
+            attr("asset", "mAAPL"),
+            attr("max_deviation", "0.1"),
+            attr("window", "60"),
+        ]
+    );
+
+    let value: DeviationGuardResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DeviationGuard {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        DeviationGuardResponse {
+            asset: "mAAPL".to_string(),
+            deviation_guard: Some(DeviationGuard {
+                max_deviation: Decimal256::percent(10),
+                window: 60u64,
+            }),
+        }
+    );
+
+    let feed_price = |price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+    };
+    let query_price = |deps: Deps| {
+        from_binary::<PriceResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::Price {
+                    base: "mAAPL".to_string(),
+                    quote: "base0000".to_string(),
+                    price_kind: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let query_pending_price = |deps: Deps| {
+        from_binary::<PendingPriceResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::PendingPrice {
+                    asset: "mAAPL".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // the first price has nothing to deviate from
+    let mut env = mock_env();
+    let info = mock_info("feeder0000", &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("1")).unwrap();
+    assert_eq!(query_price(deps.as_ref()).rate, Decimal256::one());
+
+    // moves within the max deviation are applied
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("1.1")).unwrap();
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("1.1").unwrap()
+    );
+
+    // a fat-finger move is held
+    env.block.time = env.block.time.plus_seconds(10);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), feed_price("11")).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "feed_prices"),
+            attr("held_asset", "mAAPL"),
+            attr("asset", "mAAPL"),
+            attr("price", "11"),
+        ]
+    );
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("1.1").unwrap()
+    );
+    assert_eq!(
+        query_pending_price(deps.as_ref()),
+        PendingPriceResponse {
+            asset: "mAAPL".to_string(),
+            price: Some(Decimal256::from_str("11").unwrap()),
+            last_updated_time: Some(env.block.time.seconds()),
+        }
+    );
+
+    // and dropped by a feed back within the max deviation
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("1.12")).unwrap();
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("1.12").unwrap()
+    );
+    assert_eq!(query_pending_price(deps.as_ref()).price, None);
+
+    // a second feed confirms the held move
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("2")).unwrap();
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("1.12").unwrap()
+    );
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("2.01")).unwrap();
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("2.01").unwrap()
+    );
+
+    // past the window, the previous price is no reference
+    env.block.time = env.block.time.plus_seconds(61);
+    execute(deps.as_mut(), env.clone(), info.clone(), feed_price("4")).unwrap();
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("4").unwrap()
+    );
+
+    // the owner can apply a held price
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::ConfirmPendingPrice {
+            asset: "mAAPL".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::NoPendingPrice {}) => (),
+        _ => panic!("Must return no pending price error"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(deps.as_mut(), env.clone(), info, feed_price("8")).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        ExecuteMsg::ConfirmPendingPrice {
+            asset: "mAAPL".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("owner0000", &[]),
+        ExecuteMsg::ConfirmPendingPrice {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "confirm_pending_price"),
+            attr("asset", "mAAPL"),
+            attr("price", "8"),
+        ]
+    );
+    assert_eq!(
+        query_price(deps.as_ref()).rate,
+        Decimal256::from_str("8").unwrap()
+    );
+    assert_eq!(query_pending_price(deps.as_ref()).price, None);
+}
+
+#[test]
+fn pyth_feed() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let price_id = "e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43";
+    let pyth_feed = PythFeed {
+        contract_addr: "pyth0000".to_string(),
+        price_id: price_id.to_string(),
+        max_confidence: Decimal256::percent(1),
+        max_price_age: 60u64,
+    };
+    let msg = ExecuteMsg::UpdatePythFeed {
+        asset: "mBTC".to_string(),
+        pyth_feed: Some(pyth_feed.clone()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdatePythFeed {
+            asset: "mBTC".to_string(),
+            pyth_feed: Some(PythFeed {
+                price_id: "0xe62d".to_string(),
+                ..pyth_feed.clone()
+            }),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidPythFeed {}) => (),
+        _ => panic!("Must return invalid pyth feed error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pyth_feed"),
+            attr("asset", "mBTC"),
+            attr("price_id", price_id),
+        ]
+    );
+
+    let value: PythFeedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PythFeed {
+                asset: "mBTC".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        PythFeedResponse {
+            asset: "mBTC".to_string(),
+            pyth_feed: Some(pyth_feed),
+        }
+    );
+
+    // pyth priced assets are not fed
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mBTC".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mBTC".to_string(), Decimal256::one())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg);
+    match res {
+        Err(ContractError::PythPricedAsset {}) => (),
+        _ => panic!("Must return pyth priced asset error"),
+    }
+
+    let env = mock_env();
+    let publish_time = env.block.time.seconds() - 10;
+    let pyth_price = |price: &str, conf: &str, expo: i32, publish_time: u64| PythPrice {
+        price: price.to_string(),
+        conf: conf.to_string(),
+        expo,
+        publish_time: publish_time as i64,
+    };
+    let price_query = QueryMsg::Price {
+        base: "mBTC".to_string(),
+        quote: "base0000".to_string(),
+        price_kind: Some(PriceKind::Both),
+    };
+
+    deps.querier.with_pyth_price_feed(
+        "pyth0000",
+        PriceFeed {
+            id: price_id.to_string(),
+            price: pyth_price("2512345678", "1000000", -8, publish_time),
+            ema_price: pyth_price("2500000000", "2000000", -8, publish_time),
+        },
+    );
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("25.12345678").unwrap(),
+            last_updated_base: publish_time,
+            last_updated_quote: 9999999999,
+            twap_rate: Some(Decimal256::from_str("25").unwrap()),
+            confidence: Some(Decimal256::from_str("0.01").unwrap()),
+        }
+    );
+
+    // positive exponents scale the price up
+    deps.querier.with_pyth_price_feed(
+        "pyth0000",
+        PriceFeed {
+            id: price_id.to_string(),
+            price: pyth_price("3", "0", 2, publish_time),
+            ema_price: pyth_price("3", "0", 2, publish_time),
+        },
+    );
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_uint256(300u64));
+    // prices known exactly have no confidence band
+    assert_eq!(value.confidence, None);
+
+    deps.querier.with_pyth_price_feed(
+        "pyth0000",
+        PriceFeed {
+            id: price_id.to_string(),
+            price: pyth_price("2512345678", "50000000", -8, publish_time),
+            ema_price: pyth_price("2500000000", "2000000", -8, publish_time),
+        },
+    );
+    let res = query(deps.as_ref(), env.clone(), price_query.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Pyth price confidence interval is too wide")
+    );
+
+    deps.querier.with_pyth_price_feed(
+        "pyth0000",
+        PriceFeed {
+            id: price_id.to_string(),
+            price: pyth_price("2512345678", "1000000", -8, publish_time - 60),
+            ema_price: pyth_price("2500000000", "2000000", -8, publish_time),
+        },
+    );
+    let res = query(deps.as_ref(), env, price_query);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Pyth price is too old")
+    );
+}
+
+#[test]
+fn band_feed() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let band_feed = BandFeed {
+        contract_addr: "band0000".to_string(),
+        base_symbol: "ATOM".to_string(),
+        quote_symbol: "USD".to_string(),
+        max_price_age: 60u64,
+    };
+    let msg = ExecuteMsg::UpdateBandFeed {
+        asset: "uatom".to_string(),
+        band_feed: Some(band_feed.clone()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_band_feed"),
+            attr("asset", "uatom"),
+            attr("pair", "ATOM/USD"),
+        ]
+    );
+
+    let value: BandFeedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BandFeed {
+                asset: "uatom".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        BandFeedResponse {
+            asset: "uatom".to_string(),
+            band_feed: Some(band_feed),
+        }
+    );
+
+    // band priced assets are not fed
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "uatom".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("uatom".to_string(), Decimal256::one())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg);
+    match res {
+        Err(ContractError::BandPricedAsset {}) => (),
+        _ => panic!("Must return band priced asset error"),
+    }
+
+    let env = mock_env();
+    let price_query = QueryMsg::Price {
+        base: "uatom".to_string(),
+        quote: "uusd".to_string(),
+        price_kind: None,
+    };
+
+    // the pair is as old as its oldest symbol
+    deps.querier.with_band_reference_data(
+        "band0000",
+        ("ATOM", "USD"),
+        ReferenceData {
+            rate: Uint256::from(12_340_000_000_000_000_000u128),
+            last_updated_base: env.block.time.seconds() - 10,
+            last_updated_quote: env.block.time.seconds() - 20,
+        },
+    );
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("12.34").unwrap(),
+            last_updated_base: env.block.time.seconds() - 20,
+            last_updated_quote: 9999999999,
+            twap_rate: None,
+            confidence: None,
+        }
+    );
+
+    deps.querier.with_band_reference_data(
+        "band0000",
+        ("ATOM", "USD"),
+        ReferenceData {
+            rate: Uint256::from(12_340_000_000_000_000_000u128),
+            last_updated_base: env.block.time.seconds() - 10,
+            last_updated_quote: env.block.time.seconds() - 61,
+        },
+    );
+    let res = query(deps.as_ref(), env, price_query);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Band price is too old")
+    );
+}
+
+#[test]
+fn exchange_rate_feed() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let exchange_rate_feed = ExchangeRateFeed {
+        hub_contract: "hub0000".to_string(),
+        underlying_asset: "uluna".to_string(),
+        max_rate_age: 3600u64,
+    };
+    let msg = ExecuteMsg::UpdateExchangeRateFeed {
+        asset: "bluna".to_string(),
+        exchange_rate_feed: Some(exchange_rate_feed.clone()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_exchange_rate_feed"),
+            attr("asset", "bluna"),
+            attr("underlying_asset", "uluna"),
+        ]
+    );
+
+    let value: ExchangeRateFeedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ExchangeRateFeed {
+                asset: "bluna".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        ExchangeRateFeedResponse {
+            asset: "bluna".to_string(),
+            exchange_rate_feed: Some(exchange_rate_feed),
+        }
+    );
+
+    // derived prices are not derived from each other
+    for (asset, underlying_asset) in [("stluna", "bluna"), ("uluna", "uluna")].iter() {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            ExecuteMsg::UpdateExchangeRateFeed {
+                asset: asset.to_string(),
+                exchange_rate_feed: Some(ExchangeRateFeed {
+                    hub_contract: "hub0001".to_string(),
+                    underlying_asset: underlying_asset.to_string(),
+                    max_rate_age: 3600u64,
+                }),
+            },
+        );
+        match res {
+            Err(ContractError::InvalidExchangeRateFeed {}) => (),
+            _ => panic!("Must return invalid exchange rate feed error"),
+        }
+    }
+
+    // derived assets are not fed
+    for asset in ["bluna", "uluna"].iter() {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: asset.to_string(),
+            feeder: "feeder0000".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    }
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("bluna".to_string(), Decimal256::one())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg);
+    match res {
+        Err(ContractError::DerivedPricedAsset {}) => (),
+        _ => panic!("Must return derived priced asset error"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(10);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("uluna".to_string(), Decimal256::from_str("80").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    let price_query = QueryMsg::Price {
+        base: "bluna".to_string(),
+        quote: "uusd".to_string(),
+        price_kind: None,
+    };
+
+    // the price tracks the exchange rate, and is as old as the
+    // older of the underlying price and the hub index
+    deps.querier.with_hub_state(
+        "hub0000",
+        HubStateResponse {
+            exchange_rate: Decimal256::from_str("0.95").unwrap(),
+            last_index_modification: env.block.time.seconds() - 100,
+        },
+    );
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("76").unwrap(),
+            last_updated_base: env.block.time.seconds() - 100,
+            last_updated_quote: 9999999999,
+            twap_rate: None,
+            confidence: None,
+        }
+    );
+
+    deps.querier.with_hub_state(
+        "hub0000",
+        HubStateResponse {
+            exchange_rate: Decimal256::from_str("0.9").unwrap(),
+            last_index_modification: env.block.time.seconds(),
+        },
+    );
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("72").unwrap());
+    assert_eq!(value.last_updated_base, env.block.time.seconds());
+
+    deps.querier.with_hub_state(
+        "hub0000",
+        HubStateResponse {
+            exchange_rate: Decimal256::from_str("0.9").unwrap(),
+            last_index_modification: env.block.time.seconds() - 3601,
+        },
+    );
+    let res = query(deps.as_ref(), env, price_query);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Hub exchange rate is too old")
+    );
+}
+
+#[test]
+fn amm_feed() {
+    let mut deps = mock_wasm_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "uluna".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("uluna".to_string(), Decimal256::one())],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg).unwrap();
+
+    // uluna is the second asset of the pair
+    let cumulative_prices = |price1_cumulative_last: u128| CumulativePricesResponse {
+        price0_cumulative_last: Uint128::from(7u128),
+        price1_cumulative_last: Uint128::from(price1_cumulative_last),
+    };
+    deps.querier
+        .with_cumulative_prices("pair0000", cumulative_prices(u128::MAX - 100000000));
+
+    let amm_feed = AmmFeed {
+        pair_contract: "pair0000".to_string(),
+        asset_index: 1,
+        window: 600u64,
+        max_deviation: Decimal256::percent(10),
+    };
+    let msg = ExecuteMsg::UpdateAmmFeed {
+        asset: "uluna".to_string(),
+        amm_feed: Some(amm_feed.clone()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateAmmFeed {
+            asset: "uluna".to_string(),
+            amm_feed: Some(AmmFeed {
+                asset_index: 2,
+                ..amm_feed.clone()
+            }),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidAmmFeed {}) => (),
+        _ => panic!("Must return invalid amm feed error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_amm_feed"),
+            attr("asset", "uluna"),
+            attr("pair_contract", "pair0000"),
+        ]
+    );
+
+    let value: AmmFeedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AmmFeed {
+                asset: "uluna".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        AmmFeedResponse {
+            asset: "uluna".to_string(),
+            amm_feed: Some(amm_feed),
+        }
+    );
+
+    // no snapshot is as old as the window yet
+    let twap_query = QueryMsg::AmmTwap {
+        asset: "uluna".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), twap_query.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "No AMM price snapshot as old as the window")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        ExecuteMsg::SnapshotAmmPrice {
+            asset: "uluna".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "snapshot_amm_price"),
+            attr("asset", "uluna"),
+            attr("recorded", "false"),
+        ]
+    );
+
+    // the cumulative price wraps around, at a price of 1.05
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(600);
+    deps.querier
+        .with_cumulative_prices("pair0000", cumulative_prices(630000000 - 100000001));
+    let value: AmmTwapResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), twap_query.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        AmmTwapResponse {
+            asset: "uluna".to_string(),
+            rate: Decimal256::from_str("1.05").unwrap(),
+            window: 600u64,
+        }
+    );
+
+    let price_query = QueryMsg::Price {
+        base: "uluna".to_string(),
+        quote: "uusd".to_string(),
+        price_kind: None,
+    };
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::one());
+
+    // the fed price deviates from the AMM TWAP of 1.5
+    deps.querier
+        .with_cumulative_prices("pair0000", cumulative_prices(900000000 - 100000001));
+    let res = query(deps.as_ref(), env.clone(), price_query.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price of uluna deviates from its AMM TWAP")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a stale price falling back to the last good price is checked against the AMM too
+    let msg = ExecuteMsg::UpdateMaxPriceAge {
+        asset: "uluna".to_string(),
+        max_price_age: Some(60u64),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::UpdateFallbackPolicy {
+        asset: "uluna".to_string(),
+        fallback_policy: Some(FallbackPolicy::LastGoodPrice { max_age: 1000u64 }),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let res = query(deps.as_ref(), env.clone(), price_query.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price of uluna deviates from its AMM TWAP")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the last good price is served once it is in line with the AMM
+    deps.querier
+        .with_cumulative_prices("pair0000", cumulative_prices(630000000 - 100000001));
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::one());
+    deps.querier
+        .with_cumulative_prices("pair0000", cumulative_prices(900000000 - 100000001));
+
+    // the previous snapshot keeps pricing the TWAP over the window
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::SnapshotAmmPrice {
+            asset: "uluna".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("recorded", "true"));
+    let value: AmmTwapResponse =
+        from_binary(&query(deps.as_ref(), env, twap_query).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.5").unwrap());
+    assert_eq!(value.window, 600u64);
+}
+
+#[test]
+fn price_subscription() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let feed_price = |price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder0000", &[]),
+        feed_price("1"),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidation0000", &[]),
+        ExecuteMsg::Subscribe {
+            asset: "mAAPL".to_string(),
+            thresholds: vec![
+                Decimal256::from_str("1.1").unwrap(),
+                Decimal256::from_str("0.9").unwrap(),
+            ],
+        },
+    );
+    match res {
+        Err(ContractError::InvalidPriceThresholds(10)) => (),
+        _ => panic!("Must return invalid price thresholds error"),
+    }
+
+    let thresholds = vec![
+        Decimal256::from_str("0.9").unwrap(),
+        Decimal256::from_str("1.1").unwrap(),
+    ];
+    let msg = ExecuteMsg::Subscribe {
+        asset: "mAAPL".to_string(),
+        thresholds: thresholds.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidation0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "subscribe"),
+            attr("asset", "mAAPL"),
+            attr("subscriber", "liquidation0000"),
+        ]
+    );
+
+    // the band starts at the current price
+    let value: SubscriptionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Subscriptions {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        SubscriptionsResponse {
+            asset: "mAAPL".to_string(),
+            subscriptions: vec![SubscriptionResponse {
+                subscriber: "liquidation0000".to_string(),
+                thresholds,
+                band: 1,
+            }],
+        }
+    );
+
+    // moves within the band are not notified
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder0000", &[]),
+        feed_price("1.05"),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder0000", &[]),
+        feed_price("0.8"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1u64,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidation0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&PriceSubscriberMsg::PriceBandCrossed {
+                    asset: "mAAPL".to_string(),
+                    price: Decimal256::from_str("0.8").unwrap(),
+                    prev_band: 1,
+                    band: 0,
+                })
+                .unwrap(),
+            }),
+            gas_limit: Some(300000u64),
+            reply_on: ReplyOn::Error,
+        }]
+    );
+
+    // failing callbacks do not revert the feed
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1u64,
+            result: ContractResult::Err("out of gas".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "price_callback_failed")]
+    );
+
+    // subscribers of an asset are capped
+    for i in 1..10 {
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&format!("addr{:04}", i), &[]),
+            msg.clone(),
+        )
+        .unwrap();
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0010", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::TooManySubscribers(10)) => (),
+        _ => panic!("Must return too many subscribers error"),
+    }
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidation0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // only the owner unsubscribes others
+    let msg = ExecuteMsg::Unsubscribe {
+        asset: "mAAPL".to_string(),
+        subscriber: Some("liquidation0000".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unsubscribe"),
+            attr("asset", "mAAPL"),
+            attr("subscriber", "liquidation0000"),
+        ]
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Unsubscribe {
+            asset: "mAAPL".to_string(),
+            subscriber: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("subscriber", "addr0001"));
+
+    let value: SubscriptionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Subscriptions {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(value.subscriptions.len(), 8);
+}
+
+#[test]
+fn emergency_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::one())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+    let msg = ExecuteMsg::UpdateMaxPriceAge {
+        asset: "mAAPL".to_string(),
+        max_price_age: Some(60u64),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let expires_at = env.block.time.seconds() + 3600;
+    let msg = ExecuteMsg::SetEmergencyPrice {
+        asset: "mAAPL".to_string(),
+        price: Decimal256::percent(150),
+        expires_at,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the expiry is mandatory and bounded
+    for expires_at in [
+        env.block.time.seconds(),
+        env.block.time.seconds() + MAX_EMERGENCY_PRICE_DURATION + 1,
+    ]
+    .iter()
+    {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner0000", &[]),
+            ExecuteMsg::SetEmergencyPrice {
+                asset: "mAAPL".to_string(),
+                price: Decimal256::percent(150),
+                expires_at: *expires_at,
+            },
+        );
+        match res {
+            Err(ContractError::InvalidEmergencyPrice(MAX_EMERGENCY_PRICE_DURATION)) => (),
+            _ => panic!("Must return invalid emergency price error"),
+        }
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_emergency_price"),
+            attr("asset", "mAAPL"),
+            attr("price", "1.5"),
+            attr("expires_at", expires_at.to_string()),
+            attr("prev_price", ""),
+        ]
+    );
+
+    let value: EmergencyPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::EmergencyPrice {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        EmergencyPriceResponse {
+            asset: "mAAPL".to_string(),
+            price: Some(Decimal256::percent(150)),
+            expires_at: Some(expires_at),
+        }
+    );
+
+    // the emergency price is fresh although feeds stopped
+    let price_query = QueryMsg::Price {
+        base: "mAAPL".to_string(),
+        quote: "uusd".to_string(),
+        price_kind: Some(PriceKind::Both),
+    };
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(1800);
+    let value: PriceResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), price_query.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::percent(150),
+            last_updated_base: env.block.time.seconds(),
+            last_updated_quote: 9999999999,
+            twap_rate: Some(Decimal256::percent(150)),
+            confidence: None,
+        }
+    );
+
+    // fed prices are stored but overridden
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::percent(120))],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "feed_prices"),
+            attr("overridden_asset", "mAAPL"),
+            attr("asset", "mAAPL"),
+            attr("price", "1.2"),
+        ]
+    );
+
+    // the fed price applies again once the emergency price expires
+    let fed_time = env.block.time.seconds();
+    env.block.time = env.block.time.plus_seconds(1800);
+    let res = query(deps.as_ref(), env.clone(), price_query);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            format!("Price of mAAPL is too old; last updated: {}", fed_time)
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let value: EmergencyPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::EmergencyPrice {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(value.price, None);
+
+    let msg = ExecuteMsg::ClearEmergencyPrice {
+        asset: "mAAPL".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "clear_emergency_price"),
+            attr("asset", "mAAPL"),
+            attr("price", "1.5"),
+        ]
+    );
+    let res = execute(deps.as_mut(), env, mock_info("owner0000", &[]), msg);
+    match res {
+        Err(ContractError::NoEmergencyPrice {}) => (),
+        _ => panic!("Must return no emergency price error"),
+    }
+}
@@ -0,0 +1,100 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_observation_at, read_oldest_observations, read_twap_window,
+    remove_observation, store_observation, store_twap_window, Config, Observation,
+};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Storage};
+use moneymarket::oracle::TwapWindowResponse;
+
+pub fn update_twap_window(
+    deps: DepsMut,
+    info: MessageInfo,
+    twap_window: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if twap_window == Some(0) {
+        return Err(ContractError::InvalidTwapWindow {});
+    }
+
+    store_twap_window(deps.storage, twap_window)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_twap_window"),
+        attr("twap_window", read_twap_window(deps.storage)?.to_string()),
+    ]))
+}
+
+/// Accumulate the previous price up to `time` and record the new price.
+/// Observations older than the TWAP window are pruned, keeping the last
+/// one before the window start to price it
+pub(crate) fn record_observation(
+    storage: &mut dyn Storage,
+    asset: &str,
+    price: Decimal256,
+    time: u64,
+) -> StdResult<()> {
+    let cumulative_price = match read_observation_at(storage, asset, time)? {
+        Some(last) => last.cumulative_price + last.price * elapsed(last.time, time),
+        None => Decimal256::zero(),
+    };
+    store_observation(
+        storage,
+        asset,
+        &Observation {
+            time,
+            price,
+            cumulative_price,
+        },
+    )?;
+
+    let window_start = time.saturating_sub(read_twap_window(storage)?);
+    loop {
+        let oldest = read_oldest_observations(storage, asset, 2)?;
+        if oldest.len() < 2 || oldest[1].time > window_start {
+            break;
+        }
+
+        remove_observation(storage, asset, oldest[0].time);
+    }
+
+    Ok(())
+}
+
+/// Time-weighted average price over the TWAP window ending at `time`,
+/// over the recorded history when it is shorter than the window
+pub(crate) fn compute_twap(storage: &dyn Storage, asset: &str, time: u64) -> StdResult<Decimal256> {
+    let last = read_observation_at(storage, asset, time)?
+        .ok_or_else(|| StdError::generic_err("No price history for the specified asset exist"))?;
+    let oldest_time = read_oldest_observations(storage, asset, 1)?[0].time;
+
+    let window_start = std::cmp::max(time.saturating_sub(read_twap_window(storage)?), oldest_time);
+    if window_start >= time {
+        return Ok(last.price);
+    }
+
+    let cumulative_price_at = |observation: &Observation, at: u64| {
+        observation.cumulative_price + observation.price * elapsed(observation.time, at)
+    };
+    let start = read_observation_at(storage, asset, window_start)?
+        .ok_or_else(|| StdError::generic_err("No price history for the specified asset exist"))?;
+
+    Ok(
+        (cumulative_price_at(&last, time) - cumulative_price_at(&start, window_start))
+            / elapsed(window_start, time),
+    )
+}
+
+fn elapsed(from: u64, to: u64) -> Decimal256 {
+    Decimal256::from_uint256(Uint256::from(to - from))
+}
+
+pub fn query_twap_window(deps: Deps) -> StdResult<TwapWindowResponse> {
+    Ok(TwapWindowResponse {
+        twap_window: read_twap_window(deps.storage)?,
+    })
+}
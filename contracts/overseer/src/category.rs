@@ -0,0 +1,199 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+
+use crate::collateral::compute_borrow_limit;
+use crate::error::ContractError;
+use crate::querier::query_borrower_info;
+use crate::state::{
+    bump_borrow_limit_cache_version, read_borrower_category, read_category,
+    read_category_share_limit, read_collaterals, read_config, read_whitelist_elem,
+    remove_borrow_limit_cache, store_borrower_category, store_category, store_category_share_limit,
+    Category, Config,
+};
+
+use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::overseer::{
+    BorrowerCategoryResponse, CategoryResponse, CategoryShareLimitResponse,
+};
+use moneymarket::tokens::Tokens;
+
+pub fn register_category(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    max_ltv: Decimal256,
+    collateral_tokens: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_tokens = collateral_tokens
+        .iter()
+        .map(|collateral_token| {
+            let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+
+            // every category member must be whitelisted
+            read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+            Ok(collateral_token_raw)
+        })
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+
+    bump_borrow_limit_cache_version(deps.storage)?;
+    store_category(
+        deps.storage,
+        &name,
+        &Category {
+            max_ltv,
+            collateral_tokens,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_category"),
+        attr("name", name),
+        attr("LTV", max_ltv.to_string()),
+    ]))
+}
+
+pub fn update_category_share_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    max_collateral_share: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    read_category(deps.storage, &name)?;
+    if let Some(max_collateral_share) = max_collateral_share {
+        if max_collateral_share.is_zero() || max_collateral_share > Decimal256::one() {
+            return Err(ContractError::InvalidCollateralShare {});
+        }
+    }
+
+    bump_borrow_limit_cache_version(deps.storage)?;
+    store_category_share_limit(deps.storage, &name, max_collateral_share)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_category_share_limit"),
+        attr("name", name),
+        attr(
+            "max_collateral_share",
+            max_collateral_share
+                .map(|share| share.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Opt the whole position of the sender into the given category,
+/// or back into the default mode when `category` is `None`.
+pub fn set_borrower_category(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    category: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    if let Some(category) = category.clone() {
+        let category: Category = read_category(deps.storage, &category)?;
+        if collaterals
+            .iter()
+            .any(|c| !category.collateral_tokens.contains(&c.0))
+        {
+            return Err(ContractError::CollateralNotInCategory {});
+        }
+    }
+
+    store_borrower_category(deps.storage, &borrower_raw, category.clone())?;
+    remove_borrow_limit_cache(deps.storage, &borrower_raw);
+
+    // Leaving a category lowers the LTV, so the loan
+    // must still be covered by the new borrow limit
+    let (borrow_limit, _) = compute_borrow_limit(
+        deps.as_ref(),
+        &env,
+        &borrower_raw,
+        &collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    let borrow_amount_res: BorrowerInfoResponse =
+        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
+    if borrow_limit < borrow_amount_res.loan_amount {
+        return Err(ContractError::CategoryChangeTooLarge(borrow_limit.into()));
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_borrower_category"),
+        attr("borrower", borrower),
+        attr("category", category.unwrap_or_default()),
+    ]))
+}
+
+/// Returns an error if the borrower is in a category
+/// which does not include all the given collaterals
+#[allow(clippy::ptr_arg)]
+pub fn assert_category_collaterals(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> Result<(), ContractError> {
+    if let Some(category) = read_borrower_category(deps.storage, borrower)? {
+        let category: Category = read_category(deps.storage, &category)?;
+        if collaterals
+            .iter()
+            .any(|c| !category.collateral_tokens.contains(&c.0))
+        {
+            return Err(ContractError::CollateralNotInCategory {});
+        }
+    }
+
+    Ok(())
+}
+
+pub fn query_category(deps: Deps, name: String) -> StdResult<CategoryResponse> {
+    let category: Category = read_category(deps.storage, &name)?;
+    Ok(CategoryResponse {
+        name,
+        max_ltv: category.max_ltv,
+        collateral_tokens: category
+            .collateral_tokens
+            .iter()
+            .map(|c| Ok(deps.api.addr_humanize(c)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+    })
+}
+
+pub fn query_category_share_limit(
+    deps: Deps,
+    name: String,
+) -> StdResult<CategoryShareLimitResponse> {
+    let max_collateral_share = read_category_share_limit(deps.storage, &name)?;
+    Ok(CategoryShareLimitResponse {
+        name,
+        max_collateral_share,
+    })
+}
+
+pub fn query_borrower_category(deps: Deps, borrower: Addr) -> StdResult<BorrowerCategoryResponse> {
+    let category = read_borrower_category(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+
+    Ok(BorrowerCategoryResponse {
+        borrower: borrower.to_string(),
+        category,
+    })
+}
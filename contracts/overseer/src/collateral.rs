@@ -1,48 +1,89 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
-    WasmMsg,
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, SubMsg, WasmMsg,
 };
 
+use crate::category::assert_category_collaterals;
+use crate::credit_line::{apply_ltv_multiplier, credit_line_attributes};
+use crate::debt_ceiling::allocate_borrower_debt;
 use crate::error::ContractError;
-use crate::querier::{query_borrower_info, query_liquidation_amount};
+use crate::exclusive_liquidation::assert_liquidator;
+use crate::freeze::assert_not_frozen;
+use crate::gradual_liquidation::slice_liquidation;
+use crate::liquidation_throttle::throttle_liquidation;
+use crate::migration::{read_custody_contract, skip_custody_migration};
+use crate::oracle_quorum::query_quorum_price;
+use crate::querier::{
+    query_auction_config, query_borrower_auctions, query_borrower_info, query_liquidation_amount,
+    query_simulate_bid_execution, query_swap_liquidation_amount,
+};
+use crate::risk_notification::risk_notification_messages;
+use crate::seizure::order_seizure;
 use crate::state::{
-    read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
-    Config, WhitelistElem,
+    read_all_collaterals, read_borrow_limit_cache, read_borrow_limit_cache_version,
+    read_borrower_category, read_category, read_category_share_limit, read_collateral_oracle,
+    read_collaterals, read_confidence_threshold, read_config, read_credit_line,
+    read_last_lock_height, read_max_price_age, read_min_lock_duration, read_oracle_quorum,
+    read_price_kind, read_unlock_buffer, read_whitelist_elem, store_borrow_limit_cache,
+    store_collaterals, store_last_lock_height, store_liquidatable_since, BorrowLimitCache,
+    Category, Config, PriceStamp, WhitelistElem,
 };
+use crate::watchlist::{collateral_value, position_ltv, risk_alert_messages};
+use crate::whitelist_status::{assert_active_collaterals, read_ltv_factor};
 
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::liquidation::LiquidationAmountResponse;
 use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::math::{mul_dec, Rounding};
 use moneymarket::oracle::PriceResponse;
-use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
+use moneymarket::overseer::{
+    AllCollateralsResponse, BorrowLimitResponse, BorrowLimitsResponse, CollateralDetail,
+    CollateralsDetailedResponse, CollateralsResponse, CreditLine, RiskyPositionResponse,
+    RiskyPositionsResponse, SimulateLiquidationResponse, SimulatedCollateralLiquidation,
+};
+use moneymarket::querier::{query_balance, query_price, query_price_of_kind};
 use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
 
+// maximum number of borrowers accepted by a single BorrowLimits query
+const MAX_BORROW_LIMITS_QUERY: usize = 30;
+
 pub fn lock_collateral(
     deps: DepsMut,
-    info: MessageInfo,
+    env: Env,
+    borrower: Addr,
     collaterals_human: TokensHuman,
 ) -> Result<Response, ContractError> {
-    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    assert_not_frozen(deps.storage)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
 
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
+    assert_lockable(deps.as_ref(), &env, &borrower_raw, &collaterals)?;
+
+    for collateral in collaterals.iter() {
+        store_last_lock_height(deps.storage, &collateral.0, &borrower_raw, env.block.height)?;
+
+        let locked_amount: Uint256 = cur_collaterals
+            .iter()
+            .find(|c| c.0 == collateral.0)
+            .map(|c| c.1)
+            .unwrap_or_default();
+        skip_custody_migration(deps.storage, &collateral.0, &borrower_raw, locked_amount)?;
+    }
 
     cur_collaterals.add(collaterals.clone());
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
     for collateral in collaterals {
-        let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let custody_contract = read_custody_contract(deps.storage, &collateral.0, &borrower_raw)?;
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps
-                .api
-                .addr_humanize(&whitelist_elem.custody_contract)?
-                .to_string(),
+            contract_addr: deps.api.addr_humanize(&custody_contract)?.to_string(),
             funds: vec![],
             msg: to_binary(&CustodyExecuteMsg::LockCollateral {
-                borrower: info.sender.to_string(),
+                borrower: borrower.to_string(),
                 amount: collateral.1,
             })?,
         }));
@@ -56,21 +97,87 @@ pub fn lock_collateral(
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "lock_collateral"),
-        attr("borrower", info.sender),
+        attr("borrower", borrower),
         attr("collaterals", collateral_logs.join(",")),
     ]))
 }
 
+/// Checks the collaterals can be locked by the borrower
+#[allow(clippy::ptr_arg)]
+pub(crate) fn assert_lockable(
+    deps: Deps,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> Result<(), ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    assert_category_collaterals(deps, borrower, collaterals)?;
+    assert_active_collaterals(deps, collaterals)?;
+
+    // collaterals with their own max price age cannot be locked on a stale feed
+    for collateral in collaterals.iter() {
+        if read_max_price_age(deps.storage, &collateral.0)?.is_some() {
+            query_collateral_price(deps, &config, &collateral.0, Some(env.block.time.seconds()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the min lock duration of the collaterals
+/// passed since the last lock of the borrower
+#[allow(clippy::ptr_arg)]
+pub(crate) fn assert_lock_duration_passed(
+    deps: Deps,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> Result<(), ContractError> {
+    for collateral in collaterals.iter() {
+        if let (Some(min_lock_duration), Some(last_lock_height)) = (
+            read_min_lock_duration(deps.storage, &collateral.0)?,
+            read_last_lock_height(deps.storage, &collateral.0, borrower)?,
+        ) {
+            if env.block.height < last_lock_height + min_lock_duration {
+                return Err(ContractError::MinLockDurationNotPassed(
+                    last_lock_height + min_lock_duration,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Borrow limit left to the loan by an unlock of the collaterals,
+/// reduced by the largest unlock buffer among them
+#[allow(clippy::ptr_arg)]
+pub(crate) fn unlock_borrow_limit(
+    deps: Deps,
+    collaterals: &Tokens,
+    borrow_limit: Uint256,
+) -> StdResult<Uint256> {
+    let mut unlock_buffer = Decimal256::zero();
+    for collateral in collaterals.iter() {
+        if let Some(buffer) = read_unlock_buffer(deps.storage, &collateral.0)? {
+            unlock_buffer = std::cmp::max(unlock_buffer, buffer);
+        }
+    }
+
+    Ok(borrow_limit * (Decimal256::one() - unlock_buffer))
+}
+
 pub fn unlock_collateral(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    borrower: Addr,
     collaterals_human: TokensHuman,
 ) -> Result<Response, ContractError> {
+    assert_not_frozen(deps.storage)?;
+
     let config: Config = read_config(deps.storage)?;
     let market = deps.api.addr_humanize(&config.market_contract)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
@@ -80,28 +187,44 @@ pub fn unlock_collateral(
         return Err(ContractError::UnlockExceedsLocked {});
     }
 
+    assert_lock_duration_passed(deps.as_ref(), &env, &borrower_raw, &collaterals)?;
+
     // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, _) = compute_borrow_limit(
-        deps.as_ref(),
-        &cur_collaterals,
-        Some(env.block.time.seconds()),
-    )?;
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit_cached(deps.branch(), &env, &borrower_raw, &cur_collaterals)?;
     let borrow_amount_res: BorrowerInfoResponse =
         query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    if borrow_limit < borrow_amount_res.loan_amount {
-        return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
+    let unlock_limit = unlock_borrow_limit(deps.as_ref(), &collaterals, borrow_limit)?;
+    if unlock_limit < borrow_amount_res.loan_amount {
+        return Err(ContractError::UnlockTooLarge(unlock_limit.into()));
     }
 
+    let notification_messages: Vec<SubMsg> = risk_notification_messages(
+        deps.branch(),
+        &borrower_raw,
+        borrow_amount_res.loan_amount,
+        collateral_value(&cur_collaterals, &collateral_prices),
+    )?;
+
+    // The loan moves onto the remaining collaterals
+    allocate_borrower_debt(
+        deps.branch(),
+        &env,
+        &borrower_raw,
+        &cur_collaterals,
+        &collateral_prices,
+        borrow_limit,
+        borrow_amount_res.loan_amount,
+        true,
+    )?;
+
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
     let mut messages: Vec<SubMsg> = vec![];
     for collateral in collaterals.clone() {
-        let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let custody_contract = read_custody_contract(deps.storage, &collateral.0, &borrower_raw)?;
         messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps
-                .api
-                .addr_humanize(&whitelist_elem.custody_contract)?
-                .to_string(),
+            contract_addr: deps.api.addr_humanize(&custody_contract)?.to_string(),
             funds: vec![],
             msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
                 borrower: borrower.to_string(),
@@ -118,31 +241,48 @@ pub fn unlock_collateral(
 
     Ok(Response::new()
         .add_submessages(messages)
+        .add_submessages(notification_messages)
         .add_attributes(vec![
             attr("action", "unlock_collateral"),
             attr("borrower", borrower),
             attr("collaterals", collateral_logs.join(",")),
-        ]))
+        ])
+        .add_attributes(credit_line_attributes(deps.storage, &borrower_raw)?))
 }
 
 pub fn liquidate_collateral(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     borrower: Addr,
+    max_spread: Option<Decimal256>,
 ) -> Result<Response, ContractError> {
+    assert_not_frozen(deps.storage)?;
+
     let config: Config = read_config(deps.storage)?;
     let market = deps.api.addr_humanize(&config.market_contract)?;
 
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
 
-    // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+    // the loan is not repaid until the auctioned collaterals are sold, so the
+    // position must not be liquidated again meanwhile. Liquidation contracts
+    // without auctions fail the query
+    let liquidation_contract = deps.api.addr_humanize(&config.liquidation_contract)?;
+    if query_borrower_auctions(
         deps.as_ref(),
-        &cur_collaterals,
-        Some(env.block.time.seconds()),
-    )?;
+        liquidation_contract.clone(),
+        borrower.clone(),
+    )
+    .map(|res| !res.auctions.is_empty())
+    .unwrap_or(false)
+    {
+        return Err(ContractError::AuctionInProgress {});
+    }
+
+    // Compute borrow limit with collaterals except unlock target collaterals
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit_cached(deps.branch(), &env, &borrower_raw, &cur_collaterals)?;
     let borrow_amount_res: BorrowerInfoResponse =
         query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
     let borrow_amount = borrow_amount_res.loan_amount;
@@ -153,21 +293,94 @@ pub fn liquidate_collateral(
         return Err(ContractError::CannotLiquidateSafeLoan {});
     }
 
-    let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.liquidation_contract)?,
+    // liquidators outside of the whitelist wait for the exclusive window
+    if let Some(open_height) = assert_liquidator(deps.branch(), &env, &borrower_raw, &info.sender)?
+    {
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "liquidation_window_started"),
+            attr("borrower", borrower),
+            attr("open_height", open_height.to_string()),
+        ]));
+    }
+
+    // watchers see the position as it was before the liquidation
+    let collateral_value = collateral_value(&cur_collaterals, &collateral_prices);
+    let mut alert_messages: Vec<SubMsg> = risk_alert_messages(
+        deps.branch(),
+        &borrower_raw,
         borrow_amount,
-        borrow_limit,
-        &cur_collaterals.to_human(deps.as_ref())?,
-        collateral_prices,
+        collateral_value,
     )?;
+    alert_messages.append(&mut risk_notification_messages(
+        deps.branch(),
+        &borrower_raw,
+        borrow_amount,
+        collateral_value,
+    )?);
+
+    // collaterals sold on the DEX are not limited by the bids
+    let liquidation_amount_res: LiquidationAmountResponse = if max_spread.is_some() {
+        query_swap_liquidation_amount(
+            deps.as_ref(),
+            liquidation_contract.clone(),
+            borrow_amount,
+            borrow_limit,
+            &cur_collaterals.to_human(deps.as_ref())?,
+            collateral_prices.clone(),
+        )?
+    } else {
+        query_liquidation_amount(
+            deps.as_ref(),
+            liquidation_contract.clone(),
+            borrow_amount,
+            borrow_limit,
+            &cur_collaterals.to_human(deps.as_ref())?,
+            collateral_prices.clone(),
+        )?
+    };
 
     let liquidation_amount = liquidation_amount_res.collaterals.to_raw(deps.as_ref())?;
+    let liquidation_amount = order_seizure(
+        deps.as_ref(),
+        &borrower_raw,
+        &cur_collaterals,
+        &collateral_prices,
+        liquidation_amount,
+    )?;
+    let liquidation_amount = slice_liquidation(
+        deps.branch(),
+        &env,
+        &borrower_raw,
+        &cur_collaterals,
+        &collateral_prices,
+        liquidation_amount,
+    )?;
+    let liquidation_requested = !liquidation_amount.is_empty();
+    let liquidation_amount = throttle_liquidation(
+        deps.branch(),
+        &borrower_raw,
+        &cur_collaterals,
+        &collateral_prices,
+        liquidation_amount,
+    )?;
+
+    // the liquidation caps are reached, the borrower waits in the queue
+    if liquidation_requested && liquidation_amount.is_empty() {
+        return Ok(Response::new()
+            .add_submessages(alert_messages)
+            .add_attributes(vec![
+                attr("action", "liquidation_throttled"),
+                attr("borrower", borrower),
+            ]));
+    }
 
     // Store left collaterals
     cur_collaterals.sub(liquidation_amount.clone())?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
+    // a new exclusive window starts if the position is liquidatable again
+    store_liquidatable_since(deps.storage, &borrower_raw, None)?;
+
     let market_contract = deps.api.addr_humanize(&config.market_contract)?;
     let prev_balance: Uint256 =
         query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?;
@@ -175,34 +388,141 @@ pub fn liquidate_collateral(
     let liquidation_messages: Vec<CosmosMsg> = liquidation_amount
         .iter()
         .map(|collateral| {
-            let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+            let custody_contract =
+                read_custody_contract(deps.storage, &collateral.0, &borrower_raw)?;
 
             Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps
-                    .api
-                    .addr_humanize(&whitelist_elem.custody_contract)?
-                    .to_string(),
+                contract_addr: deps.api.addr_humanize(&custody_contract)?.to_string(),
                 funds: vec![],
                 msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
                     liquidator: info.sender.to_string(),
                     borrower: borrower.to_string(),
                     amount: collateral.1,
+                    max_spread,
                 })?,
             }))
         })
         .filter(|msg| msg.is_ok())
         .collect::<StdResult<Vec<CosmosMsg>>>()?;
 
-    Ok(Response::new()
-        .add_messages(liquidation_messages)
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+    // auctioned collaterals repay the loan once they are sold, so nothing is repaid
+    // here when all of them are auctioned. Liquidation contracts without auctions
+    // fail the query. Swaps repay the loan right away
+    let mut auctioned = max_spread.is_none() && !liquidation_amount.is_empty();
+    for collateral in liquidation_amount.iter() {
+        auctioned &= query_auction_config(
+            deps.as_ref(),
+            liquidation_contract.clone(),
+            deps.api.addr_humanize(&collateral.0)?,
+        )
+        .map(|res| res.auction_config.is_some())
+        .unwrap_or(false);
+    }
+
+    let mut response = Response::new().add_messages(liquidation_messages);
+    if !auctioned {
+        response = response.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: market_contract.to_string(),
             funds: vec![],
             msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
                 borrower: borrower.to_string(),
                 prev_balance,
             })?,
-        })))
+        }));
+    }
+
+    Ok(response
+        .add_submessages(alert_messages)
+        .add_attributes(credit_line_attributes(deps.storage, &borrower_raw)?))
+}
+
+/// Liquidation of the borrower at the current prices and bid pools,
+/// before the liquidation caps are applied
+pub fn query_simulate_liquidation(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<SimulateLiquidationResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+        deps,
+        &env,
+        &borrower_raw,
+        &collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    let borrow_amount = query_borrower_info(
+        deps,
+        deps.api.addr_humanize(&config.market_contract)?,
+        borrower.clone(),
+        env.block.height,
+    )?
+    .loan_amount;
+
+    let mut simulated_collaterals: Vec<SimulatedCollateralLiquidation> = vec![];
+    let mut repay_amount = Uint256::zero();
+    let mut liquidated_value = Uint256::zero();
+    if borrow_limit < borrow_amount {
+        let liquidation_contract = deps.api.addr_humanize(&config.liquidation_contract)?;
+        let liquidation_amount = query_liquidation_amount(
+            deps,
+            liquidation_contract.clone(),
+            borrow_amount,
+            borrow_limit,
+            &collaterals.to_human(deps)?,
+            collateral_prices.clone(),
+        )?
+        .collaterals
+        .to_raw(deps)?;
+        let liquidation_amount = order_seizure(
+            deps,
+            &borrower_raw,
+            &collaterals,
+            &collateral_prices,
+            liquidation_amount,
+        )?;
+
+        for (collateral_token_raw, amount) in liquidation_amount.into_iter() {
+            let collateral_token = deps.api.addr_humanize(&collateral_token_raw)?.to_string();
+            let price = collaterals
+                .iter()
+                .position(|c| c.0 == collateral_token_raw)
+                .map(|index| collateral_prices[index])
+                .ok_or(ContractError::NoOraclePrice(collateral_token.clone()))?;
+
+            let simulation = query_simulate_bid_execution(
+                deps,
+                liquidation_contract.clone(),
+                collateral_token.clone(),
+                amount,
+                price,
+            )?;
+
+            repay_amount += simulation.repay_amount;
+            liquidated_value += simulation.collateral_amount * price;
+            simulated_collaterals.push(SimulatedCollateralLiquidation {
+                collateral_token,
+                amount: simulation.collateral_amount,
+                repay_amount: simulation.repay_amount,
+                fills: simulation.fills,
+            });
+        }
+    }
+
+    let loan_amount = borrow_amount - std::cmp::min(borrow_amount, repay_amount);
+    Ok(SimulateLiquidationResponse {
+        borrower: borrower.to_string(),
+        collaterals: simulated_collaterals,
+        repay_amount,
+        loan_amount,
+        ltv: position_ltv(
+            loan_amount,
+            collateral_value(&collaterals, &collateral_prices) - liquidated_value,
+        ),
+    })
 }
 
 pub fn query_collaterals(deps: Deps, borrower: Addr) -> StdResult<CollateralsResponse> {
@@ -239,54 +559,435 @@ pub fn query_all_collaterals(
 #[allow(clippy::ptr_arg)]
 pub(crate) fn compute_borrow_limit(
     deps: Deps,
+    env: &Env,
+    borrower: &CanonicalAddr,
     collaterals: &Tokens,
     block_time: Option<u64>,
-) -> StdResult<(Uint256, Vec<Decimal256>)> {
+) -> Result<(Uint256, Vec<Decimal256>), ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let collateral_prices: Vec<Decimal256> = collaterals
+        .iter()
+        .map(|collateral| query_collateral_price(deps, &config, &collateral.0, block_time))
+        .collect::<Result<Vec<Decimal256>, ContractError>>()?;
+    let borrow_limit =
+        compute_borrow_limit_at_prices(deps, env, borrower, collaterals, &collateral_prices)?;
+
+    // returns borrow_limit with collaterals value in stable denom
+    Ok((borrow_limit, collateral_prices))
+}
+
+/// Borrow limit of the collaterals valued at the given prices
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_borrow_limit_at_prices(
+    deps: Deps,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+) -> StdResult<Uint256> {
+    let max_ltvs: Vec<Decimal256> =
+        read_max_ltvs(deps, env, borrower, collaterals, collateral_prices)?;
 
     let mut borrow_limit: Uint256 = Uint256::zero();
-    let mut collateral_prices: Vec<Decimal256> = vec![];
-    for collateral in collaterals.iter() {
-        let collateral_token = collateral.0.clone();
-        let collateral_amount = collateral.1;
+    for ((collateral, price), max_ltv) in collaterals.iter().zip(collateral_prices).zip(max_ltvs) {
+        let collateral_value = mul_dec(collateral.1, *price, Rounding::Down)?;
+        borrow_limit += mul_dec(collateral_value, max_ltv, Rounding::Down)?;
+    }
 
-        let price: PriceResponse = query_price(
+    if let Some(CreditLine::FixedLimit(fixed_limit)) = read_credit_line(deps.storage, borrower)? {
+        borrow_limit = std::cmp::max(borrow_limit, fixed_limit);
+    }
+
+    Ok(borrow_limit)
+}
+
+/// Oracle price of the collateral in stable denom. With a `block_time`,
+/// the price must be updated within the max price age of the collateral,
+/// or the price timeframe when it has none. Collaterals with an oracle
+/// quorum are priced by their quorum instead. The price is of the kind
+/// set for the collateral, spot by default.
+pub(crate) fn query_collateral_price(
+    deps: Deps,
+    config: &Config,
+    collateral_token: &CanonicalAddr,
+    block_time: Option<u64>,
+) -> Result<Decimal256, ContractError> {
+    let collateral_token_human = deps.api.addr_humanize(collateral_token)?.to_string();
+    let max_price_age =
+        read_max_price_age(deps.storage, collateral_token)?.unwrap_or(config.price_timeframe);
+    let price_kind = read_price_kind(deps.storage, collateral_token)?;
+    let confidence_threshold = read_confidence_threshold(deps.storage, collateral_token)?;
+    if let Some(oracle_quorum) = read_oracle_quorum(deps.storage, collateral_token)? {
+        return query_quorum_price(
             deps,
-            oracle_contract.clone(),
-            (deps.api.addr_humanize(&collateral_token)?).to_string(),
-            config.stable_denom.to_string(),
-            block_time.map(|block_time| TimeConstraints {
-                block_time,
-                valid_timeframe: config.price_timeframe,
-            }),
-        )?;
+            config,
+            collateral_token_human,
+            &oracle_quorum,
+            price_kind,
+            confidence_threshold,
+            max_price_age,
+            block_time,
+        );
+    }
+
+    let price: PriceResponse = query_price_of_kind(
+        deps,
+        deps.api.addr_humanize(&read_collateral_oracle(
+            deps.storage,
+            config,
+            collateral_token,
+        )?)?,
+        collateral_token_human.clone(),
+        config.stable_denom.to_string(),
+        price_kind,
+        None,
+    )?;
 
-        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
-        let collateral_value = collateral_amount * price.rate;
-        borrow_limit += collateral_value * elem.max_ltv;
-        collateral_prices.push(price.rate);
+    if let Some(block_time) = block_time {
+        let last_updated = std::cmp::min(price.last_updated_base, price.last_updated_quote);
+        if last_updated + max_price_age < block_time {
+            return Err(ContractError::PriceTooOld(
+                collateral_token_human,
+                last_updated,
+            ));
+        }
     }
 
-    // returns borrow_limit with collaterals value in stable denom
+    Ok(collateral_rate(&price, confidence_threshold))
+}
+
+/// Rate the collateral is valued at, the lower bound of the price when
+/// its confidence band is wider than the threshold
+pub(crate) fn collateral_rate(
+    price: &PriceResponse,
+    confidence_threshold: Option<Decimal256>,
+) -> Decimal256 {
+    match (price.confidence, confidence_threshold) {
+        (Some(confidence), Some(confidence_threshold))
+            if !price.rate.is_zero() && confidence / price.rate > confidence_threshold =>
+        {
+            if confidence < price.rate {
+                price.rate - confidence
+            } else {
+                Decimal256::zero()
+            }
+        }
+        _ => price.rate,
+    }
+}
+
+/// Effective LTV of each collateral for the borrower;
+/// borrowers opted into a category get its boosted LTV,
+/// ramped down for deprecated collaterals. The boost is dropped when
+/// a single collateral exceeds the category share of the borrow limit
+#[allow(clippy::ptr_arg)]
+pub(crate) fn read_max_ltvs(
+    deps: Deps,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+) -> StdResult<Vec<Decimal256>> {
+    let category: Option<(Category, Option<Decimal256>)> =
+        match read_borrower_category(deps.storage, borrower)? {
+            Some(category) => Some((
+                read_category(deps.storage, &category)?,
+                read_category_share_limit(deps.storage, &category)?,
+            )),
+            None => None,
+        };
+    let credit_line: Option<CreditLine> = read_credit_line(deps.storage, borrower)?;
+
+    let read_ltvs = |category: Option<&Category>| -> StdResult<Vec<Decimal256>> {
+        collaterals
+            .iter()
+            .map(|collateral| {
+                let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+                let max_ltv = match category {
+                    Some(category) if category.collateral_tokens.contains(&collateral.0) => {
+                        category.max_ltv
+                    }
+                    _ => elem.max_ltv,
+                };
+
+                let max_ltv =
+                    max_ltv * read_ltv_factor(deps, &collateral.0, env.block.time.seconds())?;
+                Ok(apply_ltv_multiplier(&credit_line, max_ltv))
+            })
+            .collect()
+    };
+
+    let (category, max_collateral_share) = match category {
+        Some((category, max_collateral_share)) => (category, max_collateral_share),
+        None => return read_ltvs(None),
+    };
+
+    let max_ltvs = read_ltvs(Some(&category))?;
+    if let Some(max_collateral_share) = max_collateral_share {
+        let contributions: Vec<Uint256> = collaterals
+            .iter()
+            .zip(collateral_prices)
+            .zip(max_ltvs.iter())
+            .map(|((collateral, price), max_ltv)| collateral.1 * *price * *max_ltv)
+            .collect();
+        let borrow_limit = contributions
+            .iter()
+            .fold(Uint256::zero(), |sum, contribution| sum + *contribution);
+
+        if contributions
+            .iter()
+            .any(|contribution| *contribution > borrow_limit * max_collateral_share)
+        {
+            return read_ltvs(None);
+        }
+    }
+
+    Ok(max_ltvs)
+}
+
+/// Same as [compute_borrow_limit] at the current block time, but reuses
+/// the result computed for the same collaterals earlier in this block
+/// as long as none of the oracle feeds pricing them were updated since.
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_borrow_limit_cached(
+    deps: DepsMut,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> Result<(Uint256, Vec<Decimal256>), ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let price_stamps = query_price_stamps(deps.as_ref(), &config, collaterals)?;
+    let version = read_borrow_limit_cache_version(deps.storage)?;
+    if let Some(cache) = read_borrow_limit_cache(deps.storage, borrower)? {
+        if cache.height == env.block.height
+            && cache.version == version
+            && &cache.collaterals == collaterals
+            && cache.price_stamps == price_stamps
+        {
+            return Ok((cache.borrow_limit, cache.collateral_prices));
+        }
+    }
+
+    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+        deps.as_ref(),
+        env,
+        borrower,
+        collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+
+    store_borrow_limit_cache(
+        deps.storage,
+        borrower,
+        &BorrowLimitCache {
+            height: env.block.height,
+            version,
+            collaterals: collaterals.clone(),
+            price_stamps,
+            borrow_limit,
+            collateral_prices: collateral_prices.clone(),
+        },
+    )?;
+
     Ok((borrow_limit, collateral_prices))
 }
 
+/// Spot feed of every oracle pricing the collaterals, in order;
+/// quorum oracles failing to answer are stamped as `None`
+#[allow(clippy::ptr_arg)]
+fn query_price_stamps(
+    deps: Deps,
+    config: &Config,
+    collaterals: &Tokens,
+) -> Result<Vec<Option<PriceStamp>>, ContractError> {
+    let mut price_stamps: Vec<Option<PriceStamp>> = vec![];
+    for collateral in collaterals.iter() {
+        let collateral_token_human = deps.api.addr_humanize(&collateral.0)?.to_string();
+        let query_stamp = |oracle: &CanonicalAddr| -> StdResult<PriceStamp> {
+            let price: PriceResponse = query_price(
+                deps,
+                deps.api.addr_humanize(oracle)?,
+                collateral_token_human.clone(),
+                config.stable_denom.to_string(),
+                None,
+            )?;
+            Ok(PriceStamp {
+                rate: price.rate,
+                last_updated_base: price.last_updated_base,
+                last_updated_quote: price.last_updated_quote,
+            })
+        };
+
+        match read_oracle_quorum(deps.storage, &collateral.0)? {
+            Some(oracle_quorum) => {
+                for oracle in oracle_quorum.oracles.iter() {
+                    price_stamps.push(query_stamp(oracle).ok());
+                }
+            }
+            None => {
+                let oracle = read_collateral_oracle(deps.storage, config, &collateral.0)?;
+                price_stamps.push(Some(query_stamp(&oracle)?));
+            }
+        }
+    }
+
+    Ok(price_stamps)
+}
+
 pub fn query_borrow_limit(
     deps: Deps,
+    env: Env,
     borrower: Addr,
     block_time: Option<u64>,
 ) -> StdResult<BorrowLimitResponse> {
-    let collaterals = read_collaterals(
-        deps.storage,
-        &deps.api.addr_canonicalize(borrower.as_str())?,
-    );
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals = read_collaterals(deps.storage, &borrower_raw);
 
     // Compute borrow limit with collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    let (borrow_limit, _) =
+        compute_borrow_limit(deps, &env, &borrower_raw, &collaterals, block_time)?;
 
     Ok(BorrowLimitResponse {
         borrower: borrower.to_string(),
         borrow_limit,
     })
 }
+
+pub fn query_borrow_limits(
+    deps: Deps,
+    env: Env,
+    borrowers: Vec<Addr>,
+    block_time: Option<u64>,
+) -> StdResult<BorrowLimitsResponse> {
+    if borrowers.len() > MAX_BORROW_LIMITS_QUERY {
+        return Err(ContractError::TooManyBorrowers(MAX_BORROW_LIMITS_QUERY).into());
+    }
+
+    let borrow_limits = borrowers
+        .into_iter()
+        .map(|borrower| query_borrow_limit(deps, env.clone(), borrower, block_time))
+        .collect::<StdResult<Vec<BorrowLimitResponse>>>()?;
+
+    Ok(BorrowLimitsResponse { borrow_limits })
+}
+
+pub fn query_collaterals_detailed(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<CollateralsDetailedResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit(deps, &env, &borrower_raw, &collaterals, None)?;
+    let max_ltvs: Vec<Decimal256> =
+        read_max_ltvs(deps, &env, &borrower_raw, &collaterals, &collateral_prices)?;
+    let loan_amount = query_borrower_info(
+        deps,
+        deps.api.addr_humanize(&config.market_contract)?,
+        borrower.clone(),
+        env.block.height,
+    )?
+    .loan_amount;
+
+    let collaterals = collaterals
+        .iter()
+        .zip(collateral_prices)
+        .zip(max_ltvs)
+        .map(|((collateral, price), max_ltv)| {
+            let value = collateral.1 * price;
+            let collateral_borrow_limit = value * max_ltv;
+
+            // price at which the loan exceeds the borrow limit,
+            // when the prices of the other collaterals are unchanged
+            let other_borrow_limit = borrow_limit - collateral_borrow_limit;
+            let liquidation_price =
+                if loan_amount <= other_borrow_limit || collateral.1.is_zero() || max_ltv.is_zero()
+                {
+                    Decimal256::zero()
+                } else {
+                    Decimal256::from_uint256(loan_amount - other_borrow_limit)
+                        / (Decimal256::from_uint256(collateral.1) * max_ltv)
+                };
+
+            Ok(CollateralDetail {
+                collateral_token: deps.api.addr_humanize(&collateral.0)?.to_string(),
+                amount: collateral.1,
+                price,
+                value,
+                max_ltv,
+                borrow_limit: collateral_borrow_limit,
+                liquidation_price,
+            })
+        })
+        .collect::<StdResult<Vec<CollateralDetail>>>()?;
+
+    Ok(CollateralsDetailedResponse {
+        borrower: borrower.to_string(),
+        loan_amount,
+        borrow_limit,
+        collaterals,
+    })
+}
+
+/// Scans a page of the collaterals storage, so a page can hold
+/// fewer positions than `limit` when most borrowers are safe
+pub fn query_risky_positions(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    min_risk_ratio: Decimal256,
+) -> StdResult<RiskyPositionsResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let all_collaterals: Vec<CollateralsResponse> = read_all_collaterals(deps, start_after, limit)?;
+    let last_borrower = all_collaterals.last().map(|c| c.borrower.clone());
+
+    let mut positions: Vec<RiskyPositionResponse> = vec![];
+    for collaterals in all_collaterals {
+        let borrower = deps.api.addr_validate(&collaterals.borrower)?;
+        let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+        let (borrow_limit, _) = compute_borrow_limit(
+            deps,
+            &env,
+            &borrower_raw,
+            &collaterals.collaterals.to_raw(deps)?,
+            None,
+        )?;
+        let loan_amount =
+            query_borrower_info(deps, market.clone(), borrower.clone(), env.block.height)?
+                .loan_amount;
+
+        let risk_ratio = if !borrow_limit.is_zero() {
+            Decimal256::from_ratio(loan_amount, borrow_limit)
+        } else if !loan_amount.is_zero() {
+            Decimal256::MAX
+        } else {
+            Decimal256::zero()
+        };
+
+        if risk_ratio >= min_risk_ratio {
+            positions.push(RiskyPositionResponse {
+                borrower: borrower.to_string(),
+                loan_amount,
+                borrow_limit,
+                risk_ratio,
+            });
+        }
+    }
+
+    Ok(RiskyPositionsResponse {
+        positions,
+        last_borrower,
+    })
+}
@@ -1,30 +1,92 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, WasmMsg,
+    attr, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Reply, Response, StdResult, SubMsg, WasmMsg,
 };
 
+use crate::category::{
+    query_borrower_category, query_category, query_category_share_limit, register_category,
+    set_borrower_category, update_category_share_limit,
+};
 use crate::collateral::{
     liquidate_collateral, lock_collateral, query_all_collaterals, query_borrow_limit,
-    query_collaterals, unlock_collateral,
+    query_borrow_limits, query_collaterals, query_collaterals_detailed, query_risky_positions,
+    query_simulate_liquidation, unlock_collateral,
+};
+use crate::credit_line::{query_credit_line, update_credit_line};
+use crate::custody_interface::assert_custody_interface;
+use crate::debt_ceiling::{query_debt_ceiling, sync_borrower_debt, update_debt_ceiling};
+use crate::deleverage::{deleverage, query_auto_deleverage, set_auto_deleverage};
+use crate::distribution_cap::{
+    compute_capped_distribution, query_distribution_cap, update_distribution_cap,
 };
+use crate::dynamic_rate::{
+    compute_dynamic_deposit_rate, query_dynamic_rate_config, query_projected_runway,
+    update_dynamic_rate_config,
+};
+use crate::epoch_history::{query_epoch_history, record_epoch_snapshot};
 use crate::error::ContractError;
+use crate::exclusive_liquidation::{
+    query_exclusive_liquidation, query_liquidation_window, update_exclusive_liquidation,
+};
+use crate::freeze::{freeze_protocol, query_freeze_state, unfreeze_protocol, update_guardian};
+use crate::gradual_liquidation::{
+    query_gradual_liquidation, query_liquidation_plan, update_gradual_liquidation,
+};
+use crate::intent::query_validate_intent;
+use crate::keeper::{execute_epoch_step, query_epoch_progress, update_keeper_reward};
+use crate::liquidation_throttle::{query_liquidation_throttle, update_liquidation_cap};
+use crate::listing::{
+    approve_whitelist_proposal, propose_whitelist, query_interest_buffer,
+    query_whitelist_proposals, register_proposed_whitelist, reject_whitelist_proposal,
+    update_proposal_bond, INSTANTIATE_CUSTODY_REPLY_ID,
+};
+use crate::mass_simulation::query_mass_simulate;
+use crate::migration::{migrate_collateral, query_custody_migration};
+use crate::operator::{assert_operator_permission, query_operator, set_operator};
+use crate::oracle_quorum::{query_oracle_quorum, update_oracle_quorum};
 use crate::querier::query_epoch_state;
+use crate::risk_notification::{
+    deregister_risk_notification, query_risk_notification, register_risk_notification,
+    RISK_NOTIFICATION_REPLY_ID,
+};
+use crate::seizure::{
+    query_seizure_order, query_seizure_preference, update_seizure_order, update_seizure_preference,
+};
+use crate::self_liquidation::self_liquidate;
 use crate::state::{
-    read_config, read_epoch_state, read_whitelist, read_whitelist_elem, store_config,
-    store_epoch_state, store_whitelist_elem, Config, EpochState, WhitelistElem,
+    bump_borrow_limit_cache_version, read_carried_interest, read_collateral_oracle,
+    read_confidence_threshold, read_config, read_distribution_cap, read_dynamic_rate_config,
+    read_epoch_cursor, read_epoch_progress, read_epoch_state, read_max_price_age,
+    read_min_lock_duration, read_price_kind, read_unlock_buffer, read_whitelist,
+    read_whitelist_elem, remove_epoch_cursor, store_carried_interest, store_collateral_oracle,
+    store_confidence_threshold, store_config, store_epoch_cursor, store_epoch_state,
+    store_max_price_age, store_min_lock_duration, store_prev_distributed_interest,
+    store_price_kind, store_unlock_buffer, store_whitelist_elem, Config, EpochCursor, EpochState,
+    WhitelistElem,
 };
+use crate::watchlist::{
+    epoch_risk_alert_messages, query_risk_watches, update_risk_watcher, watch_borrower,
+    RISK_ALERT_REPLY_ID,
+};
+use crate::whitelist_status::{query_whitelist_status, update_whitelist_status};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use moneymarket::common::optional_addr_validate;
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::oracle::PriceKind;
 use moneymarket::overseer::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    CollateralOracleResponse, ConfidenceThresholdResponse, ConfigResponse, ExecuteMsg,
+    InstantiateMsg, MaxPriceAgeResponse, MinLockDurationResponse, OperatorPermission,
+    PriceKindResponse, QueryMsg, UnlockBufferResponse, WhitelistResponse, WhitelistResponseElem,
 };
-use moneymarket::querier::{deduct_tax, query_balance};
+use moneymarket::querier::{deduct_tax, store_fee_model, FeeModel};
+
+// custodies distributing rewards per epoch operations call
+const EPOCH_REWARDS_BATCH: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -99,6 +161,7 @@ pub fn execute(
                 price_timeframe,
             )
         }
+        ExecuteMsg::UpdateFeeModel { fee_model } => update_fee_model(deps, info, fee_model),
         ExecuteMsg::Whitelist {
             name,
             symbol,
@@ -131,19 +194,386 @@ pub fn execute(
                 max_ltv,
             )
         }
+        ExecuteMsg::UpdateWhitelistStatus {
+            collateral_token,
+            status,
+            ltv_ramp_duration,
+        } => {
+            let api = deps.api;
+            update_whitelist_status(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                status,
+                ltv_ramp_duration,
+            )
+        }
+        ExecuteMsg::UpdateMaxPriceAge {
+            collateral_token,
+            max_price_age,
+        } => {
+            let api = deps.api;
+            update_max_price_age(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                max_price_age,
+            )
+        }
+        ExecuteMsg::UpdateOracleQuorum {
+            collateral_token,
+            oracle_quorum,
+        } => {
+            let api = deps.api;
+            update_oracle_quorum(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                oracle_quorum,
+            )
+        }
+        ExecuteMsg::UpdatePriceKind {
+            collateral_token,
+            price_kind,
+        } => {
+            let api = deps.api;
+            update_price_kind(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                price_kind,
+            )
+        }
+        ExecuteMsg::UpdateConfidenceThreshold {
+            collateral_token,
+            confidence_threshold,
+        } => {
+            let api = deps.api;
+            update_confidence_threshold(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                confidence_threshold,
+            )
+        }
+        ExecuteMsg::UpdateLiquidationCap {
+            collateral_token,
+            liquidation_cap,
+        } => {
+            let api = deps.api;
+            update_liquidation_cap(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                liquidation_cap,
+            )
+        }
+        ExecuteMsg::UpdateExclusiveLiquidation {
+            exclusive_liquidation,
+        } => update_exclusive_liquidation(deps, info, exclusive_liquidation),
+        ExecuteMsg::UpdateGradualLiquidation {
+            gradual_liquidation,
+        } => update_gradual_liquidation(deps, info, gradual_liquidation),
+        ExecuteMsg::UpdateSeizureOrder { seizure_order } => {
+            update_seizure_order(deps, info, seizure_order)
+        }
+        ExecuteMsg::UpdateSeizurePreference { collaterals } => {
+            update_seizure_preference(deps, info, collaterals)
+        }
+        ExecuteMsg::UpdateCollateralOracle {
+            collateral_token,
+            oracle_contract,
+        } => {
+            let api = deps.api;
+            update_collateral_oracle(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                optional_addr_validate(api, oracle_contract)?,
+            )
+        }
+        ExecuteMsg::UpdateUnlockBuffer {
+            collateral_token,
+            unlock_buffer,
+        } => {
+            let api = deps.api;
+            update_unlock_buffer(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                unlock_buffer,
+            )
+        }
+        ExecuteMsg::UpdateMinLockDuration {
+            collateral_token,
+            min_lock_duration,
+        } => {
+            let api = deps.api;
+            update_min_lock_duration(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                min_lock_duration,
+            )
+        }
+        ExecuteMsg::UpdateCreditLine {
+            borrower,
+            credit_line,
+        } => {
+            let api = deps.api;
+            update_credit_line(deps, info, api.addr_validate(&borrower)?, credit_line)
+        }
+        ExecuteMsg::UpdateRiskWatcher {
+            watcher,
+            registered,
+        } => {
+            let api = deps.api;
+            update_risk_watcher(deps, info, api.addr_validate(&watcher)?, registered)
+        }
+        ExecuteMsg::RegisterRiskNotification {
+            contract,
+            ltv_bands,
+        } => {
+            let api = deps.api;
+            register_risk_notification(deps, info, api.addr_validate(&contract)?, ltv_bands)
+        }
+        ExecuteMsg::DeregisterRiskNotification {} => deregister_risk_notification(deps, info),
+        ExecuteMsg::WatchBorrower {
+            borrower,
+            ltv_threshold,
+        } => {
+            let api = deps.api;
+            watch_borrower(deps, info, api.addr_validate(&borrower)?, ltv_threshold)
+        }
         ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
         ExecuteMsg::UpdateEpochState {
             interest_buffer,
             distributed_interest,
         } => update_epoch_state(deps, env, info, interest_buffer, distributed_interest),
-        ExecuteMsg::LockCollateral { collaterals } => lock_collateral(deps, info, collaterals),
+        ExecuteMsg::UpdateProposalBond { proposal_bond } => {
+            update_proposal_bond(deps, info, proposal_bond)
+        }
+        ExecuteMsg::ApproveWhitelistProposal { proposal_id } => {
+            approve_whitelist_proposal(deps, info, proposal_id)
+        }
+        ExecuteMsg::RejectWhitelistProposal { proposal_id, slash } => {
+            reject_whitelist_proposal(deps, info, proposal_id, slash)
+        }
+        ExecuteMsg::RegisterCategory {
+            name,
+            max_ltv,
+            collateral_tokens,
+        } => {
+            let api = deps.api;
+            register_category(
+                deps,
+                info,
+                name,
+                max_ltv,
+                collateral_tokens
+                    .iter()
+                    .map(|collateral_token| api.addr_validate(collateral_token))
+                    .collect::<StdResult<Vec<Addr>>>()?,
+            )
+        }
+        ExecuteMsg::UpdateDistributionCap {
+            max_distribution,
+            smoothing_window,
+        } => update_distribution_cap(deps, info, max_distribution, smoothing_window),
+        ExecuteMsg::UpdateDynamicRateConfig {
+            min_deposit_rate,
+            max_deposit_rate,
+            target_runway,
+            max_change,
+        } => update_dynamic_rate_config(
+            deps,
+            info,
+            min_deposit_rate,
+            max_deposit_rate,
+            target_runway,
+            max_change,
+        ),
+        ExecuteMsg::MigrateCollateral {
+            borrower,
+            old_custody,
+            new_custody,
+            limit,
+        } => {
+            let api = deps.api;
+            migrate_collateral(
+                deps,
+                info,
+                optional_addr_validate(api, borrower)?,
+                api.addr_validate(&old_custody)?,
+                api.addr_validate(&new_custody)?,
+                limit,
+            )
+        }
+        ExecuteMsg::UpdateDebtCeiling {
+            collateral_token,
+            debt_ceiling,
+        } => {
+            let api = deps.api;
+            update_debt_ceiling(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                debt_ceiling,
+            )
+        }
+        ExecuteMsg::UpdateKeeperReward { keeper_reward } => {
+            update_keeper_reward(deps, info, keeper_reward)
+        }
+        ExecuteMsg::UpdateGuardian {
+            guardian,
+            unfreeze_delay,
+        } => {
+            let api = deps.api;
+            update_guardian(
+                deps,
+                info,
+                optional_addr_validate(api, guardian)?,
+                unfreeze_delay,
+            )
+        }
+        ExecuteMsg::FreezeProtocol {} => freeze_protocol(deps, env, info),
+        ExecuteMsg::UnfreezeProtocol {} => unfreeze_protocol(deps, env, info),
+        ExecuteMsg::LockCollateral { collaterals } => {
+            lock_collateral(deps, env, info.sender, collaterals)
+        }
         ExecuteMsg::UnlockCollateral { collaterals } => {
-            unlock_collateral(deps, env, info, collaterals)
+            unlock_collateral(deps, env, info.sender, collaterals)
+        }
+        ExecuteMsg::SelfLiquidate {
+            collateral_token,
+            amount,
+        } => {
+            let api = deps.api;
+            self_liquidate(
+                deps,
+                env,
+                info.sender,
+                api.addr_validate(&collateral_token)?,
+                amount,
+            )
+        }
+        ExecuteMsg::UpdateCategoryShareLimit {
+            name,
+            max_collateral_share,
+        } => update_category_share_limit(deps, info, name, max_collateral_share),
+        ExecuteMsg::SetBorrowerCategory { category } => {
+            set_borrower_category(deps, env, info.sender, category)
+        }
+        ExecuteMsg::SetAutoDeleverage {
+            trigger_ratio,
+            target_ratio,
+        } => set_auto_deleverage(deps, info, trigger_ratio, target_ratio),
+        ExecuteMsg::SetOperator {
+            operator,
+            permissions,
+        } => {
+            let api = deps.api;
+            set_operator(deps, info, api.addr_validate(&operator)?, permissions)
+        }
+        ExecuteMsg::LockCollateralFor {
+            borrower,
+            collaterals,
+        } => {
+            let borrower = deps.api.addr_validate(&borrower)?;
+            assert_operator_permission(
+                deps.as_ref(),
+                &borrower,
+                &info.sender,
+                OperatorPermission::LockCollateral,
+            )?;
+            Ok(lock_collateral(deps, env, borrower, collaterals)?
+                .add_attribute("operator", info.sender))
+        }
+        ExecuteMsg::UnlockCollateralFor {
+            borrower,
+            collaterals,
+        } => {
+            let borrower = deps.api.addr_validate(&borrower)?;
+            assert_operator_permission(
+                deps.as_ref(),
+                &borrower,
+                &info.sender,
+                OperatorPermission::UnlockCollateral,
+            )?;
+            Ok(unlock_collateral(deps, env, borrower, collaterals)?
+                .add_attribute("operator", info.sender))
+        }
+        ExecuteMsg::SetBorrowerCategoryFor { borrower, category } => {
+            let borrower = deps.api.addr_validate(&borrower)?;
+            assert_operator_permission(
+                deps.as_ref(),
+                &borrower,
+                &info.sender,
+                OperatorPermission::SetBorrowerCategory,
+            )?;
+            Ok(set_borrower_category(deps, env, borrower, category)?
+                .add_attribute("operator", info.sender))
+        }
+        ExecuteMsg::SyncBorrowerDebt { borrower } => {
+            let api = deps.api;
+            sync_borrower_debt(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::Deleverage { borrower } => {
+            let api = deps.api;
+            deleverage(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::LiquidateCollateral {
+            borrower,
+            max_spread,
+        } => {
+            let api = deps.api;
+            liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?, max_spread)
         }
-        ExecuteMsg::LiquidateCollateral { borrower } => {
+        ExecuteMsg::ProposeWhitelist {
+            name,
+            symbol,
+            collateral_token,
+            custody_code_id,
+            custody_init_msg,
+            max_ltv,
+        } => {
             let api = deps.api;
-            liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?)
+            propose_whitelist(
+                deps,
+                info,
+                name,
+                symbol,
+                api.addr_validate(&collateral_token)?,
+                custody_code_id,
+                custody_init_msg,
+                max_ltv,
+            )
+        }
+        ExecuteMsg::ExecuteEpochStep {} => execute_epoch_step(deps, env, info),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_CUSTODY_REPLY_ID => {
+            // get new custody contract address
+            let data = msg
+                .result
+                .into_result()
+                .map_err(ContractError::CustodyInstantiationFailed)?
+                .data
+                .ok_or(ContractError::MissingInstantiateData {})?;
+
+            register_proposed_whitelist(deps, data)
+        }
+        // a failing risk watcher callback must not revert the operation
+        RISK_ALERT_REPLY_ID => Ok(Response::new().add_attribute("action", "risk_alert_failed")),
+        RISK_NOTIFICATION_REPLY_ID => {
+            Ok(Response::new().add_attribute("action", "risk_notification_failed"))
         }
+        _ => Err(ContractError::InvalidReplyId {}),
     }
 }
 
@@ -205,11 +635,31 @@ pub fn update_config(
         config.price_timeframe = price_timeframe;
     }
 
+    // oracle or price timeframe changes affect cached borrow limits
+    bump_borrow_limit_cache_version(deps.storage)?;
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn update_fee_model(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_model: FeeModel,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_fee_model(deps.storage, &fee_model)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fee_model"),
+        attr("fee_model", fee_model.to_string()),
+    ]))
+}
+
 pub fn register_whitelist(
     deps: DepsMut,
     info: MessageInfo,
@@ -229,6 +679,8 @@ pub fn register_whitelist(
         return Err(ContractError::TokenAlreadyRegistered {});
     }
 
+    assert_custody_interface(deps.as_ref(), custody_contract.clone())?;
+
     store_whitelist_elem(
         deps.storage,
         &collateral_token_raw,
@@ -267,11 +719,13 @@ pub fn update_whitelist(
         read_whitelist_elem(deps.storage, &collateral_token_raw)?;
 
     if let Some(custody_contract) = custody_contract {
+        assert_custody_interface(deps.as_ref(), custody_contract.clone())?;
         whitelist_elem.custody_contract = deps.api.addr_canonicalize(custody_contract.as_str())?;
     }
 
     if let Some(max_ltv) = max_ltv {
         whitelist_elem.max_ltv = max_ltv;
+        bump_borrow_limit_cache_version(deps.storage)?;
     }
 
     store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
@@ -287,49 +741,367 @@ pub fn update_whitelist(
     ]))
 }
 
-pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+pub fn update_min_lock_duration(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    min_lock_duration: Option<u64>,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let state: EpochState = read_epoch_state(deps.storage)?;
-    if env.block.height < state.last_executed_height + config.epoch_period {
-        return Err(ContractError::EpochNotPassed(state.last_executed_height));
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
     }
 
-    // # of blocks from the last executed height
-    let blocks = Uint256::from(env.block.height - state.last_executed_height);
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_min_lock_duration(deps.storage, &collateral_token_raw, min_lock_duration)?;
 
-    // Compute next epoch state
-    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
-    let epoch_state: EpochStateResponse = query_epoch_state(
-        deps.as_ref(),
-        market_contract.clone(),
-        env.block.height,
-        None,
-    )?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_min_lock_duration"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "min_lock_duration",
+            min_lock_duration.map(|d| d.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
 
-    // effective_deposit_rate = cur_exchange_rate / prev_exchange_rate
-    // deposit_rate = (effective_deposit_rate - 1) / blocks
-    let effective_deposit_rate = epoch_state.exchange_rate / state.prev_exchange_rate;
-    let deposit_rate =
-        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks);
+pub fn update_collateral_oracle(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    oracle_contract: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-    let mut interest_buffer = query_balance(
-        deps.as_ref(),
-        env.contract.address.clone(),
-        config.stable_denom.to_string(),
-    )?;
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let oracle_contract_raw = match oracle_contract.as_ref() {
+        Some(oracle_contract) => Some(deps.api.addr_canonicalize(oracle_contract.as_str())?),
+        None => None,
+    };
+    store_collateral_oracle(deps.storage, &collateral_token_raw, oracle_contract_raw)?;
 
-    // Send accrued_buffer * config.anc_purchase_factor amount stable token to collector
-    let accrued_buffer = interest_buffer - state.prev_interest_buffer;
-    let anc_purchase_amount = accrued_buffer * config.anc_purchase_factor;
-    if !anc_purchase_amount.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
-                .addr_humanize(&config.collector_contract)?
-                .to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
+    // cached borrow limits were priced by the previous oracle
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_collateral_oracle"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "oracle_contract",
+            oracle_contract.map(|o| o.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn update_unlock_buffer(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    unlock_buffer: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(unlock_buffer) = unlock_buffer {
+        if unlock_buffer >= Decimal256::one() {
+            return Err(ContractError::InvalidUnlockBuffer {});
+        }
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_unlock_buffer(deps.storage, &collateral_token_raw, unlock_buffer)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_unlock_buffer"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "unlock_buffer",
+            unlock_buffer.unwrap_or_else(Decimal256::zero).to_string(),
+        ),
+    ]))
+}
+
+pub fn update_max_price_age(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    max_price_age: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_max_price_age(deps.storage, &collateral_token_raw, max_price_age)?;
+
+    // cached borrow limits were priced under the previous staleness rule
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_max_price_age"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "max_price_age",
+            max_price_age.unwrap_or(config.price_timeframe).to_string(),
+        ),
+    ]))
+}
+
+pub fn update_price_kind(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    price_kind: Option<PriceKind>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_price_kind(deps.storage, &collateral_token_raw, price_kind.clone())?;
+
+    // cached borrow limits were priced by the previous price kind
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_price_kind"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "price_kind",
+            price_kind.unwrap_or(PriceKind::Spot).to_string(),
+        ),
+    ]))
+}
+
+pub fn update_confidence_threshold(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    confidence_threshold: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_confidence_threshold(deps.storage, &collateral_token_raw, confidence_threshold)?;
+
+    // cached borrow limits were valued under the previous threshold
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_confidence_threshold"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "confidence_threshold",
+            confidence_threshold
+                .map(|confidence_threshold| confidence_threshold.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    // resume the epoch operations left unfinished by the previous call
+    if let Some(cursor) = read_epoch_cursor(deps.storage)? {
+        return resume_epoch_operations(deps, env, cursor);
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let state: EpochState = read_epoch_state(deps.storage)?;
+    if env.block.height < state.last_executed_height + config.epoch_period {
+        return Err(ContractError::EpochNotPassed(state.last_executed_height));
+    }
+
+    // epoch is being processed step by step by keepers
+    if read_epoch_progress(deps.storage)?.is_some() {
+        return Err(ContractError::EpochInProgress {});
+    }
+
+    let (deposit_rate, epoch_state) = compute_deposit_rate(deps.as_ref(), &env, &config, &state)?;
+
+    let interest_buffer = query_interest_buffer(deps.as_ref(), &env, &config)?;
+
+    let (
+        mut messages,
+        interest_buffer,
+        distributed_interest,
+        anc_purchase_amount,
+        carried_interest,
+    ) = distribute_interest_buffer(
+        deps.as_ref(),
+        &env,
+        &config,
+        &state,
+        deposit_rate,
+        interest_buffer,
+    )?;
+    store_carried_interest(deps.storage, &carried_interest)?;
+
+    // Execute DistributeRewards
+    let (mut rewards_messages, rewards_cursor) = distribute_rewards_messages(deps.as_ref(), None)?;
+    messages.append(&mut rewards_messages);
+
+    let mut attributes = vec![
+        attr("action", "epoch_operations"),
+        attr("deposit_rate", deposit_rate.to_string()),
+        attr("exchange_rate", epoch_state.exchange_rate.to_string()),
+        attr("aterra_supply", epoch_state.aterra_supply),
+        attr("distributed_interest", distributed_interest),
+        attr("anc_purchase_amount", anc_purchase_amount),
+    ];
+
+    match rewards_cursor {
+        // more custodies left, the epoch state is updated by the last call
+        Some(last_collateral) => {
+            attributes.push(attr(
+                "last_collateral",
+                deps.api.addr_humanize(&last_collateral)?,
+            ));
+            store_epoch_cursor(
+                deps.storage,
+                &EpochCursor {
+                    last_collateral,
+                    interest_buffer,
+                    distributed_interest,
+                },
+            )?;
+        }
+        // TODO: Should this become a reply? If so which SubMsg to make reply_on?
+        // Execute store epoch state operation
+        None => messages.push(update_epoch_state_message(
+            &env,
+            interest_buffer,
+            distributed_interest,
+        )?),
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+fn resume_epoch_operations(
+    deps: DepsMut,
+    env: Env,
+    cursor: EpochCursor,
+) -> Result<Response, ContractError> {
+    let (mut messages, rewards_cursor) =
+        distribute_rewards_messages(deps.as_ref(), Some(cursor.last_collateral))?;
+
+    let mut attributes = vec![attr("action", "resume_epoch_operations")];
+    match rewards_cursor {
+        Some(last_collateral) => {
+            attributes.push(attr(
+                "last_collateral",
+                deps.api.addr_humanize(&last_collateral)?,
+            ));
+            store_epoch_cursor(
+                deps.storage,
+                &EpochCursor {
+                    last_collateral,
+                    ..cursor
+                },
+            )?;
+        }
+        None => {
+            remove_epoch_cursor(deps.storage);
+            messages.push(update_epoch_state_message(
+                &env,
+                cursor.interest_buffer,
+                cursor.distributed_interest,
+            )?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+fn update_epoch_state_message(
+    env: &Env,
+    interest_buffer: Uint256,
+    distributed_interest: Uint256,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::UpdateEpochState {
+            interest_buffer,
+            distributed_interest,
+        })?,
+    }))
+}
+
+/// Compute the per block deposit rate of the epoch
+/// from the market exchange rate growth
+pub(crate) fn compute_deposit_rate(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    state: &EpochState,
+) -> StdResult<(Decimal256, EpochStateResponse)> {
+    // # of blocks from the last executed height
+    let blocks = Uint256::from(env.block.height - state.last_executed_height);
+
+    // Compute next epoch state
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+    let epoch_state: EpochStateResponse =
+        query_epoch_state(deps, market_contract, env.block.height, None)?;
+
+    // effective_deposit_rate = cur_exchange_rate / prev_exchange_rate
+    // deposit_rate = (effective_deposit_rate - 1) / blocks
+    let effective_deposit_rate = epoch_state.exchange_rate / state.prev_exchange_rate;
+    let deposit_rate =
+        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks);
+
+    Ok((deposit_rate, epoch_state))
+}
+
+/// Send the ANC purchase portion of the accrued buffer to the collector
+/// and top up the market when deposit rate < threshold_deposit_rate.
+/// Returns the messages with the left interest buffer, the distributed
+/// interest, the ANC purchase amount and the interest carried over
+#[allow(clippy::type_complexity)]
+pub(crate) fn distribute_interest_buffer(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    state: &EpochState,
+    deposit_rate: Decimal256,
+    mut interest_buffer: Uint256,
+) -> StdResult<(Vec<CosmosMsg>, Uint256, Uint256, Uint256, Uint256)> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    // # of blocks from the last executed height
+    let blocks = Uint256::from(env.block.height - state.last_executed_height);
+
+    // Send accrued_buffer * config.anc_purchase_factor amount stable token to collector
+    let accrued_buffer = interest_buffer - state.prev_interest_buffer;
+    let anc_purchase_amount = accrued_buffer * config.anc_purchase_factor;
+    if !anc_purchase_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: deps
+                .api
+                .addr_humanize(&config.collector_contract)?
+                .to_string(),
+            amount: vec![deduct_tax(
+                deps,
                 Coin {
                     denom: config.stable_denom.to_string(),
                     amount: anc_purchase_amount.into(),
@@ -342,79 +1114,96 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
     interest_buffer = interest_buffer - anc_purchase_amount;
 
     // Distribute Interest Buffer to depositor
-    // Only executed when deposit rate < threshold_deposit_rate
-    let mut distributed_interest: Uint256 = Uint256::zero();
-    if deposit_rate < config.threshold_deposit_rate {
+    // Only missing deposits when deposit rate < threshold_deposit_rate
+    let missing_deposits = if deposit_rate < config.threshold_deposit_rate {
         // missing_deposit_rate(_per_block)
         let missing_deposit_rate = config.threshold_deposit_rate - deposit_rate;
         let prev_deposits = state.prev_aterra_supply * state.prev_exchange_rate;
 
         // missing_deposits = prev_deposits * missing_deposit_rate(_per_block) * blocks
-        let missing_deposits = prev_deposits * blocks * missing_deposit_rate;
-        let distribution_buffer = interest_buffer * config.buffer_distribution_factor;
-
-        // When there was not enough deposits happens,
-        // distribute interest to market contract
-        distributed_interest = std::cmp::min(missing_deposits, distribution_buffer);
-        interest_buffer = interest_buffer - distributed_interest;
-
-        if !distributed_interest.is_zero() {
-            // deduct tax
-            distributed_interest = Uint256::from(
-                deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom.to_string(),
-                        amount: distributed_interest.into(),
-                    },
-                )?
-                .amount,
-            );
-
-            // Send some portion of interest buffer to Market contract
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: market_contract.to_string(),
-                amount: vec![Coin {
-                    denom: config.stable_denom,
+        prev_deposits * blocks * missing_deposit_rate
+    } else {
+        Uint256::zero()
+    };
+    let distribution_buffer = interest_buffer * config.buffer_distribution_factor;
+
+    // When there was not enough deposits happens,
+    // distribute interest to market contract
+    let (mut distributed_interest, carried_interest) = compute_capped_distribution(
+        &read_distribution_cap(deps.storage)?,
+        missing_deposits,
+        read_carried_interest(deps.storage)?,
+        distribution_buffer,
+    );
+    interest_buffer = interest_buffer - distributed_interest;
+
+    if !distributed_interest.is_zero() {
+        // deduct tax
+        distributed_interest = Uint256::from(
+            deduct_tax(
+                deps,
+                Coin {
+                    denom: config.stable_denom.to_string(),
                     amount: distributed_interest.into(),
-                }],
-            }));
-        }
-    }
+                },
+            )?
+            .amount,
+        );
 
-    // Execute DistributeRewards
-    let whitelist: Vec<WhitelistResponseElem> = read_whitelist(deps.as_ref(), None, None)?;
-    for elem in whitelist.iter() {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: elem.custody_contract.clone(),
-            funds: vec![],
-            msg: to_binary(&CustodyExecuteMsg::DistributeRewards {})?,
+        // Send some portion of interest buffer to Market contract
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+            amount: vec![Coin {
+                denom: config.stable_denom.to_string(),
+                amount: distributed_interest.into(),
+            }],
         }));
     }
 
-    // TODO: Should this become a reply? If so which SubMsg to make reply_on?
-    // Execute store epoch state operation
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: env.contract.address.to_string(),
-        funds: vec![],
-        msg: to_binary(&ExecuteMsg::UpdateEpochState {
-            interest_buffer,
-            distributed_interest,
-        })?,
-    }));
+    Ok((
+        messages,
+        interest_buffer,
+        distributed_interest,
+        anc_purchase_amount,
+        carried_interest,
+    ))
+}
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "epoch_operations"),
-        attr("deposit_rate", deposit_rate.to_string()),
-        attr("exchange_rate", epoch_state.exchange_rate.to_string()),
-        attr("aterra_supply", epoch_state.aterra_supply),
-        attr("distributed_interest", distributed_interest),
-        attr("anc_purchase_amount", anc_purchase_amount),
-    ]))
+/// DistributeRewards messages for the next batch of custodies after
+/// `start_after`, with the last collateral of the batch when more are left
+pub(crate) fn distribute_rewards_messages(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+) -> StdResult<(Vec<CosmosMsg>, Option<CanonicalAddr>)> {
+    let mut whitelist: Vec<WhitelistResponseElem> =
+        read_whitelist(deps, start_after, Some(EPOCH_REWARDS_BATCH + 1))?;
+
+    let rewards_cursor = if whitelist.len() > EPOCH_REWARDS_BATCH as usize {
+        whitelist.truncate(EPOCH_REWARDS_BATCH as usize);
+        Some(
+            deps.api
+                .addr_canonicalize(&whitelist[EPOCH_REWARDS_BATCH as usize - 1].collateral_token)?,
+        )
+    } else {
+        None
+    };
+
+    let messages = whitelist
+        .iter()
+        .map(|elem| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: elem.custody_contract.clone(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {})?,
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok((messages, rewards_cursor))
 }
 
 pub fn update_epoch_state(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     // To store interest buffer before receiving epoch staking rewards,
@@ -422,7 +1211,7 @@ pub fn update_epoch_state(
     interest_buffer: Uint256,
     distributed_interest: Uint256,
 ) -> Result<Response, ContractError> {
-    let config: Config = read_config(deps.storage)?;
+    let mut config: Config = read_config(deps.storage)?;
     let overseer_epoch_state: EpochState = read_epoch_state(deps.storage)?;
     if info.sender != env.contract.address {
         return Err(ContractError::Unauthorized {});
@@ -458,8 +1247,34 @@ pub fn update_epoch_state(
             deposit_rate,
         },
     )?;
+    store_prev_distributed_interest(deps.storage, &distributed_interest)?;
+    record_epoch_snapshot(
+        deps.branch(),
+        &env,
+        &config,
+        deposit_rate,
+        interest_buffer,
+        distributed_interest,
+    )?;
 
-    Ok(Response::new()
+    // Let the controller move the subsidized rate according to
+    // the projected yield reserve runway
+    let dynamic_rate_config = read_dynamic_rate_config(deps.storage)?;
+    if let Some(dynamic_rate_config) = dynamic_rate_config.as_ref() {
+        let target_deposit_rate = compute_dynamic_deposit_rate(
+            dynamic_rate_config,
+            config.target_deposit_rate,
+            interest_buffer,
+            distributed_interest,
+        );
+
+        config.target_deposit_rate = target_deposit_rate;
+        store_config(deps.storage, &config)?;
+    }
+
+    let alert_messages: Vec<SubMsg> = epoch_risk_alert_messages(deps.branch(), &env)?;
+
+    let response = Response::new()
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: market_contract.to_string(),
             funds: vec![],
@@ -470,6 +1285,7 @@ pub fn update_epoch_state(
                 distributed_interest,
             })?,
         }))
+        .add_submessages(alert_messages)
         .add_attributes(vec![
             attr("action", "update_epoch_state"),
             attr("deposit_rate", deposit_rate.to_string()),
@@ -479,11 +1295,20 @@ pub fn update_epoch_state(
                 market_epoch_state.exchange_rate.to_string(),
             ),
             attr("interest_buffer", interest_buffer),
-        ]))
+        ]);
+
+    if dynamic_rate_config.is_some() {
+        return Ok(response.add_attribute(
+            "target_deposit_rate",
+            config.target_deposit_rate.to_string(),
+        ));
+    }
+
+    Ok(response)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::EpochState {} => to_binary(&query_state(deps)?),
@@ -501,6 +1326,11 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             deps,
             deps.api.addr_validate(&borrower)?,
         )?),
+        QueryMsg::CollateralsDetailed { borrower } => to_binary(&query_collaterals_detailed(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
         QueryMsg::AllCollaterals { start_after, limit } => to_binary(&query_all_collaterals(
             deps,
             optional_addr_validate(deps.api, start_after)?,
@@ -511,9 +1341,145 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             block_time,
         } => to_binary(&query_borrow_limit(
             deps,
+            env,
             deps.api.addr_validate(&borrower)?,
             block_time,
         )?),
+        QueryMsg::BorrowLimits {
+            borrowers,
+            block_time,
+        } => to_binary(&query_borrow_limits(
+            deps,
+            env,
+            borrowers
+                .iter()
+                .map(|borrower| deps.api.addr_validate(borrower))
+                .collect::<StdResult<Vec<Addr>>>()?,
+            block_time,
+        )?),
+        QueryMsg::FreezeState {} => to_binary(&query_freeze_state(deps)?),
+        QueryMsg::EpochProgress {} => to_binary(&query_epoch_progress(deps)?),
+        QueryMsg::WhitelistProposals { start_after, limit } => {
+            to_binary(&query_whitelist_proposals(deps, start_after, limit)?)
+        }
+        QueryMsg::DynamicRateConfig {} => to_binary(&query_dynamic_rate_config(deps)?),
+        QueryMsg::DistributionCap {} => to_binary(&query_distribution_cap(deps)?),
+        QueryMsg::ProjectedRunway {} => to_binary(&query_projected_runway(deps, env)?),
+        QueryMsg::Category { name } => to_binary(&query_category(deps, name)?),
+        QueryMsg::CategoryShareLimit { name } => {
+            to_binary(&query_category_share_limit(deps, name)?)
+        }
+        QueryMsg::BorrowerCategory { borrower } => to_binary(&query_borrower_category(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::RiskyPositions {
+            start_after,
+            limit,
+            min_risk_ratio,
+        } => to_binary(&query_risky_positions(
+            deps,
+            env,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+            min_risk_ratio,
+        )?),
+        QueryMsg::MassSimulate { price_shock } => {
+            to_binary(&query_mass_simulate(deps, env, price_shock)?)
+        }
+        QueryMsg::DebtCeiling { collateral_token } => to_binary(&query_debt_ceiling(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::AutoDeleverage { borrower } => to_binary(&query_auto_deleverage(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::Operator { borrower, operator } => to_binary(&query_operator(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            deps.api.addr_validate(&operator)?,
+        )?),
+        QueryMsg::MaxPriceAge { collateral_token } => to_binary(&query_max_price_age(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::OracleQuorum { collateral_token } => to_binary(&query_oracle_quorum(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::PriceKind { collateral_token } => to_binary(&query_price_kind(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::ConfidenceThreshold { collateral_token } => to_binary(
+            &query_confidence_threshold(deps, deps.api.addr_validate(&collateral_token)?)?,
+        ),
+        QueryMsg::LiquidationThrottle { collateral_token } => to_binary(
+            &query_liquidation_throttle(deps, deps.api.addr_validate(&collateral_token)?)?,
+        ),
+        QueryMsg::ExclusiveLiquidation {} => to_binary(&query_exclusive_liquidation(deps)?),
+        QueryMsg::LiquidationWindow { borrower } => to_binary(&query_liquidation_window(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::GradualLiquidation {} => to_binary(&query_gradual_liquidation(deps)?),
+        QueryMsg::LiquidationPlan { borrower } => to_binary(&query_liquidation_plan(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::SeizureOrder {} => to_binary(&query_seizure_order(deps)?),
+        QueryMsg::SeizurePreference { borrower } => to_binary(&query_seizure_preference(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::CollateralOracle { collateral_token } => to_binary(&query_collateral_oracle(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::UnlockBuffer { collateral_token } => to_binary(&query_unlock_buffer(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::MinLockDuration { collateral_token } => to_binary(&query_min_lock_duration(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::CustodyMigration { collateral_token } => to_binary(&query_custody_migration(
+            deps,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
+        QueryMsg::CreditLine { borrower } => to_binary(&query_credit_line(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::RiskNotification { borrower } => to_binary(&query_risk_notification(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::RiskWatches { watcher } => to_binary(&query_risk_watches(
+            deps,
+            deps.api.addr_validate(&watcher)?,
+        )?),
+        QueryMsg::SimulateLiquidation { borrower } => to_binary(&query_simulate_liquidation(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::ValidateIntent { borrower, actions } => to_binary(&query_validate_intent(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            actions,
+        )?),
+        QueryMsg::EpochHistory { start_after, limit } => {
+            to_binary(&query_epoch_history(deps, start_after, limit)?)
+        }
+        QueryMsg::WhitelistStatus { collateral_token } => to_binary(&query_whitelist_status(
+            deps,
+            env,
+            deps.api.addr_validate(&collateral_token)?,
+        )?),
     }
 }
 
@@ -579,3 +1545,87 @@ pub fn query_whitelist(
         Ok(WhitelistResponse { elems: whitelist })
     }
 }
+
+pub fn query_min_lock_duration(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<MinLockDurationResponse> {
+    let min_lock_duration = read_min_lock_duration(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(MinLockDurationResponse {
+        collateral_token: collateral_token.to_string(),
+        min_lock_duration,
+    })
+}
+
+pub fn query_collateral_oracle(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<CollateralOracleResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let oracle_contract = read_collateral_oracle(
+        deps.storage,
+        &config,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(CollateralOracleResponse {
+        collateral_token: collateral_token.to_string(),
+        oracle_contract: deps.api.addr_humanize(&oracle_contract)?.to_string(),
+    })
+}
+
+pub fn query_unlock_buffer(deps: Deps, collateral_token: Addr) -> StdResult<UnlockBufferResponse> {
+    let unlock_buffer = read_unlock_buffer(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(UnlockBufferResponse {
+        collateral_token: collateral_token.to_string(),
+        unlock_buffer: unlock_buffer.unwrap_or_else(Decimal256::zero),
+    })
+}
+
+pub fn query_max_price_age(deps: Deps, collateral_token: Addr) -> StdResult<MaxPriceAgeResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let max_price_age = read_max_price_age(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(MaxPriceAgeResponse {
+        collateral_token: collateral_token.to_string(),
+        max_price_age: max_price_age.unwrap_or(config.price_timeframe),
+    })
+}
+
+pub fn query_price_kind(deps: Deps, collateral_token: Addr) -> StdResult<PriceKindResponse> {
+    let price_kind = read_price_kind(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(PriceKindResponse {
+        collateral_token: collateral_token.to_string(),
+        price_kind: price_kind.unwrap_or(PriceKind::Spot),
+    })
+}
+
+pub fn query_confidence_threshold(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<ConfidenceThresholdResponse> {
+    let confidence_threshold = read_confidence_threshold(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(ConfidenceThresholdResponse {
+        collateral_token: collateral_token.to_string(),
+        confidence_threshold,
+    })
+}
@@ -0,0 +1,78 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, Addr, Attribute, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage,
+};
+
+use crate::error::ContractError;
+use crate::state::{
+    bump_borrow_limit_cache_version, read_config, read_credit_line, store_credit_line, Config,
+};
+
+use moneymarket::overseer::{CreditLine, CreditLineResponse};
+
+pub fn update_credit_line(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    credit_line: Option<CreditLine>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(CreditLine::LtvMultiplier(multiplier)) = &credit_line {
+        if multiplier.is_zero() {
+            return Err(ContractError::InvalidCreditLine {});
+        }
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    store_credit_line(deps.storage, &borrower_raw, credit_line.clone())?;
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_credit_line"),
+        attr("borrower", borrower),
+        attr(
+            "credit_line",
+            credit_line.map(|c| c.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// LTV of a collateral for the borrower once its LTV multiplier applies
+pub(crate) fn apply_ltv_multiplier(
+    credit_line: &Option<CreditLine>,
+    max_ltv: Decimal256,
+) -> Decimal256 {
+    match credit_line {
+        Some(CreditLine::LtvMultiplier(multiplier)) => {
+            std::cmp::min(max_ltv * *multiplier, Decimal256::one())
+        }
+        _ => max_ltv,
+    }
+}
+
+/// Logs the credit line taken into account by an operation on the borrower
+pub(crate) fn credit_line_attributes(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Vec<Attribute>> {
+    Ok(match read_credit_line(storage, borrower)? {
+        Some(credit_line) => vec![attr("credit_line", credit_line.to_string())],
+        None => vec![],
+    })
+}
+
+pub fn query_credit_line(deps: Deps, borrower: Addr) -> StdResult<CreditLineResponse> {
+    let credit_line: Option<CreditLine> = read_credit_line(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+
+    Ok(CreditLineResponse {
+        borrower: borrower.to_string(),
+        credit_line,
+    })
+}
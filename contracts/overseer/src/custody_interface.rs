@@ -0,0 +1,40 @@
+use cosmwasm_std::{Addr, Deps};
+
+use crate::error::ContractError;
+use crate::querier::query_custody_interface;
+
+use moneymarket::custody::{CustodyCapability, CUSTODY_INTERFACE_VERSION};
+
+/// Operations the overseer drives every custody contract with;
+/// collateral migration is only needed for replaced custodies
+const REQUIRED_CAPABILITIES: [CustodyCapability; 4] = [
+    CustodyCapability::LockCollateral,
+    CustodyCapability::UnlockCollateral,
+    CustodyCapability::LiquidateCollateral,
+    CustodyCapability::DistributeRewards,
+];
+
+/// Handshake with a custody contract before it is whitelisted, so
+/// custody implementations from other teams are only accepted when
+/// they speak the interface version the overseer was built against
+pub(crate) fn assert_custody_interface(
+    deps: Deps,
+    custody_contract: Addr,
+) -> Result<(), ContractError> {
+    let interface = query_custody_interface(deps, custody_contract)?;
+    if interface.version != CUSTODY_INTERFACE_VERSION {
+        return Err(ContractError::UnsupportedCustodyInterface(
+            interface.version,
+        ));
+    }
+
+    for capability in REQUIRED_CAPABILITIES.iter() {
+        if !interface.capabilities.contains(capability) {
+            return Err(ContractError::MissingCustodyCapability(
+                capability.to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
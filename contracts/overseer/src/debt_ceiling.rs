@@ -0,0 +1,183 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
+};
+
+use crate::collateral::{compute_borrow_limit_cached, read_max_ltvs};
+use crate::error::ContractError;
+use crate::querier::query_borrower_info;
+use crate::risk_notification::risk_notification_messages;
+use crate::state::{
+    read_borrower_debt, read_collateral_debt, read_collaterals, read_config, read_debt_ceiling,
+    read_whitelist_elem, store_borrower_debt, store_collateral_debt, store_debt_ceiling, Config,
+};
+
+use crate::watchlist::collateral_value;
+
+use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::overseer::DebtCeilingResponse;
+use moneymarket::tokens::Tokens;
+
+pub fn update_debt_ceiling(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    debt_ceiling: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_debt_ceiling(deps.storage, &collateral_token_raw, debt_ceiling)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_debt_ceiling"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "debt_ceiling",
+            debt_ceiling.map(|c| c.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Re-allocates the current loan of the borrower over its collaterals.
+/// The market invokes this after every borrow, in which case a collateral
+/// going over its debt ceiling fails the whole borrow; anyone else can
+/// call it to refresh stale allocations after repayments.
+pub fn sync_borrower_debt(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+    let enforce_ceiling = info.sender == market;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit_cached(deps.branch(), &env, &borrower_raw, &collaterals)?;
+    let borrow_amount_res: BorrowerInfoResponse =
+        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
+
+    let notification_messages: Vec<SubMsg> = risk_notification_messages(
+        deps.branch(),
+        &borrower_raw,
+        borrow_amount_res.loan_amount,
+        collateral_value(&collaterals, &collateral_prices),
+    )?;
+
+    allocate_borrower_debt(
+        deps,
+        &env,
+        &borrower_raw,
+        &collaterals,
+        &collateral_prices,
+        borrow_limit,
+        borrow_amount_res.loan_amount,
+        enforce_ceiling,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(notification_messages)
+        .add_attributes(vec![
+            attr("action", "sync_borrower_debt"),
+            attr("borrower", borrower),
+            attr("loan_amount", borrow_amount_res.loan_amount),
+        ]))
+}
+
+/// Splits the loan pro-rata by the borrow limit contribution
+/// of each collateral and stores it as the borrower allocation
+#[allow(clippy::ptr_arg, clippy::too_many_arguments)]
+pub(crate) fn allocate_borrower_debt(
+    deps: DepsMut,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+    borrow_limit: Uint256,
+    loan_amount: Uint256,
+    enforce_ceiling: bool,
+) -> Result<(), ContractError> {
+    let max_ltvs: Vec<Decimal256> =
+        read_max_ltvs(deps.as_ref(), env, borrower, collaterals, collateral_prices)?;
+
+    let mut borrower_debt: Tokens = vec![];
+    if !borrow_limit.is_zero() {
+        for ((collateral, price), max_ltv) in
+            collaterals.iter().zip(collateral_prices).zip(max_ltvs)
+        {
+            let contribution = collateral.1 * *price * max_ltv;
+            let debt = loan_amount.multiply_ratio(contribution, borrow_limit);
+            if !debt.is_zero() {
+                borrower_debt.push((collateral.0.clone(), debt));
+            }
+        }
+    }
+
+    // move the aggregate debts from the previous allocation to the new one,
+    // checking every ceiling before anything is stored
+    let prev_borrower_debt: Tokens = read_borrower_debt(deps.storage, borrower)?;
+    let mut collateral_debts: Vec<(CanonicalAddr, Uint256)> = vec![];
+    for (collateral_token, debt) in prev_borrower_debt.iter() {
+        let collateral_debt = read_collateral_debt(deps.storage, collateral_token)?;
+        collateral_debts.push((
+            collateral_token.clone(),
+            collateral_debt - std::cmp::min(*debt, collateral_debt),
+        ));
+    }
+
+    for (collateral_token, debt) in borrower_debt.iter() {
+        let prev_debt = prev_borrower_debt
+            .iter()
+            .find(|d| &d.0 == collateral_token)
+            .map(|d| d.1)
+            .unwrap_or_default();
+        let collateral_debt = match collateral_debts
+            .iter_mut()
+            .find(|d| &d.0 == collateral_token)
+        {
+            Some(collateral_debt) => {
+                collateral_debt.1 += *debt;
+                collateral_debt.1
+            }
+            None => {
+                let collateral_debt = read_collateral_debt(deps.storage, collateral_token)? + *debt;
+                collateral_debts.push((collateral_token.clone(), collateral_debt));
+                collateral_debt
+            }
+        };
+
+        // only a growing allocation can hit the ceiling,
+        // so positions above it can still be reduced
+        if enforce_ceiling && *debt > prev_debt {
+            if let Some(debt_ceiling) = read_debt_ceiling(deps.storage, collateral_token)? {
+                if collateral_debt > debt_ceiling {
+                    return Err(ContractError::DebtCeilingExceeded(debt_ceiling.into()));
+                }
+            }
+        }
+    }
+
+    for (collateral_token, collateral_debt) in collateral_debts.iter() {
+        store_collateral_debt(deps.storage, collateral_token, collateral_debt)?;
+    }
+    store_borrower_debt(deps.storage, borrower, &borrower_debt)?;
+
+    Ok(())
+}
+
+pub fn query_debt_ceiling(deps: Deps, collateral_token: Addr) -> StdResult<DebtCeilingResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+
+    Ok(DebtCeilingResponse {
+        collateral_token: collateral_token.to_string(),
+        debt_ceiling: read_debt_ceiling(deps.storage, &collateral_token_raw)?,
+        collateral_debt: read_collateral_debt(deps.storage, &collateral_token_raw)?,
+    })
+}
@@ -0,0 +1,195 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+};
+
+use crate::collateral::{compute_borrow_limit_cached, read_max_ltvs};
+use crate::credit_line::credit_line_attributes;
+use crate::error::ContractError;
+use crate::freeze::assert_not_frozen;
+use crate::migration::read_custody_contract;
+use crate::querier::query_borrower_info;
+use crate::state::{
+    read_auto_deleverage, read_collaterals, read_config, store_auto_deleverage, store_collaterals,
+    AutoDeleverage, Config,
+};
+
+use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::overseer::AutoDeleverageResponse;
+use moneymarket::querier::query_balance;
+use moneymarket::tokens::{Tokens, TokensMath};
+
+/// Opt the sender into automatic deleveraging,
+/// or out of it when both ratios are `None`.
+pub fn set_auto_deleverage(
+    deps: DepsMut,
+    info: MessageInfo,
+    trigger_ratio: Option<Decimal256>,
+    target_ratio: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let setting = match (trigger_ratio, target_ratio) {
+        (Some(trigger_ratio), Some(target_ratio)) => {
+            if target_ratio >= trigger_ratio || trigger_ratio >= Decimal256::one() {
+                return Err(ContractError::InvalidAutoDeleverage {});
+            }
+
+            Some(AutoDeleverage {
+                trigger_ratio,
+                target_ratio,
+            })
+        }
+        (None, None) => None,
+        _ => return Err(ContractError::InvalidAutoDeleverage {}),
+    };
+
+    store_auto_deleverage(deps.storage, &borrower_raw, setting.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_auto_deleverage"),
+        attr("borrower", info.sender),
+        attr(
+            "trigger_ratio",
+            setting
+                .as_ref()
+                .map(|s| s.trigger_ratio.to_string())
+                .unwrap_or_default(),
+        ),
+        attr(
+            "target_ratio",
+            setting
+                .map(|s| s.target_ratio.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Sells the smallest slice of the borrower collaterals that brings the
+/// loan back to the target ratio of the borrow limit. The slice goes through
+/// the liquidation contract like a regular liquidation, so the sender
+/// receives the liquidator fee as the service fee for triggering it.
+pub fn deleverage(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    assert_not_frozen(deps.storage)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let setting: AutoDeleverage = match read_auto_deleverage(deps.storage, &borrower_raw)? {
+        Some(setting) => setting,
+        None => return Err(ContractError::AutoDeleverageDisabled {}),
+    };
+
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit_cached(deps.branch(), &env, &borrower_raw, &cur_collaterals)?;
+    let max_ltvs: Vec<Decimal256> = read_max_ltvs(
+        deps.as_ref(),
+        &env,
+        &borrower_raw,
+        &cur_collaterals,
+        &collateral_prices,
+    )?;
+    let borrow_amount_res: BorrowerInfoResponse = query_borrower_info(
+        deps.as_ref(),
+        market.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?;
+    let borrow_amount = borrow_amount_res.loan_amount;
+
+    if borrow_amount > borrow_limit {
+        return Err(ContractError::CannotDeleverageUnsafeLoan {});
+    }
+
+    if borrow_amount.is_zero() || borrow_amount < borrow_limit * setting.trigger_ratio {
+        return Err(ContractError::DeleverageNotTriggered {});
+    }
+
+    // Selling `value` of a collateral repays `value` of the loan and
+    // lowers the borrow limit by `value * ltv`, so reaching the target needs
+    // value = (loan - target * limit) / (1 - target * ltv)
+    let mut left_loan = borrow_amount;
+    let mut left_limit = borrow_limit;
+    let mut deleverage_amount: Tokens = vec![];
+    for ((collateral, price), max_ltv) in
+        cur_collaterals.iter().zip(collateral_prices).zip(max_ltvs)
+    {
+        let target_loan = left_limit * setting.target_ratio;
+        if left_loan <= target_loan || price.is_zero() {
+            continue;
+        }
+
+        let required_value =
+            (left_loan - target_loan) / (Decimal256::one() - setting.target_ratio * max_ltv);
+        let amount = std::cmp::min(required_value / price, collateral.1);
+        if amount.is_zero() {
+            continue;
+        }
+
+        let sold_value = amount * price;
+        left_loan = left_loan - std::cmp::min(sold_value, left_loan);
+        left_limit = left_limit - std::cmp::min(sold_value * max_ltv, left_limit);
+        deleverage_amount.push((collateral.0.clone(), amount));
+    }
+
+    cur_collaterals.sub(deleverage_amount.clone())?;
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+
+    let prev_balance: Uint256 = query_balance(deps.as_ref(), market.clone(), config.stable_denom)?;
+
+    let deleverage_messages: Vec<CosmosMsg> = deleverage_amount
+        .iter()
+        .map(|collateral| {
+            let custody_contract =
+                read_custody_contract(deps.storage, &collateral.0, &borrower_raw)?;
+
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&custody_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: info.sender.to_string(),
+                    borrower: borrower.to_string(),
+                    amount: collateral.1,
+                    max_spread: None,
+                })?,
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new()
+        .add_messages(deleverage_messages)
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market.to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                borrower: borrower.to_string(),
+                prev_balance,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "deleverage"),
+            attr("borrower", borrower),
+            attr("keeper", info.sender),
+        ])
+        .add_attributes(credit_line_attributes(deps.storage, &borrower_raw)?))
+}
+
+pub fn query_auto_deleverage(deps: Deps, borrower: Addr) -> StdResult<AutoDeleverageResponse> {
+    let setting: Option<AutoDeleverage> = read_auto_deleverage(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+
+    Ok(AutoDeleverageResponse {
+        borrower: borrower.to_string(),
+        trigger_ratio: setting.as_ref().map(|s| s.trigger_ratio),
+        target_ratio: setting.map(|s| s.target_ratio),
+    })
+}
@@ -0,0 +1,88 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdResult};
+
+use crate::error::ContractError;
+use crate::state::{
+    read_carried_interest, read_config, read_distribution_cap, store_distribution_cap, Config,
+    DistributionCap,
+};
+
+use moneymarket::overseer::DistributionCapResponse;
+
+pub fn update_distribution_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_distribution: Option<Uint256>,
+    smoothing_window: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if smoothing_window == 0 {
+        return Err(ContractError::InvalidSmoothingWindow {});
+    }
+
+    store_distribution_cap(
+        deps.storage,
+        &DistributionCap {
+            max_distribution,
+            smoothing_window,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_distribution_cap"),
+        attr(
+            "max_distribution",
+            max_distribution.map(|m| m.to_string()).unwrap_or_default(),
+        ),
+        attr("smoothing_window", smoothing_window.to_string()),
+    ]))
+}
+
+/// Returns the interest to distribute this epoch and the interest
+/// carried over to the next one. Without a cap the missing deposits are
+/// distributed at once; otherwise the missing deposits and the carried
+/// interest are spread over the smoothing window and capped, where only
+/// the amount held back by the cap or the window is carried over.
+pub(crate) fn compute_capped_distribution(
+    distribution_cap: &Option<DistributionCap>,
+    missing_deposits: Uint256,
+    carried_interest: Uint256,
+    distribution_buffer: Uint256,
+) -> (Uint256, Uint256) {
+    let distribution_cap = match distribution_cap {
+        Some(distribution_cap) => distribution_cap,
+        None => {
+            return (
+                std::cmp::min(missing_deposits, distribution_buffer),
+                Uint256::zero(),
+            )
+        }
+    };
+
+    let distributable = std::cmp::min(missing_deposits + carried_interest, distribution_buffer);
+    let mut distribution =
+        distributable.multiply_ratio(1u64, std::cmp::max(distribution_cap.smoothing_window, 1));
+    if let Some(max_distribution) = distribution_cap.max_distribution {
+        distribution = std::cmp::min(distribution, max_distribution);
+    }
+
+    (distribution, distributable - distribution)
+}
+
+pub fn query_distribution_cap(deps: Deps) -> StdResult<DistributionCapResponse> {
+    let distribution_cap: Option<DistributionCap> = read_distribution_cap(deps.storage)?;
+
+    Ok(DistributionCapResponse {
+        max_distribution: distribution_cap
+            .as_ref()
+            .and_then(|cap| cap.max_distribution),
+        smoothing_window: distribution_cap
+            .map(|cap| cap.smoothing_window)
+            .unwrap_or(1),
+        carried_interest: read_carried_interest(deps.storage)?,
+    })
+}
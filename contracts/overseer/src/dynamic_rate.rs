@@ -0,0 +1,110 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+
+use crate::error::ContractError;
+use crate::listing::query_interest_buffer;
+use crate::state::{
+    read_config, read_dynamic_rate_config, read_prev_distributed_interest,
+    store_dynamic_rate_config, Config, DynamicRateConfig,
+};
+
+use moneymarket::overseer::{DynamicRateConfigResponse, ProjectedRunwayResponse};
+
+pub fn update_dynamic_rate_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    min_deposit_rate: Decimal256,
+    max_deposit_rate: Decimal256,
+    target_runway: u64,
+    max_change: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if min_deposit_rate > max_deposit_rate {
+        return Err(ContractError::InvalidDynamicRateBounds {});
+    }
+
+    store_dynamic_rate_config(
+        deps.storage,
+        &DynamicRateConfig {
+            min_deposit_rate,
+            max_deposit_rate,
+            target_runway,
+            max_change,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_dynamic_rate_config")]))
+}
+
+/// Scale the current deposit rate by the ratio of the projected runway
+/// (interest_buffer / distributed_interest) to the target runway, bounded
+/// by the per epoch max_change and the governance min/max deposit rates.
+pub(crate) fn compute_dynamic_deposit_rate(
+    dynamic_rate_config: &DynamicRateConfig,
+    current_rate: Decimal256,
+    interest_buffer: Uint256,
+    distributed_interest: Uint256,
+) -> Decimal256 {
+    let upper = current_rate + dynamic_rate_config.max_change;
+    let lower = if current_rate > dynamic_rate_config.max_change {
+        current_rate - dynamic_rate_config.max_change
+    } else {
+        Decimal256::zero()
+    };
+
+    // nothing was spent from the buffer, so the runway is unbounded
+    let next_rate = if distributed_interest.is_zero() || dynamic_rate_config.target_runway == 0 {
+        upper
+    } else {
+        let runway_ratio = Decimal256::from_ratio(
+            interest_buffer,
+            distributed_interest * Uint256::from(dynamic_rate_config.target_runway),
+        );
+
+        std::cmp::max(lower, std::cmp::min(upper, current_rate * runway_ratio))
+    };
+
+    std::cmp::max(
+        dynamic_rate_config.min_deposit_rate,
+        std::cmp::min(dynamic_rate_config.max_deposit_rate, next_rate),
+    )
+}
+
+pub fn query_dynamic_rate_config(deps: Deps) -> StdResult<Option<DynamicRateConfigResponse>> {
+    Ok(
+        read_dynamic_rate_config(deps.storage)?.map(|dynamic_rate_config| {
+            DynamicRateConfigResponse {
+                min_deposit_rate: dynamic_rate_config.min_deposit_rate,
+                max_deposit_rate: dynamic_rate_config.max_deposit_rate,
+                target_runway: dynamic_rate_config.target_runway,
+                max_change: dynamic_rate_config.max_change,
+            }
+        }),
+    )
+}
+
+pub fn query_projected_runway(deps: Deps, env: Env) -> StdResult<ProjectedRunwayResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let interest_buffer = query_interest_buffer(deps, &env, &config)?;
+    let distributed_interest = read_prev_distributed_interest(deps.storage)?;
+
+    let runway = if distributed_interest.is_zero() {
+        None
+    } else {
+        Some(Decimal256::from_ratio(
+            interest_buffer,
+            distributed_interest,
+        ))
+    };
+
+    Ok(ProjectedRunwayResponse {
+        interest_buffer,
+        distributed_interest,
+        runway,
+        target_deposit_rate: config.target_deposit_rate,
+    })
+}
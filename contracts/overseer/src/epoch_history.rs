@@ -0,0 +1,62 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Deps, DepsMut, Env, StdResult};
+
+use crate::collateral::query_collateral_price;
+use crate::querier::query_market_state;
+use crate::state::{
+    read_epoch_history, read_total_collaterals, store_epoch_snapshot, Config, EpochSnapshot,
+};
+
+use moneymarket::market::StateResponse;
+use moneymarket::overseer::EpochHistoryResponse;
+
+/// Appends the snapshot of the epoch executed at the current height.
+/// Collaterals are valued at the latest oracle price regardless of its age,
+/// so a stale feed cannot block the epoch operations.
+pub(crate) fn record_epoch_snapshot(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    deposit_rate: Decimal256,
+    interest_buffer: Uint256,
+    distributed_interest: Uint256,
+) -> StdResult<()> {
+    let mut total_collateral_value = Uint256::zero();
+    for (collateral_token, amount) in read_total_collaterals(deps.storage)? {
+        if amount.is_zero() {
+            continue;
+        }
+
+        let price = query_collateral_price(deps.as_ref(), config, &collateral_token, None)?;
+        total_collateral_value += amount * price;
+    }
+
+    let market_state: StateResponse = query_market_state(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.market_contract)?,
+        env.block.height,
+    )?;
+
+    store_epoch_snapshot(
+        deps.storage,
+        env.block.height,
+        &EpochSnapshot {
+            time: env.block.time.seconds(),
+            deposit_rate,
+            interest_buffer,
+            distributed_interest,
+            total_collateral_value,
+            total_liabilities: market_state.total_liabilities,
+        },
+    )
+}
+
+pub fn query_epoch_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EpochHistoryResponse> {
+    Ok(EpochHistoryResponse {
+        snapshots: read_epoch_history(deps.storage, start_after, limit)?,
+    })
+}
@@ -15,15 +15,172 @@ pub enum ContractError {
     #[error("Cannot liquidate safely collateralized loan")]
     CannotLiquidateSafeLoan {},
 
+    #[error("Collaterals of the borrower are being auctioned")]
+    AuctionInProgress {},
+
     #[error("An epoch has not passed yet; last executed height: {0}")]
     EpochNotPassed(u64),
 
+    #[error("Epoch operations are already in progress")]
+    EpochInProgress {},
+
     #[error("Token is already registered as collateral")]
     TokenAlreadyRegistered {},
 
     #[error("Unlock amount cannot exceed locked amount")]
     UnlockExceedsLocked {},
 
+    #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
+    BorrowExceedsLimit(u128),
+
+    #[error("Collateral cannot be unlocked before height: {0}")]
+    MinLockDurationNotPassed(u64),
+
     #[error("Unlock amount too high; Loan liability becomes greater than borrow limit: {0}")]
     UnlockTooLarge(u128),
+
+    #[error("Protocol is frozen")]
+    ProtocolFrozen {},
+
+    #[error("Protocol is not frozen")]
+    ProtocolNotFrozen {},
+
+    #[error("Unfreeze delay has not passed yet; can unfreeze after: {0}")]
+    UnfreezeDelayNotPassed(u64),
+
+    #[error("Collateral is not part of the borrower category")]
+    CollateralNotInCategory {},
+
+    #[error("Category change too risky; Loan liability becomes greater than borrow limit: {0}")]
+    CategoryChangeTooLarge(u128),
+
+    #[error("Invalid collateral share; must be bigger than zero and at most one")]
+    InvalidCollateralShare {},
+
+    #[error("Minimum deposit rate cannot be bigger than maximum deposit rate")]
+    InvalidDynamicRateBounds {},
+
+    #[error("Whitelist proposals are not enabled")]
+    WhitelistProposalsDisabled {},
+
+    #[error("Proposal bond is too small; required bond: {0}")]
+    InsufficientProposalBond(u128),
+
+    #[error("Invalid reply ID")]
+    InvalidReplyId {},
+
+    #[error("Target ratio must be smaller than trigger ratio and trigger ratio smaller than 1")]
+    InvalidAutoDeleverage {},
+
+    #[error("Auto deleveraging is not enabled for the borrower")]
+    AutoDeleverageDisabled {},
+
+    #[error("Loan is below the auto deleveraging trigger")]
+    DeleverageNotTriggered {},
+
+    #[error("Loan is above the borrow limit; liquidate instead")]
+    CannotDeleverageUnsafeLoan {},
+
+    #[error("Collateral does not accept new locks")]
+    CollateralNotActive {},
+
+    #[error("Invalid whitelist status transition")]
+    InvalidWhitelistStatusTransition {},
+
+    #[error("LTV ramp has not finished yet; can delist after: {0}")]
+    LtvRampNotFinished(u64),
+
+    #[error("No whitelisted collateral matches the custody migration")]
+    CustodyMigrationNotFound {},
+
+    #[error("Debt ceiling of the collateral exceeded; debt ceiling: {0}")]
+    DebtCeilingExceeded(u128),
+
+    #[error("Price of {0} is too old; last updated: {1}")]
+    PriceTooOld(String, u64),
+
+    #[error("Unlock buffer must be less than one")]
+    InvalidUnlockBuffer {},
+
+    #[error("Oracle quorum must be between one and the # of distinct oracles, at most {0}")]
+    InvalidOracleQuorum(u32),
+
+    #[error("No oracle price available for {0}")]
+    NoOraclePrice(String),
+
+    #[error("Credit line LTV multiplier must be greater than zero")]
+    InvalidCreditLine {},
+
+    #[error("Risk watcher cannot watch more than {0} borrowers")]
+    TooManyRiskWatches(u32),
+
+    #[error("LTV threshold must be greater than zero")]
+    InvalidLtvThreshold {},
+
+    #[error("LTV bands must be ascending, greater than zero and at most {0}")]
+    InvalidLtvBands(u32),
+
+    #[error("Distribution smoothing window must be at least one epoch")]
+    InvalidSmoothingWindow {},
+
+    #[error("Custody interface version {0} is not supported")]
+    UnsupportedCustodyInterface(u32),
+
+    #[error("Custody does not implement {0}")]
+    MissingCustodyCapability(String),
+
+    #[error("Operator is not permitted to {0}")]
+    OperatorNotPermitted(String),
+
+    #[error("Exclusive liquidation period must be at least one block")]
+    InvalidExclusiveLiquidation {},
+
+    #[error("Only whitelisted liquidators can liquidate the borrower until block {0}")]
+    LiquidatorNotWhitelisted(u64),
+
+    #[error("Gradual liquidations need at least one tranche and one block between tranches")]
+    InvalidGradualLiquidation {},
+
+    #[error("The next liquidation tranche of the borrower starts at block {0}")]
+    LiquidationTrancheNotDue(u64),
+
+    #[error("Seizure preferences list whitelisted collaterals once")]
+    InvalidSeizurePreference {},
+
+    #[error("Self-liquidation amount cannot exceed locked amount")]
+    SelfLiquidationExceedsLocked {},
+
+    #[error("The borrower has no loan to repay with a self-liquidation")]
+    SelfLiquidationWithoutLoan {},
+
+    #[error("Price shock must be lower than one")]
+    InvalidPriceShock {},
+
+    #[error("Whitelist proposal {0} does not exist")]
+    WhitelistProposalNotFound(u64),
+
+    #[error("Token is not registered as collateral")]
+    TokenNotRegistered {},
+
+    #[error("Category is not registered")]
+    CategoryNotRegistered {},
+
+    #[error("Cannot query more than {0} borrowers at once")]
+    TooManyBorrowers(usize),
+
+    #[error("Custody instantiation failed: {0}")]
+    CustodyInstantiationFailed(String),
+
+    #[error("Missing instantiate response data")]
+    MissingInstantiateData {},
+}
+
+// Queries only return standard errors
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
 }
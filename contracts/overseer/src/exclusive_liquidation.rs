@@ -0,0 +1,139 @@
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_exclusive_liquidation, read_liquidatable_since, store_exclusive_liquidation,
+    store_liquidatable_since, Config, ExclusiveLiquidationInfo,
+};
+
+use moneymarket::overseer::{
+    ExclusiveLiquidation, ExclusiveLiquidationResponse, LiquidationWindowResponse,
+};
+
+pub fn update_exclusive_liquidation(
+    deps: DepsMut,
+    info: MessageInfo,
+    exclusive_liquidation: Option<ExclusiveLiquidation>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let exclusive_liquidation_info = match exclusive_liquidation.as_ref() {
+        Some(exclusive_liquidation) => {
+            if exclusive_liquidation.exclusive_blocks == 0 {
+                return Err(ContractError::InvalidExclusiveLiquidation {});
+            }
+
+            let mut liquidators = vec![];
+            for liquidator in exclusive_liquidation.liquidators.iter() {
+                let liquidator_raw = deps
+                    .api
+                    .addr_canonicalize(deps.api.addr_validate(liquidator)?.as_str())?;
+                if !liquidators.contains(&liquidator_raw) {
+                    liquidators.push(liquidator_raw);
+                }
+            }
+
+            Some(ExclusiveLiquidationInfo {
+                liquidators,
+                exclusive_blocks: exclusive_liquidation.exclusive_blocks,
+            })
+        }
+        None => None,
+    };
+    store_exclusive_liquidation(deps.storage, exclusive_liquidation_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_exclusive_liquidation"),
+        attr(
+            "exclusive_blocks",
+            exclusive_liquidation
+                .map(|e| e.exclusive_blocks.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Liquidators outside of the whitelist cannot liquidate the borrower until
+/// `exclusive_blocks` after the position was found liquidatable. The first
+/// of their attempts starts the window, returning the height it opens at
+pub(crate) fn assert_liquidator(
+    deps: DepsMut,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    liquidator: &Addr,
+) -> Result<Option<u64>, ContractError> {
+    let exclusive_liquidation = match read_exclusive_liquidation(deps.storage)? {
+        Some(exclusive_liquidation) => exclusive_liquidation,
+        None => return Ok(None),
+    };
+
+    let liquidator_raw = deps.api.addr_canonicalize(liquidator.as_str())?;
+    if exclusive_liquidation.liquidators.contains(&liquidator_raw) {
+        return Ok(None);
+    }
+
+    match read_liquidatable_since(deps.storage, borrower)? {
+        Some(since) => {
+            let open_height = since + exclusive_liquidation.exclusive_blocks;
+            if env.block.height < open_height {
+                return Err(ContractError::LiquidatorNotWhitelisted(open_height));
+            }
+
+            Ok(None)
+        }
+        None => {
+            store_liquidatable_since(deps.storage, borrower, Some(env.block.height))?;
+            Ok(Some(
+                env.block.height + exclusive_liquidation.exclusive_blocks,
+            ))
+        }
+    }
+}
+
+pub fn query_exclusive_liquidation(deps: Deps) -> StdResult<ExclusiveLiquidationResponse> {
+    let exclusive_liquidation = match read_exclusive_liquidation(deps.storage)? {
+        Some(exclusive_liquidation) => Some(ExclusiveLiquidation {
+            liquidators: exclusive_liquidation
+                .liquidators
+                .iter()
+                .map(|liquidator| Ok(deps.api.addr_humanize(liquidator)?.to_string()))
+                .collect::<StdResult<Vec<String>>>()?,
+            exclusive_blocks: exclusive_liquidation.exclusive_blocks,
+        }),
+        None => None,
+    };
+
+    Ok(ExclusiveLiquidationResponse {
+        exclusive_liquidation,
+    })
+}
+
+pub fn query_liquidation_window(
+    deps: Deps,
+    borrower: Addr,
+) -> StdResult<LiquidationWindowResponse> {
+    let liquidatable_since = read_liquidatable_since(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+    let open_height = match (
+        read_exclusive_liquidation(deps.storage)?,
+        liquidatable_since,
+    ) {
+        (Some(exclusive_liquidation), Some(since)) => {
+            Some(since + exclusive_liquidation.exclusive_blocks)
+        }
+        _ => None,
+    };
+
+    Ok(LiquidationWindowResponse {
+        borrower: borrower.to_string(),
+        liquidatable_since,
+        open_height,
+    })
+}
@@ -0,0 +1,119 @@
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+
+use crate::error::ContractError;
+use crate::state::{read_config, read_freeze_state, store_freeze_state, Config, FreezeState};
+
+use moneymarket::overseer::FreezeStateResponse;
+
+pub fn update_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<Addr>,
+    unfreeze_delay: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut freeze_state: FreezeState = read_freeze_state(deps.storage)?;
+    if let Some(guardian) = guardian {
+        freeze_state.guardian = Some(deps.api.addr_canonicalize(guardian.as_str())?);
+    }
+
+    if let Some(unfreeze_delay) = unfreeze_delay {
+        // the delay of an ongoing freeze cannot be shortened
+        if freeze_state.frozen_at.is_some() {
+            return Err(ContractError::ProtocolFrozen {});
+        }
+
+        freeze_state.unfreeze_delay = unfreeze_delay;
+    }
+
+    store_freeze_state(deps.storage, &freeze_state)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_guardian")]))
+}
+
+pub fn freeze_protocol(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut freeze_state: FreezeState = read_freeze_state(deps.storage)?;
+    assert_guardian_or_owner(deps.as_ref(), &info, &freeze_state)?;
+
+    if freeze_state.frozen_at.is_some() {
+        return Err(ContractError::ProtocolFrozen {});
+    }
+
+    freeze_state.frozen_at = Some(env.block.time.seconds());
+    store_freeze_state(deps.storage, &freeze_state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "freeze_protocol"),
+        attr("frozen_at", env.block.time.seconds().to_string()),
+    ]))
+}
+
+pub fn unfreeze_protocol(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut freeze_state: FreezeState = read_freeze_state(deps.storage)?;
+    assert_guardian_or_owner(deps.as_ref(), &info, &freeze_state)?;
+
+    let frozen_at = match freeze_state.frozen_at {
+        Some(v) => v,
+        None => return Err(ContractError::ProtocolNotFrozen {}),
+    };
+
+    // the protocol must stay frozen for at least `unfreeze_delay` seconds
+    let unfreeze_available_at = frozen_at + freeze_state.unfreeze_delay;
+    if env.block.time.seconds() < unfreeze_available_at {
+        return Err(ContractError::UnfreezeDelayNotPassed(unfreeze_available_at));
+    }
+
+    freeze_state.frozen_at = None;
+    store_freeze_state(deps.storage, &freeze_state)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "unfreeze_protocol")]))
+}
+
+/// Returns an error when the protocol is frozen. Every path that
+/// moves collateral in or out of the custody contracts must call this.
+pub fn assert_not_frozen(storage: &dyn Storage) -> Result<(), ContractError> {
+    if read_freeze_state(storage)?.frozen_at.is_some() {
+        return Err(ContractError::ProtocolFrozen {});
+    }
+
+    Ok(())
+}
+
+fn assert_guardian_or_owner(
+    deps: Deps,
+    info: &MessageInfo,
+    freeze_state: &FreezeState,
+) -> Result<(), ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && Some(sender_raw) != freeze_state.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+pub fn query_freeze_state(deps: Deps) -> StdResult<FreezeStateResponse> {
+    let freeze_state: FreezeState = read_freeze_state(deps.storage)?;
+    Ok(FreezeStateResponse {
+        guardian: freeze_state
+            .guardian
+            .map(|guardian| deps.api.addr_humanize(&guardian))
+            .transpose()?
+            .map(|guardian| guardian.to_string()),
+        unfreeze_delay: freeze_state.unfreeze_delay,
+        frozen: freeze_state.frozen_at.is_some(),
+        frozen_at: freeze_state.frozen_at,
+    })
+}
@@ -0,0 +1,139 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_gradual_liquidation, read_liquidation_plan, store_gradual_liquidation,
+    store_liquidation_plan, Config, LiquidationPlan,
+};
+
+use moneymarket::overseer::{
+    GradualLiquidation, GradualLiquidationResponse, LiquidationPlanResponse,
+};
+use moneymarket::tokens::Tokens;
+
+pub fn update_gradual_liquidation(
+    deps: DepsMut,
+    info: MessageInfo,
+    gradual_liquidation: Option<GradualLiquidation>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_gradual_liquidation")];
+    if let Some(gradual_liquidation) = gradual_liquidation.as_ref() {
+        if gradual_liquidation.tranches == 0 || gradual_liquidation.tranche_interval == 0 {
+            return Err(ContractError::InvalidGradualLiquidation {});
+        }
+
+        attributes.extend(vec![
+            attr("size_threshold", gradual_liquidation.size_threshold),
+            attr("tranches", gradual_liquidation.tranches.to_string()),
+            attr(
+                "tranche_interval",
+                gradual_liquidation.tranche_interval.to_string(),
+            ),
+        ]);
+    }
+    store_gradual_liquidation(deps.storage, gradual_liquidation)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Cut the liquidation amounts of positions above the size threshold down
+/// to the next tranche of their liquidation plan. Each tranche liquidates an
+/// equal share of what is left to liquidate, so the tranches follow the
+/// position as it changes. A plan not continued within a tranche interval of
+/// its next tranche is dropped, and a new one starts over
+#[allow(clippy::ptr_arg)]
+pub(crate) fn slice_liquidation(
+    deps: DepsMut,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+    liquidation_amount: Tokens,
+) -> Result<Tokens, ContractError> {
+    let gradual_liquidation = match read_gradual_liquidation(deps.storage)? {
+        Some(gradual_liquidation) => gradual_liquidation,
+        None => return Ok(liquidation_amount),
+    };
+
+    let liquidation_plan = read_liquidation_plan(deps.storage, borrower)?
+        .filter(|plan| env.block.height < plan.next_height + gradual_liquidation.tranche_interval);
+    let remaining_tranches = match liquidation_plan {
+        Some(plan) => {
+            if env.block.height < plan.next_height {
+                return Err(ContractError::LiquidationTrancheNotDue(plan.next_height));
+            }
+
+            plan.remaining_tranches
+        }
+        None => {
+            let liquidation_value = liquidation_amount
+                .iter()
+                .map(|(collateral_token, amount)| {
+                    let price = collaterals
+                        .iter()
+                        .zip(collateral_prices)
+                        .find(|(collateral, _)| collateral.0 == *collateral_token)
+                        .map(|(_, price)| *price)
+                        .unwrap_or_default();
+                    *amount * price
+                })
+                .fold(Uint256::zero(), |sum, value| sum + value);
+            if liquidation_value <= gradual_liquidation.size_threshold {
+                store_liquidation_plan(deps.storage, borrower, None)?;
+                return Ok(liquidation_amount);
+            }
+
+            gradual_liquidation.tranches
+        }
+    };
+
+    let liquidation_plan = if remaining_tranches > 1 {
+        Some(LiquidationPlan {
+            remaining_tranches: remaining_tranches - 1,
+            next_height: env.block.height + gradual_liquidation.tranche_interval,
+        })
+    } else {
+        None
+    };
+    store_liquidation_plan(deps.storage, borrower, liquidation_plan)?;
+
+    Ok(liquidation_amount
+        .into_iter()
+        .map(|(collateral_token, amount)| {
+            (
+                collateral_token,
+                amount.multiply_ratio(1u64, remaining_tranches as u64),
+            )
+        })
+        .filter(|(_, amount)| !amount.is_zero())
+        .collect())
+}
+
+pub fn query_gradual_liquidation(deps: Deps) -> StdResult<GradualLiquidationResponse> {
+    Ok(GradualLiquidationResponse {
+        gradual_liquidation: read_gradual_liquidation(deps.storage)?,
+    })
+}
+
+pub fn query_liquidation_plan(deps: Deps, borrower: Addr) -> StdResult<LiquidationPlanResponse> {
+    let liquidation_plan = read_liquidation_plan(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+
+    Ok(LiquidationPlanResponse {
+        borrower: borrower.to_string(),
+        remaining_tranches: liquidation_plan
+            .as_ref()
+            .map(|plan| plan.remaining_tranches),
+        next_height: liquidation_plan.map(|plan| plan.next_height),
+    })
+}
@@ -0,0 +1,144 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{Addr, CanonicalAddr, Deps, Env, StdResult};
+
+use crate::collateral::{
+    assert_lock_duration_passed, assert_lockable, compute_borrow_limit, unlock_borrow_limit,
+};
+use crate::error::ContractError;
+use crate::querier::query_borrower_info;
+use crate::state::{read_collaterals, read_config, read_min_lock_duration, Config};
+use crate::watchlist::{collateral_value, position_ltv};
+
+use moneymarket::overseer::{Intent, ValidateIntentResponse};
+use moneymarket::tokens::{Tokens, TokensMath, TokensToHuman, TokensToRaw};
+
+/// Borrower position as the simulated actions leave it
+struct Position {
+    collaterals: Tokens,
+    loan_amount: Uint256,
+    // collaterals locked by the simulated actions
+    locked: Vec<CanonicalAddr>,
+}
+
+pub fn query_validate_intent(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    actions: Vec<Intent>,
+) -> StdResult<ValidateIntentResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let loan_amount = query_borrower_info(
+        deps,
+        deps.api.addr_humanize(&config.market_contract)?,
+        borrower,
+        env.block.height,
+    )?
+    .loan_amount;
+
+    let mut position = Position {
+        collaterals: read_collaterals(deps.storage, &borrower_raw),
+        loan_amount,
+        locked: vec![],
+    };
+
+    let mut failure: Option<(u32, ContractError)> = None;
+    for (index, action) in actions.into_iter().enumerate() {
+        match simulate_action(deps, &env, &borrower_raw, &position, action) {
+            Ok(next_position) => position = next_position,
+            Err(err) => {
+                failure = Some((index as u32, err));
+                break;
+            }
+        }
+    }
+
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit(deps, &env, &borrower_raw, &position.collaterals, None)?;
+    let collateral_value = collateral_value(&position.collaterals, &collateral_prices);
+    let ltv = position_ltv(position.loan_amount, collateral_value);
+
+    Ok(ValidateIntentResponse {
+        valid: failure.is_none(),
+        failed_action: failure.as_ref().map(|f| f.0),
+        error: failure.map(|f| f.1.to_string()),
+        collaterals: position.collaterals.to_human(deps)?,
+        loan_amount: position.loan_amount,
+        borrow_limit,
+        ltv,
+    })
+}
+
+/// Applies the same checks as the execution of the action
+fn simulate_action(
+    deps: Deps,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    position: &Position,
+    action: Intent,
+) -> Result<Position, ContractError> {
+    let mut collaterals = position.collaterals.clone();
+    let mut loan_amount = position.loan_amount;
+    let mut locked = position.locked.clone();
+    let block_time = Some(env.block.time.seconds());
+
+    match action {
+        Intent::LockCollateral { collaterals: lock } => {
+            let lock: Tokens = lock.to_raw(deps)?;
+            assert_lockable(deps, env, borrower, &lock)?;
+
+            for collateral in lock.iter() {
+                if !locked.contains(&collateral.0) {
+                    locked.push(collateral.0.clone());
+                }
+            }
+            collaterals.add(lock);
+        }
+        Intent::UnlockCollateral {
+            collaterals: unlock,
+        } => {
+            let unlock: Tokens = unlock.to_raw(deps)?;
+            if collaterals.sub(unlock.clone()).is_err() {
+                return Err(ContractError::UnlockExceedsLocked {});
+            }
+
+            assert_lock_duration_passed(deps, env, borrower, &unlock)?;
+            for collateral in unlock.iter().filter(|c| locked.contains(&c.0)) {
+                if let Some(min_lock_duration) =
+                    read_min_lock_duration(deps.storage, &collateral.0)?
+                {
+                    if min_lock_duration > 0 {
+                        return Err(ContractError::MinLockDurationNotPassed(
+                            env.block.height + min_lock_duration,
+                        ));
+                    }
+                }
+            }
+
+            let (borrow_limit, _) =
+                compute_borrow_limit(deps, env, borrower, &collaterals, block_time)?;
+            let unlock_limit = unlock_borrow_limit(deps, &unlock, borrow_limit)?;
+            if unlock_limit < loan_amount {
+                return Err(ContractError::UnlockTooLarge(unlock_limit.into()));
+            }
+        }
+        Intent::BorrowStable { amount } => {
+            let (borrow_limit, _) =
+                compute_borrow_limit(deps, env, borrower, &collaterals, block_time)?;
+            if borrow_limit < loan_amount + amount {
+                return Err(ContractError::BorrowExceedsLimit(borrow_limit.into()));
+            }
+
+            loan_amount += amount;
+        }
+        Intent::RepayStable { amount } => {
+            loan_amount = loan_amount - std::cmp::min(amount, loan_amount);
+        }
+    }
+
+    Ok(Position {
+        collaterals,
+        loan_amount,
+        locked,
+    })
+}
@@ -0,0 +1,196 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Attribute, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
+    Response, StdResult, WasmMsg,
+};
+
+use crate::contract::{
+    compute_deposit_rate, distribute_interest_buffer, distribute_rewards_messages,
+};
+use crate::error::ContractError;
+use crate::listing::query_interest_buffer;
+use crate::state::{
+    read_config, read_epoch_cursor, read_epoch_progress, read_epoch_state, read_keeper_reward,
+    remove_epoch_progress, store_carried_interest, store_epoch_progress, store_keeper_reward,
+    Config, EpochProgress, EpochState,
+};
+
+use moneymarket::overseer::{EpochProgressResponse, EpochStep, ExecuteMsg};
+use moneymarket::querier::deduct_tax;
+
+pub fn update_keeper_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    keeper_reward: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_keeper_reward(deps.storage, &keeper_reward)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_keeper_reward"),
+        attr("keeper_reward", keeper_reward),
+    ]))
+}
+
+/// Run the next step of the epoch operations. Each step can run
+/// only once per epoch, so the epoch is processed over several
+/// transactions instead of a single oversized one.
+pub fn execute_epoch_step(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let state: EpochState = read_epoch_state(deps.storage)?;
+
+    // epoch is being processed by resumed epoch operations
+    if read_epoch_cursor(deps.storage)?.is_some() {
+        return Err(ContractError::EpochInProgress {});
+    }
+
+    let mut progress: EpochProgress = match read_epoch_progress(deps.storage)? {
+        Some(progress) => progress,
+        None => {
+            if env.block.height < state.last_executed_height + config.epoch_period {
+                return Err(ContractError::EpochNotPassed(state.last_executed_height));
+            }
+
+            EpochProgress {
+                next_step: EpochStep::ComputeDepositRate,
+                deposit_rate: Decimal256::zero(),
+                interest_buffer: Uint256::zero(),
+                distributed_interest: Uint256::zero(),
+                keeper_rewards: Uint256::zero(),
+                rewards_cursor: None,
+            }
+        }
+    };
+
+    let step = progress.next_step.clone();
+    let keeper_reward = read_keeper_reward(deps.storage)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes: Vec<Attribute> = vec![
+        attr("action", "execute_epoch_step"),
+        attr("step", step.to_string()),
+    ];
+
+    let reward: Uint256 = match step {
+        EpochStep::ComputeDepositRate => {
+            let (deposit_rate, _) = compute_deposit_rate(deps.as_ref(), &env, &config, &state)?;
+            attributes.push(attr("deposit_rate", deposit_rate.to_string()));
+
+            let interest_buffer = query_interest_buffer(deps.as_ref(), &env, &config)?;
+            let reward = std::cmp::min(keeper_reward, interest_buffer);
+
+            progress.deposit_rate = deposit_rate;
+            progress.keeper_rewards += reward;
+            progress.next_step = EpochStep::DistributeInterestBuffer;
+            reward
+        }
+        EpochStep::DistributeInterestBuffer => {
+            // add back the keeper rewards already paid in this epoch,
+            // so they are not mistaken for a decrease of the accrued buffer
+            let interest_buffer =
+                query_interest_buffer(deps.as_ref(), &env, &config)? + progress.keeper_rewards;
+
+            let (
+                mut distribute_messages,
+                interest_buffer,
+                distributed_interest,
+                anc_purchase_amount,
+                carried_interest,
+            ) = distribute_interest_buffer(
+                deps.as_ref(),
+                &env,
+                &config,
+                &state,
+                progress.deposit_rate,
+                interest_buffer,
+            )?;
+            store_carried_interest(deps.storage, &carried_interest)?;
+            messages.append(&mut distribute_messages);
+            attributes.push(attr("distributed_interest", distributed_interest));
+            attributes.push(attr("anc_purchase_amount", anc_purchase_amount));
+
+            let interest_buffer = if interest_buffer > progress.keeper_rewards {
+                interest_buffer - progress.keeper_rewards
+            } else {
+                Uint256::zero()
+            };
+            let reward = std::cmp::min(keeper_reward, interest_buffer);
+
+            progress.interest_buffer = interest_buffer - reward;
+            progress.distributed_interest = distributed_interest;
+            progress.next_step = EpochStep::DistributeRewards;
+            reward
+        }
+        EpochStep::DistributeRewards => {
+            // stay on this step until every custody distributed its rewards
+            let (mut rewards_messages, rewards_cursor) =
+                distribute_rewards_messages(deps.as_ref(), progress.rewards_cursor.take())?;
+            messages.append(&mut rewards_messages);
+
+            let reward = std::cmp::min(keeper_reward, progress.interest_buffer);
+            progress.interest_buffer = progress.interest_buffer - reward;
+            if rewards_cursor.is_none() {
+                progress.next_step = EpochStep::UpdateEpochState;
+            }
+            progress.rewards_cursor = rewards_cursor;
+            reward
+        }
+        EpochStep::UpdateEpochState => {
+            let reward = std::cmp::min(keeper_reward, progress.interest_buffer);
+            progress.interest_buffer = progress.interest_buffer - reward;
+
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                    interest_buffer: progress.interest_buffer,
+                    distributed_interest: progress.distributed_interest,
+                })?,
+            }));
+            reward
+        }
+    };
+
+    if step == EpochStep::UpdateEpochState {
+        remove_epoch_progress(deps.storage);
+    } else {
+        store_epoch_progress(deps.storage, &progress)?;
+    }
+
+    if !reward.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: reward.into(),
+                },
+            )?],
+        }));
+    }
+
+    attributes.push(attr("keeper_reward", reward));
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+pub fn query_epoch_progress(deps: Deps) -> StdResult<EpochProgressResponse> {
+    let next_step = match read_epoch_progress(deps.storage)? {
+        Some(progress) => progress.next_step,
+        None => EpochStep::ComputeDepositRate,
+    };
+
+    Ok(EpochProgressResponse {
+        next_step,
+        keeper_reward: read_keeper_reward(deps.storage)?,
+    })
+}
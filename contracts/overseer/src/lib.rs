@@ -1,8 +1,32 @@
+pub mod category;
 pub mod collateral;
 pub mod contract;
+pub mod credit_line;
+pub mod custody_interface;
+pub mod debt_ceiling;
+pub mod deleverage;
+pub mod distribution_cap;
+pub mod dynamic_rate;
+pub mod epoch_history;
 pub mod error;
+pub mod exclusive_liquidation;
+pub mod freeze;
+pub mod gradual_liquidation;
+pub mod intent;
+pub mod keeper;
+pub mod liquidation_throttle;
+pub mod listing;
+pub mod mass_simulation;
+pub mod migration;
+pub mod operator;
+pub mod oracle_quorum;
 pub mod querier;
+pub mod risk_notification;
+pub mod seizure;
+pub mod self_liquidation;
 pub mod state;
+pub mod watchlist;
+pub mod whitelist_status;
 
 #[cfg(test)]
 mod testing;
@@ -0,0 +1,143 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Addr, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdResult};
+
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_epoch_state, read_liquidation_cap, read_liquidation_queue,
+    read_liquidation_throttle, read_whitelist_elem, store_liquidation_cap, store_liquidation_queue,
+    store_liquidation_throttle, Config, LiquidationThrottle,
+};
+
+use moneymarket::overseer::LiquidationThrottleResponse;
+use moneymarket::tokens::Tokens;
+
+pub fn update_liquidation_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    liquidation_cap: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    store_liquidation_cap(deps.storage, &collateral_token_raw, liquidation_cap)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_liquidation_cap"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "liquidation_cap",
+            liquidation_cap.map(|c| c.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Move the throttle to the given epoch; the borrowers queued so far
+/// get priority, the ones left from before are dropped
+fn roll_liquidation_throttle(throttle: &mut LiquidationThrottle, epoch: u64) {
+    if throttle.epoch != epoch {
+        throttle.priority_epoch = throttle.epoch;
+        throttle.priority = throttle.queued;
+        throttle.queued = 0;
+        throttle.liquidated_value = Uint256::zero();
+        throttle.epoch = epoch;
+    }
+}
+
+/// Cut the liquidation amounts down to what is left of the per epoch
+/// liquidation cap of each collateral. Borrowers cut short are queued and
+/// get priority in the next epoch, during which the other borrowers
+/// cannot be liquidated for the collateral until the queue is served.
+#[allow(clippy::ptr_arg)]
+pub(crate) fn throttle_liquidation(
+    deps: DepsMut,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+    liquidation_amount: Tokens,
+) -> StdResult<Tokens> {
+    let epoch = read_epoch_state(deps.storage)?.last_executed_height;
+
+    let mut throttled_amount: Tokens = vec![];
+    for (collateral_token, amount) in liquidation_amount.into_iter() {
+        let liquidation_cap = match read_liquidation_cap(deps.storage, &collateral_token)? {
+            Some(liquidation_cap) => liquidation_cap,
+            None => {
+                throttled_amount.push((collateral_token, amount));
+                continue;
+            }
+        };
+
+        let mut throttle = read_liquidation_throttle(deps.storage, &collateral_token)?;
+        roll_liquidation_throttle(&mut throttle, epoch);
+
+        let mut queued_epoch = read_liquidation_queue(deps.storage, &collateral_token, borrower)?;
+        let has_priority = throttle.priority > 0 && queued_epoch == Some(throttle.priority_epoch);
+        if has_priority {
+            throttle.priority -= 1;
+            queued_epoch = None;
+        }
+
+        let remaining = if throttle.priority > 0 && !has_priority {
+            Uint256::zero()
+        } else if throttle.liquidated_value < liquidation_cap {
+            liquidation_cap - throttle.liquidated_value
+        } else {
+            Uint256::zero()
+        };
+
+        let price = collaterals
+            .iter()
+            .zip(collateral_prices)
+            .find(|(collateral, _)| collateral.0 == collateral_token)
+            .map(|(_, price)| *price)
+            .unwrap_or_default();
+        let value = amount * price;
+
+        let amount = if value > remaining {
+            // wait for the next epoch to liquidate the rest
+            if queued_epoch != Some(epoch) {
+                queued_epoch = Some(epoch);
+                throttle.queued += 1;
+            }
+
+            throttle.liquidated_value += remaining;
+            amount.multiply_ratio(remaining, value)
+        } else {
+            throttle.liquidated_value += value;
+            amount
+        };
+
+        store_liquidation_queue(deps.storage, &collateral_token, borrower, queued_epoch)?;
+        store_liquidation_throttle(deps.storage, &collateral_token, &throttle)?;
+        if !amount.is_zero() {
+            throttled_amount.push((collateral_token, amount));
+        }
+    }
+
+    Ok(throttled_amount)
+}
+
+pub fn query_liquidation_throttle(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<LiquidationThrottleResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let mut throttle = read_liquidation_throttle(deps.storage, &collateral_token_raw)?;
+    roll_liquidation_throttle(
+        &mut throttle,
+        read_epoch_state(deps.storage)?.last_executed_height,
+    );
+
+    Ok(LiquidationThrottleResponse {
+        collateral_token: collateral_token.to_string(),
+        liquidation_cap: read_liquidation_cap(deps.storage, &collateral_token_raw)?,
+        liquidated_value: throttle.liquidated_value,
+        queued_borrowers: throttle.queued,
+        priority_borrowers: throttle.priority,
+    })
+}
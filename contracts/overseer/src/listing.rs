@@ -0,0 +1,263 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, SubMsg, WasmMsg,
+};
+
+use crate::custody_interface::assert_custody_interface;
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_pending_proposal, read_proposal_bond, read_total_proposal_bonds,
+    read_whitelist_elem, read_whitelist_proposal, read_whitelist_proposals,
+    remove_whitelist_proposal, store_pending_proposal, store_proposal_bond,
+    store_total_proposal_bonds, store_whitelist_elem, store_whitelist_proposal, Config,
+    WhitelistElem, WhitelistProposal,
+};
+
+use moneymarket::overseer::{WhitelistProposalResponse, WhitelistProposalsResponse};
+use moneymarket::querier::{deduct_tax, query_balance};
+use moneymarket::reply::parse_instantiate_contract_address;
+
+pub const INSTANTIATE_CUSTODY_REPLY_ID: u64 = 1;
+
+/// Stable balance of the overseer, excluding the bonds of open
+/// proposals which are owed back to proposers or the collector
+pub fn query_interest_buffer(deps: Deps, env: &Env, config: &Config) -> StdResult<Uint256> {
+    let balance = query_balance(
+        deps,
+        env.contract.address.clone(),
+        config.stable_denom.to_string(),
+    )?;
+    let total_proposal_bonds = read_total_proposal_bonds(deps.storage)?;
+
+    Ok(if balance > total_proposal_bonds {
+        balance - total_proposal_bonds
+    } else {
+        Uint256::zero()
+    })
+}
+
+fn release_proposal_bond(storage: &mut dyn Storage, bond_amount: Uint256) -> StdResult<()> {
+    let total_proposal_bonds = read_total_proposal_bonds(storage)?;
+    store_total_proposal_bonds(storage, &(total_proposal_bonds - bond_amount))
+}
+
+pub fn update_proposal_bond(
+    deps: DepsMut,
+    info: MessageInfo,
+    proposal_bond: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_proposal_bond(deps.storage, &proposal_bond)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_proposal_bond"),
+        attr("proposal_bond", proposal_bond),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn propose_whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    symbol: String,
+    collateral_token: Addr,
+    custody_code_id: u64,
+    custody_init_msg: Binary,
+    max_ltv: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let proposal_bond = match read_proposal_bond(deps.storage)? {
+        Some(v) => v,
+        None => return Err(ContractError::WhitelistProposalsDisabled {}),
+    };
+
+    // Check bond amount
+    let bond_amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+    if bond_amount < proposal_bond {
+        return Err(ContractError::InsufficientProposalBond(
+            proposal_bond.into(),
+        ));
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    if read_whitelist_elem(deps.storage, &collateral_token_raw).is_ok() {
+        return Err(ContractError::TokenAlreadyRegistered {});
+    }
+
+    let total_proposal_bonds = read_total_proposal_bonds(deps.storage)?;
+    store_total_proposal_bonds(deps.storage, &(total_proposal_bonds + bond_amount))?;
+
+    let proposal_id = store_whitelist_proposal(
+        deps.storage,
+        &WhitelistProposal {
+            proposer: deps.api.addr_canonicalize(info.sender.as_str())?,
+            name,
+            symbol,
+            collateral_token: collateral_token_raw,
+            custody_code_id,
+            custody_init_msg,
+            max_ltv,
+            bond_amount,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_whitelist"),
+        attr("proposal_id", proposal_id.to_string()),
+        attr("proposer", info.sender),
+        attr("collateral_token", collateral_token),
+        attr("bond_amount", bond_amount),
+    ]))
+}
+
+/// Instantiate the proposed custody contract and return the bond
+/// to the proposer; the collateral is registered once the reply
+/// with the custody address arrives
+pub fn approve_whitelist_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let proposal: WhitelistProposal = read_whitelist_proposal(deps.storage, proposal_id)?;
+    if read_whitelist_elem(deps.storage, &proposal.collateral_token).is_ok() {
+        return Err(ContractError::TokenAlreadyRegistered {});
+    }
+
+    store_pending_proposal(deps.storage, proposal_id)?;
+    release_proposal_bond(deps.storage, proposal.bond_amount)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !proposal.bond_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: deps.api.addr_humanize(&proposal.proposer)?.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: proposal.bond_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: Some(deps.api.addr_humanize(&config.owner_addr)?.to_string()),
+                code_id: proposal.custody_code_id,
+                funds: vec![],
+                label: "".to_string(),
+                msg: proposal.custody_init_msg,
+            }),
+            INSTANTIATE_CUSTODY_REPLY_ID,
+        ))
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "approve_whitelist_proposal"),
+            attr("proposal_id", proposal_id.to_string()),
+        ]))
+}
+
+/// Drop the proposal; spam proposals lose their bond to the collector
+pub fn reject_whitelist_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+    proposal_id: u64,
+    slash: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let proposal: WhitelistProposal = read_whitelist_proposal(deps.storage, proposal_id)?;
+    remove_whitelist_proposal(deps.storage, proposal_id);
+    release_proposal_bond(deps.storage, proposal.bond_amount)?;
+
+    let recipient = if slash {
+        deps.api.addr_humanize(&config.collector_contract)?
+    } else {
+        deps.api.addr_humanize(&proposal.proposer)?
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !proposal.bond_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: proposal.bond_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "reject_whitelist_proposal"),
+        attr("proposal_id", proposal_id.to_string()),
+        attr("slashed", slash.to_string()),
+    ]))
+}
+
+/// Register the collateral of the pending proposal
+/// with the newly instantiated custody contract
+pub fn register_proposed_whitelist(deps: DepsMut, data: Binary) -> Result<Response, ContractError> {
+    let custody_contract = deps
+        .api
+        .addr_validate(&parse_instantiate_contract_address(data.as_slice())?)?;
+    assert_custody_interface(deps.as_ref(), custody_contract.clone())?;
+
+    let proposal_id = read_pending_proposal(deps.storage)?;
+    let proposal: WhitelistProposal = read_whitelist_proposal(deps.storage, proposal_id)?;
+    remove_whitelist_proposal(deps.storage, proposal_id);
+
+    store_whitelist_elem(
+        deps.storage,
+        &proposal.collateral_token,
+        &WhitelistElem {
+            name: proposal.name.to_string(),
+            symbol: proposal.symbol.to_string(),
+            custody_contract: deps.api.addr_canonicalize(custody_contract.as_str())?,
+            max_ltv: proposal.max_ltv,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_whitelist"),
+        attr("name", proposal.name),
+        attr("symbol", proposal.symbol),
+        attr(
+            "collateral_token",
+            deps.api.addr_humanize(&proposal.collateral_token)?,
+        ),
+        attr("custody_contract", custody_contract),
+        attr("LTV", proposal.max_ltv.to_string()),
+    ]))
+}
+
+pub fn query_whitelist_proposals(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<WhitelistProposalsResponse> {
+    let proposals: Vec<WhitelistProposalResponse> =
+        read_whitelist_proposals(deps, start_after, limit)?;
+    Ok(WhitelistProposalsResponse { proposals })
+}
@@ -0,0 +1,141 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{CanonicalAddr, Deps, Env, StdResult};
+
+use crate::collateral::{compute_borrow_limit_at_prices, query_collateral_price};
+use crate::error::ContractError;
+use crate::querier::{query_borrower_info, query_liquidation_amount, query_simulate_bid_execution};
+use crate::state::{read_all_collaterals, read_config, Config};
+
+use moneymarket::overseer::{CollateralsResponse, MassSimulateResponse, MassSimulatedCollateral};
+use moneymarket::tokens::{Tokens, TokensToRaw};
+
+/// Liquidations of every borrower if all the collateral prices dropped by
+/// the price shock, summed per collateral and run against the bid pools.
+/// The whole book is scanned, so it is meant for off-chain monitoring
+pub fn query_mass_simulate(
+    deps: Deps,
+    env: Env,
+    price_shock: Decimal256,
+) -> StdResult<MassSimulateResponse> {
+    if price_shock >= Decimal256::one() {
+        return Err(ContractError::InvalidPriceShock {}.into());
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+    let liquidation_contract = deps.api.addr_humanize(&config.liquidation_contract)?;
+
+    // shocked prices, queried once per collateral
+    let mut prices: Vec<(CanonicalAddr, Decimal256)> = vec![];
+    let mut liquidations: Vec<(String, Uint256)> = vec![];
+    let mut liquidatable_positions: u32 = 0;
+    let mut liquidatable_debt = Uint256::zero();
+
+    let mut start_after: Option<CanonicalAddr> = None;
+    loop {
+        let all_collaterals: Vec<CollateralsResponse> =
+            read_all_collaterals(deps, start_after.clone(), None)?;
+        if all_collaterals.is_empty() {
+            break;
+        }
+
+        for collaterals in all_collaterals.iter() {
+            let borrower = deps.api.addr_validate(&collaterals.borrower)?;
+            let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+            let collaterals_raw: Tokens = collaterals.collaterals.to_raw(deps)?;
+
+            let mut collateral_prices: Vec<Decimal256> = vec![];
+            for collateral in collaterals_raw.iter() {
+                let price = match prices.iter().find(|(token, _)| *token == collateral.0) {
+                    Some((_, price)) => *price,
+                    None => {
+                        let price = query_collateral_price(deps, &config, &collateral.0, None)?
+                            * (Decimal256::one() - price_shock);
+                        prices.push((collateral.0.clone(), price));
+                        price
+                    }
+                };
+                collateral_prices.push(price);
+            }
+
+            let borrow_limit = compute_borrow_limit_at_prices(
+                deps,
+                &env,
+                &borrower_raw,
+                &collaterals_raw,
+                &collateral_prices,
+            )?;
+            let loan_amount =
+                query_borrower_info(deps, market.clone(), borrower, env.block.height)?.loan_amount;
+            if loan_amount <= borrow_limit {
+                continue;
+            }
+
+            liquidatable_positions += 1;
+            liquidatable_debt += loan_amount;
+
+            let liquidation_amount = query_liquidation_amount(
+                deps,
+                liquidation_contract.clone(),
+                loan_amount,
+                borrow_limit,
+                &collaterals.collaterals,
+                collateral_prices,
+            )?
+            .collaterals;
+            for (collateral_token, amount) in liquidation_amount.into_iter() {
+                match liquidations
+                    .iter_mut()
+                    .find(|(token, _)| *token == collateral_token)
+                {
+                    Some((_, total)) => *total += amount,
+                    None => liquidations.push((collateral_token, amount)),
+                }
+            }
+        }
+
+        start_after = Some(
+            deps.api
+                .addr_canonicalize(&all_collaterals.last().unwrap().borrower)?,
+        );
+    }
+
+    let mut collaterals: Vec<MassSimulatedCollateral> = vec![];
+    let mut repay_amount = Uint256::zero();
+    for (collateral_token, liquidation_amount) in liquidations.into_iter() {
+        let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+        let price = prices
+            .iter()
+            .find(|(token, _)| *token == collateral_token_raw)
+            .map(|(_, price)| *price)
+            .unwrap_or_default();
+
+        let simulation = query_simulate_bid_execution(
+            deps,
+            liquidation_contract.clone(),
+            collateral_token.clone(),
+            liquidation_amount,
+            price,
+        )?;
+
+        repay_amount += simulation.repay_amount;
+        collaterals.push(MassSimulatedCollateral {
+            collateral_token,
+            price,
+            liquidation_amount,
+            absorbed_amount: simulation.collateral_amount,
+            repay_amount: simulation.repay_amount,
+        });
+    }
+
+    Ok(MassSimulateResponse {
+        price_shock,
+        liquidatable_positions,
+        liquidatable_debt,
+        repay_amount,
+        absorbed: collaterals
+            .iter()
+            .all(|collateral| collateral.absorbed_amount == collateral.liquidation_amount),
+        collaterals,
+    })
+}
@@ -0,0 +1,223 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response,
+    StdResult, Storage, WasmMsg,
+};
+
+use crate::error::ContractError;
+use crate::freeze::assert_not_frozen;
+use crate::state::{
+    read_all_collaterals, read_collateral_token_by_custody, read_collaterals, read_config,
+    read_custody_migration, read_migrated_borrower, read_whitelist_elem, store_custody_migration,
+    store_migrated_borrower, store_whitelist_elem, Config, CustodyMigration, WhitelistElem,
+};
+
+use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::overseer::{CollateralsResponse, CustodyMigrationResponse};
+
+/// Moves locked collateral from `old_custody` to `new_custody`.
+/// The first call, made by the owner, points the whitelist to the new
+/// custody; after that the owner migrates the borrowers in batches
+/// and every borrower can migrate its own collateral at any time.
+/// A batch without any borrower left completes the migration.
+pub fn migrate_collateral(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    borrower: Option<Addr>,
+    old_custody: Addr,
+    new_custody: Addr,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_not_frozen(deps.storage)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let old_custody_raw = deps.api.addr_canonicalize(old_custody.as_str())?;
+    let new_custody_raw = deps.api.addr_canonicalize(new_custody.as_str())?;
+
+    let collateral_token = match read_collateral_token_by_custody(deps.storage, &old_custody_raw)? {
+        Some(collateral_token) => collateral_token,
+        None => match read_collateral_token_by_custody(deps.storage, &new_custody_raw)? {
+            Some(collateral_token) => collateral_token,
+            None => return Err(ContractError::CustodyMigrationNotFound {}),
+        },
+    };
+
+    let mut migration: CustodyMigration =
+        match read_custody_migration(deps.storage, &collateral_token)? {
+            Some(migration) => {
+                if migration.old_custody != old_custody_raw
+                    || migration.new_custody != new_custody_raw
+                {
+                    return Err(ContractError::CustodyMigrationNotFound {});
+                }
+
+                migration
+            }
+            None => {
+                // only the owner can start a migration
+                if sender_raw != config.owner_addr {
+                    return Err(ContractError::Unauthorized {});
+                }
+
+                let mut whitelist_elem: WhitelistElem =
+                    read_whitelist_elem(deps.storage, &collateral_token)?;
+                if whitelist_elem.custody_contract != old_custody_raw
+                    || old_custody_raw == new_custody_raw
+                {
+                    return Err(ContractError::CustodyMigrationNotFound {});
+                }
+
+                whitelist_elem.custody_contract = new_custody_raw.clone();
+                store_whitelist_elem(deps.storage, &collateral_token, &whitelist_elem)?;
+
+                CustodyMigration {
+                    old_custody: old_custody_raw,
+                    new_custody: new_custody_raw,
+                    last_borrower: None,
+                }
+            }
+        };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut done = false;
+    if let Some(borrower) = borrower {
+        let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+        if sender_raw != config.owner_addr && sender_raw != borrower_raw {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        messages.append(&mut migrate_borrower_collateral(
+            deps.branch(),
+            &collateral_token,
+            &migration,
+            &borrower_raw,
+        )?);
+    } else {
+        if sender_raw != config.owner_addr {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let all_collaterals: Vec<CollateralsResponse> =
+            read_all_collaterals(deps.as_ref(), migration.last_borrower.clone(), limit)?;
+        done = all_collaterals.is_empty();
+        for collaterals in all_collaterals {
+            let borrower_raw = deps.api.addr_canonicalize(&collaterals.borrower)?;
+            messages.append(&mut migrate_borrower_collateral(
+                deps.branch(),
+                &collateral_token,
+                &migration,
+                &borrower_raw,
+            )?);
+            migration.last_borrower = Some(borrower_raw);
+        }
+    }
+
+    store_custody_migration(
+        deps.storage,
+        &collateral_token,
+        if done { None } else { Some(migration) },
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "migrate_collateral"),
+        attr("old_custody", old_custody),
+        attr("new_custody", new_custody),
+        attr("done", done.to_string()),
+    ]))
+}
+
+fn migrate_borrower_collateral(
+    deps: DepsMut,
+    collateral_token: &CanonicalAddr,
+    migration: &CustodyMigration,
+    borrower: &CanonicalAddr,
+) -> StdResult<Vec<CosmosMsg>> {
+    if read_migrated_borrower(deps.storage, &migration.new_custody, borrower)? {
+        return Ok(vec![]);
+    }
+
+    store_migrated_borrower(deps.storage, &migration.new_custody, borrower)?;
+
+    let amount: Uint256 = read_collaterals(deps.storage, borrower)
+        .iter()
+        .find(|c| &c.0 == collateral_token)
+        .map(|c| c.1)
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Ok(vec![]);
+    }
+
+    let borrower = deps.api.addr_humanize(borrower)?.to_string();
+    let new_custody = deps.api.addr_humanize(&migration.new_custody)?.to_string();
+    Ok(vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&migration.old_custody)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::MigrateCollateral {
+                borrower: borrower.clone(),
+                amount,
+                new_custody: new_custody.clone(),
+            })?,
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: new_custody,
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LockCollateral { borrower, amount })?,
+        }),
+    ])
+}
+
+/// Custody contract holding the collateral of the borrower;
+/// the replaced one while its collateral is not migrated yet
+pub(crate) fn read_custody_contract(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> StdResult<CanonicalAddr> {
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(storage, collateral_token)?;
+    if let Some(migration) = read_custody_migration(storage, collateral_token)? {
+        if !read_migrated_borrower(storage, &migration.new_custody, borrower)? {
+            return Ok(migration.old_custody);
+        }
+    }
+
+    Ok(whitelist_elem.custody_contract)
+}
+
+/// Borrowers without any locked collateral in the replaced custody
+/// contract can go straight to the new one
+pub(crate) fn skip_custody_migration(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+    locked_amount: Uint256,
+) -> StdResult<()> {
+    if let Some(migration) = read_custody_migration(storage, collateral_token)? {
+        if locked_amount.is_zero() {
+            store_migrated_borrower(storage, &migration.new_custody, borrower)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn query_custody_migration(
+    deps: Deps,
+    collateral_token: Addr,
+) -> StdResult<Option<CustodyMigrationResponse>> {
+    let migration = read_custody_migration(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    migration
+        .map(|migration| {
+            Ok(CustodyMigrationResponse {
+                collateral_token: collateral_token.to_string(),
+                old_custody: deps.api.addr_humanize(&migration.old_custody)?.to_string(),
+                new_custody: deps.api.addr_humanize(&migration.new_custody)?.to_string(),
+            })
+        })
+        .transpose()
+}
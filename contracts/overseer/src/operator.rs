@@ -0,0 +1,74 @@
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, MessageInfo, Response, StdResult};
+
+use crate::error::ContractError;
+use crate::state::{read_operator_permissions, store_operator_permissions};
+
+use moneymarket::overseer::{OperatorPermission, OperatorResponse};
+
+/// Overwrite the permissions the sender grants to the operator;
+/// no permissions removes the operator
+pub fn set_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: Addr,
+    permissions: Vec<OperatorPermission>,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let operator_raw = deps.api.addr_canonicalize(operator.as_str())?;
+
+    let mut unique_permissions: Vec<OperatorPermission> = vec![];
+    for permission in permissions {
+        if !unique_permissions.contains(&permission) {
+            unique_permissions.push(permission);
+        }
+    }
+    let permissions = unique_permissions;
+    store_operator_permissions(deps.storage, &borrower_raw, &operator_raw, &permissions)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_operator"),
+        attr("borrower", info.sender),
+        attr("operator", operator),
+        attr(
+            "permissions",
+            permissions
+                .iter()
+                .map(|permission| permission.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]))
+}
+
+/// Returns an error unless the borrower granted the permission to the operator
+pub fn assert_operator_permission(
+    deps: Deps,
+    borrower: &Addr,
+    operator: &Addr,
+    permission: OperatorPermission,
+) -> Result<(), ContractError> {
+    let permissions = read_operator_permissions(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        &deps.api.addr_canonicalize(operator.as_str())?,
+    )?;
+    if !permissions.contains(&permission) {
+        return Err(ContractError::OperatorNotPermitted(permission.to_string()));
+    }
+
+    Ok(())
+}
+
+pub fn query_operator(deps: Deps, borrower: Addr, operator: Addr) -> StdResult<OperatorResponse> {
+    let permissions = read_operator_permissions(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        &deps.api.addr_canonicalize(operator.as_str())?,
+    )?;
+
+    Ok(OperatorResponse {
+        borrower: borrower.to_string(),
+        operator: operator.to_string(),
+        permissions,
+    })
+}
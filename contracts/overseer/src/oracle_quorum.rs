@@ -0,0 +1,155 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult};
+
+use crate::collateral::collateral_rate;
+use crate::error::ContractError;
+use crate::state::{
+    bump_borrow_limit_cache_version, read_config, read_oracle_quorum, read_whitelist_elem,
+    store_oracle_quorum, Config, OracleQuorumInfo,
+};
+
+use moneymarket::oracle::{PriceKind, PriceResponse};
+use moneymarket::overseer::{OracleQuorum, OracleQuorumResponse};
+use moneymarket::querier::query_price_of_kind;
+
+// maximum number of oracles pricing a single collateral
+const MAX_QUORUM_ORACLES: u32 = 5;
+
+pub fn update_oracle_quorum(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    oracle_quorum: Option<OracleQuorum>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+
+    let oracle_quorum_info = match oracle_quorum.as_ref() {
+        Some(oracle_quorum) => {
+            let mut oracles = vec![];
+            for oracle in oracle_quorum.oracles.iter() {
+                let oracle_raw = deps
+                    .api
+                    .addr_canonicalize(deps.api.addr_validate(oracle)?.as_str())?;
+                if oracles.contains(&oracle_raw) {
+                    return Err(ContractError::InvalidOracleQuorum(MAX_QUORUM_ORACLES));
+                }
+                oracles.push(oracle_raw);
+            }
+
+            if oracles.len() > MAX_QUORUM_ORACLES as usize
+                || oracle_quorum.quorum == 0
+                || oracle_quorum.quorum as usize > oracles.len()
+            {
+                return Err(ContractError::InvalidOracleQuorum(MAX_QUORUM_ORACLES));
+            }
+
+            Some(OracleQuorumInfo {
+                oracles,
+                quorum: oracle_quorum.quorum,
+            })
+        }
+        None => None,
+    };
+    store_oracle_quorum(deps.storage, &collateral_token_raw, oracle_quorum_info)?;
+
+    // cached borrow limits were priced by the previous quorum
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_oracle_quorum"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "quorum",
+            oracle_quorum
+                .map(|q| format!("{}/{}", q.quorum, q.oracles.len()))
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Median price of the fresh feeds once the quorum is reached, the
+/// lowest price of every answering feed otherwise. Feeds failing to
+/// answer are left out; without `block_time` every answer is fresh.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn query_quorum_price(
+    deps: Deps,
+    config: &Config,
+    collateral_token: String,
+    oracle_quorum: &OracleQuorumInfo,
+    price_kind: Option<PriceKind>,
+    confidence_threshold: Option<Decimal256>,
+    max_price_age: u64,
+    block_time: Option<u64>,
+) -> Result<Decimal256, ContractError> {
+    let mut prices: Vec<Decimal256> = vec![];
+    let mut fresh_prices: Vec<Decimal256> = vec![];
+    for oracle in oracle_quorum.oracles.iter() {
+        let price: PriceResponse = match query_price_of_kind(
+            deps,
+            deps.api.addr_humanize(oracle)?,
+            collateral_token.clone(),
+            config.stable_denom.to_string(),
+            price_kind.clone(),
+            None,
+        ) {
+            Ok(price) => price,
+            Err(_) => continue,
+        };
+
+        let last_updated = std::cmp::min(price.last_updated_base, price.last_updated_quote);
+        let fresh = match block_time {
+            Some(block_time) => last_updated + max_price_age >= block_time,
+            None => true,
+        };
+
+        let rate = collateral_rate(&price, confidence_threshold);
+        prices.push(rate);
+        if fresh {
+            fresh_prices.push(rate);
+        }
+    }
+
+    if fresh_prices.len() >= oracle_quorum.quorum as usize {
+        fresh_prices.sort();
+        let mid = fresh_prices.len() / 2;
+        return Ok(if fresh_prices.len().is_multiple_of(2) {
+            (fresh_prices[mid - 1] + fresh_prices[mid]) * Decimal256::percent(50)
+        } else {
+            fresh_prices[mid]
+        });
+    }
+
+    prices
+        .into_iter()
+        .min()
+        .ok_or(ContractError::NoOraclePrice(collateral_token))
+}
+
+pub fn query_oracle_quorum(deps: Deps, collateral_token: Addr) -> StdResult<OracleQuorumResponse> {
+    let oracle_quorum = read_oracle_quorum(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+
+    Ok(OracleQuorumResponse {
+        collateral_token: collateral_token.to_string(),
+        oracle_quorum: oracle_quorum
+            .map(|oracle_quorum| {
+                Ok::<OracleQuorum, StdError>(OracleQuorum {
+                    oracles: oracle_quorum
+                        .oracles
+                        .iter()
+                        .map(|oracle| Ok(deps.api.addr_humanize(oracle)?.to_string()))
+                        .collect::<StdResult<Vec<String>>>()?,
+                    quorum: oracle_quorum.quorum,
+                })
+            })
+            .transpose()?,
+    })
+}
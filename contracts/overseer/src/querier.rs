@@ -1,8 +1,13 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
+use cosmwasm_std::{Addr, Deps, StdResult};
 
-use moneymarket::liquidation::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
-use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, QueryMsg as MarketQueryMsg};
+use moneymarket::custody::{CustodyQuerier, InterfaceVersionResponse};
+use moneymarket::liquidation::{LiquidationAmountResponse, LiquidationQuerier};
+use moneymarket::liquidation_queue::{
+    AuctionConfigResponse, AuctionsResponse, BidPoolsResponse, LiquidationQueueQuerier,
+    SimulateBidExecutionResponse,
+};
+use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, MarketQuerier, StateResponse};
 use moneymarket::tokens::TokensHuman;
 
 pub fn query_epoch_state(
@@ -11,16 +16,16 @@ pub fn query_epoch_state(
     block_height: u64,
     distributed_interest: Option<Uint256>,
 ) -> StdResult<EpochStateResponse> {
-    let epoch_state: EpochStateResponse =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: market_addr.to_string(),
-            msg: to_binary(&MarketQueryMsg::EpochState {
-                block_height: Some(block_height),
-                distributed_interest,
-            })?,
-        }))?;
+    MarketQuerier::new(deps.querier, market_addr)
+        .epoch_state(Some(block_height), distributed_interest)
+}
 
-    Ok(epoch_state)
+pub fn query_market_state(
+    deps: Deps,
+    market_addr: Addr,
+    block_height: u64,
+) -> StdResult<StateResponse> {
+    MarketQuerier::new(deps.querier, market_addr).state(Some(block_height))
 }
 
 /// Query borrow amount from the market contract
@@ -30,16 +35,8 @@ pub fn query_borrower_info(
     borrower: Addr,
     block_height: u64,
 ) -> StdResult<BorrowerInfoResponse> {
-    let borrower_amount: BorrowerInfoResponse =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: market_addr.to_string(),
-            msg: to_binary(&MarketQueryMsg::BorrowerInfo {
-                borrower: borrower.to_string(),
-                block_height: Some(block_height),
-            })?,
-        }))?;
-
-    Ok(borrower_amount)
+    MarketQuerier::new(deps.querier, market_addr)
+        .borrower_info(borrower.to_string(), Some(block_height))
 }
 
 #[allow(clippy::ptr_arg)]
@@ -51,16 +48,88 @@ pub fn query_liquidation_amount(
     collaterals: &TokensHuman,
     collateral_prices: Vec<Decimal256>,
 ) -> StdResult<LiquidationAmountResponse> {
-    let liquidation_amount_res: LiquidationAmountResponse =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: liquidation_contract.to_string(),
-            msg: to_binary(&LiquidationQueryMsg::LiquidationAmount {
-                borrow_amount,
-                borrow_limit,
-                collaterals: collaterals.clone(),
-                collateral_prices,
-            })?,
-        }))?;
+    LiquidationQuerier::new(deps.querier, liquidation_contract).liquidation_amount(
+        borrow_amount,
+        borrow_limit,
+        collaterals.clone(),
+        collateral_prices,
+    )
+}
+
+/// Liquidation amount when the collaterals are sold on the swap router
+pub fn query_swap_liquidation_amount(
+    deps: Deps,
+    liquidation_contract: Addr,
+    borrow_amount: Uint256,
+    borrow_limit: Uint256,
+    collaterals: &TokensHuman,
+    collateral_prices: Vec<Decimal256>,
+) -> StdResult<LiquidationAmountResponse> {
+    let liquidation_amount_res = LiquidationQueueQuerier::new(deps.querier, liquidation_contract)
+        .swap_liquidation_amount(
+        borrow_amount,
+        borrow_limit,
+        collaterals.clone(),
+        collateral_prices,
+    )?;
+
+    Ok(LiquidationAmountResponse {
+        collaterals: liquidation_amount_res.collaterals,
+    })
+}
+
+pub fn query_auction_config(
+    deps: Deps,
+    liquidation_contract: Addr,
+    collateral_token: Addr,
+) -> StdResult<AuctionConfigResponse> {
+    LiquidationQueueQuerier::new(deps.querier, liquidation_contract)
+        .auction_config(collateral_token.to_string())
+}
 
-    Ok(liquidation_amount_res)
+/// Active auctions of the borrower, one is enough to know if any is open
+pub fn query_borrower_auctions(
+    deps: Deps,
+    liquidation_contract: Addr,
+    borrower: Addr,
+) -> StdResult<AuctionsResponse> {
+    LiquidationQueueQuerier::new(deps.querier, liquidation_contract).auctions_by_borrower(
+        borrower.to_string(),
+        None,
+        Some(1u8),
+    )
+}
+
+/// Stable denom bid pools of the collateral, every slot fits in one page
+pub fn query_bid_pools(
+    deps: Deps,
+    liquidation_contract: Addr,
+    collateral_token: Addr,
+) -> StdResult<BidPoolsResponse> {
+    LiquidationQueueQuerier::new(deps.querier, liquidation_contract).bid_pools_by_collateral(
+        collateral_token.to_string(),
+        None,
+        Some(u8::MAX),
+    )
+}
+
+pub fn query_simulate_bid_execution(
+    deps: Deps,
+    liquidation_contract: Addr,
+    collateral_token: String,
+    amount: Uint256,
+    price: Decimal256,
+) -> StdResult<SimulateBidExecutionResponse> {
+    LiquidationQueueQuerier::new(deps.querier, liquidation_contract).simulate_bid_execution(
+        collateral_token,
+        amount,
+        price,
+    )
+}
+
+pub fn query_custody_interface(
+    deps: Deps,
+    custody_addr: Addr,
+) -> StdResult<InterfaceVersionResponse> {
+    CustodyQuerier::new(deps.querier, custody_addr).interface_version()
 }
@@ -0,0 +1,140 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, ReplyOn, Response,
+    StdResult, SubMsg, WasmMsg,
+};
+
+use crate::error::ContractError;
+use crate::state::{read_risk_notification, store_risk_notification, RiskNotification};
+use crate::watchlist::position_ltv;
+
+use moneymarket::overseer::{RiskNotificationMsg, RiskNotificationResponse};
+
+pub const RISK_NOTIFICATION_REPLY_ID: u64 = 3;
+
+// gas available to a borrower notification contract,
+// so it cannot make the operation emitting it run out of gas
+const RISK_NOTIFICATION_GAS_LIMIT: u64 = 300_000;
+
+// maximum number of LTV bands of a single borrower
+const MAX_LTV_BANDS: u32 = 10;
+
+pub fn register_risk_notification(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: Addr,
+    ltv_bands: Vec<Decimal256>,
+) -> Result<Response, ContractError> {
+    if ltv_bands.is_empty()
+        || ltv_bands.len() > MAX_LTV_BANDS as usize
+        || ltv_bands[0].is_zero()
+        || ltv_bands.windows(2).any(|bands| bands[0] >= bands[1])
+    {
+        return Err(ContractError::InvalidLtvBands(MAX_LTV_BANDS));
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    store_risk_notification(
+        deps.storage,
+        &borrower_raw,
+        Some(RiskNotification {
+            contract: deps.api.addr_canonicalize(contract.as_str())?,
+            ltv_bands,
+            band: 0,
+        }),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_risk_notification"),
+        attr("borrower", info.sender),
+        attr("contract", contract),
+    ]))
+}
+
+pub fn deregister_risk_notification(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    store_risk_notification(deps.storage, &borrower_raw, None)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deregister_risk_notification"),
+        attr("borrower", info.sender),
+    ]))
+}
+
+/// Callback to the notification contract of the borrower when its LTV
+/// moved into another band since the last check. The callback is gas
+/// capped and its failure is ignored in the reply.
+pub(crate) fn risk_notification_messages(
+    deps: DepsMut,
+    borrower: &CanonicalAddr,
+    loan_amount: Uint256,
+    collateral_value: Uint256,
+) -> StdResult<Vec<SubMsg>> {
+    let mut risk_notification: RiskNotification =
+        match read_risk_notification(deps.storage, borrower)? {
+            Some(risk_notification) => risk_notification,
+            None => return Ok(vec![]),
+        };
+
+    let ltv = position_ltv(loan_amount, collateral_value);
+    let band = risk_notification
+        .ltv_bands
+        .iter()
+        .filter(|ltv_band| **ltv_band <= ltv)
+        .count() as u32;
+    if band == risk_notification.band {
+        return Ok(vec![]);
+    }
+
+    let prev_band = risk_notification.band;
+    risk_notification.band = band;
+    store_risk_notification(deps.storage, borrower, Some(risk_notification.clone()))?;
+
+    Ok(vec![SubMsg {
+        id: RISK_NOTIFICATION_REPLY_ID,
+        msg: CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&risk_notification.contract)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&RiskNotificationMsg::LtvBandCrossed {
+                borrower: deps.api.addr_humanize(borrower)?.to_string(),
+                ltv,
+                prev_band,
+                band,
+                loan_amount,
+                collateral_value,
+            })?,
+        }),
+        gas_limit: Some(RISK_NOTIFICATION_GAS_LIMIT),
+        reply_on: ReplyOn::Error,
+    }])
+}
+
+pub fn query_risk_notification(
+    deps: Deps,
+    borrower: Addr,
+) -> StdResult<Option<RiskNotificationResponse>> {
+    let risk_notification = read_risk_notification(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+
+    risk_notification
+        .map(|risk_notification| {
+            Ok(RiskNotificationResponse {
+                borrower: borrower.to_string(),
+                contract: deps
+                    .api
+                    .addr_humanize(&risk_notification.contract)?
+                    .to_string(),
+                ltv_bands: risk_notification.ltv_bands,
+                band: risk_notification.band,
+            })
+        })
+        .transpose()
+}
@@ -0,0 +1,197 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{attr, Addr, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdResult};
+
+use crate::error::ContractError;
+use crate::querier::query_bid_pools;
+use crate::state::{
+    read_config, read_seizure_order, read_seizure_preference, read_whitelist_elem,
+    store_seizure_order, store_seizure_preference, Config,
+};
+
+use moneymarket::overseer::{SeizureOrder, SeizureOrderResponse, SeizurePreferenceResponse};
+use moneymarket::tokens::Tokens;
+
+pub fn update_seizure_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    seizure_order: Option<SeizureOrder>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let seizure_order_attr = seizure_order
+        .as_ref()
+        .map(|seizure_order| seizure_order.to_string())
+        .unwrap_or_default();
+    store_seizure_order(deps.storage, seizure_order)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_seizure_order"),
+        attr("seizure_order", seizure_order_attr),
+    ]))
+}
+
+pub fn update_seizure_preference(
+    deps: DepsMut,
+    info: MessageInfo,
+    collaterals: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    let collaterals_raw = match collaterals.as_ref() {
+        Some(collaterals) => {
+            let mut collaterals_raw: Vec<CanonicalAddr> = vec![];
+            for collateral_token in collaterals.iter() {
+                let collateral_token_raw = deps.api.addr_canonicalize(collateral_token)?;
+                if collaterals_raw.contains(&collateral_token_raw)
+                    || read_whitelist_elem(deps.storage, &collateral_token_raw).is_err()
+                {
+                    return Err(ContractError::InvalidSeizurePreference {});
+                }
+
+                collaterals_raw.push(collateral_token_raw);
+            }
+
+            Some(collaterals_raw)
+        }
+        None => None,
+    };
+    store_seizure_preference(deps.storage, &borrower_raw, collaterals_raw)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_seizure_preference"),
+        attr("borrower", info.sender),
+        attr("collaterals", collaterals.unwrap_or_default().join(",")),
+    ]))
+}
+
+/// Seize the value of the liquidation amount from the collaterals in the
+/// seizure order, each collateral being seized in full before the next one.
+/// The liquidation amount is left as it is without a seizure order
+#[allow(clippy::ptr_arg)]
+pub(crate) fn order_seizure(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+    liquidation_amount: Tokens,
+) -> Result<Tokens, ContractError> {
+    let seizure_order = match read_seizure_order(deps.storage)? {
+        Some(seizure_order) => seizure_order,
+        None => return Ok(liquidation_amount),
+    };
+    if collaterals.len() < 2 || liquidation_amount.is_empty() {
+        return Ok(liquidation_amount);
+    }
+
+    // indices of the collaterals, in the order they are seized
+    let mut order: Vec<usize> = (0..collaterals.len()).collect();
+    match seizure_order {
+        SeizureOrder::BorrowerPreference => {
+            let preference = read_seizure_preference(deps.storage, borrower)?;
+            if preference.is_empty() {
+                return Ok(liquidation_amount);
+            }
+
+            order.sort_by_key(|index| {
+                preference
+                    .iter()
+                    .position(|collateral_token| *collateral_token == collaterals[*index].0)
+                    .unwrap_or(preference.len())
+            });
+        }
+        SeizureOrder::MostLiquidFirst | SeizureOrder::HighestPremiumCapacityFirst => {
+            let config: Config = read_config(deps.storage)?;
+            let liquidation_contract = deps.api.addr_humanize(&config.liquidation_contract)?;
+
+            let mut capacities: Vec<Uint256> = vec![];
+            for collateral in collaterals.iter() {
+                let bid_pools = query_bid_pools(
+                    deps,
+                    liquidation_contract.clone(),
+                    deps.api.addr_humanize(&collateral.0)?,
+                )?
+                .bid_pools;
+                capacities.push(
+                    bid_pools
+                        .iter()
+                        .map(|bid_pool| match seizure_order {
+                            SeizureOrder::HighestPremiumCapacityFirst => {
+                                bid_pool.total_bid_amount * bid_pool.premium_rate
+                            }
+                            _ => bid_pool.total_bid_amount,
+                        })
+                        .fold(Uint256::zero(), |sum, capacity| sum + capacity),
+                );
+            }
+
+            // the sort is stable, collaterals of equal capacity keep their order
+            order.sort_by(|a, b| capacities[*b].cmp(&capacities[*a]));
+        }
+    }
+
+    let mut remaining_value = liquidation_amount
+        .iter()
+        .map(|(collateral_token, amount)| {
+            let price = collaterals
+                .iter()
+                .position(|collateral| collateral.0 == *collateral_token)
+                .map(|index| collateral_prices[index])
+                .unwrap_or_default();
+            *amount * price
+        })
+        .fold(Uint256::zero(), |sum, value| sum + value);
+
+    let mut ordered_amount: Tokens = vec![];
+    for index in order.into_iter() {
+        let (collateral_token, balance) = &collaterals[index];
+        let price = collateral_prices[index];
+        if remaining_value.is_zero() {
+            break;
+        }
+        if price.is_zero() {
+            continue;
+        }
+
+        // round up, so no dust is left to seize from the next collateral
+        let mut amount = remaining_value / price;
+        if amount * price < remaining_value {
+            amount += Uint256::one();
+        }
+        let amount = std::cmp::min(amount, *balance);
+        if amount.is_zero() {
+            continue;
+        }
+
+        remaining_value = remaining_value - std::cmp::min(remaining_value, amount * price);
+        ordered_amount.push((collateral_token.clone(), amount));
+    }
+
+    Ok(ordered_amount)
+}
+
+pub fn query_seizure_order(deps: Deps) -> StdResult<SeizureOrderResponse> {
+    Ok(SeizureOrderResponse {
+        seizure_order: read_seizure_order(deps.storage)?,
+    })
+}
+
+pub fn query_seizure_preference(
+    deps: Deps,
+    borrower: Addr,
+) -> StdResult<SeizurePreferenceResponse> {
+    let collaterals = read_seizure_preference(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+
+    Ok(SeizurePreferenceResponse {
+        borrower: borrower.to_string(),
+        collaterals: collaterals
+            .iter()
+            .map(|collateral_token| Ok(deps.api.addr_humanize(collateral_token)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+    })
+}
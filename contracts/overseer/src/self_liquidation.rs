@@ -0,0 +1,86 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{attr, to_binary, Addr, CosmosMsg, DepsMut, Env, Response, WasmMsg};
+
+use crate::credit_line::credit_line_attributes;
+use crate::error::ContractError;
+use crate::freeze::assert_not_frozen;
+use crate::migration::read_custody_contract;
+use crate::querier::query_borrower_info;
+use crate::state::{read_collaterals, read_config, store_collaterals, Config};
+
+use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::querier::query_balance;
+use moneymarket::tokens::{Tokens, TokensMath};
+
+/// The borrower sells locked collateral to the zero premium bids and the
+/// proceeds repay the loan, any amount above the loan is paid back.
+/// Unlike liquidations, the position does not need to be unsafe
+pub fn self_liquidate(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    collateral_token: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    assert_not_frozen(deps.storage)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    if amount.is_zero()
+        || cur_collaterals
+            .sub(vec![(collateral_token_raw.clone(), amount)])
+            .is_err()
+    {
+        return Err(ContractError::SelfLiquidationExceedsLocked {});
+    }
+
+    let loan_amount = query_borrower_info(
+        deps.as_ref(),
+        market_contract.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?
+    .loan_amount;
+    if loan_amount.is_zero() {
+        return Err(ContractError::SelfLiquidationWithoutLoan {});
+    }
+
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+
+    let custody_contract =
+        read_custody_contract(deps.storage, &collateral_token_raw, &borrower_raw)?;
+    let prev_balance: Uint256 =
+        query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&custody_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::SelfLiquidateCollateral {
+                    borrower: borrower.to_string(),
+                    amount,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: market_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: borrower.to_string(),
+                    prev_balance,
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "self_liquidate"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("amount", amount),
+        ])
+        .add_attributes(credit_line_attributes(deps.storage, &borrower_raw)?))
+}
@@ -2,17 +2,74 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
+use cosmwasm_std::{Binary, CanonicalAddr, Deps, Order, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, WhitelistResponseElem};
+use moneymarket::oracle::PriceKind;
+use moneymarket::overseer::{
+    CollateralsResponse, CreditLine, EpochSnapshotResponse, EpochStep, GradualLiquidation,
+    OperatorPermission, SeizureOrder, WhitelistProposalResponse, WhitelistResponseElem,
+    WhitelistStatus,
+};
+use moneymarket::pagination::{calc_limit, calc_range_start};
 use moneymarket::tokens::Tokens;
 
+use crate::error::ContractError;
+
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
+const KEY_FREEZE_STATE: &[u8] = b"freeze_state";
+const KEY_EPOCH_PROGRESS: &[u8] = b"epoch_progress";
+const KEY_EPOCH_CURSOR: &[u8] = b"epoch_cursor";
+const KEY_KEEPER_REWARD: &[u8] = b"keeper_reward";
+const KEY_DYNAMIC_RATE_CONFIG: &[u8] = b"dynamic_rate_config";
+const KEY_DISTRIBUTION_CAP: &[u8] = b"distribution_cap";
+const KEY_CARRIED_INTEREST: &[u8] = b"carried_interest";
+const KEY_PREV_DISTRIBUTED_INTEREST: &[u8] = b"prev_distributed_interest";
+const KEY_PROPOSAL_BOND: &[u8] = b"proposal_bond";
+const KEY_PROPOSAL_COUNT: &[u8] = b"proposal_count";
+const KEY_PENDING_PROPOSAL: &[u8] = b"pending_proposal";
+const KEY_TOTAL_PROPOSAL_BONDS: &[u8] = b"total_proposal_bonds";
+const KEY_BORROW_LIMIT_CACHE_VERSION: &[u8] = b"borrow_limit_cache_version";
+const KEY_EXCLUSIVE_LIQUIDATION: &[u8] = b"exclusive_liquidation";
+const KEY_GRADUAL_LIQUIDATION: &[u8] = b"gradual_liquidation";
+const KEY_SEIZURE_ORDER: &[u8] = b"seizure_order";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_CATEGORY: &[u8] = b"category";
+const PREFIX_BORROWER_CATEGORY: &[u8] = b"borrower_category";
+const PREFIX_CATEGORY_SHARE_LIMIT: &[u8] = b"category_share_limit";
+const PREFIX_WHITELIST_PROPOSAL: &[u8] = b"whitelist_proposal";
+const PREFIX_AUTO_DELEVERAGE: &[u8] = b"auto_deleverage";
+const PREFIX_BORROW_LIMIT_CACHE: &[u8] = b"borrow_limit_cache";
+const PREFIX_DEBT_CEILING: &[u8] = b"debt_ceiling";
+const PREFIX_COLLATERAL_DEBT: &[u8] = b"collateral_debt";
+const PREFIX_BORROWER_DEBT: &[u8] = b"borrower_debt";
+const PREFIX_CUSTODY_MIGRATION: &[u8] = b"custody_migration";
+const PREFIX_MIGRATED_BORROWER: &[u8] = b"migrated_borrower";
+const PREFIX_WHITELIST_STATUS: &[u8] = b"whitelist_status";
+const PREFIX_TOTAL_COLLATERAL: &[u8] = b"total_collateral";
+const PREFIX_EPOCH_HISTORY: &[u8] = b"epoch_history";
+const PREFIX_MAX_PRICE_AGE: &[u8] = b"max_price_age";
+const PREFIX_CREDIT_LINE: &[u8] = b"credit_line";
+const PREFIX_COLLATERAL_ORACLE: &[u8] = b"collateral_oracle";
+const PREFIX_MIN_LOCK_DURATION: &[u8] = b"min_lock_duration";
+const PREFIX_UNLOCK_BUFFER: &[u8] = b"unlock_buffer";
+const PREFIX_LAST_LOCK_HEIGHT: &[u8] = b"last_lock_height";
+const PREFIX_ORACLE_QUORUM: &[u8] = b"oracle_quorum";
+const PREFIX_LIQUIDATION_CAP: &[u8] = b"liquidation_cap";
+const PREFIX_LIQUIDATION_THROTTLE: &[u8] = b"liquidation_throttle";
+const PREFIX_LIQUIDATION_QUEUE: &[u8] = b"liquidation_queue";
+const PREFIX_RISK_NOTIFICATION: &[u8] = b"risk_notification";
+const PREFIX_OPERATOR: &[u8] = b"operator";
+const PREFIX_RISK_WATCHER: &[u8] = b"risk_watcher";
+const PREFIX_RISK_WATCH: &[u8] = b"risk_watch";
+const PREFIX_LIQUIDATABLE_SINCE: &[u8] = b"liquidatable_since";
+const PREFIX_LIQUIDATION_PLAN: &[u8] = b"liquidation_plan";
+const PREFIX_SEIZURE_PREFERENCE: &[u8] = b"seizure_preference";
+const PREFIX_PRICE_KIND: &[u8] = b"price_kind";
+const PREFIX_CONFIDENCE_THRESHOLD: &[u8] = b"confidence_threshold";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -39,6 +96,56 @@ pub struct EpochState {
     pub last_executed_height: u64,
 }
 
+/// Intermediate results of an epoch processed step by step
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochProgress {
+    pub next_step: EpochStep,
+    pub deposit_rate: Decimal256,
+    pub interest_buffer: Uint256,
+    pub distributed_interest: Uint256,
+    // keeper rewards paid before the interest buffer distribution
+    pub keeper_rewards: Uint256,
+    // last collateral whose custody distributed rewards
+    pub rewards_cursor: Option<CanonicalAddr>,
+}
+
+/// Epoch operations left unfinished once the rewards distribution
+/// outgrew a single call
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochCursor {
+    // last collateral whose custody distributed rewards
+    pub last_collateral: CanonicalAddr,
+    pub interest_buffer: Uint256,
+    pub distributed_interest: Uint256,
+}
+
+/// Bounds of the controller adjusting the target deposit rate
+/// from the projected yield reserve runway
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DynamicRateConfig {
+    pub min_deposit_rate: Decimal256,
+    pub max_deposit_rate: Decimal256,
+    // desired # of epochs until the interest buffer is depleted
+    pub target_runway: u64,
+    // maximum change of the deposit rate per epoch
+    pub max_change: Decimal256,
+}
+
+/// Bounds of the interest buffer distribution per epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DistributionCap {
+    pub max_distribution: Option<Uint256>,
+    // # of epochs the distribution is spread over
+    pub smoothing_window: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FreezeState {
+    pub guardian: Option<CanonicalAddr>,
+    pub unfreeze_delay: u64,
+    pub frozen_at: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistElem {
     pub name: String,
@@ -47,6 +154,143 @@ pub struct WhitelistElem {
     pub custody_contract: CanonicalAddr,
 }
 
+/// Lifecycle status of a whitelisted collateral; the LTV ramp
+/// is only set once the collateral is deprecated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistStatusInfo {
+    pub status: WhitelistStatus,
+    pub ltv_ramp_start: u64,
+    pub ltv_ramp_end: u64,
+}
+
+/// Protocol metrics recorded once the epoch state is updated,
+/// keyed by the height the epoch was executed at
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochSnapshot {
+    pub time: u64,
+    pub deposit_rate: Decimal256,
+    pub interest_buffer: Uint256,
+    pub distributed_interest: Uint256,
+    pub total_collateral_value: Uint256,
+    pub total_liabilities: Decimal256,
+}
+
+/// Oracle contracts pricing a collateral together
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleQuorumInfo {
+    pub oracles: Vec<CanonicalAddr>,
+    pub quorum: u32,
+}
+
+/// Collateral value liquidated in the epoch started at `epoch`, and
+/// the borrowers queued because the liquidation cap was reached. Borrowers
+/// queued in `priority_epoch` are liquidated first in the current epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct LiquidationThrottle {
+    pub epoch: u64,
+    pub liquidated_value: Uint256,
+    pub queued: u32,
+    pub priority_epoch: u64,
+    pub priority: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistProposal {
+    pub proposer: CanonicalAddr,
+    pub name: String,
+    pub symbol: String,
+    pub collateral_token: CanonicalAddr,
+    pub custody_code_id: u64,
+    pub custody_init_msg: Binary,
+    pub max_ltv: Decimal256,
+    pub bond_amount: Uint256,
+}
+
+/// Group of mutually correlated collaterals which can be
+/// borrowed against with a boosted LTV
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Category {
+    pub max_ltv: Decimal256,
+    pub collateral_tokens: Vec<CanonicalAddr>,
+}
+
+/// Borrower opt-in for automatic deleveraging; once the loan reaches
+/// `trigger_ratio` of the borrow limit, anyone can sell collateral
+/// to bring it back down to `target_ratio`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoDeleverage {
+    pub trigger_ratio: Decimal256,
+    pub target_ratio: Decimal256,
+}
+
+/// Borrow limit computed for a collaterals snapshot at `height`; only
+/// valid while the cache `version` has not been bumped by an LTV change
+/// and the oracle feeds still match the `price_stamps` it was priced at
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowLimitCache {
+    pub height: u64,
+    pub version: u64,
+    pub collaterals: Tokens,
+    pub price_stamps: Vec<Option<PriceStamp>>,
+    pub borrow_limit: Uint256,
+    pub collateral_prices: Vec<Decimal256>,
+}
+
+/// Spot feed of an oracle pricing a collateral; any feed changes
+/// either its rate or its update times
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceStamp {
+    pub rate: Decimal256,
+    pub last_updated_base: u64,
+    pub last_updated_quote: u64,
+}
+
+/// In progress replacement of the custody contract of a collateral;
+/// borrowers keep using `old_custody` until their collateral is migrated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CustodyMigration {
+    pub old_custody: CanonicalAddr,
+    pub new_custody: CanonicalAddr,
+    pub last_borrower: Option<CanonicalAddr>,
+}
+
+/// Risk contract registered by governance to watch borrowers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskWatcher {
+    pub watch_count: u32,
+}
+
+/// Watch of a risk contract on a borrower; `alerted` is set once the
+/// LTV crosses the threshold and cleared when it falls back below it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskWatch {
+    pub ltv_threshold: Decimal256,
+    pub alerted: bool,
+}
+
+/// Liquidators allowed to liquidate a position during the first
+/// `exclusive_blocks` after it was found liquidatable
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExclusiveLiquidationInfo {
+    pub liquidators: Vec<CanonicalAddr>,
+    pub exclusive_blocks: u64,
+}
+
+/// Ongoing liquidation of a borrower split into tranches
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationPlan {
+    pub remaining_tranches: u32,
+    pub next_height: u64,
+}
+
+/// Contract of a borrower notified when its LTV changes band
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskNotification {
+    pub contract: CanonicalAddr,
+    pub ltv_bands: Vec<Decimal256>,
+    pub band: u32,
+}
+
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
     Singleton::new(storage, KEY_CONFIG).save(data)
 }
@@ -63,6 +307,203 @@ pub fn read_epoch_state(storage: &dyn Storage) -> StdResult<EpochState> {
     ReadonlySingleton::new(storage, KEY_EPOCH_STATE).load()
 }
 
+pub fn store_epoch_progress(storage: &mut dyn Storage, data: &EpochProgress) -> StdResult<()> {
+    Singleton::new(storage, KEY_EPOCH_PROGRESS).save(data)
+}
+
+pub fn remove_epoch_progress(storage: &mut dyn Storage) {
+    Singleton::<EpochProgress>::new(storage, KEY_EPOCH_PROGRESS).remove()
+}
+
+pub fn read_epoch_progress(storage: &dyn Storage) -> StdResult<Option<EpochProgress>> {
+    ReadonlySingleton::new(storage, KEY_EPOCH_PROGRESS).may_load()
+}
+
+pub fn store_epoch_cursor(storage: &mut dyn Storage, data: &EpochCursor) -> StdResult<()> {
+    Singleton::new(storage, KEY_EPOCH_CURSOR).save(data)
+}
+
+pub fn remove_epoch_cursor(storage: &mut dyn Storage) {
+    Singleton::<EpochCursor>::new(storage, KEY_EPOCH_CURSOR).remove()
+}
+
+pub fn read_epoch_cursor(storage: &dyn Storage) -> StdResult<Option<EpochCursor>> {
+    ReadonlySingleton::new(storage, KEY_EPOCH_CURSOR).may_load()
+}
+
+pub fn store_keeper_reward(storage: &mut dyn Storage, keeper_reward: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_KEEPER_REWARD).save(keeper_reward)
+}
+
+pub fn read_keeper_reward(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_KEEPER_REWARD)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_dynamic_rate_config(
+    storage: &mut dyn Storage,
+    data: &DynamicRateConfig,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_DYNAMIC_RATE_CONFIG).save(data)
+}
+
+pub fn read_dynamic_rate_config(storage: &dyn Storage) -> StdResult<Option<DynamicRateConfig>> {
+    ReadonlySingleton::new(storage, KEY_DYNAMIC_RATE_CONFIG).may_load()
+}
+
+pub fn store_distribution_cap(storage: &mut dyn Storage, data: &DistributionCap) -> StdResult<()> {
+    Singleton::new(storage, KEY_DISTRIBUTION_CAP).save(data)
+}
+
+pub fn read_distribution_cap(storage: &dyn Storage) -> StdResult<Option<DistributionCap>> {
+    ReadonlySingleton::new(storage, KEY_DISTRIBUTION_CAP).may_load()
+}
+
+pub fn store_carried_interest(
+    storage: &mut dyn Storage,
+    carried_interest: &Uint256,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_CARRIED_INTEREST).save(carried_interest)
+}
+
+pub fn read_carried_interest(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_CARRIED_INTEREST)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_prev_distributed_interest(
+    storage: &mut dyn Storage,
+    distributed_interest: &Uint256,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_PREV_DISTRIBUTED_INTEREST).save(distributed_interest)
+}
+
+pub fn read_prev_distributed_interest(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(
+        ReadonlySingleton::new(storage, KEY_PREV_DISTRIBUTED_INTEREST)
+            .may_load()?
+            .unwrap_or_default(),
+    )
+}
+
+pub fn store_proposal_bond(storage: &mut dyn Storage, proposal_bond: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_PROPOSAL_BOND).save(proposal_bond)
+}
+
+pub fn read_proposal_bond(storage: &dyn Storage) -> StdResult<Option<Uint256>> {
+    ReadonlySingleton::new(storage, KEY_PROPOSAL_BOND).may_load()
+}
+
+pub fn store_pending_proposal(storage: &mut dyn Storage, proposal_id: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_PENDING_PROPOSAL).save(&proposal_id)
+}
+
+pub fn read_pending_proposal(storage: &dyn Storage) -> StdResult<u64> {
+    ReadonlySingleton::new(storage, KEY_PENDING_PROPOSAL).load()
+}
+
+pub fn store_total_proposal_bonds(
+    storage: &mut dyn Storage,
+    total_proposal_bonds: &Uint256,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_TOTAL_PROPOSAL_BONDS).save(total_proposal_bonds)
+}
+
+/// Bonds held by the overseer for proposals not yet approved or rejected
+pub fn read_total_proposal_bonds(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_TOTAL_PROPOSAL_BONDS)
+        .may_load()?
+        .unwrap_or_else(Uint256::zero))
+}
+
+pub fn store_whitelist_proposal(
+    storage: &mut dyn Storage,
+    proposal: &WhitelistProposal,
+) -> StdResult<u64> {
+    let proposal_id: u64 = ReadonlySingleton::<u64>::new(storage, KEY_PROPOSAL_COUNT)
+        .may_load()?
+        .unwrap_or_default()
+        + 1;
+    Singleton::new(storage, KEY_PROPOSAL_COUNT).save(&proposal_id)?;
+
+    let mut proposal_bucket: Bucket<WhitelistProposal> =
+        Bucket::new(storage, PREFIX_WHITELIST_PROPOSAL);
+    proposal_bucket.save(&proposal_id.to_be_bytes(), proposal)?;
+
+    Ok(proposal_id)
+}
+
+pub fn remove_whitelist_proposal(storage: &mut dyn Storage, proposal_id: u64) {
+    let mut proposal_bucket: Bucket<WhitelistProposal> =
+        Bucket::new(storage, PREFIX_WHITELIST_PROPOSAL);
+    proposal_bucket.remove(&proposal_id.to_be_bytes());
+}
+
+pub fn read_whitelist_proposal(
+    storage: &dyn Storage,
+    proposal_id: u64,
+) -> StdResult<WhitelistProposal> {
+    let proposal_bucket: ReadonlyBucket<WhitelistProposal> =
+        ReadonlyBucket::new(storage, PREFIX_WHITELIST_PROPOSAL);
+    match proposal_bucket.load(&proposal_id.to_be_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(ContractError::WhitelistProposalNotFound(proposal_id).into()),
+    }
+}
+
+pub fn read_whitelist_proposals(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<WhitelistProposalResponse>> {
+    let proposal_bucket: ReadonlyBucket<WhitelistProposal> =
+        ReadonlyBucket::new(deps.storage, PREFIX_WHITELIST_PROPOSAL);
+
+    let limit = calc_limit(limit);
+    let start = calc_range_start(start_after);
+
+    proposal_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut proposal_id = [0u8; 8];
+            proposal_id.copy_from_slice(&k);
+
+            Ok(WhitelistProposalResponse {
+                proposal_id: u64::from_be_bytes(proposal_id),
+                proposer: deps.api.addr_humanize(&v.proposer)?.to_string(),
+                name: v.name,
+                symbol: v.symbol,
+                collateral_token: deps.api.addr_humanize(&v.collateral_token)?.to_string(),
+                custody_code_id: v.custody_code_id,
+                custody_init_msg: v.custody_init_msg,
+                max_ltv: v.max_ltv,
+                bond_amount: v.bond_amount,
+            })
+        })
+        .collect()
+}
+
+// default delay between a freeze and the earliest possible unfreeze
+const DEFAULT_UNFREEZE_DELAY: u64 = 86400u64;
+
+pub fn store_freeze_state(storage: &mut dyn Storage, data: &FreezeState) -> StdResult<()> {
+    Singleton::new(storage, KEY_FREEZE_STATE).save(data)
+}
+
+pub fn read_freeze_state(storage: &dyn Storage) -> StdResult<FreezeState> {
+    Ok(ReadonlySingleton::new(storage, KEY_FREEZE_STATE)
+        .may_load()?
+        .unwrap_or(FreezeState {
+            guardian: None,
+            unfreeze_delay: DEFAULT_UNFREEZE_DELAY,
+            frozen_at: None,
+        }))
+}
+
 pub fn store_whitelist_elem(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
@@ -82,9 +523,7 @@ pub fn read_whitelist_elem(
         ReadonlyBucket::new(storage, PREFIX_WHITELIST);
     match whitelist_bucket.load(collateral_token.as_slice()) {
         Ok(v) => Ok(v),
-        _ => Err(StdError::generic_err(
-            "Token is not registered as collateral",
-        )),
+        _ => Err(ContractError::TokenNotRegistered {}.into()),
     }
 }
 
@@ -96,7 +535,7 @@ pub fn read_whitelist(
     let whitelist_bucket: ReadonlyBucket<WhitelistElem> =
         ReadonlyBucket::new(deps.storage, PREFIX_WHITELIST);
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = calc_limit(limit);
     let start = calc_range_start(start_after);
 
     whitelist_bucket
@@ -117,12 +556,49 @@ pub fn read_whitelist(
         .collect()
 }
 
+/// Whitelisted collateral token held by the given custody contract
+pub fn read_collateral_token_by_custody(
+    storage: &dyn Storage,
+    custody_contract: &CanonicalAddr,
+) -> StdResult<Option<CanonicalAddr>> {
+    let whitelist_bucket: ReadonlyBucket<WhitelistElem> =
+        ReadonlyBucket::new(storage, PREFIX_WHITELIST);
+    for elem in whitelist_bucket.range(None, None, Order::Ascending) {
+        let (k, v) = elem?;
+        if &v.custody_contract == custody_contract {
+            return Ok(Some(CanonicalAddr::from(k)));
+        }
+    }
+
+    Ok(None)
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn store_collaterals(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
     collaterals: &Tokens,
 ) -> StdResult<()> {
+    // keep the per token totals in sync with the borrower collaterals
+    let prev_collaterals: Tokens = read_collaterals(storage, borrower);
+    let mut total_collateral_bucket: Bucket<Uint256> =
+        Bucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    for (collateral_token, amount) in prev_collaterals.iter() {
+        let total: Uint256 = total_collateral_bucket
+            .may_load(collateral_token.as_slice())?
+            .unwrap_or_default();
+        total_collateral_bucket.save(
+            collateral_token.as_slice(),
+            &(total - std::cmp::min(*amount, total)),
+        )?;
+    }
+    for (collateral_token, amount) in collaterals.iter() {
+        let total: Uint256 = total_collateral_bucket
+            .may_load(collateral_token.as_slice())?
+            .unwrap_or_default();
+        total_collateral_bucket.save(collateral_token.as_slice(), &(total + *amount))?;
+    }
+
     let mut collaterals_bucket: Bucket<Tokens> = Bucket::new(storage, PREFIX_COLLATERALS);
     if collaterals.is_empty() {
         collaterals_bucket.remove(borrower.as_slice());
@@ -142,44 +618,937 @@ pub fn read_collaterals(storage: &dyn Storage, borrower: &CanonicalAddr) -> Toke
     }
 }
 
-// settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
-pub fn read_all_collaterals(
-    deps: Deps,
-    start_after: Option<CanonicalAddr>,
-    limit: Option<u32>,
-) -> StdResult<Vec<CollateralsResponse>> {
-    let whitelist_bucket: ReadonlyBucket<Tokens> =
-        ReadonlyBucket::new(deps.storage, PREFIX_COLLATERALS);
+pub fn store_category(storage: &mut dyn Storage, name: &str, category: &Category) -> StdResult<()> {
+    let mut category_bucket: Bucket<Category> = Bucket::new(storage, PREFIX_CATEGORY);
+    category_bucket.save(name.as_bytes(), category)
+}
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start(start_after);
+pub fn read_category(storage: &dyn Storage, name: &str) -> StdResult<Category> {
+    let category_bucket: ReadonlyBucket<Category> = ReadonlyBucket::new(storage, PREFIX_CATEGORY);
+    match category_bucket.load(name.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(ContractError::CategoryNotRegistered {}.into()),
+    }
+}
 
-    whitelist_bucket
-        .range(start.as_deref(), None, Order::Ascending)
-        .take(limit)
-        .map(|elem| {
-            let (k, v) = elem?;
-            let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
-            let collaterals: Vec<(String, Uint256)> = v
-                .iter()
-                .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
-                .collect::<StdResult<Vec<(String, Uint256)>>>()?;
+pub fn store_category_share_limit(
+    storage: &mut dyn Storage,
+    name: &str,
+    max_collateral_share: Option<Decimal256>,
+) -> StdResult<()> {
+    let mut share_limit_bucket: Bucket<Decimal256> =
+        Bucket::new(storage, PREFIX_CATEGORY_SHARE_LIMIT);
+    match max_collateral_share {
+        Some(max_collateral_share) => {
+            share_limit_bucket.save(name.as_bytes(), &max_collateral_share)
+        }
+        None => {
+            share_limit_bucket.remove(name.as_bytes());
+            Ok(())
+        }
+    }
+}
 
-            Ok(CollateralsResponse {
-                borrower,
-                collaterals,
-            })
-        })
-        .collect()
+pub fn read_category_share_limit(
+    storage: &dyn Storage,
+    name: &str,
+) -> StdResult<Option<Decimal256>> {
+    let share_limit_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::new(storage, PREFIX_CATEGORY_SHARE_LIMIT);
+    share_limit_bucket.may_load(name.as_bytes())
+}
+
+pub fn store_borrower_category(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    category: Option<String>,
+) -> StdResult<()> {
+    let mut borrower_category_bucket: Bucket<String> =
+        Bucket::new(storage, PREFIX_BORROWER_CATEGORY);
+    match category {
+        Some(category) => borrower_category_bucket.save(borrower.as_slice(), &category),
+        None => {
+            borrower_category_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_borrower_category(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<String>> {
+    let borrower_category_bucket: ReadonlyBucket<String> =
+        ReadonlyBucket::new(storage, PREFIX_BORROWER_CATEGORY);
+    borrower_category_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_auto_deleverage(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    setting: Option<AutoDeleverage>,
+) -> StdResult<()> {
+    let mut auto_deleverage_bucket: Bucket<AutoDeleverage> =
+        Bucket::new(storage, PREFIX_AUTO_DELEVERAGE);
+    match setting {
+        Some(setting) => auto_deleverage_bucket.save(borrower.as_slice(), &setting),
+        None => {
+            auto_deleverage_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_auto_deleverage(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<AutoDeleverage>> {
+    let auto_deleverage_bucket: ReadonlyBucket<AutoDeleverage> =
+        ReadonlyBucket::new(storage, PREFIX_AUTO_DELEVERAGE);
+    auto_deleverage_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_borrow_limit_cache(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    cache: &BorrowLimitCache,
+) -> StdResult<()> {
+    let mut cache_bucket: Bucket<BorrowLimitCache> =
+        Bucket::new(storage, PREFIX_BORROW_LIMIT_CACHE);
+    cache_bucket.save(borrower.as_slice(), cache)
+}
+
+pub fn remove_borrow_limit_cache(storage: &mut dyn Storage, borrower: &CanonicalAddr) {
+    let mut cache_bucket: Bucket<BorrowLimitCache> =
+        Bucket::new(storage, PREFIX_BORROW_LIMIT_CACHE);
+    cache_bucket.remove(borrower.as_slice());
+}
+
+pub fn read_borrow_limit_cache(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<BorrowLimitCache>> {
+    let cache_bucket: ReadonlyBucket<BorrowLimitCache> =
+        ReadonlyBucket::new(storage, PREFIX_BORROW_LIMIT_CACHE);
+    cache_bucket.may_load(borrower.as_slice())
+}
+
+/// Invalidates every cached borrow limit at once
+pub fn bump_borrow_limit_cache_version(storage: &mut dyn Storage) -> StdResult<()> {
+    let version = read_borrow_limit_cache_version(storage)?;
+    Singleton::new(storage, KEY_BORROW_LIMIT_CACHE_VERSION).save(&(version + 1))
+}
+
+pub fn read_borrow_limit_cache_version(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(
+        ReadonlySingleton::<u64>::new(storage, KEY_BORROW_LIMIT_CACHE_VERSION)
+            .may_load()?
+            .unwrap_or_default(),
+    )
+}
+
+pub fn store_debt_ceiling(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    debt_ceiling: Option<Uint256>,
+) -> StdResult<()> {
+    let mut debt_ceiling_bucket: Bucket<Uint256> = Bucket::new(storage, PREFIX_DEBT_CEILING);
+    match debt_ceiling {
+        Some(debt_ceiling) => debt_ceiling_bucket.save(collateral_token.as_slice(), &debt_ceiling),
+        None => {
+            debt_ceiling_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_debt_ceiling(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<Uint256>> {
+    let debt_ceiling_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_DEBT_CEILING);
+    debt_ceiling_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_collateral_debt(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    collateral_debt: &Uint256,
+) -> StdResult<()> {
+    let mut collateral_debt_bucket: Bucket<Uint256> = Bucket::new(storage, PREFIX_COLLATERAL_DEBT);
+    collateral_debt_bucket.save(collateral_token.as_slice(), collateral_debt)
+}
+
+pub fn read_collateral_debt(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Uint256> {
+    let collateral_debt_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_DEBT);
+    Ok(collateral_debt_bucket
+        .may_load(collateral_token.as_slice())?
+        .unwrap_or_default())
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
-    start_after.map(|addr| {
-        let mut v = addr.as_slice().to_vec();
-        v.push(1);
-        v
-    })
+/// Debt of the borrower allocated to each of its collaterals
+pub fn store_borrower_debt(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    borrower_debt: &Tokens,
+) -> StdResult<()> {
+    let mut borrower_debt_bucket: Bucket<Tokens> = Bucket::new(storage, PREFIX_BORROWER_DEBT);
+    if borrower_debt.is_empty() {
+        borrower_debt_bucket.remove(borrower.as_slice());
+        Ok(())
+    } else {
+        borrower_debt_bucket.save(borrower.as_slice(), borrower_debt)
+    }
+}
+
+pub fn read_borrower_debt(storage: &dyn Storage, borrower: &CanonicalAddr) -> StdResult<Tokens> {
+    let borrower_debt_bucket: ReadonlyBucket<Tokens> =
+        ReadonlyBucket::new(storage, PREFIX_BORROWER_DEBT);
+    Ok(borrower_debt_bucket
+        .may_load(borrower.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_custody_migration(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    migration: Option<CustodyMigration>,
+) -> StdResult<()> {
+    let mut migration_bucket: Bucket<CustodyMigration> =
+        Bucket::new(storage, PREFIX_CUSTODY_MIGRATION);
+    match migration {
+        Some(migration) => migration_bucket.save(collateral_token.as_slice(), &migration),
+        None => {
+            migration_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_custody_migration(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<CustodyMigration>> {
+    let migration_bucket: ReadonlyBucket<CustodyMigration> =
+        ReadonlyBucket::new(storage, PREFIX_CUSTODY_MIGRATION);
+    migration_bucket.may_load(collateral_token.as_slice())
+}
+
+/// Migrated borrowers are recorded per new custody contract,
+/// so records of a finished migration never leak into the next one
+pub fn store_migrated_borrower(
+    storage: &mut dyn Storage,
+    new_custody: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> StdResult<()> {
+    let mut migrated_bucket: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_MIGRATED_BORROWER, new_custody.as_slice()]);
+    migrated_bucket.save(borrower.as_slice(), &true)
+}
+
+pub fn read_migrated_borrower(
+    storage: &dyn Storage,
+    new_custody: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> StdResult<bool> {
+    let migrated_bucket: ReadonlyBucket<bool> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_MIGRATED_BORROWER, new_custody.as_slice()]);
+    Ok(migrated_bucket
+        .may_load(borrower.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_whitelist_status(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    status: &WhitelistStatusInfo,
+) -> StdResult<()> {
+    let mut status_bucket: Bucket<WhitelistStatusInfo> =
+        Bucket::new(storage, PREFIX_WHITELIST_STATUS);
+    status_bucket.save(collateral_token.as_slice(), status)
+}
+
+pub fn read_whitelist_status(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<WhitelistStatusInfo> {
+    let status_bucket: ReadonlyBucket<WhitelistStatusInfo> =
+        ReadonlyBucket::new(storage, PREFIX_WHITELIST_STATUS);
+    Ok(status_bucket
+        .may_load(collateral_token.as_slice())?
+        .unwrap_or(WhitelistStatusInfo {
+            status: WhitelistStatus::Active,
+            ltv_ramp_start: 0,
+            ltv_ramp_end: 0,
+        }))
+}
+
+pub fn store_max_price_age(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    max_price_age: Option<u64>,
+) -> StdResult<()> {
+    let mut max_price_age_bucket: Bucket<u64> = Bucket::new(storage, PREFIX_MAX_PRICE_AGE);
+    match max_price_age {
+        Some(max_price_age) => {
+            max_price_age_bucket.save(collateral_token.as_slice(), &max_price_age)
+        }
+        None => {
+            max_price_age_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_max_price_age(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<u64>> {
+    let max_price_age_bucket: ReadonlyBucket<u64> =
+        ReadonlyBucket::new(storage, PREFIX_MAX_PRICE_AGE);
+    max_price_age_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_price_kind(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    price_kind: Option<PriceKind>,
+) -> StdResult<()> {
+    let mut price_kind_bucket: Bucket<PriceKind> = Bucket::new(storage, PREFIX_PRICE_KIND);
+    match price_kind {
+        Some(price_kind) => price_kind_bucket.save(collateral_token.as_slice(), &price_kind),
+        None => {
+            price_kind_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_price_kind(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<PriceKind>> {
+    let price_kind_bucket: ReadonlyBucket<PriceKind> =
+        ReadonlyBucket::new(storage, PREFIX_PRICE_KIND);
+    price_kind_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_confidence_threshold(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    confidence_threshold: Option<Decimal256>,
+) -> StdResult<()> {
+    let mut confidence_threshold_bucket: Bucket<Decimal256> =
+        Bucket::new(storage, PREFIX_CONFIDENCE_THRESHOLD);
+    match confidence_threshold {
+        Some(confidence_threshold) => {
+            confidence_threshold_bucket.save(collateral_token.as_slice(), &confidence_threshold)
+        }
+        None => {
+            confidence_threshold_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_confidence_threshold(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<Decimal256>> {
+    let confidence_threshold_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::new(storage, PREFIX_CONFIDENCE_THRESHOLD);
+    confidence_threshold_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_collateral_oracle(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    oracle_contract: Option<CanonicalAddr>,
+) -> StdResult<()> {
+    let mut collateral_oracle_bucket: Bucket<CanonicalAddr> =
+        Bucket::new(storage, PREFIX_COLLATERAL_ORACLE);
+    match oracle_contract {
+        Some(oracle_contract) => {
+            collateral_oracle_bucket.save(collateral_token.as_slice(), &oracle_contract)
+        }
+        None => {
+            collateral_oracle_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+/// Oracle pricing the collateral, the global oracle when it has no override
+pub fn read_collateral_oracle(
+    storage: &dyn Storage,
+    config: &Config,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<CanonicalAddr> {
+    let collateral_oracle_bucket: ReadonlyBucket<CanonicalAddr> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_ORACLE);
+    Ok(collateral_oracle_bucket
+        .may_load(collateral_token.as_slice())?
+        .unwrap_or_else(|| config.oracle_contract.clone()))
+}
+
+pub fn store_min_lock_duration(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    min_lock_duration: Option<u64>,
+) -> StdResult<()> {
+    let mut duration_bucket: Bucket<u64> = Bucket::new(storage, PREFIX_MIN_LOCK_DURATION);
+    match min_lock_duration {
+        Some(min_lock_duration) => {
+            duration_bucket.save(collateral_token.as_slice(), &min_lock_duration)
+        }
+        None => {
+            duration_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_min_lock_duration(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<u64>> {
+    let duration_bucket: ReadonlyBucket<u64> =
+        ReadonlyBucket::new(storage, PREFIX_MIN_LOCK_DURATION);
+    duration_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_unlock_buffer(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    unlock_buffer: Option<Decimal256>,
+) -> StdResult<()> {
+    let mut unlock_buffer_bucket: Bucket<Decimal256> = Bucket::new(storage, PREFIX_UNLOCK_BUFFER);
+    match unlock_buffer {
+        Some(unlock_buffer) => {
+            unlock_buffer_bucket.save(collateral_token.as_slice(), &unlock_buffer)
+        }
+        None => {
+            unlock_buffer_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_unlock_buffer(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<Decimal256>> {
+    let unlock_buffer_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::new(storage, PREFIX_UNLOCK_BUFFER);
+    unlock_buffer_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_last_lock_height(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+    height: u64,
+) -> StdResult<()> {
+    let mut height_bucket: Bucket<u64> = Bucket::multilevel(
+        storage,
+        &[PREFIX_LAST_LOCK_HEIGHT, collateral_token.as_slice()],
+    );
+    height_bucket.save(borrower.as_slice(), &height)
+}
+
+pub fn read_last_lock_height(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<u64>> {
+    let height_bucket: ReadonlyBucket<u64> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_LAST_LOCK_HEIGHT, collateral_token.as_slice()],
+    );
+    height_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_oracle_quorum(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    oracle_quorum: Option<OracleQuorumInfo>,
+) -> StdResult<()> {
+    let mut oracle_quorum_bucket: Bucket<OracleQuorumInfo> =
+        Bucket::new(storage, PREFIX_ORACLE_QUORUM);
+    match oracle_quorum {
+        Some(oracle_quorum) => {
+            oracle_quorum_bucket.save(collateral_token.as_slice(), &oracle_quorum)
+        }
+        None => {
+            oracle_quorum_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_oracle_quorum(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<OracleQuorumInfo>> {
+    let oracle_quorum_bucket: ReadonlyBucket<OracleQuorumInfo> =
+        ReadonlyBucket::new(storage, PREFIX_ORACLE_QUORUM);
+    oracle_quorum_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_liquidation_cap(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    liquidation_cap: Option<Uint256>,
+) -> StdResult<()> {
+    let mut liquidation_cap_bucket: Bucket<Uint256> = Bucket::new(storage, PREFIX_LIQUIDATION_CAP);
+    match liquidation_cap {
+        Some(liquidation_cap) => {
+            liquidation_cap_bucket.save(collateral_token.as_slice(), &liquidation_cap)
+        }
+        None => {
+            liquidation_cap_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_liquidation_cap(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<Uint256>> {
+    let liquidation_cap_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_LIQUIDATION_CAP);
+    liquidation_cap_bucket.may_load(collateral_token.as_slice())
+}
+
+pub fn store_liquidation_throttle(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    throttle: &LiquidationThrottle,
+) -> StdResult<()> {
+    let mut throttle_bucket: Bucket<LiquidationThrottle> =
+        Bucket::new(storage, PREFIX_LIQUIDATION_THROTTLE);
+    throttle_bucket.save(collateral_token.as_slice(), throttle)
+}
+
+pub fn read_liquidation_throttle(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<LiquidationThrottle> {
+    let throttle_bucket: ReadonlyBucket<LiquidationThrottle> =
+        ReadonlyBucket::new(storage, PREFIX_LIQUIDATION_THROTTLE);
+    Ok(throttle_bucket
+        .may_load(collateral_token.as_slice())?
+        .unwrap_or_default())
+}
+
+/// Store the epoch the borrower was queued in, or remove it with `None`
+pub fn store_liquidation_queue(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+    queued_epoch: Option<u64>,
+) -> StdResult<()> {
+    let mut queue_bucket: Bucket<u64> = Bucket::multilevel(
+        storage,
+        &[PREFIX_LIQUIDATION_QUEUE, collateral_token.as_slice()],
+    );
+    match queued_epoch {
+        Some(queued_epoch) => queue_bucket.save(borrower.as_slice(), &queued_epoch),
+        None => {
+            queue_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_liquidation_queue(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<u64>> {
+    let queue_bucket: ReadonlyBucket<u64> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_LIQUIDATION_QUEUE, collateral_token.as_slice()],
+    );
+    queue_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_credit_line(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    credit_line: Option<CreditLine>,
+) -> StdResult<()> {
+    let mut credit_line_bucket: Bucket<CreditLine> = Bucket::new(storage, PREFIX_CREDIT_LINE);
+    match credit_line {
+        Some(credit_line) => credit_line_bucket.save(borrower.as_slice(), &credit_line),
+        None => {
+            credit_line_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_credit_line(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<CreditLine>> {
+    let credit_line_bucket: ReadonlyBucket<CreditLine> =
+        ReadonlyBucket::new(storage, PREFIX_CREDIT_LINE);
+    credit_line_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_risk_notification(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    risk_notification: Option<RiskNotification>,
+) -> StdResult<()> {
+    let mut notification_bucket: Bucket<RiskNotification> =
+        Bucket::new(storage, PREFIX_RISK_NOTIFICATION);
+    match risk_notification {
+        Some(risk_notification) => {
+            notification_bucket.save(borrower.as_slice(), &risk_notification)
+        }
+        None => {
+            notification_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_risk_notification(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<RiskNotification>> {
+    let notification_bucket: ReadonlyBucket<RiskNotification> =
+        ReadonlyBucket::new(storage, PREFIX_RISK_NOTIFICATION);
+    notification_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_operator_permissions(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    operator: &CanonicalAddr,
+    permissions: &[OperatorPermission],
+) -> StdResult<()> {
+    let mut operator_bucket: Bucket<Vec<OperatorPermission>> =
+        Bucket::multilevel(storage, &[PREFIX_OPERATOR, borrower.as_slice()]);
+    if permissions.is_empty() {
+        operator_bucket.remove(operator.as_slice());
+        Ok(())
+    } else {
+        operator_bucket.save(operator.as_slice(), &permissions.to_vec())
+    }
+}
+
+pub fn read_operator_permissions(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    operator: &CanonicalAddr,
+) -> StdResult<Vec<OperatorPermission>> {
+    let operator_bucket: ReadonlyBucket<Vec<OperatorPermission>> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_OPERATOR, borrower.as_slice()]);
+    Ok(operator_bucket
+        .may_load(operator.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_risk_watcher(
+    storage: &mut dyn Storage,
+    watcher: &CanonicalAddr,
+    risk_watcher: Option<RiskWatcher>,
+) -> StdResult<()> {
+    let mut watcher_bucket: Bucket<RiskWatcher> = Bucket::new(storage, PREFIX_RISK_WATCHER);
+    match risk_watcher {
+        Some(risk_watcher) => watcher_bucket.save(watcher.as_slice(), &risk_watcher),
+        None => {
+            watcher_bucket.remove(watcher.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_risk_watcher(
+    storage: &dyn Storage,
+    watcher: &CanonicalAddr,
+) -> StdResult<Option<RiskWatcher>> {
+    let watcher_bucket: ReadonlyBucket<RiskWatcher> =
+        ReadonlyBucket::new(storage, PREFIX_RISK_WATCHER);
+    watcher_bucket.may_load(watcher.as_slice())
+}
+
+pub fn read_risk_watchers(storage: &dyn Storage) -> StdResult<Vec<CanonicalAddr>> {
+    let watcher_bucket: ReadonlyBucket<RiskWatcher> =
+        ReadonlyBucket::new(storage, PREFIX_RISK_WATCHER);
+    watcher_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, _) = elem?;
+            Ok(CanonicalAddr::from(k))
+        })
+        .collect()
+}
+
+pub fn store_risk_watch(
+    storage: &mut dyn Storage,
+    watcher: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+    risk_watch: Option<RiskWatch>,
+) -> StdResult<()> {
+    let mut watch_bucket: Bucket<RiskWatch> =
+        Bucket::multilevel(storage, &[PREFIX_RISK_WATCH, watcher.as_slice()]);
+    match risk_watch {
+        Some(risk_watch) => watch_bucket.save(borrower.as_slice(), &risk_watch),
+        None => {
+            watch_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_risk_watch(
+    storage: &dyn Storage,
+    watcher: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<RiskWatch>> {
+    let watch_bucket: ReadonlyBucket<RiskWatch> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_RISK_WATCH, watcher.as_slice()]);
+    watch_bucket.may_load(borrower.as_slice())
+}
+
+pub fn read_risk_watches(
+    storage: &dyn Storage,
+    watcher: &CanonicalAddr,
+) -> StdResult<Vec<(CanonicalAddr, RiskWatch)>> {
+    let watch_bucket: ReadonlyBucket<RiskWatch> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_RISK_WATCH, watcher.as_slice()]);
+    watch_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+pub fn store_exclusive_liquidation(
+    storage: &mut dyn Storage,
+    exclusive_liquidation: Option<ExclusiveLiquidationInfo>,
+) -> StdResult<()> {
+    match exclusive_liquidation {
+        Some(exclusive_liquidation) => {
+            Singleton::new(storage, KEY_EXCLUSIVE_LIQUIDATION).save(&exclusive_liquidation)
+        }
+        None => {
+            Singleton::<ExclusiveLiquidationInfo>::new(storage, KEY_EXCLUSIVE_LIQUIDATION).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_exclusive_liquidation(
+    storage: &dyn Storage,
+) -> StdResult<Option<ExclusiveLiquidationInfo>> {
+    ReadonlySingleton::new(storage, KEY_EXCLUSIVE_LIQUIDATION).may_load()
+}
+
+pub fn store_gradual_liquidation(
+    storage: &mut dyn Storage,
+    gradual_liquidation: Option<GradualLiquidation>,
+) -> StdResult<()> {
+    match gradual_liquidation {
+        Some(gradual_liquidation) => {
+            Singleton::new(storage, KEY_GRADUAL_LIQUIDATION).save(&gradual_liquidation)
+        }
+        None => {
+            Singleton::<GradualLiquidation>::new(storage, KEY_GRADUAL_LIQUIDATION).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_gradual_liquidation(storage: &dyn Storage) -> StdResult<Option<GradualLiquidation>> {
+    ReadonlySingleton::new(storage, KEY_GRADUAL_LIQUIDATION).may_load()
+}
+
+pub fn store_liquidation_plan(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    liquidation_plan: Option<LiquidationPlan>,
+) -> StdResult<()> {
+    let mut plan_bucket: Bucket<LiquidationPlan> = Bucket::new(storage, PREFIX_LIQUIDATION_PLAN);
+    match liquidation_plan {
+        Some(liquidation_plan) => plan_bucket.save(borrower.as_slice(), &liquidation_plan),
+        None => {
+            plan_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_liquidation_plan(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<LiquidationPlan>> {
+    let plan_bucket: ReadonlyBucket<LiquidationPlan> =
+        ReadonlyBucket::new(storage, PREFIX_LIQUIDATION_PLAN);
+    plan_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_seizure_order(
+    storage: &mut dyn Storage,
+    seizure_order: Option<SeizureOrder>,
+) -> StdResult<()> {
+    match seizure_order {
+        Some(seizure_order) => Singleton::new(storage, KEY_SEIZURE_ORDER).save(&seizure_order),
+        None => {
+            Singleton::<SeizureOrder>::new(storage, KEY_SEIZURE_ORDER).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_seizure_order(storage: &dyn Storage) -> StdResult<Option<SeizureOrder>> {
+    ReadonlySingleton::new(storage, KEY_SEIZURE_ORDER).may_load()
+}
+
+pub fn store_seizure_preference(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    collaterals: Option<Vec<CanonicalAddr>>,
+) -> StdResult<()> {
+    let mut preference_bucket: Bucket<Vec<CanonicalAddr>> =
+        Bucket::new(storage, PREFIX_SEIZURE_PREFERENCE);
+    match collaterals {
+        Some(collaterals) => preference_bucket.save(borrower.as_slice(), &collaterals),
+        None => {
+            preference_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_seizure_preference(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let preference_bucket: ReadonlyBucket<Vec<CanonicalAddr>> =
+        ReadonlyBucket::new(storage, PREFIX_SEIZURE_PREFERENCE);
+    Ok(preference_bucket
+        .may_load(borrower.as_slice())?
+        .unwrap_or_default())
+}
+
+/// Store the height the position of the borrower was found liquidatable,
+/// or remove it with `None`
+pub fn store_liquidatable_since(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    height: Option<u64>,
+) -> StdResult<()> {
+    let mut since_bucket: Bucket<u64> = Bucket::new(storage, PREFIX_LIQUIDATABLE_SINCE);
+    match height {
+        Some(height) => since_bucket.save(borrower.as_slice(), &height),
+        None => {
+            since_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_liquidatable_since(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<u64>> {
+    let since_bucket: ReadonlyBucket<u64> = ReadonlyBucket::new(storage, PREFIX_LIQUIDATABLE_SINCE);
+    since_bucket.may_load(borrower.as_slice())
+}
+
+/// Locked amount of every collateral token ever locked
+pub fn read_total_collaterals(storage: &dyn Storage) -> StdResult<Tokens> {
+    let total_collateral_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+pub fn store_epoch_snapshot(
+    storage: &mut dyn Storage,
+    height: u64,
+    snapshot: &EpochSnapshot,
+) -> StdResult<()> {
+    let mut history_bucket: Bucket<EpochSnapshot> = Bucket::new(storage, PREFIX_EPOCH_HISTORY);
+    history_bucket.save(&height.to_be_bytes(), snapshot)
+}
+
+pub fn read_epoch_history(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EpochSnapshotResponse>> {
+    let history_bucket: ReadonlyBucket<EpochSnapshot> =
+        ReadonlyBucket::new(storage, PREFIX_EPOCH_HISTORY);
+
+    let limit = calc_limit(limit);
+    let start = calc_range_start(start_after);
+
+    history_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut height = [0u8; 8];
+            height.copy_from_slice(&k);
+
+            Ok(EpochSnapshotResponse {
+                height: u64::from_be_bytes(height),
+                time: v.time,
+                deposit_rate: v.deposit_rate,
+                interest_buffer: v.interest_buffer,
+                distributed_interest: v.distributed_interest,
+                total_collateral_value: v.total_collateral_value,
+                total_liabilities: v.total_liabilities,
+            })
+        })
+        .collect()
+}
+
+pub fn read_all_collaterals(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<CollateralsResponse>> {
+    let whitelist_bucket: ReadonlyBucket<Tokens> =
+        ReadonlyBucket::new(deps.storage, PREFIX_COLLATERALS);
+
+    let limit = calc_limit(limit);
+    let start = calc_range_start(start_after);
+
+    whitelist_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
+            let collaterals: Vec<(String, Uint256)> = v
+                .iter()
+                .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
+                .collect::<StdResult<Vec<(String, Uint256)>>>()?;
+
+            Ok(CollateralsResponse {
+                borrower,
+                collaterals,
+            })
+        })
+        .collect()
 }
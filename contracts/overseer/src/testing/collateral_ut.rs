@@ -1,5 +1,6 @@
 use crate::collateral::compute_borrow_limit;
 use crate::contract::{execute, instantiate};
+use crate::state::store_borrower_category;
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -85,7 +86,8 @@ fn proper_compute_borrow_limit() {
     );
     collaterals.push(token2);
 
-    let res = compute_borrow_limit(deps.as_ref(), &collaterals, None).unwrap();
+    let borrower_raw = deps.api.addr_canonicalize("addr0000").unwrap();
+    let res = compute_borrow_limit(deps.as_ref(), &env, &borrower_raw, &collaterals, None).unwrap();
     let vec: Vec<Decimal256> = vec![
         Decimal256::from_uint256(1000u128),
         Decimal256::from_uint256(2000u128),
@@ -94,3 +96,72 @@ fn proper_compute_borrow_limit() {
     let res2 = (Uint256::from(1800000u128), vec);
     assert_eq!(res, res2);
 }
+
+#[test]
+fn proper_compute_category_borrow_limit() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterCategory {
+        name: "luna".to_string(),
+        max_ltv: Decimal256::percent(80),
+        collateral_tokens: vec!["bluna".to_string()],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(1000u128),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let borrower_raw = deps.api.addr_canonicalize("addr0000").unwrap();
+    let collaterals: Tokens = vec![(
+        deps.api.addr_canonicalize("bluna").unwrap(),
+        Uint256::from(1000u128),
+    )];
+
+    let (borrow_limit, _) =
+        compute_borrow_limit(deps.as_ref(), &env, &borrower_raw, &collaterals, None).unwrap();
+    assert_eq!(borrow_limit, Uint256::from(600000u128));
+
+    store_borrower_category(
+        deps.as_mut().storage,
+        &borrower_raw,
+        Some("luna".to_string()),
+    )
+    .unwrap();
+    let (borrow_limit, _) =
+        compute_borrow_limit(deps.as_ref(), &env, &borrower_raw, &collaterals, None).unwrap();
+    assert_eq!(borrow_limit, Uint256::from(800000u128));
+}
@@ -9,9 +9,16 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
+use moneymarket::custody::{
+    CustodyCapability, InterfaceVersionResponse, CUSTODY_INTERFACE_VERSION,
+};
 use moneymarket::liquidation::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse};
-use moneymarket::oracle::PriceResponse;
+use moneymarket::liquidation_queue::{
+    AuctionConfig, AuctionConfigResponse, AuctionResponse, AuctionsResponse, BidFill,
+    BidPoolResponse, BidPoolsResponse, SimulateBidExecutionResponse,
+};
+use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, StateResponse};
+use moneymarket::oracle::{PriceKind, PriceResponse};
 use moneymarket::tokens::TokensHuman;
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
@@ -24,13 +31,19 @@ pub enum QueryMsg {
         block_height: Option<u64>,
         distributed_interest: Option<Uint256>,
     },
+    /// Query state to market contract
+    State { block_height: Option<u64> },
     /// Query loan amount to market contract
     BorrowerInfo {
         borrower: String,
         block_height: Option<u64>,
     },
     /// Query oracle price to oracle contract
-    Price { base: String, quote: String },
+    Price {
+        base: String,
+        quote: String,
+        price_kind: Option<PriceKind>,
+    },
     /// Query liquidation amount to liquidation model contract
     LiquidationAmount {
         borrow_amount: Uint256,
@@ -38,6 +51,35 @@ pub enum QueryMsg {
         collaterals: TokensHuman,
         collateral_prices: Vec<Decimal256>,
     },
+    /// Query liquidation amount of collaterals sold on the swap router
+    SwapLiquidationAmount {
+        borrow_amount: Uint256,
+        borrow_limit: Uint256,
+        collaterals: TokensHuman,
+        collateral_prices: Vec<Decimal256>,
+    },
+    /// Query interface version to custody contract
+    InterfaceVersion {},
+    /// Query auction config to liquidation contract
+    AuctionConfig { collateral_token: String },
+    /// Query active auctions of the borrower to liquidation contract
+    AuctionsByBorrower {
+        borrower: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    /// Query stable denom bid pools to liquidation contract
+    BidPoolsByCollateral {
+        collateral_token: String,
+        start_after: Option<u8>,
+        limit: Option<u8>,
+    },
+    /// Query bid execution simulation to liquidation contract
+    SimulateBidExecution {
+        collateral_token: String,
+        amount: Uint256,
+        price: Decimal256,
+    },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -59,9 +101,21 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     tax_querier: TaxQuerier,
     epoch_state_querier: EpochStateQuerier,
+    total_liabilities_querier: TotalLiabilitiesQuerier,
     oracle_price_querier: OraclePriceQuerier,
+    // prices of oracle contracts answering differently from the default one
+    contract_oracle_price_querier: HashMap<String, OraclePriceQuerier>,
+    // time-weighted average prices, the spot price when not set
+    twap_price_querier: HashMap<(String, String), Decimal256>,
+    price_confidence_querier: HashMap<(String, String), Decimal256>,
     loan_amount_querier: LoanAmountQuerier,
     liquidation_percent_querier: LiquidationPercentQuerier,
+    custody_interface_querier: CustodyInterfaceQuerier,
+    auction_config_querier: HashMap<String, AuctionConfig>,
+    // active auctions of each borrower
+    auctions_querier: HashMap<String, Vec<AuctionResponse>>,
+    // total bid amount and premium rate of each bid pool
+    bid_pools_querier: HashMap<String, Vec<(Uint256, Decimal256)>>,
 }
 
 #[derive(Clone, Default)]
@@ -139,6 +193,25 @@ pub(crate) fn epoch_state_to_map(
     epoch_state_map
 }
 
+#[derive(Clone, Default)]
+pub struct TotalLiabilitiesQuerier {
+    // this lets us iterate over all pairs that match the first string
+    total_liabilities: HashMap<String, Decimal256>,
+}
+
+impl TotalLiabilitiesQuerier {
+    pub fn new(total_liabilities: &[(&String, &Decimal256)]) -> Self {
+        let mut total_liabilities_map: HashMap<String, Decimal256> = HashMap::new();
+        for (market_contract, total_liabilities) in total_liabilities.iter() {
+            total_liabilities_map.insert((*market_contract).clone(), **total_liabilities);
+        }
+
+        TotalLiabilitiesQuerier {
+            total_liabilities: total_liabilities_map,
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct LoanAmountQuerier {
     // this lets us iterate over all pairs that match the first string
@@ -163,6 +236,25 @@ pub(crate) fn borrower_amount_to_map(
     borrower_amount_map
 }
 
+#[derive(Clone, Default)]
+pub struct CustodyInterfaceQuerier {
+    // custody contracts not in the map implement the full interface
+    custody_interface: HashMap<String, InterfaceVersionResponse>,
+}
+
+impl CustodyInterfaceQuerier {
+    pub fn new(custody_interface: &[(&String, &InterfaceVersionResponse)]) -> Self {
+        let mut custody_interface_map: HashMap<String, InterfaceVersionResponse> = HashMap::new();
+        for (custody_contract, custody_interface) in custody_interface.iter() {
+            custody_interface_map.insert((*custody_contract).clone(), (*custody_interface).clone());
+        }
+
+        CustodyInterfaceQuerier {
+            custody_interface: custody_interface_map,
+        }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -248,6 +340,33 @@ impl WasmMockQuerier {
                             request: msg.as_slice().into(),
                         }),
                     },
+                    QueryMsg::State { block_height: _ } => {
+                        match self
+                            .total_liabilities_querier
+                            .total_liabilities
+                            .get(contract_addr)
+                        {
+                            Some(v) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&StateResponse {
+                                    total_liabilities: *v,
+                                    total_reserves: Decimal256::zero(),
+                                    last_interest_updated: 0u64,
+                                    last_reward_updated: 0u64,
+                                    global_interest_index: Decimal256::one(),
+                                    global_reward_index: Decimal256::zero(),
+                                    anc_emission_rate: Decimal256::zero(),
+                                    global_deposit_reward_index: Decimal256::zero(),
+                                    anc_deposit_emission_rate: Decimal256::zero(),
+                                    prev_aterra_supply: Uint256::zero(),
+                                    prev_exchange_rate: Decimal256::one(),
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No market state exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
                     QueryMsg::BorrowerInfo {
                         borrower,
                         block_height: _,
@@ -266,13 +385,34 @@ impl WasmMockQuerier {
                             request: msg.as_slice().into(),
                         }),
                     },
-                    QueryMsg::Price { base, quote } => {
-                        match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
+                    QueryMsg::Price {
+                        base,
+                        quote,
+                        price_kind,
+                    } => {
+                        let oracle_price_querier = self
+                            .contract_oracle_price_querier
+                            .get(contract_addr)
+                            .unwrap_or(&self.oracle_price_querier);
+                        let pair = (base, quote);
+                        match oracle_price_querier.oracle_price.get(&pair) {
                             Some(v) => {
+                                let twap_rate = match price_kind {
+                                    Some(PriceKind::Twap) | Some(PriceKind::Both) => {
+                                        Some(*self.twap_price_querier.get(&pair).unwrap_or(&v.0))
+                                    }
+                                    _ => None,
+                                };
+                                let rate = match price_kind {
+                                    Some(PriceKind::Twap) => twap_rate.unwrap(),
+                                    _ => v.0,
+                                };
                                 SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
-                                    rate: v.0,
+                                    rate,
                                     last_updated_base: v.1,
                                     last_updated_quote: v.2,
+                                    twap_rate,
+                                    confidence: self.price_confidence_querier.get(&pair).cloned(),
                                 })))
                             }
                             None => SystemResult::Err(SystemError::InvalidRequest {
@@ -286,6 +426,12 @@ impl WasmMockQuerier {
                         borrow_limit,
                         collaterals,
                         collateral_prices: _,
+                    }
+                    | QueryMsg::SwapLiquidationAmount {
+                        borrow_amount,
+                        borrow_limit,
+                        collaterals,
+                        collateral_prices: _,
                     } => {
                         match self
                             .liquidation_percent_querier
@@ -317,6 +463,84 @@ impl WasmMockQuerier {
                             }),
                         }
                     }
+                    QueryMsg::InterfaceVersion {} => {
+                        let res = match self
+                            .custody_interface_querier
+                            .custody_interface
+                            .get(contract_addr)
+                        {
+                            Some(v) => v.clone(),
+                            None => InterfaceVersionResponse {
+                                version: CUSTODY_INTERFACE_VERSION,
+                                capabilities: vec![
+                                    CustodyCapability::LockCollateral,
+                                    CustodyCapability::UnlockCollateral,
+                                    CustodyCapability::LiquidateCollateral,
+                                    CustodyCapability::DistributeRewards,
+                                    CustodyCapability::MigrateCollateral,
+                                ],
+                            },
+                        };
+                        SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                    }
+                    QueryMsg::SimulateBidExecution {
+                        collateral_token,
+                        amount,
+                        price,
+                    } => SystemResult::Ok(ContractResult::from(to_binary(
+                        &SimulateBidExecutionResponse {
+                            collateral_token,
+                            collateral_amount: amount,
+                            repay_amount: amount * price,
+                            bid_fee: Uint256::zero(),
+                            liquidator_fee: Uint256::zero(),
+                            fills: vec![BidFill {
+                                premium_rate: Decimal256::zero(),
+                                collateral_amount: amount,
+                                stable_amount: amount * price,
+                            }],
+                        },
+                    ))),
+                    QueryMsg::AuctionConfig { collateral_token } => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&AuctionConfigResponse {
+                            auction_config: self
+                                .auction_config_querier
+                                .get(&collateral_token)
+                                .cloned(),
+                            collateral_token,
+                        })))
+                    }
+                    QueryMsg::AuctionsByBorrower {
+                        borrower,
+                        start_after: _,
+                        limit: _,
+                    } => SystemResult::Ok(ContractResult::from(to_binary(&AuctionsResponse {
+                        auctions: self
+                            .auctions_querier
+                            .get(&borrower)
+                            .cloned()
+                            .unwrap_or_default(),
+                    }))),
+                    QueryMsg::BidPoolsByCollateral {
+                        collateral_token,
+                        start_after: _,
+                        limit: _,
+                    } => SystemResult::Ok(ContractResult::from(to_binary(&BidPoolsResponse {
+                        bid_pools: self
+                            .bid_pools_querier
+                            .get(&collateral_token)
+                            .cloned()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(total_bid_amount, premium_rate)| BidPoolResponse {
+                                sum_snapshot: Decimal256::zero(),
+                                total_share: Decimal256::zero(),
+                                total_bid_amount,
+                                premium_rate,
+                                current_epoch: Uint128::zero(),
+                            })
+                            .collect(),
+                    }))),
                 }
             }
             _ => self.base.handle_query(request),
@@ -330,12 +554,24 @@ impl WasmMockQuerier {
             base,
             tax_querier: TaxQuerier::default(),
             epoch_state_querier: EpochStateQuerier::default(),
+            total_liabilities_querier: TotalLiabilitiesQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
+            contract_oracle_price_querier: HashMap::new(),
+            twap_price_querier: HashMap::new(),
+            price_confidence_querier: HashMap::new(),
             loan_amount_querier: LoanAmountQuerier::default(),
             liquidation_percent_querier: LiquidationPercentQuerier::default(),
+            custody_interface_querier: CustodyInterfaceQuerier::default(),
+            auction_config_querier: HashMap::new(),
+            auctions_querier: HashMap::new(),
+            bid_pools_querier: HashMap::new(),
         }
     }
 
+    pub fn update_balance(&mut self, addr: &str, balance: Vec<Coin>) {
+        self.base.update_balance(addr, balance);
+    }
+
     // configure the tax mock querier
     pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
@@ -345,6 +581,10 @@ impl WasmMockQuerier {
         self.epoch_state_querier = EpochStateQuerier::new(epoch_state);
     }
 
+    pub fn with_total_liabilities(&mut self, total_liabilities: &[(&String, &Decimal256)]) {
+        self.total_liabilities_querier = TotalLiabilitiesQuerier::new(total_liabilities);
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn with_oracle_price(
         &mut self,
@@ -353,6 +593,32 @@ impl WasmMockQuerier {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn with_contract_oracle_price(
+        &mut self,
+        oracle_contract: &str,
+        oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+    ) {
+        self.contract_oracle_price_querier.insert(
+            oracle_contract.to_string(),
+            OraclePriceQuerier::new(oracle_price),
+        );
+    }
+
+    pub fn with_price_confidence(&mut self, confidence: &[(&(String, String), &Decimal256)]) {
+        for (base_quote, confidence) in confidence.iter() {
+            self.price_confidence_querier
+                .insert((*base_quote).clone(), **confidence);
+        }
+    }
+
+    pub fn with_twap_price(&mut self, twap_price: &[(&(String, String), &Decimal256)]) {
+        for (base_quote, twap_price) in twap_price.iter() {
+            self.twap_price_querier
+                .insert((*base_quote).clone(), **twap_price);
+        }
+    }
+
     pub fn with_loan_amount(&mut self, loan_amount: &[(&String, &Uint256)]) {
         self.loan_amount_querier = LoanAmountQuerier::new(loan_amount);
     }
@@ -360,4 +626,32 @@ impl WasmMockQuerier {
     pub fn with_liquidation_percent(&mut self, liquidation_percent: &[(&String, &Decimal256)]) {
         self.liquidation_percent_querier = LiquidationPercentQuerier::new(liquidation_percent);
     }
+
+    pub fn with_custody_interface(
+        &mut self,
+        custody_interface: &[(&String, &InterfaceVersionResponse)],
+    ) {
+        self.custody_interface_querier = CustodyInterfaceQuerier::new(custody_interface);
+    }
+
+    pub fn with_auction_config(&mut self, auction_config: &[(&String, &AuctionConfig)]) {
+        for (collateral_token, auction_config) in auction_config.iter() {
+            self.auction_config_querier
+                .insert((*collateral_token).clone(), (*auction_config).clone());
+        }
+    }
+
+    pub fn with_auctions(&mut self, auctions: &[(&String, &[AuctionResponse])]) {
+        for (borrower, auctions) in auctions.iter() {
+            self.auctions_querier
+                .insert((*borrower).clone(), auctions.to_vec());
+        }
+    }
+
+    pub fn with_bid_pools(&mut self, bid_pools: &[(&String, &[(Uint256, Decimal256)])]) {
+        for (collateral_token, bid_pools) in bid_pools.iter() {
+            self.bid_pools_querier
+                .insert((*collateral_token).clone(), bid_pools.to_vec());
+        }
+    }
 }
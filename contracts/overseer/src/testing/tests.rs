@@ -1,4 +1,4 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, query, reply};
 use crate::error::ContractError;
 use crate::querier::query_epoch_state;
 use crate::state::{read_epoch_state, store_epoch_state, EpochState};
@@ -7,20 +7,48 @@ use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal,
-    SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, BankMsg, Binary, CanonicalAddr, Coin, ContractResult,
+    CosmosMsg, Decimal, Deps, Reply, ReplyOn, StdError, StdResult, SubMsg, SubMsgExecutionResponse,
+    Uint128, WasmMsg,
 };
 
-use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::custody::{
+    CustodyCapability, ExecuteMsg as CustodyExecuteMsg, InterfaceVersionResponse,
+    CUSTODY_INTERFACE_VERSION,
+};
+use moneymarket::liquidation_queue::{AuctionConfig, AuctionResponse, BidFill};
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::oracle::PriceKind;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    AllCollateralsResponse, AutoDeleverageResponse, BorrowLimitResponse, BorrowLimitsResponse,
+    BorrowerCategoryResponse, CategoryResponse, CategoryShareLimitResponse, CollateralDetail,
+    CollateralOracleResponse, CollateralsDetailedResponse, CollateralsResponse,
+    ConfidenceThresholdResponse, ConfigResponse, CreditLine, CreditLineResponse,
+    CustodyMigrationResponse, DebtCeilingResponse, DistributionCapResponse,
+    DynamicRateConfigResponse, EpochHistoryResponse, EpochProgressResponse, EpochSnapshotResponse,
+    EpochStep, ExclusiveLiquidation, ExclusiveLiquidationResponse, ExecuteMsg, FreezeStateResponse,
+    GradualLiquidation, GradualLiquidationResponse, InstantiateMsg, Intent,
+    LiquidationPlanResponse, LiquidationThrottleResponse, LiquidationWindowResponse,
+    MassSimulateResponse, MassSimulatedCollateral, MaxPriceAgeResponse, MinLockDurationResponse,
+    OperatorPermission, OperatorResponse, OracleQuorum, OracleQuorumResponse, PriceKindResponse,
+    ProjectedRunwayResponse, QueryMsg, RiskNotificationMsg, RiskNotificationResponse,
+    RiskWatchResponse, RiskWatcherMsg, RiskWatchesResponse, RiskyPositionResponse,
+    RiskyPositionsResponse, SeizureOrder, SeizurePreferenceResponse, SimulateLiquidationResponse,
+    SimulatedCollateralLiquidation, UnlockBufferResponse, ValidateIntentResponse,
+    WhitelistProposalsResponse, WhitelistResponse, WhitelistResponseElem, WhitelistStatus,
+    WhitelistStatusResponse,
 };
 use moneymarket::querier::deduct_tax;
 
 use std::str::FromStr;
 
+// MsgInstantiateContractResponse data of an instantiate reply
+fn instantiate_reply_data(contract_address: &str) -> Binary {
+    let mut data = vec![0x0a, contract_address.len() as u8];
+    data.extend_from_slice(contract_address.as_bytes());
+    Binary::from(data)
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -612,6 +640,8 @@ fn update_epoch_state() {
         &"market".to_string(),
         &(Uint256::from(1000000u64), Decimal256::percent(120)),
     )]);
+    deps.querier
+        .with_total_liabilities(&[(&"market".to_string(), &Decimal256::zero())]);
 
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
@@ -1155,6 +1185,7 @@ fn liquidate_collateral() {
 
     let msg = ExecuteMsg::LiquidateCollateral {
         borrower: "addr0000".to_string(),
+        max_spread: None,
     };
     let info = mock_info("addr0001", &[]);
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
@@ -1176,6 +1207,7 @@ fn liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(100000u64),
+                    max_spread: None,
                 })
                 .unwrap(),
             })),
@@ -1186,6 +1218,7 @@ fn liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(10000u64),
+                    max_spread: None,
                 })
                 .unwrap(),
             })),
@@ -1221,3 +1254,6027 @@ fn liquidate_collateral() {
         }
     );
 }
+
+#[test]
+fn liquidate_collateral_with_swap() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000001u64))]);
+
+    // the custody sells the collateral on the DEX, which repays the loan right away
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        max_spread: Some(Decimal256::percent(5)),
+    };
+    let res = execute(deps.as_mut(), env, mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                    max_spread: Some(Decimal256::percent(5)),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+}
+
+#[test]
+fn self_liquidate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::SelfLiquidate {
+        collateral_token: "bluna".to_string(),
+        amount: Uint256::from(1000001u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::SelfLiquidationExceedsLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SelfLiquidate {
+        collateral_token: "bluna".to_string(),
+        amount: Uint256::from(400000u64),
+    };
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::SelfLiquidationWithoutLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // safe positions can be self-liquidated
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(100000u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::SelfLiquidateCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(400000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res: CollateralsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Collaterals {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(600000u64))]
+    );
+}
+
+#[test]
+fn query_borrow_limits() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(2000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimits {
+            borrowers: vec![
+                "addr0000".to_string(),
+                "addr0001".to_string(),
+                "addr0002".to_string(),
+            ],
+            block_time: Some(env.block.time.seconds()),
+        },
+    )
+    .unwrap();
+    let borrow_limits_res: BorrowLimitsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrow_limits_res,
+        BorrowLimitsResponse {
+            borrow_limits: vec![
+                BorrowLimitResponse {
+                    borrower: "addr0000".to_string(),
+                    borrow_limit: Uint256::from(600000000u64),
+                },
+                BorrowLimitResponse {
+                    borrower: "addr0001".to_string(),
+                    borrow_limit: Uint256::from(1200000000u64),
+                },
+                BorrowLimitResponse {
+                    borrower: "addr0002".to_string(),
+                    borrow_limit: Uint256::zero(),
+                },
+            ]
+        }
+    );
+
+    // too many borrowers in a single query
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::BorrowLimits {
+            borrowers: (0..31).map(|i| format!("addr{:04}", i)).collect(),
+            block_time: None,
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn freeze_protocol() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let mut env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // only owner can set the guardian
+    let msg = ExecuteMsg::UpdateGuardian {
+        guardian: Some("guardian".to_string()),
+        unfreeze_delay: Some(3600u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // only guardian or owner can freeze
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::FreezeProtocol {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let guardian_info = mock_info("guardian", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        guardian_info.clone(),
+        ExecuteMsg::FreezeProtocol {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "freeze_protocol"),
+            attr("frozen_at", env.block.time.seconds().to_string()),
+        ]
+    );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::FreezeState {}).unwrap();
+    let freeze_state_res: FreezeStateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        freeze_state_res,
+        FreezeStateResponse {
+            guardian: Some("guardian".to_string()),
+            unfreeze_delay: 3600u64,
+            frozen: true,
+            frozen_at: Some(env.block.time.seconds()),
+        }
+    );
+
+    // all collateral movements are blocked
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::ProtocolFrozen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::ProtocolFrozen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        max_spread: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::ProtocolFrozen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::MigrateCollateral {
+        borrower: None,
+        old_custody: "custody_bluna".to_string(),
+        new_custody: "custody_bluna_v2".to_string(),
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg);
+    match res {
+        Err(ContractError::ProtocolFrozen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the delay cannot be changed while frozen
+    let msg = ExecuteMsg::UpdateGuardian {
+        guardian: None,
+        unfreeze_delay: Some(0u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::ProtocolFrozen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // cannot unfreeze before the delay
+    let frozen_at = env.block.time.seconds();
+    env.block.time = env.block.time.plus_seconds(3599u64);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        guardian_info.clone(),
+        ExecuteMsg::UnfreezeProtocol {},
+    );
+    match res {
+        Err(ContractError::UnfreezeDelayNotPassed(t)) => assert_eq!(t, frozen_at + 3600u64),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        guardian_info,
+        ExecuteMsg::UnfreezeProtocol {},
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+}
+
+#[test]
+fn borrower_category() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // only owner can register category
+    let msg = ExecuteMsg::RegisterCategory {
+        name: "luna".to_string(),
+        max_ltv: Decimal256::percent(80),
+        collateral_tokens: vec!["bluna".to_string()],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Category {
+            name: "luna".to_string(),
+        },
+    )
+    .unwrap();
+    let category_res: CategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        category_res,
+        CategoryResponse {
+            name: "luna".to_string(),
+            max_ltv: Decimal256::percent(80),
+            collateral_tokens: vec!["bluna".to_string()],
+        }
+    );
+
+    let borrower_info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(1000000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(700000000u64))]);
+
+    // batom is not part of the category
+    let msg = ExecuteMsg::SetBorrowerCategory {
+        category: Some("luna".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        borrower_info.clone(),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::CollateralNotInCategory {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        borrower_info.clone(),
+        unlock_msg,
+    )
+    .unwrap();
+
+    let _res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerCategory {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_category_res: BorrowerCategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower_category_res.category, Some("luna".to_string()));
+
+    // borrow limit = 1000 * 1000000 * 0.8
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(800000000u64));
+
+    // cannot lock collateral outside of the category
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg);
+    match res {
+        Err(ContractError::CollateralNotInCategory {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // cannot leave the category when the loan exceeds the default borrow limit
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(700000000u64))]);
+    let msg = ExecuteMsg::SetBorrowerCategory { category: None };
+    let res = execute(deps.as_mut(), env, borrower_info, msg);
+    match res {
+        Err(ContractError::CategoryChangeTooLarge(600000000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn execute_epoch_steps() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // only owner can update keeper reward
+    let msg = ExecuteMsg::UpdateKeeperReward {
+        keeper_reward: Uint256::from(1000000u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let keeper_info = mock_info("keeper", &[]);
+    let msg = ExecuteMsg::ExecuteEpochStep {};
+    let res = execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg.clone());
+    match res {
+        Err(ContractError::EpochNotPassed(12345)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    let keeper_reward_msg = SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+        to_address: "keeper".to_string(),
+        amount: vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    }));
+
+    // step 1: compute deposit rate
+    let res = execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![keeper_reward_msg.clone()]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_epoch_step"),
+            attr("step", "compute_deposit_rate"),
+            attr("deposit_rate", "0.000002314814814814"),
+            attr("keeper_reward", "1000000"),
+        ]
+    );
+
+    // cannot run all operations at once while an epoch is in progress
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecuteEpochOperations {},
+    );
+    match res {
+        Err(ContractError::EpochInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EpochProgress {}).unwrap();
+    let progress_res: EpochProgressResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        progress_res,
+        EpochProgressResponse {
+            next_step: EpochStep::DistributeInterestBuffer,
+            keeper_reward: Uint256::from(1000000u64),
+        }
+    );
+
+    // step 2: distribute interest buffer
+    // accrued_buffer = 10,000,000,000 + 1,000,000 (paid keeper reward)
+    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,200,000
+    let res = execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2_000_200_000u128),
+                }],
+            })),
+            keeper_reward_msg.clone(),
+        ]
+    );
+
+    // step 3: distribute rewards
+    let res = execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
+            })),
+            keeper_reward_msg.clone(),
+        ]
+    );
+
+    // step 4: update epoch state
+    // interest_buffer = 10,001,000,000 - 2,000,200,000 - 4 * 1,000,000
+    let res = execute(deps.as_mut(), env.clone(), keeper_info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(7_996_800_000u128),
+                    distributed_interest: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+            keeper_reward_msg,
+        ]
+    );
+
+    let res = query(deps.as_ref(), env, QueryMsg::EpochProgress {}).unwrap();
+    let progress_res: EpochProgressResponse = from_binary(&res).unwrap();
+    assert_eq!(progress_res.next_step, EpochStep::ComputeDepositRate);
+}
+
+#[test]
+fn dynamic_target_deposit_rate() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // min bigger than max
+    let msg = ExecuteMsg::UpdateDynamicRateConfig {
+        min_deposit_rate: Decimal256::permille(10),
+        max_deposit_rate: Decimal256::permille(1),
+        target_runway: 100000u64,
+        max_change: Decimal256::permille(1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidDynamicRateBounds {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateDynamicRateConfig {
+        min_deposit_rate: Decimal256::permille(1),
+        max_deposit_rate: Decimal256::permille(10),
+        target_runway: 100000u64,
+        max_change: Decimal256::permille(1),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::DynamicRateConfig {}).unwrap();
+    let dynamic_rate_config_res: Option<DynamicRateConfigResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        dynamic_rate_config_res,
+        Some(DynamicRateConfigResponse {
+            min_deposit_rate: Decimal256::permille(1),
+            max_deposit_rate: Decimal256::permille(10),
+            target_runway: 100000u64,
+            max_change: Decimal256::permille(1),
+        })
+    );
+
+    let mut env = mock_env();
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+    deps.querier
+        .with_total_liabilities(&[(&"market".to_string(), &Decimal256::zero())]);
+
+    // runway = 10,000,000,000 / 1,000,000 = 10,000 epochs
+    // 0.005 * 10,000 / 100,000 = 0.0005
+    // bounded by max_change => 0.004
+    let msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(10000000000u128),
+        distributed_interest: Uint256::from(1000000u128),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
+                deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+                target_deposit_rate: Decimal256::permille(4),
+                threshold_deposit_rate: Decimal256::permille(3),
+                distributed_interest: Uint256::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("target_deposit_rate", "0.004")
+    );
+
+    // the threshold deposit rate is left to governance
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.target_deposit_rate, Decimal256::permille(4));
+    assert_eq!(config_res.threshold_deposit_rate, Decimal256::permille(3));
+
+    let res = query(deps.as_ref(), env, QueryMsg::ProjectedRunway {}).unwrap();
+    let runway_res: ProjectedRunwayResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        runway_res,
+        ProjectedRunwayResponse {
+            interest_buffer: Uint256::from(10000000000u128),
+            distributed_interest: Uint256::from(1000000u128),
+            runway: Some(Decimal256::from_uint256(10000u64)),
+            target_deposit_rate: Decimal256::permille(4),
+        }
+    );
+}
+
+#[test]
+fn whitelist_proposal() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let propose_msg = ExecuteMsg::ProposeWhitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_code_id: 12u64,
+        custody_init_msg: to_binary("custody_init").unwrap(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let bond = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000000u128),
+    }];
+
+    // proposals are disabled until the bond is set
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &bond),
+        propose_msg.clone(),
+    );
+    match res {
+        Err(ContractError::WhitelistProposalsDisabled {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateProposalBond {
+        proposal_bond: Uint256::from(1000000u64),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        propose_msg.clone(),
+    );
+    match res {
+        Err(ContractError::InsufficientProposalBond(1000000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // spam proposal is slashed
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &bond),
+        propose_msg.clone(),
+    )
+    .unwrap();
+    let msg = ExecuteMsg::RejectWhitelistProposal {
+        proposal_id: 1u64,
+        slash: true,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: bond.clone(),
+        }))]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &bond),
+        propose_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_whitelist"),
+            attr("proposal_id", "2"),
+            attr("proposer", "addr0001"),
+            attr("collateral_token", "bluna"),
+            attr("bond_amount", "1000000"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::WhitelistProposals {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let proposals_res: WhitelistProposalsResponse = from_binary(&res).unwrap();
+    assert_eq!(proposals_res.proposals.len(), 1);
+    assert_eq!(proposals_res.proposals[0].proposal_id, 2u64);
+    assert_eq!(proposals_res.proposals[0].proposer, "addr0001".to_string());
+
+    // only owner can approve
+    let msg = ExecuteMsg::ApproveWhitelistProposal { proposal_id: 2u64 };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Instantiate {
+                    admin: Some("owner".to_string()),
+                    code_id: 12u64,
+                    funds: vec![],
+                    label: "".to_string(),
+                    msg: to_binary("custody_init").unwrap(),
+                }),
+                1
+            ),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: bond,
+            }))
+        ]
+    );
+
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(instantiate_reply_data("custody_bluna")),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Whitelist {
+            collateral_token: Some("bluna".to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        whitelist_res,
+        WhitelistResponse {
+            elems: vec![WhitelistResponseElem {
+                name: "bluna".to_string(),
+                symbol: "bluna".to_string(),
+                max_ltv: Decimal256::percent(60),
+                custody_contract: "custody_bluna".to_string(),
+                collateral_token: "bluna".to_string(),
+            }]
+        }
+    );
+
+    // proposal is consumed
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+}
+
+#[test]
+fn whitelist_proposal_bonds_excluded_from_interest_buffer() {
+    // 10,000,000,000 accrued buffer + 1,000,000 proposal bond
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10001000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateProposalBond {
+        proposal_bond: Uint256::from(1000000u64),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ProposeWhitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_code_id: 12u64,
+        custody_init_msg: to_binary("custody_init").unwrap(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let bond = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000000u128),
+    }];
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &bond),
+        msg,
+    )
+    .unwrap();
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // the bond is not part of the accrued buffer
+    // anc_purchase_amount = 10,000,000,000 * 0.2 = 2,000,000,000
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(2_000_000_000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(8_000_000_000u128),
+                    distributed_interest: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    // collector was paid; the bond is still held
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(8001000000u128),
+        }],
+    );
+    deps.querier
+        .with_total_liabilities(&[(&"market".to_string(), &Decimal256::zero())]);
+    let msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(8_000_000_000u128),
+        distributed_interest: Uint256::zero(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // refunding the bond does not shrink the interest buffer
+    let msg = ExecuteMsg::RejectWhitelistProposal {
+        proposal_id: 1u64,
+        slash: false,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(8000000000u128),
+        }],
+    );
+
+    env.block.height += 86400u64;
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    // nothing accrued since the last epoch
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("anc_purchase_amount", "0")));
+}
+
+#[test]
+fn query_collaterals_detailed() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(2000000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(100u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(10u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 100 * 1000000 * 0.6 + 10 * 2000000 * 0.5 = 70,000,000
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(64000000u64))]);
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::CollateralsDetailed {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let detailed_res: CollateralsDetailedResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        detailed_res,
+        CollateralsDetailedResponse {
+            borrower: "addr0000".to_string(),
+            loan_amount: Uint256::from(64000000u64),
+            borrow_limit: Uint256::from(70000000u64),
+            collaterals: vec![
+                // (64,000,000 - 10,000,000) / (1,000,000 * 0.6)
+                CollateralDetail {
+                    collateral_token: "bluna".to_string(),
+                    amount: Uint256::from(1000000u64),
+                    price: Decimal256::from_ratio(100u64, 1u64),
+                    value: Uint256::from(100000000u64),
+                    max_ltv: Decimal256::percent(60),
+                    borrow_limit: Uint256::from(60000000u64),
+                    liquidation_price: Decimal256::from_ratio(90u64, 1u64),
+                },
+                // (64,000,000 - 60,000,000) / (2,000,000 * 0.5)
+                CollateralDetail {
+                    collateral_token: "batom".to_string(),
+                    amount: Uint256::from(2000000u64),
+                    price: Decimal256::from_ratio(10u64, 1u64),
+                    value: Uint256::from(20000000u64),
+                    max_ltv: Decimal256::percent(50),
+                    borrow_limit: Uint256::from(10000000u64),
+                    liquidation_price: Decimal256::from_ratio(4u64, 1u64),
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn auto_deleverage() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(100u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow_limit = 100 * 1000000 * 0.5 = 50,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(46000000u64))]);
+
+    let msg = ExecuteMsg::Deleverage {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::AutoDeleverageDisabled {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::SetAutoDeleverage {
+            trigger_ratio: Some(Decimal256::percent(80)),
+            target_ratio: Some(Decimal256::percent(90)),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidAutoDeleverage {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::SetAutoDeleverage {
+            trigger_ratio: Some(Decimal256::percent(90)),
+            target_ratio: Some(Decimal256::percent(80)),
+        },
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::AutoDeleverage {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let auto_deleverage_res: AutoDeleverageResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        auto_deleverage_res,
+        AutoDeleverageResponse {
+            borrower: "addr0000".to_string(),
+            trigger_ratio: Some(Decimal256::percent(90)),
+            target_ratio: Some(Decimal256::percent(80)),
+        }
+    );
+
+    // 44,000,000 / 50,000,000 = 0.88 < 0.9
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(44000000u64))]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::DeleverageNotTriggered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // above the borrow limit, regular liquidation applies
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(50000001u64))]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::CannotDeleverageUnsafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // (46,000,000 - 0.8 * 50,000,000) / (1 - 0.8 * 0.5) = 10,000,000 uusd
+    // = 100,000 bluna; leaves 36,000,000 / 45,000,000 = 0.8
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(46000000u64))]);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "keeper".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100000u64),
+                    max_spread: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![("bluna".to_string(), Uint256::from(900000u64))]
+        }
+    );
+
+    // opt out
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::SetAutoDeleverage {
+            trigger_ratio: None,
+            target_ratio: None,
+        },
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::AutoDeleverage {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let auto_deleverage_res: AutoDeleverageResponse = from_binary(&res).unwrap();
+    assert_eq!(auto_deleverage_res.trigger_ratio, None);
+}
+
+#[test]
+fn query_risky_positions() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001", "addr0002"] {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(10u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow_limit = 10 * 1000000 * 0.5 = 5,000,000 uusd for everyone
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(4500000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0002".to_string(), &Uint256::from(6000000u64)),
+    ]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RiskyPositions {
+            start_after: None,
+            limit: Some(2),
+            min_risk_ratio: Decimal256::percent(90),
+        },
+    )
+    .unwrap();
+    let risky_res: RiskyPositionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        risky_res,
+        RiskyPositionsResponse {
+            positions: vec![RiskyPositionResponse {
+                borrower: "addr0000".to_string(),
+                loan_amount: Uint256::from(4500000u64),
+                borrow_limit: Uint256::from(5000000u64),
+                risk_ratio: Decimal256::percent(90),
+            }],
+            last_borrower: Some("addr0001".to_string()),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RiskyPositions {
+            start_after: risky_res.last_borrower,
+            limit: Some(2),
+            min_risk_ratio: Decimal256::percent(90),
+        },
+    )
+    .unwrap();
+    let risky_res: RiskyPositionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        risky_res,
+        RiskyPositionsResponse {
+            positions: vec![RiskyPositionResponse {
+                borrower: "addr0002".to_string(),
+                loan_amount: Uint256::from(6000000u64),
+                borrow_limit: Uint256::from(5000000u64),
+                risk_ratio: Decimal256::percent(120),
+            }],
+            last_borrower: Some("addr0002".to_string()),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::RiskyPositions {
+            start_after: risky_res.last_borrower,
+            limit: None,
+            min_risk_ratio: Decimal256::percent(90),
+        },
+    )
+    .unwrap();
+    let risky_res: RiskyPositionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        risky_res,
+        RiskyPositionsResponse {
+            positions: vec![],
+            last_borrower: None,
+        }
+    );
+}
+
+#[test]
+fn borrow_limit_cache() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(2000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(10u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(2000000u64))]);
+
+    // borrow_limit = 10 * 1000000 * 0.5 = 5,000,000 uusd
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the cached limit is still safe to liquidate against
+    let liquidate_msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        max_spread: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        liquidate_msg.clone(),
+    );
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // price drops fed within the same block are picked up
+    // right away, even with the same update times
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(3u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // 3 * 1000000 * 0.5 = 1,500,000 uusd
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: Some(env.block.time.seconds()),
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(1500000u64));
+
+    let _res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        liquidate_msg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn debt_ceiling() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for token in ["bluna", "batom"] {
+        let msg = ExecuteMsg::Whitelist {
+            name: token.to_string(),
+            symbol: token.to_string(),
+            collateral_token: token.to_string(),
+            custody_contract: format!("custody_{}", token),
+            max_ltv: Decimal256::percent(50),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::UpdateDebtCeiling {
+        collateral_token: "bluna".to_string(),
+        debt_ceiling: Some(Uint256::from(5000000u64)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(1000000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(10u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(10u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // addr0000 borrow_limit = 5,000,000 (bluna) + 5,000,000 (batom)
+    // so the 6,000,000 loan puts 3,000,000 on each
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(6000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(3000000u64)),
+    ]);
+    let msg = ExecuteMsg::SyncBorrowerDebt {
+        borrower: "addr0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::DebtCeiling {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let debt_ceiling_res: DebtCeilingResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        debt_ceiling_res,
+        DebtCeilingResponse {
+            collateral_token: "bluna".to_string(),
+            debt_ceiling: Some(Uint256::from(5000000u64)),
+            collateral_debt: Uint256::from(3000000u64),
+        }
+    );
+
+    // 3,000,000 + 3,000,000 > 5,000,000
+    let msg = ExecuteMsg::SyncBorrowerDebt {
+        borrower: "addr0001".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("market", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::DebtCeilingExceeded(5000000u128)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(6000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(2000000u64)),
+    ]);
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+
+    // unlocking batom moves the whole loan of addr0000 onto bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(4000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(2000000u64)),
+    ]);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::DebtCeilingExceeded(5000000u128)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::DebtCeiling {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let debt_ceiling_res: DebtCeilingResponse = from_binary(&res).unwrap();
+    assert_eq!(debt_ceiling_res.collateral_debt, Uint256::from(5000000u64));
+}
+
+#[test]
+fn migrate_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(100u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(50u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(10u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::zero()),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
+
+    let msg = ExecuteMsg::MigrateCollateral {
+        borrower: None,
+        old_custody: "custody_bluna".to_string(),
+        new_custody: "custody_bluna_v2".to_string(),
+        limit: Some(1),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::MigrateCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100u64),
+                    new_custody: "custody_bluna_v2".to_string(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna_v2".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Whitelist {
+            collateral_token: Some("bluna".to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        whitelist_res.elems[0].custody_contract,
+        "custody_bluna_v2".to_string()
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CustodyMigration {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let migration_res: Option<CustodyMigrationResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        migration_res,
+        Some(CustodyMigrationResponse {
+            collateral_token: "bluna".to_string(),
+            old_custody: "custody_bluna".to_string(),
+            new_custody: "custody_bluna_v2".to_string(),
+        })
+    );
+
+    // not migrated yet, so addr0001 still uses the old custody
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(10u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0001".to_string(),
+                amount: Uint256::from(10u64),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // new borrowers go straight to the new custody
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(10u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0002", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna_v2".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                borrower: "addr0002".to_string(),
+                amount: Uint256::from(10u64),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // borrower migrates its own collateral
+    let msg = ExecuteMsg::MigrateCollateral {
+        borrower: Some("addr0001".to_string()),
+        old_custody: "custody_bluna".to_string(),
+        new_custody: "custody_bluna_v2".to_string(),
+        limit: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::MigrateCollateral {
+                    borrower: "addr0001".to_string(),
+                    amount: Uint256::from(40u64),
+                    new_custody: "custody_bluna_v2".to_string(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna_v2".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0001".to_string(),
+                    amount: Uint256::from(40u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // remaining borrowers are already migrated
+    let msg = ExecuteMsg::MigrateCollateral {
+        borrower: None,
+        old_custody: "custody_bluna".to_string(),
+        new_custody: "custody_bluna_v2".to_string(),
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(res.attributes[3], attr("done", "false"));
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.attributes[3], attr("done", "true"));
+
+    // the migration is over
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CustodyMigration {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let migration_res: Option<CustodyMigrationResponse> = from_binary(&res).unwrap();
+    assert_eq!(migration_res, None);
+
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::CustodyMigrationNotFound {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn whitelist_status() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let mut env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds() + 1000u64,
+            env.block.time.seconds() + 1000u64,
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let msg = ExecuteMsg::UpdateWhitelistStatus {
+        collateral_token: "bluna".to_string(),
+        status: WhitelistStatus::DepositPaused,
+        ltv_ramp_duration: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_whitelist_status"),
+            attr("collateral_token", "bluna"),
+            attr("status", "deposit_paused"),
+        ]
+    );
+
+    // paused collaterals cannot be locked
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::CollateralNotActive {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // delisting must go through deprecation
+    let msg = ExecuteMsg::UpdateWhitelistStatus {
+        collateral_token: "bluna".to_string(),
+        status: WhitelistStatus::Delisted,
+        ltv_ramp_duration: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidWhitelistStatusTransition {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateWhitelistStatus {
+        collateral_token: "bluna".to_string(),
+        status: WhitelistStatus::Deprecated,
+        ltv_ramp_duration: Some(1000u64),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let ramp_start = env.block.time.seconds();
+
+    // halfway through the ramp half of the LTV is left
+    env.block.time = env.block.time.plus_seconds(500);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::WhitelistStatus {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let status_res: WhitelistStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        status_res,
+        WhitelistStatusResponse {
+            collateral_token: "bluna".to_string(),
+            status: WhitelistStatus::Deprecated,
+            max_ltv: Decimal256::percent(30),
+            ltv_ramp_start: Some(ramp_start),
+            ltv_ramp_end: Some(ramp_start + 1000u64),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(300000u64));
+
+    let msg = ExecuteMsg::UpdateWhitelistStatus {
+        collateral_token: "bluna".to_string(),
+        status: WhitelistStatus::Delisted,
+        ltv_ramp_duration: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::LtvRampNotFinished(ramp_end)) => {
+            assert_eq!(ramp_end, ramp_start + 1000u64)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(500);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::zero());
+}
+
+#[test]
+fn epoch_history() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for token in ["bluna", "batom"] {
+        let msg = ExecuteMsg::Whitelist {
+            name: token.to_string(),
+            symbol: token.to_string(),
+            collateral_token: token.to_string(),
+            custody_contract: format!("custody_{}", token),
+            max_ltv: Decimal256::percent(50),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(2000000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    // stale prices are still used for the snapshot
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::from_ratio(10u64, 1u64), 0u64, 0u64),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(Decimal256::from_ratio(5u64, 1u64), 0u64, 0u64),
+        ),
+    ]);
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+    deps.querier.with_total_liabilities(&[(
+        &"market".to_string(),
+        &Decimal256::from_uint256(Uint256::from(15000000u64)),
+    )]);
+
+    let mut env = mock_env();
+    env.block.height += 86400u64;
+    let msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(10000000000u128),
+        distributed_interest: Uint256::from(1000000u128),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    let first_height = env.block.height;
+
+    // unlocked collaterals leave the total
+    deps.querier
+        .with_loan_amount(&[(&"addr0001".to_string(), &Uint256::zero())]);
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    env.block.height += 86400u64;
+    let msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(9000000000u128),
+        distributed_interest: Uint256::zero(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::EpochHistory {
+            start_after: None,
+            limit: Some(1u32),
+        },
+    )
+    .unwrap();
+    let history_res: EpochHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        history_res,
+        EpochHistoryResponse {
+            snapshots: vec![EpochSnapshotResponse {
+                height: first_height,
+                time: env.block.time.seconds(),
+                deposit_rate: Decimal256::from_ratio(1u64, 432000u64),
+                interest_buffer: Uint256::from(10000000000u128),
+                distributed_interest: Uint256::from(1000000u128),
+                // 2,000,000 * 10 + 2,000,000 * 5
+                total_collateral_value: Uint256::from(30000000u64),
+                total_liabilities: Decimal256::from_uint256(Uint256::from(15000000u64)),
+            }],
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::EpochHistory {
+            start_after: Some(first_height),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history_res: EpochHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(history_res.snapshots.len(), 1);
+    assert_eq!(history_res.snapshots[0].height, env.block.height);
+    assert_eq!(
+        history_res.snapshots[0].total_collateral_value,
+        Uint256::from(20000000u64)
+    );
+}
+
+#[test]
+fn max_price_age() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // the price is 30 seconds old
+    let last_updated = env.block.time.seconds() - 30u64;
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(Decimal256::one(), last_updated, last_updated),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateMaxPriceAge {
+        collateral_token: "bluna".to_string(),
+        max_price_age: Some(20u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::MaxPriceAge {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let max_price_age_res: MaxPriceAgeResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        max_price_age_res,
+        MaxPriceAgeResponse {
+            collateral_token: "bluna".to_string(),
+            max_price_age: 20u64,
+        }
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::PriceTooOld(collateral_token, updated)) => {
+            assert_eq!(collateral_token, "bluna");
+            assert_eq!(updated, last_updated);
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::PriceTooOld(_, _)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: Some(env.block.time.seconds()),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!(
+            "Generic error: Price of bluna is too old; last updated: {}",
+            last_updated
+        )
+    );
+
+    // back to the 60 seconds price timeframe
+    let msg = ExecuteMsg::UpdateMaxPriceAge {
+        collateral_token: "bluna".to_string(),
+        max_price_age: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+}
+
+#[test]
+fn price_kind() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // the spot price spiked above the TWAP
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(2u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_twap_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &Decimal256::one(),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let borrow_limit = |deps: Deps| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        from_binary::<BorrowLimitResponse>(&res)
+            .unwrap()
+            .borrow_limit
+    };
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(1000000u64));
+
+    let msg = ExecuteMsg::UpdatePriceKind {
+        collateral_token: "bluna".to_string(),
+        price_kind: Some(PriceKind::Twap),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_price_kind"),
+            attr("collateral_token", "bluna"),
+            attr("price_kind", "twap"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PriceKind {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let price_kind_res: PriceKindResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        price_kind_res,
+        PriceKindResponse {
+            collateral_token: "bluna".to_string(),
+            price_kind: PriceKind::Twap,
+        }
+    );
+
+    // the collateral is valued at the TWAP
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(500000u64));
+
+    // back to the spot price
+    let msg = ExecuteMsg::UpdatePriceKind {
+        collateral_token: "bluna".to_string(),
+        price_kind: None,
+    };
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(1000000u64));
+}
+
+#[test]
+fn confidence_threshold() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // the price is known within 10%
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(2u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_price_confidence(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &Decimal256::percent(20),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let borrow_limit = |deps: Deps| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        from_binary::<BorrowLimitResponse>(&res)
+            .unwrap()
+            .borrow_limit
+    };
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(1000000u64));
+
+    let msg = ExecuteMsg::UpdateConfidenceThreshold {
+        collateral_token: "bluna".to_string(),
+        confidence_threshold: Some(Decimal256::percent(5)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_confidence_threshold"),
+            attr("collateral_token", "bluna"),
+            attr("confidence_threshold", "0.05"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::ConfidenceThreshold {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let confidence_threshold_res: ConfidenceThresholdResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        confidence_threshold_res,
+        ConfidenceThresholdResponse {
+            collateral_token: "bluna".to_string(),
+            confidence_threshold: Some(Decimal256::percent(5)),
+        }
+    );
+
+    // the band is wider than the threshold, the collateral is valued at 1.8
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(900000u64));
+
+    // the band is within the threshold
+    let msg = ExecuteMsg::UpdateConfidenceThreshold {
+        collateral_token: "bluna".to_string(),
+        confidence_threshold: Some(Decimal256::percent(10)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(1000000u64));
+
+    let msg = ExecuteMsg::UpdateConfidenceThreshold {
+        collateral_token: "bluna".to_string(),
+        confidence_threshold: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ConfidenceThreshold {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let confidence_threshold_res: ConfidenceThresholdResponse = from_binary(&res).unwrap();
+    assert_eq!(confidence_threshold_res.confidence_threshold, None);
+
+    // unlock caches the borrow limit of the remaining collaterals
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(500000u64));
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(460000u64))]);
+    let liquidate_msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        max_spread: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        liquidate_msg.clone(),
+    );
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // tightening the threshold within the same block
+    // invalidates the cached limit, 500000 * 1.8 * 0.5 = 450000
+    let msg = ExecuteMsg::UpdateConfidenceThreshold {
+        collateral_token: "bluna".to_string(),
+        confidence_threshold: Some(Decimal256::percent(5)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(450000u64));
+    let _res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        liquidate_msg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn credit_line() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let borrow_limit = |deps: Deps| -> Uint256 {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.borrow_limit
+    };
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(500000u64));
+
+    let msg = ExecuteMsg::UpdateCreditLine {
+        borrower: "addr0000".to_string(),
+        credit_line: Some(CreditLine::LtvMultiplier(Decimal256::percent(150))),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_credit_line"),
+            attr("borrower", "addr0000"),
+            attr("credit_line", "ltv_multiplier:1.5"),
+        ]
+    );
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(750000u64));
+
+    // the multiplied LTV is capped at 100%
+    let msg = ExecuteMsg::UpdateCreditLine {
+        borrower: "addr0000".to_string(),
+        credit_line: Some(CreditLine::LtvMultiplier(Decimal256::percent(300))),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(1000000u64));
+
+    let msg = ExecuteMsg::UpdateCreditLine {
+        borrower: "addr0000".to_string(),
+        credit_line: Some(CreditLine::LtvMultiplier(Decimal256::zero())),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidCreditLine {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateCreditLine {
+        borrower: "addr0000".to_string(),
+        credit_line: Some(CreditLine::FixedLimit(Uint256::from(2000000u64))),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(2000000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CreditLine {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let credit_line_res: CreditLineResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        credit_line_res,
+        CreditLineResponse {
+            borrower: "addr0000".to_string(),
+            credit_line: Some(CreditLine::FixedLimit(Uint256::from(2000000u64))),
+        }
+    );
+
+    // the fixed limit covers the loan without any collateral
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1000000bluna"),
+            attr("credit_line", "fixed_limit:2000000"),
+        ]
+    );
+
+    let msg = ExecuteMsg::UpdateCreditLine {
+        borrower: "addr0000".to_string(),
+        credit_line: None,
+    };
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::zero());
+}
+
+#[test]
+fn risk_watchlist() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let mut env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+    deps.querier
+        .with_total_liabilities(&[(&"market".to_string(), &Decimal256::zero())]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(400000u64))]);
+
+    // only registered watchers can watch borrowers
+    let msg = ExecuteMsg::WatchBorrower {
+        borrower: "addr0000".to_string(),
+        ltv_threshold: Some(Decimal256::percent(45)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("risk0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let register_msg = ExecuteMsg::UpdateRiskWatcher {
+        watcher: "risk0000".to_string(),
+        registered: true,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        register_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), register_msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("risk0000", &[]),
+        ExecuteMsg::WatchBorrower {
+            borrower: "addr0000".to_string(),
+            ltv_threshold: Some(Decimal256::zero()),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidLtvThreshold {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("risk0000", &[]), msg).unwrap();
+
+    let epoch_msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::zero(),
+        distributed_interest: Uint256::zero(),
+    };
+
+    // LTV 40% is below the threshold
+    env.block.height += 86400u64;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        epoch_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // LTV 50% crosses the threshold
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(500000u64))]);
+    env.block.height += 86400u64;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        epoch_msg.clone(),
+    )
+    .unwrap();
+    let alert = SubMsg::reply_on_error(
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "risk0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&RiskWatcherMsg::LtvAlert {
+                borrower: "addr0000".to_string(),
+                ltv_threshold: Decimal256::percent(45),
+                ltv: Decimal256::percent(50),
+                loan_amount: Uint256::from(500000u64),
+                collateral_value: Uint256::from(1000000u64),
+            })
+            .unwrap(),
+        }),
+        2u64,
+    );
+    assert_eq!(res.messages[1], alert);
+
+    // no alert while the LTV stays above the threshold
+    env.block.height += 86400u64;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        epoch_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // falling back below the threshold re-arms the watch
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(400000u64))]);
+    env.block.height += 86400u64;
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        epoch_msg,
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RiskWatches {
+            watcher: "risk0000".to_string(),
+        },
+    )
+    .unwrap();
+    let watches_res: RiskWatchesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        watches_res,
+        RiskWatchesResponse {
+            watcher: "risk0000".to_string(),
+            watches: vec![RiskWatchResponse {
+                borrower: "addr0000".to_string(),
+                ltv_threshold: Decimal256::percent(45),
+                alerted: false,
+            }],
+        }
+    );
+
+    // liquidation checks alert too
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000u64))]);
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        max_spread: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "risk0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&RiskWatcherMsg::LtvAlert {
+                    borrower: "addr0000".to_string(),
+                    ltv_threshold: Decimal256::percent(45),
+                    ltv: Decimal256::percent(60),
+                    loan_amount: Uint256::from(600000u64),
+                    collateral_value: Uint256::from(1000000u64),
+                })
+                .unwrap(),
+            }),
+            2u64,
+        )
+    );
+
+    // failing callbacks are swallowed
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 2u64,
+            result: ContractResult::Err("watcher error".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "risk_alert_failed")]);
+
+    // deregistering drops the watches
+    let msg = ExecuteMsg::UpdateRiskWatcher {
+        watcher: "risk0000".to_string(),
+        registered: false,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::RiskWatches {
+            watcher: "risk0000".to_string(),
+        },
+    )
+    .unwrap();
+    let watches_res: RiskWatchesResponse = from_binary(&res).unwrap();
+    assert!(watches_res.watches.is_empty());
+}
+
+#[test]
+fn min_lock_duration() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let mut env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateMinLockDuration {
+        collateral_token: "bluna".to_string(),
+        min_lock_duration: Some(10u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::MinLockDuration {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let duration_res: MinLockDurationResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        duration_res,
+        MinLockDurationResponse {
+            collateral_token: "bluna".to_string(),
+            min_lock_duration: Some(10u64),
+        }
+    );
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let lock_msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        lock_msg.clone(),
+    )
+    .unwrap();
+    let lock_height = env.block.height;
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+    };
+    env.block.height += 9u64;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unlock_msg.clone(),
+    );
+    match res {
+        Err(ContractError::MinLockDurationNotPassed(height)) => {
+            assert_eq!(height, lock_height + 10u64)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height += 1u64;
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unlock_msg.clone(),
+    )
+    .unwrap();
+
+    // every lock restarts the duration
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        lock_msg,
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unlock_msg.clone(),
+    );
+    match res {
+        Err(ContractError::MinLockDurationNotPassed(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateMinLockDuration {
+        collateral_token: "bluna".to_string(),
+        min_lock_duration: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let _res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), unlock_msg).unwrap();
+}
+
+#[test]
+fn validate_intent() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(200000u64))]);
+
+    // the borrow does not fit the borrow limit left by the unlock
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::ValidateIntent {
+            borrower: "addr0000".to_string(),
+            actions: vec![
+                Intent::UnlockCollateral {
+                    collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+                },
+                Intent::BorrowStable {
+                    amount: Uint256::from(100000u64),
+                },
+            ],
+        },
+    )
+    .unwrap();
+    let intent_res: ValidateIntentResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        intent_res,
+        ValidateIntentResponse {
+            valid: false,
+            failed_action: Some(1u32),
+            error: Some(
+                "Borrow amount too high; Loan liability becomes greater than borrow limit: 250000"
+                    .to_string()
+            ),
+            collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+            loan_amount: Uint256::from(200000u64),
+            borrow_limit: Uint256::from(250000u64),
+            ltv: Decimal256::percent(40),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::ValidateIntent {
+            borrower: "addr0000".to_string(),
+            actions: vec![Intent::UnlockCollateral {
+                collaterals: vec![("bluna".to_string(), Uint256::from(2000000u64))],
+            }],
+        },
+    )
+    .unwrap();
+    let intent_res: ValidateIntentResponse = from_binary(&res).unwrap();
+    assert_eq!(intent_res.failed_action, Some(0u32));
+    assert_eq!(
+        intent_res.error,
+        Some("Unlock amount cannot exceed locked amount".to_string())
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::ValidateIntent {
+            borrower: "addr0000".to_string(),
+            actions: vec![
+                Intent::LockCollateral {
+                    collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+                },
+                Intent::BorrowStable {
+                    amount: Uint256::from(500000u64),
+                },
+                Intent::RepayStable {
+                    amount: Uint256::from(100000u64),
+                },
+            ],
+        },
+    )
+    .unwrap();
+    let intent_res: ValidateIntentResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        intent_res,
+        ValidateIntentResponse {
+            valid: true,
+            failed_action: None,
+            error: None,
+            collaterals: vec![("bluna".to_string(), Uint256::from(2000000u64))],
+            loan_amount: Uint256::from(600000u64),
+            borrow_limit: Uint256::from(1000000u64),
+            ltv: Decimal256::percent(30),
+        }
+    );
+}
+
+#[test]
+fn distribution_cap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateDistributionCap {
+        max_distribution: Some(Uint256::from(30000u64)),
+        smoothing_window: 2u64,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateDistributionCap {
+        max_distribution: Some(Uint256::from(30000u64)),
+        smoothing_window: 0u64,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSmoothingWindow {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateDistributionCap {
+        max_distribution: Some(Uint256::from(30000u64)),
+        smoothing_window: 2u64,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_distribution_cap"),
+            attr("max_distribution", "30000"),
+            attr("smoothing_window", "2"),
+        ]
+    );
+
+    // nothing accrued, so the whole buffer can back the distribution
+    store_epoch_state(
+        deps.as_mut().storage,
+        &EpochState {
+            last_executed_height: env.block.height,
+            prev_exchange_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(1000000u64),
+            prev_interest_buffer: Uint256::from(10000000000u128),
+            deposit_rate: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::one()),
+    )]);
+    env.block.height += 86400u64;
+
+    // missing_deposits = 1,000,000 * 86400 * 0.000001 = 86,400
+    // smoothed = 86,400 / 2 = 43,200, capped at 30,000
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteEpochOperations {},
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("distributed_interest", "30000")));
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::DistributionCap {}).unwrap();
+    let cap_res: DistributionCapResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        cap_res,
+        DistributionCapResponse {
+            max_distribution: Some(Uint256::from(30000u64)),
+            smoothing_window: 2u64,
+            carried_interest: Uint256::from(56400u64),
+        }
+    );
+
+    // the deposit rate reached the threshold,
+    // only half of the carried interest is paid
+    store_epoch_state(
+        deps.as_mut().storage,
+        &EpochState {
+            last_executed_height: env.block.height,
+            prev_exchange_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(1000000u64),
+            prev_interest_buffer: Uint256::from(10000000000u128),
+            deposit_rate: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(
+            Uint256::from(1000000u64),
+            Decimal256::from_str("1.0864").unwrap(),
+        ),
+    )]);
+    env.block.height += 86400u64;
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecuteEpochOperations {},
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("distributed_interest", "28200")));
+
+    let res = query(deps.as_ref(), env, QueryMsg::DistributionCap {}).unwrap();
+    let cap_res: DistributionCapResponse = from_binary(&res).unwrap();
+    assert_eq!(cap_res.carried_interest, Uint256::from(28200u64));
+}
+
+#[test]
+fn custody_interface_handshake() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_custody_interface(&[
+        (
+            &"custody_v2".to_string(),
+            &InterfaceVersionResponse {
+                version: CUSTODY_INTERFACE_VERSION + 1,
+                capabilities: vec![],
+            },
+        ),
+        (
+            &"custody_partial".to_string(),
+            &InterfaceVersionResponse {
+                version: CUSTODY_INTERFACE_VERSION,
+                capabilities: vec![
+                    CustodyCapability::LockCollateral,
+                    CustodyCapability::UnlockCollateral,
+                    CustodyCapability::DistributeRewards,
+                ],
+            },
+        ),
+    ]);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_v2".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnsupportedCustodyInterface(2)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_partial".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::MissingCustodyCapability(capability)) => {
+            assert_eq!(capability, "liquidate_collateral")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // replacing the custody runs the handshake again
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: Some("custody_partial".to_string()),
+        max_ltv: None,
+    };
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::MissingCustodyCapability(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn collateral_oracle() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_contract_oracle_price(
+        "exotic_oracle",
+        &[(
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::percent(50),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        )],
+    );
+
+    let msg = ExecuteMsg::UpdateCollateralOracle {
+        collateral_token: "bluna".to_string(),
+        oracle_contract: Some("exotic_oracle".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let query_borrow_limit = |deps: Deps| -> BorrowLimitResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::BorrowLimit {
+                    borrower: "addr0000".to_string(),
+                    block_time: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_borrow_limit(deps.as_ref()).borrow_limit,
+        Uint256::from(500000u64)
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_collateral_oracle"),
+            attr("collateral_token", "bluna"),
+            attr("oracle_contract", "exotic_oracle"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CollateralOracle {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let oracle_res: CollateralOracleResponse = from_binary(&res).unwrap();
+    assert_eq!(oracle_res.oracle_contract, "exotic_oracle".to_string());
+
+    // priced by the collateral oracle
+    assert_eq!(
+        query_borrow_limit(deps.as_ref()).borrow_limit,
+        Uint256::from(250000u64)
+    );
+
+    // back to the global oracle
+    let msg = ExecuteMsg::UpdateCollateralOracle {
+        collateral_token: "bluna".to_string(),
+        oracle_contract: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::CollateralOracle {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let oracle_res: CollateralOracleResponse = from_binary(&res).unwrap();
+    assert_eq!(oracle_res.oracle_contract, "oracle".to_string());
+    assert_eq!(
+        query_borrow_limit(deps.as_ref()).borrow_limit,
+        Uint256::from(500000u64)
+    );
+}
+
+#[test]
+fn liquidation_throttle() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001", "addr0002"].iter() {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(700000u64)),
+        (&"addr0001".to_string(), &Uint256::from(700000u64)),
+        (&"addr0002".to_string(), &Uint256::from(700000u64)),
+    ]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(10))]);
+
+    let msg = ExecuteMsg::UpdateLiquidationCap {
+        collateral_token: "bluna".to_string(),
+        liquidation_cap: Some(Uint256::from(150000u64)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let liquidate = |borrower: &str| ExecuteMsg::LiquidateCollateral {
+        borrower: borrower.to_string(),
+        max_spread: None,
+    };
+    let liquidation_message = |borrower: &str, amount: u64| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                liquidator: "liquidator".to_string(),
+                borrower: borrower.to_string(),
+                amount: Uint256::from(amount),
+                max_spread: None,
+            })
+            .unwrap(),
+        }))
+    };
+    let liquidator = mock_info("liquidator", &[]);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0000", 100000));
+
+    // only 50,000 left of the cap
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0001"),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0001", 50000));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidation_throttled"),
+            attr("borrower", "addr0000"),
+        ]
+    );
+
+    let query_throttle = |deps: Deps| -> LiquidationThrottleResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::LiquidationThrottle {
+                    collateral_token: "bluna".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_throttle(deps.as_ref()),
+        LiquidationThrottleResponse {
+            collateral_token: "bluna".to_string(),
+            liquidation_cap: Some(Uint256::from(150000u64)),
+            liquidated_value: Uint256::from(150000u64),
+            queued_borrowers: 2u32,
+            priority_borrowers: 0u32,
+        }
+    );
+
+    // next epoch, the queued borrowers come first
+    let mut epoch_state = read_epoch_state(deps.as_ref().storage).unwrap();
+    epoch_state.last_executed_height += 86400u64;
+    store_epoch_state(deps.as_mut().storage, &epoch_state).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0002"),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    // 950,000 left, 10% liquidated
+    let res = execute(deps.as_mut(), env, liquidator, liquidate("addr0001")).unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0001", 95000));
+
+    assert_eq!(
+        query_throttle(deps.as_ref()),
+        LiquidationThrottleResponse {
+            collateral_token: "bluna".to_string(),
+            liquidation_cap: Some(Uint256::from(150000u64)),
+            liquidated_value: Uint256::from(95000u64),
+            queued_borrowers: 1u32,
+            priority_borrowers: 1u32,
+        }
+    );
+}
+
+#[test]
+fn risk_notification() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let borrower = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), borrower.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(400000u64))]);
+
+    let msg = ExecuteMsg::RegisterRiskNotification {
+        contract: "auto_repay".to_string(),
+        ltv_bands: vec![Decimal256::percent(70), Decimal256::percent(50)],
+    };
+    let res = execute(deps.as_mut(), env.clone(), borrower.clone(), msg);
+    match res {
+        Err(ContractError::InvalidLtvBands(10)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterRiskNotification {
+        contract: "auto_repay".to_string(),
+        ltv_bands: vec![Decimal256::percent(50), Decimal256::percent(70)],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), borrower.clone(), msg).unwrap();
+
+    // ltv = 400,000 / 900,000 stays below the first band
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(100000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), borrower.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // ltv = 400,000 / 700,000 moves into the first band
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(200000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), borrower.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg {
+            id: 3u64,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "auto_repay".to_string(),
+                funds: vec![],
+                msg: to_binary(&RiskNotificationMsg::LtvBandCrossed {
+                    borrower: "addr0000".to_string(),
+                    ltv: Decimal256::from_ratio(400000u64, 700000u64),
+                    prev_band: 0u32,
+                    band: 1u32,
+                    loan_amount: Uint256::from(400000u64),
+                    collateral_value: Uint256::from(700000u64),
+                })
+                .unwrap(),
+            }),
+            gas_limit: Some(300000u64),
+            reply_on: ReplyOn::Error,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RiskNotification {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let notification_res: Option<RiskNotificationResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        notification_res,
+        Some(RiskNotificationResponse {
+            borrower: "addr0000".to_string(),
+            contract: "auto_repay".to_string(),
+            ltv_bands: vec![Decimal256::percent(50), Decimal256::percent(70)],
+            band: 1u32,
+        })
+    );
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        borrower,
+        ExecuteMsg::DeregisterRiskNotification {},
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::RiskNotification {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let notification_res: Option<RiskNotificationResponse> = from_binary(&res).unwrap();
+    assert_eq!(notification_res, None);
+}
+
+#[test]
+fn unlock_buffer() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(300000u64))]);
+
+    let msg = ExecuteMsg::UpdateUnlockBuffer {
+        collateral_token: "bluna".to_string(),
+        unlock_buffer: Some(Decimal256::percent(20)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateUnlockBuffer {
+        collateral_token: "bluna".to_string(),
+        unlock_buffer: Some(Decimal256::one()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidUnlockBuffer {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateUnlockBuffer {
+        collateral_token: "bluna".to_string(),
+        unlock_buffer: Some(Decimal256::percent(20)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::UnlockBuffer {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let buffer_res: UnlockBufferResponse = from_binary(&res).unwrap();
+    assert_eq!(buffer_res.unlock_buffer, Decimal256::percent(20));
+
+    // borrow_limit = 650,000 * 0.5 = 325,000, only 260,000 left to the loan
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(350000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(260000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 800,000 * 0.5 = 400,000, 320,000 left to the loan
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(200000u64))],
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+}
+
+#[test]
+fn oracle_quorum() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let now = env.block.time.seconds();
+    deps.querier.with_contract_oracle_price(
+        "oracle1",
+        &[(
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::one(), now, now),
+        )],
+    );
+    deps.querier.with_contract_oracle_price(
+        "oracle2",
+        &[(
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::percent(120), now, now),
+        )],
+    );
+    deps.querier.with_contract_oracle_price(
+        "oracle3",
+        &[(
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::percent(80), now - 100, now - 100),
+        )],
+    );
+
+    let msg = ExecuteMsg::UpdateOracleQuorum {
+        collateral_token: "bluna".to_string(),
+        oracle_quorum: Some(OracleQuorum {
+            oracles: vec!["oracle1".to_string(), "oracle2".to_string()],
+            quorum: 1,
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateOracleQuorum {
+        collateral_token: "bluna".to_string(),
+        oracle_quorum: Some(OracleQuorum {
+            oracles: vec!["oracle1".to_string(), "oracle2".to_string()],
+            quorum: 3,
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidOracleQuorum(5)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateOracleQuorum {
+        collateral_token: "bluna".to_string(),
+        oracle_quorum: Some(OracleQuorum {
+            oracles: vec!["oracle1".to_string(), "oracle1".to_string()],
+            quorum: 1,
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidOracleQuorum(5)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let oracle_quorum = OracleQuorum {
+        oracles: vec![
+            "oracle1".to_string(),
+            "oracle2".to_string(),
+            "oracle3".to_string(),
+        ],
+        quorum: 3,
+    };
+    let msg = ExecuteMsg::UpdateOracleQuorum {
+        collateral_token: "bluna".to_string(),
+        oracle_quorum: Some(oracle_quorum.clone()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_oracle_quorum"),
+            attr("collateral_token", "bluna"),
+            attr("quorum", "3/3"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::OracleQuorum {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let quorum_res: OracleQuorumResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        quorum_res,
+        OracleQuorumResponse {
+            collateral_token: "bluna".to_string(),
+            oracle_quorum: Some(oracle_quorum),
+        }
+    );
+
+    let borrow_limit = |deps: Deps| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: Some(now),
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.borrow_limit
+    };
+
+    // only two fresh feeds, the lowest answer prices the collateral
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(400000u64));
+
+    // three fresh feeds, the median prices the collateral
+    deps.querier.with_contract_oracle_price(
+        "oracle3",
+        &[(
+            &("bluna".to_string(), "uusd".to_string()),
+            &(Decimal256::percent(80), now, now),
+        )],
+    );
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(500000u64));
+
+    // even number of fresh feeds, the mean of the middle two
+    let msg = ExecuteMsg::UpdateOracleQuorum {
+        collateral_token: "bluna".to_string(),
+        oracle_quorum: Some(OracleQuorum {
+            oracles: vec!["oracle1".to_string(), "oracle2".to_string()],
+            quorum: 2,
+        }),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(550000u64));
+
+    let msg = ExecuteMsg::UpdateOracleQuorum {
+        collateral_token: "bluna".to_string(),
+        oracle_quorum: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::OracleQuorum {
+            collateral_token: "bluna".to_string(),
+        },
+    )
+    .unwrap();
+    let quorum_res: OracleQuorumResponse = from_binary(&res).unwrap();
+    assert_eq!(quorum_res.oracle_quorum, None);
+}
+
+#[test]
+fn resume_epoch_operations() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for i in 0..12 {
+        let msg = ExecuteMsg::Whitelist {
+            name: format!("token{:02}", i),
+            symbol: format!("token{:02}", i),
+            collateral_token: format!("token{:02}", i),
+            custody_contract: format!("custody{:02}", i),
+            max_ltv: Decimal256::percent(60),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    let update_epoch_state = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::UpdateEpochState {
+            interest_buffer: Uint256::from(8_000_000_000u128),
+            distributed_interest: Uint256::zero(),
+        })
+        .unwrap(),
+    }));
+    let distributed_custodies = |messages: &[SubMsg]| -> Vec<String> {
+        messages
+            .iter()
+            .filter_map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) if *msg == to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap() => {
+                    Some(contract_addr.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    };
+
+    // the first call distributes the rewards of the first batch only
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let mut custodies = distributed_custodies(&res.messages);
+    assert_eq!(custodies.len(), 10);
+    assert!(!res.messages.contains(&update_epoch_state));
+    assert_eq!(res.attributes[0], attr("action", "epoch_operations"));
+    assert_eq!(res.attributes[6].key, "last_collateral");
+
+    // keepers cannot step into the unfinished epoch
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteEpochStep {},
+    );
+    match res {
+        Err(ContractError::EpochInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the second call finishes the rewards and updates the epoch state
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "resume_epoch_operations")]
+    );
+    let mut remaining = distributed_custodies(&res.messages);
+    assert_eq!(remaining.len(), 2);
+    assert_eq!(res.messages.last(), Some(&update_epoch_state));
+
+    custodies.append(&mut remaining);
+    custodies.sort();
+    custodies.dedup();
+    assert_eq!(
+        custodies,
+        (0..12)
+            .map(|i| format!("custody{:02}", i))
+            .collect::<Vec<String>>()
+    );
+
+    // the cursor is cleared, the next call starts a new epoch
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(res.attributes[0], attr("action", "epoch_operations"));
+}
+
+#[test]
+fn category_share_limit() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for token in ["bluna", "batom"] {
+        let msg = ExecuteMsg::Whitelist {
+            name: token.to_string(),
+            symbol: token.to_string(),
+            collateral_token: token.to_string(),
+            custody_contract: format!("custody_{}", token),
+            max_ltv: Decimal256::percent(50),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::RegisterCategory {
+        name: "luna".to_string(),
+        max_ltv: Decimal256::percent(80),
+        collateral_tokens: vec!["bluna".to_string(), "batom".to_string()],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let borrower_info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(100000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::SetBorrowerCategory {
+        category: Some("luna".to_string()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg).unwrap();
+
+    let borrow_limit = |deps: Deps| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.borrow_limit
+    };
+
+    // 1,100,000 * 0.8
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(880000u64));
+
+    let msg = ExecuteMsg::UpdateCategoryShareLimit {
+        name: "luna".to_string(),
+        max_collateral_share: Some(Decimal256::percent(80)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    for max_collateral_share in [Decimal256::zero(), Decimal256::percent(101)] {
+        let msg = ExecuteMsg::UpdateCategoryShareLimit {
+            name: "luna".to_string(),
+            max_collateral_share: Some(max_collateral_share),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidCollateralShare {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let msg = ExecuteMsg::UpdateCategoryShareLimit {
+        name: "eth".to_string(),
+        max_collateral_share: Some(Decimal256::percent(80)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Category is not registered")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateCategoryShareLimit {
+        name: "luna".to_string(),
+        max_collateral_share: Some(Decimal256::percent(80)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_category_share_limit"),
+            attr("name", "luna"),
+            attr("max_collateral_share", "0.8"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CategoryShareLimit {
+            name: "luna".to_string(),
+        },
+    )
+    .unwrap();
+    let share_limit_res: CategoryShareLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        share_limit_res,
+        CategoryShareLimitResponse {
+            name: "luna".to_string(),
+            max_collateral_share: Some(Decimal256::percent(80)),
+        }
+    );
+
+    // bluna contributes 800,000 of 880,000, the default LTV applies
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(550000u64));
+
+    // bluna contributes 800,000 of 1,280,000, the boosted LTV applies
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(500000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), borrower_info, msg).unwrap();
+    assert_eq!(borrow_limit(deps.as_ref()), Uint256::from(1280000u64));
+
+    let msg = ExecuteMsg::UpdateCategoryShareLimit {
+        name: "luna".to_string(),
+        max_collateral_share: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::CategoryShareLimit {
+            name: "luna".to_string(),
+        },
+    )
+    .unwrap();
+    let share_limit_res: CategoryShareLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(share_limit_res.max_collateral_share, None);
+}
+
+#[test]
+fn position_operator() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(50),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let msg = ExecuteMsg::SetOperator {
+        operator: "manager".to_string(),
+        permissions: vec![
+            OperatorPermission::LockCollateral,
+            OperatorPermission::UnlockCollateral,
+            OperatorPermission::LockCollateral,
+        ],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_operator"),
+            attr("borrower", "addr0000"),
+            attr("operator", "manager"),
+            attr("permissions", "lock_collateral,unlock_collateral"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Operator {
+            borrower: "addr0000".to_string(),
+            operator: "manager".to_string(),
+        },
+    )
+    .unwrap();
+    let operator_res: OperatorResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        operator_res,
+        OperatorResponse {
+            borrower: "addr0000".to_string(),
+            operator: "manager".to_string(),
+            permissions: vec![
+                OperatorPermission::LockCollateral,
+                OperatorPermission::UnlockCollateral,
+            ],
+        }
+    );
+
+    let msg = ExecuteMsg::LockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::OperatorNotPermitted(permission)) => {
+            assert_eq!(permission, "lock_collateral")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("manager", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1000000bluna"),
+            attr("operator", "manager"),
+        ]
+    );
+
+    let msg = ExecuteMsg::SetBorrowerCategoryFor {
+        borrower: "addr0000".to_string(),
+        category: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("manager", &[]), msg);
+    match res {
+        Err(ContractError::OperatorNotPermitted(permission)) => {
+            assert_eq!(permission, "set_borrower_category")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // unlocked collaterals are released to the borrower, not the operator
+    let msg = ExecuteMsg::UnlockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![("bluna".to_string(), Uint256::from(400000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("manager", &[]), msg).unwrap();
+    assert!(res
+        .messages
+        .contains(&SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(400000u64),
+            })
+            .unwrap(),
+        }))));
+    assert!(res.attributes.contains(&attr("operator", "manager")));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(600000u64))]
+    );
+
+    // no permissions revokes the operator
+    let msg = ExecuteMsg::SetOperator {
+        operator: "manager".to_string(),
+        permissions: vec![],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UnlockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![("bluna".to_string(), Uint256::from(400000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("manager", &[]), msg);
+    match res {
+        Err(ContractError::OperatorNotPermitted(permission)) => {
+            assert_eq!(permission, "unlock_collateral")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Operator {
+            borrower: "addr0000".to_string(),
+            operator: "manager".to_string(),
+        },
+    )
+    .unwrap();
+    let operator_res: OperatorResponse = from_binary(&res).unwrap();
+    assert!(operator_res.permissions.is_empty());
+}
+
+#[test]
+fn liquidate_auctioned_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_auction_config(&[(
+        &"bluna".to_string(),
+        &AuctionConfig {
+            initial_discount: Decimal256::percent(10),
+            decay_period: 3600u64,
+            expiry_period: 3600u64,
+        },
+    )]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000001u64))]);
+
+    // the auction repays the loan, nothing is repaid from the liquidation
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+        max_spread: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                liquidator: "addr0001".to_string(),
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(10000u64),
+                max_spread: None,
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // the position is not liquidated again until the auction is over
+    deps.querier.with_auctions(&[(
+        &"addr0000".to_string(),
+        &[AuctionResponse {
+            idx: Uint128::from(1u128),
+            collateral_token: "bluna".to_string(),
+            borrower: "addr0000".to_string(),
+            amount: Uint256::from(10000u64),
+            start_time: env.block.time.seconds(),
+            discount: Decimal256::percent(10),
+            expires_at: env.block.time.seconds() + 7200u64,
+        }],
+    )]);
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()) {
+        Err(ContractError::AuctionInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_auctions(&[(&"addr0000".to_string(), &[])]);
+    execute(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn simulate_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let simulate = |deps: Deps| -> SimulateLiquidationResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::SimulateLiquidation {
+                    borrower: "addr0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    // safe loans are not liquidated
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000000u64))]);
+    assert_eq!(
+        simulate(deps.as_ref()),
+        SimulateLiquidationResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![],
+            repay_amount: Uint256::zero(),
+            loan_amount: Uint256::from(600000000u64),
+            ltv: Decimal256::percent(60),
+        }
+    );
+
+    // 1% of the collateral is liquidated at the oracle price
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000001u64))]);
+    assert_eq!(
+        simulate(deps.as_ref()),
+        SimulateLiquidationResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![SimulatedCollateralLiquidation {
+                collateral_token: "bluna".to_string(),
+                amount: Uint256::from(10000u64),
+                repay_amount: Uint256::from(10000000u64),
+                fills: vec![BidFill {
+                    premium_rate: Decimal256::zero(),
+                    collateral_amount: Uint256::from(10000u64),
+                    stable_amount: Uint256::from(10000000u64),
+                }],
+            }],
+            repay_amount: Uint256::from(10000000u64),
+            loan_amount: Uint256::from(590000001u64),
+            ltv: Decimal256::from_ratio(590000001u64, 990000000u64),
+        }
+    );
+}
+
+#[test]
+fn seizure_order() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(10))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for collateral_token in ["bluna", "beth"].iter() {
+        let msg = ExecuteMsg::Whitelist {
+            name: collateral_token.to_string(),
+            symbol: collateral_token.to_string(),
+            collateral_token: collateral_token.to_string(),
+            custody_contract: format!("custody_{}", collateral_token),
+            max_ltv: Decimal256::percent(60),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("beth".to_string(), Uint256::from(200000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("beth".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = (2 * 1000000 + 200000) * 0.6 = 1,320,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(1500000u64))]);
+    deps.querier.with_bid_pools(&[
+        (
+            &"bluna".to_string(),
+            &[(Uint256::from(100000u64), Decimal256::percent(10))],
+        ),
+        (
+            &"beth".to_string(),
+            &[(Uint256::from(500000u64), Decimal256::percent(1))],
+        ),
+    ]);
+
+    let seized = |deps: Deps| -> Vec<(String, Uint256)> {
+        let res: SimulateLiquidationResponse = from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::SimulateLiquidation {
+                    borrower: "addr0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.collaterals
+            .into_iter()
+            .map(|collateral| (collateral.collateral_token, collateral.amount))
+            .collect()
+    };
+
+    // 10% of each collateral without a seizure order, worth 220,000 uusd
+    assert_eq!(
+        seized(deps.as_ref()),
+        vec![
+            ("beth".to_string(), Uint256::from(20000u64)),
+            ("bluna".to_string(), Uint256::from(100000u64)),
+        ]
+    );
+
+    let msg = ExecuteMsg::UpdateSeizureOrder {
+        seizure_order: Some(SeizureOrder::MostLiquidFirst),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_seizure_order"),
+            attr("seizure_order", "most_liquid_first"),
+        ]
+    );
+
+    // beth has the most bids, it is seized in full before bluna
+    assert_eq!(
+        seized(deps.as_ref()),
+        vec![
+            ("beth".to_string(), Uint256::from(200000u64)),
+            ("bluna".to_string(), Uint256::from(10000u64)),
+        ]
+    );
+
+    // bluna bids weigh the most times their premium
+    let msg = ExecuteMsg::UpdateSeizureOrder {
+        seizure_order: Some(SeizureOrder::HighestPremiumCapacityFirst),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        seized(deps.as_ref()),
+        vec![("bluna".to_string(), Uint256::from(110000u64))]
+    );
+
+    // borrowers without a preference are seized proportionally
+    let msg = ExecuteMsg::UpdateSeizureOrder {
+        seizure_order: Some(SeizureOrder::BorrowerPreference),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        seized(deps.as_ref()),
+        vec![
+            ("beth".to_string(), Uint256::from(20000u64)),
+            ("bluna".to_string(), Uint256::from(100000u64)),
+        ]
+    );
+
+    for collaterals in [
+        vec!["beth".to_string(), "beth".to_string()],
+        vec!["bluna".to_string(), "token0000".to_string()],
+    ]
+    .iter()
+    {
+        let msg = ExecuteMsg::UpdateSeizurePreference {
+            collaterals: Some(collaterals.clone()),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+        match res {
+            Err(ContractError::InvalidSeizurePreference {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let msg = ExecuteMsg::UpdateSeizurePreference {
+        collaterals: Some(vec!["beth".to_string()]),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let res: SeizurePreferenceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SeizurePreference {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.collaterals, vec!["beth".to_string()]);
+
+    assert_eq!(
+        seized(deps.as_ref()),
+        vec![
+            ("beth".to_string(), Uint256::from(200000u64)),
+            ("bluna".to_string(), Uint256::from(10000u64)),
+        ]
+    );
+}
+
+#[test]
+fn mass_simulate() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001"].iter() {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(500000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(400000000u64)),
+    ]);
+
+    let simulate = |deps: Deps, price_shock: Decimal256| -> StdResult<MassSimulateResponse> {
+        from_binary(&query(
+            deps,
+            mock_env(),
+            QueryMsg::MassSimulate { price_shock },
+        )?)
+    };
+
+    match simulate(deps.as_ref(), Decimal256::one()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price shock must be lower than one")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    // both positions are safe at the oracle price
+    assert_eq!(
+        simulate(deps.as_ref(), Decimal256::zero()).unwrap(),
+        MassSimulateResponse {
+            price_shock: Decimal256::zero(),
+            liquidatable_positions: 0,
+            liquidatable_debt: Uint256::zero(),
+            repay_amount: Uint256::zero(),
+            absorbed: true,
+            collaterals: vec![],
+        }
+    );
+
+    // borrow_limit = 800 * 1000000 * 0.6 = 480,000,000 uusd
+    // only addr0000 is liquidated, 1% of its collateral at the shocked price
+    assert_eq!(
+        simulate(deps.as_ref(), Decimal256::percent(20)).unwrap(),
+        MassSimulateResponse {
+            price_shock: Decimal256::percent(20),
+            liquidatable_positions: 1,
+            liquidatable_debt: Uint256::from(500000000u64),
+            repay_amount: Uint256::from(8000000u64),
+            absorbed: true,
+            collaterals: vec![MassSimulatedCollateral {
+                collateral_token: "bluna".to_string(),
+                price: Decimal256::from_ratio(800u64, 1u64),
+                liquidation_amount: Uint256::from(10000u64),
+                absorbed_amount: Uint256::from(10000u64),
+                repay_amount: Uint256::from(8000000u64),
+            }],
+        }
+    );
+}
+
+#[test]
+fn exclusive_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let mut env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001"].iter() {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(700000u64)),
+        (&"addr0001".to_string(), &Uint256::from(700000u64)),
+    ]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(10))]);
+
+    let msg = ExecuteMsg::UpdateExclusiveLiquidation {
+        exclusive_liquidation: Some(ExclusiveLiquidation {
+            liquidators: vec!["keeper".to_string()],
+            exclusive_blocks: 0u64,
+        }),
+    };
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::InvalidExclusiveLiquidation {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateExclusiveLiquidation {
+        exclusive_liquidation: Some(ExclusiveLiquidation {
+            liquidators: vec!["keeper".to_string(), "keeper".to_string()],
+            exclusive_blocks: 10u64,
+        }),
+    };
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_exclusive_liquidation"),
+            attr("exclusive_blocks", "10"),
+        ]
+    );
+
+    let res: ExclusiveLiquidationResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ExclusiveLiquidation {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        ExclusiveLiquidationResponse {
+            exclusive_liquidation: Some(ExclusiveLiquidation {
+                liquidators: vec!["keeper".to_string()],
+                exclusive_blocks: 10u64,
+            }),
+        }
+    );
+
+    let liquidate = |borrower: &str| ExecuteMsg::LiquidateCollateral {
+        borrower: borrower.to_string(),
+        max_spread: None,
+    };
+    let query_window = |deps: Deps, borrower: &str| -> LiquidationWindowResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::LiquidationWindow {
+                    borrower: borrower.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // whitelisted liquidators liquidate right away
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        liquidate("addr0001"),
+    )
+    .unwrap();
+    assert!(!res.messages.is_empty());
+
+    // the first attempt of anyone else starts the window
+    let start_height = env.block.height;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidator", &[]),
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidation_window_started"),
+            attr("borrower", "addr0000"),
+            attr("open_height", (start_height + 10).to_string()),
+        ]
+    );
+    assert_eq!(
+        query_window(deps.as_ref(), "addr0000"),
+        LiquidationWindowResponse {
+            borrower: "addr0000".to_string(),
+            liquidatable_since: Some(start_height),
+            open_height: Some(start_height + 10),
+        }
+    );
+
+    env.block.height += 9;
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidator", &[]),
+        liquidate("addr0000"),
+    ) {
+        Err(ContractError::LiquidatorNotWhitelisted(height)) => {
+            assert_eq!(height, start_height + 10)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the window is open to everyone, and reset by the liquidation
+    env.block.height += 1;
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("liquidator", &[]),
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert!(!res.messages.is_empty());
+    assert_eq!(
+        query_window(deps.as_ref(), "addr0000"),
+        LiquidationWindowResponse {
+            borrower: "addr0000".to_string(),
+            liquidatable_since: None,
+            open_height: None,
+        }
+    );
+}
+
+#[test]
+fn gradual_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for (borrower, amount) in [("addr0000", 1000000u64), ("addr0001", 200000u64)].iter() {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(*amount))],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(700000u64)),
+        (&"addr0001".to_string(), &Uint256::from(700000u64)),
+    ]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(40))]);
+
+    let msg = ExecuteMsg::UpdateGradualLiquidation {
+        gradual_liquidation: Some(GradualLiquidation {
+            size_threshold: Uint256::from(100000u64),
+            tranches: 4u32,
+            tranche_interval: 10u64,
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let invalid_msg = ExecuteMsg::UpdateGradualLiquidation {
+        gradual_liquidation: Some(GradualLiquidation {
+            size_threshold: Uint256::from(100000u64),
+            tranches: 0u32,
+            tranche_interval: 10u64,
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), invalid_msg);
+    match res {
+        Err(ContractError::InvalidGradualLiquidation {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_gradual_liquidation"),
+            attr("size_threshold", "100000"),
+            attr("tranches", "4"),
+            attr("tranche_interval", "10"),
+        ]
+    );
+
+    let res: GradualLiquidationResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GradualLiquidation {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res.gradual_liquidation,
+        Some(GradualLiquidation {
+            size_threshold: Uint256::from(100000u64),
+            tranches: 4u32,
+            tranche_interval: 10u64,
+        })
+    );
+
+    let liquidate = |borrower: &str| ExecuteMsg::LiquidateCollateral {
+        borrower: borrower.to_string(),
+        max_spread: None,
+    };
+    let liquidation_message = |borrower: &str, amount: u64| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                liquidator: "liquidator".to_string(),
+                borrower: borrower.to_string(),
+                amount: Uint256::from(amount),
+                max_spread: None,
+            })
+            .unwrap(),
+        }))
+    };
+    let query_plan = |deps: Deps| -> LiquidationPlanResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::LiquidationPlan {
+                    borrower: "addr0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let liquidator = mock_info("liquidator", &[]);
+
+    // 400,000 to liquidate, split into 4 tranches
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0000", 100000));
+    assert_eq!(
+        query_plan(deps.as_ref()),
+        LiquidationPlanResponse {
+            borrower: "addr0000".to_string(),
+            remaining_tranches: Some(3u32),
+            next_height: Some(env.block.height + 10),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0000"),
+    );
+    match res {
+        Err(ContractError::LiquidationTrancheNotDue(height)) => {
+            assert_eq!(height, env.block.height + 10)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // small positions are liquidated at once
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0001"),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0001", 80000));
+
+    // 360,000 left to liquidate over the 3 remaining tranches
+    let mut env = mock_env();
+    env.block.height += 10;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator.clone(),
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0000", 120000));
+    assert_eq!(
+        query_plan(deps.as_ref()),
+        LiquidationPlanResponse {
+            borrower: "addr0000".to_string(),
+            remaining_tranches: Some(2u32),
+            next_height: Some(env.block.height + 10),
+        }
+    );
+
+    // a plan left behind for a tranche interval starts over
+    env.block.height += 20;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator,
+        liquidate("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], liquidation_message("addr0000", 78000));
+    assert_eq!(
+        query_plan(deps.as_ref()),
+        LiquidationPlanResponse {
+            borrower: "addr0000".to_string(),
+            remaining_tranches: Some(3u32),
+            next_height: Some(env.block.height + 10),
+        }
+    );
+}
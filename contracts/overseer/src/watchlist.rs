@@ -0,0 +1,249 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, SubMsg, WasmMsg,
+};
+
+use crate::collateral::compute_borrow_limit;
+use crate::error::ContractError;
+use crate::querier::query_borrower_info;
+use crate::state::{
+    read_collaterals, read_config, read_risk_watch, read_risk_watcher, read_risk_watchers,
+    read_risk_watches, store_risk_watch, store_risk_watcher, Config, RiskWatch, RiskWatcher,
+};
+
+use moneymarket::overseer::{RiskWatchResponse, RiskWatcherMsg, RiskWatchesResponse};
+use moneymarket::tokens::Tokens;
+
+pub const RISK_ALERT_REPLY_ID: u64 = 2;
+
+// maximum number of borrowers watched by a single risk watcher
+const MAX_RISK_WATCHES: u32 = 30;
+
+pub fn update_risk_watcher(
+    deps: DepsMut,
+    info: MessageInfo,
+    watcher: Addr,
+    registered: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let watcher_raw = deps.api.addr_canonicalize(watcher.as_str())?;
+    if registered {
+        if read_risk_watcher(deps.storage, &watcher_raw)?.is_none() {
+            store_risk_watcher(
+                deps.storage,
+                &watcher_raw,
+                Some(RiskWatcher { watch_count: 0 }),
+            )?;
+        }
+    } else {
+        for (borrower, _) in read_risk_watches(deps.storage, &watcher_raw)? {
+            store_risk_watch(deps.storage, &watcher_raw, &borrower, None)?;
+        }
+        store_risk_watcher(deps.storage, &watcher_raw, None)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_risk_watcher"),
+        attr("watcher", watcher),
+        attr("registered", registered.to_string()),
+    ]))
+}
+
+pub fn watch_borrower(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    ltv_threshold: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let watcher_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut risk_watcher: RiskWatcher = match read_risk_watcher(deps.storage, &watcher_raw)? {
+        Some(risk_watcher) => risk_watcher,
+        None => return Err(ContractError::Unauthorized {}),
+    };
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let watched = read_risk_watch(deps.storage, &watcher_raw, &borrower_raw)?.is_some();
+    match ltv_threshold {
+        Some(ltv_threshold) => {
+            if ltv_threshold.is_zero() {
+                return Err(ContractError::InvalidLtvThreshold {});
+            }
+
+            if !watched {
+                if risk_watcher.watch_count >= MAX_RISK_WATCHES {
+                    return Err(ContractError::TooManyRiskWatches(MAX_RISK_WATCHES));
+                }
+                risk_watcher.watch_count += 1;
+            }
+
+            store_risk_watch(
+                deps.storage,
+                &watcher_raw,
+                &borrower_raw,
+                Some(RiskWatch {
+                    ltv_threshold,
+                    alerted: false,
+                }),
+            )?;
+        }
+        None => {
+            if watched {
+                risk_watcher.watch_count -= 1;
+            }
+            store_risk_watch(deps.storage, &watcher_raw, &borrower_raw, None)?;
+        }
+    }
+
+    store_risk_watcher(deps.storage, &watcher_raw, Some(risk_watcher))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "watch_borrower"),
+        attr("watcher", info.sender),
+        attr("borrower", borrower),
+        attr(
+            "ltv_threshold",
+            ltv_threshold.map(|t| t.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Callbacks to the watchers of the borrower whose threshold is crossed.
+/// A failing callback is ignored in the reply, so a watcher
+/// cannot block the operation emitting it.
+pub(crate) fn risk_alert_messages(
+    deps: DepsMut,
+    borrower: &CanonicalAddr,
+    loan_amount: Uint256,
+    collateral_value: Uint256,
+) -> StdResult<Vec<SubMsg>> {
+    let ltv = position_ltv(loan_amount, collateral_value);
+
+    let mut messages: Vec<SubMsg> = vec![];
+    for watcher in read_risk_watchers(deps.storage)? {
+        let mut risk_watch: RiskWatch = match read_risk_watch(deps.storage, &watcher, borrower)? {
+            Some(risk_watch) => risk_watch,
+            None => continue,
+        };
+
+        let above = ltv >= risk_watch.ltv_threshold;
+        if above == risk_watch.alerted {
+            continue;
+        }
+
+        risk_watch.alerted = above;
+        store_risk_watch(deps.storage, &watcher, borrower, Some(risk_watch.clone()))?;
+        if !above {
+            continue;
+        }
+
+        messages.push(SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&watcher)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&RiskWatcherMsg::LtvAlert {
+                    borrower: deps.api.addr_humanize(borrower)?.to_string(),
+                    ltv_threshold: risk_watch.ltv_threshold,
+                    ltv,
+                    loan_amount,
+                    collateral_value,
+                })?,
+            }),
+            RISK_ALERT_REPLY_ID,
+        ));
+    }
+
+    Ok(messages)
+}
+
+/// Value of the collaterals in stable denom at the given prices
+pub(crate) fn collateral_value(
+    collaterals: &[(CanonicalAddr, Uint256)],
+    prices: &[Decimal256],
+) -> Uint256 {
+    collaterals
+        .iter()
+        .zip(prices)
+        .fold(Uint256::zero(), |value, (collateral, price)| {
+            value + collateral.1 * *price
+        })
+}
+
+/// Loan over collateral value; a loan without collateral is at the max LTV
+pub(crate) fn position_ltv(loan_amount: Uint256, collateral_value: Uint256) -> Decimal256 {
+    if !collateral_value.is_zero() {
+        Decimal256::from_ratio(loan_amount, collateral_value)
+    } else if !loan_amount.is_zero() {
+        Decimal256::MAX
+    } else {
+        Decimal256::zero()
+    }
+}
+
+/// Checks every watched borrower at the latest prices; borrowers
+/// whose position cannot be valued are skipped until the next epoch
+pub(crate) fn epoch_risk_alert_messages(mut deps: DepsMut, env: &Env) -> StdResult<Vec<SubMsg>> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let mut borrowers: Vec<CanonicalAddr> = vec![];
+    for watcher in read_risk_watchers(deps.storage)? {
+        for (borrower, _) in read_risk_watches(deps.storage, &watcher)? {
+            if !borrowers.contains(&borrower) {
+                borrowers.push(borrower);
+            }
+        }
+    }
+
+    let mut messages: Vec<SubMsg> = vec![];
+    for borrower in borrowers {
+        let collaterals: Tokens = read_collaterals(deps.storage, &borrower);
+        let collateral_prices =
+            match compute_borrow_limit(deps.as_ref(), env, &borrower, &collaterals, None) {
+                Ok((_, collateral_prices)) => collateral_prices,
+                Err(_) => continue,
+            };
+        let loan_amount = match query_borrower_info(
+            deps.as_ref(),
+            market.clone(),
+            deps.api.addr_humanize(&borrower)?,
+            env.block.height,
+        ) {
+            Ok(borrower_info) => borrower_info.loan_amount,
+            Err(_) => continue,
+        };
+
+        let collateral_value = collateral_value(&collaterals, &collateral_prices);
+
+        messages.append(&mut risk_alert_messages(
+            deps.branch(),
+            &borrower,
+            loan_amount,
+            collateral_value,
+        )?);
+    }
+
+    Ok(messages)
+}
+
+pub fn query_risk_watches(deps: Deps, watcher: Addr) -> StdResult<RiskWatchesResponse> {
+    let watches = read_risk_watches(deps.storage, &deps.api.addr_canonicalize(watcher.as_str())?)?
+        .into_iter()
+        .map(|(borrower, risk_watch)| {
+            Ok(RiskWatchResponse {
+                borrower: deps.api.addr_humanize(&borrower)?.to_string(),
+                ltv_threshold: risk_watch.ltv_threshold,
+                alerted: risk_watch.alerted,
+            })
+        })
+        .collect::<StdResult<Vec<RiskWatchResponse>>>()?;
+
+    Ok(RiskWatchesResponse {
+        watcher: watcher.to_string(),
+        watches,
+    })
+}
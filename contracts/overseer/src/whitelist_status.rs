@@ -0,0 +1,123 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+
+use crate::error::ContractError;
+use crate::state::{
+    bump_borrow_limit_cache_version, read_config, read_whitelist_elem, read_whitelist_status,
+    store_whitelist_status, Config, WhitelistElem, WhitelistStatusInfo,
+};
+
+use moneymarket::overseer::{WhitelistStatus, WhitelistStatusResponse};
+use moneymarket::tokens::Tokens;
+
+/// Allowed transitions are Active <-> DepositPaused -> Deprecated -> Delisted,
+/// where delisting waits for the LTV ramp to reach zero, so no borrow
+/// limit depends on the collateral anymore
+pub fn update_whitelist_status(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    status: WhitelistStatus,
+    ltv_ramp_duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+
+    let mut status_info: WhitelistStatusInfo =
+        read_whitelist_status(deps.storage, &collateral_token_raw)?;
+    let cur_time = env.block.time.seconds();
+    match (&status_info.status, &status) {
+        (WhitelistStatus::Active, WhitelistStatus::DepositPaused)
+        | (WhitelistStatus::DepositPaused, WhitelistStatus::Active) => {}
+        (WhitelistStatus::Active, WhitelistStatus::Deprecated)
+        | (WhitelistStatus::DepositPaused, WhitelistStatus::Deprecated) => {
+            let ltv_ramp_duration = match ltv_ramp_duration {
+                Some(ltv_ramp_duration) => ltv_ramp_duration,
+                None => return Err(ContractError::InvalidWhitelistStatusTransition {}),
+            };
+
+            status_info.ltv_ramp_start = cur_time;
+            status_info.ltv_ramp_end = cur_time + ltv_ramp_duration;
+        }
+        (WhitelistStatus::Deprecated, WhitelistStatus::Delisted) => {
+            if cur_time < status_info.ltv_ramp_end {
+                return Err(ContractError::LtvRampNotFinished(status_info.ltv_ramp_end));
+            }
+        }
+        _ => return Err(ContractError::InvalidWhitelistStatusTransition {}),
+    }
+
+    status_info.status = status.clone();
+    store_whitelist_status(deps.storage, &collateral_token_raw, &status_info)?;
+    bump_borrow_limit_cache_version(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_whitelist_status"),
+        attr("collateral_token", collateral_token),
+        attr("status", status.to_string()),
+    ]))
+}
+
+/// Returns an error if any of the collaterals does not accept new locks
+#[allow(clippy::ptr_arg)]
+pub fn assert_active_collaterals(deps: Deps, collaterals: &Tokens) -> Result<(), ContractError> {
+    for collateral in collaterals.iter() {
+        if read_whitelist_status(deps.storage, &collateral.0)?.status != WhitelistStatus::Active {
+            return Err(ContractError::CollateralNotActive {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Share of the LTV still in effect at `cur_time`
+pub(crate) fn read_ltv_factor(
+    deps: Deps,
+    collateral_token: &CanonicalAddr,
+    cur_time: u64,
+) -> StdResult<Decimal256> {
+    let status_info: WhitelistStatusInfo = read_whitelist_status(deps.storage, collateral_token)?;
+    Ok(match status_info.status {
+        WhitelistStatus::Active | WhitelistStatus::DepositPaused => Decimal256::one(),
+        WhitelistStatus::Deprecated if cur_time < status_info.ltv_ramp_end => {
+            Decimal256::from_ratio(
+                status_info.ltv_ramp_end - cur_time,
+                status_info.ltv_ramp_end - status_info.ltv_ramp_start,
+            )
+        }
+        WhitelistStatus::Deprecated | WhitelistStatus::Delisted => Decimal256::zero(),
+    })
+}
+
+pub fn query_whitelist_status(
+    deps: Deps,
+    env: Env,
+    collateral_token: Addr,
+) -> StdResult<WhitelistStatusResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let status_info: WhitelistStatusInfo =
+        read_whitelist_status(deps.storage, &collateral_token_raw)?;
+    let ltv_factor = read_ltv_factor(deps, &collateral_token_raw, env.block.time.seconds())?;
+
+    let ramped = matches!(
+        status_info.status,
+        WhitelistStatus::Deprecated | WhitelistStatus::Delisted
+    );
+
+    Ok(WhitelistStatusResponse {
+        collateral_token: collateral_token.to_string(),
+        max_ltv: whitelist_elem.max_ltv * ltv_factor,
+        ltv_ramp_start: ramped.then_some(status_info.ltv_ramp_start),
+        ltv_ramp_end: ramped.then_some(status_info.ltv_ramp_end),
+        status: status_info.status,
+    })
+}
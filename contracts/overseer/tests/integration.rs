@@ -0,0 +1,867 @@
+//! Lock, borrow and liquidation flows through the overseer, with the
+//! market, interest model, distribution model, oracle, custody and
+//! liquidation contracts wired together in a simulated chain
+
+use std::str::FromStr;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    to_binary, Addr, Api, Binary, BlockInfo, CanonicalAddr, Coin, CosmosMsg, Decimal, DepsMut, Env,
+    MessageInfo, RecoverPubkeyError, Response, StdError, StdResult, Storage, Uint128,
+    VerificationError, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::{
+    App, AppBuilder, AppResponse, Contract, ContractWrapper, CustomHandler, Executor,
+};
+use terra_cosmwasm::{
+    TaxCapResponse, TaxRateResponse, TerraMsgWrapper, TerraQuery, TerraQueryWrapper,
+};
+
+use moneymarket::custody::{
+    BAssetInfo, BorrowerResponse, Cw20HookMsg as CustodyCw20HookMsg,
+    ExecuteMsg as CustodyExecuteMsg, InstantiateMsg as CustodyInstantiateMsg,
+    QueryMsg as CustodyQueryMsg,
+};
+use moneymarket::distribution_model::InstantiateMsg as DistributionModelInstantiateMsg;
+use moneymarket::interest_model::InstantiateMsg as InterestModelInstantiateMsg;
+use moneymarket::liquidation::{
+    ExecuteMsg as LiquidationExecuteMsg, InstantiateMsg as LiquidationInstantiateMsg,
+};
+use moneymarket::market::{
+    BorrowerInfoResponse, ConfigResponse as MarketConfigResponse, ExecuteMsg as MarketExecuteMsg,
+    InstantiateMsg as MarketInstantiateMsg, QueryMsg as MarketQueryMsg,
+};
+use moneymarket::oracle::{ExecuteMsg as OracleExecuteMsg, InstantiateMsg as OracleInstantiateMsg};
+use moneymarket::overseer::{
+    BorrowLimitResponse, CollateralsResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+};
+use moneymarket_market::error::ContractError as MarketContractError;
+
+const STABLE_DENOM: &str = "uusd";
+const OWNER: &str = "owner";
+const FEEDER: &str = "feeder";
+const DEPOSITOR: &str = "depositor";
+const BORROWER: &str = "borrower";
+const LIQUIDATOR: &str = "liquidator";
+
+/// Api keeping the case of the addresses, the simulated chain
+/// names its contracts `Contract #<index>`
+struct CaseSensitiveApi(MockApi);
+
+impl Api for CaseSensitiveApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_canonicalize(human)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        if human.len() < 3 {
+            return Err(StdError::generic_err(
+                "Invalid input: human address too short",
+            ));
+        }
+        Ok(CanonicalAddr::from(human.as_bytes()))
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        String::from_utf8(canonical.to_vec())
+            .map(Addr::unchecked)
+            .map_err(StdError::invalid_utf8)
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+/// Terra treasury without any tax
+struct TerraMock;
+
+impl CustomHandler<TerraMsgWrapper, TerraQueryWrapper> for TerraMock {
+    fn execute(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _block: &BlockInfo,
+        sender: Addr,
+        msg: TerraMsgWrapper,
+    ) -> anyhow::Result<AppResponse> {
+        panic!("Unexpected terra msg {:?} from {:?}", msg, sender)
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _block: &BlockInfo,
+        msg: TerraQueryWrapper,
+    ) -> anyhow::Result<Binary> {
+        match msg.query_data {
+            TerraQuery::TaxRate {} => Ok(to_binary(&TaxRateResponse {
+                rate: Decimal::zero(),
+            })?),
+            TerraQuery::TaxCap { .. } => Ok(to_binary(&TaxCapResponse {
+                cap: Uint128::zero(),
+            })?),
+            query_data => panic!("Unexpected terra query {:?}", query_data),
+        }
+    }
+}
+
+type TerraApp = App<TerraMsgWrapper, TerraQueryWrapper>;
+
+fn contract_token() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+fn contract_overseer() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(
+        ContractWrapper::new_with_empty(
+            moneymarket_overseer::contract::execute,
+            moneymarket_overseer::contract::instantiate,
+            moneymarket_overseer::contract::query,
+        )
+        .with_reply_empty(moneymarket_overseer::contract::reply),
+    )
+}
+
+/// The simulated chain requires a label on every contract,
+/// the market instantiates its aterra token without any
+fn instantiate_market(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: MarketInstantiateMsg,
+) -> Result<Response, MarketContractError> {
+    let mut res = moneymarket_market::contract::instantiate(deps, env, info, msg)?;
+    for sub_msg in res.messages.iter_mut() {
+        if let CosmosMsg::Wasm(WasmMsg::Instantiate { label, .. }) = &mut sub_msg.msg {
+            *label = "aterra".to_string();
+        }
+    }
+    Ok(res)
+}
+
+fn contract_market() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(
+        ContractWrapper::new_with_empty(
+            moneymarket_market::contract::execute,
+            instantiate_market,
+            moneymarket_market::contract::query,
+        )
+        .with_reply_empty(moneymarket_market::contract::reply),
+    )
+}
+
+fn contract_interest_model() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        moneymarket_interest_model::contract::execute,
+        moneymarket_interest_model::contract::instantiate,
+        moneymarket_interest_model::contract::query,
+    ))
+}
+
+fn contract_distribution_model() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        moneymarket_distribution_model::contract::execute,
+        moneymarket_distribution_model::contract::instantiate,
+        moneymarket_distribution_model::contract::query,
+    ))
+}
+
+fn contract_oracle() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(
+        ContractWrapper::new_with_empty(
+            moneymarket_oracle::contract::execute,
+            moneymarket_oracle::contract::instantiate,
+            moneymarket_oracle::contract::query,
+        )
+        .with_reply_empty(moneymarket_oracle::contract::reply),
+    )
+}
+
+fn contract_liquidation() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        moneymarket_liquidation::contract::execute,
+        moneymarket_liquidation::contract::instantiate,
+        moneymarket_liquidation::contract::query,
+    ))
+}
+
+/// The custody is instantiated without any message, so its
+/// response is the same with terra messages
+fn instantiate_custody(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CustodyInstantiateMsg,
+) -> StdResult<Response<TerraMsgWrapper>> {
+    let res = moneymarket_custody_bluna::contract::instantiate(deps, env, info, msg)?;
+    Ok(Response::new().add_attributes(res.attributes))
+}
+
+fn contract_custody() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(
+        ContractWrapper::new(
+            moneymarket_custody_bluna::contract::execute,
+            instantiate_custody,
+            moneymarket_custody_bluna::contract::query,
+        )
+        .with_reply(moneymarket_custody_bluna::contract::reply),
+    )
+}
+
+/// Contracts of a money market with a single bLuna collateral
+struct Suite {
+    app: TerraApp,
+    overseer: Addr,
+    market: Addr,
+    oracle: Addr,
+    custody: Addr,
+    liquidation: Addr,
+    bluna: Addr,
+}
+
+impl Suite {
+    fn new() -> Self {
+        let mut app: TerraApp = AppBuilder::new()
+            .with_api(CaseSensitiveApi(MockApi::default()))
+            .with_custom(TerraMock)
+            .build();
+        for (addr, amount) in [
+            (OWNER, 1_000_000u128),
+            (DEPOSITOR, 100_000_000u128),
+            (BORROWER, 10_000_000u128),
+            (LIQUIDATOR, 10_000_000u128),
+        ] {
+            app.init_bank_balance(
+                &Addr::unchecked(addr),
+                vec![Coin::new(amount, STABLE_DENOM)],
+            )
+            .unwrap();
+        }
+
+        let owner = Addr::unchecked(OWNER);
+        let token_code_id = app.store_code(contract_token());
+        let overseer_code_id = app.store_code(contract_overseer());
+        let market_code_id = app.store_code(contract_market());
+        let interest_model_code_id = app.store_code(contract_interest_model());
+        let distribution_model_code_id = app.store_code(contract_distribution_model());
+        let oracle_code_id = app.store_code(contract_oracle());
+        let liquidation_code_id = app.store_code(contract_liquidation());
+        let custody_code_id = app.store_code(contract_custody());
+
+        let bluna = app
+            .instantiate_contract(
+                token_code_id,
+                owner.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "bonded luna".to_string(),
+                    symbol: "BLUNA".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: BORROWER.to_string(),
+                        amount: Uint128::from(1_000_000u128),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "bluna",
+                None,
+            )
+            .unwrap();
+
+        let oracle = app
+            .instantiate_contract(
+                oracle_code_id,
+                owner.clone(),
+                &OracleInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    base_asset: STABLE_DENOM.to_string(),
+                },
+                &[],
+                "oracle",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            oracle.clone(),
+            &OracleExecuteMsg::RegisterFeeder {
+                asset: bluna.to_string(),
+                feeder: FEEDER.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let interest_model = app
+            .instantiate_contract(
+                interest_model_code_id,
+                owner.clone(),
+                &InterestModelInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    base_rate: Decimal256::from_str("0.000000002").unwrap(),
+                    optimal_utilization: Decimal256::percent(80),
+                    slope1: Decimal256::from_str("0.00000002").unwrap(),
+                    slope2: Decimal256::from_str("0.0000002").unwrap(),
+                    reserve_factor: Decimal256::zero(),
+                },
+                &[],
+                "interest model",
+                None,
+            )
+            .unwrap();
+
+        let distribution_model = app
+            .instantiate_contract(
+                distribution_model_code_id,
+                owner.clone(),
+                &DistributionModelInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    emission_cap: Decimal256::from_uint256(100u64),
+                    emission_floor: Decimal256::from_uint256(10u64),
+                    increment_multiplier: Decimal256::percent(110),
+                    decrement_multiplier: Decimal256::percent(90),
+                },
+                &[],
+                "distribution model",
+                None,
+            )
+            .unwrap();
+
+        let market = app
+            .instantiate_contract(
+                market_code_id,
+                owner.clone(),
+                &MarketInstantiateMsg {
+                    owner_addr: OWNER.to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    aterra_code_id: token_code_id,
+                    anc_emission_rate: Decimal256::one(),
+                    max_borrow_factor: Decimal256::one(),
+                },
+                &[Coin::new(1_000_000u128, STABLE_DENOM)],
+                "market",
+                None,
+            )
+            .unwrap();
+
+        let liquidation = app
+            .instantiate_contract(
+                liquidation_code_id,
+                owner.clone(),
+                &LiquidationInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    oracle_contract: oracle.to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    safe_ratio: Decimal256::percent(80),
+                    bid_fee: Decimal256::percent(1),
+                    max_premium_rate: Decimal256::percent(30),
+                    liquidation_threshold: Uint256::from(200u64),
+                    price_timeframe: 60u64,
+                },
+                &[],
+                "liquidation",
+                None,
+            )
+            .unwrap();
+
+        let overseer = app
+            .instantiate_contract(
+                overseer_code_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    owner_addr: OWNER.to_string(),
+                    oracle_contract: oracle.to_string(),
+                    market_contract: market.to_string(),
+                    liquidation_contract: liquidation.to_string(),
+                    collector_contract: "collector".to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    epoch_period: 86400u64,
+                    threshold_deposit_rate: Decimal256::permille(3),
+                    target_deposit_rate: Decimal256::permille(5),
+                    buffer_distribution_factor: Decimal256::percent(20),
+                    anc_purchase_factor: Decimal256::percent(20),
+                    price_timeframe: 60u64,
+                },
+                &[],
+                "overseer",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            owner.clone(),
+            market.clone(),
+            &MarketExecuteMsg::RegisterContracts {
+                overseer_contract: overseer.to_string(),
+                interest_model: interest_model.to_string(),
+                distribution_model: distribution_model.to_string(),
+                collector_contract: "collector".to_string(),
+                distributor_contract: "distributor".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let custody = app
+            .instantiate_contract(
+                custody_code_id,
+                owner.clone(),
+                &CustodyInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    collateral_token: bluna.to_string(),
+                    overseer_contract: overseer.to_string(),
+                    market_contract: market.to_string(),
+                    reward_contract: "reward".to_string(),
+                    liquidation_contract: liquidation.to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    basset_info: BAssetInfo {
+                        name: "bonded luna".to_string(),
+                        symbol: "BLUNA".to_string(),
+                        decimals: 6,
+                    },
+                },
+                &[],
+                "custody bluna",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            owner,
+            overseer.clone(),
+            &ExecuteMsg::Whitelist {
+                name: "bonded luna".to_string(),
+                symbol: "BLUNA".to_string(),
+                collateral_token: bluna.to_string(),
+                custody_contract: custody.to_string(),
+                max_ltv: Decimal256::percent(50),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // lenders provide the market liquidity
+        app.execute_contract(
+            Addr::unchecked(DEPOSITOR),
+            market.clone(),
+            &MarketExecuteMsg::DepositStable {},
+            &[Coin::new(100_000_000u128, STABLE_DENOM)],
+        )
+        .unwrap();
+
+        Suite {
+            app,
+            overseer,
+            market,
+            oracle,
+            custody,
+            liquidation,
+            bluna,
+        }
+    }
+
+    fn next_block(&mut self) {
+        self.app.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(6);
+        });
+    }
+
+    fn feed_price(&mut self, price: Decimal256) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(FEEDER),
+                self.oracle.clone(),
+                &OracleExecuteMsg::FeedPrice {
+                    prices: vec![(self.bluna.to_string(), price)],
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    /// Deposit the collateral in the custody and lock it in the overseer
+    fn deposit_and_lock(&mut self, borrower: &str, amount: u128) -> anyhow::Result<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(borrower),
+            self.bluna.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.custody.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {}).unwrap(),
+            },
+            &[],
+        )?;
+        self.app.execute_contract(
+            Addr::unchecked(borrower),
+            self.overseer.clone(),
+            &ExecuteMsg::LockCollateral {
+                collaterals: vec![(self.bluna.to_string(), Uint256::from(amount))],
+            },
+            &[],
+        )
+    }
+
+    fn borrow(&mut self, borrower: &str, amount: u128) -> anyhow::Result<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(borrower),
+            self.market.clone(),
+            &MarketExecuteMsg::BorrowStable {
+                borrow_amount: Uint256::from(amount),
+                to: None,
+            },
+            &[],
+        )
+    }
+
+    fn loan_amount(&self, borrower: &str) -> Uint256 {
+        let res: BorrowerInfoResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.market.clone(),
+                &MarketQueryMsg::BorrowerInfo {
+                    borrower: borrower.to_string(),
+                    block_height: Some(self.app.block_info().height),
+                },
+            )
+            .unwrap();
+        res.loan_amount
+    }
+
+    fn borrow_limit(&self, borrower: &str) -> Uint256 {
+        let res: BorrowLimitResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.overseer.clone(),
+                &QueryMsg::BorrowLimit {
+                    borrower: borrower.to_string(),
+                    block_time: None,
+                },
+            )
+            .unwrap();
+        res.borrow_limit
+    }
+
+    fn locked_collateral(&self, borrower: &str) -> Uint256 {
+        let res: CollateralsResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.overseer.clone(),
+                &QueryMsg::Collaterals {
+                    borrower: borrower.to_string(),
+                },
+            )
+            .unwrap();
+        res.collaterals
+            .iter()
+            .find(|(token, _)| token == self.bluna.as_str())
+            .map(|(_, amount)| *amount)
+            .unwrap_or_default()
+    }
+
+    fn custody_balance(&self, borrower: &str) -> BorrowerResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.custody.clone(),
+                &CustodyQueryMsg::Borrower {
+                    address: borrower.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn bluna_balance(&self, address: &str) -> Uint128 {
+        let res: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.bluna.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    }
+
+    fn stable_balance(&self, address: &Addr) -> Uint128 {
+        self.app
+            .wrap()
+            .query_balance(address, STABLE_DENOM)
+            .unwrap()
+            .amount
+    }
+}
+
+#[test]
+fn proper_wiring() {
+    let suite = Suite::new();
+
+    let config: MarketConfigResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.market.clone(), &MarketQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.overseer_contract, suite.overseer.to_string());
+
+    // the aterra token is instantiated by the market
+    let aterra = Addr::unchecked(config.aterra_contract);
+    let res: BalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            aterra,
+            &Cw20QueryMsg::Balance {
+                address: DEPOSITOR.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.balance, Uint128::from(100_000_000u128));
+    assert_eq!(
+        suite.stable_balance(&suite.market),
+        Uint128::from(101_000_000u128)
+    );
+}
+
+#[test]
+fn lock_borrow_and_liquidate() {
+    let mut suite = Suite::new();
+    suite.feed_price(Decimal256::from_uint256(10u64));
+
+    // 1_000_000 bLuna at 10 uusd, 50% LTV
+    suite.deposit_and_lock(BORROWER, 1_000_000).unwrap();
+    assert_eq!(
+        suite.locked_collateral(BORROWER),
+        Uint256::from(1_000_000u64)
+    );
+    assert_eq!(suite.borrow_limit(BORROWER), Uint256::from(5_000_000u64));
+
+    let err = suite.borrow(BORROWER, 5_000_001).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Loan liability becomes greater than borrow limit"));
+
+    suite.borrow(BORROWER, 4_000_000).unwrap();
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::from(4_000_000u64));
+    assert_eq!(
+        suite.stable_balance(&Addr::unchecked(BORROWER)),
+        Uint128::from(14_000_000u128)
+    );
+
+    // locked collateral only leaves the custody through the overseer
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(BORROWER),
+            suite.custody.clone(),
+            &CustodyExecuteMsg::WithdrawCollateral {
+                amount: Some(Uint256::from(1u64)),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Withdraw amount cannot exceed the user's spendable amount"));
+
+    // a safe loan cannot be liquidated
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            suite.liquidation.clone(),
+            &LiquidationExecuteMsg::SubmitBid {
+                collateral_token: suite.bluna.to_string(),
+                premium_rate: Decimal256::percent(5),
+            },
+            &[Coin::new(10_000_000u128, STABLE_DENOM)],
+        )
+        .unwrap();
+    let liquidate_msg = ExecuteMsg::LiquidateCollateral {
+        borrower: BORROWER.to_string(),
+        max_spread: None,
+    };
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            suite.overseer.clone(),
+            &liquidate_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Cannot liquidate safely collateralized loan"));
+
+    // the price drop puts the loan above the borrow limit
+    suite.next_block();
+    suite.feed_price(Decimal256::from_uint256(7u64));
+    assert_eq!(suite.borrow_limit(BORROWER), Uint256::from(3_500_000u64));
+
+    let market_balance = suite.stable_balance(&suite.market);
+    let loan_amount = suite.loan_amount(BORROWER);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(LIQUIDATOR),
+            suite.overseer.clone(),
+            &liquidate_msg,
+            &[],
+        )
+        .unwrap();
+
+    // the liquidator bought the seized collateral
+    let seized = suite.bluna_balance(LIQUIDATOR);
+    assert!(!seized.is_zero());
+    assert_eq!(
+        suite.locked_collateral(BORROWER),
+        Uint256::from(1_000_000u64) - Uint256::from(seized)
+    );
+    assert_eq!(
+        suite.custody_balance(BORROWER).balance,
+        Uint256::from(1_000_000u64) - Uint256::from(seized)
+    );
+
+    // and its bid repaid the loan in the market
+    let repaid = suite.stable_balance(&suite.market) - market_balance;
+    assert!(!repaid.is_zero());
+    // the interest index division rounds the loan down
+    let expected_loan_amount = loan_amount - Uint256::from(repaid);
+    let loan_amount = suite.loan_amount(BORROWER);
+    assert!(loan_amount <= expected_loan_amount);
+    assert!(expected_loan_amount - loan_amount <= Uint256::one());
+    assert!(suite.borrow_limit(BORROWER) >= suite.loan_amount(BORROWER));
+}
+
+#[test]
+fn repay_unlock_and_withdraw() {
+    let mut suite = Suite::new();
+    suite.feed_price(Decimal256::from_uint256(10u64));
+
+    suite.deposit_and_lock(BORROWER, 1_000_000).unwrap();
+    suite.borrow(BORROWER, 4_000_000).unwrap();
+
+    // the remaining collateral must cover the loan
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![(suite.bluna.to_string(), Uint256::from(1_000_000u64))],
+    };
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(BORROWER),
+            suite.overseer.clone(),
+            &unlock_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Loan liability becomes greater than borrow limit"));
+
+    // a stale price stops the borrows
+    suite.app.update_block(|block| {
+        block.height += 100;
+        block.time = block.time.plus_seconds(600);
+    });
+    let err = suite.borrow(BORROWER, 100).unwrap_err();
+    assert!(err.to_string().contains("is too old"));
+
+    // repaying more than the loan refunds the difference
+    suite.feed_price(Decimal256::from_uint256(10u64));
+    let loan_amount = suite.loan_amount(BORROWER);
+    assert!(loan_amount > Uint256::from(4_000_000u64));
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(BORROWER),
+            suite.market.clone(),
+            &MarketExecuteMsg::RepayStable {},
+            &[Coin::new(5_000_000u128, STABLE_DENOM)],
+        )
+        .unwrap();
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::zero());
+    assert_eq!(
+        suite.stable_balance(&Addr::unchecked(BORROWER)),
+        Uint128::from(14_000_000u128) - Uint128::from(loan_amount)
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(BORROWER),
+            suite.overseer.clone(),
+            &unlock_msg,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(suite.locked_collateral(BORROWER), Uint256::zero());
+    assert_eq!(
+        suite.custody_balance(BORROWER).spendable,
+        Uint256::from(1_000_000u64)
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(BORROWER),
+            suite.custody.clone(),
+            &CustodyExecuteMsg::WithdrawCollateral { amount: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(suite.bluna_balance(BORROWER), Uint128::from(1_000_000u128));
+    assert_eq!(suite.custody_balance(BORROWER).balance, Uint256::zero());
+}
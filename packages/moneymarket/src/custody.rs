@@ -1,8 +1,19 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
 use cw20::Cw20ReceiveMsg;
+use std::fmt;
+
+use crate::liquidation_venue::{LiquidationVenue, LiquidationVenueResponse};
+use crate::querier::FeeModel;
+use crate::swap::SwapRoute;
+
+/// Version of the overseer <-> custody interface this package defines;
+/// the overseer only drives custody contracts reporting this version
+pub const CUSTODY_INTERFACE_VERSION: u32 = 1;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -40,6 +51,13 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         liquidation_contract: Option<String>,
     },
+    /// Route the liquidated collateral to the bid queue
+    /// or to an external venue adapter
+    UpdateLiquidationVenue { liquidation_venue: LiquidationVenue },
+    /// Fee model the chain charges the native transfers with
+    UpdateFeeModel { fee_model: FeeModel },
+    /// Where the claimed rewards are swapped to the stable denom
+    UpdateRewardSwapRoute { reward_swap_route: SwapRoute },
     /// Make specified amount of tokens unspendable
     LockCollateral { borrower: String, amount: Uint256 },
     /// Make specified amount of collateral tokens spendable
@@ -53,6 +71,20 @@ pub enum ExecuteMsg {
         liquidator: String,
         borrower: String,
         amount: Uint256,
+        /// Sell the collateral on the DEX within the max spread instead of the bids
+        max_spread: Option<Decimal256>,
+    },
+
+    /// Sell locked collateral of the borrower to the zero premium bids
+    /// of the liquidation contract and repay the loan with the proceeds
+    SelfLiquidateCollateral { borrower: String, amount: Uint256 },
+
+    /// Send locked collateral to the replacing custody contract,
+    /// which credits it to the borrower
+    MigrateCollateral {
+        borrower: String,
+        amount: Uint256,
+        new_custody: String,
     },
 
     ////////////////////
@@ -70,6 +102,9 @@ pub enum ExecuteMsg {
 pub enum Cw20HookMsg {
     /// Deposit collateral token
     DepositCollateral {},
+    /// Deposit collateral token migrated from
+    /// the replaced custody contract on behalf of the borrower
+    ReceiveMigratedCollateral { borrower: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -83,6 +118,10 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Interface version and the overseer operations the custody implements
+    InterfaceVersion {},
+    LiquidationVenue {},
+    RewardSwapRoute {},
 }
 
 // We define a custom struct for each query response
@@ -112,9 +151,94 @@ pub struct BorrowersResponse {
     pub borrowers: Vec<BorrowerResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterfaceVersionResponse {
+    pub version: u32,
+    pub capabilities: Vec<CustodyCapability>,
+}
+
+/// Overseer operation a custody contract can be driven with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CustodyCapability {
+    LockCollateral,
+    UnlockCollateral,
+    LiquidateCollateral,
+    DistributeRewards,
+    MigrateCollateral,
+    SelfLiquidateCollateral,
+}
+
+impl fmt::Display for CustodyCapability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CustodyCapability::LockCollateral => write!(f, "lock_collateral"),
+            CustodyCapability::UnlockCollateral => write!(f, "unlock_collateral"),
+            CustodyCapability::LiquidateCollateral => write!(f, "liquidate_collateral"),
+            CustodyCapability::DistributeRewards => write!(f, "distribute_rewards"),
+            CustodyCapability::MigrateCollateral => write!(f, "migrate_collateral"),
+            CustodyCapability::SelfLiquidateCollateral => write!(f, "self_liquidate_collateral"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BAssetInfo {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardSwapRouteResponse {
+    pub reward_swap_route: SwapRoute,
+}
+
+/// Typed client of the custody queries
+pub struct CustodyQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> CustodyQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        CustodyQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn borrower(&self, address: String) -> StdResult<BorrowerResponse> {
+        self.query(&QueryMsg::Borrower { address })
+    }
+
+    pub fn borrowers(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BorrowersResponse> {
+        self.query(&QueryMsg::Borrowers { start_after, limit })
+    }
+
+    /// Interface version and the overseer operations the custody implements
+    pub fn interface_version(&self) -> StdResult<InterfaceVersionResponse> {
+        self.query(&QueryMsg::InterfaceVersion {})
+    }
+
+    pub fn liquidation_venue(&self) -> StdResult<LiquidationVenueResponse> {
+        self.query(&QueryMsg::LiquidationVenue {})
+    }
+
+    pub fn reward_swap_route(&self) -> StdResult<RewardSwapRouteResponse> {
+        self.query(&QueryMsg::RewardSwapRoute {})
+    }
+}
@@ -1,7 +1,9 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -22,6 +24,126 @@ pub enum ExecuteMsg {
         increment_multiplier: Option<Decimal256>,
         decrement_multiplier: Option<Decimal256>,
     },
+    /// Set the decay schedule of the emission, or only adjust it to the
+    /// deposit rate with `None`
+    UpdateEmissionSchedule {
+        emission_schedule: Option<EmissionSchedule>,
+    },
+    /// Register the market for a weighted share of the global emission,
+    /// or deregister it with `None`
+    UpdateMarketWeight {
+        market: String,
+        market_weight: Option<MarketWeight>,
+    },
+    /// Adjust the global emission to the deposit rate, and allocate it
+    /// across the registered markets by weight
+    UpdateEmissionRate {
+        deposit_rate: Decimal256,
+        target_deposit_rate: Decimal256,
+        threshold_deposit_rate: Decimal256,
+    },
+    /// Set the share of the emission incentivizing borrowers, the rest
+    /// incentivizing depositors
+    UpdateEmissionSplit { borrower_share: Decimal256 },
+    /// Set the runway thresholds throttling the emission, or stop
+    /// throttling it with `None`
+    UpdateReserveGuard { reserve_guard: Option<ReserveGuard> },
+    /// Throttle or release the emission with the interest buffer runway
+    /// reported by the overseer. Can be sent by anyone
+    UpdateReserveHealth {},
+    /// Set the gauge voting the weights of the markets, or keep the
+    /// weights set by the owner with `None`
+    UpdateGauge { gauge: Option<Gauge> },
+    /// Weigh the registered markets by their gauge votes, once an epoch.
+    /// Can be sent by anyone
+    SyncGaugeWeights {},
+    /// Credit or debit the pending emission budget of a market, to
+    /// correct a past mis-distribution. Recorded with its reason
+    AdjustEmission {
+        market: String,
+        delta: EmissionDelta,
+        reason: String,
+    },
+    /// Route the emission of a market into a vesting escrow, or pay it
+    /// out directly with `None`
+    UpdateVesting {
+        market: String,
+        vesting: Option<Vesting>,
+    },
+    /// Set the total amount the model may emit, or lift the limit with
+    /// `None`. Once emitted, the emission rate is zero
+    UpdateEmissionBudget { emission_budget: Option<Uint256> },
+}
+
+/// Vesting of the emission of a market, routed into the escrow each
+/// `UpdateEmissionRate`. Each routed amount vests linearly over
+/// `duration` seconds, nothing vesting before `cliff` seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Vesting {
+    pub escrow_contract: String,
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmissionDelta {
+    Credit { amount: Uint256 },
+    Debit { amount: Uint256 },
+}
+
+/// Gauge contract voting the weights of the markets each epoch. A market
+/// is weighted by its share of the votes, bounded to `min_share` and
+/// `max_share`, so a thin vote can not starve or capture the emission.
+/// The emission is then allocated in proportion to the bounded weights
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Gauge {
+    pub contract_addr: String,
+    /// In seconds
+    pub epoch_period: u64,
+    pub min_share: Decimal256,
+    pub max_share: Decimal256,
+}
+
+/// Throttling of the emission while the runway of the interest buffer
+/// of the overseer, in epochs, is short. The emission is throttled once
+/// the runway falls below `low_runway`, and released once it recovers to
+/// `high_runway`. While throttled, the emission cap and floor are scaled
+/// by the runway over `high_runway`, down to zero with the buffer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveGuard {
+    pub overseer_contract: String,
+    pub low_runway: Decimal256,
+    pub high_runway: Decimal256,
+}
+
+/// Share of the global emission of a market, weight over the total weight
+/// of the markets, up to its emission cap. The emission above the cap is
+/// not distributed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketWeight {
+    pub weight: Decimal256,
+    pub emission_cap: Option<Decimal256>,
+}
+
+/// Decay of the emission over time. The emission cap and floor are scaled
+/// by the schedule, the emission rate adjusting within the scaled bounds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmissionSchedule {
+    /// Halved every `halving_period` blocks from `start_height`
+    Halving {
+        start_height: u64,
+        halving_period: u64,
+    },
+    /// Scaled by the last phase started, in increasing start heights
+    Piecewise { phases: Vec<EmissionPhase> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionPhase {
+    pub start_height: u64,
+    pub scale: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -34,6 +156,36 @@ pub enum QueryMsg {
         threshold_deposit_rate: Decimal256,
         current_emission_rate: Decimal256,
     },
+    /// Emission the next `UpdateEmissionRate` would set from
+    /// `current_rate`, without updating it
+    SimulateEmissionUpdate {
+        deposit_rate: Decimal256,
+        target_deposit_rate: Decimal256,
+        threshold_deposit_rate: Decimal256,
+        current_rate: Decimal256,
+    },
+    EmissionSchedule {},
+    EmissionState {},
+    EmissionSplit {},
+    ReserveGuard {},
+    Gauge {},
+    Vesting {
+        market: String,
+    },
+    EmissionBudget {},
+    MarketEmissions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    PendingEmission {
+        market: String,
+    },
+    /// Adjustments in order, of all markets or of `market` only
+    EmissionAdjustments {
+        market: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -50,4 +202,228 @@ pub struct ConfigResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AncEmissionRateResponse {
     pub emission_rate: Decimal256,
+    /// Share of the emission rate incentivizing borrowers
+    pub borrower_emission_rate: Decimal256,
+    /// Share of the emission rate incentivizing depositors
+    pub depositor_emission_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateEmissionUpdateResponse {
+    pub emission_rate: Decimal256,
+    pub borrower_emission_rate: Decimal256,
+    pub depositor_emission_rate: Decimal256,
+    /// Bounds of the emission rate at the current block
+    pub emission_cap: Decimal256,
+    pub emission_floor: Decimal256,
+    /// Allocation of the emission rate across the markets
+    pub markets: Vec<MarketEmissionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionScheduleResponse {
+    pub emission_schedule: Option<EmissionSchedule>,
+    /// Scale of the emission at the current block
+    pub scale: Decimal256,
+    pub emission_cap: Decimal256,
+    pub emission_floor: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionStateResponse {
+    pub global_emission_rate: Decimal256,
+    pub total_weight: Decimal256,
+    pub last_updated_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketEmissionResponse {
+    pub market: String,
+    pub weight: Decimal256,
+    pub emission_cap: Option<Decimal256>,
+    pub emission_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketEmissionsResponse {
+    pub markets: Vec<MarketEmissionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionSplitResponse {
+    pub borrower_share: Decimal256,
+    pub depositor_share: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveGuardResponse {
+    pub reserve_guard: Option<ReserveGuard>,
+    pub throttled: bool,
+    /// Runway at the last health update; `None` when the buffer was not
+    /// spent
+    pub runway: Option<Decimal256>,
+    pub scale: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeResponse {
+    pub gauge: Option<Gauge>,
+    pub last_synced_time: u64,
+}
+
+/// Net pending emission budget of a market, at most one of `credit`
+/// and `debit` being positive
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingEmissionResponse {
+    pub market: String,
+    pub credit: Uint256,
+    pub debit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionAdjustmentResponse {
+    pub adjustment_id: u64,
+    pub market: String,
+    pub delta: EmissionDelta,
+    pub reason: String,
+    pub adjuster: String,
+    pub height: u64,
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionAdjustmentsResponse {
+    pub adjustments: Vec<EmissionAdjustmentResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingResponse {
+    pub market: String,
+    pub vesting: Option<Vesting>,
+    /// Emission routed into the escrow
+    pub routed_amount: Uint256,
+    /// Routed emission vested in the escrow
+    pub vested_amount: Uint256,
+    /// Vested emission not yet claimed from the escrow
+    pub claimable_amount: Uint256,
+}
+
+/// `emitted_amount` adds up the global emission of each epoch
+/// closed by `UpdateEmissionRate`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionBudgetResponse {
+    pub emission_budget: Option<Uint256>,
+    pub emitted_amount: Uint256,
+    pub remaining_amount: Option<Uint256>,
+    pub exhausted: bool,
+}
+
+/// Typed client of the distribution model queries
+pub struct DistributionModelQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> DistributionModelQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        DistributionModelQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn anc_emission_rate(
+        &self,
+        deposit_rate: Decimal256,
+        target_deposit_rate: Decimal256,
+        threshold_deposit_rate: Decimal256,
+        current_emission_rate: Decimal256,
+    ) -> StdResult<AncEmissionRateResponse> {
+        self.query(&QueryMsg::AncEmissionRate {
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+            current_emission_rate,
+        })
+    }
+
+    /// Emission the next `UpdateEmissionRate` would set from
+    /// `current_rate`, without updating it
+    pub fn simulate_emission_update(
+        &self,
+        deposit_rate: Decimal256,
+        target_deposit_rate: Decimal256,
+        threshold_deposit_rate: Decimal256,
+        current_rate: Decimal256,
+    ) -> StdResult<SimulateEmissionUpdateResponse> {
+        self.query(&QueryMsg::SimulateEmissionUpdate {
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+            current_rate,
+        })
+    }
+
+    pub fn emission_schedule(&self) -> StdResult<EmissionScheduleResponse> {
+        self.query(&QueryMsg::EmissionSchedule {})
+    }
+
+    pub fn emission_state(&self) -> StdResult<EmissionStateResponse> {
+        self.query(&QueryMsg::EmissionState {})
+    }
+
+    pub fn emission_split(&self) -> StdResult<EmissionSplitResponse> {
+        self.query(&QueryMsg::EmissionSplit {})
+    }
+
+    pub fn reserve_guard(&self) -> StdResult<ReserveGuardResponse> {
+        self.query(&QueryMsg::ReserveGuard {})
+    }
+
+    pub fn gauge(&self) -> StdResult<GaugeResponse> {
+        self.query(&QueryMsg::Gauge {})
+    }
+
+    pub fn vesting(&self, market: String) -> StdResult<VestingResponse> {
+        self.query(&QueryMsg::Vesting { market })
+    }
+
+    pub fn emission_budget(&self) -> StdResult<EmissionBudgetResponse> {
+        self.query(&QueryMsg::EmissionBudget {})
+    }
+
+    pub fn market_emissions(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MarketEmissionsResponse> {
+        self.query(&QueryMsg::MarketEmissions { start_after, limit })
+    }
+
+    pub fn pending_emission(&self, market: String) -> StdResult<PendingEmissionResponse> {
+        self.query(&QueryMsg::PendingEmission { market })
+    }
+
+    /// Adjustments in order, of all markets or of `market` only
+    pub fn emission_adjustments(
+        &self,
+        market: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<EmissionAdjustmentsResponse> {
+        self.query(&QueryMsg::EmissionAdjustments {
+            market,
+            start_after,
+            limit,
+        })
+    }
 }
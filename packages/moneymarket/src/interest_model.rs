@@ -1,25 +1,117 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
 
+/// Seconds between the proposal of a config update and its execution
+pub const CONFIG_UPDATE_DELAY: u64 = 172800;
+
+/// The borrow rate rises from `base_rate` by `slope1` up to the optimal
+/// utilization, then by `slope2` up to a full utilization. The deposits
+/// earn the borrow interest but for the `reserve_factor` share kept as
+/// reserves. This default curve prices the markets without a curve of
+/// their own
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub owner: String,
     pub base_rate: Decimal256,
-    pub interest_multiplier: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub slope1: Decimal256,
+    pub slope2: Decimal256,
+    pub reserve_factor: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// Propose a config update, executable [CONFIG_UPDATE_DELAY] seconds
+    /// later. A new proposal replaces the pending one. With a market, the
+    /// curve of the market is updated, starting from the default curve
+    /// when it has none yet
     UpdateConfig {
+        market: Option<String>,
         owner: Option<String>,
         base_rate: Option<Decimal256>,
-        interest_multiplier: Option<Decimal256>,
+        optimal_utilization: Option<Decimal256>,
+        slope1: Option<Decimal256>,
+        slope2: Option<Decimal256>,
+        reserve_factor: Option<Decimal256>,
+    },
+    /// Apply the pending config update once its delay has passed
+    ExecuteConfigUpdate { market: Option<String> },
+    /// Drop the pending config update
+    CancelConfigUpdate { market: Option<String> },
+    /// Set the controller adjusting slope1, or remove it with `None`
+    UpdateRateController {
+        rate_controller: Option<RateController>,
+    },
+    /// Adjust slope1 to the utilization of the market, once per epoch
+    AdjustRate {},
+    /// Price the market at its smoothed utilization, or at its
+    /// instantaneous utilization with `None`
+    UpdateUtilizationSmoothing {
+        market: String,
+        utilization_smoothing: Option<UtilizationSmoothing>,
     },
+    /// Fold the utilization of the sending market, before its
+    /// transaction, into its average
+    RecordUtilization {
+        market_balance: Uint256,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    },
+    /// Set the parameters of the fixed rate quotes, or stop quoting with
+    /// `None`
+    UpdateFixedRateConfig {
+        fixed_rate_config: Option<FixedRateConfig>,
+    },
+}
+
+/// Fixed rates are the borrow rate of the market once the loan is taken,
+/// raised by `duration_premium` of it for each year of the loan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedRateConfig {
+    pub duration_premium: Decimal256,
+    /// Longest loan quoted, in seconds
+    pub max_duration: u64,
 }
 
+/// Moving average of the utilization of a market. Every record moves the
+/// average toward the utilization by the time passed since the last
+/// record over `period`, so a same-block deposit and withdrawal does not
+/// move the rate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UtilizationSmoothing {
+    /// Seconds for the average to reach a new utilization
+    pub period: u64,
+}
+
+/// Controller moving slope1 toward the rate keeping the market at the
+/// target utilization. Each epoch slope1 is moved by `gain` times the
+/// deviation of the utilization from the target, at most by `max_step`
+/// and within `min_slope1` and `max_slope1`, so that a sustained
+/// deviation keeps moving the rate. The epoch is at least an hour, and
+/// `max_step` bounded so that slope1 can not cross its compiled bound
+/// faster than a config update, [CONFIG_UPDATE_DELAY] seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateController {
+    pub market_contract: String,
+    pub target_utilization: Decimal256,
+    /// Seconds between two adjustments
+    pub epoch_period: u64,
+    pub gain: Decimal256,
+    pub max_step: Decimal256,
+    pub min_slope1: Decimal256,
+    pub max_slope1: Decimal256,
+}
+
+/// Migrates the linear model config to the two-slope model, the
+/// interest multiplier becoming `slope1` of a 100% optimal utilization
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -28,6 +120,45 @@ pub enum QueryMsg {
         market_balance: Uint256,
         total_liabilities: Decimal256,
         total_reserves: Decimal256,
+        market: Option<String>,
+    },
+    RateModel {
+        market: Option<String>,
+    },
+    /// Deposit rate of the market at its current utilization
+    DepositRate {
+        market: String,
+    },
+    /// Fixed borrow rate of a loan of `amount` from the market over
+    /// `duration` seconds
+    QuoteFixedRate {
+        market: String,
+        amount: Uint256,
+        duration: u64,
+    },
+    /// Interest accrued over the utilizations of the path, each lasting
+    /// `blocks_per_step` blocks (a single block by default)
+    Backtest {
+        utilization_path: Vec<Decimal256>,
+        blocks_per_step: Option<u64>,
+        market: Option<String>,
+    },
+    /// Rates at `points` utilizations evenly spaced from 0% to 100%
+    RateCurve {
+        points: u32,
+        market: Option<String>,
+    },
+    RateController {},
+    UtilizationAverage {
+        market: String,
+    },
+    FixedRateConfig {},
+    PendingConfig {
+        market: Option<String>,
+    },
+    Markets {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
 }
 
@@ -36,7 +167,10 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: String,
     pub base_rate: Decimal256,
-    pub interest_multiplier: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub slope1: Decimal256,
+    pub slope2: Decimal256,
+    pub reserve_factor: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -44,3 +178,210 @@ pub struct ConfigResponse {
 pub struct BorrowRateResponse {
     pub rate: Decimal256,
 }
+
+/// Borrow rates at the kinks of the curve, linear in between
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateModelResponse {
+    pub base_rate: Decimal256,
+    pub optimal_utilization: Decimal256,
+    /// Borrow rate at the optimal utilization
+    pub optimal_rate: Decimal256,
+    /// Borrow rate at a full utilization
+    pub max_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateControllerResponse {
+    pub rate_controller: Option<RateController>,
+    pub last_adjusted_time: Option<u64>,
+    /// Utilization of the market at the last adjustment
+    pub last_utilization: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfigResponse {
+    pub owner: Option<String>,
+    pub base_rate: Option<Decimal256>,
+    pub optimal_utilization: Option<Decimal256>,
+    pub slope1: Option<Decimal256>,
+    pub slope2: Option<Decimal256>,
+    pub reserve_factor: Option<Decimal256>,
+    pub executable_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketCurveResponse {
+    pub market: String,
+    pub base_rate: Decimal256,
+    pub optimal_utilization: Decimal256,
+    pub slope1: Decimal256,
+    pub slope2: Decimal256,
+    pub reserve_factor: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketsResponse {
+    pub markets: Vec<MarketCurveResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateCurvePoint {
+    pub utilization: Decimal256,
+    pub borrow_rate: Decimal256,
+    /// Borrow interest shared by the deposits at the utilization, net
+    /// of the reserve factor
+    pub deposit_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateCurveResponse {
+    pub points: Vec<RateCurvePoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositRateResponse {
+    pub market: String,
+    pub utilization: Decimal256,
+    pub borrow_rate: Decimal256,
+    pub deposit_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UtilizationAverageResponse {
+    pub market: String,
+    pub utilization_smoothing: Option<UtilizationSmoothing>,
+    pub average_utilization: Option<Decimal256>,
+    pub last_recorded_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedRateConfigResponse {
+    pub fixed_rate_config: Option<FixedRateConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedRateQuoteResponse {
+    pub market: String,
+    pub amount: Uint256,
+    pub duration: u64,
+    /// Utilization of the market once the loan is taken
+    pub utilization: Decimal256,
+    pub floating_rate: Decimal256,
+    pub fixed_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BacktestResponse {
+    /// Growth of a loan over the path
+    pub interest_factor: Decimal256,
+    /// Growth of a deposit over the path
+    pub deposit_factor: Decimal256,
+    pub blocks: u64,
+}
+
+/// Typed client of the interest model queries
+pub struct InterestModelQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> InterestModelQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        InterestModelQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn borrow_rate(
+        &self,
+        market_balance: Uint256,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+        market: Option<String>,
+    ) -> StdResult<BorrowRateResponse> {
+        self.query(&QueryMsg::BorrowRate {
+            market_balance,
+            total_liabilities,
+            total_reserves,
+            market,
+        })
+    }
+
+    pub fn rate_model(&self, market: Option<String>) -> StdResult<RateModelResponse> {
+        self.query(&QueryMsg::RateModel { market })
+    }
+
+    /// Deposit rate of the market at its current utilization
+    pub fn deposit_rate(&self, market: String) -> StdResult<DepositRateResponse> {
+        self.query(&QueryMsg::DepositRate { market })
+    }
+
+    /// Fixed borrow rate of a loan of `amount` from the market over
+    /// `duration` seconds
+    pub fn quote_fixed_rate(
+        &self,
+        market: String,
+        amount: Uint256,
+        duration: u64,
+    ) -> StdResult<FixedRateQuoteResponse> {
+        self.query(&QueryMsg::QuoteFixedRate {
+            market,
+            amount,
+            duration,
+        })
+    }
+
+    /// Interest accrued over the utilizations of the path, each lasting
+    /// `blocks_per_step` blocks (a single block by default)
+    pub fn backtest(
+        &self,
+        utilization_path: Vec<Decimal256>,
+        blocks_per_step: Option<u64>,
+        market: Option<String>,
+    ) -> StdResult<BacktestResponse> {
+        self.query(&QueryMsg::Backtest {
+            utilization_path,
+            blocks_per_step,
+            market,
+        })
+    }
+
+    /// Rates at `points` utilizations evenly spaced from 0% to 100%
+    pub fn rate_curve(&self, points: u32, market: Option<String>) -> StdResult<RateCurveResponse> {
+        self.query(&QueryMsg::RateCurve { points, market })
+    }
+
+    pub fn rate_controller(&self) -> StdResult<RateControllerResponse> {
+        self.query(&QueryMsg::RateController {})
+    }
+
+    pub fn utilization_average(&self, market: String) -> StdResult<UtilizationAverageResponse> {
+        self.query(&QueryMsg::UtilizationAverage { market })
+    }
+
+    pub fn fixed_rate_config(&self) -> StdResult<FixedRateConfigResponse> {
+        self.query(&QueryMsg::FixedRateConfig {})
+    }
+
+    pub fn pending_config(&self, market: Option<String>) -> StdResult<PendingConfigResponse> {
+        self.query(&QueryMsg::PendingConfig { market })
+    }
+
+    pub fn markets(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MarketsResponse> {
+        self.query(&QueryMsg::Markets { start_after, limit })
+    }
+}
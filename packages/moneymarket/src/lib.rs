@@ -4,10 +4,17 @@ pub mod distribution_model;
 pub mod interest_model;
 pub mod liquidation;
 pub mod liquidation_queue;
+pub mod liquidation_venue;
 pub mod market;
+pub mod math;
 pub mod oracle;
 pub mod overseer;
+pub mod pagination;
 pub mod querier;
+pub mod reply;
+pub mod response;
+pub mod reward_index;
+pub mod swap;
 pub mod tokens;
 
 #[cfg(test)]
@@ -1,9 +1,12 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
 use cw20::Cw20ReceiveMsg;
 
+use crate::querier::FeeModel;
 use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -42,6 +45,10 @@ pub enum ExecuteMsg {
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
     },
+    /// Fee model the chain charges the native transfers with
+    UpdateFeeModel {
+        fee_model: FeeModel,
+    },
     SubmitBid {
         collateral_token: String,
         premium_rate: Decimal256,
@@ -50,6 +57,43 @@ pub enum ExecuteMsg {
         collateral_token: String,
         amount: Option<Uint256>,
     },
+    /// Size liquidations to bring the collateral LTV of the borrower
+    /// back to `target_ltv`, or back to the safe ratio with `None`
+    UpdateTargetLtv {
+        target_ltv: Option<Decimal256>,
+    },
+    /// Override the flat config values for a collateral,
+    /// or remove the overrides with `None`
+    UpdateCollateralConfig {
+        collateral_token: String,
+        collateral_config: Option<CollateralConfig>,
+    },
+    /// Reject bid executions on oracle prices outside of the price band
+    /// until confirmed, or remove the price band with `None`
+    UpdatePriceBand {
+        price_band: Option<PriceBand>,
+    },
+    /// Record the oracle price of the collateral, to start or
+    /// confirm a price move out of the price band
+    RecordPrice {
+        collateral_token: String,
+    },
+}
+
+/// Prices moving more than `max_price_change` from the reference price must
+/// be recorded again within the band at least `confirmation_blocks` later
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceBand {
+    pub max_price_change: Decimal256,
+    pub confirmation_blocks: u64,
+}
+
+/// Per-collateral overrides; unset values fall back to the flat config
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralConfig {
+    pub safe_ratio: Option<Decimal256>,
+    pub bid_fee: Option<Decimal256>,
+    pub max_premium_rate: Option<Decimal256>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,6 +103,8 @@ pub enum Cw20HookMsg {
         liquidator: String,
         fee_address: Option<String>,
         repay_address: Option<String>,
+        /// Borrower whose loan is repaid by auctions
+        borrower: Option<String>,
     },
 }
 
@@ -86,6 +132,14 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    TargetLtv {},
+    CollateralConfig {
+        collateral_token: String,
+    },
+    PriceBand {},
+    PriceReference {
+        collateral_token: String,
+    },
 }
 
 // We define a custom struct for each query response
@@ -101,6 +155,21 @@ pub struct ConfigResponse {
     pub price_timeframe: u64,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TargetLtvResponse {
+    pub target_ltv: Option<Decimal256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralConfigResponse {
+    pub collateral_token: String,
+    pub safe_ratio: Decimal256,
+    pub bid_fee: Decimal256,
+    pub max_premium_rate: Decimal256,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LiquidationAmountResponse {
@@ -121,3 +190,115 @@ pub struct BidResponse {
 pub struct BidsResponse {
     pub bids: Vec<BidResponse>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceBandResponse {
+    pub price_band: Option<PriceBand>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceObservation {
+    pub price: Decimal256,
+    pub height: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceReferenceResponse {
+    pub collateral_token: String,
+    pub confirmed: Option<PriceObservation>,
+    /// First record of a price move waiting for confirmation
+    pub pending: Option<PriceObservation>,
+}
+
+/// Typed client of the liquidation contract queries
+pub struct LiquidationQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> LiquidationQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        LiquidationQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn liquidation_amount(
+        &self,
+        borrow_amount: Uint256,
+        borrow_limit: Uint256,
+        collaterals: TokensHuman,
+        collateral_prices: Vec<Decimal256>,
+    ) -> StdResult<LiquidationAmountResponse> {
+        self.query(&QueryMsg::LiquidationAmount {
+            borrow_amount,
+            borrow_limit,
+            collaterals,
+            collateral_prices,
+        })
+    }
+
+    pub fn bid(&self, collateral_token: String, bidder: String) -> StdResult<BidResponse> {
+        self.query(&QueryMsg::Bid {
+            collateral_token,
+            bidder,
+        })
+    }
+
+    pub fn bids_by_user(
+        &self,
+        bidder: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BidsResponse> {
+        self.query(&QueryMsg::BidsByUser {
+            bidder,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn bids_by_collateral(
+        &self,
+        collateral_token: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BidsResponse> {
+        self.query(&QueryMsg::BidsByCollateral {
+            collateral_token,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn target_ltv(&self) -> StdResult<TargetLtvResponse> {
+        self.query(&QueryMsg::TargetLtv {})
+    }
+
+    pub fn collateral_config(
+        &self,
+        collateral_token: String,
+    ) -> StdResult<CollateralConfigResponse> {
+        self.query(&QueryMsg::CollateralConfig { collateral_token })
+    }
+
+    pub fn price_band(&self) -> StdResult<PriceBandResponse> {
+        self.query(&QueryMsg::PriceBand {})
+    }
+
+    pub fn price_reference(&self, collateral_token: String) -> StdResult<PriceReferenceResponse> {
+        self.query(&QueryMsg::PriceReference { collateral_token })
+    }
+}
@@ -1,10 +1,13 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult, Uint128};
 use cw20::Cw20ReceiveMsg;
 
+use crate::querier::FeeModel;
+use crate::swap::SwapRoute;
 use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,6 +50,11 @@ pub enum ExecuteMsg {
         waiting_period: Option<u64>,
         overseer: Option<String>,
     },
+    /// Owner operation to set the fee model the chain charges
+    /// the native transfers with
+    UpdateFeeModel {
+        fee_model: FeeModel,
+    },
     /// Owner operation to whitelist a new collateral
     WhitelistCollateral {
         collateral_token: String,
@@ -79,6 +87,102 @@ pub enum ExecuteMsg {
         collateral_token: String,
         bids_idx: Option<Vec<Uint128>>,
     },
+    /// Owner operation to keep bids from being activated
+    /// in the same block as a collateral price update
+    UpdateSnipingProtection {
+        enabled: bool,
+    },
+    /// Owner operation to sell the collateral in a dutch auction instead
+    /// of filling the bid pools, `None` switches back to the bid pools
+    UpdateAuctionConfig {
+        collateral_token: String,
+        auction_config: Option<AuctionConfig>,
+    },
+    /// Buy collateral from an active auction at the current discount
+    AuctionBid {
+        auction_idx: Uint128,
+    },
+    /// Sell the unsold collateral of an expired auction to the bid pools
+    ExpireAuction {
+        auction_idx: Uint128,
+    },
+    /// Owner operation to charge the liquidations of the collateral other
+    /// fees than the config ones, `None` switches back to the config fees
+    UpdateCollateralFees {
+        collateral_token: String,
+        collateral_fees: Option<CollateralFees>,
+    },
+    /// Owner operation to share the bid fee with an insurance fund and
+    /// the liquidation caller, `None` sends it all to the fee address
+    UpdateFeeSplit {
+        fee_split: Option<FeeSplit>,
+    },
+    /// Owner operation to allow bids to be refilled from aTerra deposits,
+    /// `None` disables the top-ups
+    UpdateTopUpConfig {
+        top_up_config: Option<TopUpConfig>,
+    },
+    /// Bid owners can pre-authorize the redemption of their aTerra, through a cw20
+    /// allowance to this contract, to refill the bid once its pool is depleted
+    UpdateBidTopUp {
+        bid_idx: Uint128,
+        top_up: Option<BidTopUp>,
+    },
+    /// Owner operation to accept bids in other stable denoms than
+    /// the stable denom, replacing the previous list
+    UpdateBidDenoms {
+        bid_denoms: Vec<String>,
+    },
+    /// Owner operation to let keepers retract the bids left untouched and
+    /// unfilled for the expiry period, `None` disables the expiry
+    UpdateBidExpiry {
+        bid_expiry: Option<BidExpiry>,
+    },
+    /// Retracts expired bids back to their owners,
+    /// paying the keeper reward to the sender
+    ExpireBids {
+        bids_idx: Vec<Uint128>,
+    },
+    /// Owner operation to set the DEX router that liquidations with
+    /// swap sell the collateral to, `None` disables them
+    UpdateSwapRouter {
+        swap_router: Option<SwapRoute>,
+    },
+    /// Owner operation to stream reward tokens to the stable bids of the
+    /// collateral, `None` stops the emissions
+    UpdateBidIncentives {
+        collateral_token: String,
+        bid_incentives: Option<BidIncentives>,
+    },
+    /// Claim the reward tokens earned by the bids of the sender
+    ClaimBidRewards {
+        collateral_token: String,
+    },
+    /// Owner operation to cap the bids each bidder can hold on the
+    /// collateral, `None` removes the caps
+    UpdateBidderLimits {
+        collateral_token: String,
+        bidder_limits: Option<BidderLimits>,
+    },
+    /// (internal) Redeem the aTerra of the bid owner to refill the bid
+    TopUpBid {
+        bid_idx: Uint128,
+    },
+    /// (internal) Add the redeemed stable to the bid
+    RefillBid {
+        bid_idx: Uint128,
+        prev_balance: Uint256,
+    },
+    /// (internal) Repay the loan from the collateral swap proceeds,
+    /// the remainder goes to the liquidator
+    RepayFromSwap {
+        liquidator: String,
+        repay_address: String,
+        fee_address: String,
+        repay_amount: Uint256,
+        bid_fee: Uint256,
+        prev_balance: Uint256,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -89,6 +193,27 @@ pub enum Cw20HookMsg {
         liquidator: String, // Legacy parameter, ignored
         fee_address: Option<String>,
         repay_address: Option<String>,
+        /// Borrower whose loan is repaid by auctions
+        borrower: Option<String>,
+        /// Denom of the borrower liability, the stable denom by default.
+        /// Bids in other denoms are swapped to it
+        repay_denom: Option<String>,
+    },
+    /// Custody interface to liquidate the sent collateral by selling it on
+    /// the swap router instead of the bids. The loan is repaid as a bid in
+    /// the highest premium slot would, the liquidator gets the remainder
+    ExecuteBidWithSwap {
+        liquidator: String,
+        fee_address: Option<String>,
+        repay_address: Option<String>,
+        /// Max spread of the swap below the oracle price
+        max_spread: Decimal256,
+    },
+    /// Custody interface to sell the collateral of the borrower to the
+    /// zero premium bids, without bid and liquidator fees
+    ExecuteSelfLiquidation {
+        borrower: String,
+        repay_address: Option<String>,
     },
 }
 
@@ -102,14 +227,22 @@ pub enum QueryMsg {
         collaterals: TokensHuman,
         collateral_prices: Vec<Decimal256>,
     },
+    /// Liquidation amount when the collaterals are sold on the swap router
+    SwapLiquidationAmount {
+        borrow_amount: Uint256,
+        borrow_limit: Uint256,
+        collaterals: TokensHuman,
+        collateral_prices: Vec<Decimal256>,
+    },
     CollateralInfo {
         collateral_token: String,
     },
     Bid {
         bid_idx: Uint128,
     },
+    /// Bids of the bidder, across all collateral tokens when none is given
     BidsByUser {
-        collateral_token: String,
+        collateral_token: Option<String>,
         bidder: String,
         start_after: Option<Uint128>,
         limit: Option<u8>,
@@ -123,6 +256,105 @@ pub enum QueryMsg {
         start_after: Option<u8>,
         limit: Option<u8>,
     },
+    /// Bid pools of the collateral in the given bid denom
+    BidPoolsByDenom {
+        collateral_token: String,
+        bid_denom: String,
+        start_after: Option<u8>,
+        limit: Option<u8>,
+    },
+    BidDenoms {},
+    SnipingProtection {},
+    AuctionConfig {
+        collateral_token: String,
+    },
+    ActiveAuctions {
+        collateral_token: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    /// Active auctions repaying the loan of the borrower
+    AuctionsByBorrower {
+        borrower: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    /// Premiums and repay amount of liquidating the collateral
+    /// against the current bid pools, or auction
+    SimulateBidExecution {
+        collateral_token: String,
+        amount: Uint256,
+        price: Decimal256,
+    },
+    /// Fees charged on the liquidations of the collateral
+    CollateralFees {
+        collateral_token: String,
+    },
+    FeeSplit {},
+    /// Cumulative bid fees sent to each destination,
+    /// in the stable denom when no bid denom is given
+    FeeStats {
+        bid_denom: Option<String>,
+    },
+    TopUpConfig {},
+    BidTopUp {
+        bid_idx: Uint128,
+    },
+    BidExpiry {},
+    /// Expired bids among the next `limit` bids after `start_after`
+    ExpiredBids {
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    SwapRouter {},
+    /// Executed liquidations, oldest first
+    LiquidationLogs {
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    LiquidationLogsByBorrower {
+        borrower: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    BidIncentives {
+        collateral_token: String,
+    },
+    /// Reward tokens earned by the bids of the bidder and not claimed yet
+    BidderRewards {
+        collateral_token: String,
+        bidder: String,
+    },
+    BidderLimits {
+        collateral_token: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionConfig {
+    /// Discount applied to the oracle price when the auction starts
+    pub initial_discount: Decimal256,
+    /// Time for the discount to decay down to the oracle price (seconds)
+    pub decay_period: u64,
+    /// Time the collateral stays on sale at the oracle price before the
+    /// auction can be expired to the bid pools (seconds)
+    pub expiry_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralFees {
+    pub bid_fee: Decimal256,
+    pub liquidator_fee: Decimal256,
+}
+
+/// The remainder of the bid fee goes to the fee address (overseer yield reserve)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeSplit {
+    pub insurance_fund: String,
+    /// Portion of the bid fee sent to the insurance fund
+    pub insurance_share: Decimal256,
+    /// Portion of the bid fee sent to the liquidator
+    pub caller_share: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -150,13 +382,13 @@ pub struct BidResponse {
     pub collateral_token: String,
     pub premium_slot: u8,
     pub bidder: String,
+    pub bid_denom: String,
     pub amount: Uint256,
-    pub product_snapshot: Decimal256,
+    pub share: Decimal256,
     pub sum_snapshot: Decimal256,
     pub pending_liquidated_collateral: Uint256,
     pub wait_end: Option<u64>,
     pub epoch_snapshot: Uint128,
-    pub scale_snapshot: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -167,11 +399,10 @@ pub struct BidsResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BidPoolResponse {
     pub sum_snapshot: Decimal256,
-    pub product_snapshot: Decimal256,
+    pub total_share: Decimal256,
     pub total_bid_amount: Uint256,
     pub premium_rate: Decimal256,
     pub current_epoch: Uint128,
-    pub current_scale: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -186,3 +417,432 @@ pub struct CollateralInfoResponse {
 pub struct BidPoolsResponse {
     pub bid_pools: Vec<BidPoolResponse>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidDenomsResponse {
+    pub stable_denom: String,
+    /// Other denoms accepted for bids
+    pub bid_denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SnipingProtectionResponse {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionConfigResponse {
+    pub collateral_token: String,
+    pub auction_config: Option<AuctionConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralFeesResponse {
+    pub collateral_token: String,
+    pub bid_fee: Decimal256,
+    pub liquidator_fee: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionResponse {
+    pub idx: Uint128,
+    pub collateral_token: String,
+    pub borrower: String,
+    pub amount: Uint256,
+    pub start_time: u64,
+    pub discount: Decimal256,
+    pub expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionsResponse {
+    pub auctions: Vec<AuctionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidFill {
+    pub premium_rate: Decimal256,
+    pub collateral_amount: Uint256,
+    pub stable_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateBidExecutionResponse {
+    pub collateral_token: String,
+    /// Liquidated collateral, lower than the requested amount
+    /// when the bid pools run out of bids
+    pub collateral_amount: Uint256,
+    pub repay_amount: Uint256,
+    pub bid_fee: Uint256,
+    pub liquidator_fee: Uint256,
+    pub fills: Vec<BidFill>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopUpConfig {
+    pub market_contract: String,
+    pub aterra_contract: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidTopUp {
+    /// aTerra redeemed on each refill
+    pub refill_amount: Uint256,
+    /// Max aTerra redeemed across all refills
+    pub cap: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopUpConfigResponse {
+    pub top_up_config: Option<TopUpConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidTopUpResponse {
+    pub bid_idx: Uint128,
+    /// The cap is the aTerra amount left to redeem
+    pub top_up: Option<BidTopUp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidExpiry {
+    /// Seconds since the last bid update before it expires
+    pub expiry_period: u64,
+    /// Rate of the retracted amount paid to the keeper
+    pub keeper_reward: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidExpiryResponse {
+    pub bid_expiry: Option<BidExpiry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExpiredBidsResponse {
+    pub bids_idx: Vec<Uint128>,
+    /// Last scanned bid, to continue the scan from
+    pub last_bid_idx: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapRouterResponse {
+    pub swap_router: Option<SwapRoute>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationLogResponse {
+    pub idx: Uint128,
+    pub height: u64,
+    pub borrower: Option<String>,
+    pub collateral_token: String,
+    pub collateral_amount: Uint256,
+    /// Average premium paid on the collateral oracle price
+    pub premium_rate: Decimal256,
+    pub repay_denom: String,
+    pub repay_amount: Uint256,
+    pub liquidator: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationLogsResponse {
+    pub logs: Vec<LiquidationLogResponse>,
+}
+
+/// The emissions are split across the premium slots, weighted toward the
+/// low premiums, and shared by the active bids of each slot
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidIncentives {
+    pub reward_token: String,
+    /// Reward tokens emitted per second across the premium slots
+    pub emission_rate: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidIncentivesResponse {
+    pub collateral_token: String,
+    pub bid_incentives: Option<BidIncentives>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderRewardsResponse {
+    pub collateral_token: String,
+    pub bidder: String,
+    pub reward_token: Option<String>,
+    pub pending_rewards: Uint256,
+}
+
+/// Caps checked when a bidder submits a bid on the collateral
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderLimits {
+    /// # of bids with a remaining amount a bidder can hold
+    pub max_bids: u32,
+    /// Remaining amount of the bids of a bidder in a bid denom
+    pub max_bid_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderLimitsResponse {
+    pub collateral_token: String,
+    pub bidder_limits: Option<BidderLimits>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeSplitResponse {
+    pub fee_split: Option<FeeSplit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeStatsResponse {
+    pub yield_reserve: Uint256,
+    pub insurance_fund: Uint256,
+    pub caller: Uint256,
+}
+
+/// Typed client of the liquidation queue queries
+pub struct LiquidationQueueQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> LiquidationQueueQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        LiquidationQueueQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn liquidation_amount(
+        &self,
+        borrow_amount: Uint256,
+        borrow_limit: Uint256,
+        collaterals: TokensHuman,
+        collateral_prices: Vec<Decimal256>,
+    ) -> StdResult<LiquidationAmountResponse> {
+        self.query(&QueryMsg::LiquidationAmount {
+            borrow_amount,
+            borrow_limit,
+            collaterals,
+            collateral_prices,
+        })
+    }
+
+    /// Liquidation amount when the collaterals are sold on the swap router
+    pub fn swap_liquidation_amount(
+        &self,
+        borrow_amount: Uint256,
+        borrow_limit: Uint256,
+        collaterals: TokensHuman,
+        collateral_prices: Vec<Decimal256>,
+    ) -> StdResult<LiquidationAmountResponse> {
+        self.query(&QueryMsg::SwapLiquidationAmount {
+            borrow_amount,
+            borrow_limit,
+            collaterals,
+            collateral_prices,
+        })
+    }
+
+    pub fn collateral_info(&self, collateral_token: String) -> StdResult<CollateralInfoResponse> {
+        self.query(&QueryMsg::CollateralInfo { collateral_token })
+    }
+
+    pub fn bid(&self, bid_idx: Uint128) -> StdResult<BidResponse> {
+        self.query(&QueryMsg::Bid { bid_idx })
+    }
+
+    /// Bids of the bidder, across all collateral tokens when none is given
+    pub fn bids_by_user(
+        &self,
+        collateral_token: Option<String>,
+        bidder: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    ) -> StdResult<BidsResponse> {
+        self.query(&QueryMsg::BidsByUser {
+            collateral_token,
+            bidder,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn bid_pool(&self, collateral_token: String, bid_slot: u8) -> StdResult<BidPoolResponse> {
+        self.query(&QueryMsg::BidPool {
+            collateral_token,
+            bid_slot,
+        })
+    }
+
+    pub fn bid_pools_by_collateral(
+        &self,
+        collateral_token: String,
+        start_after: Option<u8>,
+        limit: Option<u8>,
+    ) -> StdResult<BidPoolsResponse> {
+        self.query(&QueryMsg::BidPoolsByCollateral {
+            collateral_token,
+            start_after,
+            limit,
+        })
+    }
+
+    /// Bid pools of the collateral in the given bid denom
+    pub fn bid_pools_by_denom(
+        &self,
+        collateral_token: String,
+        bid_denom: String,
+        start_after: Option<u8>,
+        limit: Option<u8>,
+    ) -> StdResult<BidPoolsResponse> {
+        self.query(&QueryMsg::BidPoolsByDenom {
+            collateral_token,
+            bid_denom,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn bid_denoms(&self) -> StdResult<BidDenomsResponse> {
+        self.query(&QueryMsg::BidDenoms {})
+    }
+
+    pub fn sniping_protection(&self) -> StdResult<SnipingProtectionResponse> {
+        self.query(&QueryMsg::SnipingProtection {})
+    }
+
+    pub fn auction_config(&self, collateral_token: String) -> StdResult<AuctionConfigResponse> {
+        self.query(&QueryMsg::AuctionConfig { collateral_token })
+    }
+
+    pub fn collateral_fees(&self, collateral_token: String) -> StdResult<CollateralFeesResponse> {
+        self.query(&QueryMsg::CollateralFees { collateral_token })
+    }
+
+    pub fn active_auctions(
+        &self,
+        collateral_token: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    ) -> StdResult<AuctionsResponse> {
+        self.query(&QueryMsg::ActiveAuctions {
+            collateral_token,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn auctions_by_borrower(
+        &self,
+        borrower: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    ) -> StdResult<AuctionsResponse> {
+        self.query(&QueryMsg::AuctionsByBorrower {
+            borrower,
+            start_after,
+            limit,
+        })
+    }
+
+    /// Premiums and repay amount of liquidating the collateral
+    /// against the current bid pools, or auction
+    pub fn simulate_bid_execution(
+        &self,
+        collateral_token: String,
+        amount: Uint256,
+        price: Decimal256,
+    ) -> StdResult<SimulateBidExecutionResponse> {
+        self.query(&QueryMsg::SimulateBidExecution {
+            collateral_token,
+            amount,
+            price,
+        })
+    }
+
+    pub fn fee_split(&self) -> StdResult<FeeSplitResponse> {
+        self.query(&QueryMsg::FeeSplit {})
+    }
+
+    /// Cumulative bid fees sent to each destination,
+    /// in the stable denom when no bid denom is given
+    pub fn fee_stats(&self, bid_denom: Option<String>) -> StdResult<FeeStatsResponse> {
+        self.query(&QueryMsg::FeeStats { bid_denom })
+    }
+
+    pub fn top_up_config(&self) -> StdResult<TopUpConfigResponse> {
+        self.query(&QueryMsg::TopUpConfig {})
+    }
+
+    pub fn bid_top_up(&self, bid_idx: Uint128) -> StdResult<BidTopUpResponse> {
+        self.query(&QueryMsg::BidTopUp { bid_idx })
+    }
+
+    pub fn bid_expiry(&self) -> StdResult<BidExpiryResponse> {
+        self.query(&QueryMsg::BidExpiry {})
+    }
+
+    /// Expired bids among the next `limit` bids after `start_after`
+    pub fn expired_bids(
+        &self,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    ) -> StdResult<ExpiredBidsResponse> {
+        self.query(&QueryMsg::ExpiredBids { start_after, limit })
+    }
+
+    pub fn swap_router(&self) -> StdResult<SwapRouterResponse> {
+        self.query(&QueryMsg::SwapRouter {})
+    }
+
+    /// Executed liquidations, oldest first
+    pub fn liquidation_logs(
+        &self,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    ) -> StdResult<LiquidationLogsResponse> {
+        self.query(&QueryMsg::LiquidationLogs { start_after, limit })
+    }
+
+    pub fn liquidation_logs_by_borrower(
+        &self,
+        borrower: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    ) -> StdResult<LiquidationLogsResponse> {
+        self.query(&QueryMsg::LiquidationLogsByBorrower {
+            borrower,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn bid_incentives(&self, collateral_token: String) -> StdResult<BidIncentivesResponse> {
+        self.query(&QueryMsg::BidIncentives { collateral_token })
+    }
+
+    /// Reward tokens earned by the bids of the bidder and not claimed yet
+    pub fn bidder_rewards(
+        &self,
+        collateral_token: String,
+        bidder: String,
+    ) -> StdResult<BidderRewardsResponse> {
+        self.query(&QueryMsg::BidderRewards {
+            collateral_token,
+            bidder,
+        })
+    }
+
+    pub fn bidder_limits(&self, collateral_token: String) -> StdResult<BidderLimitsResponse> {
+        self.query(&QueryMsg::BidderLimits { collateral_token })
+    }
+}
@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+
+/// Attribute key venues report the stable amount repaid to the market with
+pub const REPAY_AMOUNT_ATTRIBUTE: &str = "repay_amount";
+
+/// Venue the custody contracts sell liquidated collateral on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidationVenue {
+    /// Bids of the liquidation contract of the custody config
+    BidQueue {},
+    /// External AMM or orderbook adapter implementing `Cw20HookMsg`
+    Adapter { contract_addr: String },
+}
+
+/// Hook external venue adapters receive the liquidated collateral with.
+/// Adapters sell the collateral, send the repay amount to `repay_address`,
+/// and report it with the `repay_amount` attribute
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    ExecuteLiquidation {
+        liquidator: String,
+        borrower: String,
+        repay_address: String,
+        fee_address: String,
+        max_spread: Option<Decimal256>,
+    },
+}
+
+/// Response data of a custody liquidation, whatever the venue
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationResult {
+    pub venue: String,
+    /// Stable amount the venue repaid in the liquidation,
+    /// zero when repaid later such as by auctions
+    pub repay_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationVenueResponse {
+    pub liquidation_venue: LiquidationVenue,
+}
@@ -1,9 +1,13 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
 use cw20::Cw20ReceiveMsg;
 
+use crate::querier::FeeModel;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
@@ -49,6 +53,10 @@ pub enum ExecuteMsg {
         interest_model: Option<String>,
         distribution_model: Option<String>,
     },
+    /// Fee model the chain charges the native transfers with
+    UpdateFeeModel {
+        fee_model: FeeModel,
+    },
 
     ////////////////////
     /// Overseer operations
@@ -84,6 +92,12 @@ pub enum ExecuteMsg {
     /// Repay stable asset to decrease liability
     RepayStable {},
 
+    /// Repay stable asset to decrease the liability of another borrower,
+    /// any amount above the loan is paid back to the borrower
+    RepayStableFor {
+        borrower: String,
+    },
+
     /// Claim distributed ANC rewards
     ClaimRewards {
         to: Option<String>,
@@ -143,6 +157,9 @@ pub struct StateResponse {
     pub global_interest_index: Decimal256,
     pub global_reward_index: Decimal256,
     pub anc_emission_rate: Decimal256,
+    /// Reward accrued by an aterra token since the market was instantiated
+    pub global_deposit_reward_index: Decimal256,
+    pub anc_deposit_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
 }
@@ -169,3 +186,61 @@ pub struct BorrowerInfoResponse {
 pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
+
+/// Typed client of the market queries
+pub struct MarketQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> MarketQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        MarketQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn state(&self, block_height: Option<u64>) -> StdResult<StateResponse> {
+        self.query(&QueryMsg::State { block_height })
+    }
+
+    pub fn epoch_state(
+        &self,
+        block_height: Option<u64>,
+        distributed_interest: Option<Uint256>,
+    ) -> StdResult<EpochStateResponse> {
+        self.query(&QueryMsg::EpochState {
+            block_height,
+            distributed_interest,
+        })
+    }
+
+    pub fn borrower_info(
+        &self,
+        borrower: String,
+        block_height: Option<u64>,
+    ) -> StdResult<BorrowerInfoResponse> {
+        self.query(&QueryMsg::BorrowerInfo {
+            borrower,
+            block_height,
+        })
+    }
+
+    pub fn borrower_infos(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BorrowerInfosResponse> {
+        self.query(&QueryMsg::BorrowerInfos { start_after, limit })
+    }
+}
@@ -0,0 +1,148 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{DivideByZeroError, OverflowError, OverflowOperation, StdError, StdResult};
+
+// Decimal256 and Uint256 panic on overflow, underflow and division by zero,
+// and always round down; the money market math goes through these instead
+// to fail with a typed error and pick the rounding direction
+
+/// Direction an inexact result is rounded in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+pub fn checked_add(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    if a.0 > Decimal256::MAX.0 - b.0 {
+        return Err(overflow(OverflowOperation::Add, a, b));
+    }
+
+    Ok(a + b)
+}
+
+pub fn checked_sub(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    if a < b {
+        return Err(overflow(OverflowOperation::Sub, a, b));
+    }
+
+    Ok(a - b)
+}
+
+/// a * b, rounded down
+pub fn checked_mul(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    if !b.is_zero() && a.0 > Decimal256::MAX.0 / b.0 {
+        return Err(overflow(OverflowOperation::Mul, a, b));
+    }
+
+    Ok(a * b)
+}
+
+/// a / b, rounded down
+pub fn checked_div(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    div_rounded(a, b, Rounding::Down)
+}
+
+/// a / b, rounded in the given direction
+pub fn div_rounded(a: Decimal256, b: Decimal256, rounding: Rounding) -> StdResult<Decimal256> {
+    if b.is_zero() {
+        return Err(StdError::divide_by_zero(DivideByZeroError::new(a)));
+    }
+
+    mul_div(
+        Uint256(a.0),
+        Uint256(Decimal256::DECIMAL_FRACTIONAL),
+        Uint256(b.0),
+        rounding,
+    )
+    .map(|quotient| Decimal256(quotient.0))
+    .ok_or_else(|| overflow(OverflowOperation::Mul, a, Decimal256::one()))
+}
+
+pub fn checked_from_uint256(a: Uint256) -> StdResult<Decimal256> {
+    if a.0 > Decimal256::MAX.0 / Decimal256::DECIMAL_FRACTIONAL {
+        return Err(StdError::overflow(OverflowError::new(
+            OverflowOperation::Mul,
+            a,
+            Decimal256::DECIMAL_FRACTIONAL,
+        )));
+    }
+
+    Ok(Decimal256::from_uint256(a))
+}
+
+/// a - b, or zero when b exceeds a
+pub fn saturating_sub(a: Decimal256, b: Decimal256) -> Decimal256 {
+    if a > b {
+        a - b
+    } else {
+        Decimal256::zero()
+    }
+}
+
+/// One minus the rate, which can not exceed one
+pub fn complement(rate: Decimal256) -> StdResult<Decimal256> {
+    checked_sub(Decimal256::one(), rate)
+}
+
+/// amount * numerator / denominator, rounded in the given direction
+pub fn mul_ratio(
+    amount: Uint256,
+    numerator: Uint256,
+    denominator: Uint256,
+    rounding: Rounding,
+) -> StdResult<Uint256> {
+    if denominator.is_zero() {
+        return Err(StdError::divide_by_zero(DivideByZeroError::new(amount)));
+    }
+
+    mul_div(amount, numerator, denominator, rounding).ok_or_else(|| {
+        StdError::overflow(OverflowError::new(
+            OverflowOperation::Mul,
+            amount,
+            numerator,
+        ))
+    })
+}
+
+/// The rate applied to the amount, rounded in the given direction
+pub fn mul_dec(amount: Uint256, rate: Decimal256, rounding: Rounding) -> StdResult<Uint256> {
+    mul_ratio(
+        amount,
+        Uint256(rate.0),
+        Uint256(Decimal256::DECIMAL_FRACTIONAL),
+        rounding,
+    )
+}
+
+/// The amount divided by the rate, rounded in the given direction
+pub fn div_dec(amount: Uint256, rate: Decimal256, rounding: Rounding) -> StdResult<Uint256> {
+    if rate.is_zero() {
+        return Err(StdError::divide_by_zero(DivideByZeroError::new(amount)));
+    }
+
+    mul_ratio(
+        amount,
+        Uint256(Decimal256::DECIMAL_FRACTIONAL),
+        Uint256(rate.0),
+        rounding,
+    )
+}
+
+// a * b / c, or `None` when a * b overflows; c is not zero
+fn mul_div(a: Uint256, b: Uint256, c: Uint256, rounding: Rounding) -> Option<Uint256> {
+    let (product, overflowed) = a.0.overflowing_mul(b.0);
+    if overflowed {
+        return None;
+    }
+
+    let quotient = Uint256(product / c.0);
+    if rounding == Rounding::Up && !(product % c.0).is_zero() {
+        return Some(quotient + Uint256::one());
+    }
+
+    Some(quotient)
+}
+
+fn overflow(operation: OverflowOperation, a: Decimal256, b: Decimal256) -> StdError {
+    StdError::overflow(OverflowError::new(operation, a, b))
+}
@@ -130,13 +130,15 @@ impl WasmMockQuerier {
                 contract_addr: _,
                 msg,
             }) => match from_binary(msg).unwrap() {
-                OracleQueryMsg::Price { base, quote } => {
+                OracleQueryMsg::Price { base, quote, .. } => {
                     match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
                         Some(v) => {
                             SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
                                 rate: v.0,
                                 last_updated_base: v.1,
                                 last_updated_quote: v.2,
+                                twap_rate: None,
+                                confidence: None,
                             })))
                         }
                         None => SystemResult::Err(SystemError::InvalidRequest {
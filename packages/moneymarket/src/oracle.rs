@@ -1,7 +1,13 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{Addr, Binary, QuerierWrapper, StdResult};
+use std::fmt;
+
+/// Longest an emergency price can be set for, 3 days
+pub const MAX_EMERGENCY_PRICE_DURATION: u64 = 259200;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -15,13 +21,156 @@ pub enum ExecuteMsg {
     UpdateConfig {
         owner: Option<String>,
     },
+    /// Register the ed25519 public key signing prices of the asset;
+    /// `None` removes the signer
+    RegisterSigner {
+        asset: String,
+        signer: Option<Binary>,
+    },
+    /// Feed `SignedPrices`, signed by the signers of their assets, on
+    /// behalf of the signers
+    FeedPricesSigned {
+        payload: Binary,
+        signature: Binary,
+    },
+    /// Replace the feeder of the asset at once, dropping any rotation
+    /// in progress
     RegisterFeeder {
         asset: String,
         feeder: String,
     },
+    /// Propose the next feeder of the asset, the current feeder still
+    /// being accepted for `overlap` seconds once the proposal is accepted
+    ProposeFeeder {
+        asset: String,
+        feeder: String,
+        overlap: u64,
+    },
+    /// Accept the proposal, by the proposed feeder
+    AcceptFeeder {
+        asset: String,
+    },
     FeedPrice {
         prices: Vec<(String, Decimal256)>, // (asset, price)
     },
+    /// Quote the fed prices of the asset in another asset, its price in
+    /// the base asset being routed through the quote asset price; `None`
+    /// goes back to base asset quotes
+    UpdatePriceQuote {
+        asset: String,
+        quote_asset: Option<String>,
+    },
+    /// Length in seconds of the TWAP window and of the price history
+    /// kept for it; `None` goes back to the default window
+    UpdateTwapWindow {
+        twap_window: Option<u64>,
+    },
+    /// Age in seconds after which the asset price is rejected by `Price`
+    /// queries; `None` accepts prices of any age
+    UpdateMaxPriceAge {
+        asset: String,
+        max_price_age: Option<u64>,
+    },
+    /// Price the asset from several feeders, its price being the median
+    /// of the fresh submissions once `quorum` of them are fresh;
+    /// `None` goes back to the registered feeder
+    UpdateFeederQuorum {
+        asset: String,
+        feeder_quorum: Option<FeederQuorum>,
+    },
+    /// Pull the asset price from a Pyth contract instead of feeders;
+    /// `None` goes back to fed prices
+    UpdatePythFeed {
+        asset: String,
+        pyth_feed: Option<PythFeed>,
+    },
+    /// Pull the asset price from a Band std-reference contract instead
+    /// of feeders, Pyth feeds taking precedence; `None` goes back to
+    /// fed prices
+    UpdateBandFeed {
+        asset: String,
+        band_feed: Option<BandFeed>,
+    },
+    /// Derive the asset price from the price of its underlying asset and
+    /// the exchange rate of an LST hub contract; `None` goes back to fed
+    /// prices
+    UpdateExchangeRateFeed {
+        asset: String,
+        exchange_rate_feed: Option<ExchangeRateFeed>,
+    },
+    /// Pause the asset price once its feeders miss `max_missed`
+    /// consecutive heartbeats; `None` removes the heartbeat
+    UpdateHeartbeat {
+        asset: String,
+        heartbeat: Option<Heartbeat>,
+    },
+    /// Pause the asset price, `Price` queries falling back to its
+    /// fallback policy
+    PausePrice {
+        asset: String,
+    },
+    /// Resume the price of an asset paused by the owner or for missed
+    /// heartbeats
+    ResumePrice {
+        asset: String,
+    },
+    /// Price used when the asset price is paused, too old or failing;
+    /// `None` goes back to failing
+    UpdateFallbackPolicy {
+        asset: String,
+        fallback_policy: Option<FallbackPolicy>,
+    },
+    /// Hold fed prices moving away from the previous price by more than
+    /// the max deviation until confirmed; `None` removes the guard
+    UpdateDeviationGuard {
+        asset: String,
+        deviation_guard: Option<DeviationGuard>,
+    },
+    /// Apply the held price of the asset (owner override)
+    ConfirmPendingPrice {
+        asset: String,
+    },
+    /// Check the asset price against the TWAP of an AMM pair of the
+    /// asset and the base asset, `Price` queries failing when they
+    /// deviate by more than the max deviation; `None` removes the check
+    UpdateAmmFeed {
+        asset: String,
+        amm_feed: Option<AmmFeed>,
+    },
+    /// Snapshot the cumulative price of the AMM pair of the asset, once
+    /// the last snapshot is as old as the window (anyone)
+    SnapshotAmmPrice {
+        asset: String,
+    },
+    /// Override every source of the asset price until `expires_at`, for
+    /// incidents where it cannot be fed; at most
+    /// [MAX_EMERGENCY_PRICE_DURATION] seconds ahead
+    SetEmergencyPrice {
+        asset: String,
+        price: Decimal256,
+        expires_at: u64,
+    },
+    ClearEmergencyPrice {
+        asset: String,
+    },
+    /// Get a gas capped [PriceSubscriberMsg::PriceBandCrossed] callback
+    /// whenever the fed asset price moves into another of the ascending
+    /// `thresholds`, by the subscribing contract
+    Subscribe {
+        asset: String,
+        thresholds: Vec<Decimal256>,
+    },
+    /// Drop the subscription of the sender, or of `subscriber` (owner)
+    Unsubscribe {
+        asset: String,
+        subscriber: Option<String>,
+    },
+    /// Describe the asset to integrators, returned with its price by
+    /// `Prices` queries; `None` removes the metadata
+    UpdateAssetMetadata {
+        asset: String,
+        asset_metadata: Option<AssetMetadata>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -34,11 +183,248 @@ pub enum QueryMsg {
     Price {
         base: String,
         quote: String,
+        /// Spot price when not set
+        price_kind: Option<PriceKind>,
     },
     Prices {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Rates of the pair at its latest price checkpoints, newest first
+    PriceHistory {
+        base: String,
+        quote: String,
+        limit: Option<u32>,
+    },
+    TwapWindow {},
+    PriceQuote {
+        asset: String,
+    },
+    Signer {
+        asset: String,
+    },
+    Heartbeat {
+        asset: String,
+    },
+    FallbackPolicy {
+        asset: String,
+    },
+    FeederStats {
+        asset: String,
+    },
+    FeederRotation {
+        asset: String,
+    },
+    MaxPriceAge {
+        asset: String,
+    },
+    FeederQuorum {
+        asset: String,
+    },
+    PythFeed {
+        asset: String,
+    },
+    BandFeed {
+        asset: String,
+    },
+    ExchangeRateFeed {
+        asset: String,
+    },
+    DeviationGuard {
+        asset: String,
+    },
+    PendingPrice {
+        asset: String,
+    },
+    AssetMetadata {
+        asset: String,
+    },
+    AmmFeed {
+        asset: String,
+    },
+    AmmTwap {
+        asset: String,
+    },
+    Subscriptions {
+        asset: String,
+    },
+    EmergencyPrice {
+        asset: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederQuorum {
+    pub feeders: Vec<String>,
+    /// # of fresh submissions required to update the price
+    pub quorum: u32,
+    /// Age in seconds after which a submission is no longer fresh
+    pub max_price_age: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PythFeed {
+    pub contract_addr: String,
+    /// Hex encoded id of the Pyth price feed
+    pub price_id: String,
+    /// Highest confidence interval accepted, as a ratio of the price
+    pub max_confidence: Decimal256,
+    /// Age in seconds after which the Pyth price is rejected
+    pub max_price_age: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BandFeed {
+    pub contract_addr: String,
+    /// Band symbols of the pair, the quote being the base asset
+    pub base_symbol: String,
+    pub quote_symbol: String,
+    /// Age in seconds after which the Band price is rejected
+    pub max_price_age: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateFeed {
+    pub hub_contract: String,
+    /// Asset an LST unit is redeemable for at the hub exchange rate
+    pub underlying_asset: String,
+    /// Age in seconds of the last hub index update after which the
+    /// exchange rate is rejected
+    pub max_rate_age: u64,
+}
+
+/// Astroport compatible pair, whose cumulative prices accumulate the
+/// pool price scaled by 10^6 every second
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmmFeed {
+    pub pair_contract: String,
+    /// Index of the asset in the pair, 0 or 1
+    pub asset_index: u8,
+    /// Minimum length in seconds of the TWAP
+    pub window: u64,
+    pub max_deviation: Decimal256,
+}
+
+/// Payload of `FeedPricesSigned`, as JSON
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedPrices {
+    /// Chain and oracle contract the prices are signed for
+    pub chain_id: String,
+    pub oracle_contract: String,
+    pub prices: Vec<SignedPrice>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedPrice {
+    pub asset: String,
+    pub price: Decimal256,
+    /// Time the price was signed at, its last updated time
+    pub timestamp: u64,
+}
+
+/// Every `interval` seconds without a price update is a missed heartbeat
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Heartbeat {
+    pub interval: u64,
+    pub max_missed: u32,
+}
+
+/// A fed price deviating from a previous price younger than `window`
+/// seconds is held, and applied once a later feed deviates from it by
+/// no more than `max_deviation`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeviationGuard {
+    pub max_deviation: Decimal256,
+    pub window: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackPolicy {
+    Fail,
+    /// Last fed price, up to `max_age` seconds old
+    LastGoodPrice {
+        max_age: u64,
+    },
+    /// Price set by the owner
+    FixedPrice {
+        price: Decimal256,
+    },
+}
+
+impl fmt::Display for FallbackPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FallbackPolicy::Fail => write!(f, "fail"),
+            FallbackPolicy::LastGoodPrice { .. } => write!(f, "last_good_price"),
+            FallbackPolicy::FixedPrice { .. } => write!(f, "fixed_price"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetMetadata {
+    /// Decimals of the asset amounts the price applies to
+    pub decimals: u8,
+    pub source: PriceSource,
+    /// Hub contract the asset is redeemable from, for LSTs
+    pub hub_contract: Option<String>,
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    Feeder,
+    Pyth,
+    Band,
+    /// Derived from the hub exchange rate
+    ExchangeRate,
+}
+
+impl fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PriceSource::Feeder => write!(f, "feeder"),
+            PriceSource::Pyth => write!(f, "pyth"),
+            PriceSource::Band => write!(f, "band"),
+            PriceSource::ExchangeRate => write!(f, "exchange_rate"),
+        }
+    }
+}
+
+/// Callbacks sent to the price subscribers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSubscriberMsg {
+    /// The asset price moved from `prev_band` to `band`, the # of
+    /// subscribed thresholds at or below the price
+    PriceBandCrossed {
+        asset: String,
+        price: Decimal256,
+        prev_band: u32,
+        band: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceKind {
+    Spot,
+    /// Time-weighted average price over the TWAP window
+    Twap,
+    /// `rate` is the spot price and `twap_rate` the TWAP
+    Both,
+}
+
+impl fmt::Display for PriceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PriceKind::Spot => write!(f, "spot"),
+            PriceKind::Twap => write!(f, "twap"),
+            PriceKind::Both => write!(f, "both"),
+        }
+    }
 }
 
 // We define a custom struct for each query response
@@ -61,6 +447,10 @@ pub struct PriceResponse {
     pub rate: Decimal256,
     pub last_updated_base: u64,
     pub last_updated_quote: u64,
+    /// Set when the TWAP was requested
+    pub twap_rate: Option<Decimal256>,
+    /// Confidence interval of `rate`, for sources publishing one
+    pub confidence: Option<Decimal256>,
 }
 
 // We define a custom struct for each query response
@@ -69,6 +459,8 @@ pub struct PricesResponseElem {
     pub asset: String,
     pub price: Decimal256,
     pub last_updated_time: u64,
+    /// `None` when no metadata is registered for the asset
+    pub metadata: Option<AssetMetadata>,
 }
 
 // We define a custom struct for each query response
@@ -76,3 +468,288 @@ pub struct PricesResponseElem {
 pub struct PricesResponse {
     pub prices: Vec<PricesResponseElem>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceQuoteResponse {
+    pub asset: String,
+    /// `None` when the asset is quoted in the base asset
+    pub quote_asset: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TwapWindowResponse {
+    pub twap_window: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederRotationResponse {
+    pub asset: String,
+    pub proposed_feeder: Option<String>,
+    pub overlap: Option<u64>,
+    /// Previous feeder, still accepted until `previous_feeder_until`
+    pub previous_feeder: Option<String>,
+    pub previous_feeder_until: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignerResponse {
+    pub asset: String,
+    pub signer: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HeartbeatResponse {
+    pub asset: String,
+    pub heartbeat: Option<Heartbeat>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FallbackPolicyResponse {
+    pub asset: String,
+    pub fallback_policy: FallbackPolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederStatsResponse {
+    pub asset: String,
+    /// # of price updates submitted by the feeders
+    pub updates: u64,
+    pub last_update_time: Option<u64>,
+    /// Longest time in seconds between two updates
+    pub max_gap: u64,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceCheckpoint {
+    pub time: u64,
+    pub rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceHistoryResponse {
+    pub checkpoints: Vec<PriceCheckpoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaxPriceAgeResponse {
+    pub asset: String,
+    pub max_price_age: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederQuorumResponse {
+    pub asset: String,
+    /// `None` when the asset is priced by a single feeder
+    pub feeder_quorum: Option<FeederQuorum>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PythFeedResponse {
+    pub asset: String,
+    /// `None` when the asset price is fed
+    pub pyth_feed: Option<PythFeed>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BandFeedResponse {
+    pub asset: String,
+    /// `None` when the asset price is not pulled from Band
+    pub band_feed: Option<BandFeed>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateFeedResponse {
+    pub asset: String,
+    /// `None` when the asset price is not derived
+    pub exchange_rate_feed: Option<ExchangeRateFeed>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeviationGuardResponse {
+    pub asset: String,
+    pub deviation_guard: Option<DeviationGuard>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmmFeedResponse {
+    pub asset: String,
+    pub amm_feed: Option<AmmFeed>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmmTwapResponse {
+    pub asset: String,
+    pub rate: Decimal256,
+    /// Seconds the TWAP is computed over, at least the feed window
+    pub window: u64,
+}
+
+/// `None` when no emergency price is in effect
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmergencyPriceResponse {
+    pub asset: String,
+    pub price: Option<Decimal256>,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriptionResponse {
+    pub subscriber: String,
+    pub thresholds: Vec<Decimal256>,
+    pub band: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriptionsResponse {
+    pub asset: String,
+    pub subscriptions: Vec<SubscriptionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetMetadataResponse {
+    pub asset: String,
+    pub asset_metadata: Option<AssetMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPriceResponse {
+    pub asset: String,
+    pub price: Option<Decimal256>,
+    pub last_updated_time: Option<u64>,
+}
+
+/// Typed client of the oracle queries
+pub struct OracleQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> OracleQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        OracleQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn feeder(&self, asset: String) -> StdResult<FeederResponse> {
+        self.query(&QueryMsg::Feeder { asset })
+    }
+
+    pub fn price(
+        &self,
+        base: String,
+        quote: String,
+        price_kind: Option<PriceKind>,
+    ) -> StdResult<PriceResponse> {
+        self.query(&QueryMsg::Price {
+            base,
+            quote,
+            price_kind,
+        })
+    }
+
+    pub fn prices(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<PricesResponse> {
+        self.query(&QueryMsg::Prices { start_after, limit })
+    }
+
+    /// Rates of the pair at its latest price checkpoints, newest first
+    pub fn price_history(
+        &self,
+        base: String,
+        quote: String,
+        limit: Option<u32>,
+    ) -> StdResult<PriceHistoryResponse> {
+        self.query(&QueryMsg::PriceHistory { base, quote, limit })
+    }
+
+    pub fn twap_window(&self) -> StdResult<TwapWindowResponse> {
+        self.query(&QueryMsg::TwapWindow {})
+    }
+
+    pub fn price_quote(&self, asset: String) -> StdResult<PriceQuoteResponse> {
+        self.query(&QueryMsg::PriceQuote { asset })
+    }
+
+    pub fn signer(&self, asset: String) -> StdResult<SignerResponse> {
+        self.query(&QueryMsg::Signer { asset })
+    }
+
+    pub fn heartbeat(&self, asset: String) -> StdResult<HeartbeatResponse> {
+        self.query(&QueryMsg::Heartbeat { asset })
+    }
+
+    pub fn fallback_policy(&self, asset: String) -> StdResult<FallbackPolicyResponse> {
+        self.query(&QueryMsg::FallbackPolicy { asset })
+    }
+
+    pub fn feeder_stats(&self, asset: String) -> StdResult<FeederStatsResponse> {
+        self.query(&QueryMsg::FeederStats { asset })
+    }
+
+    pub fn feeder_rotation(&self, asset: String) -> StdResult<FeederRotationResponse> {
+        self.query(&QueryMsg::FeederRotation { asset })
+    }
+
+    pub fn max_price_age(&self, asset: String) -> StdResult<MaxPriceAgeResponse> {
+        self.query(&QueryMsg::MaxPriceAge { asset })
+    }
+
+    pub fn feeder_quorum(&self, asset: String) -> StdResult<FeederQuorumResponse> {
+        self.query(&QueryMsg::FeederQuorum { asset })
+    }
+
+    pub fn pyth_feed(&self, asset: String) -> StdResult<PythFeedResponse> {
+        self.query(&QueryMsg::PythFeed { asset })
+    }
+
+    pub fn band_feed(&self, asset: String) -> StdResult<BandFeedResponse> {
+        self.query(&QueryMsg::BandFeed { asset })
+    }
+
+    pub fn exchange_rate_feed(&self, asset: String) -> StdResult<ExchangeRateFeedResponse> {
+        self.query(&QueryMsg::ExchangeRateFeed { asset })
+    }
+
+    pub fn deviation_guard(&self, asset: String) -> StdResult<DeviationGuardResponse> {
+        self.query(&QueryMsg::DeviationGuard { asset })
+    }
+
+    pub fn pending_price(&self, asset: String) -> StdResult<PendingPriceResponse> {
+        self.query(&QueryMsg::PendingPrice { asset })
+    }
+
+    pub fn asset_metadata(&self, asset: String) -> StdResult<AssetMetadataResponse> {
+        self.query(&QueryMsg::AssetMetadata { asset })
+    }
+
+    pub fn amm_feed(&self, asset: String) -> StdResult<AmmFeedResponse> {
+        self.query(&QueryMsg::AmmFeed { asset })
+    }
+
+    pub fn amm_twap(&self, asset: String) -> StdResult<AmmTwapResponse> {
+        self.query(&QueryMsg::AmmTwap { asset })
+    }
+
+    pub fn subscriptions(&self, asset: String) -> StdResult<SubscriptionsResponse> {
+        self.query(&QueryMsg::Subscriptions { asset })
+    }
+
+    pub fn emergency_price(&self, asset: String) -> StdResult<EmergencyPriceResponse> {
+        self.query(&QueryMsg::EmergencyPrice { asset })
+    }
+}
@@ -1,8 +1,14 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::liquidation_queue::BidFill;
+use crate::oracle::PriceKind;
+use crate::querier::FeeModel;
 use crate::tokens::TokensHuman;
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, Binary, QuerierWrapper, StdResult};
+use std::fmt;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -56,6 +62,10 @@ pub enum ExecuteMsg {
         epoch_period: Option<u64>,
         price_timeframe: Option<u64>,
     },
+    /// Fee model the chain charges the native transfers with
+    UpdateFeeModel {
+        fee_model: FeeModel,
+    },
 
     /// Create new custody contract for the given collateral token
     Whitelist {
@@ -71,6 +81,113 @@ pub enum ExecuteMsg {
         custody_contract: Option<String>, // bAsset custody contract
         max_ltv: Option<Decimal256>,      // Loan To Value ratio
     },
+    /// Move a collateral through its deprecation lifecycle;
+    /// deprecating requires the duration of the LTV ramp to zero
+    UpdateWhitelistStatus {
+        collateral_token: String,
+        status: WhitelistStatus,
+        ltv_ramp_duration: Option<u64>,
+    },
+    /// Maximum age of the collateral oracle price accepted by
+    /// lock, unlock and borrow limit computations; `None` falls
+    /// back to the global price timeframe
+    UpdateMaxPriceAge {
+        collateral_token: String,
+        max_price_age: Option<u64>,
+    },
+    /// Price the collateral from several oracle contracts, taking the
+    /// median of the fresh feeds once `quorum` of them are fresh, or the
+    /// lowest price otherwise; `None` goes back to a single oracle
+    UpdateOracleQuorum {
+        collateral_token: String,
+        oracle_quorum: Option<OracleQuorum>,
+    },
+    /// Kind of oracle price the collateral is valued at, the TWAP
+    /// resisting short-lived price moves; `None` goes back to spot
+    UpdatePriceKind {
+        collateral_token: String,
+        price_kind: Option<PriceKind>,
+    },
+    /// Relative confidence band above which the collateral is valued at
+    /// the lower bound of its price; `None` always uses the price
+    UpdateConfidenceThreshold {
+        collateral_token: String,
+        confidence_threshold: Option<Decimal256>,
+    },
+    /// Cap the collateral value liquidated per epoch for the collateral;
+    /// `None` removes the cap
+    UpdateLiquidationCap {
+        collateral_token: String,
+        liquidation_cap: Option<Uint256>,
+    },
+    /// Let only the whitelisted liquidators liquidate a position during the
+    /// first `exclusive_blocks` after it was found liquidatable, after which
+    /// anyone can; `None` opens liquidations to everyone
+    UpdateExclusiveLiquidation {
+        exclusive_liquidation: Option<ExclusiveLiquidation>,
+    },
+    /// Liquidate positions worth more than `size_threshold` in tranches
+    /// spread over multiple blocks; `None` liquidates them at once
+    UpdateGradualLiquidation {
+        gradual_liquidation: Option<GradualLiquidation>,
+    },
+    /// Order in which the collaterals of a borrower are seized when the
+    /// borrower has more than one; `None` seizes them all proportionally
+    UpdateSeizureOrder {
+        seizure_order: Option<SeizureOrder>,
+    },
+    /// Seize the collaterals of the sender in the given order when the
+    /// seizure order follows the borrowers; `None` removes the preference
+    UpdateSeizurePreference {
+        collaterals: Option<Vec<String>>,
+    },
+    /// Price the collateral with its own oracle contract instead of
+    /// the global one; `None` falls back to the global oracle
+    UpdateCollateralOracle {
+        collateral_token: String,
+        oracle_contract: Option<String>,
+    },
+    /// Share of the borrow limit kept free when unlocking the collateral,
+    /// so an unlock cannot leave the loan right at the limit;
+    /// `None` removes it
+    UpdateUnlockBuffer {
+        collateral_token: String,
+        unlock_buffer: Option<Decimal256>,
+    },
+    /// Minimum # of blocks between the last lock of the collateral
+    /// by a borrower and its next unlock; `None` removes it
+    UpdateMinLockDuration {
+        collateral_token: String,
+        min_lock_duration: Option<u64>,
+    },
+    /// Grant a borrower a credit line overriding its
+    /// collateral based borrow limit, or revoke it with `None`
+    UpdateCreditLine {
+        borrower: String,
+        credit_line: Option<CreditLine>,
+    },
+    /// Register a risk contract allowed to watch borrowers,
+    /// or deregister it together with its watches
+    UpdateRiskWatcher {
+        watcher: String,
+        registered: bool,
+    },
+    /// Executed by a risk watcher to get a [RiskWatcherMsg::LtvAlert]
+    /// callback whenever the borrower LTV crosses `ltv_threshold`
+    /// during epoch operations or liquidation checks;
+    /// `None` stops watching the borrower
+    WatchBorrower {
+        borrower: String,
+        ltv_threshold: Option<Decimal256>,
+    },
+    /// Get a gas capped [RiskNotificationMsg::LtvBandCrossed] callback on
+    /// `contract` whenever the sender LTV moves into another of the
+    /// ascending `ltv_bands` during unlock, borrow or liquidation checks
+    RegisterRiskNotification {
+        contract: String,
+        ltv_bands: Vec<Decimal256>,
+    },
+    DeregisterRiskNotification {},
 
     /// Claims all staking rewards from the bAsset contracts
     /// and also do a epoch basis updates
@@ -83,6 +200,95 @@ pub enum ExecuteMsg {
         distributed_interest: Uint256,
     },
 
+    /// Set the stable bond required to propose a new collateral
+    UpdateProposalBond {
+        proposal_bond: Uint256,
+    },
+    /// Instantiate the custody contract of the proposal,
+    /// register the collateral and return the bond
+    ApproveWhitelistProposal {
+        proposal_id: u64,
+    },
+    /// Remove the proposal and return the bond,
+    /// or send it to the collector when `slash` is set
+    RejectWhitelistProposal {
+        proposal_id: u64,
+        slash: bool,
+    },
+
+    /// Register or overwrite a category of correlated collaterals
+    /// with a boosted LTV
+    RegisterCategory {
+        name: String,
+        max_ltv: Decimal256,
+        collateral_tokens: Vec<String>,
+    },
+    /// Drop the boosted LTV of the category for positions where a single
+    /// collateral contributes more than `max_collateral_share` of the
+    /// borrow limit, or remove the rule with `None`
+    UpdateCategoryShareLimit {
+        name: String,
+        max_collateral_share: Option<Decimal256>,
+    },
+
+    /// Let the target deposit rate follow the projected
+    /// interest buffer runway within the given bounds.
+    /// Setting max_change to zero freezes the rate
+    UpdateDynamicRateConfig {
+        min_deposit_rate: Decimal256,
+        max_deposit_rate: Decimal256,
+        target_runway: u64, // # of epochs
+        max_change: Decimal256,
+    },
+
+    /// Bound the interest buffer distribution of an epoch. The distribution
+    /// is spread over `smoothing_window` epochs and capped at
+    /// `max_distribution`; the held back amount is carried over
+    UpdateDistributionCap {
+        max_distribution: Option<Uint256>,
+        smoothing_window: u64, // # of epochs
+    },
+
+    /// Move locked collateral from a replaced custody contract to the new
+    /// one; a batch of borrowers at a time, or only `borrower` when given.
+    /// Borrowers can also migrate their own collateral
+    MigrateCollateral {
+        borrower: Option<String>,
+        old_custody: String,
+        new_custody: String,
+        limit: Option<u32>,
+    },
+
+    /// Cap the debt that can be backed by the given collateral,
+    /// or remove the cap with `None`
+    UpdateDebtCeiling {
+        collateral_token: String,
+        debt_ceiling: Option<Uint256>,
+    },
+
+    /// Set the stable amount paid from the interest buffer
+    /// to the keeper of each epoch step
+    UpdateKeeperReward {
+        keeper_reward: Uint256,
+    },
+
+    /// Set the guardian allowed to freeze the protocol
+    /// and the minimum delay before an unfreeze;
+    /// the delay cannot be changed while frozen
+    UpdateGuardian {
+        guardian: Option<String>,
+        unfreeze_delay: Option<u64>,
+    },
+
+    ///////////////////////
+    /// Guardian operations
+    ///////////////////////
+
+    /// Block all collateral locks, unlocks and liquidations
+    FreezeProtocol {},
+    /// Lift the freeze once the unfreeze delay has passed
+    UnfreezeProtocol {},
+
     ////////////////////
     /// User operations
     ////////////////////
@@ -92,11 +298,266 @@ pub enum ExecuteMsg {
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Opt the whole position into a collateral category,
+    /// or back to the default mode with `None`
+    SetBorrowerCategory {
+        category: Option<String>,
+    },
+    /// Opt into automatic deleveraging between the two loan to
+    /// borrow limit ratios, or out of it with `None`
+    SetAutoDeleverage {
+        trigger_ratio: Option<Decimal256>,
+        target_ratio: Option<Decimal256>,
+    },
+    /// Let the operator manage the position of the sender with the given
+    /// permissions, or revoke the operator with no permissions
+    SetOperator {
+        operator: String,
+        permissions: Vec<OperatorPermission>,
+    },
+    /// Sell locked collateral of the sender to the zero premium bids,
+    /// without bid fee, and repay the loan with the proceeds
+    SelfLiquidate {
+        collateral_token: String,
+        amount: Uint256,
+    },
+
+    ////////////////////////
+    /// Operator operations
+    ////////////////////////
+
+    /// Lock collaterals the borrower deposited to the custodies
+    LockCollateralFor {
+        borrower: String,
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    },
+    /// Unlock collaterals, always released to the borrower
+    UnlockCollateralFor {
+        borrower: String,
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    },
+    SetBorrowerCategoryFor {
+        borrower: String,
+        category: Option<String>,
+    },
 
     /////////////////////////////
     /// Permissionless operations
     /////////////////////////////
-    LiquidateCollateral { borrower: String },
+    LiquidateCollateral {
+        borrower: String,
+        /// Sell the collateral on the DEX within the max spread instead of the bids,
+        /// for the liquidator to get the remainder of the swap
+        max_spread: Option<Decimal256>,
+    },
+    /// Re-allocate the loan of the borrower over its collaterals;
+    /// invoked by the market after every borrow
+    SyncBorrowerDebt {
+        borrower: String,
+    },
+    /// Sell a slice of an opted in borrower collaterals
+    /// to bring the loan back to its target ratio
+    Deleverage {
+        borrower: String,
+    },
+
+    /// Propose a new collateral by posting the proposal bond
+    ProposeWhitelist {
+        name: String,             // bAsset name
+        symbol: String,           // bAsset symbol
+        collateral_token: String, // bAsset token contract
+        custody_code_id: u64,     // bAsset custody contract code
+        custody_init_msg: Binary, // bAsset custody contract InstantiateMsg
+        max_ltv: Decimal256,      // Loan To Value ratio
+    },
+
+    /// Run the next step of the epoch operations
+    /// and pay the keeper reward to the sender
+    ExecuteEpochStep {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OperatorPermission {
+    LockCollateral,
+    UnlockCollateral,
+    SetBorrowerCategory,
+}
+
+impl fmt::Display for OperatorPermission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OperatorPermission::LockCollateral => write!(f, "lock_collateral"),
+            OperatorPermission::UnlockCollateral => write!(f, "unlock_collateral"),
+            OperatorPermission::SetBorrowerCategory => write!(f, "set_borrower_category"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochStep {
+    /// Compute the deposit rate of the passed epoch
+    ComputeDepositRate,
+    /// Purchase ANC and distribute the interest buffer to the market
+    DistributeInterestBuffer,
+    /// Invoke [Custody] DistributeRewards
+    DistributeRewards,
+    /// Store the epoch state and update the market emission rate
+    UpdateEpochState,
+}
+
+impl fmt::Display for EpochStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EpochStep::ComputeDepositRate => write!(f, "compute_deposit_rate"),
+            EpochStep::DistributeInterestBuffer => write!(f, "distribute_interest_buffer"),
+            EpochStep::DistributeRewards => write!(f, "distribute_rewards"),
+            EpochStep::UpdateEpochState => write!(f, "update_epoch_state"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitelistStatus {
+    /// Accepts new locks
+    Active,
+    /// Blocks new locks
+    DepositPaused,
+    /// Blocks new locks and ramps the LTV down to zero
+    Deprecated,
+    /// The LTV is zero and only unlocks are allowed
+    Delisted,
+}
+
+impl fmt::Display for WhitelistStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WhitelistStatus::Active => write!(f, "active"),
+            WhitelistStatus::DepositPaused => write!(f, "deposit_paused"),
+            WhitelistStatus::Deprecated => write!(f, "deprecated"),
+            WhitelistStatus::Delisted => write!(f, "delisted"),
+        }
+    }
+}
+
+/// Borrower action simulated by [QueryMsg::ValidateIntent]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Intent {
+    LockCollateral {
+        collaterals: TokensHuman,
+    },
+    UnlockCollateral {
+        collaterals: TokensHuman,
+    },
+    /// Only checked against the borrow limit; the market
+    /// liquidity and max borrow factor are not simulated
+    BorrowStable {
+        amount: Uint256,
+    },
+    RepayStable {
+        amount: Uint256,
+    },
+}
+
+/// Callbacks sent to the risk watchers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskWatcherMsg {
+    /// The borrower loan / collateral value went above the watch threshold
+    LtvAlert {
+        borrower: String,
+        ltv_threshold: Decimal256,
+        ltv: Decimal256,
+        loan_amount: Uint256,
+        collateral_value: Uint256,
+    },
+}
+
+/// Callbacks sent to the risk notification contracts of borrowers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskNotificationMsg {
+    /// The borrower LTV moved from `prev_band` to `band`, the # of
+    /// registered LTV bands at or below the LTV
+    LtvBandCrossed {
+        borrower: String,
+        ltv: Decimal256,
+        prev_band: u32,
+        band: u32,
+        loan_amount: Uint256,
+        collateral_value: Uint256,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleQuorum {
+    pub oracles: Vec<String>,
+    /// # of fresh feeds required to use their median price
+    pub quorum: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExclusiveLiquidation {
+    pub liquidators: Vec<String>,
+    /// # of blocks only the liquidators can liquidate a position
+    pub exclusive_blocks: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GradualLiquidation {
+    /// Liquidated collateral value above which the liquidation is split
+    pub size_threshold: Uint256,
+    /// # of tranches the liquidation is split into
+    pub tranches: u32,
+    /// # of blocks between two tranches
+    pub tranche_interval: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeizureOrder {
+    /// Collaterals with the most bids in the liquidation queue first
+    MostLiquidFirst,
+    /// Collaterals whose bids weigh the most times their premium first
+    HighestPremiumCapacityFirst,
+    /// Collaterals in the preference order of the borrower first;
+    /// borrowers without a preference are seized proportionally
+    BorrowerPreference,
+}
+
+impl fmt::Display for SeizureOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SeizureOrder::MostLiquidFirst => write!(f, "most_liquid_first"),
+            SeizureOrder::HighestPremiumCapacityFirst => {
+                write!(f, "highest_premium_capacity_first")
+            }
+            SeizureOrder::BorrowerPreference => write!(f, "borrower_preference"),
+        }
+    }
+}
+
+/// Governance granted override of a borrower borrow limit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditLine {
+    /// Multiplies the LTV of every collateral, capped at 100%
+    LtvMultiplier(Decimal256),
+    /// Borrow limit granted regardless of the collaterals,
+    /// which still count when worth more
+    FixedLimit(Uint256),
+}
+
+impl fmt::Display for CreditLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CreditLine::LtvMultiplier(multiplier) => write!(f, "ltv_multiplier:{}", multiplier),
+            CreditLine::FixedLimit(limit) => write!(f, "fixed_limit:{}", limit),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -116,10 +577,128 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    CollateralsDetailed {
+        borrower: String,
+    },
     BorrowLimit {
         borrower: String,
         block_time: Option<u64>,
     },
+    BorrowLimits {
+        borrowers: Vec<String>,
+        block_time: Option<u64>,
+    },
+    FreezeState {},
+    EpochProgress {},
+    DynamicRateConfig {},
+    DistributionCap {},
+    WhitelistProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    ProjectedRunway {},
+    Category {
+        name: String,
+    },
+    CategoryShareLimit {
+        name: String,
+    },
+    BorrowerCategory {
+        borrower: String,
+    },
+    AutoDeleverage {
+        borrower: String,
+    },
+    Operator {
+        borrower: String,
+        operator: String,
+    },
+    DebtCeiling {
+        collateral_token: String,
+    },
+    WhitelistStatus {
+        collateral_token: String,
+    },
+    MaxPriceAge {
+        collateral_token: String,
+    },
+    CollateralOracle {
+        collateral_token: String,
+    },
+    OracleQuorum {
+        collateral_token: String,
+    },
+    PriceKind {
+        collateral_token: String,
+    },
+    ConfidenceThreshold {
+        collateral_token: String,
+    },
+    LiquidationThrottle {
+        collateral_token: String,
+    },
+    ExclusiveLiquidation {},
+    /// Block from which anyone can liquidate the borrower
+    LiquidationWindow {
+        borrower: String,
+    },
+    GradualLiquidation {},
+    /// Tranches left of the ongoing liquidation of the borrower
+    LiquidationPlan {
+        borrower: String,
+    },
+    SeizureOrder {},
+    SeizurePreference {
+        borrower: String,
+    },
+    MinLockDuration {
+        collateral_token: String,
+    },
+    UnlockBuffer {
+        collateral_token: String,
+    },
+    /// In progress replacement of the custody contract of the collateral
+    CustodyMigration {
+        collateral_token: String,
+    },
+    CreditLine {
+        borrower: String,
+    },
+    RiskWatches {
+        watcher: String,
+    },
+    RiskNotification {
+        borrower: String,
+    },
+    /// Preview the collaterals seized by a liquidation of the borrower,
+    /// the premiums filling them and the resulting position
+    SimulateLiquidation {
+        borrower: String,
+    },
+    /// Simulate the actions in order on the borrower position
+    /// and report the first one which would fail
+    ValidateIntent {
+        borrower: String,
+        actions: Vec<Intent>,
+    },
+    /// Snapshots of the executed epochs, oldest first and
+    /// paginated by the height the epoch was executed at
+    EpochHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Scan a page of borrowers and return the ones with
+    /// loan / borrow limit of at least `min_risk_ratio`
+    RiskyPositions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        min_risk_ratio: Decimal256,
+    },
+    /// Liquidate the whole book as if every collateral price dropped by
+    /// `price_shock`, and check the bid pools could fill the liquidations
+    MassSimulate {
+        price_shock: Decimal256,
+    },
 }
 
 // We define a custom struct for each query response
@@ -155,6 +734,16 @@ pub struct WhitelistResponse {
     pub elems: Vec<WhitelistResponseElem>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistStatusResponse {
+    pub collateral_token: String,
+    pub status: WhitelistStatus,
+    /// LTV in effect at the current block time
+    pub max_ltv: Decimal256,
+    pub ltv_ramp_start: Option<u64>,
+    pub ltv_ramp_end: Option<u64>,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollateralsResponse {
@@ -168,8 +757,647 @@ pub struct AllCollateralsResponse {
     pub all_collaterals: Vec<CollateralsResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralDetail {
+    pub collateral_token: String,
+    pub amount: Uint256,
+    pub price: Decimal256,
+    /// amount * price in stable denom
+    pub value: Uint256,
+    pub max_ltv: Decimal256,
+    /// Contribution of this collateral to the borrow limit
+    pub borrow_limit: Uint256,
+    /// Price of this collateral at which the loan becomes liquidatable,
+    /// assuming the other collateral prices stay the same
+    pub liquidation_price: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralsDetailedResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    pub collaterals: Vec<CollateralDetail>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowLimitResponse {
     pub borrower: String,
     pub borrow_limit: Uint256,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochStateResponse {
+    pub deposit_rate: Decimal256,
+    pub prev_aterra_supply: Uint256,
+    pub prev_exchange_rate: Decimal256,
+    pub prev_interest_buffer: Uint256,
+    pub last_executed_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochProgressResponse {
+    pub next_step: EpochStep,
+    pub keeper_reward: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistProposalResponse {
+    pub proposal_id: u64,
+    pub proposer: String,
+    pub name: String,
+    pub symbol: String,
+    pub collateral_token: String,
+    pub custody_code_id: u64,
+    pub custody_init_msg: Binary,
+    pub max_ltv: Decimal256,
+    pub bond_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistProposalsResponse {
+    pub proposals: Vec<WhitelistProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DynamicRateConfigResponse {
+    pub min_deposit_rate: Decimal256,
+    pub max_deposit_rate: Decimal256,
+    pub target_runway: u64,
+    pub max_change: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DistributionCapResponse {
+    pub max_distribution: Option<Uint256>,
+    pub smoothing_window: u64,
+    /// Distribution held back by the cap or the smoothing window,
+    /// paid out in the following epochs
+    pub carried_interest: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProjectedRunwayResponse {
+    pub interest_buffer: Uint256,
+    /// Interest distributed from the buffer in the last epoch
+    pub distributed_interest: Uint256,
+    /// # of epochs until the buffer is depleted at the last epoch spending;
+    /// `None` when nothing was distributed
+    pub runway: Option<Decimal256>,
+    pub target_deposit_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CategoryResponse {
+    pub name: String,
+    pub max_ltv: Decimal256,
+    pub collateral_tokens: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CategoryShareLimitResponse {
+    pub name: String,
+    pub max_collateral_share: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerCategoryResponse {
+    pub borrower: String,
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DebtCeilingResponse {
+    pub collateral_token: String,
+    pub debt_ceiling: Option<Uint256>,
+    /// Aggregate debt allocated to the collateral
+    pub collateral_debt: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoDeleverageResponse {
+    pub borrower: String,
+    pub trigger_ratio: Option<Decimal256>,
+    pub target_ratio: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorResponse {
+    pub borrower: String,
+    pub operator: String,
+    pub permissions: Vec<OperatorPermission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FreezeStateResponse {
+    pub guardian: Option<String>,
+    pub unfreeze_delay: u64,
+    pub frozen: bool,
+    pub frozen_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskyPositionResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    pub risk_ratio: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskyPositionsResponse {
+    pub positions: Vec<RiskyPositionResponse>,
+    /// Last scanned borrower, to be used as `start_after` of the next page
+    pub last_borrower: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowLimitsResponse {
+    pub borrow_limits: Vec<BorrowLimitResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochSnapshotResponse {
+    pub height: u64,
+    pub time: u64,
+    pub deposit_rate: Decimal256,
+    pub interest_buffer: Uint256,
+    pub distributed_interest: Uint256,
+    pub total_collateral_value: Uint256,
+    pub total_liabilities: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochHistoryResponse {
+    pub snapshots: Vec<EpochSnapshotResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaxPriceAgeResponse {
+    pub collateral_token: String,
+    /// Effective max price age, the price timeframe if none is set
+    pub max_price_age: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreditLineResponse {
+    pub borrower: String,
+    pub credit_line: Option<CreditLine>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskWatchResponse {
+    pub borrower: String,
+    pub ltv_threshold: Decimal256,
+    pub alerted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskWatchesResponse {
+    pub watcher: String,
+    pub watches: Vec<RiskWatchResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskNotificationResponse {
+    pub borrower: String,
+    pub contract: String,
+    pub ltv_bands: Vec<Decimal256>,
+    /// Band of the borrower LTV at the last check
+    pub band: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralOracleResponse {
+    pub collateral_token: String,
+    /// Effective oracle contract, the global oracle if none is set
+    pub oracle_contract: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleQuorumResponse {
+    pub collateral_token: String,
+    /// `None` when the collateral is priced by a single oracle
+    pub oracle_quorum: Option<OracleQuorum>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceKindResponse {
+    pub collateral_token: String,
+    /// Effective price kind, spot if none is set
+    pub price_kind: PriceKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfidenceThresholdResponse {
+    pub collateral_token: String,
+    /// `None` when the collateral is valued at its price regardless of
+    /// the confidence band
+    pub confidence_threshold: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExclusiveLiquidationResponse {
+    pub exclusive_liquidation: Option<ExclusiveLiquidation>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationWindowResponse {
+    pub borrower: String,
+    /// Height the position was found liquidatable by a
+    /// liquidator outside of the whitelist
+    pub liquidatable_since: Option<u64>,
+    /// Height from which anyone can liquidate the position
+    pub open_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GradualLiquidationResponse {
+    pub gradual_liquidation: Option<GradualLiquidation>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationPlanResponse {
+    pub borrower: String,
+    /// `None` when no liquidation of the borrower is ongoing
+    pub remaining_tranches: Option<u32>,
+    /// Height from which the next tranche can be liquidated
+    pub next_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeizureOrderResponse {
+    pub seizure_order: Option<SeizureOrder>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeizurePreferenceResponse {
+    pub borrower: String,
+    /// Collaterals seized first, in order
+    pub collaterals: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationThrottleResponse {
+    pub collateral_token: String,
+    pub liquidation_cap: Option<Uint256>,
+    /// Collateral value liquidated in the current epoch
+    pub liquidated_value: Uint256,
+    /// Borrowers cut short by the cap in the current epoch
+    pub queued_borrowers: u32,
+    /// Borrowers queued in the previous epoch still waiting
+    /// to be liquidated before anyone else
+    pub priority_borrowers: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinLockDurationResponse {
+    pub collateral_token: String,
+    pub min_lock_duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnlockBufferResponse {
+    pub collateral_token: String,
+    /// Zero if none is set
+    pub unlock_buffer: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CustodyMigrationResponse {
+    pub collateral_token: String,
+    pub old_custody: String,
+    pub new_custody: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulatedCollateralLiquidation {
+    pub collateral_token: String,
+    pub amount: Uint256,
+    pub repay_amount: Uint256,
+    /// Bid pool premiums filling the liquidation
+    pub fills: Vec<BidFill>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateLiquidationResponse {
+    pub borrower: String,
+    pub collaterals: Vec<SimulatedCollateralLiquidation>,
+    pub repay_amount: Uint256,
+    /// Position once the liquidation is repaid
+    pub loan_amount: Uint256,
+    /// loan_amount / collateral value
+    pub ltv: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MassSimulatedCollateral {
+    pub collateral_token: String,
+    /// Oracle price after the price shock
+    pub price: Decimal256,
+    pub liquidation_amount: Uint256,
+    /// Collateral the bid pools can fill
+    pub absorbed_amount: Uint256,
+    pub repay_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MassSimulateResponse {
+    pub price_shock: Decimal256,
+    pub liquidatable_positions: u32,
+    /// Loans of the positions over their borrow limit
+    pub liquidatable_debt: Uint256,
+    pub repay_amount: Uint256,
+    /// Whether the bid pools fill the liquidations of every collateral
+    pub absorbed: bool,
+    pub collaterals: Vec<MassSimulatedCollateral>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidateIntentResponse {
+    pub valid: bool,
+    /// Index of the first failing action and its error
+    pub failed_action: Option<u32>,
+    pub error: Option<String>,
+    /// Position once every action before the failing one is applied
+    pub collaterals: TokensHuman,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    /// loan_amount / collateral value
+    pub ltv: Decimal256,
+}
+
+/// Typed client of the overseer queries
+pub struct OverseerQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract_addr: Addr,
+}
+
+impl<'a> OverseerQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract_addr: Addr) -> Self {
+        OverseerQuerier {
+            querier,
+            contract_addr,
+        }
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier
+            .query_wasm_smart(self.contract_addr.to_string(), msg)
+    }
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn epoch_state(&self) -> StdResult<EpochStateResponse> {
+        self.query(&QueryMsg::EpochState {})
+    }
+
+    pub fn whitelist(
+        &self,
+        collateral_token: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<WhitelistResponse> {
+        self.query(&QueryMsg::Whitelist {
+            collateral_token,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn collaterals(&self, borrower: String) -> StdResult<CollateralsResponse> {
+        self.query(&QueryMsg::Collaterals { borrower })
+    }
+
+    pub fn all_collaterals(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllCollateralsResponse> {
+        self.query(&QueryMsg::AllCollaterals { start_after, limit })
+    }
+
+    pub fn collaterals_detailed(&self, borrower: String) -> StdResult<CollateralsDetailedResponse> {
+        self.query(&QueryMsg::CollateralsDetailed { borrower })
+    }
+
+    pub fn borrow_limit(
+        &self,
+        borrower: String,
+        block_time: Option<u64>,
+    ) -> StdResult<BorrowLimitResponse> {
+        self.query(&QueryMsg::BorrowLimit {
+            borrower,
+            block_time,
+        })
+    }
+
+    pub fn borrow_limits(
+        &self,
+        borrowers: Vec<String>,
+        block_time: Option<u64>,
+    ) -> StdResult<BorrowLimitsResponse> {
+        self.query(&QueryMsg::BorrowLimits {
+            borrowers,
+            block_time,
+        })
+    }
+
+    pub fn freeze_state(&self) -> StdResult<FreezeStateResponse> {
+        self.query(&QueryMsg::FreezeState {})
+    }
+
+    pub fn epoch_progress(&self) -> StdResult<EpochProgressResponse> {
+        self.query(&QueryMsg::EpochProgress {})
+    }
+
+    pub fn dynamic_rate_config(&self) -> StdResult<Option<DynamicRateConfigResponse>> {
+        self.query(&QueryMsg::DynamicRateConfig {})
+    }
+
+    pub fn distribution_cap(&self) -> StdResult<DistributionCapResponse> {
+        self.query(&QueryMsg::DistributionCap {})
+    }
+
+    pub fn whitelist_proposals(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<WhitelistProposalsResponse> {
+        self.query(&QueryMsg::WhitelistProposals { start_after, limit })
+    }
+
+    pub fn projected_runway(&self) -> StdResult<ProjectedRunwayResponse> {
+        self.query(&QueryMsg::ProjectedRunway {})
+    }
+
+    pub fn category(&self, name: String) -> StdResult<CategoryResponse> {
+        self.query(&QueryMsg::Category { name })
+    }
+
+    pub fn category_share_limit(&self, name: String) -> StdResult<CategoryShareLimitResponse> {
+        self.query(&QueryMsg::CategoryShareLimit { name })
+    }
+
+    pub fn borrower_category(&self, borrower: String) -> StdResult<BorrowerCategoryResponse> {
+        self.query(&QueryMsg::BorrowerCategory { borrower })
+    }
+
+    pub fn auto_deleverage(&self, borrower: String) -> StdResult<AutoDeleverageResponse> {
+        self.query(&QueryMsg::AutoDeleverage { borrower })
+    }
+
+    pub fn operator(&self, borrower: String, operator: String) -> StdResult<OperatorResponse> {
+        self.query(&QueryMsg::Operator { borrower, operator })
+    }
+
+    pub fn debt_ceiling(&self, collateral_token: String) -> StdResult<DebtCeilingResponse> {
+        self.query(&QueryMsg::DebtCeiling { collateral_token })
+    }
+
+    pub fn whitelist_status(&self, collateral_token: String) -> StdResult<WhitelistStatusResponse> {
+        self.query(&QueryMsg::WhitelistStatus { collateral_token })
+    }
+
+    pub fn max_price_age(&self, collateral_token: String) -> StdResult<MaxPriceAgeResponse> {
+        self.query(&QueryMsg::MaxPriceAge { collateral_token })
+    }
+
+    pub fn collateral_oracle(
+        &self,
+        collateral_token: String,
+    ) -> StdResult<CollateralOracleResponse> {
+        self.query(&QueryMsg::CollateralOracle { collateral_token })
+    }
+
+    pub fn oracle_quorum(&self, collateral_token: String) -> StdResult<OracleQuorumResponse> {
+        self.query(&QueryMsg::OracleQuorum { collateral_token })
+    }
+
+    pub fn price_kind(&self, collateral_token: String) -> StdResult<PriceKindResponse> {
+        self.query(&QueryMsg::PriceKind { collateral_token })
+    }
+
+    pub fn confidence_threshold(
+        &self,
+        collateral_token: String,
+    ) -> StdResult<ConfidenceThresholdResponse> {
+        self.query(&QueryMsg::ConfidenceThreshold { collateral_token })
+    }
+
+    pub fn liquidation_throttle(
+        &self,
+        collateral_token: String,
+    ) -> StdResult<LiquidationThrottleResponse> {
+        self.query(&QueryMsg::LiquidationThrottle { collateral_token })
+    }
+
+    pub fn exclusive_liquidation(&self) -> StdResult<ExclusiveLiquidationResponse> {
+        self.query(&QueryMsg::ExclusiveLiquidation {})
+    }
+
+    /// Block from which anyone can liquidate the borrower
+    pub fn liquidation_window(&self, borrower: String) -> StdResult<LiquidationWindowResponse> {
+        self.query(&QueryMsg::LiquidationWindow { borrower })
+    }
+
+    pub fn gradual_liquidation(&self) -> StdResult<GradualLiquidationResponse> {
+        self.query(&QueryMsg::GradualLiquidation {})
+    }
+
+    /// Tranches left of the ongoing liquidation of the borrower
+    pub fn liquidation_plan(&self, borrower: String) -> StdResult<LiquidationPlanResponse> {
+        self.query(&QueryMsg::LiquidationPlan { borrower })
+    }
+
+    pub fn seizure_order(&self) -> StdResult<SeizureOrderResponse> {
+        self.query(&QueryMsg::SeizureOrder {})
+    }
+
+    pub fn seizure_preference(&self, borrower: String) -> StdResult<SeizurePreferenceResponse> {
+        self.query(&QueryMsg::SeizurePreference { borrower })
+    }
+
+    pub fn min_lock_duration(
+        &self,
+        collateral_token: String,
+    ) -> StdResult<MinLockDurationResponse> {
+        self.query(&QueryMsg::MinLockDuration { collateral_token })
+    }
+
+    pub fn unlock_buffer(&self, collateral_token: String) -> StdResult<UnlockBufferResponse> {
+        self.query(&QueryMsg::UnlockBuffer { collateral_token })
+    }
+
+    /// In progress replacement of the custody contract of the collateral
+    pub fn custody_migration(
+        &self,
+        collateral_token: String,
+    ) -> StdResult<Option<CustodyMigrationResponse>> {
+        self.query(&QueryMsg::CustodyMigration { collateral_token })
+    }
+
+    pub fn credit_line(&self, borrower: String) -> StdResult<CreditLineResponse> {
+        self.query(&QueryMsg::CreditLine { borrower })
+    }
+
+    pub fn risk_watches(&self, watcher: String) -> StdResult<RiskWatchesResponse> {
+        self.query(&QueryMsg::RiskWatches { watcher })
+    }
+
+    pub fn risk_notification(
+        &self,
+        borrower: String,
+    ) -> StdResult<Option<RiskNotificationResponse>> {
+        self.query(&QueryMsg::RiskNotification { borrower })
+    }
+
+    /// Preview the collaterals seized by a liquidation of the borrower,
+    /// the premiums filling them and the resulting position
+    pub fn simulate_liquidation(&self, borrower: String) -> StdResult<SimulateLiquidationResponse> {
+        self.query(&QueryMsg::SimulateLiquidation { borrower })
+    }
+
+    /// Simulate the actions in order on the borrower position
+    /// and report the first one which would fail
+    pub fn validate_intent(
+        &self,
+        borrower: String,
+        actions: Vec<Intent>,
+    ) -> StdResult<ValidateIntentResponse> {
+        self.query(&QueryMsg::ValidateIntent { borrower, actions })
+    }
+
+    /// Snapshots of the executed epochs, oldest first and
+    /// paginated by the height the epoch was executed at
+    pub fn epoch_history(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<EpochHistoryResponse> {
+        self.query(&QueryMsg::EpochHistory { start_after, limit })
+    }
+
+    /// Scan a page of borrowers and return the ones with
+    /// loan / borrow limit of at least `min_risk_ratio`
+    pub fn risky_positions(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        min_risk_ratio: Decimal256,
+    ) -> StdResult<RiskyPositionsResponse> {
+        self.query(&QueryMsg::RiskyPositions {
+            start_after,
+            limit,
+            min_risk_ratio,
+        })
+    }
+
+    /// Liquidate the whole book as if every collateral price dropped by
+    /// `price_shock`, and check the bid pools could fill the liquidations
+    pub fn mass_simulate(&self, price_shock: Decimal256) -> StdResult<MassSimulateResponse> {
+        self.query(&QueryMsg::MassSimulate { price_shock })
+    }
+}
@@ -0,0 +1,60 @@
+use cosmwasm_std::{CanonicalAddr, Uint128};
+
+// settings for pagination
+pub const MAX_LIMIT: u32 = 30;
+pub const DEFAULT_LIMIT: u32 = 10;
+
+/// Number of items to read, the default limit when none is given,
+/// capped by the max limit
+pub fn calc_limit(limit: Option<u32>) -> usize {
+    calc_limit_with_max(limit, MAX_LIMIT)
+}
+
+/// Number of items to read, capped by `max_limit` instead of the max limit
+pub fn calc_limit_with_max(limit: Option<u32>, max_limit: u32) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(max_limit) as usize
+}
+
+/// Key of a paginated bucket, as it is stored
+pub trait RangeKey {
+    fn to_key(&self) -> Vec<u8>;
+}
+
+impl RangeKey for CanonicalAddr {
+    fn to_key(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+}
+
+impl RangeKey for String {
+    fn to_key(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl RangeKey for u8 {
+    fn to_key(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+}
+
+impl RangeKey for u64 {
+    fn to_key(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+}
+
+impl RangeKey for Uint128 {
+    fn to_key(&self) -> Vec<u8> {
+        self.u128().to_be_bytes().to_vec()
+    }
+}
+
+/// This will set the first key after the provided key, by appending a 1 byte
+pub fn calc_range_start<K: RangeKey>(start_after: Option<K>) -> Option<Vec<u8>> {
+    start_after.map(|key| {
+        let mut v = key.to_key();
+        v.push(1);
+        v
+    })
+}
@@ -1,15 +1,17 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Deps, QueryRequest,
-    StdError, StdResult, Uint128, WasmQuery,
+    StdError, StdResult, Storage, Uint128, WasmQuery,
 };
+use cosmwasm_storage::{singleton, singleton_read};
 use cw20::{Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::TerraQuerier;
 
-use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+use crate::oracle::{OracleQuerier, PriceKind, PriceResponse};
 
 pub fn query_all_balances(deps: Deps, account_addr: Addr) -> StdResult<Vec<Coin>> {
     // load price form the oracle
@@ -60,27 +62,91 @@ pub fn query_supply(deps: Deps, contract_addr: Addr) -> StdResult<Uint256> {
     Ok(Uint256::from(token_info.total_supply))
 }
 
+/// How the chain charges the native transfers of a contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeModel {
+    /// Transfers are not charged
+    NoTax {},
+    /// Each transfer is charged a fixed amount of the sent denom
+    FixedFee { amount: Uint256 },
+    /// Transfers are charged the tax rate of the treasury module,
+    /// up to the tax cap of the denom, as on Terra classic
+    TreasuryTax {},
+}
+
+impl fmt::Display for FeeModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeeModel::NoTax {} => write!(f, "no_tax"),
+            FeeModel::FixedFee { .. } => write!(f, "fixed_fee"),
+            FeeModel::TreasuryTax {} => write!(f, "treasury_tax"),
+        }
+    }
+}
+
+impl Default for FeeModel {
+    fn default() -> Self {
+        FeeModel::TreasuryTax {}
+    }
+}
+
+static KEY_FEE_MODEL: &[u8] = b"fee_model";
+
+pub fn store_fee_model(storage: &mut dyn Storage, fee_model: &FeeModel) -> StdResult<()> {
+    singleton(storage, KEY_FEE_MODEL).save(fee_model)
+}
+
+/// Fee model of the deployment, the treasury tax unless another was stored
+pub fn read_fee_model(storage: &dyn Storage) -> StdResult<FeeModel> {
+    Ok(singleton_read(storage, KEY_FEE_MODEL)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Proportional rate and absolute cap of the fee charged on a transfer of
+/// the denom. A fixed fee is no rate, capped at its amount
 pub fn query_tax_rate_and_cap(deps: Deps, denom: String) -> StdResult<(Decimal256, Uint256)> {
-    let terra_querier = TerraQuerier::new(&deps.querier);
-    let rate = terra_querier.query_tax_rate()?.rate;
-    let cap = terra_querier.query_tax_cap(denom)?.cap;
-    Ok((rate.into(), cap.into()))
+    match read_fee_model(deps.storage)? {
+        FeeModel::NoTax {} => Ok((Decimal256::zero(), Uint256::zero())),
+        FeeModel::FixedFee { amount } => Ok((Decimal256::zero(), amount)),
+        FeeModel::TreasuryTax {} => {
+            let terra_querier = TerraQuerier::new(&deps.querier);
+            let rate = terra_querier.query_tax_rate()?.rate;
+            let cap = terra_querier.query_tax_cap(denom)?.cap;
+            Ok((rate.into(), cap.into()))
+        }
+    }
 }
 
+/// Proportional rate of the fee charged on transfers, zero unless taxed
+/// by the treasury
 pub fn query_tax_rate(deps: Deps) -> StdResult<Decimal256> {
-    let terra_querier = TerraQuerier::new(&deps.querier);
-    Ok(terra_querier.query_tax_rate()?.rate.into())
+    match read_fee_model(deps.storage)? {
+        FeeModel::NoTax {} | FeeModel::FixedFee { .. } => Ok(Decimal256::zero()),
+        FeeModel::TreasuryTax {} => {
+            let terra_querier = TerraQuerier::new(&deps.querier);
+            Ok(terra_querier.query_tax_rate()?.rate.into())
+        }
+    }
 }
 
+/// Fee charged on a transfer of the coin, out of the coin
 pub fn compute_tax(deps: Deps, coin: &Coin) -> StdResult<Uint256> {
-    let terra_querier = TerraQuerier::new(&deps.querier);
-    let tax_rate = Decimal256::from((terra_querier.query_tax_rate()?).rate);
-    let tax_cap = Uint256::from((terra_querier.query_tax_cap(coin.denom.to_string())?).cap);
     let amount = Uint256::from(coin.amount);
-    Ok(std::cmp::min(
-        amount * Decimal256::one() - amount / (Decimal256::one() + tax_rate),
-        tax_cap,
-    ))
+    match read_fee_model(deps.storage)? {
+        FeeModel::NoTax {} => Ok(Uint256::zero()),
+        FeeModel::FixedFee { amount: fee } => Ok(std::cmp::min(amount, fee)),
+        FeeModel::TreasuryTax {} => {
+            let terra_querier = TerraQuerier::new(&deps.querier);
+            let tax_rate = Decimal256::from((terra_querier.query_tax_rate()?).rate);
+            let tax_cap = Uint256::from((terra_querier.query_tax_cap(coin.denom.to_string())?).cap);
+            Ok(std::cmp::min(
+                amount * Decimal256::one() - amount / (Decimal256::one() + tax_rate),
+                tax_cap,
+            ))
+        }
+    }
 }
 
 pub fn deduct_tax(deps: Deps, coin: Coin) -> StdResult<Coin> {
@@ -103,12 +169,21 @@ pub fn query_price(
     base: String,
     quote: String,
     time_contraints: Option<TimeConstraints>,
+) -> StdResult<PriceResponse> {
+    query_price_of_kind(deps, oracle_addr, base, quote, None, time_contraints)
+}
+
+/// Same as `query_price`, with `rate` being the price of the given kind
+pub fn query_price_of_kind(
+    deps: Deps,
+    oracle_addr: Addr,
+    base: String,
+    quote: String,
+    price_kind: Option<PriceKind>,
+    time_contraints: Option<TimeConstraints>,
 ) -> StdResult<PriceResponse> {
     let oracle_price: PriceResponse =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: oracle_addr.to_string(),
-            msg: to_binary(&OracleQueryMsg::Price { base, quote })?,
-        }))?;
+        OracleQuerier::new(deps.querier, oracle_addr).price(base, quote, price_kind)?;
 
     if let Some(time_contraints) = time_contraints {
         let valid_update_time = time_contraints.block_time - time_contraints.valid_timeframe;
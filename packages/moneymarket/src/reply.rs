@@ -0,0 +1,20 @@
+use cosmwasm_std::{StdError, StdResult};
+use protobuf::Message;
+
+use crate::response::MsgInstantiateContractResponse;
+
+/// Address of the contract instantiated by a submessage, read from the
+/// protobuf encoded MsgInstantiateContractResponse data of its reply
+pub fn parse_instantiate_contract_address(data: &[u8]) -> StdResult<String> {
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(data).map_err(|_| parse_error())?;
+
+    match res.get_contract_address() {
+        "" => Err(parse_error()),
+        contract_address => Ok(contract_address.to_string()),
+    }
+}
+
+fn parse_error() -> StdError {
+    StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+}
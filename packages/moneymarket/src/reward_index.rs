@@ -0,0 +1,60 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{StdResult, Storage};
+use cosmwasm_storage::{Bucket, ReadonlyBucket};
+
+// Rewards accrue to a global index, the rewards per share, and a holder
+// earns its shares times the growth of the index since its own snapshot.
+// The holder must be settled before its shares change
+
+/// Global index after `reward` is distributed over `total_shares`.
+/// A reward without shares is not distributed
+pub fn accrue_index(
+    global_index: Decimal256,
+    reward: Decimal256,
+    total_shares: Decimal256,
+) -> Decimal256 {
+    if reward.is_zero() || total_shares.is_zero() {
+        return global_index;
+    }
+
+    global_index + reward / total_shares
+}
+
+/// Reward of `shares` since the holder index was snapshot
+pub fn pending_reward(
+    global_index: Decimal256,
+    holder_index: Decimal256,
+    shares: Decimal256,
+) -> Decimal256 {
+    if global_index <= holder_index {
+        return Decimal256::zero();
+    }
+
+    shares * (global_index - holder_index)
+}
+
+/// Stores an index under the nested `namespaces`, as a bucket does
+pub fn store_index(
+    storage: &mut dyn Storage,
+    namespaces: &[&[u8]],
+    key: &[u8],
+    index: Decimal256,
+) -> StdResult<()> {
+    let mut index_bucket: Bucket<Decimal256> = Bucket::multilevel(storage, namespaces);
+    index_bucket.save(key, &index)
+}
+
+/// Index under the nested `namespaces`, zero until stored
+pub fn read_index(
+    storage: &dyn Storage,
+    namespaces: &[&[u8]],
+    key: &[u8],
+) -> StdResult<Decimal256> {
+    let index_bucket: ReadonlyBucket<Decimal256> = ReadonlyBucket::multilevel(storage, namespaces);
+    Ok(index_bucket.may_load(key)?.unwrap_or_default())
+}
+
+pub fn remove_index(storage: &mut dyn Storage, namespaces: &[&[u8]], key: &[u8]) {
+    let mut index_bucket: Bucket<Decimal256> = Bucket::multilevel(storage, namespaces);
+    index_bucket.remove(key)
+}
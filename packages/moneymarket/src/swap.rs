@@ -0,0 +1,185 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Api, Binary, Coin, CosmosMsg, StdError, StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use terra_cosmwasm::{create_swap_msg, TerraMsgWrapper};
+use terraswap::asset::AssetInfo;
+use terraswap::router::{
+    Cw20HookMsg as TerraswapCw20HookMsg, SwapOperation as TerraswapSwapOperation,
+};
+
+/// Where a contract swaps its assets
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapRoute {
+    /// Swap of the market module, native coins only
+    NativeSwap {},
+    /// Router of a Terraswap deployment
+    Terraswap { router: String },
+    /// Router of an Astroport deployment
+    Astroport { router: String },
+    /// Router of the generic interface, executing `GenericRouterMsg`
+    Generic { router: String },
+}
+
+impl Default for SwapRoute {
+    fn default() -> Self {
+        SwapRoute::NativeSwap {}
+    }
+}
+
+/// Execute and cw20 hook message of the Astroport router
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AstroportRouterMsg {
+    ExecuteSwapOperations {
+        operations: Vec<AstroportSwapOperation>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AstroportSwapOperation {
+    AstroSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+/// Execute and cw20 hook message of a generic router, swapping the sent
+/// assets for the ask asset and sending back at least the minimum receive
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GenericRouterMsg {
+    Swap {
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+    },
+}
+
+impl SwapRoute {
+    /// The route with its router address validated
+    pub fn validate(self, api: &dyn Api) -> StdResult<SwapRoute> {
+        match &self {
+            SwapRoute::NativeSwap {} => {}
+            SwapRoute::Terraswap { router }
+            | SwapRoute::Astroport { router }
+            | SwapRoute::Generic { router } => {
+                api.addr_validate(router)?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    pub fn router(&self) -> Option<&str> {
+        match self {
+            SwapRoute::NativeSwap {} => None,
+            SwapRoute::Terraswap { router }
+            | SwapRoute::Astroport { router }
+            | SwapRoute::Generic { router } => Some(router),
+        }
+    }
+
+    /// Swaps the native coin for the ask asset, sent back to the sender
+    pub fn swap_native_msg(
+        &self,
+        offer: Coin,
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<CosmosMsg<TerraMsgWrapper>> {
+        let router = match self.router() {
+            Some(router) => router.to_string(),
+            None => {
+                return match ask_asset_info {
+                    AssetInfo::NativeToken { denom } if minimum_receive.is_none() => {
+                        Ok(create_swap_msg(offer, denom))
+                    }
+                    _ => Err(market_swap_error()),
+                }
+            }
+        };
+
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: router,
+            msg: self.router_msg(
+                AssetInfo::NativeToken {
+                    denom: offer.denom.clone(),
+                },
+                ask_asset_info,
+                minimum_receive,
+            )?,
+            funds: vec![offer],
+        }))
+    }
+
+    /// Swaps the amount of the cw20 token for the ask asset, sent back to the sender
+    pub fn swap_cw20_msg(
+        &self,
+        token: String,
+        amount: Uint128,
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<CosmosMsg<TerraMsgWrapper>> {
+        let router = self.router().ok_or_else(market_swap_error)?.to_string();
+
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token.clone(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: router,
+                amount,
+                msg: self.router_msg(
+                    AssetInfo::Token {
+                        contract_addr: token,
+                    },
+                    ask_asset_info,
+                    minimum_receive,
+                )?,
+            })?,
+        }))
+    }
+
+    // the routers take the same message executed or as cw20 hook
+    fn router_msg(
+        &self,
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<Binary> {
+        match self {
+            SwapRoute::NativeSwap {} => Err(market_swap_error()),
+            SwapRoute::Terraswap { .. } => {
+                to_binary(&TerraswapCw20HookMsg::ExecuteSwapOperations {
+                    operations: vec![TerraswapSwapOperation::TerraSwap {
+                        offer_asset_info,
+                        ask_asset_info,
+                    }],
+                    minimum_receive,
+                    to: None,
+                })
+            }
+            SwapRoute::Astroport { .. } => to_binary(&AstroportRouterMsg::ExecuteSwapOperations {
+                operations: vec![AstroportSwapOperation::AstroSwap {
+                    offer_asset_info,
+                    ask_asset_info,
+                }],
+                minimum_receive,
+                to: None,
+            }),
+            SwapRoute::Generic { .. } => to_binary(&GenericRouterMsg::Swap {
+                ask_asset_info,
+                minimum_receive,
+            }),
+        }
+    }
+}
+
+fn market_swap_error() -> StdError {
+    StdError::generic_err("The market module only swaps native coins, without minimum receive")
+}
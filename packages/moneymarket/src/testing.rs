@@ -1,10 +1,32 @@
+use crate::math::{
+    checked_add, checked_div, checked_from_uint256, checked_mul, checked_sub, complement, div_dec,
+    div_rounded, mul_dec, mul_ratio, saturating_sub, Rounding,
+};
 use crate::mock_querier::mock_dependencies;
-use crate::oracle::PriceResponse;
-use crate::querier::{compute_tax, deduct_tax, query_price, query_tax_rate, TimeConstraints};
+use crate::oracle::{OracleQuerier, PriceResponse};
+use crate::pagination::{calc_limit, calc_limit_with_max, calc_range_start, MAX_LIMIT};
+use crate::querier::{
+    compute_tax, deduct_tax, query_price, query_tax_rate, query_tax_rate_and_cap, read_fee_model,
+    store_fee_model, FeeModel, TimeConstraints,
+};
+use crate::reply::parse_instantiate_contract_address;
+use crate::reward_index::{accrue_index, pending_reward, read_index, remove_index, store_index};
+use crate::swap::{GenericRouterMsg, SwapRoute};
 use crate::tokens::{Tokens, TokensHuman, TokensMath, TokensToRaw};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Addr, Api, CanonicalAddr, Coin, Decimal, StdError, Uint128};
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{
+    to_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, Order, StdError, Uint128,
+    WasmMsg,
+};
+use cosmwasm_storage::{Bucket, ReadonlyBucket};
+use cw20::Cw20ExecuteMsg;
+use terra_cosmwasm::create_swap_msg;
+use terraswap::asset::AssetInfo;
+use terraswap::router::{
+    Cw20HookMsg as TerraswapCw20HookMsg, SwapOperation as TerraswapSwapOperation,
+};
 
 #[test]
 fn tax_rate_querier() {
@@ -67,6 +89,58 @@ fn test_deduct_tax() {
     );
 }
 
+#[test]
+fn fee_models() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    // deployments default to the treasury tax
+    assert_eq!(
+        read_fee_model(deps.as_ref().storage).unwrap(),
+        FeeModel::TreasuryTax {}
+    );
+    assert_eq!(
+        compute_tax(deps.as_ref(), &Coin::new(50000000u128, "uusd")).unwrap(),
+        Uint256::from(495050u64)
+    );
+
+    store_fee_model(deps.as_mut().storage, &FeeModel::NoTax {}).unwrap();
+    assert_eq!(
+        deduct_tax(deps.as_ref(), Coin::new(50000000u128, "uusd")).unwrap(),
+        Coin::new(50000000u128, "uusd")
+    );
+    assert_eq!(query_tax_rate(deps.as_ref()).unwrap(), Decimal256::zero());
+    assert_eq!(
+        query_tax_rate_and_cap(deps.as_ref(), "uusd".to_string()).unwrap(),
+        (Decimal256::zero(), Uint256::zero())
+    );
+
+    // a fixed fee never takes more than the transfer
+    store_fee_model(
+        deps.as_mut().storage,
+        &FeeModel::FixedFee {
+            amount: Uint256::from(1000u64),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        deduct_tax(deps.as_ref(), Coin::new(50000000u128, "uusd")).unwrap(),
+        Coin::new(49999000u128, "uusd")
+    );
+    assert_eq!(
+        compute_tax(deps.as_ref(), &Coin::new(500u128, "uusd")).unwrap(),
+        Uint256::from(500u64)
+    );
+    assert_eq!(query_tax_rate(deps.as_ref()).unwrap(), Decimal256::zero());
+    assert_eq!(
+        query_tax_rate_and_cap(deps.as_ref(), "uusd".to_string()).unwrap(),
+        (Decimal256::zero(), Uint256::from(1000u64))
+    );
+}
+
 #[test]
 fn oracle_price_querier() {
     let mut deps = mock_dependencies(&[]);
@@ -91,8 +165,16 @@ fn oracle_price_querier() {
             rate: Decimal256::from_ratio(131, 2),
             last_updated_base: 123,
             last_updated_quote: 321,
+            twap_rate: None,
+            confidence: None,
         }
     );
+    assert_eq!(
+        OracleQuerier::new(deps.as_ref().querier, Addr::unchecked("oracle"))
+            .price("terra123123".to_string(), "uusd".to_string(), None)
+            .unwrap(),
+        oracle_price
+    );
 
     query_price(
         deps.as_ref(),
@@ -300,3 +382,426 @@ fn token_math_invalid_token_2() {
 
     let _ = tokens_1_raw.sub(tokens_2_raw);
 }
+
+#[test]
+fn pagination() {
+    assert_eq!(calc_limit(None), 10);
+    assert_eq!(calc_limit(Some(5)), 5);
+    assert_eq!(calc_limit(Some(100)), MAX_LIMIT as usize);
+    assert_eq!(calc_limit_with_max(Some(100), 31), 31);
+    assert_eq!(calc_range_start::<u64>(None), None);
+
+    // pages resume right after the last key, whatever its width
+    let mut storage = MockStorage::new();
+    let mut bucket: Bucket<u64> = Bucket::new(&mut storage, b"ids");
+    for id in [1u64, 2u64, 256u64, u64::MAX].iter() {
+        bucket.save(&id.to_be_bytes(), id).unwrap();
+    }
+    let bucket: ReadonlyBucket<u64> = ReadonlyBucket::new(&storage, b"ids");
+    let page = |start_after: Option<u64>| -> Vec<u64> {
+        let start = calc_range_start(start_after);
+        bucket
+            .range(start.as_deref(), None, Order::Ascending)
+            .take(calc_limit(Some(2)))
+            .map(|item| item.unwrap().1)
+            .collect()
+    };
+    assert_eq!(page(None), vec![1u64, 2u64]);
+    assert_eq!(page(Some(2u64)), vec![256u64, u64::MAX]);
+    assert_eq!(page(Some(u64::MAX)), Vec::<u64>::new());
+
+    let mut storage = MockStorage::new();
+    let mut bucket: Bucket<String> = Bucket::new(&mut storage, b"names");
+    for name in ["addr", "addr0", "addr1"].iter() {
+        bucket.save(name.as_bytes(), &name.to_string()).unwrap();
+    }
+    let bucket: ReadonlyBucket<String> = ReadonlyBucket::new(&storage, b"names");
+    let start = calc_range_start(Some("addr".to_string()));
+    let names: Vec<String> = bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .map(|item| item.unwrap().1)
+        .collect();
+    assert_eq!(names, vec!["addr0".to_string(), "addr1".to_string()]);
+}
+
+#[test]
+fn math() {
+    let amount = Uint256::from(10u64);
+    assert_eq!(
+        mul_ratio(
+            amount,
+            Uint256::from(2u64),
+            Uint256::from(3u64),
+            Rounding::Down
+        )
+        .unwrap(),
+        Uint256::from(6u64)
+    );
+    assert_eq!(
+        mul_ratio(
+            amount,
+            Uint256::from(2u64),
+            Uint256::from(3u64),
+            Rounding::Up
+        )
+        .unwrap(),
+        Uint256::from(7u64)
+    );
+    // exact results are not rounded up
+    assert_eq!(
+        mul_ratio(
+            amount,
+            Uint256::from(3u64),
+            Uint256::from(5u64),
+            Rounding::Up
+        )
+        .unwrap(),
+        Uint256::from(6u64)
+    );
+    assert!(matches!(
+        mul_ratio(amount, Uint256::one(), Uint256::zero(), Rounding::Down),
+        Err(StdError::DivideByZero { .. })
+    ));
+    // the product only has to fit in 256 bits
+    assert_eq!(
+        mul_ratio(
+            Uint256::from(u128::MAX),
+            Uint256::from(u128::MAX),
+            Uint256::from(u128::MAX),
+            Rounding::Down
+        )
+        .unwrap(),
+        Uint256::from(u128::MAX)
+    );
+    let large = Uint256::from(u128::MAX) * Uint256::from(u128::MAX);
+    assert!(matches!(
+        mul_ratio(large, large, Uint256::one(), Rounding::Down),
+        Err(StdError::Overflow { .. })
+    ));
+
+    let rate = Decimal256::percent(30);
+    assert_eq!(
+        mul_dec(Uint256::from(5u64), rate, Rounding::Down).unwrap(),
+        Uint256::one()
+    );
+    assert_eq!(
+        mul_dec(Uint256::from(5u64), rate, Rounding::Up).unwrap(),
+        Uint256::from(2u64)
+    );
+    assert_eq!(
+        div_dec(Uint256::from(10u64), rate, Rounding::Down).unwrap(),
+        Uint256::from(33u64)
+    );
+    assert_eq!(
+        div_dec(Uint256::from(10u64), rate, Rounding::Up).unwrap(),
+        Uint256::from(34u64)
+    );
+    assert!(matches!(
+        div_dec(amount, Decimal256::zero(), Rounding::Down),
+        Err(StdError::DivideByZero { .. })
+    ));
+
+    let third = checked_div(Decimal256::one(), Decimal256::from_uint256(3u64)).unwrap();
+    assert_eq!(third, Decimal256::from_ratio(1, 3));
+    assert!(
+        div_rounded(
+            Decimal256::one(),
+            Decimal256::from_uint256(3u64),
+            Rounding::Up
+        )
+        .unwrap()
+            > third
+    );
+    assert!(matches!(
+        checked_div(Decimal256::one(), Decimal256::zero()),
+        Err(StdError::DivideByZero { .. })
+    ));
+
+    assert_eq!(complement(rate).unwrap(), Decimal256::percent(70));
+    assert!(matches!(
+        complement(Decimal256::percent(101)),
+        Err(StdError::Overflow { .. })
+    ));
+    assert_eq!(
+        saturating_sub(Decimal256::percent(1), rate),
+        Decimal256::zero()
+    );
+    assert!(matches!(
+        checked_sub(Decimal256::percent(1), rate),
+        Err(StdError::Overflow { .. })
+    ));
+    assert!(matches!(
+        checked_add(Decimal256::MAX, rate),
+        Err(StdError::Overflow { .. })
+    ));
+    assert!(matches!(
+        checked_mul(Decimal256::MAX, Decimal256::percent(200)),
+        Err(StdError::Overflow { .. })
+    ));
+    assert_eq!(
+        checked_mul(Decimal256::percent(200), Decimal256::percent(50)).unwrap(),
+        Decimal256::one()
+    );
+    assert!(matches!(
+        checked_from_uint256(Uint256(Decimal256::MAX.0)),
+        Err(StdError::Overflow { .. })
+    ));
+}
+
+#[test]
+fn instantiate_reply() {
+    // contract_address = 1, data = 2
+    let mut data = vec![0x0a, 0x07];
+    data.extend_from_slice(b"AT-uusd");
+    data.extend_from_slice(&[0x12, 0x02, 0xab, 0xcd]);
+    assert_eq!(
+        parse_instantiate_contract_address(&data).unwrap(),
+        "AT-uusd"
+    );
+
+    // the fields can come in any order, next to unknown ones
+    let contract_address = "terra".repeat(30);
+    let mut data = vec![0x12, 0x00, 0x18, 0xac, 0x02, 0x0a, 0x96, 0x01];
+    data.extend_from_slice(contract_address.as_bytes());
+    assert_eq!(
+        parse_instantiate_contract_address(&data).unwrap(),
+        contract_address
+    );
+
+    let invalid_data: Vec<Vec<u8>> = vec![
+        vec![],
+        vec![0x12, 0x02, 0xab, 0xcd],
+        vec![0x0a, 0x07, 0x41, 0x54],
+        vec![0x0a, 0x02, 0xff, 0xfe],
+        vec![0x0b],
+    ];
+    for data in invalid_data {
+        match parse_instantiate_contract_address(&data) {
+            Err(StdError::ParseErr { target_type, .. }) => {
+                assert_eq!(target_type, "MsgInstantiateContractResponse")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+#[test]
+fn swap_routes() {
+    let deps = mock_dependencies(&[]);
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let uluna = Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::from(100u128),
+    };
+
+    // the market module only swaps to native coins, without minimum receive
+    let route = SwapRoute::default();
+    assert_eq!(route.clone().validate(deps.as_ref().api).unwrap(), route);
+    assert_eq!(route.router(), None);
+    assert_eq!(
+        route
+            .swap_native_msg(uluna.clone(), uusd.clone(), None)
+            .unwrap(),
+        create_swap_msg(uluna.clone(), "uusd".to_string())
+    );
+    assert!(route
+        .swap_native_msg(uluna.clone(), uusd.clone(), Some(Uint128::from(90u128)))
+        .is_err());
+    assert!(route
+        .swap_cw20_msg(
+            "token".to_string(),
+            Uint128::from(100u128),
+            uusd.clone(),
+            None
+        )
+        .is_err());
+
+    let route = SwapRoute::Terraswap {
+        router: "router".to_string(),
+    };
+    assert_eq!(route.router(), Some("router"));
+    assert_eq!(
+        route
+            .swap_cw20_msg(
+                "token".to_string(),
+                Uint128::from(100u128),
+                uusd.clone(),
+                Some(Uint128::from(90u128)),
+            )
+            .unwrap(),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "router".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&TerraswapCw20HookMsg::ExecuteSwapOperations {
+                    operations: vec![TerraswapSwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::Token {
+                            contract_addr: "token".to_string(),
+                        },
+                        ask_asset_info: uusd.clone(),
+                    }],
+                    minimum_receive: Some(Uint128::from(90u128)),
+                    to: None,
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+        })
+    );
+
+    let route = SwapRoute::Generic {
+        router: "router".to_string(),
+    };
+    assert_eq!(
+        route
+            .swap_native_msg(uluna.clone(), uusd.clone(), Some(Uint128::from(90u128)))
+            .unwrap(),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "router".to_string(),
+            msg: to_binary(&GenericRouterMsg::Swap {
+                ask_asset_info: uusd,
+                minimum_receive: Some(Uint128::from(90u128)),
+            })
+            .unwrap(),
+            funds: vec![uluna],
+        })
+    );
+}
+
+#[test]
+fn reward_index() {
+    // no reward without shares, nor before the index grows
+    assert_eq!(
+        accrue_index(
+            Decimal256::one(),
+            Decimal256::percent(50),
+            Decimal256::zero()
+        ),
+        Decimal256::one()
+    );
+    assert_eq!(
+        pending_reward(
+            Decimal256::one(),
+            Decimal256::percent(150),
+            Decimal256::from_uint256(10u64)
+        ),
+        Decimal256::zero()
+    );
+
+    let global_index = accrue_index(
+        Decimal256::one(),
+        Decimal256::from_uint256(50u64),
+        Decimal256::from_uint256(100u64),
+    );
+    assert_eq!(global_index, Decimal256::percent(150));
+    assert_eq!(
+        pending_reward(
+            global_index,
+            Decimal256::one(),
+            Decimal256::from_uint256(10u64)
+        ),
+        Decimal256::from_uint256(5u64)
+    );
+
+    // an index is stored as in a bucket of the same namespaces
+    let mut storage = MockStorage::new();
+    assert_eq!(
+        read_index(&storage, &[b"index", b"pool"], b"key").unwrap(),
+        Decimal256::zero()
+    );
+    store_index(&mut storage, &[b"index", b"pool"], b"key", global_index).unwrap();
+    let index_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::multilevel(&storage, &[b"index", b"pool"]);
+    assert_eq!(index_bucket.load(b"key").unwrap(), global_index);
+
+    let mut index_bucket: Bucket<Decimal256> = Bucket::new(&mut storage, b"index");
+    index_bucket.save(b"key", &Decimal256::one()).unwrap();
+    assert_eq!(
+        read_index(&storage, &[b"index"], b"key").unwrap(),
+        Decimal256::one()
+    );
+    remove_index(&mut storage, &[b"index"], b"key");
+    assert_eq!(
+        read_index(&storage, &[b"index"], b"key").unwrap(),
+        Decimal256::zero()
+    );
+}
+
+// xorshift, seeded for reproducible runs
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn next_amount(&mut self) -> u64 {
+        self.next_u64() % 1_000_000_000_000
+    }
+}
+
+#[test]
+fn reward_index_properties() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..200 {
+        // holders with random shares, settled at random accruals
+        let shares: Vec<u64> = (0..5).map(|_| rng.next_amount() + 1).collect();
+        let total_shares = Decimal256::from_uint256(shares.iter().sum::<u64>());
+        let mut holder_indexes = vec![Decimal256::zero(); shares.len()];
+        let mut holder_rewards = vec![Decimal256::zero(); shares.len()];
+
+        let mut global_index = Decimal256::zero();
+        let mut total_reward = Decimal256::zero();
+        for _ in 0..20 {
+            let reward = Decimal256::from_uint256(rng.next_amount());
+            let accrued_index = accrue_index(global_index, reward, total_shares);
+            // the index never decreases
+            assert!(accrued_index >= global_index);
+            global_index = accrued_index;
+            total_reward += reward;
+
+            let holder = (rng.next_u64() % shares.len() as u64) as usize;
+            holder_rewards[holder] += pending_reward(
+                global_index,
+                holder_indexes[holder],
+                Decimal256::from_uint256(shares[holder]),
+            );
+            holder_indexes[holder] = global_index;
+        }
+
+        // settling twice earns nothing more
+        for (holder, share) in shares.iter().enumerate() {
+            let reward = pending_reward(
+                global_index,
+                holder_indexes[holder],
+                Decimal256::from_uint256(*share),
+            );
+            holder_rewards[holder] += reward;
+            holder_indexes[holder] = global_index;
+            assert_eq!(
+                pending_reward(
+                    global_index,
+                    holder_indexes[holder],
+                    Decimal256::from_uint256(*share)
+                ),
+                Decimal256::zero()
+            );
+        }
+
+        // holders never earn more than the rewards, and lose at most
+        // the rounding of each accrual and settlement
+        let distributed = holder_rewards
+            .iter()
+            .fold(Decimal256::zero(), |total, reward| total + *reward);
+        assert!(distributed <= total_reward);
+        assert!(total_reward - distributed < Decimal256::one());
+    }
+}